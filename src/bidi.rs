@@ -0,0 +1,229 @@
+// Poterm - Modern TUI editor for .po translation files
+// Copyright (c) 2025 AnmiTaliDev <anmitali198@gmail.com>
+// Licensed under the Apache License, Version 2.0
+
+//! Display order of mixed right-to-left and left-to-right text
+//!
+//! The Unicode bidirectional algorithm comes from `unicode-bidi`, including
+//! explicit embeddings and isolates typed into the text. Placeholders such
+//! as `%s` and `{name}` and markup tags are wrapped in left-to-right
+//! isolates first, so they stay readable as single words.
+
+use unicode_bidi::{BidiClass, BidiInfo, Level};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Ltr,
+    Rtl,
+}
+
+const LEFT_TO_RIGHT_ISOLATE: char = '\u{2066}';
+const POP_DIRECTIONAL_ISOLATE: char = '\u{2069}';
+
+/// Strong right-to-left: Hebrew, Arabic, Syriac, Thaana, N'Ko and others
+pub fn is_rtl_char(c: char) -> bool {
+    matches!(unicode_bidi::bidi_class(c), BidiClass::R | BidiClass::AL)
+}
+
+pub fn has_rtl(text: &str) -> bool {
+    text.chars().any(is_rtl_char)
+}
+
+/// Direction of the first strong character outside placeholders and
+/// isolates, or `fallback` without one
+pub fn base_direction(text: &str, fallback: Direction) -> Direction {
+    let (isolated, _) = isolate_placeholders(text);
+    match unicode_bidi::get_base_direction(isolated.as_str()) {
+        unicode_bidi::Direction::Ltr => Direction::Ltr,
+        unicode_bidi::Direction::Rtl => Direction::Rtl,
+        unicode_bidi::Direction::Mixed => fallback,
+    }
+}
+
+/// Length in chars of a placeholder or tag starting at `chars[0]`
+fn token_len(chars: &[char]) -> Option<usize> {
+    let closing = match chars.first()? {
+        '{' => '}',
+        '<' => '>',
+        '%' => {
+            // %s, %1$s, %-5.2f, %lld
+            let mut len = 1;
+            while chars.get(len).is_some_and(|c| c.is_ascii_digit() || "$-+ #0.*'".contains(*c)) {
+                len += 1;
+            }
+            while chars.get(len).is_some_and(|c| "hlLqjzt".contains(*c)) {
+                len += 1;
+            }
+            return chars.get(len).filter(|c| c.is_ascii_alphabetic()).map(|_| len + 1);
+        }
+        _ => return None,
+    };
+    let end = chars.iter().position(|&c| c == closing)?;
+    (end > 1 && !chars[1..end].iter().any(|c| c.is_whitespace() || *c == chars[0])).then_some(end + 1)
+}
+
+/// `text` with every placeholder and tag in a left-to-right isolate, and
+/// whether each char of the result is one of `text`'s rather than an
+/// added isolate mark
+fn isolate_placeholders(text: &str) -> (String, Vec<bool>) {
+    let chars: Vec<char> = text.chars().collect();
+    let mut isolated = String::with_capacity(text.len());
+    let mut original = Vec::with_capacity(chars.len());
+    let mut i = 0;
+    while i < chars.len() {
+        match token_len(&chars[i..]) {
+            Some(len) => {
+                isolated.push(LEFT_TO_RIGHT_ISOLATE);
+                isolated.extend(&chars[i..i + len]);
+                isolated.push(POP_DIRECTIONAL_ISOLATE);
+                original.push(false);
+                original.extend(std::iter::repeat_n(true, len));
+                original.push(false);
+                i += len;
+            }
+            None => {
+                isolated.push(chars[i]);
+                original.push(true);
+                i += 1;
+            }
+        }
+    }
+    (isolated, original)
+}
+
+/// Resolved embedding level of every char, for `text` shown as one line:
+/// even is left-to-right
+pub fn levels(text: &str, base: Direction) -> Vec<u8> {
+    let (isolated, original) = isolate_placeholders(text);
+    let base_level = if base == Direction::Rtl { Level::rtl() } else { Level::ltr() };
+    let info = BidiInfo::new(&isolated, Some(base_level));
+
+    // Levels by byte, with the line rules applied to each paragraph
+    let mut levels = vec![base_level; isolated.len()];
+    for paragraph in &info.paragraphs {
+        let range = paragraph.range.clone();
+        levels[range.clone()].copy_from_slice(&info.reordered_levels(paragraph, range.clone())[range]);
+    }
+    isolated
+        .char_indices()
+        .zip(original)
+        .filter(|&(_, original)| original)
+        .map(|((i, _), _)| levels[i].number())
+        .collect()
+}
+
+/// Char indices of `text` in left-to-right display order
+pub fn visual_order(levels: &[u8]) -> Vec<usize> {
+    BidiInfo::reorder_visual(&Level::vec(levels))
+}
+
+/// Bracket as drawn in right-to-left text
+pub fn mirror(c: char) -> char {
+    match c {
+        '(' => ')',
+        ')' => '(',
+        '[' => ']',
+        ']' => '[',
+        '{' => '}',
+        '}' => '{',
+        '<' => '>',
+        '>' => '<',
+        '«' => '»',
+        '»' => '«',
+        _ => c,
+    }
+}
+
+/// `text` in display order with brackets mirrored in right-to-left runs
+pub fn reorder(text: &str, base: Direction) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let levels = levels(text, base);
+    visual_order(&levels)
+        .into_iter()
+        .map(|i| if levels[i] % 2 == 1 { mirror(chars[i]) } else { chars[i] })
+        .collect()
+}
+
+/// Display slot of the insertion point before char `cursor` of `text`: the
+/// number of chars drawn left of it. The end of the text is where the
+/// paragraph direction continues.
+pub fn visual_slot(text: &str, base: Direction, cursor: usize) -> usize {
+    let levels = levels(text, base);
+    if cursor >= levels.len() {
+        return if base == Direction::Rtl { 0 } else { levels.len() };
+    }
+    let slot = visual_order(&levels).iter().position(|&i| i == cursor).unwrap_or(0);
+    // The char after the point is drawn left of it in right-to-left runs
+    if levels[cursor] % 2 == 1 {
+        slot + 1
+    } else {
+        slot
+    }
+}
+
+/// Logical cursor one display slot to the left or right of `cursor`
+pub fn visual_step(text: &str, base: Direction, cursor: usize, left: bool) -> usize {
+    let len = text.chars().count();
+    let slot = visual_slot(text, base, cursor);
+    let target = if left { slot.checked_sub(1) } else { Some(slot + 1).filter(|&slot| slot <= len) };
+    let Some(target) = target else {
+        return cursor;
+    };
+    // Pick the logical position whose slot is the target
+    (0..=len).find(|&candidate| visual_slot(text, base, candidate) == target).unwrap_or(cursor)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reorder_mixed_text() {
+        // Hebrew letters are drawn right to left, English keeps its order
+        assert_eq!(reorder("שלום", Direction::Rtl), "םולש");
+        assert_eq!(reorder("abc שלום def", Direction::Ltr), "abc םולש def");
+        assert_eq!(reorder("שלום abc", Direction::Rtl), "abc םולש");
+        // Numbers in right-to-left text keep their digit order
+        assert_eq!(reorder("שלום 123", Direction::Rtl), "123 םולש");
+        // Brackets are mirrored in right-to-left runs
+        assert_eq!(reorder("(שלום)", Direction::Rtl), "(םולש)");
+        assert_eq!(reorder("", Direction::Rtl), "");
+    }
+
+    #[test]
+    fn test_placeholders_stay_intact() {
+        assert_eq!(reorder("فتح %s الآن", Direction::Rtl), "نآلا %s حتف");
+        assert_eq!(reorder("فتح %1$s", Direction::Rtl), "%1$s حتف");
+        assert_eq!(reorder("ملف {name}", Direction::Rtl), "{name} فلم");
+        assert_eq!(reorder("<b>نص</b>", Direction::Rtl), "</b>صن<b>");
+        assert_eq!(base_direction("%s فتح", Direction::Ltr), Direction::Rtl);
+        assert_eq!(base_direction("123", Direction::Rtl), Direction::Rtl);
+    }
+
+    #[test]
+    fn test_explicit_isolates() {
+        // A right-to-left isolate keeps its English word out of the
+        // surrounding left-to-right text's order
+        assert_eq!(reorder("a \u{2067}שלום abc\u{2069} b", Direction::Ltr), "a \u{2067}abc םולש\u{2069} b");
+        // Text in an isolate doesn't decide the direction
+        assert_eq!(base_direction("\u{2067}שלום\u{2069} hello", Direction::Rtl), Direction::Ltr);
+        assert_eq!(levels("\u{202b}ab\u{202c}", Direction::Ltr)[1..3], [2, 2]);
+    }
+
+    #[test]
+    fn test_cursor_slots() {
+        // "אב": א is drawn rightmost. Before א the point is at the right
+        // edge, after ב at the left edge.
+        assert_eq!(visual_slot("אב", Direction::Rtl, 0), 2);
+        assert_eq!(visual_slot("אב", Direction::Rtl, 1), 1);
+        assert_eq!(visual_slot("אב", Direction::Rtl, 2), 0);
+        assert_eq!(visual_slot("ab", Direction::Ltr, 1), 1);
+        assert_eq!(visual_slot("ab", Direction::Ltr, 2), 2);
+
+        // Visual Left in right-to-left text moves forward logically
+        assert_eq!(visual_step("אב", Direction::Rtl, 0, true), 1);
+        assert_eq!(visual_step("אב", Direction::Rtl, 1, true), 2);
+        assert_eq!(visual_step("אב", Direction::Rtl, 2, true), 2);
+        assert_eq!(visual_step("אב", Direction::Rtl, 2, false), 1);
+    }
+}
@@ -0,0 +1,248 @@
+// Poterm - Modern TUI editor for .po translation files
+// Copyright (c) 2025 AnmiTaliDev <anmitali198@gmail.com>
+// Licensed under the Apache License, Version 2.0
+
+//! Minimal directory browser for opening catalogs
+
+use crate::input::TextInput;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Row of the browser listing
+#[derive(Debug, Clone, PartialEq)]
+pub struct BrowserEntry {
+    pub name: String,
+    pub is_dir: bool,
+}
+
+impl BrowserEntry {
+    fn parent() -> Self {
+        Self {
+            name: "..".to_string(),
+            is_dir: true,
+        }
+    }
+}
+
+/// What the browser wants the editor to do after a key press
+#[derive(Debug, Clone, PartialEq)]
+pub enum BrowserEvent {
+    None,
+    Open(PathBuf),
+    Close,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileBrowser {
+    pub dir: PathBuf,
+    /// Directory contents, or the error that prevented listing it
+    pub listing: Result<Vec<BrowserEntry>, String>,
+    /// Type-to-filter line
+    pub filter: TextInput,
+    /// Index into `visible()`
+    pub selected: usize,
+    pub show_hidden: bool,
+}
+
+impl FileBrowser {
+    pub fn new(dir: PathBuf) -> Self {
+        let mut browser = Self {
+            dir,
+            listing: Ok(Vec::new()),
+            filter: TextInput::default(),
+            selected: 0,
+            show_hidden: false,
+        };
+        browser.refresh();
+        browser
+    }
+
+    fn refresh(&mut self) {
+        self.listing = list_directory(&self.dir, self.show_hidden);
+        self.selected = 0;
+    }
+
+    /// Rows matching the filter. `..` is always offered.
+    pub fn visible(&self) -> Vec<&BrowserEntry> {
+        let Ok(ref entries) = self.listing else {
+            return Vec::new();
+        };
+        let query = self.filter.text.to_lowercase();
+        entries
+            .iter()
+            .filter(|entry| entry.name == ".." || entry.name.to_lowercase().contains(&query))
+            .collect()
+    }
+
+    fn enter_directory(&mut self, dir: PathBuf) {
+        self.dir = dir;
+        self.filter = TextInput::default();
+        self.refresh();
+    }
+
+    fn go_up(&mut self) {
+        let parent = match self.dir.canonicalize() {
+            Ok(dir) => dir.parent().map(Path::to_path_buf),
+            Err(_) => self.dir.parent().map(Path::to_path_buf),
+        };
+        if let Some(parent) = parent {
+            self.enter_directory(parent);
+        }
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> BrowserEvent {
+        match key.code {
+            KeyCode::Esc => return BrowserEvent::Close,
+            KeyCode::Up => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down if self.selected + 1 < self.visible().len() => self.selected += 1,
+            KeyCode::Char('h') if key.modifiers.contains(KeyModifiers::ALT) => {
+                self.show_hidden = !self.show_hidden;
+                self.refresh();
+            }
+            KeyCode::Backspace if self.filter.text.is_empty() => self.go_up(),
+            KeyCode::Enter => {
+                let Some(entry) = self.visible().get(self.selected).map(|e| (*e).clone()) else {
+                    return BrowserEvent::None;
+                };
+                if entry.name == ".." {
+                    self.go_up();
+                } else if entry.is_dir {
+                    self.enter_directory(self.dir.join(&entry.name));
+                } else {
+                    return BrowserEvent::Open(self.dir.join(&entry.name));
+                }
+            }
+            _ => {
+                if self.filter.handle_key(key) {
+                    self.selected = 0;
+                }
+            }
+        }
+        BrowserEvent::None
+    }
+}
+
+/// Directories and `*.po`/`*.pot` files in `dir`, directories first
+fn list_directory(dir: &Path, show_hidden: bool) -> Result<Vec<BrowserEntry>, String> {
+    let read_dir = fs::read_dir(dir).map_err(|e| format!("Cannot list {}: {}", dir.display(), e))?;
+
+    let mut entries: Vec<BrowserEntry> = read_dir
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let name = entry.file_name().into_string().ok()?;
+            if name.starts_with('.') && !show_hidden {
+                return None;
+            }
+            let is_dir = entry.path().is_dir();
+            let is_catalog = name.ends_with(".po") || name.ends_with(".pot");
+            (is_dir || is_catalog).then_some(BrowserEntry { name, is_dir })
+        })
+        .collect();
+    entries.sort_by(|a, b| b.is_dir.cmp(&a.is_dir).then_with(|| a.name.cmp(&b.name)));
+    entries.insert(0, BrowserEntry::parent());
+    Ok(entries)
+}
+
+/// Tab completion of a typed path: the longest common start of the
+/// directories and catalogs beginning with its last component, with a `/`
+/// after a single directory. None when nothing matches.
+pub fn complete_path(text: &str) -> Option<String> {
+    let (dir, prefix) = match text.rfind('/') {
+        Some(slash) => text.split_at(slash + 1),
+        None => ("", text),
+    };
+    let listing = list_directory(Path::new(if dir.is_empty() { "." } else { dir }), prefix.starts_with('.')).ok()?;
+    let matches: Vec<&BrowserEntry> = listing
+        .iter()
+        .filter(|entry| entry.name != ".." && entry.name.starts_with(prefix))
+        .collect();
+    let first = matches.first()?;
+    let common = matches.iter().fold(first.name.as_str(), |common, entry| common_start(common, &entry.name));
+    let slash = if matches.len() == 1 && first.is_dir { "/" } else { "" };
+    Some(format!("{}{}{}", dir, common, slash))
+}
+
+fn common_start<'a>(a: &'a str, b: &str) -> &'a str {
+    let len = a
+        .char_indices()
+        .zip(b.chars())
+        .take_while(|((_, x), y)| x == y)
+        .last()
+        .map_or(0, |((i, c), _)| i + c.len_utf8());
+    &a[..len]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    fn names(browser: &FileBrowser) -> Vec<&str> {
+        browser.visible().iter().map(|e| e.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_browse_filter_and_open() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("po")).unwrap();
+        fs::create_dir(dir.path().join(".git")).unwrap();
+        fs::write(dir.path().join("po/fr.po"), "").unwrap();
+        fs::write(dir.path().join("po/ru.po"), "").unwrap();
+        fs::write(dir.path().join("po/.draft.po"), "").unwrap();
+        fs::write(dir.path().join("README.md"), "").unwrap();
+        fs::write(dir.path().join("app.pot"), "").unwrap();
+
+        let mut browser = FileBrowser::new(dir.path().to_path_buf());
+        assert_eq!(names(&browser), vec!["..", "po", "app.pot"]);
+
+        browser.handle_key(key(KeyCode::Down));
+        browser.handle_key(key(KeyCode::Enter));
+        assert_eq!(names(&browser), vec!["..", "fr.po", "ru.po"]);
+
+        browser.handle_key(KeyEvent::new(KeyCode::Char('h'), KeyModifiers::ALT));
+        assert_eq!(names(&browser), vec!["..", ".draft.po", "fr.po", "ru.po"]);
+
+        for c in "ru".chars() {
+            browser.handle_key(key(KeyCode::Char(c)));
+        }
+        assert_eq!(names(&browser), vec!["..", "ru.po"]);
+        browser.handle_key(key(KeyCode::Down));
+        assert_eq!(
+            browser.handle_key(key(KeyCode::Enter)),
+            BrowserEvent::Open(dir.path().join("po").join("ru.po"))
+        );
+
+        // Backspace on an empty filter goes up
+        browser.handle_key(key(KeyCode::Backspace));
+        browser.handle_key(key(KeyCode::Backspace));
+        browser.handle_key(key(KeyCode::Backspace));
+        assert_eq!(browser.dir, dir.path().canonicalize().unwrap());
+        assert_eq!(browser.handle_key(key(KeyCode::Esc)), BrowserEvent::Close);
+    }
+
+    #[test]
+    fn test_unreadable_directory_is_reported() {
+        let browser = FileBrowser::new(PathBuf::from("/nonexistent/poterm"));
+        assert!(browser.listing.as_ref().unwrap_err().starts_with("Cannot list /nonexistent/poterm"));
+        assert!(browser.visible().is_empty());
+    }
+
+    #[test]
+    fn test_complete_path() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("locale")).unwrap();
+        fs::create_dir(dir.path().join("po")).unwrap();
+        fs::write(dir.path().join("po/ru.po"), "").unwrap();
+        fs::write(dir.path().join("po/ru_RU.po"), "").unwrap();
+        let base = format!("{}/", dir.path().display());
+
+        assert_eq!(complete_path(&format!("{}l", base)), Some(format!("{}locale/", base)));
+        assert_eq!(complete_path(&format!("{}po/r", base)), Some(format!("{}po/ru", base)));
+        assert_eq!(complete_path(&format!("{}po/ru_", base)), Some(format!("{}po/ru_RU.po", base)));
+        assert_eq!(complete_path(&format!("{}x", base)), None);
+    }
+}
@@ -0,0 +1,137 @@
+// Poterm - Modern TUI editor for .po translation files
+// Copyright (c) 2025 AnmiTaliDev <anmitali198@gmail.com>
+// Licensed under the Apache License, Version 2.0
+
+//! Finding characters by Unicode name or codepoint to insert them
+
+use std::sync::OnceLock;
+
+/// The spaces, punctuation and symbols translations need most, offered
+/// before anything is typed; the rest are found by name or codepoint
+pub const COMMON: &[char] = &[
+    // Spaces and invisible characters
+    '\u{a0}', '\u{202f}', '\u{2007}', '\u{2008}', '\u{2009}', '\u{200a}', '\u{2002}', '\u{2003}',
+    '\u{2004}', '\u{2005}', '\u{2006}', '\u{205f}', '\u{3000}', '\u{200b}', '\u{200c}', '\u{200d}',
+    '\u{2060}', '\u{ad}', '\u{200e}', '\u{200f}', '\u{61c}', '\u{2066}', '\u{2067}', '\u{2068}',
+    '\u{2069}',
+    // Dashes and hyphens
+    '\u{2010}', '\u{2011}', '\u{2012}', '\u{2013}', '\u{2014}', '\u{2015}', '\u{2212}',
+    // Quotation marks
+    '\u{201c}', '\u{201d}', '\u{2018}', '\u{2019}', '\u{201e}', '\u{201a}', '\u{201f}', '\u{ab}',
+    '\u{bb}', '\u{2039}', '\u{203a}', '\u{300c}', '\u{300d}', '\u{300e}', '\u{300f}', '\u{2032}',
+    '\u{2033}',
+    // Punctuation
+    '\u{2026}', '\u{b7}', '\u{2022}', '\u{2023}', '\u{2027}', '\u{a1}', '\u{bf}', '\u{203d}',
+    '\u{a7}', '\u{b6}', '\u{2020}', '\u{2021}', '\u{2116}', '\u{3001}', '\u{3002}', '\u{ff0c}',
+    '\u{ff1a}', '\u{ff1f}', '\u{ff01}', '\u{60c}', '\u{61f}', '\u{5be}',
+    // Symbols
+    '\u{a9}', '\u{ae}', '\u{2122}', '\u{b0}', '\u{2103}', '\u{d7}', '\u{f7}', '\u{b1}', '\u{2248}',
+    '\u{2260}', '\u{2264}', '\u{2265}', '\u{221e}', '\u{2030}', '\u{b5}', '\u{2190}', '\u{2192}',
+    '\u{2191}', '\u{2193}', '\u{2194}', '\u{21b5}', '\u{2713}', '\u{2717}', '\u{2318}', '\u{2325}',
+    '\u{21e7}', '\u{20ac}', '\u{a3}', '\u{a5}', '\u{20bd}', '\u{20b4}', '\u{20b9}', '\u{20a9}',
+    '\u{20ba}', '\u{a2}', '\u{a4}',
+];
+
+/// Unicode name of `c`, None for unassigned codepoints and controls
+pub fn name(c: char) -> Option<String> {
+    unicode_names2::name(c).map(|name| name.to_string())
+}
+
+/// Up to `limit` characters whose name contains every word of `query`:
+/// an exact match, then the common characters, then names starting with
+/// the query, each in codepoint order
+pub fn search(query: &str, limit: usize) -> Vec<char> {
+    let words: Vec<String> = query.split_whitespace().map(str::to_uppercase).collect();
+    if words.is_empty() {
+        return Vec::new();
+    }
+    let query = words.join(" ");
+    let mut found: Vec<_> = named_characters()
+        .iter()
+        .filter(|(_, name)| words.iter().all(|word| name.contains(word.as_str())))
+        .collect();
+    // Sorting is stable, so ties stay in codepoint order
+    found.sort_by_key(|(c, name)| (*name != query, !COMMON.contains(c), !name.starts_with(&query)));
+    found.into_iter().take(limit).map(|&(c, _)| c).collect()
+}
+
+/// Every character that has a name, with it. Built on the first search.
+fn named_characters() -> &'static [(char, String)] {
+    static NAMED: OnceLock<Vec<(char, String)>> = OnceLock::new();
+    NAMED.get_or_init(|| ('\0'..=char::MAX).filter_map(|c| Some((c, name(c)?))).collect())
+}
+
+/// `U+XXXX` notation
+pub fn codepoint(c: char) -> String {
+    format!("U+{:04X}", c as u32)
+}
+
+/// Read `2011`, `U+2011` or `0x2011` as a character. None when the text is
+/// not a codepoint, so it can be searched by name instead; an error for
+/// codepoints that are not insertable characters.
+pub fn parse_codepoint(text: &str) -> Option<Result<char, String>> {
+    let text = text.trim();
+    let (digits, prefixed) = match text.get(..2) {
+        Some("U+" | "u+" | "0x" | "0X") => (&text[2..], true),
+        _ => (text, false),
+    };
+    if digits.is_empty() || digits.len() > 8 || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    // Short hex-looking words such as `add` are searched as names
+    if !prefixed && digits.len() < 4 {
+        return None;
+    }
+
+    let value = u32::from_str_radix(digits, 16).ok()?;
+    Some(match char::from_u32(value) {
+        Some(c) if c.is_control() && c != '\n' && c != '\t' => {
+            Err(format!("U+{:04X} is a control character", value))
+        }
+        Some(c) => Ok(c),
+        None if (0xd800..=0xdfff).contains(&value) => {
+            Err(format!("U+{:04X} is a UTF-16 surrogate, not a character", value))
+        }
+        None => Err(format!("U+{:04X} is beyond the last codepoint, U+10FFFF", value)),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_codepoint() {
+        assert_eq!(parse_codepoint("2011"), Some(Ok('\u{2011}')));
+        assert_eq!(parse_codepoint(" U+a0 "), Some(Ok('\u{a0}')));
+        assert_eq!(parse_codepoint("0x1F600"), Some(Ok('😀')));
+        assert_eq!(parse_codepoint("narrow no-break"), None);
+        assert_eq!(parse_codepoint("add"), None);
+        assert_eq!(parse_codepoint("U+"), None);
+
+        assert_eq!(parse_codepoint("D800"), Some(Err("U+D800 is a UTF-16 surrogate, not a character".to_string())));
+        assert_eq!(parse_codepoint("110000"), Some(Err("U+110000 is beyond the last codepoint, U+10FFFF".to_string())));
+        assert!(parse_codepoint("u+7").unwrap().is_err());
+    }
+
+    #[test]
+    fn test_common() {
+        assert_eq!(codepoint('\u{a0}'), "U+00A0");
+        for (i, c) in COMMON.iter().enumerate() {
+            assert!(!COMMON[..i].contains(c), "{} is listed twice", codepoint(*c));
+            assert!(name(*c).is_some(), "{} has no name", codepoint(*c));
+        }
+    }
+
+    #[test]
+    fn test_search_by_name() {
+        assert_eq!(name('\u{202f}').as_deref(), Some("NARROW NO-BREAK SPACE"));
+        assert_eq!(search("narrow no-break", 10), vec!['\u{202f}']);
+        // Not in the common list
+        assert!(search("snowman", 10).contains(&'☃'));
+        assert_eq!(search("greek small letter alpha", 1), vec!['α']);
+        // An exact name, then the common characters
+        assert_eq!(search("space", 3), vec![' ', '\u{a0}', '\u{2002}']);
+        assert!(search("  ", 10).is_empty());
+    }
+}
@@ -0,0 +1,295 @@
+// Poterm - Modern TUI editor for .po translation files
+// Copyright (c) 2025 AnmiTaliDev <anmitali198@gmail.com>
+// Licensed under the Apache License, Version 2.0
+
+use crate::config::Config;
+use crate::gettext::PoFile;
+use crate::glossary::Glossary;
+use crate::history::OutputFormat;
+use crate::validate::{run_checks, run_duplicate_checks, EntryIssue, Severity};
+use anyhow::Result;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct Totals {
+    errors: usize,
+    warnings: usize,
+    infos: usize,
+    /// Acknowledged issues, which never fail a check
+    suppressed: usize,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Options {
+    /// Only report entries defined more than once
+    pub duplicates_only: bool,
+    /// List suppressed issues instead of only counting them
+    pub show_suppressed: bool,
+    pub format: OutputFormat,
+}
+
+/// One reported issue, or a file that could not be checked at all, in
+/// which case there is no line or check
+#[derive(Debug, Clone, PartialEq, Serialize)]
+struct Reported {
+    file: PathBuf,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    line: Option<usize>,
+    severity: Severity,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    check: Option<&'static str>,
+    message: String,
+    suppressed: bool,
+}
+
+impl fmt::Display for Reported {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:", self.file.display())?;
+        if let Some(line) = self.line {
+            write!(f, "{}:", line)?;
+        }
+        write!(f, " {}: {}", self.severity, self.message)?;
+        if let Some(check) = self.check {
+            write!(f, " [{}]", check)?;
+        }
+        if self.suppressed {
+            write!(f, " (suppressed)")?;
+        }
+        Ok(())
+    }
+}
+
+/// Headless `poterm check`: validate each file and print one
+/// `file:line: severity: message [check]` line per issue, or a JSON array
+/// of the same issues. Suppressed issues are only counted, or listed too
+/// with `show_suppressed`, marked `(suppressed)` or `"suppressed": true`.
+/// A glossary that fails to load is an error of each file using it. Returns
+/// false when any file has unsuppressed errors or could not be read.
+pub fn run(files: &[PathBuf], config: &Config, options: &Options) -> Result<bool> {
+    check_files(files, config, options, &mut io::stdout().lock())
+}
+
+fn check_files<W: Write>(files: &[PathBuf], config: &Config, options: &Options, out: &mut W) -> Result<bool> {
+    let mut totals = Totals::default();
+    let mut unreadable = 0;
+    // Failures are kept too, so each file using the glossary reports them
+    let mut glossaries: HashMap<PathBuf, Result<Glossary, String>> = HashMap::new();
+    let mut reported = Vec::new();
+
+    for path in files {
+        match PoFile::from_file(path) {
+            Ok(po_file) => {
+                let issues = if options.duplicates_only {
+                    run_duplicate_checks(&po_file)
+                } else {
+                    let glossary = match config.glossary_path(Some(path)) {
+                        Some(glossary_path) => {
+                            let loaded = glossaries.entry(glossary_path.clone()).or_insert_with(|| {
+                                Glossary::from_file(&glossary_path).map_err(|e| format!("{:#}", e))
+                            });
+                            match loaded {
+                                Ok(glossary) => Some(&*glossary),
+                                Err(e) => {
+                                    reported.push(Reported {
+                                        file: path.clone(),
+                                        line: None,
+                                        severity: Severity::Error,
+                                        check: Some("glossary"),
+                                        message: e.clone(),
+                                        suppressed: false,
+                                    });
+                                    totals.errors += 1;
+                                    None
+                                }
+                            }
+                        }
+                        None => None,
+                    };
+                    run_checks(&po_file, &config.style, glossary)
+                };
+                let file_totals = report(path, &po_file, &issues, options.show_suppressed, &mut reported);
+                totals.errors += file_totals.errors;
+                totals.warnings += file_totals.warnings;
+                totals.infos += file_totals.infos;
+                totals.suppressed += file_totals.suppressed;
+            }
+            Err(e) => {
+                reported.push(Reported {
+                    file: path.clone(),
+                    line: None,
+                    severity: Severity::Error,
+                    check: None,
+                    message: format!("{:#}", e),
+                    suppressed: false,
+                });
+                unreadable += 1;
+            }
+        }
+        // Text is printed as each file is done
+        if options.format == OutputFormat::Text {
+            for found in reported.drain(..) {
+                writeln!(out, "{}", found)?;
+            }
+        }
+    }
+    if options.format == OutputFormat::Json {
+        writeln!(out, "{}", serde_json::to_string_pretty(&reported)?)?;
+    }
+
+    let infos = match totals.infos {
+        0 => String::new(),
+        count => format!(", {} info", count),
+    };
+    let suppressed = match totals.suppressed {
+        0 => String::new(),
+        count => format!(", {} suppressed", count),
+    };
+    eprintln!(
+        "{} error(s), {} warning(s){}{} in {} file(s)",
+        totals.errors,
+        totals.warnings,
+        infos,
+        suppressed,
+        files.len()
+    );
+    Ok(totals.errors == 0 && unreadable == 0)
+}
+
+fn report(
+    path: &Path,
+    po_file: &PoFile,
+    issues: &[EntryIssue],
+    show_suppressed: bool,
+    reported: &mut Vec<Reported>,
+) -> Totals {
+    let mut totals = Totals::default();
+    for found in issues {
+        if found.suppressed {
+            totals.suppressed += 1;
+        } else {
+            match found.issue.severity {
+                Severity::Error => totals.errors += 1,
+                Severity::Warning => totals.warnings += 1,
+                Severity::Info => totals.infos += 1,
+            }
+        }
+        if found.suppressed && !show_suppressed {
+            continue;
+        }
+        reported.push(Reported {
+            file: path.to_path_buf(),
+            line: Some(po_file.entries[found.entry].line),
+            severity: found.issue.severity,
+            check: Some(found.issue.check),
+            message: found.issue.message.clone(),
+            suppressed: found.suppressed,
+        });
+    }
+    totals
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::StyleConfig;
+
+    fn text(reported: &[Reported]) -> String {
+        reported.iter().map(|found| format!("{}\n", found)).collect()
+    }
+
+    #[test]
+    fn test_report_format() {
+        let content = "msgid \"<b>Bold</b>\"\nmsgstr \"<b>Жирный\"\n\nmsgid \"<i>A &amp; B</i>\"\nmsgstr \"<i>А & Б</i>\"\n";
+        let po_file = PoFile::parse(content).unwrap();
+
+        let mut out = Vec::new();
+        let issues = run_checks(&po_file, &StyleConfig::default(), None);
+        let totals = report(Path::new("ru.po"), &po_file, &issues, false, &mut out);
+        assert_eq!(totals, Totals { errors: 1, warnings: 1, infos: 0, suppressed: 0 });
+        assert_eq!(
+            text(&out),
+            "ru.po:1: error: Missing tag </b> [markup]\n\
+             ru.po:4: warning: Stray '&' at character 6 in markup, use &amp; [markup]\n"
+        );
+    }
+
+    #[test]
+    fn test_duplicates_report() {
+        let content = "msgid \"Open\"\nmsgstr \"<b>\"\n\nmsgctxt \"menu\"\nmsgid \"Open\"\nmsgstr \"\"\n\nmsgid \"Open\"\nmsgstr \"\"\n";
+        let po_file = PoFile::parse(content).unwrap();
+
+        let mut out = Vec::new();
+        let totals = report(Path::new("ru.po"), &po_file, &run_duplicate_checks(&po_file), false, &mut out);
+        assert_eq!(totals, Totals { errors: 1, warnings: 0, infos: 1, suppressed: 0 });
+        assert_eq!(
+            text(&out),
+            "ru.po:5: info: Same msgid as line 1 with no context [duplicate-msgid]\n\
+             ru.po:8: error: Duplicate message definition, first defined at line 1 [duplicate]\n"
+        );
+    }
+
+    #[test]
+    fn test_suppressed_issues() {
+        let content = "# poterm: ignore=markup\nmsgid \"<b>Bold</b>\"\nmsgstr \"<b>Жирный\"\n";
+        let po_file = PoFile::parse(content).unwrap();
+        let issues = run_checks(&po_file, &StyleConfig::default(), None);
+
+        let mut out = Vec::new();
+        let totals = report(Path::new("ru.po"), &po_file, &issues, false, &mut out);
+        assert_eq!(totals, Totals { errors: 0, warnings: 0, infos: 0, suppressed: 1 });
+        assert!(out.is_empty());
+
+        let totals = report(Path::new("ru.po"), &po_file, &issues, true, &mut out);
+        assert_eq!(totals.suppressed, 1);
+        assert_eq!(
+            text(&out),
+            "ru.po:2: error: Missing tag </b> [markup] (suppressed)\n"
+        );
+        assert_eq!(
+            serde_json::to_value(&out).unwrap(),
+            serde_json::json!([{
+                "file": "ru.po",
+                "line": 2,
+                "severity": "error",
+                "check": "markup",
+                "message": "Missing tag </b>",
+                "suppressed": true,
+            }])
+        );
+    }
+
+    #[test]
+    fn test_unreadable_file() {
+        let found = Reported {
+            file: PathBuf::from("missing.po"),
+            line: None,
+            severity: Severity::Error,
+            check: None,
+            message: "Failed to read missing.po".to_string(),
+            suppressed: false,
+        };
+        assert_eq!(found.to_string(), "missing.po: error: Failed to read missing.po");
+        let json = serde_json::to_value(&found).unwrap();
+        assert!(json.get("line").is_none());
+        assert!(json.get("check").is_none());
+    }
+
+    #[test]
+    fn test_missing_glossary_fails_each_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let files: Vec<PathBuf> = ["de.po", "ru.po"].iter().map(|name| dir.path().join(name)).collect();
+        for path in &files {
+            std::fs::write(path, "msgid \"Open\"\nmsgstr \"Open\"\n").unwrap();
+        }
+        let config = Config { glossary: Some(PathBuf::from("glossary.tsv")), ..Config::default() };
+        let mut out = Vec::new();
+        assert!(!check_files(&files, &config, &Options::default(), &mut out).unwrap());
+        let out = String::from_utf8(out).unwrap();
+        assert_eq!(out.lines().count(), 2);
+        assert!(out.lines().all(|line| line.contains("error: Failed to read glossary") && line.ends_with("[glossary]")));
+    }
+}
@@ -0,0 +1,98 @@
+// Poterm - Modern TUI editor for .po translation files
+// Copyright (c) 2025 AnmiTaliDev <anmitali198@gmail.com>
+// Licensed under the Apache License, Version 2.0
+
+//! Copy and paste through the system clipboard with `arboard`. Where that
+//! can't be reached, e.g. over SSH, copying falls back to the OSC 52 escape
+//! sequence, which most terminals pass on to the clipboard of the machine
+//! they run on.
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use crossterm::Command;
+use std::fmt;
+
+/// Ask the terminal to put text on its clipboard (OSC 52)
+pub struct SetClipboard<'a>(pub &'a str);
+
+impl Command for SetClipboard<'_> {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        write!(f, "\x1b]52;c;{}\x07", STANDARD.encode(self.0))
+    }
+}
+
+/// The clipboard the editor copies to and pastes from. Without the system
+/// clipboard, as in tests, text is only kept for pasting within the editor.
+#[derive(Default)]
+pub struct Clipboard {
+    system: bool,
+    /// Missing when there is no display server to reach
+    native: Option<arboard::Clipboard>,
+    /// The text copied last, pasted when the system clipboard can't be read
+    last: Option<String>,
+    /// Copied text for the event loop to send to the terminal
+    terminal_copy: Option<String>,
+}
+
+impl Clipboard {
+    pub fn system() -> Self {
+        Self {
+            system: true,
+            native: arboard::Clipboard::new().ok(),
+            ..Self::default()
+        }
+    }
+
+    /// Copy `text`, returning where it went for the status line
+    pub fn copy(&mut self, text: &str) -> &'static str {
+        self.last = Some(text.to_string());
+        if !self.system {
+            return "poterm";
+        }
+        if let Some(ref mut native) = self.native {
+            if native.set_text(text).is_ok() {
+                return "the system clipboard";
+            }
+        }
+        self.terminal_copy = Some(text.to_string());
+        "the terminal"
+    }
+
+    pub fn paste(&mut self) -> Option<String> {
+        let system = self.native.as_mut().and_then(|native| native.get_text().ok());
+        system.or_else(|| self.last.clone())
+    }
+
+    /// Text to write to the terminal with `SetClipboard`, once
+    pub fn take_terminal_copy(&mut self) -> Option<String> {
+        self.terminal_copy.take()
+    }
+}
+
+/// Pasted text as the editor stores it: terminals send line breaks as
+/// carriage returns
+pub fn normalize_newlines(text: &str) -> String {
+    text.replace("\r\n", "\n").replace('\r', "\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_osc52() {
+        let mut sequence = String::new();
+        SetClipboard("Файл").write_ansi(&mut sequence).unwrap();
+        assert_eq!(sequence, "\x1b]52;c;0KTQsNC50Ls=\x07");
+    }
+
+    #[test]
+    fn test_internal_clipboard() {
+        let mut clipboard = Clipboard::default();
+        assert_eq!(clipboard.paste(), None);
+        assert_eq!(clipboard.copy("Открыть\nфайл"), "poterm");
+        assert_eq!(clipboard.paste().as_deref(), Some("Открыть\nфайл"));
+        assert_eq!(clipboard.take_terminal_copy(), None);
+        assert_eq!(normalize_newlines("a\r\nb\rc\n"), "a\nb\nc\n");
+    }
+}
@@ -0,0 +1,306 @@
+// Poterm - Modern TUI editor for .po translation files
+// Copyright (c) 2025 AnmiTaliDev <anmitali198@gmail.com>
+// Licensed under the Apache License, Version 2.0
+
+//! Column model for the entry list
+
+use crate::config::ListConfig;
+use serde::Deserialize;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// The msgid preview keeps at least this many cells; optional columns are
+/// dropped before it gets narrower
+const MIN_PREVIEW_WIDTH: u16 = 16;
+
+/// Flags worth a badge after the msgid preview
+const FLAG_BADGES: [(&str, char); 2] = [("c-format", 'ⓒ'), ("no-wrap", '⊘')];
+
+/// A context prefix narrower than this is left out
+const MIN_CONTEXT_WIDTH: usize = 4;
+
+/// Numbering shown in the index column
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IndexMode {
+    /// Position of the entry in the file
+    #[default]
+    Original,
+    /// Position within the current filter
+    Filtered,
+    Off,
+}
+
+impl IndexMode {
+    pub fn next(&self) -> Self {
+        match self {
+            IndexMode::Original => IndexMode::Filtered,
+            IndexMode::Filtered => IndexMode::Off,
+            IndexMode::Off => IndexMode::Original,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            IndexMode::Original => "file position",
+            IndexMode::Filtered => "filter position",
+            IndexMode::Off => "off",
+        }
+    }
+}
+
+/// Entry list columns, in display order
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Column {
+    /// Translated / fuzzy / untranslated glyph
+    Status,
+    Index,
+    /// Quick-jump mark digit
+    Mark,
+    /// Number of QA issues
+    Issues,
+    /// Entry changed since the last save
+    Modified,
+    /// msgid preview, takes the remaining width
+    Preview,
+    /// First source reference, right-aligned
+    Reference,
+}
+
+/// Columns that fit into the list, with their widths
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnLayout {
+    pub columns: Vec<(Column, u16)>,
+}
+
+impl ColumnLayout {
+    /// Lay out the configured columns in `width` cells. `max_index` is the
+    /// largest number the index column has to show and `reference_width`
+    /// the widest reference label. Optional columns are dropped, least
+    /// important first, to keep the preview readable.
+    pub fn new(config: &ListConfig, width: u16, max_index: usize, reference_width: u16) -> Self {
+        let index_width = max_index.max(1).to_string().len() as u16 + 1;
+
+        let mut columns = vec![(Column::Status, 2)];
+        if config.index != IndexMode::Off {
+            columns.push((Column::Index, index_width));
+        }
+        columns.push((Column::Mark, 1));
+        if config.issues {
+            columns.push((Column::Issues, 3));
+        }
+        if config.modified {
+            columns.push((Column::Modified, 2));
+        }
+        if config.reference && reference_width > 0 {
+            // A space before it, and never more than a third of the list
+            columns.push((Column::Reference, (reference_width + 1).min(width / 3)));
+        }
+
+        for optional in [Column::Reference, Column::Issues, Column::Modified, Column::Index] {
+            let used: u16 = columns.iter().map(|(_, w)| w).sum();
+            if width.saturating_sub(used) >= MIN_PREVIEW_WIDTH {
+                break;
+            }
+            columns.retain(|(column, _)| *column != optional);
+        }
+
+        let used: u16 = columns.iter().map(|(_, w)| w).sum();
+        let preview = (Column::Preview, width.saturating_sub(used));
+        match columns.iter().position(|(column, _)| *column == Column::Reference) {
+            Some(reference) => columns.insert(reference, preview),
+            None => columns.push(preview),
+        }
+        Self { columns }
+    }
+
+    pub fn has(&self, column: Column) -> bool {
+        self.columns.iter().any(|(c, _)| *c == column)
+    }
+}
+
+/// Cut `text` to at most `width` display cells, ending with `…` when it was
+/// shortened. Line breaks are shown as `⏎`.
+pub fn truncate_to_width(text: &str, width: u16) -> String {
+    let width = width as usize;
+    let mut result = String::new();
+    let mut used = 0;
+    let mut chars = text.chars().map(|c| if c == '\n' { '⏎' } else { c }).peekable();
+
+    while let Some(c) = chars.next() {
+        let char_width = c.width().unwrap_or(0);
+        let reserve = if chars.peek().is_some() { 1 } else { 0 };
+        if used + char_width + reserve > width {
+            if used < width {
+                result.push('…');
+            }
+            break;
+        }
+        result.push(c);
+        used += char_width;
+    }
+    result
+}
+
+/// Parts of the preview column: `[context] msgid ⓒ`
+#[derive(Debug, Clone, PartialEq)]
+pub struct PreviewCell {
+    /// Bracketed msgctxt with a trailing space, or empty
+    pub context: String,
+    pub msgid: String,
+    /// Flag badges with a leading space, or empty
+    pub badges: String,
+}
+
+impl PreviewCell {
+    /// Fit the parts into `width` cells. Badges are kept whole, and the
+    /// context takes at most half of what is left so the msgid stays
+    /// readable.
+    pub fn new(msgctxt: Option<&str>, msgid: &str, flags: &[String], badges: bool, width: u16) -> Self {
+        let mut badge_text: String = FLAG_BADGES
+            .iter()
+            .filter(|(flag, _)| badges && flags.iter().any(|f| f == flag))
+            .map(|&(_, badge)| badge)
+            .collect();
+        if !badge_text.is_empty() {
+            badge_text.insert(0, ' ');
+        }
+        if badge_text.width() >= width as usize {
+            badge_text.clear();
+        }
+        let rest = width as usize - badge_text.width();
+
+        let context = match msgctxt {
+            Some(context) if rest / 2 >= MIN_CONTEXT_WIDTH => {
+                let budget = (context.width() + 3).min(rest / 2);
+                format!("[{}] ", truncate_to_width(context, (budget - 3) as u16))
+            }
+            _ => String::new(),
+        };
+        Self {
+            msgid: truncate_to_width(msgid, (rest - context.width()) as u16),
+            context,
+            badges: badge_text,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.context.width() + self.msgid.width() + self.badges.width()
+    }
+}
+
+/// `src/gtk/window.c:215` -> `window.c:215`
+pub fn reference_label(reference: &str) -> &str {
+    reference.rsplit('/').next().unwrap_or(reference)
+}
+
+/// `reference` in at most `width` cells: leading directories are dropped
+/// first, then the file name is cut
+pub fn fit_reference(reference: &str, width: u16) -> String {
+    let mut rest = reference;
+    while rest.width() > width as usize {
+        match rest.split_once('/') {
+            Some((_, tail)) => rest = tail,
+            None => return truncate_to_width(rest, width),
+        }
+    }
+    rest.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(layout: &ColumnLayout) -> Vec<Column> {
+        layout.columns.iter().map(|(c, _)| *c).collect()
+    }
+
+    #[test]
+    fn test_layout_drops_optional_columns_when_narrow() {
+        let config = ListConfig {
+            index: IndexMode::Original,
+            issues: true,
+            modified: true,
+            reference: false,
+            ..ListConfig::default()
+        };
+
+        let wide = ColumnLayout::new(&config, 60, 1200, 0);
+        assert_eq!(
+            kinds(&wide),
+            vec![Column::Status, Column::Index, Column::Mark, Column::Issues, Column::Modified, Column::Preview]
+        );
+        assert_eq!(wide.columns[1].1, 5);
+        assert_eq!(wide.columns.last().unwrap().1, 60 - 2 - 5 - 1 - 3 - 2);
+
+        let narrow = ColumnLayout::new(&config, 24, 1200, 0);
+        assert_eq!(kinds(&narrow), vec![Column::Status, Column::Index, Column::Mark, Column::Preview]);
+
+        let tiny = ColumnLayout::new(&config, 10, 1200, 0);
+        assert_eq!(kinds(&tiny), vec![Column::Status, Column::Mark, Column::Preview]);
+    }
+
+    #[test]
+    fn test_index_mode_cycle() {
+        assert_eq!(IndexMode::Original.next(), IndexMode::Filtered);
+        assert_eq!(IndexMode::Off.next(), IndexMode::Original);
+    }
+
+    #[test]
+    fn test_truncate_to_width() {
+        assert_eq!(truncate_to_width("Open file", 20), "Open file");
+        assert_eq!(truncate_to_width("Open file", 6), "Open …");
+        assert_eq!(truncate_to_width("Открыть файл", 8), "Открыть…");
+        // Wide characters never overflow the budget
+        assert_eq!(truncate_to_width("日本語のテキスト", 7), "日本語…");
+        assert_eq!(truncate_to_width("Файл 🙂🙂🙂", 8), "Файл 🙂…");
+        assert_eq!(truncate_to_width("a\nb", 5), "a⏎b");
+    }
+
+    #[test]
+    fn test_reference_column() {
+        let config = ListConfig {
+            reference: true,
+            ..ListConfig::default()
+        };
+        let layout = ColumnLayout::new(&config, 60, 100, 12);
+        assert_eq!(
+            layout.columns,
+            vec![(Column::Status, 2), (Column::Index, 4), (Column::Mark, 1), (Column::Preview, 40), (Column::Reference, 13)]
+        );
+        // Capped to a third of the list, dropped first when narrow
+        assert_eq!(ColumnLayout::new(&config, 60, 100, 40).columns.last(), Some(&(Column::Reference, 20)));
+        assert!(!ColumnLayout::new(&config, 30, 100, 12).has(Column::Reference));
+        assert!(!ColumnLayout::new(&config, 60, 100, 0).has(Column::Reference));
+    }
+
+    #[test]
+    fn test_preview_cell() {
+        let flags = vec!["c-format".to_string(), "no-wrap".to_string()];
+        let cell = PreviewCell::new(Some("column header"), "Name", &flags, true, 40);
+        assert_eq!((cell.context.as_str(), cell.msgid.as_str(), cell.badges.as_str()), ("[column header] ", "Name", " ⓒ⊘"));
+
+        // The context gives way to the msgid first, then disappears
+        let cell = PreviewCell::new(Some("column header"), "Name of the file", &flags, true, 24);
+        assert_eq!((cell.context.as_str(), cell.msgid.as_str()), ("[column…] ", "Name of th…"));
+        assert_eq!(cell.width(), 24);
+        let cell = PreviewCell::new(Some("column header"), "Name", &[], true, 7);
+        assert_eq!((cell.context.as_str(), cell.msgid.as_str()), ("", "Name"));
+
+        // Only notable flags get a badge, and badges can be turned off
+        let cell = PreviewCell::new(None, "Name", &["fuzzy".to_string(), "c-format".to_string()], true, 20);
+        assert_eq!(cell.badges, " ⓒ");
+        assert_eq!(PreviewCell::new(None, "Name", &flags, false, 20).badges, "");
+        // Never wider than the column, even with no room left
+        assert_eq!(PreviewCell::new(Some("menu"), "Name", &flags, true, 2).width(), 2);
+    }
+
+    #[test]
+    fn test_fit_reference() {
+        assert_eq!(reference_label("src/gtk/window.c:215"), "window.c:215");
+        assert_eq!(reference_label("main.c:1"), "main.c:1");
+        assert_eq!(fit_reference("src/gtk/window.c:215", 30), "src/gtk/window.c:215");
+        assert_eq!(fit_reference("src/gtk/window.c:215", 16), "gtk/window.c:215");
+        assert_eq!(fit_reference("src/gtk/window.c:215", 14), "window.c:215");
+        assert_eq!(fit_reference("src/gtk/window.c:215", 8), "window.…");
+    }
+}
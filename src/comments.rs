@@ -0,0 +1,356 @@
+// Poterm - Modern TUI editor for .po translation files
+// Copyright (c) 2025 AnmiTaliDev <anmitali198@gmail.com>
+// Licensed under the Apache License, Version 2.0
+
+use crate::gettext::PoEntry;
+use crate::input::TextInput;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CommentSection {
+    /// `# ` translator comments
+    Translator,
+    /// `#. ` comments extracted from the source code
+    Extracted,
+    /// `#, ` flags
+    Flags,
+}
+
+impl CommentSection {
+    pub fn title(&self) -> &'static str {
+        match self {
+            CommentSection::Translator => "Translator comments (#)",
+            CommentSection::Extracted => "Extracted comments (#.)",
+            CommentSection::Flags => "Flags (#,)",
+        }
+    }
+
+    fn next(&self) -> Self {
+        match self {
+            CommentSection::Translator => CommentSection::Extracted,
+            CommentSection::Extracted => CommentSection::Flags,
+            CommentSection::Flags => CommentSection::Translator,
+        }
+    }
+
+    fn rows<'a>(&self, entry: &'a PoEntry) -> &'a Vec<String> {
+        match self {
+            CommentSection::Translator => &entry.comments,
+            CommentSection::Extracted => &entry.extracted_comments,
+            CommentSection::Flags => &entry.flags,
+        }
+    }
+
+    fn rows_mut<'a>(&self, entry: &'a mut PoEntry) -> &'a mut Vec<String> {
+        match self {
+            CommentSection::Translator => &mut entry.comments,
+            CommentSection::Extracted => &mut entry.extracted_comments,
+            CommentSection::Flags => &mut entry.flags,
+        }
+    }
+}
+
+/// Row being edited in the comment manager
+#[derive(Debug, Clone, PartialEq)]
+pub struct RowEdit {
+    /// Row index, or None when adding a new row
+    pub row: Option<usize>,
+    pub input: TextInput,
+}
+
+/// What a key press did to the comment manager
+#[derive(Debug, Clone, PartialEq)]
+pub enum CommentEvent {
+    None,
+    /// The entry's comments or flags were changed
+    Changed,
+    Message(String),
+    Close,
+}
+
+/// Overlay state for managing the comments and flags of one entry
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommentManager {
+    pub entry_index: usize,
+    pub section: CommentSection,
+    pub selected: usize,
+    pub edit: Option<RowEdit>,
+    pub allow_extracted_edit: bool,
+    /// Esc applies a row edit like Enter instead of discarding it
+    pub esc_commits: bool,
+}
+
+impl CommentManager {
+    pub fn new(entry_index: usize, allow_extracted_edit: bool) -> Self {
+        Self {
+            entry_index,
+            section: CommentSection::Translator,
+            selected: 0,
+            edit: None,
+            allow_extracted_edit,
+            esc_commits: false,
+        }
+    }
+
+    fn is_read_only(&self) -> bool {
+        self.section == CommentSection::Extracted && !self.allow_extracted_edit
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent, entry: &mut PoEntry) -> CommentEvent {
+        if self.edit.is_some() {
+            return self.handle_edit_key(key, entry);
+        }
+
+        let len = self.section.rows(entry).len();
+        let shift = key.modifiers.contains(KeyModifiers::SHIFT);
+        let alt = key.modifiers.contains(KeyModifiers::ALT);
+
+        match key.code {
+            KeyCode::Esc => CommentEvent::Close,
+            KeyCode::Tab => {
+                self.section = self.section.next();
+                self.selected = 0;
+                CommentEvent::None
+            }
+            KeyCode::Up if alt => self.move_row(entry, -1),
+            KeyCode::Down if alt => self.move_row(entry, 1),
+            KeyCode::Char('K') => self.move_row(entry, -1),
+            KeyCode::Char('J') => self.move_row(entry, 1),
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.selected = self.selected.saturating_sub(1);
+                CommentEvent::None
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                if self.selected + 1 < len {
+                    self.selected += 1;
+                }
+                CommentEvent::None
+            }
+            KeyCode::Char('a') | KeyCode::Insert if !shift => {
+                if self.is_read_only() {
+                    return self.read_only_message();
+                }
+                self.edit = Some(RowEdit {
+                    row: None,
+                    input: TextInput::default(),
+                });
+                CommentEvent::None
+            }
+            KeyCode::Char('e') | KeyCode::Enter => {
+                if self.is_read_only() {
+                    return self.read_only_message();
+                }
+                if let Some(text) = self.section.rows(entry).get(self.selected) {
+                    self.edit = Some(RowEdit {
+                        row: Some(self.selected),
+                        input: TextInput::new(text),
+                    });
+                }
+                CommentEvent::None
+            }
+            KeyCode::Char('d') | KeyCode::Delete => {
+                if self.is_read_only() {
+                    return self.read_only_message();
+                }
+                if self.selected >= len {
+                    return CommentEvent::None;
+                }
+                self.section.rows_mut(entry).remove(self.selected);
+                if self.selected > 0 && self.selected >= len - 1 {
+                    self.selected -= 1;
+                }
+                self.changed(entry)
+            }
+            _ => CommentEvent::None,
+        }
+    }
+
+    fn handle_edit_key(&mut self, key: KeyEvent, entry: &mut PoEntry) -> CommentEvent {
+        let Some(edit) = self.edit.as_mut() else {
+            return CommentEvent::None;
+        };
+
+        match key.code {
+            KeyCode::Esc if !self.esc_commits => {
+                self.edit = None;
+                CommentEvent::None
+            }
+            KeyCode::Enter | KeyCode::Esc => {
+                let (row, text) = (edit.row, edit.input.text.trim().to_string());
+                self.edit = None;
+                let rows = self.section.rows_mut(entry);
+                match row {
+                    Some(row) if text.is_empty() => {
+                        rows.remove(row);
+                        self.selected = self.selected.min(rows.len().saturating_sub(1));
+                    }
+                    Some(row) => {
+                        if rows[row] == text {
+                            return CommentEvent::None;
+                        }
+                        rows[row] = text;
+                    }
+                    None if text.is_empty() => return CommentEvent::None,
+                    None => {
+                        let at = if rows.is_empty() { 0 } else { (self.selected + 1).min(rows.len()) };
+                        rows.insert(at, text);
+                        self.selected = at;
+                    }
+                }
+                self.changed(entry)
+            }
+            // Flags are a comma separated list, so a comma would split the row
+            KeyCode::Char(',') if self.section == CommentSection::Flags => CommentEvent::None,
+            _ => {
+                edit.input.handle_key(key);
+                CommentEvent::None
+            }
+        }
+    }
+
+    fn move_row(&mut self, entry: &mut PoEntry, delta: isize) -> CommentEvent {
+        if self.is_read_only() {
+            return self.read_only_message();
+        }
+        let rows = self.section.rows_mut(entry);
+        let target = self.selected as isize + delta;
+        if self.selected >= rows.len() || target < 0 || target as usize >= rows.len() {
+            return CommentEvent::None;
+        }
+        rows.swap(self.selected, target as usize);
+        self.selected = target as usize;
+        self.changed(entry)
+    }
+
+    fn changed(&self, entry: &mut PoEntry) -> CommentEvent {
+        if self.section == CommentSection::Flags {
+            entry.update_status();
+        }
+        CommentEvent::Changed
+    }
+
+    fn read_only_message(&self) -> CommentEvent {
+        CommentEvent::Message(
+            "Extracted comments come from the source code and are read-only".to_string(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    fn type_text(manager: &mut CommentManager, entry: &mut PoEntry, text: &str) {
+        for c in text.chars() {
+            manager.handle_key(key(KeyCode::Char(c)), entry);
+        }
+    }
+
+    fn sample_entry() -> PoEntry {
+        let mut entry = PoEntry::new();
+        entry.msgid = "Open".to_string();
+        entry.comments = vec!["first".to_string(), "second".to_string()];
+        entry.extracted_comments = vec!["TRANSLATORS: verb".to_string()];
+        entry.flags = vec!["c-format".to_string()];
+        entry
+    }
+
+    #[test]
+    fn test_add_edit_and_delete_translator_comments() {
+        let mut entry = sample_entry();
+        let mut manager = CommentManager::new(0, false);
+
+        manager.handle_key(key(KeyCode::Char('a')), &mut entry);
+        type_text(&mut manager, &mut entry, "added");
+        assert_eq!(manager.handle_key(key(KeyCode::Enter), &mut entry), CommentEvent::Changed);
+        assert_eq!(entry.comments, vec!["first", "added", "second"]);
+        assert_eq!(manager.selected, 1);
+
+        manager.handle_key(key(KeyCode::Char('e')), &mut entry);
+        manager.handle_key(key(KeyCode::Backspace), &mut entry);
+        manager.handle_key(key(KeyCode::Enter), &mut entry);
+        assert_eq!(entry.comments, vec!["first", "adde", "second"]);
+
+        manager.handle_key(key(KeyCode::Char('d')), &mut entry);
+        assert_eq!(entry.comments, vec!["first", "second"]);
+    }
+
+    #[test]
+    fn test_edit_cancel_and_reorder() {
+        let mut entry = sample_entry();
+        let mut manager = CommentManager::new(0, false);
+
+        manager.handle_key(key(KeyCode::Char('e')), &mut entry);
+        type_text(&mut manager, &mut entry, " changed");
+        assert_eq!(manager.handle_key(key(KeyCode::Esc), &mut entry), CommentEvent::None);
+        assert_eq!(entry.comments, vec!["first", "second"]);
+
+        manager.handle_key(KeyEvent::new(KeyCode::Char('J'), KeyModifiers::SHIFT), &mut entry);
+        assert_eq!(entry.comments, vec!["second", "first"]);
+        assert_eq!(manager.selected, 1);
+
+        assert_eq!(manager.handle_key(key(KeyCode::Esc), &mut entry), CommentEvent::Close);
+    }
+
+    #[test]
+    fn test_extracted_comments_read_only_by_default() {
+        let mut entry = sample_entry();
+        let mut manager = CommentManager::new(0, false);
+        manager.handle_key(key(KeyCode::Tab), &mut entry);
+        assert_eq!(manager.section, CommentSection::Extracted);
+
+        let event = manager.handle_key(key(KeyCode::Char('d')), &mut entry);
+        assert!(matches!(event, CommentEvent::Message(_)));
+        assert_eq!(entry.extracted_comments.len(), 1);
+
+        let mut manager = CommentManager::new(0, true);
+        manager.handle_key(key(KeyCode::Tab), &mut entry);
+        assert_eq!(manager.handle_key(key(KeyCode::Char('d')), &mut entry), CommentEvent::Changed);
+        assert!(entry.extracted_comments.is_empty());
+    }
+
+    #[test]
+    fn test_flags_update_status() {
+        let mut entry = sample_entry();
+        entry.set_msgstr("Открыть".to_string());
+        let mut manager = CommentManager::new(0, false);
+        manager.handle_key(key(KeyCode::Tab), &mut entry);
+        manager.handle_key(key(KeyCode::Tab), &mut entry);
+
+        manager.handle_key(key(KeyCode::Char('a')), &mut entry);
+        type_text(&mut manager, &mut entry, "fu,zzy");
+        manager.handle_key(key(KeyCode::Enter), &mut entry);
+        assert_eq!(entry.flags, vec!["c-format", "fuzzy"]);
+        assert!(entry.is_fuzzy);
+
+        manager.handle_key(key(KeyCode::Char('d')), &mut entry);
+        assert!(!entry.is_fuzzy);
+        assert!(entry.is_translated);
+    }
+
+    #[test]
+    fn test_esc_discards_or_commits_row_edit() {
+        let mut entry = PoEntry::new();
+        entry.comments = vec!["old".to_string()];
+
+        for esc_commits in [false, true] {
+            let mut manager = CommentManager::new(0, false);
+            manager.esc_commits = esc_commits;
+            manager.handle_key(key(KeyCode::Enter), &mut entry);
+            manager.handle_key(key(KeyCode::Char('!')), &mut entry);
+            let event = manager.handle_key(key(KeyCode::Esc), &mut entry);
+            assert!(manager.edit.is_none());
+            if esc_commits {
+                assert_eq!(event, CommentEvent::Changed);
+                assert_eq!(entry.comments, vec!["old!"]);
+            } else {
+                assert_eq!(event, CommentEvent::None);
+                assert_eq!(entry.comments, vec!["old"]);
+            }
+        }
+    }
+}
@@ -0,0 +1,126 @@
+// Poterm - Modern TUI editor for .po translation files
+// Copyright (c) 2025 AnmiTaliDev <anmitali198@gmail.com>
+// Licensed under the Apache License, Version 2.0
+
+//! Word completion from the translations already in a catalog
+
+use crate::gettext::PoEntry;
+use std::collections::HashMap;
+
+/// Shorter words are quicker to type than to pick
+pub const MIN_WORD_LEN: usize = 5;
+/// Characters typed before completions are offered
+pub const MIN_PREFIX_LEN: usize = 3;
+/// Completions offered at once
+const MAX_COMPLETIONS: usize = 8;
+
+/// How often each word occurs in the msgstrs of a catalog
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WordIndex {
+    counts: HashMap<String, usize>,
+}
+
+impl WordIndex {
+    pub fn build(entries: &[PoEntry]) -> Self {
+        let mut index = Self::default();
+        for entry in entries {
+            index.add(&entry.msgstr);
+        }
+        index
+    }
+
+    pub fn add(&mut self, text: &str) {
+        for word in words(text) {
+            *self.counts.entry(word.to_string()).or_default() += 1;
+        }
+    }
+
+    pub fn remove(&mut self, text: &str) {
+        for word in words(text) {
+            if let Some(count) = self.counts.get_mut(word) {
+                *count -= 1;
+                if *count == 0 {
+                    self.counts.remove(word);
+                }
+            }
+        }
+    }
+
+    /// Words starting with `prefix` and longer than it, most frequent first
+    pub fn complete(&self, prefix: &str) -> Vec<String> {
+        let prefix_len = prefix.chars().count();
+        let mut matches: Vec<_> = self
+            .counts
+            .iter()
+            .filter(|(word, _)| word.starts_with(prefix) && word.chars().count() > prefix_len)
+            .collect();
+        matches.sort_by(|(a, a_count), (b, b_count)| b_count.cmp(a_count).then_with(|| a.cmp(b)));
+        matches.into_iter().take(MAX_COMPLETIONS).map(|(word, _)| word.clone()).collect()
+    }
+}
+
+/// Words long enough to be worth completing
+fn words(text: &str) -> impl Iterator<Item = &str> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| word.chars().count() >= MIN_WORD_LEN)
+}
+
+/// Char index where the word ending at `cursor` starts
+pub fn word_start(text: &str, cursor: usize) -> usize {
+    let before: Vec<char> = text.chars().take(cursor).collect();
+    let word_len = before.iter().rev().take_while(|c| c.is_alphanumeric()).count();
+    before.len() - word_len
+}
+
+/// Completion popup while a msgstr is edited
+#[derive(Debug, Clone, PartialEq)]
+pub struct Completion {
+    /// Char index of the first character of the typed prefix
+    pub start: usize,
+    pub words: Vec<String>,
+    pub selected: usize,
+}
+
+impl Completion {
+    pub fn next(&mut self) {
+        self.selected = (self.selected + 1) % self.words.len();
+    }
+
+    pub fn previous(&mut self) {
+        self.selected = (self.selected + self.words.len() - 1) % self.words.len();
+    }
+
+    pub fn selected_word(&self) -> &str {
+        &self.words[self.selected]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ranked_by_frequency() {
+        let mut index = WordIndex::default();
+        index.add("Профилирование включено. Профиль: профилирование");
+        index.add("Профилирование, профилировщик и профилирование");
+
+        assert_eq!(index.complete("проф"), vec!["профилирование", "профилировщик"]);
+        assert_eq!(index.complete("Проф"), vec!["Профилирование", "Профиль"]);
+        // Short words and exact matches are not offered
+        assert!(index.complete("включено").is_empty());
+
+        index.remove("Профилирование, профилировщик и профилирование");
+        assert_eq!(index.complete("проф"), vec!["профилирование"]);
+        index.remove("Профилирование включено. Профиль: профилирование");
+        assert_eq!(index, WordIndex::default());
+    }
+
+    #[test]
+    fn test_word_start() {
+        assert_eq!(word_start("Die Benutz", 10), 4);
+        assert_eq!(word_start("Die Benutz", 3), 0);
+        assert_eq!(word_start("Die ", 4), 4);
+        assert_eq!(word_start("", 0), 0);
+    }
+}
@@ -0,0 +1,425 @@
+// Poterm - Modern TUI editor for .po translation files
+// Copyright (c) 2025 AnmiTaliDev <anmitali198@gmail.com>
+// Licensed under the Apache License, Version 2.0
+
+use crate::columns::IndexMode;
+use crate::keymap::{self, Action};
+use crate::merge::MergeOptions;
+use crate::validate::StyleRule;
+use anyhow::{anyhow, Context, Result};
+use crossterm::event::{KeyCode, KeyModifiers};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum KeymapPreset {
+    #[default]
+    Default,
+    Vim,
+}
+
+/// What the header's main completion percentage measures
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProgressMetric {
+    #[default]
+    Entries,
+    /// Source words, so long untranslated strings weigh more
+    Words,
+}
+
+/// Entry selected when a file is opened
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum StartAt {
+    /// The entry selected when the file was last closed
+    LastSession,
+    #[default]
+    First,
+    /// The first untranslated entry, else the first fuzzy one
+    FirstUntranslated,
+}
+
+/// What Left and Right do in right-to-left text
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CursorMovement {
+    /// Previous and next character in the text
+    #[default]
+    Logical,
+    /// The character drawn left or right of the cursor
+    Visual,
+}
+
+/// What Tab does with the buffer when it moves an edit to the next field
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FieldSwitch {
+    /// Apply the buffer to the entry, as Enter does
+    #[default]
+    Commit,
+    /// Keep the buffer aside until the field is edited again; moving to
+    /// another entry drops it
+    Stash,
+}
+
+/// `[style]` section: typography checks shown in the QA panel
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StyleConfig {
+    /// Rules to run; defaults depend on the catalog's `Language` header
+    pub enabled: Option<Vec<StyleRule>>,
+    /// Rules to skip even when enabled by default
+    pub disabled: Vec<StyleRule>,
+    /// Translations shorter than this may be shared by different msgids
+    pub same_translation_min_length: usize,
+    /// Translations that may be shared by different msgids
+    pub same_translation_ignore: Vec<String>,
+}
+
+impl Default for StyleConfig {
+    fn default() -> Self {
+        Self {
+            enabled: None,
+            disabled: Vec::new(),
+            same_translation_min_length: 4,
+            same_translation_ignore: Vec::new(),
+        }
+    }
+}
+
+/// `[list]` section: columns of the entry list
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ListConfig {
+    /// Numbering: "original", "filtered" or "off"
+    pub index: IndexMode,
+    /// Show the number of QA issues per entry
+    pub issues: bool,
+    /// Mark entries changed since the last save
+    pub modified: bool,
+    /// Show the file and line of each entry's first reference
+    pub reference: bool,
+    /// Rows kept visible above and below the selection when scrolling
+    pub scroll_margin: usize,
+    /// Keep the selection in the middle of the list
+    pub center_selection: bool,
+    /// Badges after the msgid for notable flags: ⓒ c-format, ⊘ no-wrap
+    pub badges: bool,
+}
+
+impl Default for ListConfig {
+    fn default() -> Self {
+        Self {
+            index: IndexMode::default(),
+            issues: false,
+            modified: false,
+            reference: false,
+            scroll_margin: 3,
+            center_selection: false,
+            badges: true,
+        }
+    }
+}
+
+/// `[typography]` section: keys typing characters that terminals make hard
+/// to enter. An empty string leaves the character unbound.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TypographyConfig {
+    /// Non-breaking space, U+00A0
+    pub nbsp: String,
+    pub em_dash: String,
+    pub ellipsis: String,
+    /// Quotation marks for the catalog's language; pressed again it
+    /// switches to the next style
+    pub quotes: String,
+}
+
+impl Default for TypographyConfig {
+    fn default() -> Self {
+        Self {
+            nbsp: "alt+space".to_string(),
+            em_dash: "alt+-".to_string(),
+            ellipsis: "alt+.".to_string(),
+            quotes: "alt+'".to_string(),
+        }
+    }
+}
+
+impl TypographyConfig {
+    pub fn bindings(&self) -> Result<Vec<((KeyModifiers, KeyCode), Action)>> {
+        [
+            (&self.nbsp, Action::InsertChar('\u{a0}')),
+            (&self.em_dash, Action::InsertChar('—')),
+            (&self.ellipsis, Action::InsertChar('…')),
+            (&self.quotes, Action::CycleQuotes),
+        ]
+        .into_iter()
+        .filter(|(spec, _)| !spec.is_empty())
+        .map(|(spec, action)| {
+            let key = keymap::parse_key(spec).ok_or_else(|| anyhow!("Unknown key in [typography]: {}", spec))?;
+            Ok((key, action))
+        })
+        .collect()
+    }
+}
+
+/// User configuration read from `$XDG_CONFIG_HOME/poterm/config.toml`
+/// (or `~/.config/poterm/config.toml`). Every field is optional.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub keymap: KeymapPreset,
+    /// Allow editing `#.` comments, which normally come from the source code
+    pub allow_extracted_comment_edit: bool,
+    /// Edit msgids without confirming first; msgmerge matches on them
+    pub allow_msgid_edit: bool,
+    /// Show the open file and its modified state in the terminal title
+    pub terminal_title: bool,
+    /// Allow piping entries through shell commands
+    pub pipe_commands: bool,
+    /// Seconds before a piped shell command is killed
+    pub pipe_timeout: u64,
+    /// Header completion percentage: "entries" or "words"
+    pub progress: ProgressMetric,
+    /// Remember recent files and their cursor position between runs
+    pub persist_state: bool,
+    /// Record the time spent in each file, shown in the statistics
+    pub track_time: bool,
+    /// Journal unsaved edits to a swap file next to the open file
+    pub swap_file: bool,
+    /// Write a changed file to its recovery file this many seconds after the
+    /// last save; 0 never does
+    pub autosave: u64,
+    /// Autosave over the file itself rather than to a recovery file
+    pub autosave_in_place: bool,
+    /// Entry to select on opening: "last-session", "first" or
+    /// "first-untranslated"
+    pub start_at: StartAt,
+    /// Apply an edit on Esc instead of discarding it. Unset follows the
+    /// keymap: vim leaves insert mode keeping the text.
+    pub esc_commits: Option<bool>,
+    /// Tab while editing: "commit" or "stash" the buffer before moving to
+    /// the next field
+    pub field_switch: FieldSwitch,
+    /// Stamp PO-Revision-Date when saving a changed file rather than on
+    /// every header edit
+    pub revision_date_on_save: bool,
+    /// Wrap long strings at 79 columns on save like msgcat, rather than
+    /// keeping each on one line
+    pub wrap: bool,
+    /// Copy the file to `<name>.bak` before saving over it
+    pub backup: bool,
+    /// When merging a template, give new entries the translation of an old
+    /// msgid at least this similar (0 to 1), marked fuzzy; 0 never does
+    pub merge_similarity: f64,
+    /// Offer words from the catalog's translations while typing a msgstr
+    pub autocomplete: bool,
+    /// Draw right-to-left text in display order. Turn off for terminals
+    /// that reorder it themselves.
+    pub bidi: bool,
+    /// Left and Right in right-to-left text: "logical" or "visual"
+    pub cursor_movement: CursorMovement,
+    /// Flag marking translations that need review. Unlike fuzzy, such
+    /// entries are still compiled by msgfmt.
+    pub needs_work_flag: String,
+    /// Columns of the translation preview, the width the program shows
+    /// the text in
+    pub preview_width: u16,
+    /// Glossary file of terms and their translations. A relative path is
+    /// looked up next to the open catalog.
+    pub glossary: Option<PathBuf>,
+    /// CSV file each save appends the catalog's statistics to. A relative
+    /// path is kept next to the state file.
+    pub progress_log: Option<PathBuf>,
+    /// Abbreviations expanded in the editor, e.g. `";tm" = "™"`
+    pub snippets: BTreeMap<String, String>,
+    pub typography: TypographyConfig,
+    pub style: StyleConfig,
+    pub list: ListConfig,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            keymap: KeymapPreset::Default,
+            allow_extracted_comment_edit: false,
+            allow_msgid_edit: false,
+            terminal_title: true,
+            pipe_commands: true,
+            pipe_timeout: 10,
+            progress: ProgressMetric::Entries,
+            persist_state: true,
+            track_time: false,
+            swap_file: true,
+            autosave: 120,
+            autosave_in_place: false,
+            start_at: StartAt::First,
+            esc_commits: None,
+            field_switch: FieldSwitch::Commit,
+            revision_date_on_save: true,
+            wrap: true,
+            backup: false,
+            merge_similarity: 0.0,
+            autocomplete: true,
+            bidi: true,
+            cursor_movement: CursorMovement::Logical,
+            needs_work_flag: "poterm-needs-work".to_string(),
+            preview_width: 60,
+            glossary: None,
+            progress_log: None,
+            snippets: BTreeMap::new(),
+            typography: TypographyConfig::default(),
+            style: StyleConfig::default(),
+            list: ListConfig::default(),
+        }
+    }
+}
+
+impl Config {
+    pub fn default_path() -> Option<PathBuf> {
+        let base = match env::var_os("XDG_CONFIG_HOME") {
+            Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+            _ => PathBuf::from(env::var_os("HOME")?).join(".config"),
+        };
+        Some(base.join("poterm").join("config.toml"))
+    }
+
+    /// Load the configuration from the default location, falling back to
+    /// defaults when no config file exists.
+    pub fn load() -> Result<Self> {
+        match Self::default_path() {
+            Some(path) if path.exists() => Self::from_file(&path),
+            _ => Ok(Self::default()),
+        }
+    }
+
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file: {}", path.display()))?;
+        Self::parse(&content).with_context(|| format!("Invalid config file: {}", path.display()))
+    }
+
+    pub fn parse(content: &str) -> Result<Self> {
+        let config: Self = toml::from_str(content)?;
+        config.typography.bindings()?;
+        Ok(config)
+    }
+
+    /// Glossary for the catalog at `catalog`
+    pub fn glossary_path(&self, catalog: Option<&Path>) -> Option<PathBuf> {
+        let glossary = self.glossary.as_ref()?;
+        let dir = catalog.and_then(Path::parent).unwrap_or(Path::new(""));
+        Some(dir.join(glossary))
+    }
+
+    pub fn merge_options(&self) -> MergeOptions {
+        MergeOptions {
+            similarity: Some(self.merge_similarity).filter(|&similarity| similarity > 0.0),
+        }
+    }
+
+    pub fn esc_commits(&self) -> bool {
+        self.esc_commits.unwrap_or(self.keymap == KeymapPreset::Vim)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_empty_config() {
+        assert_eq!(Config::parse("").unwrap(), Config::default());
+    }
+
+    #[test]
+    fn test_parse_keymap() {
+        let config = Config::parse("keymap = \"vim\"").unwrap();
+        assert_eq!(config.keymap, KeymapPreset::Vim);
+    }
+
+    #[test]
+    fn test_parse_style_section() {
+        let config = Config::parse("[style]\nenabled = [\"ellipsis\", \"double-space\"]\n").unwrap();
+        assert_eq!(config.style.enabled, Some(vec![StyleRule::Ellipsis, StyleRule::DoubleSpace]));
+        assert!(Config::parse("[style]\nenabled = [\"no-such-rule\"]\n").is_err());
+        assert_eq!(config.style.same_translation_min_length, 4);
+        let config = Config::parse("[style]\nsame_translation_ignore = [\"Закрыть\"]\n").unwrap();
+        assert_eq!(config.style.same_translation_ignore, vec!["Закрыть"]);
+    }
+
+    #[test]
+    fn test_parse_list_section() {
+        let config = Config::parse("[list]\nindex = \"filtered\"\nissues = true\n").unwrap();
+        assert_eq!(config.list.index, IndexMode::Filtered);
+        assert!(config.list.issues);
+        assert!(!config.list.modified);
+        assert_eq!(config.list.scroll_margin, 3);
+    }
+
+    #[test]
+    fn test_reject_unknown_keys() {
+        assert!(Config::parse("keymap = \"emacs\"").is_err());
+        assert!(Config::parse("no_such_option = true").is_err());
+    }
+
+    #[test]
+    fn test_esc_commits_follows_keymap() {
+        assert!(!Config::default().esc_commits());
+        assert!(Config::parse("keymap = \"vim\"").unwrap().esc_commits());
+        assert!(!Config::parse("keymap = \"vim\"\nesc_commits = false").unwrap().esc_commits());
+        assert!(Config::parse("esc_commits = true").unwrap().esc_commits());
+        assert_eq!(Config::default().field_switch, FieldSwitch::Commit);
+        assert_eq!(Config::parse("field_switch = \"stash\"").unwrap().field_switch, FieldSwitch::Stash);
+    }
+
+    #[test]
+    fn test_parse_bidi() {
+        let config = Config::parse("bidi = false\ncursor_movement = \"visual\"").unwrap();
+        assert!(!config.bidi);
+        assert_eq!(config.cursor_movement, CursorMovement::Visual);
+        assert!(Config::parse("cursor_movement = \"sideways\"").is_err());
+    }
+
+    #[test]
+    fn test_parse_start_at() {
+        assert_eq!(Config::default().start_at, StartAt::First);
+        let config = Config::parse("start_at = \"first-untranslated\"").unwrap();
+        assert_eq!(config.start_at, StartAt::FirstUntranslated);
+        assert!(Config::parse("start_at = \"middle\"").is_err());
+    }
+
+    #[test]
+    fn test_glossary_path() {
+        assert_eq!(Config::default().glossary_path(Some(Path::new("po/ru.po"))), None);
+        let config = Config::parse("glossary = \"terms.tsv\"").unwrap();
+        assert_eq!(config.glossary_path(Some(Path::new("po/ru.po"))), Some(PathBuf::from("po/terms.tsv")));
+        assert_eq!(config.glossary_path(None), Some(PathBuf::from("terms.tsv")));
+        let config = Config::parse("glossary = \"/srv/terms.tsv\"").unwrap();
+        assert_eq!(config.glossary_path(Some(Path::new("po/ru.po"))), Some(PathBuf::from("/srv/terms.tsv")));
+    }
+
+    #[test]
+    fn test_parse_snippets() {
+        let config = Config::parse("[snippets]\n\";tm\" = \"™\"\n\";sig\" = \"--\\nXYZ\"\n").unwrap();
+        assert_eq!(config.snippets[";tm"], "™");
+        assert_eq!(config.snippets[";sig"], "--\nXYZ");
+    }
+
+    #[test]
+    fn test_parse_typography_keys() {
+        assert_eq!(Config::default().typography.bindings().unwrap().len(), 4);
+        let config = Config::parse("[typography]\nem_dash = \"alt+m\"\nquotes = \"\"\n").unwrap();
+        let bindings = config.typography.bindings().unwrap();
+        assert!(bindings.contains(&((KeyModifiers::ALT, KeyCode::Char('m')), Action::InsertChar('—'))));
+        assert!(!bindings.iter().any(|(_, action)| *action == Action::CycleQuotes));
+        assert!(Config::parse("[typography]\nnbsp = \"alt+nothing\"\n").is_err());
+    }
+}
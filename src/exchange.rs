@@ -0,0 +1,182 @@
+// Poterm - Modern TUI editor for .po translation files
+// Copyright (c) 2025 AnmiTaliDev <anmitali198@gmail.com>
+// Licensed under the Apache License, Version 2.0
+
+//! Translations sent out to tools other than poterm and read back from
+//! them. The format of a file is told by its extension.
+
+pub mod csv;
+pub mod json;
+pub mod xliff;
+
+use crate::gettext::{EntryKey, PoFile};
+use json::{JsonOptions, JsonStyle};
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+
+/// File formats entries can be exported to and translations imported from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Csv,
+    Xliff,
+    Json,
+}
+
+impl Format {
+    /// The format of `path`, by its extension
+    pub fn of(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "csv" => Some(Format::Csv),
+            "xlf" | "xliff" => Some(Format::Xliff),
+            "json" => Some(Format::Json),
+            _ => None,
+        }
+    }
+
+    fn of_path(path: &Path) -> Result<Self> {
+        Self::of(path).with_context(|| format!("Can't tell the format of {}, use a .csv, .xlf or .json file", path.display()))
+    }
+}
+
+/// A translation read back, for the entry with `key`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Translation {
+    pub key: EntryKey,
+    /// msgstr, or msgstr[0], msgstr[1], ... of plural entries
+    pub forms: Vec<String>,
+    /// Whether to mark the entry fuzzy, None to leave it as it is
+    pub fuzzy: Option<bool>,
+}
+
+/// What an import did to the catalog
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ImportSummary {
+    /// Translations put into their entries
+    pub matched: usize,
+    /// Translations left empty, which change nothing
+    pub skipped: usize,
+    /// Translations of entries the catalog does not have
+    pub unknown: usize,
+}
+
+impl fmt::Display for ImportSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} matched, {} skipped, {} unknown", self.matched, self.skipped, self.unknown)
+    }
+}
+
+/// Put `translations` into the entries with their msgctxt and msgid. An
+/// empty translation is skipped rather than clearing the entry.
+pub fn apply(po_file: &mut PoFile, translations: Vec<Translation>) -> ImportSummary {
+    let mut summary = ImportSummary::default();
+    let mut by_key: HashMap<EntryKey, usize> = HashMap::new();
+    for (index, entry) in po_file.entries.iter().enumerate() {
+        by_key.entry(entry.key()).or_insert(index);
+    }
+    let mut changed = false;
+    for translation in translations {
+        let Some(&index) = by_key.get(&translation.key) else {
+            summary.unknown += 1;
+            continue;
+        };
+        if translation.forms.iter().all(String::is_empty) {
+            summary.skipped += 1;
+            continue;
+        }
+        let entry = &mut po_file.entries[index];
+        let before = entry.clone();
+        let form_count = entry.form_count();
+        for (form, text) in translation.forms.into_iter().enumerate().take(form_count) {
+            entry.set_form(form, text);
+        }
+        if translation.fuzzy.is_some_and(|fuzzy| fuzzy != entry.is_fuzzy) {
+            entry.toggle_fuzzy();
+        }
+        changed |= *entry != before;
+        summary.matched += 1;
+    }
+    if changed {
+        po_file.mark_modified();
+    }
+    summary
+}
+
+/// Write the entries of the catalog to `path` in the format its extension
+/// names, JSON as a flat map. Returns how many were written.
+pub fn export(po_file: &PoFile, path: &Path, options: &csv::CsvOptions) -> Result<usize> {
+    match Format::of_path(path)? {
+        Format::Csv => po_file.export_csv(path, options),
+        Format::Xliff => po_file.export_xliff(path),
+        Format::Json => po_file.export_json(path, JsonStyle::Flat, &JsonOptions::default()),
+    }
+}
+
+/// Read the translations in `path` into the catalog
+pub fn import(po_file: &mut PoFile, path: &Path) -> Result<ImportSummary> {
+    match Format::of_path(path)? {
+        Format::Csv => po_file.import_csv(path),
+        Format::Xliff => po_file.import_xliff(path),
+        Format::Json => po_file.import_json(path, JsonStyle::Flat, None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn translation(msgctxt: Option<&str>, msgid: &str, forms: &[&str], fuzzy: Option<bool>) -> Translation {
+        Translation {
+            key: EntryKey {
+                msgctxt: msgctxt.map(str::to_string),
+                msgid: msgid.to_string(),
+            },
+            forms: forms.iter().map(|form| form.to_string()).collect(),
+            fuzzy,
+        }
+    }
+
+    #[test]
+    fn test_apply() {
+        let mut po_file = PoFile::parse(concat!(
+            "msgid \"Open\"\nmsgstr \"\"\n\n",
+            "msgctxt \"menu\"\nmsgid \"Open\"\nmsgstr \"\"\n\n",
+            "#, fuzzy\nmsgid \"Quit\"\nmsgstr \"Выйти\"\n\n",
+            "msgid \"%d file\"\nmsgid_plural \"%d files\"\nmsgstr[0] \"\"\nmsgstr[1] \"\"\n",
+        ))
+        .unwrap();
+        let summary = apply(
+            &mut po_file,
+            vec![
+                translation(Some("menu"), "Open", &["Открыть…"], None),
+                translation(None, "Quit", &["Выход"], Some(false)),
+                translation(None, "%d file", &["%d файл", "%d файла", "%d файлов"], Some(true)),
+                translation(None, "Open", &[""], Some(false)),
+                translation(None, "Close", &["Закрыть"], None),
+            ],
+        );
+        assert_eq!(summary, ImportSummary { matched: 3, skipped: 1, unknown: 1 });
+        assert_eq!(summary.to_string(), "3 matched, 1 skipped, 1 unknown");
+        assert!(po_file.is_modified());
+        assert_eq!(po_file.entries[0].msgstr, "");
+        assert_eq!(po_file.entries[1].msgstr, "Открыть…");
+        assert!(po_file.entries[2].is_translated);
+        assert_eq!(po_file.entries[2].msgstr, "Выход");
+        // Forms beyond the entry's are dropped
+        assert_eq!(po_file.entries[3].msgstr_plural, ["%d файл", "%d файла"]);
+        assert!(po_file.entries[3].is_fuzzy);
+    }
+
+    #[test]
+    fn test_format_of() {
+        assert_eq!(Format::of(Path::new("po/ru.CSV")), Some(Format::Csv));
+        assert_eq!(Format::of(Path::new("ru.xliff")), Some(Format::Xliff));
+        assert_eq!(Format::of(Path::new("de.json")), Some(Format::Json));
+        assert_eq!(Format::of(Path::new("ru.po")), None);
+        let mut po_file = PoFile::default();
+        let error = import(&mut po_file, Path::new("ru.txt")).unwrap_err();
+        assert_eq!(error.to_string(), "Can't tell the format of ru.txt, use a .csv, .xlf or .json file");
+    }
+}
@@ -0,0 +1,260 @@
+// Poterm - Modern TUI editor for .po translation files
+// Copyright (c) 2025 AnmiTaliDev <anmitali198@gmail.com>
+// Licensed under the Apache License, Version 2.0
+
+//! Entries as CSV, for review in a spreadsheet
+//!
+//! Fields are quoted as RFC 4180 has it: in double quotes when they hold the
+//! delimiter, a quote or a line break, with quotes doubled. Reading back
+//! goes by the names in the first row, so columns may be moved or dropped.
+
+use super::Translation;
+use crate::config::Config;
+use crate::gettext::{EntryKey, PoEntry, PoFile};
+use ::csv::{ReaderBuilder, StringRecord, Terminator, WriterBuilder};
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::Path;
+
+/// Lets spreadsheets tell the file is UTF-8
+const BOM: char = '\u{feff}';
+/// Delimiters told apart when reading, in the order they are tried
+const DELIMITERS: [u8; 3] = [b',', b';', b'\t'];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CsvOptions {
+    /// `,`, or `;` for spreadsheets that expect it. Only ASCII ones.
+    pub delimiter: char,
+    /// Only untranslated and fuzzy entries
+    pub pending_only: bool,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: ',',
+            pending_only: false,
+        }
+    }
+}
+
+/// The entries as CSV: msgctxt, msgid, msgid_plural, the translation
+/// (`msgstr[0]`, `msgstr[1]`, ... once there are plural entries), whether
+/// it is fuzzy and the translator comments. Returns the text and how many
+/// entries it has.
+pub fn to_csv(po_file: &PoFile, options: &CsvOptions) -> (String, usize) {
+    let entries: Vec<&PoEntry> = po_file
+        .entries
+        .iter()
+        .filter(|entry| !options.pending_only || !entry.is_translated)
+        .collect();
+    let forms = entries.iter().map(|entry| entry.form_count()).max().unwrap_or(1);
+
+    let mut header = vec!["msgctxt".to_string(), "msgid".to_string(), "msgid_plural".to_string()];
+    if forms > 1 {
+        header.extend((0..forms).map(|form| format!("msgstr[{}]", form)));
+    } else {
+        header.push("msgstr".to_string());
+    }
+    header.extend(["fuzzy".to_string(), "comments".to_string()]);
+
+    let mut writer = WriterBuilder::new()
+        .delimiter(options.delimiter as u8)
+        .terminator(Terminator::CRLF)
+        .from_writer(BOM.to_string().into_bytes());
+    // Records of the same length going to memory cannot fail
+    writer.write_record(&header).expect("records fit the header");
+    for entry in &entries {
+        let mut record = vec![
+            entry.msgctxt.as_deref().unwrap_or_default(),
+            entry.msgid.as_str(),
+            entry.msgid_plural.as_deref().unwrap_or_default(),
+        ];
+        record.extend((0..forms).map(|form| if form < entry.form_count() { entry.form(form) } else { "" }));
+        record.push(if entry.is_fuzzy { "yes" } else { "" });
+        let comments = entry.comments.join("\n");
+        record.push(&comments);
+        writer.write_record(&record).expect("records fit the header");
+    }
+    let out = writer.into_inner().expect("written to memory");
+    (String::from_utf8(out).expect("fields and ASCII delimiters are UTF-8"), entries.len())
+}
+
+/// Write the entries to `path` as CSV. Returns how many were written.
+pub fn write_csv(po_file: &PoFile, path: &Path, options: &CsvOptions) -> Result<usize> {
+    let (text, count) = to_csv(po_file, options);
+    fs::write(path, text).with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(count)
+}
+
+/// Reader of the records in `text`; blank lines are skipped
+fn reader(text: &str, delimiter: u8) -> ::csv::Reader<&[u8]> {
+    ReaderBuilder::new()
+        .delimiter(delimiter)
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(text.as_bytes())
+}
+
+/// The first of `DELIMITERS` that splits a msgid column out of the first
+/// row of `text`
+fn detect_delimiter(text: &str) -> u8 {
+    let has_msgid = |delimiter| {
+        let header = reader(text, delimiter).into_records().next().and_then(Result::ok);
+        header.is_some_and(|header| header.iter().any(|field| field.trim() == "msgid"))
+    };
+    DELIMITERS.into_iter().find(|&delimiter| has_msgid(delimiter)).unwrap_or(DELIMITERS[0])
+}
+
+/// `fuzzy` column values that mean the entry is not
+fn is_no(value: &str) -> bool {
+    matches!(value.trim().to_lowercase().as_str(), "" | "no" | "0" | "false")
+}
+
+/// The translations in CSV `text`, one per record after the header row.
+/// The delimiter is the first of `,`, `;` and tab in that row.
+pub fn parse(text: &str) -> Result<Vec<Translation>> {
+    let text = text.strip_prefix(BOM).unwrap_or(text);
+    let mut records = reader(text, detect_delimiter(text)).into_records();
+    let header = records.next().context("The file is empty")??;
+    let column = |name: &str| header.iter().position(|field| field.trim() == name);
+    let msgid = column("msgid").context("No msgid column in the first row")?;
+    let (msgctxt, fuzzy) = (column("msgctxt"), column("fuzzy"));
+    let mut forms: Vec<usize> = column("msgstr").or_else(|| column("msgstr[0]")).into_iter().collect();
+    while let Some(index) = column(&format!("msgstr[{}]", forms.len())).filter(|_| !forms.is_empty()) {
+        forms.push(index);
+    }
+    if forms.is_empty() {
+        anyhow::bail!("No msgstr column in the first row");
+    }
+
+    records
+        .map(|record| {
+            let record: StringRecord = record?;
+            let cell = |index: usize| record.get(index).unwrap_or_default();
+            Ok(Translation {
+                key: EntryKey {
+                    msgctxt: msgctxt.map(cell).filter(|msgctxt| !msgctxt.is_empty()).map(str::to_string),
+                    msgid: cell(msgid).to_string(),
+                },
+                forms: forms.iter().map(|&index| cell(index).to_string()).collect(),
+                fuzzy: fuzzy.map(|index| !is_no(cell(index))),
+            })
+        })
+        .collect()
+}
+
+/// The translations in the CSV file at `path`
+pub fn read_csv(path: &Path) -> Result<Vec<Translation>> {
+    let text = fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    parse(&text).with_context(|| format!("Failed to read {}", path.display()))
+}
+
+/// Headless `poterm export-csv`: write the entries of `po_path` to
+/// `output`, or to the `.csv` next to it
+pub fn run_export(po_path: &Path, output: Option<&Path>, options: &CsvOptions) -> Result<()> {
+    let po_file = PoFile::from_file(po_path).context("Failed to load .po file")?;
+    let output = output.map_or_else(|| po_path.with_extension("csv"), Path::to_path_buf);
+    let count = po_file.export_csv(&output, options)?;
+    println!("{}: {} entries", output.display(), count);
+    Ok(())
+}
+
+/// Headless `poterm import-csv`: read the translations in `input` into the
+/// catalog at `po_path`, save it and print what matched
+pub fn run_import(po_path: &Path, input: &Path, config: &Config) -> Result<()> {
+    let mut po_file = PoFile::from_file(po_path).context("Failed to load .po file")?;
+    po_file.no_wrap = !config.wrap;
+    po_file.backup = config.backup;
+    let summary = po_file.import_csv(input)?;
+    if po_file.is_modified() {
+        po_file.save()?;
+    }
+    println!("{}: {}", po_path.display(), summary);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exchange::{apply, ImportSummary};
+
+    const CATALOG: &str = concat!(
+        "msgid \"\"\nmsgstr \"\"\n\"Language: ru\\n\"\n",
+        "\"Plural-Forms: nplurals=3; plural=(n%10==1 && n%100!=11 ? 0 : n%10>=2 && n%10<=4 && (n%100<10 || n%100>=20) ? 1 : 2);\\n\"\n\n",
+        "# Keep it short, \"please\"\nmsgctxt \"menu\"\nmsgid \"Open\"\nmsgstr \"Открыть\"\n\n",
+        "#, fuzzy\nmsgid \"Line one\\nline two, \\\"quoted\\\"\"\nmsgstr \"Строка один\\nстрока два, «в кавычках»\"\n\n",
+        "msgid \"%d file\"\nmsgid_plural \"%d files\"\nmsgstr[0] \"%d файл\"\nmsgstr[1] \"%d файла\"\nmsgstr[2] \"\"\n\n",
+        "msgid \"日本語\"\nmsgstr \"\"\n",
+    );
+
+    #[test]
+    fn test_export() {
+        let po_file = PoFile::parse(CATALOG).unwrap();
+        let (text, count) = to_csv(&po_file, &CsvOptions::default());
+        assert_eq!(count, 4);
+        let expected = concat!(
+            "\u{feff}msgctxt,msgid,msgid_plural,msgstr[0],msgstr[1],msgstr[2],fuzzy,comments\r\n",
+            "menu,Open,,Открыть,,,,\"Keep it short, \"\"please\"\"\"\r\n",
+            ",\"Line one\nline two, \"\"quoted\"\"\",,\"Строка один\nстрока два, «в кавычках»\",,,yes,\r\n",
+            ",%d file,%d files,%d файл,%d файла,,,\r\n",
+            ",日本語,,,,,,\r\n",
+        );
+        assert_eq!(text, expected);
+
+        let options = CsvOptions { delimiter: ';', pending_only: true };
+        let (text, count) = to_csv(&po_file, &options);
+        assert_eq!(count, 3);
+        assert!(text.starts_with("\u{feff}msgctxt;msgid;msgid_plural;msgstr[0];msgstr[1];msgstr[2];fuzzy;comments\r\n"));
+        assert!(text.contains(";\"Строка один\nстрока два, «в кавычках»\";"));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let po_file = PoFile::parse(CATALOG).unwrap();
+        let (text, _) = to_csv(&po_file, &CsvOptions { delimiter: ';', pending_only: false });
+        let translations = parse(&text).unwrap();
+        assert_eq!(translations.len(), 4);
+        assert_eq!(translations[0].key.msgctxt.as_deref(), Some("menu"));
+        assert_eq!(translations[1].key.msgid, "Line one\nline two, \"quoted\"");
+        assert_eq!(translations[1].forms, ["Строка один\nстрока два, «в кавычках»", "", ""]);
+        assert_eq!(translations[1].fuzzy, Some(true));
+        assert_eq!(translations[3].key.msgid, "日本語");
+
+        let mut copy = po_file.clone();
+        assert_eq!(apply(&mut copy, translations), ImportSummary { matched: 3, skipped: 1, unknown: 0 });
+        assert_eq!(copy.entries, po_file.entries);
+        assert!(!copy.is_modified());
+    }
+
+    #[test]
+    fn test_import_reviewed() {
+        let mut po_file = PoFile::parse(CATALOG).unwrap();
+        // As a spreadsheet saves it: columns dropped and moved, no BOM, LF
+        let text = concat!(
+            "fuzzy,msgid,msgctxt,msgstr[0],msgstr[1],msgstr[2]\n",
+            "no,\"Line one\nline two, \"\"quoted\"\"\",,\"Строка один\r\nстрока два\",,\n",
+            ",%d file,,%d файл,%d файла,%d файлов\n",
+            "\n",
+            ",日本語,,日本語,,\n",
+            ",Open,,Открыть,,\n",
+            ",Close,,Закрыть,,\n",
+        );
+        let summary = apply(&mut po_file, parse(text).unwrap());
+        assert_eq!(summary, ImportSummary { matched: 3, skipped: 0, unknown: 2 });
+        assert_eq!(po_file.entries[1].msgstr, "Строка один\r\nстрока два");
+        assert!(!po_file.entries[1].is_fuzzy);
+        assert!(po_file.entries[2].is_translated);
+        assert_eq!(po_file.entries[3].msgstr, "日本語");
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(parse("").unwrap_err().to_string(), "The file is empty");
+        assert_eq!(parse("msgstr\nОткрыть\n").unwrap_err().to_string(), "No msgid column in the first row");
+        assert_eq!(parse("msgid\nOpen\n").unwrap_err().to_string(), "No msgstr column in the first row");
+        // A quote never closed runs to the end of the file, as in spreadsheets
+        let translations = parse("msgid,msgstr\nOpen,Открыть\n\"Quit,Выход\n").unwrap();
+        assert_eq!(translations[1].key.msgid, "Quit,Выход\n");
+    }
+}
@@ -0,0 +1,483 @@
+// Poterm - Modern TUI editor for .po translation files
+// Copyright (c) 2025 AnmiTaliDev <anmitali198@gmail.com>
+// Licensed under the Apache License, Version 2.0
+
+//! Entries as JSON, for web i18n libraries
+//!
+//! Keys are msgids; an entry with a msgctxt gets a key made from the key
+//! format, `{msgctxt}|{msgid}` unless set. Two entries that would end up
+//! with the same key are an error rather than one silently replacing the
+//! other.
+
+use super::Translation;
+use crate::config::Config;
+use crate::gettext::{PoEntry, PoFile};
+use crate::language;
+use anyhow::{Context, Result};
+use indexmap::IndexMap;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::Path;
+
+/// Layout of the JSON file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum JsonStyle {
+    /// `{"msgid": "msgstr"}`, with the forms of plural entries in an array
+    #[default]
+    Flat,
+    /// `{"msgctxt": {"msgid": "msgstr"}}`, entries without one under `""`
+    Nested,
+    /// i18next: one key per plural form, suffixed `_one`, `_few`, `_other`, ...
+    I18next,
+}
+
+impl JsonStyle {
+    /// Key format of entries with a msgctxt when none is given. i18next
+    /// looks contexts up as `key_context`.
+    fn default_key_format(self) -> &'static str {
+        match self {
+            JsonStyle::Flat | JsonStyle::Nested => "{msgctxt}|{msgid}",
+            JsonStyle::I18next => "{msgid}_{msgctxt}",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct JsonOptions {
+    pub skip_untranslated: bool,
+    pub skip_fuzzy: bool,
+    /// Key of entries with a msgctxt, from `{msgctxt}` and `{msgid}`;
+    /// None for the style's own. Not used by the nested style.
+    pub key_format: Option<String>,
+}
+
+/// Where a value of the file goes
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Slot {
+    /// Index of the entry
+    entry: usize,
+    /// The translation form of an i18next plural key, None for the whole
+    /// translation
+    form: Option<usize>,
+}
+
+/// `{msgctxt}` and `{msgid}` in `format` replaced, in one pass so the
+/// text put in is never replaced again
+fn format_key(format: &str, msgctxt: &str, msgid: &str) -> String {
+    let mut key = String::new();
+    let mut rest = format;
+    while let Some(start) = rest.find('{') {
+        key.push_str(&rest[..start]);
+        rest = &rest[start..];
+        if let Some(after) = rest.strip_prefix("{msgctxt}") {
+            key.push_str(msgctxt);
+            rest = after;
+        } else if let Some(after) = rest.strip_prefix("{msgid}") {
+            key.push_str(msgid);
+            rest = after;
+        } else {
+            key.push('{');
+            rest = &rest[1..];
+        }
+    }
+    key.push_str(rest);
+    key
+}
+
+/// i18next suffixes of the plural forms of the catalog: the CLDR plural
+/// categories of its language, or `one` and `other` for two forms
+fn plural_suffixes(po_file: &PoFile) -> Result<&'static [&'static str]> {
+    let count = po_file.plural_count().unwrap_or(2);
+    let language = po_file.language().unwrap_or_default();
+    let known = language::plural_categories(language).filter(|categories| categories.len() == count);
+    match (known, count) {
+        (Some(categories), _) => Ok(categories),
+        (None, 1) => Ok(&["other"]),
+        (None, 2) => Ok(&["one", "other"]),
+        (None, _) if language.is_empty() => anyhow::bail!("No i18next plural suffixes known for {} forms", count),
+        (None, _) => anyhow::bail!("No i18next plural suffixes known for {} forms in {}", count, language),
+    }
+}
+
+/// The key, and the object it is in for the nested style, of every value
+/// the entries make. Fails when two of them clash.
+fn slots(po_file: &PoFile, style: JsonStyle, key_format: Option<&str>) -> Result<IndexMap<(String, String), Slot>> {
+    let key_format = key_format.unwrap_or(style.default_key_format());
+    if !key_format.contains("{msgid}") {
+        anyhow::bail!("The key format must have {{msgid}} in it");
+    }
+    let suffixes = if style == JsonStyle::I18next && po_file.entries.iter().any(PoEntry::is_plural) {
+        plural_suffixes(po_file)?
+    } else {
+        &[]
+    };
+
+    let mut slots: IndexMap<(String, String), Slot> = IndexMap::new();
+    for (index, entry) in po_file.entries.iter().enumerate() {
+        let (group, key) = match (style, entry.msgctxt.as_deref()) {
+            (JsonStyle::Nested, msgctxt) => (msgctxt.unwrap_or_default().to_string(), entry.msgid.clone()),
+            (_, Some(msgctxt)) => (String::new(), format_key(key_format, msgctxt, &entry.msgid)),
+            (_, None) => (String::new(), entry.msgid.clone()),
+        };
+        let keys: Vec<(String, Option<usize>)> = if style == JsonStyle::I18next && entry.is_plural() {
+            suffixes
+                .iter()
+                .enumerate()
+                .map(|(form, suffix)| (format!("{}_{}", key, suffix), Some(form)))
+                .collect()
+        } else {
+            vec![(key, None)]
+        };
+        for (key, form) in keys {
+            if let Some(other) = slots.get(&(group.clone(), key.clone())) {
+                anyhow::bail!(
+                    "Entries {} and {} both get the key \"{}\", use a key format with {{msgctxt}} in it",
+                    other.entry + 1,
+                    index + 1,
+                    key
+                );
+            }
+            slots.insert((group.clone(), key), Slot { entry: index, form });
+        }
+    }
+    Ok(slots)
+}
+
+fn is_exported(entry: &PoEntry, options: &JsonOptions) -> bool {
+    if entry.is_fuzzy {
+        return !options.skip_fuzzy;
+    }
+    entry.is_translated || !options.skip_untranslated
+}
+
+/// `text` as a JSON string
+fn quoted(text: &str) -> String {
+    serde_json::to_string(text).expect("strings always serialize")
+}
+
+/// The entries as JSON in `style`, keys in catalog order. Returns the text
+/// and how many entries it has.
+pub fn to_json(po_file: &PoFile, style: JsonStyle, options: &JsonOptions) -> Result<(String, usize)> {
+    let mut groups: IndexMap<&str, Vec<(&str, String)>> = IndexMap::new();
+    let mut count = 0;
+    let slots = slots(po_file, style, options.key_format.as_deref())?;
+    for ((group, key), slot) in &slots {
+        let entry = &po_file.entries[slot.entry];
+        if !is_exported(entry, options) {
+            continue;
+        }
+        let value = match slot.form {
+            Some(form) => quoted(entry.form(form)),
+            None if entry.is_plural() => {
+                let forms: Vec<String> = (0..entry.form_count()).map(|form| quoted(entry.form(form))).collect();
+                format!("[{}]", forms.join(", "))
+            }
+            None => quoted(&entry.msgstr),
+        };
+        if slot.form.unwrap_or_default() == 0 {
+            count += 1;
+        }
+        groups.entry(group).or_default().push((key, value));
+    }
+
+    let object = |members: &[(&str, String)], indent: &str| {
+        let lines: Vec<String> = members
+            .iter()
+            .map(|(key, value)| format!("{}  {}: {}", indent, quoted(key), value))
+            .collect();
+        if lines.is_empty() {
+            "{}".to_string()
+        } else {
+            format!("{{\n{}\n{}}}", lines.join(",\n"), indent)
+        }
+    };
+    let text = if style == JsonStyle::Nested {
+        let members: Vec<(&str, String)> = groups
+            .iter()
+            .map(|(group, members)| (*group, object(members, "  ")))
+            .collect();
+        object(&members, "")
+    } else {
+        object(groups.get("").map(Vec::as_slice).unwrap_or_default(), "")
+    };
+    Ok((text + "\n", count))
+}
+
+/// Write the entries to `path` as JSON. Returns how many were written.
+pub fn write_json(po_file: &PoFile, path: &Path, style: JsonStyle, options: &JsonOptions) -> Result<usize> {
+    let (text, count) = to_json(po_file, style, options)?;
+    fs::write(path, text).with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(count)
+}
+
+/// The forms in `value`: a string, an array of them, or null for none
+fn forms(key: &str, value: &Value) -> Result<Vec<String>> {
+    let text = |value: &Value| match value {
+        Value::String(text) => Ok(text.clone()),
+        Value::Null => Ok(String::new()),
+        _ => anyhow::bail!("The value of \"{}\" is not a string", key),
+    };
+    match value {
+        Value::Array(values) => values.iter().map(text).collect(),
+        _ => Ok(vec![text(value)?]),
+    }
+}
+
+/// The translations in JSON `text`, for the entries of `po_file` whose
+/// keys it has, and how many of its keys no entry has
+pub fn parse(
+    text: &str,
+    po_file: &PoFile,
+    style: JsonStyle,
+    key_format: Option<&str>,
+) -> Result<(Vec<Translation>, usize)> {
+    let root: Value = serde_json::from_str(text)?;
+    let Value::Object(root) = root else {
+        anyhow::bail!("The file is not a JSON object");
+    };
+    let mut values = Vec::new();
+    for (key, value) in &root {
+        match (style, value) {
+            (JsonStyle::Nested, Value::Object(members)) => {
+                values.extend(members.iter().map(|(member, value)| (key.as_str(), member.as_str(), value)))
+            }
+            (JsonStyle::Nested, _) => anyhow::bail!("The value of \"{}\" is not an object of translations", key),
+            _ => values.push(("", key.as_str(), value)),
+        }
+    }
+
+    let slots = slots(po_file, style, key_format)?;
+    let by_key: HashMap<(&str, &str), &Slot> = slots
+        .iter()
+        .map(|((group, key), slot)| ((group.as_str(), key.as_str()), slot))
+        .collect();
+    let mut found: BTreeMap<usize, Vec<String>> = BTreeMap::new();
+    let mut unknown = 0;
+    for (group, key, value) in values {
+        let Some(slot) = by_key.get(&(group, key)) else {
+            unknown += 1;
+            continue;
+        };
+        let forms = forms(key, value)?;
+        match slot.form {
+            Some(form) => {
+                // Forms without a key keep their translation
+                let entry = &po_file.entries[slot.entry];
+                let all = found
+                    .entry(slot.entry)
+                    .or_insert_with(|| (0..entry.form_count()).map(|form| entry.form(form).to_string()).collect());
+                if let (Some(old), Some(new)) = (all.get_mut(form), forms.into_iter().next()) {
+                    *old = new;
+                }
+            }
+            None => {
+                found.insert(slot.entry, forms);
+            }
+        }
+    }
+
+    let translations = found
+        .into_iter()
+        .map(|(index, forms)| Translation {
+            key: po_file.entries[index].key(),
+            forms,
+            fuzzy: None,
+        })
+        .collect();
+    Ok((translations, unknown))
+}
+
+/// The translations in the JSON file at `path`, for the entries of
+/// `po_file`, and how many of its keys no entry has
+pub fn read_json(
+    path: &Path,
+    po_file: &PoFile,
+    style: JsonStyle,
+    key_format: Option<&str>,
+) -> Result<(Vec<Translation>, usize)> {
+    let text = fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    parse(&text, po_file, style, key_format).with_context(|| format!("Failed to read {}", path.display()))
+}
+
+/// Headless `poterm export-json`: write the entries of `po_path` to
+/// `output`, or to the `.json` next to it
+pub fn run_export(po_path: &Path, output: Option<&Path>, style: JsonStyle, options: &JsonOptions) -> Result<()> {
+    let po_file = PoFile::from_file(po_path).context("Failed to load .po file")?;
+    let output = output.map_or_else(|| po_path.with_extension("json"), Path::to_path_buf);
+    let count = po_file.export_json(&output, style, options)?;
+    println!("{}: {} entries", output.display(), count);
+    Ok(())
+}
+
+/// Headless `poterm import-json`: read the translations in `input` into the
+/// catalog at `po_path`, save it and print what matched
+pub fn run_import(
+    po_path: &Path,
+    input: &Path,
+    style: JsonStyle,
+    key_format: Option<&str>,
+    config: &Config,
+) -> Result<()> {
+    let mut po_file = PoFile::from_file(po_path).context("Failed to load .po file")?;
+    po_file.no_wrap = !config.wrap;
+    po_file.backup = config.backup;
+    let summary = po_file.import_json(input, style, key_format)?;
+    if po_file.is_modified() {
+        po_file.save()?;
+    }
+    println!("{}: {}", po_path.display(), summary);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exchange::{apply, ImportSummary};
+
+    const CATALOG: &str = concat!(
+        "msgid \"\"\nmsgstr \"\"\n\"Language: ru\\n\"\n",
+        "\"Plural-Forms: nplurals=3; plural=(n%10==1 && n%100!=11 ? 0 : n%10>=2 && n%10<=4 && (n%100<10 || n%100>=20) ? 1 : 2);\\n\"\n\n",
+        "msgid \"Open\"\nmsgstr \"Открыть\"\n\n",
+        "msgctxt \"menu\"\nmsgid \"Open\"\nmsgstr \"Открыть…\"\n\n",
+        "#, fuzzy\nmsgid \"Say \\\"hi\\\"\"\nmsgstr \"Скажи «привет»\"\n\n",
+        "msgid \"%d file\"\nmsgid_plural \"%d files\"\nmsgstr[0] \"%d файл\"\nmsgstr[1] \"%d файла\"\nmsgstr[2] \"%d файлов\"\n\n",
+        "msgid \"Quit\"\nmsgstr \"\"\n",
+    );
+
+    fn export(style: JsonStyle, options: &JsonOptions) -> (String, usize) {
+        to_json(&PoFile::parse(CATALOG).unwrap(), style, options).unwrap()
+    }
+
+    #[test]
+    fn test_export_flat() {
+        let (text, count) = export(JsonStyle::Flat, &JsonOptions::default());
+        assert_eq!(count, 5);
+        let expected = concat!(
+            "{\n",
+            "  \"Open\": \"Открыть\",\n",
+            "  \"menu|Open\": \"Открыть…\",\n",
+            "  \"Say \\\"hi\\\"\": \"Скажи «привет»\",\n",
+            "  \"%d file\": [\"%d файл\", \"%d файла\", \"%d файлов\"],\n",
+            "  \"Quit\": \"\"\n",
+            "}\n",
+        );
+        assert_eq!(text, expected);
+
+        let options = JsonOptions {
+            skip_untranslated: true,
+            skip_fuzzy: true,
+            key_format: Some("{msgid} ({msgctxt})".to_string()),
+        };
+        let (text, count) = export(JsonStyle::Flat, &options);
+        assert_eq!(count, 3);
+        assert!(text.contains("\"Open (menu)\": \"Открыть…\""));
+        assert!(!text.contains("Quit") && !text.contains("hi"));
+    }
+
+    #[test]
+    fn test_export_nested_and_i18next() {
+        let options = JsonOptions { skip_untranslated: true, ..JsonOptions::default() };
+        let (text, _) = export(JsonStyle::Nested, &options);
+        let expected = concat!(
+            "{\n",
+            "  \"\": {\n",
+            "    \"Open\": \"Открыть\",\n",
+            "    \"Say \\\"hi\\\"\": \"Скажи «привет»\",\n",
+            "    \"%d file\": [\"%d файл\", \"%d файла\", \"%d файлов\"]\n",
+            "  },\n",
+            "  \"menu\": {\n",
+            "    \"Open\": \"Открыть…\"\n",
+            "  }\n",
+            "}\n",
+        );
+        assert_eq!(text, expected);
+
+        let (text, count) = export(JsonStyle::I18next, &options);
+        assert_eq!(count, 4);
+        let expected = concat!(
+            "{\n",
+            "  \"Open\": \"Открыть\",\n",
+            "  \"Open_menu\": \"Открыть…\",\n",
+            "  \"Say \\\"hi\\\"\": \"Скажи «привет»\",\n",
+            "  \"%d file_one\": \"%d файл\",\n",
+            "  \"%d file_few\": \"%d файла\",\n",
+            "  \"%d file_many\": \"%d файлов\"\n",
+            "}\n",
+        );
+        assert_eq!(text, expected);
+    }
+
+    #[test]
+    fn test_key_clashes() {
+        let po_file = PoFile::parse(CATALOG).unwrap();
+        let options = JsonOptions { key_format: Some("{msgid}".to_string()), ..JsonOptions::default() };
+        let error = to_json(&po_file, JsonStyle::Flat, &options).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Entries 1 and 2 both get the key \"Open\", use a key format with {msgctxt} in it"
+        );
+        let options = JsonOptions { key_format: Some("{msgctxt}".to_string()), ..JsonOptions::default() };
+        let error = to_json(&po_file, JsonStyle::Flat, &options).unwrap_err();
+        assert_eq!(error.to_string(), "The key format must have {msgid} in it");
+        // Filled in once: a msgctxt holding `{msgid}` stays as it is
+        assert_eq!(format_key("{msgctxt}.{msgid}", "{msgid}", "Open"), "{msgid}.Open");
+
+        let welsh = PoFile::parse(concat!(
+            "msgid \"\"\nmsgstr \"\"\n\"Language: cy\\n\"\n",
+            "\"Plural-Forms: nplurals=4; plural=(n==1 ? 0 : n==2 ? 1 : n != 8 && n != 11 ? 2 : 3);\\n\"\n\n",
+            "msgid \"%d file\"\nmsgid_plural \"%d files\"\nmsgstr[0] \"\"\nmsgstr[1] \"\"\nmsgstr[2] \"\"\nmsgstr[3] \"\"\n",
+        ))
+        .unwrap();
+        let error = to_json(&welsh, JsonStyle::I18next, &JsonOptions::default()).unwrap_err();
+        assert_eq!(error.to_string(), "No i18next plural suffixes known for 4 forms in cy");
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let po_file = PoFile::parse(CATALOG).unwrap();
+        for style in [JsonStyle::Flat, JsonStyle::Nested, JsonStyle::I18next] {
+            let (text, _) = to_json(&po_file, style, &JsonOptions::default()).unwrap();
+            let (translations, unknown) = parse(&text, &po_file, style, None).unwrap();
+            assert_eq!((translations.len(), unknown), (5, 0), "{:?}", style);
+            let mut copy = po_file.clone();
+            let summary = apply(&mut copy, translations);
+            assert_eq!(summary, ImportSummary { matched: 4, skipped: 1, unknown: 0 });
+            assert_eq!(copy.entries, po_file.entries);
+            assert!(!copy.is_modified());
+        }
+    }
+
+    #[test]
+    fn test_import() {
+        let mut po_file = PoFile::parse(CATALOG).unwrap();
+        let text = r#"{
+            "Open_menu": "Открыть файл…",
+            "Quit": "Выйти",
+            "%d file_many": "%d файлов!",
+            "Close": "Закрыть"
+        }"#;
+        let (translations, unknown) = parse(text, &po_file, JsonStyle::I18next, None).unwrap();
+        assert_eq!(unknown, 1);
+        assert_eq!(apply(&mut po_file, translations), ImportSummary { matched: 3, skipped: 0, unknown: 0 });
+        assert_eq!(po_file.entries[1].msgstr, "Открыть файл…");
+        // The forms left out keep their translation
+        assert_eq!(po_file.entries[3].msgstr_plural, ["%d файл", "%d файла", "%d файлов!"]);
+        assert!(po_file.entries[4].is_translated);
+
+        let text = r#"{"": {"Quit": null}, "menu": {"Open": ["Открыть"]}, "tooltip": {"Open": "Открыть"}}"#;
+        let (translations, unknown) = parse(text, &po_file, JsonStyle::Nested, None).unwrap();
+        assert_eq!(unknown, 1);
+        assert_eq!(translations[0].forms, ["Открыть"]);
+        assert_eq!(translations[1].forms, [""]);
+
+        let error = |text: &str, style| parse(text, &po_file, style, None).unwrap_err().to_string();
+        assert_eq!(error("[]", JsonStyle::Flat), "The file is not a JSON object");
+        assert_eq!(error(r#"{"Open": 1}"#, JsonStyle::Flat), "The value of \"Open\" is not a string");
+        assert_eq!(
+            error(r#"{"Open": "Открыть"}"#, JsonStyle::Nested),
+            "The value of \"Open\" is not an object of translations"
+        );
+        assert_eq!(error("{\"Open\": ", JsonStyle::Flat), "EOF while parsing a value at line 1 column 9");
+    }
+}
@@ -0,0 +1,487 @@
+// Poterm - Modern TUI editor for .po translation files
+// Copyright (c) 2025 AnmiTaliDev <anmitali198@gmail.com>
+// Licensed under the Apache License, Version 2.0
+
+//! Entries as XLIFF 1.2, the format translation agencies work in
+//!
+//! Every entry is a `<trans-unit>` numbered in catalog order, with its
+//! msgctxt as `resname`. A plural entry is a `<group>` holding one unit per
+//! form, `3[0]`, `3[1]`, ..., as Translate Toolkit writes them.
+
+use super::Translation;
+use crate::config::Config;
+use crate::gettext::{EntryKey, PoEntry, PoFile};
+use anyhow::{Context, Result};
+use roxmltree::{Document, Node};
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+const NAMESPACE: &str = "urn:oasis:names:tc:xliff:document:1.2";
+/// `restype` of the group around the forms of a plural entry
+const PLURALS: &str = "x-gettext-plurals";
+/// Target `state` of fuzzy entries
+const NEEDS_REVIEW: &str = "needs-review-translation";
+
+/// `text` escaped for element content, or with `attribute` for a quoted
+/// attribute value. Fails on characters XML 1.0 can't hold at all.
+fn escape(text: &str, attribute: bool) -> Result<String> {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            // Line ends are normalized away unless written as references
+            '\r' => out.push_str("&#13;"),
+            '"' if attribute => out.push_str("&quot;"),
+            '\n' if attribute => out.push_str("&#10;"),
+            '\t' if attribute => out.push_str("&#9;"),
+            '\t' | '\n' => out.push(c),
+            '\u{0}'..='\u{1f}' | '\u{fffe}' | '\u{ffff}' => {
+                anyhow::bail!("U+{:04X} can't be written in XML", c as u32)
+            }
+            _ => out.push(c),
+        }
+    }
+    Ok(out)
+}
+
+/// `<note>`s of the translator and extracted comments, indented by `indent`
+fn notes(entry: &PoEntry, indent: &str) -> Result<String> {
+    let mut out = String::new();
+    for (from, comments) in [("translator", &entry.comments), ("developer", &entry.extracted_comments)] {
+        if !comments.is_empty() {
+            let text = escape(&comments.join("\n"), false)?;
+            out.push_str(&format!("{}<note from=\"{}\">{}</note>\n", indent, from, text));
+        }
+    }
+    Ok(out)
+}
+
+/// `<source>` and, once there is a translation, `<target>` of one unit
+fn segments(source: &str, target: &str, fuzzy: bool, indent: &str) -> Result<String> {
+    let mut out = format!("{}<source>{}</source>\n", indent, escape(source, false)?);
+    if !target.is_empty() {
+        let state = if fuzzy { NEEDS_REVIEW } else { "translated" };
+        out.push_str(&format!("{}<target state=\"{}\">{}</target>\n", indent, state, escape(target, false)?));
+    }
+    Ok(out)
+}
+
+fn write_entry(out: &mut String, id: usize, entry: &PoEntry) -> Result<()> {
+    let resname = match entry.msgctxt {
+        Some(ref msgctxt) => format!(" resname=\"{}\"", escape(msgctxt, true)?),
+        None => String::new(),
+    };
+    let Some(ref msgid_plural) = entry.msgid_plural else {
+        out.push_str(&format!("      <trans-unit id=\"{}\"{} xml:space=\"preserve\">\n", id, resname));
+        out.push_str(&segments(&entry.msgid, &entry.msgstr, entry.is_fuzzy, "        ")?);
+        out.push_str(&notes(entry, "        ")?);
+        out.push_str("      </trans-unit>\n");
+        return Ok(());
+    };
+    out.push_str(&format!("      <group id=\"{}\" restype=\"{}\"{}>\n", id, PLURALS, resname));
+    out.push_str(&notes(entry, "        ")?);
+    for form in 0..entry.form_count() {
+        let source = if form == 0 { &entry.msgid } else { msgid_plural };
+        out.push_str(&format!("        <trans-unit id=\"{}[{}]\" xml:space=\"preserve\">\n", id, form));
+        out.push_str(&segments(source, entry.form(form), entry.is_fuzzy, "          ")?);
+        out.push_str("        </trans-unit>\n");
+    }
+    out.push_str("      </group>\n");
+    Ok(())
+}
+
+/// The entries as an XLIFF document naming `original` as its source file.
+/// Returns the text and how many entries it has.
+pub fn to_xliff(po_file: &PoFile, original: &str) -> Result<(String, usize)> {
+    let mut out = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<xliff version=\"1.2\" xmlns=\"{}\">\n",
+        NAMESPACE
+    );
+    out.push_str(&format!("  <file original=\"{}\" datatype=\"po\" source-language=\"en\"", escape(original, true)?));
+    if let Some(language) = po_file.language().filter(|language| !language.is_empty()) {
+        // BCP 47 rather than the POSIX pt_BR
+        out.push_str(&format!(" target-language=\"{}\"", escape(&language.replace('_', "-"), true)?));
+    }
+    out.push_str(">\n    <body>\n");
+    for (index, entry) in po_file.entries.iter().enumerate() {
+        write_entry(&mut out, index + 1, entry).with_context(|| format!("Entry {} can't be exported", index + 1))?;
+    }
+    out.push_str("    </body>\n  </file>\n</xliff>\n");
+    Ok((out, po_file.entries.len()))
+}
+
+/// Write the entries to `path` as XLIFF. Returns how many were written.
+pub fn write_xliff(po_file: &PoFile, path: &Path) -> Result<usize> {
+    let original = po_file.path.as_deref().and_then(Path::file_name).unwrap_or("messages.po".as_ref());
+    let (text, count) = to_xliff(po_file, &original.to_string_lossy())?;
+    fs::write(path, text).with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(count)
+}
+
+/// A `<trans-unit>`, or a plural group, read back
+#[derive(Debug, Clone, PartialEq)]
+pub struct Unit {
+    pub id: String,
+    pub resname: Option<String>,
+    /// msgid, the source of the first form of plural units
+    pub source: String,
+    pub forms: Vec<String>,
+    pub fuzzy: Option<bool>,
+}
+
+/// Whether a target `state` makes the entry fuzzy, None when it says
+/// nothing either way
+fn fuzzy_state(state: Option<&str>) -> Option<bool> {
+    match state? {
+        state if state.starts_with("needs-review") => Some(true),
+        "translated" | "final" | "signed-off" => Some(false),
+        _ => None,
+    }
+}
+
+fn is_plural_group(node: Node) -> bool {
+    node.has_tag_name("group") && node.attribute("restype") == Some(PLURALS)
+}
+
+/// Reads units out of a parsed document, reporting problems with their
+/// line and column
+struct Reader<'a, 'input> {
+    doc: &'a Document<'input>,
+}
+
+impl<'a, 'input> Reader<'a, 'input> {
+    fn at(&self, node: Node) -> String {
+        self.doc.text_pos_at(node.range().start).to_string()
+    }
+
+    fn child(&self, node: Node<'a, 'input>, name: &str) -> Option<Node<'a, 'input>> {
+        node.children().find(|child| child.has_tag_name(name))
+    }
+
+    fn id(&self, node: Node<'a, 'input>) -> Result<&'a str> {
+        let name = node.tag_name().name();
+        node.attribute("id").with_context(|| format!("<{}> without an id at {}", name, self.at(node)))
+    }
+
+    /// Text of `node`, which can't hold markup of its own
+    fn text(&self, node: Node, id: &str) -> Result<String> {
+        let mut text = String::new();
+        for child in node.children() {
+            if child.is_element() {
+                let (name, parent) = (child.tag_name().name(), node.tag_name().name());
+                anyhow::bail!("<{}> in the <{}> of {} at {}: markup is not supported", name, parent, id, self.at(child));
+            }
+            text.push_str(child.text().unwrap_or_default());
+        }
+        Ok(text)
+    }
+
+    /// Source, target and target state of the `<trans-unit>` `id`
+    fn segments(&self, node: Node<'a, 'input>, id: &str) -> Result<(String, String, Option<bool>)> {
+        let source = self
+            .child(node, "source")
+            .with_context(|| format!("trans-unit {} has no <source> at {}", id, self.at(node)))?;
+        let source = self.text(source, id)?;
+        let Some(target) = self.child(node, "target") else {
+            return Ok((source, String::new(), None));
+        };
+        Ok((source, self.text(target, id)?, fuzzy_state(target.attribute("state"))))
+    }
+
+    fn unit(&self, node: Node<'a, 'input>) -> Result<Unit> {
+        let id = self.id(node)?;
+        if id.ends_with(']') {
+            anyhow::bail!("trans-unit {} is a plural form outside a plural group at {}", id, self.at(node));
+        }
+        let (source, target, fuzzy) = self.segments(node, id)?;
+        Ok(Unit {
+            id: id.to_string(),
+            resname: node.attribute("resname").map(str::to_string),
+            source,
+            forms: vec![target],
+            fuzzy,
+        })
+    }
+
+    /// A plural group: its units must be the forms `<id>[0]`, `<id>[1]`, ...
+    fn plural(&self, node: Node<'a, 'input>) -> Result<Unit> {
+        let id = self.id(node)?;
+        let mut forms: Vec<Option<(String, String, Option<bool>)>> = Vec::new();
+        for unit in node.children().filter(|child| child.has_tag_name("trans-unit")) {
+            let unit_id = self.id(unit)?;
+            let form = unit_id
+                .strip_prefix(id)
+                .and_then(|rest| rest.strip_prefix('['))
+                .and_then(|rest| rest.strip_suffix(']'))
+                .and_then(|form| form.parse::<usize>().ok())
+                .with_context(|| format!("trans-unit {} is not a form of plural group {} at {}", unit_id, id, self.at(unit)))?;
+            if forms.len() <= form {
+                forms.resize(form + 1, None);
+            }
+            if forms[form].is_some() {
+                anyhow::bail!("Plural group {} has form {} twice at {}", id, form, self.at(unit));
+            }
+            forms[form] = Some(self.segments(unit, unit_id)?);
+        }
+        let Some(Some((source, _, _))) = forms.first() else {
+            anyhow::bail!("Plural group {} has no form 0 at {}", id, self.at(node));
+        };
+        let states: Vec<Option<bool>> = forms.iter().flatten().map(|(_, _, fuzzy)| *fuzzy).collect();
+        let fuzzy = match states.contains(&Some(true)) {
+            true => Some(true),
+            false => states.into_iter().flatten().next(),
+        };
+        Ok(Unit {
+            id: id.to_string(),
+            resname: node.attribute("resname").map(str::to_string),
+            source: source.clone(),
+            forms: forms.into_iter().map(|form| form.map(|(_, target, _)| target).unwrap_or_default()).collect(),
+            fuzzy,
+        })
+    }
+}
+
+/// The units of XLIFF `text`, in document order
+pub fn parse(text: &str) -> Result<Vec<Unit>> {
+    let doc = Document::parse(text).map_err(|e| {
+        // Not every message of roxmltree says where
+        let (message, at) = (e.to_string(), format!(" at {}", e.pos()));
+        anyhow::anyhow!("Malformed XML: {}{}", message, if message.ends_with(&at) { "" } else { &at })
+    })?;
+    let reader = Reader { doc: &doc };
+    let root = doc.root_element();
+    if !root.has_tag_name("xliff") {
+        anyhow::bail!("Not an XLIFF file: the root element is <{}>", root.tag_name().name());
+    }
+    if let Some(version) = root.attribute("version").filter(|version| !version.starts_with("1.")) {
+        anyhow::bail!("XLIFF {} is not supported, only 1.2", version);
+    }
+    let mut units = Vec::new();
+    let mut ids = HashSet::new();
+    for node in root.descendants() {
+        let unit = if is_plural_group(node) {
+            reader.plural(node)?
+        } else if node.has_tag_name("trans-unit") {
+            // Forms are read with their group
+            if node.parent().is_some_and(is_plural_group) {
+                continue;
+            }
+            reader.unit(node)?
+        } else {
+            continue;
+        };
+        if !ids.insert(unit.id.clone()) {
+            anyhow::bail!("The id {} is used twice, again at {}", unit.id, reader.at(node));
+        }
+        units.push(unit);
+    }
+    Ok(units)
+}
+
+/// What to import for each of `units`: the entry its id numbers when that
+/// still has the unit's source, otherwise the one with its resname and
+/// source, which is also how units from other tools are matched
+pub fn translations(units: Vec<Unit>, po_file: &PoFile) -> Vec<Translation> {
+    units
+        .into_iter()
+        .map(|unit| {
+            let numbered = unit.id.parse::<usize>().ok().and_then(|id| po_file.entries.get(id.checked_sub(1)?));
+            let key = match numbered {
+                Some(entry) if entry.msgid == unit.source => entry.key(),
+                _ => EntryKey {
+                    msgctxt: unit.resname,
+                    msgid: unit.source,
+                },
+            };
+            Translation {
+                key,
+                forms: unit.forms,
+                fuzzy: unit.fuzzy,
+            }
+        })
+        .collect()
+}
+
+/// The translations in the XLIFF file at `path`, for the entries of
+/// `po_file`
+pub fn read_xliff(path: &Path, po_file: &PoFile) -> Result<Vec<Translation>> {
+    let text = fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let units = parse(&text).with_context(|| format!("Failed to read {}", path.display()))?;
+    Ok(translations(units, po_file))
+}
+
+/// Headless `poterm export-xliff`: write the entries of `po_path` to
+/// `output`, or to the `.xlf` next to it
+pub fn run_export(po_path: &Path, output: Option<&Path>) -> Result<()> {
+    let po_file = PoFile::from_file(po_path).context("Failed to load .po file")?;
+    let output = output.map_or_else(|| po_path.with_extension("xlf"), Path::to_path_buf);
+    let count = po_file.export_xliff(&output)?;
+    println!("{}: {} entries", output.display(), count);
+    Ok(())
+}
+
+/// Headless `poterm import-xliff`: read the translations in `input` into
+/// the catalog at `po_path`, save it and print what matched
+pub fn run_import(po_path: &Path, input: &Path, config: &Config) -> Result<()> {
+    let mut po_file = PoFile::from_file(po_path).context("Failed to load .po file")?;
+    po_file.no_wrap = !config.wrap;
+    po_file.backup = config.backup;
+    let summary = po_file.import_xliff(input)?;
+    if po_file.is_modified() {
+        po_file.save()?;
+    }
+    println!("{}: {}", po_path.display(), summary);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exchange::{apply, ImportSummary};
+
+    const CATALOG: &str = concat!(
+        "msgid \"\"\nmsgstr \"\"\n\"Language: pt_BR\\n\"\n",
+        "\"Plural-Forms: nplurals=2; plural=(n > 1);\\n\"\n\n",
+        "# Keep it <short> & sweet\n#. Menu item\nmsgctxt \"menu \\\"File\\\"\"\nmsgid \"Open\"\nmsgstr \"Abrir\"\n\n",
+        "#, fuzzy\nmsgid \"Line one\\r\\nline two\"\nmsgstr \"Linha um\\r\\nlinha dois\"\n\n",
+        "msgid \"%d file\"\nmsgid_plural \"%d files\"\nmsgstr[0] \"%d arquivo\"\nmsgstr[1] \"\"\n\n",
+        "msgid \"Quit\"\nmsgstr \"\"\n",
+    );
+
+    #[test]
+    fn test_export() {
+        let po_file = PoFile::parse(CATALOG).unwrap();
+        let (text, count) = to_xliff(&po_file, "pt_BR.po").unwrap();
+        assert_eq!(count, 4);
+        let expected = concat!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+            "<xliff version=\"1.2\" xmlns=\"urn:oasis:names:tc:xliff:document:1.2\">\n",
+            "  <file original=\"pt_BR.po\" datatype=\"po\" source-language=\"en\" target-language=\"pt-BR\">\n",
+            "    <body>\n",
+            "      <trans-unit id=\"1\" resname=\"menu &quot;File&quot;\" xml:space=\"preserve\">\n",
+            "        <source>Open</source>\n",
+            "        <target state=\"translated\">Abrir</target>\n",
+            "        <note from=\"translator\">Keep it &lt;short&gt; &amp; sweet</note>\n",
+            "        <note from=\"developer\">Menu item</note>\n",
+            "      </trans-unit>\n",
+            "      <trans-unit id=\"2\" xml:space=\"preserve\">\n",
+            "        <source>Line one&#13;\nline two</source>\n",
+            "        <target state=\"needs-review-translation\">Linha um&#13;\nlinha dois</target>\n",
+            "      </trans-unit>\n",
+            "      <group id=\"3\" restype=\"x-gettext-plurals\">\n",
+            "        <trans-unit id=\"3[0]\" xml:space=\"preserve\">\n",
+            "          <source>%d file</source>\n",
+            "          <target state=\"translated\">%d arquivo</target>\n",
+            "        </trans-unit>\n",
+            "        <trans-unit id=\"3[1]\" xml:space=\"preserve\">\n",
+            "          <source>%d files</source>\n",
+            "        </trans-unit>\n",
+            "      </group>\n",
+            "      <trans-unit id=\"4\" xml:space=\"preserve\">\n",
+            "        <source>Quit</source>\n",
+            "      </trans-unit>\n",
+            "    </body>\n",
+            "  </file>\n",
+            "</xliff>\n",
+        );
+        assert_eq!(text, expected);
+
+        let mut po_file = PoFile::default();
+        po_file.entries.push(PoEntry { msgid: "a\u{4}b".to_string(), ..PoEntry::new() });
+        let error = to_xliff(&po_file, "ru.po").unwrap_err();
+        assert_eq!(format!("{:#}", error), "Entry 1 can't be exported: U+0004 can't be written in XML");
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let po_file = PoFile::parse(CATALOG).unwrap();
+        let (text, _) = to_xliff(&po_file, "pt_BR.po").unwrap();
+        let units = parse(&text).unwrap();
+        assert_eq!(units.len(), 4);
+        assert_eq!(units[0].resname.as_deref(), Some("menu \"File\""));
+        assert_eq!(units[1].source, "Line one\r\nline two");
+        assert_eq!(units[1].fuzzy, Some(true));
+        assert_eq!(units[2].forms, ["%d arquivo", ""]);
+        assert_eq!(units[3].fuzzy, None);
+
+        let mut copy = po_file.clone();
+        let summary = apply(&mut copy, translations(units, &po_file));
+        assert_eq!(summary, ImportSummary { matched: 3, skipped: 1, unknown: 0 });
+        assert_eq!(copy.entries, po_file.entries);
+    }
+
+    #[test]
+    fn test_import_returned() {
+        let mut po_file = PoFile::parse(CATALOG).unwrap();
+        // As a CAT tool sends it back: resname dropped, extra groups and
+        // attributes, CDATA, a unit the catalog lost since
+        let text = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xliff version="1.2" xmlns="urn:oasis:names:tc:xliff:document:1.2">
+  <file original="pt_BR.po" datatype="po" source-language="en" target-language="pt-BR">
+    <body>
+      <group id="menu">
+        <trans-unit id="1" approved="yes"><source>Open</source><target state="final">Abrir…</target></trans-unit>
+      </group>
+      <trans-unit id="2"><source>Line one&#13;
+line two</source><target state="signed-off"><![CDATA[Linha <um>]]></target></trans-unit>
+      <group id="3" restype="x-gettext-plurals">
+        <trans-unit id="3[1]"><source>%d files</source><target state="translated">%d arquivos</target></trans-unit>
+        <trans-unit id="3[0]"><source>%d file</source><target state="translated">%d arquivo</target></trans-unit>
+      </group>
+      <trans-unit id="9"><source>Close</source><target>Fechar</target></trans-unit>
+    </body>
+  </file>
+</xliff>"#;
+        let translations = translations(parse(text).unwrap(), &po_file);
+        assert_eq!(apply(&mut po_file, translations), ImportSummary { matched: 3, skipped: 0, unknown: 1 });
+        assert_eq!(po_file.entries[0].msgstr, "Abrir…");
+        assert_eq!(po_file.entries[0].msgctxt.as_deref(), Some("menu \"File\""));
+        assert_eq!(po_file.entries[1].msgstr, "Linha <um>");
+        assert!(!po_file.entries[1].is_fuzzy);
+        assert_eq!(po_file.entries[2].msgstr_plural, ["%d arquivo", "%d arquivos"]);
+        assert!(po_file.entries[2].is_translated);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let document = |body: &str| {
+            format!("<xliff version=\"1.2\">\n<file><body>\n{}\n</body></file>\n</xliff>", body)
+        };
+        let error = |text: &str| parse(text).unwrap_err().to_string();
+
+        assert_eq!(error("<xliff><file>"), "Malformed XML: the root node was opened but never closed at 1:1");
+        assert!(error(&document("<trans-unit id=\"1\"><source>a</target></trans-unit>")).starts_with("Malformed XML: "));
+        assert_eq!(error("<html/>"), "Not an XLIFF file: the root element is <html>");
+        assert_eq!(error("<xliff version=\"2.0\"/>"), "XLIFF 2.0 is not supported, only 1.2");
+        assert_eq!(error(&document("  <trans-unit><source>a</source></trans-unit>")), "<trans-unit> without an id at 3:3");
+        assert_eq!(error(&document("<trans-unit id=\"1\"/>")), "trans-unit 1 has no <source> at 3:1");
+        assert_eq!(
+            error(&document("<trans-unit id=\"1\"><source>a</source></trans-unit><trans-unit id=\"1\"><source>b</source></trans-unit>")),
+            "The id 1 is used twice, again at 3:51"
+        );
+        assert_eq!(
+            error(&document("<trans-unit id=\"1\"><source>a <g id=\"b\">b</g></source></trans-unit>")),
+            "<g> in the <source> of 1 at 3:30: markup is not supported"
+        );
+        assert_eq!(
+            error(&document("<trans-unit id=\"3[0]\"><source>a</source></trans-unit>")),
+            "trans-unit 3[0] is a plural form outside a plural group at 3:1"
+        );
+        let plural = |units: &str| document(&format!("<group id=\"3\" restype=\"x-gettext-plurals\">{}</group>", units));
+        assert_eq!(
+            error(&plural("<trans-unit id=\"4[0]\"><source>a</source></trans-unit>")),
+            "trans-unit 4[0] is not a form of plural group 3 at 3:43"
+        );
+        assert_eq!(
+            error(&plural("<trans-unit id=\"3[1]\"><source>a</source></trans-unit>")),
+            "Plural group 3 has no form 0 at 3:1"
+        );
+        assert_eq!(
+            error(&plural("<trans-unit id=\"3[0]\"><source>a</source></trans-unit><trans-unit id=\"3[0]\"><source>a</source></trans-unit>")),
+            "Plural group 3 has form 0 twice at 3:96"
+        );
+    }
+}
@@ -0,0 +1,370 @@
+// Poterm - Modern TUI editor for .po translation files
+// Copyright (c) 2025 AnmiTaliDev <anmitali198@gmail.com>
+// Licensed under the Apache License, Version 2.0
+
+//! Entry list filters
+
+use crate::gettext::PoEntry;
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum FilterMode {
+    #[default]
+    All,
+    Untranslated,
+    Fuzzy,
+    /// Translations flagged for review, see `Config::needs_work_flag`
+    NeedsWork,
+    /// Translations the QA checks find problems in
+    Issues,
+}
+
+/// Entries a text search looks at, on top of the status filter
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum SearchStatus {
+    #[default]
+    All,
+    Untranslated,
+    Fuzzy,
+    Translated,
+}
+
+impl SearchStatus {
+    pub fn next(self) -> Self {
+        match self {
+            SearchStatus::All => SearchStatus::Untranslated,
+            SearchStatus::Untranslated => SearchStatus::Fuzzy,
+            SearchStatus::Fuzzy => SearchStatus::Translated,
+            SearchStatus::Translated => SearchStatus::All,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SearchStatus::All => "All",
+            SearchStatus::Untranslated => "Untranslated",
+            SearchStatus::Fuzzy => "Fuzzy",
+            SearchStatus::Translated => "Translated",
+        }
+    }
+
+    fn matches(self, entry: &PoEntry) -> bool {
+        match self {
+            SearchStatus::All => true,
+            SearchStatus::Untranslated => !entry.is_translated && !entry.is_fuzzy,
+            SearchStatus::Fuzzy => entry.is_fuzzy,
+            SearchStatus::Translated => entry.is_translated,
+        }
+    }
+}
+
+/// How the search text is matched, toggled in the search overlay
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct SearchOptions {
+    pub case_sensitive: bool,
+    /// The text is a regular expression
+    pub regex: bool,
+    /// Also search msgctxt and the translator and extracted comments
+    pub context_and_comments: bool,
+}
+
+/// A search text compiled with its options
+#[derive(Debug, Clone)]
+pub struct TextQuery {
+    text: String,
+    options: SearchOptions,
+    /// The text itself as a pattern, escaped unless it is a regex
+    regex: Regex,
+}
+
+impl TextQuery {
+    /// An invalid regex is searched for as plain text; the error says what
+    /// is wrong with it
+    pub fn new(text: &str, options: SearchOptions) -> (Self, Option<String>) {
+        let build = |pattern: &str| RegexBuilder::new(pattern).case_insensitive(!options.case_sensitive).build();
+        let (regex, error) = match options.regex.then(|| build(text)) {
+            Some(Ok(regex)) => (Ok(regex), None),
+            Some(Err(e)) => (
+                build(&regex::escape(text)),
+                Some(format!("Invalid regex: {}", e.to_string().lines().last().unwrap_or_default().trim())),
+            ),
+            None => (build(&regex::escape(text)), None),
+        };
+        let query = Self {
+            text: text.to_string(),
+            options,
+            // Escaped text only fails to compile past the size limit, and then
+            // finds nothing
+            regex: regex.unwrap_or_else(|_| Regex::new(r"[^\s\S]").expect("valid regex")),
+        };
+        (query, error)
+    }
+
+    /// Compiled from `text` with `options`, so there is no need to compile
+    /// it again
+    pub fn is_for(&self, text: &str, options: SearchOptions) -> bool {
+        self.text == text && self.options == options
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Byte ranges of the non-empty matches in `text`
+    pub fn find_ranges(&self, text: &str) -> Vec<Range<usize>> {
+        self.regex.find_iter(text).map(|m| m.range()).filter(|range| !range.is_empty()).collect()
+    }
+
+    pub fn matches(&self, entry: &PoEntry) -> bool {
+        let contains = |text: &str| self.regex.is_match(text);
+        contains(&entry.msgid)
+            || contains(&entry.msgstr)
+            || entry.msgid_plural.as_deref().is_some_and(contains)
+            || entry.msgstr_plural.iter().any(|form| contains(form))
+            || (self.options.context_and_comments
+                && (entry.msgctxt.as_deref().is_some_and(contains)
+                    || entry.comments.iter().chain(&entry.extracted_comments).any(|comment| contains(comment))))
+    }
+}
+
+/// Independent predicates on entries; an entry is listed when it matches
+/// all of them. Each one is set and cleared on its own.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FilterSet {
+    pub status: FilterMode,
+    /// Text in msgid or msgstr, from search or the quick filter, matched
+    /// as `TextQuery` says
+    pub text: String,
+    /// Status of the entries `text` is searched in
+    pub search_status: SearchStatus,
+    /// Flag such as `c-format`
+    pub flag: Option<String>,
+    /// Part of a source reference, e.g. `export.c`
+    pub source: Option<String>,
+    /// Only entries changed since the last save
+    pub changed: bool,
+    /// Only entries with a mark
+    pub marked: bool,
+}
+
+impl FilterSet {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// `changed`, `marked`, `needs_work` and `has_issues` describe the
+    /// entry's state in the editor. `query` is `text` compiled with the search
+    /// options; without it `text` is matched case-insensitively.
+    pub fn matches(
+        &self,
+        entry: &PoEntry,
+        changed: bool,
+        marked: bool,
+        needs_work: bool,
+        has_issues: bool,
+        query: Option<&TextQuery>,
+    ) -> bool {
+        let status = match self.status {
+            FilterMode::All => true,
+            FilterMode::Untranslated => !entry.is_translated,
+            FilterMode::Fuzzy => entry.is_fuzzy,
+            FilterMode::NeedsWork => needs_work,
+            FilterMode::Issues => has_issues,
+        };
+        let text = self.text.is_empty()
+            || (self.search_status.matches(entry)
+                && match query {
+                    Some(query) => query.matches(entry),
+                    None => TextQuery::new(&self.text, SearchOptions::default()).0.matches(entry),
+                });
+        let flag = self.flag.as_ref().is_none_or(|flag| entry.flags.contains(flag));
+        let source = self.source.as_ref().is_none_or(|source| {
+            entry.references.iter().any(|reference| {
+                // `src/export.c:42` -> `src/export.c`
+                let file = reference.rsplit_once(':').map_or(reference.as_str(), |(file, _)| file);
+                file.contains(source.as_str())
+            })
+        });
+
+        status && text && flag && source && (changed || !self.changed) && (marked || !self.marked)
+    }
+
+    /// Short labels of the active predicates except the text query, e.g.
+    /// `Untranslated`, `flag:c-format`
+    pub fn labels(&self) -> Vec<String> {
+        let mut labels = Vec::new();
+        match self.status {
+            FilterMode::All => {}
+            FilterMode::Untranslated => labels.push("Untranslated".to_string()),
+            FilterMode::Fuzzy => labels.push("Fuzzy".to_string()),
+            FilterMode::NeedsWork => labels.push("Needs work".to_string()),
+            FilterMode::Issues => labels.push("Issues".to_string()),
+        }
+        if !self.text.is_empty() && self.search_status != SearchStatus::All {
+            labels.push(format!("search:{}", self.search_status.label().to_lowercase()));
+        }
+        if let Some(ref flag) = self.flag {
+            labels.push(format!("flag:{}", flag));
+        }
+        if let Some(ref source) = self.source {
+            labels.push(format!("src:{}", source));
+        }
+        if self.changed {
+            labels.push("changed".to_string());
+        }
+        if self.marked {
+            labels.push("marked".to_string());
+        }
+        labels
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(msgid: &str, msgstr: &str, flags: &[&str], references: &[&str]) -> PoEntry {
+        let mut entry = PoEntry::new();
+        entry.msgid = msgid.to_string();
+        entry.flags = flags.iter().map(|f| f.to_string()).collect();
+        entry.references = references.iter().map(|r| r.to_string()).collect();
+        entry.set_msgstr(msgstr.to_string());
+        entry
+    }
+
+    #[test]
+    fn test_predicates_combine() {
+        let export = entry("Export %s", "", &["c-format"], &["src/export.c:42"]);
+        let import = entry("Import", "Импорт", &[], &["src/import.c:7"]);
+
+        let mut filters = FilterSet {
+            status: FilterMode::Untranslated,
+            flag: Some("c-format".to_string()),
+            ..FilterSet::default()
+        };
+        assert!(filters.matches(&export, false, false, false, false, None));
+        assert!(!filters.matches(&import, false, false, false, false, None));
+
+        filters.source = Some("export.c".to_string());
+        filters.text = "EXPORT".to_string();
+        assert!(filters.matches(&export, false, false, false, false, None));
+        // Line numbers are not part of the file name
+        filters.source = Some("42".to_string());
+        assert!(!filters.matches(&export, false, false, false, false, None));
+
+        let filters = FilterSet {
+            changed: true,
+            marked: true,
+            ..FilterSet::default()
+        };
+        assert!(filters.matches(&import, true, true, false, false, None));
+        assert!(!filters.matches(&import, true, false, false, false, None));
+        assert!(!filters.is_empty());
+
+        let filters = FilterSet {
+            status: FilterMode::NeedsWork,
+            ..FilterSet::default()
+        };
+        assert!(filters.matches(&import, false, false, true, false, None));
+        assert!(!filters.matches(&import, false, false, false, false, None));
+
+        let filters = FilterSet {
+            status: FilterMode::Issues,
+            ..FilterSet::default()
+        };
+        assert!(filters.matches(&import, false, false, false, true, None));
+        assert!(!filters.matches(&import, false, false, true, false, None));
+        assert_eq!(filters.labels(), vec!["Issues"]);
+    }
+
+    #[test]
+    fn test_search_status() {
+        let open = entry("Open", "", &[], &[]);
+        let open_file = entry("Open file", "Открыть файл", &[], &[]);
+        let mut filters = FilterSet {
+            text: "open".to_string(),
+            search_status: SearchStatus::Untranslated,
+            ..FilterSet::default()
+        };
+        assert!(filters.matches(&open, false, false, false, false, None));
+        assert!(!filters.matches(&open_file, false, false, false, false, None));
+
+        filters.search_status = SearchStatus::Translated;
+        assert!(filters.matches(&open_file, false, false, false, false, None));
+        assert_eq!(filters.labels(), vec!["search:translated"]);
+        // Both the status filter and the search scope apply
+        filters.status = FilterMode::Untranslated;
+        assert!(!filters.matches(&open_file, false, false, false, false, None));
+        assert!(!filters.matches(&open, false, false, false, false, None));
+
+        // Without a query the scope selects nothing
+        filters.status = FilterMode::All;
+        filters.text.clear();
+        assert!(filters.matches(&open, false, false, false, false, None));
+        assert_eq!(SearchStatus::Translated.next(), SearchStatus::All);
+    }
+
+    #[test]
+    fn test_search_options() {
+        let mut ok = entry("OK", "ОК", &[], &[]);
+        let broken = entry("The link is broken", "", &[], &[]);
+        let format = entry("%d files", "", &[], &[]);
+        let query = |text: &str, options| {
+            let (query, error) = TextQuery::new(text, options);
+            assert_eq!(error, None);
+            query
+        };
+
+        let case = SearchOptions {
+            case_sensitive: true,
+            ..SearchOptions::default()
+        };
+        assert!(query("OK", case).matches(&ok));
+        assert!(!query("OK", case).matches(&broken));
+        assert!(query("OK", SearchOptions::default()).matches(&broken));
+
+        let regex = SearchOptions {
+            regex: true,
+            ..SearchOptions::default()
+        };
+        assert!(query(r"%[ds]", regex).matches(&format));
+        assert!(query(r"^%d \w+$", regex).matches(&format));
+        assert!(!query("%s", SearchOptions::default()).matches(&format));
+
+        // An invalid regex is searched for as it is typed
+        let (invalid, error) = TextQuery::new("(", regex);
+        assert_eq!(error.as_deref(), Some("Invalid regex: error: unclosed group"));
+        assert!(!invalid.matches(&format));
+        assert!(invalid.is_for("(", regex) && !invalid.is_for("(", case));
+
+        ok.msgctxt = Some("dialog button".to_string());
+        ok.comments = vec!["Keep it short".to_string()];
+        let everywhere = SearchOptions {
+            context_and_comments: true,
+            ..SearchOptions::default()
+        };
+        assert!(!query("button", SearchOptions::default()).matches(&ok));
+        assert!(query("button", everywhere).matches(&ok));
+        assert!(query("short", everywhere).matches(&ok));
+
+        let open = query("открыть", SearchOptions::default());
+        assert_eq!(open.find_ranges("Открыть и открыть"), vec![0..14, 18..32]);
+        assert_eq!(query("a*", regex).find_ranges("baa"), vec![1..3]);
+    }
+
+    #[test]
+    fn test_labels() {
+        let filters = FilterSet {
+            status: FilterMode::Untranslated,
+            text: "export".to_string(),
+            flag: Some("c-format".to_string()),
+            changed: true,
+            ..FilterSet::default()
+        };
+        assert_eq!(filters.labels(), vec!["Untranslated", "flag:c-format", "changed"]);
+        assert!(FilterSet::default().labels().is_empty());
+    }
+}
@@ -2,24 +2,100 @@
 // Copyright (c) 2025 AnmiTaliDev <anmitali198@gmail.com>
 // Licensed under the Apache License, Version 2.0
 
+use crate::exchange::csv::{self, CsvOptions};
+use crate::exchange::json::{self, JsonOptions, JsonStyle};
+use crate::exchange::{self, xliff, ImportSummary};
+use crate::merge::{self, MergeOptions, MergeSummary};
+use crate::mo;
+use crate::wrap::cjk_break;
 use anyhow::{Context, Result};
-use chrono;
 use regex::Regex;
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use indexmap::IndexMap;
+use std::fmt;
 use std::fs;
+use std::io::Write;
 use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::SystemTime;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// Replace the file at `path` with `content` through a temporary file in
+/// the same directory, so that a failed or interrupted write leaves the old
+/// file whole. Its permissions, and its owner where allowed, are kept; with
+/// `backup` its contents are copied to `<name>.bak` first.
+pub fn write_atomic(path: &Path, content: &str, backup: bool) -> Result<()> {
+    // Through a symlink, the file it points to is replaced
+    let path = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let name = path.file_name().context("No file name")?.to_string_lossy().into_owned();
+    let dir = path.parent().filter(|dir| !dir.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    let temp = dir.join(format!(".{}.{}.tmp", name, std::process::id()));
+    let previous = fs::metadata(&path).ok();
+
+    let write = || -> Result<()> {
+        let mut file = fs::File::create(&temp)?;
+        file.write_all(content.as_bytes())?;
+        file.sync_all()?;
+        if let Some(ref metadata) = previous {
+            fs::set_permissions(&temp, metadata.permissions())?;
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::MetadataExt;
+                let _ = std::os::unix::fs::chown(&temp, Some(metadata.uid()), Some(metadata.gid()));
+            }
+            if backup {
+                fs::copy(&path, dir.join(format!("{}.bak", name))).context("Failed to write the backup")?;
+            }
+        }
+        fs::rename(&temp, &path)?;
+        Ok(())
+    };
+    if let Err(e) = write() {
+        let _ = fs::remove_file(&temp);
+        return Err(e);
+    }
+    // Make the rename itself durable
+    #[cfg(unix)]
+    if let Ok(dir) = fs::File::open(dir) {
+        let _ = dir.sync_all();
+    }
+    Ok(())
+}
+
+/// Columns of a saved line, quotes included, as msgcat counts them
+const WRAP_WIDTH: usize = 79;
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct PoEntry {
     pub msgid: String,
+    /// For plural entries the same as `msgstr_plural[0]`
     pub msgstr: String,
+    pub msgid_plural: Option<String>,
+    /// `msgstr[0]`, `msgstr[1]`, ... of plural entries, empty otherwise
+    pub msgstr_plural: Vec<String>,
     pub msgctxt: Option<String>,
+    /// `#| msgctxt`, `#| msgid` and `#| msgid_plural` that msgmerge leaves
+    /// on fuzzy entries: the source text the translation was made for
+    pub previous_msgctxt: Option<String>,
+    pub previous_msgid: Option<String>,
+    pub previous_msgid_plural: Option<String>,
     pub comments: Vec<String>,
     pub extracted_comments: Vec<String>,
     pub references: Vec<String>,
     pub flags: Vec<String>,
     pub is_fuzzy: bool,
     pub is_translated: bool,
+    /// 1-based line of the `msgid` keyword in the source file, 0 for
+    /// entries that were not read from a file
+    pub line: usize,
+}
+
+/// Identity of an entry within a catalog, stable across filtering and sorting
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct EntryKey {
+    pub msgctxt: Option<String>,
+    pub msgid: String,
 }
 
 impl PoEntry {
@@ -27,26 +103,125 @@ impl PoEntry {
         Self {
             msgid: String::new(),
             msgstr: String::new(),
+            msgid_plural: None,
+            msgstr_plural: Vec::new(),
             msgctxt: None,
+            previous_msgctxt: None,
+            previous_msgid: None,
+            previous_msgid_plural: None,
             comments: Vec::new(),
             extracted_comments: Vec::new(),
             references: Vec::new(),
             flags: Vec::new(),
             is_fuzzy: false,
             is_translated: false,
+            line: 0,
+        }
+    }
+
+    /// The previous field a `#|` line with `keyword` sets
+    fn previous_field(&mut self, keyword: &str) -> Option<&mut Option<String>> {
+        match keyword {
+            "msgctxt" => Some(&mut self.previous_msgctxt),
+            "msgid" => Some(&mut self.previous_msgid),
+            "msgid_plural" => Some(&mut self.previous_msgid_plural),
+            _ => None,
+        }
+    }
+
+    /// First `#:` reference, e.g. `src/window.c:215`
+    pub fn first_reference(&self) -> Option<&str> {
+        self.references.iter().flat_map(|line| line.split_whitespace()).next()
+    }
+
+    pub fn key(&self) -> EntryKey {
+        EntryKey {
+            msgctxt: self.msgctxt.clone(),
+            msgid: self.msgid.clone(),
         }
     }
 
+    /// Plural entries count as translated once every form is
     pub fn update_status(&mut self) {
         self.is_fuzzy = self.flags.contains(&"fuzzy".to_string());
-        self.is_translated = !self.msgstr.is_empty() && !self.is_fuzzy;
+        let filled = if self.is_plural() {
+            !self.msgstr_plural.is_empty() && self.msgstr_plural.iter().all(|form| !form.is_empty())
+        } else {
+            !self.msgstr.is_empty()
+        };
+        self.is_translated = filled && !self.is_fuzzy;
     }
 
+    /// Sets the first form of plural entries
     pub fn set_msgstr(&mut self, msgstr: String) {
+        if self.is_plural() {
+            return self.set_form(0, msgstr);
+        }
         self.msgstr = msgstr;
         self.update_status();
     }
 
+    pub fn is_plural(&self) -> bool {
+        self.msgid_plural.is_some()
+    }
+
+    /// Whether any translation form is filled in
+    pub fn has_translation(&self) -> bool {
+        (0..self.form_count()).any(|form| !self.form(form).is_empty())
+    }
+
+    /// Number of translation forms: 1, or the plural forms of the entry
+    pub fn form_count(&self) -> usize {
+        if self.is_plural() {
+            self.msgstr_plural.len().max(1)
+        } else {
+            1
+        }
+    }
+
+    /// Translation form `form`, the msgstr of singular entries
+    pub fn form(&self, form: usize) -> &str {
+        if self.is_plural() {
+            self.msgstr_plural.get(form).map(String::as_str).unwrap_or_default()
+        } else {
+            &self.msgstr
+        }
+    }
+
+    /// Set translation form `form`, adding empty forms before it as needed
+    pub fn set_form(&mut self, form: usize, text: String) {
+        if !self.is_plural() {
+            return self.set_msgstr(text);
+        }
+        if self.msgstr_plural.len() <= form {
+            self.msgstr_plural.resize(form + 1, String::new());
+        }
+        self.msgstr_plural[form] = text;
+        self.msgstr = self.msgstr_plural[0].clone();
+        self.update_status();
+    }
+
+    /// Empty every translation form
+    pub fn clear_translation(&mut self) {
+        self.msgstr.clear();
+        self.msgstr_plural.iter_mut().for_each(String::clear);
+        self.update_status();
+    }
+
+    pub fn has_flag(&self, flag: &str) -> bool {
+        self.flags.iter().any(|f| f == flag)
+    }
+
+    /// Add `flag`, or remove it when present
+    pub fn toggle_flag(&mut self, flag: &str) {
+        if self.has_flag(flag) {
+            self.flags.retain(|f| f != flag);
+        } else {
+            self.flags.push(flag.to_string());
+        }
+        self.update_status();
+    }
+
     pub fn toggle_fuzzy(&mut self) {
         if self.is_fuzzy {
             self.flags.retain(|f| f != "fuzzy");
@@ -63,17 +238,113 @@ impl Default for PoEntry {
     }
 }
 
-#[derive(Debug, Clone)]
+/// Source (msgid) word counts by entry status
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WordStats {
+    pub total: usize,
+    pub translated: usize,
+    pub fuzzy: usize,
+    pub untranslated: usize,
+}
+
+impl WordStats {
+    /// Words that still need work: untranslated plus fuzzy
+    pub fn remaining(&self) -> usize {
+        self.untranslated + self.fuzzy
+    }
+}
+
+/// Number of words in `text`. Tokens without letters or digits, such as
+/// `-` or `…`, are not counted.
+pub fn count_words(text: &str) -> usize {
+    text.split_whitespace()
+        .filter(|token| token.chars().any(char::is_alphanumeric))
+        .count()
+}
+
+/// Standard header fields in the order gettext tools write them
+pub const CANONICAL_HEADER_KEYS: [&str; 11] = [
+    "Project-Id-Version",
+    "Report-Msgid-Bugs-To",
+    "POT-Creation-Date",
+    "PO-Revision-Date",
+    "Last-Translator",
+    "Language-Team",
+    "Language",
+    "MIME-Version",
+    "Content-Type",
+    "Content-Transfer-Encoding",
+    "Plural-Forms",
+];
+
+/// A line of the file that was skipped or misread while parsing
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseIssue {
+    pub line: usize,
+    pub message: String,
+}
+
+impl ParseIssue {
+    pub fn new(line: usize, message: impl Into<String>) -> Self {
+        Self {
+            line,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for ParseIssue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Line {}: {}", self.line, self.message)
+    }
+}
+
+/// Size and modification time of a file, to notice when something else
+/// writes it
+#[derive(Debug, Clone, PartialEq)]
+struct DiskStamp {
+    path: PathBuf,
+    len: u64,
+    modified: Option<SystemTime>,
+}
+
+impl DiskStamp {
+    fn read(path: &Path) -> Option<Self> {
+        let metadata = fs::metadata(path).ok()?;
+        Some(Self {
+            path: path.to_path_buf(),
+            len: metadata.len(),
+            modified: metadata.modified().ok(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Default)]
 pub struct PoFile {
     pub path: Option<PathBuf>,
-    pub header: HashMap<String, String>,
+    /// Header fields in file order
+    pub header: IndexMap<String, String>,
     pub entries: Vec<PoEntry>,
+    /// Obsolete `#~` entries with their comments, as read. They are written
+    /// back unchanged after the other entries.
+    pub obsolete_entries: Vec<String>,
+    /// Save every string on one line, breaking only after `\n`, like
+    /// `msgcat --no-wrap`
+    pub no_wrap: bool,
+    /// Keep the previous contents in `<name>.bak` when saving over a file
+    pub backup: bool,
     pub modified: bool,
+    /// Incremented on every change so views can cache derived data
+    pub revision: u64,
+    /// Problems met reading the file; saving loses what they describe
+    parse_issues: Vec<ParseIssue>,
+    /// The file as last read or written
+    disk_stamp: Option<DiskStamp>,
 }
 
 impl PoFile {
     pub fn new(path: PathBuf) -> Self {
-        let mut header = HashMap::new();
+        let mut header = IndexMap::new();
         header.insert("Project-Id-Version".to_string(), "PACKAGE VERSION".to_string());
         header.insert("Report-Msgid-Bugs-To".to_string(), "".to_string());
         header.insert("POT-Creation-Date".to_string(), "YEAR-MO-DA HO:MI+ZONE".to_string());
@@ -90,30 +361,53 @@ impl PoFile {
             path: Some(path),
             header,
             entries: Vec::new(),
+            obsolete_entries: Vec::new(),
+            no_wrap: false,
+            backup: false,
             modified: false,
+            revision: 0,
+            parse_issues: Vec::new(),
+            disk_stamp: None,
         }
     }
 
     pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
         let path = path.as_ref();
+        let disk_stamp = DiskStamp::read(path);
         let content = fs::read_to_string(path)
             .with_context(|| format!("Failed to read file: {}", path.display()))?;
         
         let mut po_file = Self::parse(&content)?;
         po_file.path = Some(path.to_path_buf());
         po_file.modified = false;
+        po_file.disk_stamp = disk_stamp;
         
         Ok(po_file)
     }
 
+    /// Read the compiled catalog at `path` back into a new, unsaved .po
+    /// without a path. MO files keep no comments, references or flags, and
+    /// no fuzzy or untranslated entries.
+    pub fn from_mo<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let bytes = fs::read(path).with_context(|| format!("Failed to read file: {}", path.display()))?;
+        let mut po_file = mo::from_bytes(&bytes).with_context(|| format!("Failed to read {}", path.display()))?;
+        po_file.modified = true;
+        Ok(po_file)
+    }
+
     pub fn from_pot_template<P: AsRef<Path>>(pot_path: P, po_path: P) -> Result<Self> {
         let pot_path = pot_path.as_ref();
-        let po_path = po_path.as_ref();
         
         let content = fs::read_to_string(pot_path)
             .with_context(|| format!("Failed to read POT file: {}", pot_path.display()))?;
         
-        let mut po_file = Self::parse(&content)?;
+        Ok(Self::parse(&content)?.instantiate(po_path.as_ref()))
+    }
+
+    /// New catalog at `po_path` created from this template
+    pub fn instantiate(&self, po_path: &Path) -> Self {
+        let mut po_file = self.clone();
         po_file.path = Some(po_path.to_path_buf());
         
         // Update header for new PO file
@@ -129,28 +423,54 @@ impl PoFile {
         // Clear all msgstr fields for translation
         for entry in &mut po_file.entries {
             if !entry.msgid.is_empty() {  // Don't clear header entry
-                entry.msgstr.clear();
-                entry.is_translated = false;
-                entry.is_fuzzy = false;
                 entry.flags.retain(|flag| flag != "fuzzy");
+                entry.clear_translation();
             }
         }
         
         po_file.modified = true;
-        Ok(po_file)
+        po_file
+    }
+
+    /// Whether this is a .pot template rather than a translation: by
+    /// extension, or no translations at all and a revision date that was
+    /// never filled in
+    pub fn is_template(&self) -> bool {
+        if self.path.as_deref().and_then(Path::extension).is_some_and(|ext| ext == "pot") {
+            return true;
+        }
+        !self.entries.is_empty()
+            && self.entries.iter().all(|entry| entry.msgstr.is_empty())
+            && self.header.get("PO-Revision-Date").is_some_and(|date| date.contains("YEAR-MO-DA"))
     }
 
+    /// Parse `content`, keeping what can't be read as `parse_issues`
+    /// rather than failing
     pub fn parse(content: &str) -> Result<Self> {
+        Ok(Self::parse_with_diagnostics(content).0)
+    }
+
+    /// Parse as much of `content` as possible, returning a message for every
+    /// line that was skipped or misread. Saving a catalog with diagnostics
+    /// loses whatever they describe.
+    pub fn parse_with_diagnostics(content: &str) -> (Self, Vec<String>) {
         let mut po_file = PoFile {
             path: None,
-            header: HashMap::new(),
+            header: IndexMap::new(),
             entries: Vec::new(),
+            obsolete_entries: Vec::new(),
+            no_wrap: false,
+            backup: false,
             modified: false,
+            revision: 0,
+            parse_issues: Vec::new(),
+            disk_stamp: None,
         };
 
         let lines: Vec<&str> = content.lines().collect();
         let mut i = 0;
         let mut parse_errors = Vec::new();
+        let mut seen_header = false;
 
         while i < lines.len() {
             let line = lines[i].trim();
@@ -164,24 +484,47 @@ impl PoFile {
             // Parse entry
             let mut entry = PoEntry::new();
             let start_i = i;
+            let errors_before = parse_errors.len();
 
             // Parse comments and metadata
+            let mut previous_keyword = None;
             while i < lines.len() {
                 let line = lines[i].trim();
                 if line.is_empty() {
                     break;
                 }
                 
-                if line.starts_with("#.") {
-                    entry.extracted_comments.push(line[2..].trim().to_string());
-                } else if line.starts_with("#:") {
-                    entry.references.push(line[2..].trim().to_string());
-                } else if line.starts_with("#,") {
-                    let flags: Vec<String> = line[2..]
+                if let Some(comment) = line.strip_prefix("#.") {
+                    entry.extracted_comments.push(comment.trim().to_string());
+                } else if let Some(reference) = line.strip_prefix("#:") {
+                    entry.references.push(reference.trim().to_string());
+                } else if let Some(flags) = line.strip_prefix("#,") {
+                    let flags: Vec<String> = flags
                         .split(',')
                         .map(|f| f.trim().to_string())
                         .collect();
                     entry.flags.extend(flags);
+                } else if let Some(previous) = line.strip_prefix("#|") {
+                    let previous = previous.trim();
+                    // `#| "..."` continues the field before it
+                    let keyword = if previous.starts_with('"') {
+                        previous_keyword
+                    } else {
+                        ["msgctxt", "msgid_plural", "msgid"]
+                            .into_iter()
+                            .find(|keyword| Self::starts_with_keyword(previous, keyword))
+                    };
+                    let value = if previous.starts_with('"') {
+                        Self::parse_string_literal(previous)
+                    } else {
+                        Self::parse_string_value(previous)
+                    };
+                    match (keyword.and_then(|keyword| entry.previous_field(keyword)), value) {
+                        (Some(field), Ok(value)) => *field.get_or_insert_with(String::new) += &value,
+                        (None, _) => parse_errors.push(ParseIssue::new(i + 1, format!("Unrecognized previous field ignored: {}", line))),
+                        (Some(_), Err(e)) => parse_errors.push(ParseIssue::new(i + 1, format!("Failed to parse previous field: {}", e))),
+                    }
+                    previous_keyword = keyword;
                 } else if line.starts_with('#') && !line.starts_with("#~") {
                     entry.comments.push(line[1..].trim().to_string());
                 } else {
@@ -189,117 +532,133 @@ impl PoFile {
                 }
                 i += 1;
             }
+            let sections_start = i;
 
-            // Parse msgctxt if present
-            if i < lines.len() && lines[i].trim().starts_with("msgctxt") {
-                entry.msgctxt = Some(Self::parse_string_value(lines[i].trim())?);
-                i += 1;
-                
-                // Handle multiline msgctxt
-                while i < lines.len() && lines[i].trim().starts_with('"') {
-                    if let Some(ref mut msgctxt) = entry.msgctxt {
-                        *msgctxt += &Self::parse_string_literal(lines[i].trim())?;
-                    }
+            // Keep an obsolete entry as is, continuation lines included
+            if i < lines.len() && lines[i].trim().starts_with("#~") {
+                while i < lines.len() && lines[i].trim().starts_with("#~") {
                     i += 1;
                 }
+                po_file.obsolete_entries.push(lines[start_i..i].join("\n"));
+                continue;
             }
 
-            // Parse msgid
-            if i < lines.len() && lines[i].trim().starts_with("msgid") {
-                match Self::parse_string_value(lines[i].trim()) {
-                    Ok(msgid) => {
-                        entry.msgid = msgid;
-                        i += 1;
-                        
-                        // Handle multiline msgid
-                        while i < lines.len() && lines[i].trim().starts_with('"') {
-                            match Self::parse_string_literal(lines[i].trim()) {
-                                Ok(literal) => entry.msgid += &literal,
-                                Err(e) => {
-                                    parse_errors.push(format!("Line {}: Failed to parse msgid string literal: {}", i + 1, e));
-                                    break;
-                                }
-                            }
-                            i += 1;
-                        }
-                    }
-                    Err(e) => {
-                        parse_errors.push(format!("Line {}: Failed to parse msgid: {}", i + 1, e));
-                        i += 1;
+            // Each keyword and the quoted lines after it form one string
+            if i < lines.len() && Self::starts_with_keyword(lines[i], "msgctxt") {
+                entry.msgctxt = Some(Self::parse_string_block(&lines, &mut i, "msgctxt", &mut parse_errors));
+            }
+            if i < lines.len() && Self::starts_with_keyword(lines[i], "msgid") {
+                entry.line = i + 1;
+                entry.msgid = Self::parse_string_block(&lines, &mut i, "msgid", &mut parse_errors);
+            }
+            if i < lines.len() && Self::starts_with_keyword(lines[i], "msgid_plural") {
+                entry.msgid_plural = Some(Self::parse_string_block(&lines, &mut i, "msgid_plural", &mut parse_errors));
+            }
+            while i < lines.len() && lines[i].trim().starts_with("msgstr[") {
+                let line = lines[i].trim();
+                let form = line["msgstr[".len()..].split_once(']').and_then(|(n, _)| n.parse::<usize>().ok());
+                if form.is_none() {
+                    parse_errors.push(ParseIssue::new(i + 1, "Failed to parse msgstr: bad plural form index"));
+                }
+                let value = Self::parse_string_block(&lines, &mut i, "msgstr", &mut parse_errors);
+                if let Some(form) = form {
+                    if entry.msgstr_plural.len() <= form {
+                        entry.msgstr_plural.resize(form + 1, String::new());
                     }
+                    entry.msgstr_plural[form] = value;
                 }
             }
+            if let Some(first) = entry.msgstr_plural.first() {
+                entry.msgstr = first.clone();
+            }
+            if i < lines.len() && Self::starts_with_keyword(lines[i], "msgstr") {
+                entry.msgstr = Self::parse_string_block(&lines, &mut i, "msgstr", &mut parse_errors);
+            }
 
-            // Parse msgstr
-            if i < lines.len() && lines[i].trim().starts_with("msgstr") {
-                match Self::parse_string_value(lines[i].trim()) {
-                    Ok(msgstr) => {
-                        entry.msgstr = msgstr;
-                        i += 1;
-                        
-                        // Handle multiline msgstr
-                        while i < lines.len() && lines[i].trim().starts_with('"') {
-                            match Self::parse_string_literal(lines[i].trim()) {
-                                Ok(literal) => entry.msgstr += &literal,
-                                Err(e) => {
-                                    parse_errors.push(format!("Line {}: Failed to parse msgstr string literal: {}", i + 1, e));
-                                    break;
-                                }
-                            }
-                            i += 1;
-                        }
-                    }
-                    Err(e) => {
-                        parse_errors.push(format!("Line {}: Failed to parse msgstr: {}", i + 1, e));
-                        i += 1;
-                    }
-                }
+            // Lines of no known kind are skipped
+            if i == sections_start && i < lines.len() && !lines[i].trim().is_empty() {
+                parse_errors.push(ParseIssue::new(i + 1, format!("Unrecognized line ignored: {}", lines[i].trim())));
+                i += 1;
             }
 
             // Update entry status
             entry.update_status();
 
-            // Handle header entry (msgid is empty)
-            if entry.msgid.is_empty() && start_i == 0 {
-                // Parse header
-                for line in entry.msgstr.lines() {
-                    if let Some(colon_pos) = line.find(':') {
-                        let key = line[..colon_pos].trim().to_string();
-                        let value = line[colon_pos + 1..].trim().to_string();
-                        po_file.header.insert(key, value);
-                    }
+            // Only the first entry can be the header; a later empty msgid
+            // would be written back as a second header
+            if entry.msgid.is_empty() && entry.msgctxt.is_none() {
+                if entry.line == 0 {
+                    // No msgid at all, e.g. a stray comment block
+                } else if !seen_header && po_file.entries.is_empty() {
+                    seen_header = true;
+                    po_file.read_header(&entry.msgstr);
+                } else if parse_errors.len() == errors_before {
+                    parse_errors.push(ParseIssue::new(entry.line, "Entry with an empty msgid ignored"));
                 }
-            } else if !entry.msgid.is_empty() {
+            } else {
                 po_file.entries.push(entry);
             }
         }
+        po_file.pad_plural_forms();
 
-        // Log parse errors if any occurred, but don't fail the entire parse
-        if !parse_errors.is_empty() {
-            eprintln!("Warning: {} parse errors encountered:", parse_errors.len());
-            for error in &parse_errors {
-                eprintln!("  {}", error);
+        let diagnostics = parse_errors.iter().map(ParseIssue::to_string).collect();
+        po_file.parse_issues = parse_errors;
+        (po_file, diagnostics)
+    }
+
+    /// Lines skipped or misread when the file was parsed
+    pub fn parse_issues(&self) -> &[ParseIssue] {
+        &self.parse_issues
+    }
+
+    /// `line` starts with `keyword` followed by whitespace, so `msgid` does
+    /// not match `msgid_plural`
+    fn starts_with_keyword(line: &str, keyword: &str) -> bool {
+        line.trim()
+            .strip_prefix(keyword)
+            .is_some_and(|rest| rest.starts_with(char::is_whitespace))
+    }
+
+    /// The string of the `keyword "..."` line at `i` joined with the `"..."`
+    /// lines continuing it. `i` moves past them; a line that fails to parse
+    /// is reported and skipped, so the rest of the string is still read.
+    fn parse_string_block(lines: &[&str], i: &mut usize, keyword: &str, errors: &mut Vec<ParseIssue>) -> String {
+        let mut value = Self::parse_string_value(lines[*i].trim()).unwrap_or_else(|e| {
+            errors.push(ParseIssue::new(*i + 1, format!("Failed to parse {}: {}", keyword, e)));
+            String::new()
+        });
+        *i += 1;
+        while *i < lines.len() && lines[*i].trim().starts_with('"') {
+            match Self::parse_string_literal(lines[*i].trim()) {
+                Ok(literal) => value += &literal,
+                Err(e) => errors.push(ParseIssue::new(*i + 1, format!("Failed to parse {} string literal: {}", keyword, e))),
             }
+            *i += 1;
         }
-
-        Ok(po_file)
+        value
     }
 
     fn parse_string_value(line: &str) -> Result<String> {
-        let re = Regex::new(r#"msg(?:id|str|ctxt)\s+"(.*)""#)?;
+        // Compiled once: catalogs have tens of thousands of these lines
+        static KEYWORD: OnceLock<Regex> = OnceLock::new();
+        let re = KEYWORD.get_or_init(|| Regex::new(r#"msg(?:id_plural|id|str\[\d+\]|str|ctxt)\s+"(.*)""#).expect("valid regex"));
         if let Some(captures) = re.captures(line) {
             Self::parse_string_literal(&format!("\"{}\"", &captures[1]))
         } else {
-            Ok(String::new())
+            anyhow::bail!("expected a quoted string")
         }
     }
 
     fn parse_string_literal(s: &str) -> Result<String> {
-        if !s.starts_with('"') || !s.ends_with('"') {
-            return Ok(s.to_string());
+        if s.len() < 2 || !s.starts_with('"') || !s.ends_with('"') {
+            anyhow::bail!("unterminated string {}", s);
         }
         
         let content = &s[1..s.len() - 1];
+        // `"abc\"` ends with an escaped quote, not a closing one
+        if (content.len() - content.trim_end_matches('\\').len()) % 2 == 1 {
+            anyhow::bail!("unterminated string {}", s);
+        }
         let mut result = String::new();
         let mut chars = content.chars().peekable();
         
@@ -333,12 +692,43 @@ impl PoFile {
             .replace('"', "\\\"")
     }
 
+    /// Write `keyword "value"` as msgcat does. A string with a line break
+    /// inside, or one that does not fit on the keyword's line, starts with
+    /// an empty `""` and continues with a line per `\n` and per `width`
+    /// columns. `prefix` starts every line, e.g. `#| `.
+    fn write_string(
+        f: &mut fmt::Formatter<'_>,
+        prefix: &str,
+        keyword: &str,
+        value: &str,
+        width: Option<usize>,
+    ) -> fmt::Result {
+        let mut portions: Vec<String> = value.split_inclusive('\n').map(Self::escape_string).collect();
+        if portions.is_empty() {
+            portions.push(String::new());
+        }
+        let first_column = prefix.width() + keyword.width() + 2;
+        if portions.len() == 1 && wrap_string(&portions[0], first_column, width).len() == 1 {
+            return writeln!(f, "{}{} \"{}\"", prefix, keyword, portions[0]);
+        }
+        writeln!(f, "{}{} \"\"", prefix, keyword)?;
+        for portion in &portions {
+            for line in wrap_string(portion, prefix.width() + 1, width) {
+                writeln!(f, "{}\"{}\"", prefix, line)?;
+            }
+        }
+        Ok(())
+    }
+
     pub fn save(&mut self) -> Result<()> {
         if let Some(ref path) = self.path {
             let content = self.to_string();
-            fs::write(path, content)
+            write_atomic(path, &content, self.backup)
                 .with_context(|| format!("Failed to write file: {}", path.display()))?;
+            self.disk_stamp = DiskStamp::read(path);
             self.modified = false;
+            // What could not be read was left out of the file written
+            self.parse_issues.clear();
         }
         Ok(())
     }
@@ -346,109 +736,399 @@ impl PoFile {
     pub fn save_as<P: AsRef<Path>>(&mut self, path: P) -> Result<()> {
         let path = path.as_ref().to_path_buf();
         let content = self.to_string();
-        fs::write(&path, content)
+        write_atomic(&path, &content, self.backup)
             .with_context(|| format!("Failed to write file: {}", path.display()))?;
+        self.disk_stamp = DiskStamp::read(&path);
         self.path = Some(path);
         self.modified = false;
+        self.parse_issues.clear();
         Ok(())
     }
 
-    pub fn to_string(&self) -> String {
-        let mut output = String::new();
+    /// Bring the catalog up to date with the template at `pot`, as
+    /// msgmerge does (see `merge::merge`)
+    pub fn merge_from_pot(&mut self, pot: &Path, options: &MergeOptions) -> Result<MergeSummary> {
+        let content = fs::read_to_string(pot).with_context(|| format!("Failed to read POT file: {}", pot.display()))?;
+        let template = Self::parse(&content)?;
+        Ok(merge::merge(self, &template, options))
+    }
+
+    /// Write the entries to `path` as CSV, for review in a spreadsheet.
+    /// Returns how many were written.
+    pub fn export_csv(&self, path: &Path, options: &CsvOptions) -> Result<usize> {
+        csv::write_csv(self, path, options)
+    }
+
+    /// Read the translations in the CSV file at `path` into the entries with
+    /// their msgctxt and msgid
+    pub fn import_csv(&mut self, path: &Path) -> Result<ImportSummary> {
+        let translations = csv::read_csv(path)?;
+        Ok(exchange::apply(self, translations))
+    }
+
+    /// Write the entries to `path` as XLIFF 1.2, for translation agencies.
+    /// Returns how many were written.
+    pub fn export_xliff(&self, path: &Path) -> Result<usize> {
+        xliff::write_xliff(self, path)
+    }
+
+    /// Read the translations in the XLIFF file at `path` into the entries
+    /// its trans-units stand for
+    pub fn import_xliff(&mut self, path: &Path) -> Result<ImportSummary> {
+        let translations = xliff::read_xliff(path, self)?;
+        Ok(exchange::apply(self, translations))
+    }
+
+    /// Write the entries to `path` as JSON in `style`, for web i18n
+    /// libraries. Returns how many were written.
+    pub fn export_json(&self, path: &Path, style: JsonStyle, options: &JsonOptions) -> Result<usize> {
+        json::write_json(self, path, style, options)
+    }
+
+    /// Read the translations in the JSON file at `path` into the entries
+    /// with their keys, made as `export_json` makes them
+    pub fn import_json(&mut self, path: &Path, style: JsonStyle, key_format: Option<&str>) -> Result<ImportSummary> {
+        let (translations, unknown) = json::read_json(path, self, style, key_format)?;
+        let mut summary = exchange::apply(self, translations);
+        summary.unknown += unknown;
+        Ok(summary)
+    }
+
+    /// Whether something else wrote the file since it was read or saved.
+    /// A file that is gone, or was never read, doesn't count.
+    pub fn changed_on_disk(&self) -> bool {
+        let (Some(path), Some(stamp)) = (self.path.as_ref(), self.disk_stamp.as_ref()) else {
+            return false;
+        };
+        stamp.path == *path && DiskStamp::read(path).is_some_and(|now| now != *stamp)
+    }
+
+    pub fn mark_modified(&mut self) {
+        self.modified = true;
+        self.revision += 1;
+    }
+
+    /// The content is back to how it was saved, e.g. after undo
+    pub fn mark_unmodified(&mut self) {
+        self.modified = false;
+        self.revision += 1;
+    }
+
+    /// The header as the msgstr of the empty msgid, a `Key: value` line per
+    /// field
+    pub fn header_text(&self) -> String {
+        self.header.iter().map(|(key, value)| format!("{}: {}\n", key, value)).collect()
+    }
+
+    /// Add the `Key: value` lines of the header msgstr `text` to the header
+    pub(crate) fn read_header(&mut self, text: &str) {
+        for line in text.lines() {
+            if let Some(colon_pos) = line.find(':') {
+                let key = line[..colon_pos].trim().to_string();
+                let value = line[colon_pos + 1..].trim().to_string();
+                self.header.insert(key, value);
+            }
+        }
+    }
+
+    pub fn get_header(&self) -> &IndexMap<String, String> {
+        &self.header
+    }
+
+    pub fn set_header_field(&mut self, key: String, value: String) {
+        let plural_forms = key == "Plural-Forms";
+        self.header.insert(key, value);
+        if plural_forms {
+            self.pad_plural_forms();
+        }
+        self.mark_modified();
+    }
+
+    /// Give plural entries as many forms as `Plural-Forms` asks for. Extra
+    /// forms are kept rather than thrown away.
+    pub(crate) fn pad_plural_forms(&mut self) {
+        let Some(count) = self.plural_count() else {
+            return;
+        };
+        for entry in self.entries.iter_mut().filter(|entry| entry.is_plural()) {
+            if entry.msgstr_plural.len() < count {
+                entry.msgstr_plural.resize(count, String::new());
+                entry.update_status();
+            }
+        }
+    }
+
+    /// Remove a header field. Returns false if it was not set.
+    pub fn remove_header_field(&mut self, key: &str) -> bool {
+        let removed = self.header.shift_remove(key).is_some();
+        if removed {
+            self.mark_modified();
+        }
+        removed
+    }
+
+    /// Swap the header field at `index` with its neighbour above or below.
+    /// Returns false at either end.
+    pub fn move_header_field(&mut self, index: usize, up: bool) -> bool {
+        let other = if up { index.checked_sub(1) } else { Some(index + 1) };
+        match other.filter(|&other| other < self.header.len() && index < self.header.len()) {
+            Some(other) => {
+                self.header.swap_indices(index, other);
+                self.mark_modified();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Put the header in the order gettext tools write it, with other
+    /// fields after the standard ones in alphabetical order. Returns false
+    /// if the order was already canonical.
+    pub fn sort_header(&mut self) -> bool {
+        let rank = |key: &str| {
+            CANONICAL_HEADER_KEYS
+                .iter()
+                .position(|canonical| *canonical == key)
+                .unwrap_or(CANONICAL_HEADER_KEYS.len())
+        };
+        let mut sorted = self.header.clone();
+        sorted.sort_by(|a, _, b, _| rank(a).cmp(&rank(b)).then_with(|| a.cmp(b)));
+        if sorted.keys().eq(self.header.keys()) {
+            return false;
+        }
+        self.header = sorted;
+        self.mark_modified();
+        true
+    }
+
+    pub fn update_revision_date(&mut self) {
+        let now = chrono::Utc::now();
+        let timestamp = now.format("%Y-%m-%d %H:%M%z").to_string();
+        self.set_header_field("PO-Revision-Date".to_string(), timestamp);
+    }
+
+    pub fn is_modified(&self) -> bool {
+        self.modified
+    }
+
+    /// `Language` header, if set
+    pub fn language(&self) -> Option<&str> {
+        self.header_value("Language")
+    }
+
+    /// `nplurals` from the `Plural-Forms` header
+    pub fn plural_count(&self) -> Option<usize> {
+        let forms = self.header_value("Plural-Forms")?;
+        let value = forms.split(';').find_map(|part| part.trim().strip_prefix("nplurals="))?;
+        value.trim().parse().ok().filter(|&n| n > 0)
+    }
+
+    /// Parsed `PO-Revision-Date`, None while it still holds the template
+    /// placeholder
+    pub fn revision_date(&self) -> Option<chrono::DateTime<chrono::FixedOffset>> {
+        let value = self.header_value("PO-Revision-Date")?;
+        chrono::DateTime::parse_from_str(value, "%Y-%m-%d %H:%M%z").ok()
+    }
+
+    /// Trimmed header value, treating empty values as missing
+    fn header_value(&self, key: &str) -> Option<&str> {
+        self.header.get(key).map(|v| v.trim()).filter(|v| !v.is_empty())
+    }
+
+    pub fn word_stats(&self) -> WordStats {
+        let mut stats = WordStats::default();
+        for entry in &self.entries {
+            let words = count_words(&entry.msgid);
+            stats.total += words;
+            if entry.is_translated {
+                stats.translated += words;
+            } else if entry.is_fuzzy {
+                stats.fuzzy += words;
+            } else {
+                stats.untranslated += words;
+            }
+        }
+        stats
+    }
+
+    pub fn get_stats(&self) -> (usize, usize, usize) {
+        let total = self.entries.len();
+        let translated = self.entries.iter().filter(|e| e.is_translated).count();
+        let fuzzy = self.entries.iter().filter(|e| e.is_fuzzy).count();
+        (total, translated, fuzzy)
+    }
+}
+
+impl fmt::Display for PoFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = (!self.no_wrap).then_some(WRAP_WIDTH);
+        // Blocks are separated by a blank line, with none after the last
+        let mut separate = false;
 
         // Write header
         if !self.header.is_empty() {
-            output.push_str("msgid \"\"\n");
-            output.push_str("msgstr \"\"\n");
-            for (key, value) in &self.header {
-                output.push_str(&format!("\"{}: {}\\n\"\n", key, Self::escape_string(value)));
-            }
-            output.push('\n');
+            writeln!(f, "msgid \"\"")?;
+            Self::write_string(f, "", "msgstr", &self.header_text(), width)?;
+            separate = true;
         }
 
         // Write entries
         for entry in &self.entries {
+            if separate {
+                writeln!(f)?;
+            }
+            separate = true;
+            // Entries flagged no-wrap are left unwrapped, as by msgcat
+            let width = width.filter(|_| !entry.flags.iter().any(|flag| flag == "no-wrap"));
+
             // Write comments
             for comment in &entry.comments {
-                output.push_str(&format!("# {}\n", comment));
+                writeln!(f, "# {}", comment)?;
             }
             
             // Write extracted comments
             for comment in &entry.extracted_comments {
-                output.push_str(&format!("#. {}\n", comment));
+                writeln!(f, "#. {}", comment)?;
             }
             
             // Write references
             for reference in &entry.references {
-                output.push_str(&format!("#: {}\n", reference));
+                writeln!(f, "#: {}", reference)?;
             }
             
             // Write flags
             if !entry.flags.is_empty() {
-                output.push_str(&format!("#, {}\n", entry.flags.join(", ")));
+                writeln!(f, "#, {}", entry.flags.join(", "))?;
+            }
+
+            // Write previous fields
+            let previous = [
+                ("msgctxt", &entry.previous_msgctxt),
+                ("msgid", &entry.previous_msgid),
+                ("msgid_plural", &entry.previous_msgid_plural),
+            ];
+            for (keyword, value) in previous {
+                if let Some(value) = value {
+                    Self::write_string(f, "#| ", keyword, value, width)?;
+                }
             }
 
             // Write msgctxt if present
             if let Some(ref msgctxt) = entry.msgctxt {
-                output.push_str(&format!("msgctxt \"{}\"\n", Self::escape_string(msgctxt)));
+                Self::write_string(f, "", "msgctxt", msgctxt, width)?;
             }
 
             // Write msgid
-            output.push_str(&format!("msgid \"{}\"\n", Self::escape_string(&entry.msgid)));
+            Self::write_string(f, "", "msgid", &entry.msgid, width)?;
+
+            // Write msgid_plural and one msgstr[N] per form
+            if let Some(ref plural) = entry.msgid_plural {
+                Self::write_string(f, "", "msgid_plural", plural, width)?;
+                let forms = entry.msgstr_plural.len().max(self.plural_count().unwrap_or(1));
+                for form in 0..forms {
+                    Self::write_string(f, "", &format!("msgstr[{}]", form), entry.form(form), width)?;
+                }
+                continue;
+            }
             
             // Write msgstr
-            output.push_str(&format!("msgstr \"{}\"\n", Self::escape_string(&entry.msgstr)));
-            
-            output.push('\n');
+            Self::write_string(f, "", "msgstr", &entry.msgstr, width)?;
         }
 
-        output
-    }
-
-    pub fn mark_modified(&mut self) {
-        self.modified = true;
-    }
+        for obsolete in &self.obsolete_entries {
+            if separate {
+                writeln!(f)?;
+            }
+            separate = true;
+            writeln!(f, "{}", obsolete)?;
+        }
 
-    pub fn get_header(&self) -> &HashMap<String, String> {
-        &self.header
+        Ok(())
     }
+}
 
-    pub fn get_header_mut(&mut self) -> &mut HashMap<String, String> {
-        self.modified = true;
-        &mut self.header
+/// Split an escaped string into lines of at most `width` columns, counting
+/// from `column` and leaving one for the closing quote. A word too long for
+/// a line is left whole. No width keeps the string on one line.
+fn wrap_string(text: &str, column: usize, width: Option<usize>) -> Vec<&str> {
+    let Some(width) = width else {
+        return vec![text];
+    };
+    let mut lines = Vec::new();
+    let (mut start, mut used) = (0, column);
+    // Last break opportunity in the line, with the columns used before it
+    let mut opportunity: Option<(usize, usize)> = None;
+    let units = escaped_units(text);
+    for (index, &(byte, unit)) in units.iter().enumerate() {
+        let before = index.checked_sub(2).map(|i| units[i].1);
+        let after = units.get(index + 1).map(|&(_, unit)| unit);
+        if index > 0 && byte > start && can_break(before, units[index - 1].1, unit, after) {
+            if used + 1 > width {
+                // Only reached after a word too long for any line
+                lines.push(&text[start..byte]);
+                (start, used, opportunity) = (byte, column, None);
+            } else {
+                opportunity = Some((byte, used));
+            }
+        }
+        let cells = unit.width();
+        if used + cells + 1 > width {
+            if let Some((at, used_before)) = opportunity.take() {
+                lines.push(&text[start..at]);
+                (start, used) = (at, column + used - used_before);
+            }
+        }
+        used += cells;
     }
+    lines.push(&text[start..]);
+    lines
+}
 
-    pub fn set_header_field(&mut self, key: String, value: String) {
-        self.header.insert(key, value);
-        self.modified = true;
+/// Graphemes of an escaped string with their byte offsets, escapes such as
+/// `\"` counting as one
+fn escaped_units(text: &str) -> Vec<(usize, &str)> {
+    let mut units: Vec<(usize, &str)> = Vec::new();
+    let mut escape = false;
+    for (byte, grapheme) in text.grapheme_indices(true) {
+        match units.last_mut() {
+            Some((start, unit)) if escape => *unit = &text[*start..byte + grapheme.len()],
+            _ => units.push((byte, grapheme)),
+        }
+        escape = !escape && grapheme == "\\";
     }
+    units
+}
 
-    pub fn update_revision_date(&mut self) {
-        let now = chrono::Utc::now();
-        let timestamp = now.format("%Y-%m-%d %H:%M%z").to_string();
-        self.set_header_field("PO-Revision-Date".to_string(), timestamp);
+/// Whether a line may end between `previous` and `next`, approximating the
+/// Unicode line breaking msgcat uses. `before` and `after` are the units
+/// around the two.
+fn can_break(before: Option<&str>, previous: &str, next: &str, after: Option<&str>) -> bool {
+    let quote = |unit: &str| unit.chars().next_back().is_some_and(|c| "'\"“”‘’«»„‚‹›".contains(c));
+    let last = previous.chars().next_back().unwrap_or_default();
+    let first = next.chars().next().unwrap_or_default();
+    // Spaces hang at the end of the line, closing punctuation and slashes
+    // stay with the text before them even across spaces
+    if next == " " || next == "\\n" || ".,:;!?)]}/".contains(first) {
+        return false;
     }
-
-    pub fn is_modified(&self) -> bool {
-        self.modified
+    if previous == " " {
+        // A closing quote followed by punctuation or a space is not an
+        // opening one
+        let closing = "”’»›".contains(first)
+            && after.is_none_or(|after| after == " " || after == "\\n" || after.starts_with(|c| ".,:;!?)]}".contains(c)));
+        return !closing;
     }
-
-    pub fn get_stats(&self) -> (usize, usize, usize) {
-        let total = self.entries.len();
-        let translated = self.entries.iter().filter(|e| e.is_translated).count();
-        let fuzzy = self.entries.iter().filter(|e| e.is_fuzzy).count();
-        (total, translated, fuzzy)
+    if quote(previous) || quote(next) || "([{".contains(last) || "-|\u{2013}".contains(first) {
+        return false;
     }
-}
-
-impl Default for PoFile {
-    fn default() -> Self {
-        Self {
-            path: None,
-            header: HashMap::new(),
-            entries: Vec::new(),
-            modified: false,
-        }
+    match last {
+        // Not after a leading minus, nor between numbers
+        '-' => before.is_some_and(|before| before != " ") && first.is_alphabetic(),
+        '/' => first.is_alphabetic(),
+        '|' | '\u{2013}' | '\u{2014}' | '}' => true,
+        ',' | '.' | ':' | ';' => "%([{".contains(first),
+        ')' | ']' | '$' => "([{".contains(first),
+        _ => cjk_break(last, first),
     }
 }
 
@@ -623,6 +1303,15 @@ msgstr ""
         // Check that PO-Revision-Date is updated
         assert!(po_file.header.get("PO-Revision-Date").unwrap() != "YEAR-MO-DA HO:MI+ZONE");
         assert!(po_file.modified);
+
+        // The template itself is recognized as one, the new catalog is not
+        let mut template = PoFile::parse(pot_content).unwrap();
+        assert!(template.is_template());
+        assert!(!po_file.is_template());
+        template.entries[0].set_msgstr("Привет".to_string());
+        assert!(!template.is_template());
+        template.path = Some(PathBuf::from("messages.pot"));
+        assert!(template.is_template());
     }
 
     #[test]
@@ -639,4 +1328,449 @@ msgstr ""
         let revision_date = po_file.get_header().get("PO-Revision-Date").unwrap();
         assert!(!revision_date.contains("YEAR-MO-DA"));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_parse_records_entry_lines() {
+        let content = "msgid \"\"\nmsgstr \"Language: ru\\n\"\n\n#: main.c:1\nmsgid \"One\"\nmsgstr \"\"\n\nmsgctxt \"menu\"\nmsgid \"Two\"\nmsgstr \"\"\n";
+        let po_file = PoFile::parse(content).unwrap();
+        assert_eq!(po_file.entries[0].line, 5);
+        assert_eq!(po_file.entries[1].line, 9);
+    }
+
+    #[test]
+    fn test_plural_forms_round_trip() {
+        let content = "msgid \"\"\nmsgstr \"\"\n\"Plural-Forms: nplurals=3; plural=(n%10==1 && n%100!=11 ? 0 : n%10>=2 && n%10<=4 && (n%100<10 || n%100>=20) ? 1 : 2);\\n\"\n\n\
+                       #, c-format\nmsgid \"%d file\"\nmsgid_plural \"\"\n\"%d files\"\nmsgstr[0] \"%d файл\"\nmsgstr[1] \"%d \"\n\"файла\"\nmsgstr[2] \"%d файлов\"\n\n\
+                       msgid \"%d day\"\nmsgid_plural \"%d days\"\nmsgstr[0] \"%d день\"\nmsgstr[1] \"%d дня\"\n";
+        let (po_file, diagnostics) = PoFile::parse_with_diagnostics(content);
+        assert!(diagnostics.is_empty(), "{:?}", diagnostics);
+        let files = &po_file.entries[0];
+        assert_eq!(files.msgid_plural.as_deref(), Some("%d files"));
+        assert_eq!(files.msgstr_plural, vec!["%d файл", "%d файла", "%d файлов"]);
+        assert_eq!(files.msgstr, "%d файл");
+        assert!(files.is_translated);
+
+        // Padded to nplurals, and untranslated until every form is filled
+        let mut days = po_file.entries[1].clone();
+        assert_eq!(days.form_count(), 3);
+        assert!(!days.is_translated);
+        days.set_form(2, "%d дней".to_string());
+        assert!(days.is_translated);
+        days.set_msgstr(String::new());
+        assert_eq!(days.msgstr_plural[0], "");
+        assert!(!days.is_translated);
+
+        let saved = po_file.to_string();
+        assert!(saved.contains(
+            "#, c-format\nmsgid \"%d file\"\nmsgid_plural \"%d files\"\nmsgstr[0] \"%d файл\"\nmsgstr[1] \"%d файла\"\nmsgstr[2] \"%d файлов\"\n\n"
+        ));
+        assert!(saved.contains("msgstr[1] \"%d дня\"\nmsgstr[2] \"\"\n"));
+        assert_eq!(PoFile::parse(&saved).unwrap().to_string(), saved);
+
+        // A template keeps its plural forms, emptied
+        let po = po_file.instantiate(Path::new("ru.po"));
+        assert_eq!(po.entries[0].msgstr_plural, vec!["", "", ""]);
+        assert!(!po.entries[0].is_translated);
+    }
+
+    #[test]
+    fn test_previous_msgid_round_trip() {
+        let content = concat!(
+            "#, fuzzy\n#| msgctxt \"menu\"\n#| msgid \"\"\n#| \"Open \"\n#| \"file\"\n",
+            "msgctxt \"menu\"\nmsgid \"Open a file\"\nmsgstr \"Открыть файл\"\n\n",
+            "#, fuzzy\n#| msgid \"%d file\"\n#| msgid_plural \"%d files\"\n",
+            "msgid \"%d document\"\nmsgid_plural \"%d documents\"\nmsgstr[0] \"%d файл\"\nmsgstr[1] \"%d файла\"\n",
+        );
+        let po_file = PoFile::parse(content).unwrap();
+        let entry = &po_file.entries[0];
+        assert_eq!(entry.previous_msgctxt.as_deref(), Some("menu"));
+        assert_eq!(entry.previous_msgid.as_deref(), Some("Open file"));
+        assert!(entry.comments.is_empty());
+        let entry = &po_file.entries[1];
+        assert_eq!(entry.previous_msgid.as_deref(), Some("%d file"));
+        assert_eq!(entry.previous_msgid_plural.as_deref(), Some("%d files"));
+
+        let saved = po_file.to_string();
+        assert!(saved.starts_with("#, fuzzy\n#| msgctxt \"menu\"\n#| msgid \"Open file\"\nmsgctxt"));
+        assert_eq!(PoFile::parse(&saved).unwrap().to_string(), saved);
+    }
+
+    /// A catalog with every string on one line
+    const UNWRAPPED: &str = concat!(
+            "msgid \"\"\n",
+            "msgstr \"\"\n",
+            "\"Content-Type: text/plain; charset=UTF-8\\n\"\n",
+            "\"Plural-Forms: nplurals=3; plural=(n%10==1 && n%100!=11 ? 0 : n%10>=2 && n%10<=4 && (n%100<10 || n%100>=20) ? 1 : 2);\\n\"\n",
+            "\n",
+            "#, fuzzy\n",
+            "#| msgid \"An old and very long source string that used to be here before it was rewritten\"\n",
+            "msgid \"The quick brown fox jumps over the lazy dog and keeps running far away into the forest\"\n",
+            "msgstr \"Съешь же ещё этих мягких французских булок, да выпей чаю, а потом ещё немного чаю\"\n",
+            "\n",
+            "msgid \"First line\\nSecond line\"\n",
+            "msgstr \"Fits on the keyword line\\n\"\n",
+            "\n",
+            "msgid \"Read-only files in /usr/share/locale/ru/LC_MESSAGES can't be changed by mistake\"\n",
+            "msgstr \"Impossible d'écrire « %s » : le fichier est en lecture seule, essayez encore !\"\n",
+            "\n",
+            "msgid \"Use '--files0' instead of '--files' when names contain spaces (see $VARIABLE or ${VARIABLE})\"\n",
+            "msgstr \"日本語のテキストはとても長いです。日本語のテキストはとても長いです。（括弧）日本語のテキスト\"\n",
+            "\n",
+            "msgid \"%d file\"\n",
+            "msgid_plural \"%d files were copied to the destination directory without any errors at all\"\n",
+            "msgstr[0] \"Averyveryverylongwordwithoutanyspacesthatcannotbebrokenanywherewhatsoeverbecauseitislong\"\n",
+            "msgstr[1] \"\"\n",
+            "msgstr[2] \"\"\n",
+            "\n",
+            "#, no-wrap\n",
+            "msgid \"Flagged no-wrap, so this entry stays on a single line however long it happens to be\"\n",
+            "msgstr \"\"\n",
+    );
+
+    /// `msgcat` of the catalog above
+    const MSGCAT: &str = concat!(
+            "msgid \"\"\n",
+            "msgstr \"\"\n",
+            "\"Content-Type: text/plain; charset=UTF-8\\n\"\n",
+            "\"Plural-Forms: nplurals=3; plural=(n%10==1 && n%100!=11 ? 0 : n%10>=2 && \"\n",
+            "\"n%10<=4 && (n%100<10 || n%100>=20) ? 1 : 2);\\n\"\n",
+            "\n",
+            "#, fuzzy\n",
+            "#| msgid \"\"\n",
+            "#| \"An old and very long source string that used to be here before it was \"\n",
+            "#| \"rewritten\"\n",
+            "msgid \"\"\n",
+            "\"The quick brown fox jumps over the lazy dog and keeps running far away into \"\n",
+            "\"the forest\"\n",
+            "msgstr \"\"\n",
+            "\"Съешь же ещё этих мягких французских булок, да выпей чаю, а потом ещё \"\n",
+            "\"немного чаю\"\n",
+            "\n",
+            "msgid \"\"\n",
+            "\"First line\\n\"\n",
+            "\"Second line\"\n",
+            "msgstr \"Fits on the keyword line\\n\"\n",
+            "\n",
+            "msgid \"\"\n",
+            "\"Read-only files in /usr/share/locale/ru/LC_MESSAGES can't be changed by \"\n",
+            "\"mistake\"\n",
+            "msgstr \"\"\n",
+            "\"Impossible d'écrire « %s » : le fichier est en lecture seule, essayez \"\n",
+            "\"encore !\"\n",
+            "\n",
+            "msgid \"\"\n",
+            "\"Use '--files0' instead of '--files' when names contain spaces (see $VARIABLE \"\n",
+            "\"or ${VARIABLE})\"\n",
+            "msgstr \"\"\n",
+            "\"日本語のテキストはとても長いです。日本語のテキストはとても長いです。（括弧）\"\n",
+            "\"日本語のテキスト\"\n",
+            "\n",
+            "msgid \"%d file\"\n",
+            "msgid_plural \"\"\n",
+            "\"%d files were copied to the destination directory without any errors at all\"\n",
+            "msgstr[0] \"Averyveryverylongwordwithoutanyspacesthatcannotbebrokenanywherewhatsoeverbecauseitislong\"\n",
+            "msgstr[1] \"\"\n",
+            "msgstr[2] \"\"\n",
+            "\n",
+            "#, no-wrap\n",
+            "msgid \"Flagged no-wrap, so this entry stays on a single line however long it happens to be\"\n",
+            "msgstr \"\"\n",
+    );
+
+    /// `msgcat --no-wrap` of it
+    const MSGCAT_NO_WRAP: &str = concat!(
+            "msgid \"\"\n",
+            "msgstr \"\"\n",
+            "\"Content-Type: text/plain; charset=UTF-8\\n\"\n",
+            "\"Plural-Forms: nplurals=3; plural=(n%10==1 && n%100!=11 ? 0 : n%10>=2 && n%10<=4 && (n%100<10 || n%100>=20) ? 1 : 2);\\n\"\n",
+            "\n",
+            "#, fuzzy\n",
+            "#| msgid \"An old and very long source string that used to be here before it was rewritten\"\n",
+            "msgid \"The quick brown fox jumps over the lazy dog and keeps running far away into the forest\"\n",
+            "msgstr \"Съешь же ещё этих мягких французских булок, да выпей чаю, а потом ещё немного чаю\"\n",
+            "\n",
+            "msgid \"\"\n",
+            "\"First line\\n\"\n",
+            "\"Second line\"\n",
+            "msgstr \"Fits on the keyword line\\n\"\n",
+            "\n",
+            "msgid \"Read-only files in /usr/share/locale/ru/LC_MESSAGES can't be changed by mistake\"\n",
+            "msgstr \"Impossible d'écrire « %s » : le fichier est en lecture seule, essayez encore !\"\n",
+            "\n",
+            "msgid \"Use '--files0' instead of '--files' when names contain spaces (see $VARIABLE or ${VARIABLE})\"\n",
+            "msgstr \"日本語のテキストはとても長いです。日本語のテキストはとても長いです。（括弧）日本語のテキスト\"\n",
+            "\n",
+            "msgid \"%d file\"\n",
+            "msgid_plural \"%d files were copied to the destination directory without any errors at all\"\n",
+            "msgstr[0] \"Averyveryverylongwordwithoutanyspacesthatcannotbebrokenanywherewhatsoeverbecauseitislong\"\n",
+            "msgstr[1] \"\"\n",
+            "msgstr[2] \"\"\n",
+            "\n",
+            "#, no-wrap\n",
+            "msgid \"Flagged no-wrap, so this entry stays on a single line however long it happens to be\"\n",
+            "msgstr \"\"\n",
+    );
+
+    #[test]
+    fn test_strings_are_wrapped_like_msgcat() {
+        assert_eq!(PoFile::parse(UNWRAPPED).unwrap().to_string(), MSGCAT);
+        assert_eq!(PoFile::parse(MSGCAT).unwrap().to_string(), MSGCAT);
+
+        let mut po_file = PoFile::parse(MSGCAT).unwrap();
+        po_file.no_wrap = true;
+        assert_eq!(po_file.to_string(), MSGCAT_NO_WRAP);
+    }
+
+    #[test]
+    fn test_wrap_string() {
+        // The closing quote fits at column 79, a space counts as well
+        let words = format!("{} yyyy zzzz", "x".repeat(71));
+        assert_eq!(wrap_string(&words, 1, Some(WRAP_WIDTH)), vec![format!("{} yyyy ", "x".repeat(71)), "zzzz".to_string()]);
+        let words = format!("{} yyyy zzzz", "x".repeat(72));
+        assert_eq!(wrap_string(&words, 1, Some(WRAP_WIDTH)), vec![format!("{} ", "x".repeat(72)), "yyyy zzzz".to_string()]);
+        // Escapes stay whole, and a line break stays with its line
+        let escaped = format!("{} \\\"a\\\" \\n", "x".repeat(72));
+        assert_eq!(wrap_string(&escaped, 1, Some(WRAP_WIDTH)), vec![format!("{} ", "x".repeat(72)), "\\\"a\\\" \\n".to_string()]);
+        assert_eq!(wrap_string("a b", 1, None), vec!["a b"]);
+    }
+
+    #[test]
+    fn test_obsolete_entries_round_trip() {
+        let content = concat!(
+            "msgid \"Open\"\nmsgstr \"Открыть\"\n\n",
+            "# Removed in 2.0\n#, fuzzy\n#~ msgctxt \"menu\"\n#~ msgid \"\"\n#~ \"Close \"\n#~ \"all\"\n#~ msgstr \"Закрыть всё\"\n\n",
+            "#~| msgid \"Quit\"\n#~ msgid \"Exit\"\n#~ msgstr \"Выход\"\n",
+        );
+        let po_file = PoFile::parse(content).unwrap();
+        assert_eq!(po_file.entries.len(), 1);
+        assert_eq!(po_file.obsolete_entries.len(), 2);
+        assert_eq!(po_file.to_string(), content);
+    }
+
+    #[test]
+    fn test_parse_wrapped_strings() {
+        let content = concat!(
+            "msgctxt \"\"\n\"file \"\n\"menu\"\n",
+            "msgid \"\"\n\"Open the \\\"recent\\\" \"\n\"files\\n\"\n\"\"\n",
+            "msgid_plural \"\"\n\"Open \"\n\"all\"\n",
+            "msgstr[0] \"\"\n\"Открыть \"\n\"файл\"\n",
+            "msgstr[1] \"Открыть \"\n\"файлы\"\n\n",
+            "msgid \"\"\n\"Save \"\n\"as\"\nmsgstr \"\"\n\"Сохранить \"\n\"как\"\n",
+        );
+        let po_file = PoFile::parse(content).unwrap();
+        assert_eq!(po_file.entries.len(), 2);
+        let open = &po_file.entries[0];
+        assert_eq!(open.msgctxt.as_deref(), Some("file menu"));
+        assert_eq!(open.msgid, "Open the \"recent\" files\n");
+        assert_eq!(open.msgid_plural.as_deref(), Some("Open all"));
+        assert_eq!(open.msgstr_plural, vec!["Открыть файл", "Открыть файлы"]);
+        assert_eq!((po_file.entries[1].msgid.as_str(), po_file.entries[1].msgstr.as_str()), ("Save as", "Сохранить как"));
+
+        // A bad continuation line is reported without cutting the rest off
+        let (po_file, diagnostics) =
+            PoFile::parse_with_diagnostics("msgid \"\"\n\"Save \"\n\"all\\\"\n\"files\"\nmsgstr \"Сохранить\"\n");
+        assert_eq!(diagnostics, vec!["Line 3: Failed to parse msgid string literal: unterminated string \"all\\\""]);
+        assert_eq!(po_file.entries[0].msgid, "Save files");
+        assert_eq!(po_file.entries[0].msgstr, "Сохранить");
+    }
+
+    #[test]
+    fn test_only_first_empty_msgid_is_header() {
+        let content = concat!(
+            "\n# Header comment\nmsgid \"\"\nmsgstr \"\"\n\"Language: ru\\n\"\n\n",
+            "msgid \"Open\"\nmsgstr \"Открыть\"\n\n",
+            "msgid \"\"\nmsgstr \"\"\n\"Language: de\\n\"\n\n",
+            "msgctxt \"empty\"\nmsgid \"\"\nmsgstr \"Пусто\"\n",
+        );
+        let (po_file, diagnostics) = PoFile::parse_with_diagnostics(content);
+        assert_eq!(po_file.language(), Some("ru"));
+        assert_eq!(diagnostics, vec!["Line 10: Entry with an empty msgid ignored"]);
+        assert_eq!(po_file.entries.len(), 2);
+        assert_eq!(po_file.entries[1].msgctxt.as_deref(), Some("empty"));
+
+        // Without a header the first entry is a normal one
+        let po_file = PoFile::parse("msgid \"Open\"\nmsgstr \"\"\n\nmsgid \"\"\nmsgstr \"Language: de\\n\"\n").unwrap();
+        assert_eq!(po_file.entries.len(), 1);
+        assert!(po_file.header.is_empty());
+    }
+
+    #[test]
+    fn test_parse_diagnostics() {
+        let content = "msgid \"One\"\nmsgstr \"Один\"\n\n\
+                       # Unused since 2.0\n#~ msgid \"Old\"\n#~ msgstr \"\"\n#~ \"Старый\"\n\n\
+                       msgid \"File\"\nmsgid_plural \"Files\"\nmsgstr[0] \"Файл\"\n\n\
+                       msgid Two\nmsgstr \"Два\"\n";
+        let (po_file, diagnostics) = PoFile::parse_with_diagnostics(content);
+        assert_eq!(
+            diagnostics,
+            vec![
+                "Line 13: Failed to parse msgid: expected a quoted string",
+            ]
+        );
+        assert_eq!(po_file.entries.len(), 2);
+        assert_eq!(po_file.entries[1].msgstr_plural, vec!["Файл"]);
+        assert_eq!(po_file.entries[0].msgstr, "Один");
+        assert_eq!(
+            po_file.obsolete_entries,
+            vec!["# Unused since 2.0\n#~ msgid \"Old\"\n#~ msgstr \"\"\n#~ \"Старый\""]
+        );
+
+        let (_, diagnostics) = PoFile::parse_with_diagnostics("msgid \"One\"\nmsgstr \"Один\"\n");
+        assert!(diagnostics.is_empty());
+    }
+
+    #[test]
+    fn test_header_accessors() {
+        let template = PoFile::new(PathBuf::from("new.po"));
+        assert_eq!(template.language(), None);
+        assert_eq!(template.plural_count(), None);
+        assert_eq!(template.revision_date(), None);
+
+        let mut po_file = PoFile::default();
+        po_file.set_header_field("Language".to_string(), " ru ".to_string());
+        po_file.set_header_field(
+            "Plural-Forms".to_string(),
+            "nplurals=3; plural=(n%10==1 && n%100!=11 ? 0 : 1);".to_string(),
+        );
+        po_file.set_header_field("PO-Revision-Date".to_string(), "2025-03-01 14:30+0300".to_string());
+        assert_eq!(po_file.language(), Some("ru"));
+        assert_eq!(po_file.plural_count(), Some(3));
+        assert_eq!(po_file.revision_date().unwrap().to_rfc3339(), "2025-03-01T14:30:00+03:00");
+    }
+
+    #[test]
+    fn test_word_stats() {
+        assert_eq!(count_words("Open %s file - now"), 4);
+        assert_eq!(count_words("  \n "), 0);
+
+        let mut po_file = PoFile::default();
+        for (msgid, msgstr, fuzzy) in [("One two", "Раз два", false), ("Three four five", "Три", true), ("Six", "", false)] {
+            let mut entry = PoEntry::new();
+            entry.msgid = msgid.to_string();
+            entry.msgstr = msgstr.to_string();
+            if fuzzy {
+                entry.flags.push("fuzzy".to_string());
+            }
+            entry.update_status();
+            po_file.entries.push(entry);
+        }
+
+        let stats = po_file.word_stats();
+        assert_eq!(
+            stats,
+            WordStats {
+                total: 6,
+                translated: 2,
+                fuzzy: 3,
+                untranslated: 1
+            }
+        );
+        assert_eq!(stats.remaining(), 4);
+    }
+
+    #[test]
+    fn test_removed_header_field_is_not_saved() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ru.po");
+        let mut po_file = PoFile::new(path.clone());
+        po_file.set_header_field("X-Poedit-Basepath".to_string(), "..".to_string());
+        po_file.save().unwrap();
+
+        assert!(po_file.remove_header_field("X-Poedit-Basepath"));
+        assert!(po_file.is_modified());
+        assert!(!po_file.remove_header_field("X-Poedit-Basepath"));
+        po_file.save().unwrap();
+
+        let reloaded = PoFile::from_file(&path).unwrap();
+        assert!(!reloaded.get_header().contains_key("X-Poedit-Basepath"));
+        assert!(reloaded.get_header().contains_key("Content-Type"));
+    }
+
+    #[test]
+    fn test_save_replaces_file_atomically() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ru.po");
+        fs::write(&path, "msgid \"Open\"\nmsgstr \"\"\n").unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o640)).unwrap();
+        }
+        let mut po_file = PoFile::from_file(&path).unwrap();
+        po_file.entries[0].set_msgstr("Открыть".to_string());
+        po_file.backup = true;
+        po_file.save().unwrap();
+
+        assert!(fs::read_to_string(&path).unwrap().contains("Открыть"));
+        assert_eq!(fs::read_to_string(dir.path().join("ru.po.bak")).unwrap(), "msgid \"Open\"\nmsgstr \"\"\n");
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o640);
+        }
+        let names = || {
+            let mut names: Vec<_> = fs::read_dir(dir.path()).unwrap().map(|e| e.unwrap().file_name()).collect();
+            names.sort();
+            names
+        };
+        assert_eq!(names(), ["ru.po", "ru.po.bak"]);
+
+        // A save that fails leaves the file as it was
+        let saved = fs::read_to_string(&path).unwrap();
+        fs::remove_file(dir.path().join("ru.po.bak")).unwrap();
+        fs::create_dir(dir.path().join("ru.po.bak")).unwrap();
+        po_file.entries[0].set_msgstr("Открыть файл".to_string());
+        let error = po_file.save().unwrap_err();
+        assert!(format!("{:#}", error).contains("Failed to write the backup"), "{:#}", error);
+        assert_eq!(fs::read_to_string(&path).unwrap(), saved);
+        assert_eq!(names(), ["ru.po", "ru.po.bak"]);
+    }
+
+    #[test]
+    fn test_changed_on_disk() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ru.po");
+        fs::write(&path, "msgid \"Open\"\nmsgstr \"\"\n").unwrap();
+        let mut po_file = PoFile::from_file(&path).unwrap();
+        assert!(!po_file.changed_on_disk());
+
+        fs::write(&path, "msgid \"Open\"\nmsgstr \"Открыть\"\n").unwrap();
+        assert!(po_file.changed_on_disk());
+        po_file.save().unwrap();
+        assert!(!po_file.changed_on_disk());
+
+        fs::remove_file(&path).unwrap();
+        assert!(!po_file.changed_on_disk());
+        assert!(!PoFile::new(path).changed_on_disk());
+    }
+
+    #[test]
+    fn test_header_order_is_kept_and_sorted() {
+        let content = "msgid \"\"\nmsgstr \"\"\n\"X-Generator: Poedit 3.4\\n\"\n\"Language: ru\\n\"\n\"Content-Type: text/plain; charset=UTF-8\\n\"\n\"Project-Id-Version: app 1.0\\n\"\n\"X-Bugs: none\\n\"\n\nmsgid \"One\"\nmsgstr \"\"\n";
+        let mut po_file = PoFile::parse(content).unwrap();
+        let keys = |po_file: &PoFile| po_file.get_header().keys().cloned().collect::<Vec<_>>();
+        assert_eq!(keys(&po_file), ["X-Generator", "Language", "Content-Type", "Project-Id-Version", "X-Bugs"]);
+        assert!(po_file.to_string().starts_with("msgid \"\"\nmsgstr \"\"\n\"X-Generator: Poedit 3.4\\n\"\n\"Language: ru"));
+
+        assert!(po_file.move_header_field(1, true));
+        assert!(!po_file.move_header_field(0, true));
+        assert!(!po_file.move_header_field(4, false));
+        assert_eq!(keys(&po_file)[..2], ["Language", "X-Generator"]);
+
+        assert!(po_file.sort_header());
+        assert_eq!(keys(&po_file), ["Project-Id-Version", "Language", "Content-Type", "X-Bugs", "X-Generator"]);
+        assert!(!po_file.sort_header());
+        let reparsed = PoFile::parse(&po_file.to_string()).unwrap();
+        assert_eq!(keys(&reparsed), keys(&po_file));
+
+        // New fields go last, changed ones stay where they are
+        po_file.set_header_field("Language".to_string(), "uk".to_string());
+        po_file.set_header_field("Last-Translator".to_string(), "Me".to_string());
+        assert_eq!(keys(&po_file)[1], "Language");
+        assert_eq!(keys(&po_file).last().unwrap(), "Last-Translator");
+
+        // New files are written in the gettext order
+        let new = PoFile::new(PathBuf::from("new.po"));
+        assert_eq!(keys(&new), CANONICAL_HEADER_KEYS);
+    }
+}
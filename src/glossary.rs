@@ -0,0 +1,197 @@
+// Poterm - Modern TUI editor for .po translation files
+// Copyright (c) 2025 AnmiTaliDev <anmitali198@gmail.com>
+// Licensed under the Apache License, Version 2.0
+
+//! Project termbase: source terms with their required translation
+//!
+//! The file has one term per line, fields separated by tabs (or commas when
+//! a line has no tab): the source term, its translation and optionally
+//! variants that must not be used, separated by `|`: `tab,вкладка,таб|табка`.
+//! Empty lines and lines starting with `#` are skipped.
+
+use aho_corasick::{AhoCorasick, MatchKind};
+use anyhow::{bail, Context, Result};
+use std::fs;
+use std::path::Path;
+
+/// Endings still matching a term, e.g. `tabs` for `tab`
+const SUFFIXES: [&str; 5] = ["s", "es", "ed", "ing", "'s"];
+
+/// Characters an inflected translation may differ by at the end of a word
+const STEM_TRIM: usize = 2;
+/// Stems are never cut shorter than this
+const MIN_STEM: usize = 4;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Term {
+    pub source: String,
+    pub translation: String,
+    /// Translations to avoid
+    pub forbidden: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Glossary {
+    terms: Vec<Term>,
+    /// Lowercased source terms, in `terms` order
+    matcher: AhoCorasick,
+}
+
+impl Glossary {
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path).with_context(|| format!("Failed to read glossary: {}", path.display()))?;
+        let terms = parse(&content).with_context(|| format!("Invalid glossary: {}", path.display()))?;
+        Self::new(terms)
+    }
+
+    fn new(terms: Vec<Term>) -> Result<Self> {
+        let matcher = AhoCorasick::builder()
+            .match_kind(MatchKind::LeftmostLongest)
+            .build(terms.iter().map(|term| term.source.to_lowercase()))?;
+        Ok(Self { terms, matcher })
+    }
+
+    pub fn len(&self) -> usize {
+        self.terms.len()
+    }
+
+    /// Terms occurring as words in `text`, in order of first appearance
+    pub fn terms_in(&self, text: &str) -> Vec<&Term> {
+        let text = text.to_lowercase();
+        let mut found: Vec<&Term> = Vec::new();
+        for hit in self.matcher.find_iter(&text) {
+            let term = &self.terms[hit.pattern().as_usize()];
+            if is_word_at(&text, hit.start(), hit.end()) && !found.iter().any(|t| std::ptr::eq(*t, term)) {
+                found.push(term);
+            }
+        }
+        found
+    }
+}
+
+impl Term {
+    /// A forbidden variant used in `msgstr`
+    pub fn forbidden_in(&self, msgstr: &str) -> Option<&str> {
+        let msgstr = msgstr.to_lowercase();
+        self.forbidden
+            .iter()
+            .find(|variant| contains_phrase(&msgstr, &variant.to_lowercase()))
+            .map(String::as_str)
+    }
+
+    /// Whether `msgstr` has the required translation in some inflected form
+    pub fn translated_in(&self, msgstr: &str) -> bool {
+        contains_phrase(&msgstr.to_lowercase(), &self.translation.to_lowercase())
+    }
+}
+
+fn parse(content: &str) -> Result<Vec<Term>> {
+    let mut terms = Vec::new();
+    for (i, line) in content.lines().enumerate() {
+        let line = line.trim_end_matches('\r');
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let separator = if line.contains('\t') { '\t' } else { ',' };
+        let mut fields = line.split(separator).map(str::trim);
+        let (Some(source), Some(translation)) = (fields.next(), fields.next()) else {
+            bail!("Line {}: expected a term and its translation", i + 1);
+        };
+        if source.is_empty() || translation.is_empty() {
+            bail!("Line {}: expected a term and its translation", i + 1);
+        }
+        let forbidden = fields
+            .next()
+            .unwrap_or_default()
+            .split('|')
+            .map(str::trim)
+            .filter(|variant| !variant.is_empty())
+            .map(str::to_string)
+            .collect();
+        terms.push(Term {
+            source: source.to_string(),
+            translation: translation.to_string(),
+            forbidden,
+        });
+    }
+    Ok(terms)
+}
+
+/// `text[start..end]` starts a word and ends one, or ends in a suffix that
+/// does
+fn is_word_at(text: &str, start: usize, end: usize) -> bool {
+    if text[..start].chars().next_back().is_some_and(char::is_alphanumeric) {
+        return false;
+    }
+    let rest = &text[end..];
+    let tail_len = rest.find(|c: char| !c.is_alphanumeric() && c != '\'').unwrap_or(rest.len());
+    let tail = &rest[..tail_len];
+    tail.is_empty() || SUFFIXES.contains(&tail)
+}
+
+/// Every word of `phrase` appears as a word in `text`, with its last few
+/// characters free to vary
+fn contains_phrase(text: &str, phrase: &str) -> bool {
+    phrase.split_whitespace().all(|word| {
+        let stem = stem(word);
+        let max_len = word.chars().count() + STEM_TRIM;
+        text.match_indices(stem).any(|(i, _)| {
+            let word_len = text[i..].chars().take_while(|c| c.is_alphanumeric()).count();
+            !text[..i].chars().next_back().is_some_and(char::is_alphanumeric) && word_len <= max_len
+        })
+    })
+}
+
+/// `вкладка` -> `вклад`; short words are kept whole
+fn stem(word: &str) -> &str {
+    let len = word.chars().count();
+    let keep = len.saturating_sub(STEM_TRIM).max(MIN_STEM).min(len);
+    match word.char_indices().nth(keep) {
+        Some((end, _)) => &word[..end],
+        None => word,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn glossary(content: &str) -> Glossary {
+        Glossary::new(parse(content).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        let glossary = glossary("# Terms\ntab\tвкладка\tтаб|табка\n\nfile,файл\n");
+        assert_eq!(glossary.len(), 2);
+        assert_eq!(glossary.terms[0].forbidden, vec!["таб", "табка"]);
+        assert_eq!(glossary.terms[1].translation, "файл");
+        assert!(parse("tab\n").is_err());
+        assert!(parse("tab\t\n").is_err());
+    }
+
+    #[test]
+    fn test_terms_in() {
+        let glossary = glossary("tab\tвкладка\nfile\tфайл\nfile name\tимя файла\n");
+        let sources = |text| glossary.terms_in(text).iter().map(|term| term.source.as_str()).collect::<Vec<_>>();
+        assert_eq!(sources("Close the Tab"), vec!["tab"]);
+        assert_eq!(sources("Close all tabs"), vec!["tab"]);
+        assert_eq!(sources("Table of files"), vec!["file"]);
+        assert_eq!(sources("File name of the file"), vec!["file name", "file"]);
+        assert!(sources("Profile").is_empty());
+    }
+
+    #[test]
+    fn test_translations() {
+        let term = Term {
+            source: "tab".to_string(),
+            translation: "вкладка".to_string(),
+            forbidden: vec!["таб".to_string()],
+        };
+        assert!(term.translated_in("Закрыть вкладку"));
+        assert!(term.translated_in("Вкладки"));
+        assert!(!term.translated_in("Закрыть таб"));
+        assert_eq!(term.forbidden_in("Закрыть ТАБ"), Some("таб"));
+        assert_eq!(term.forbidden_in("Таблица"), None);
+    }
+}
@@ -0,0 +1,241 @@
+// Poterm - Modern TUI editor for .po translation files
+// Copyright (c) 2025 AnmiTaliDev <anmitali198@gmail.com>
+// Licensed under the Apache License, Version 2.0
+
+//! Translation progress appended to a CSV log on every save, to chart a
+//! catalog's progress over time
+
+use crate::config::Config;
+use crate::gettext::PoFile;
+use crate::state::StateStore;
+use crate::stats::{format_count, percent};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local};
+use serde::Serialize;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// A log growing past this is moved to `<log>.1`, replacing the previous one
+const MAX_LOG_SIZE: u64 = 1024 * 1024;
+
+const CSV_HEADER: &str =
+    "time,total,translated,fuzzy,untranslated,words,words_translated,words_fuzzy,words_untranslated,path\n";
+
+/// Output of `poterm stats`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum OutputFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+/// Statistics of a catalog as it was saved
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Record {
+    pub time: DateTime<Local>,
+    /// Canonical path
+    pub path: PathBuf,
+    pub total: usize,
+    pub translated: usize,
+    pub fuzzy: usize,
+    pub untranslated: usize,
+    pub words: usize,
+    pub words_translated: usize,
+    pub words_fuzzy: usize,
+    pub words_untranslated: usize,
+}
+
+impl Record {
+    pub fn new(path: PathBuf, po_file: &PoFile, time: DateTime<Local>) -> Self {
+        let (total, translated, fuzzy) = po_file.get_stats();
+        let words = po_file.word_stats();
+        Self {
+            time,
+            path,
+            total,
+            translated,
+            fuzzy,
+            untranslated: total - translated - fuzzy,
+            words: words.total,
+            words_translated: words.translated,
+            words_fuzzy: words.fuzzy,
+            words_untranslated: words.untranslated,
+        }
+    }
+
+    /// The path goes last and quoted, so commas in it need no care when
+    /// reading the numbers back
+    fn to_csv(&self) -> String {
+        format!(
+            "{},{},{},{},{},{},{},{},{},\"{}\"\n",
+            self.time.to_rfc3339(),
+            self.total,
+            self.translated,
+            self.fuzzy,
+            self.untranslated,
+            self.words,
+            self.words_translated,
+            self.words_fuzzy,
+            self.words_untranslated,
+            self.path.display().to_string().replace('"', "\"\"")
+        )
+    }
+
+    fn from_csv(line: &str) -> Option<Self> {
+        let fields: Vec<&str> = line.splitn(10, ',').collect();
+        let [time, numbers @ .., path] = &fields[..] else {
+            return None;
+        };
+        let numbers: Vec<usize> = numbers.iter().map(|n| n.parse().ok()).collect::<Option<_>>()?;
+        let [total, translated, fuzzy, untranslated, words, words_translated, words_fuzzy, words_untranslated] =
+            numbers[..]
+        else {
+            return None;
+        };
+        let path = path.strip_prefix('"')?.strip_suffix('"')?.replace("\"\"", "\"");
+        Some(Self {
+            time: DateTime::parse_from_rfc3339(time).ok()?.with_timezone(&Local),
+            path: PathBuf::from(path),
+            total,
+            translated,
+            fuzzy,
+            untranslated,
+            words,
+            words_translated,
+            words_fuzzy,
+            words_untranslated,
+        })
+    }
+}
+
+/// The configured log. A relative path is kept next to the state file.
+pub fn log_path(config: &Config) -> Option<PathBuf> {
+    let log = config.progress_log.as_ref()?;
+    let dir = StateStore::default_path()
+        .and_then(|state| state.parent().map(Path::to_path_buf))
+        .unwrap_or_default();
+    Some(dir.join(log))
+}
+
+/// `progress.csv` -> `progress.csv.1`
+fn rotated_path(log: &Path) -> PathBuf {
+    let mut name = log.as_os_str().to_owned();
+    name.push(".1");
+    PathBuf::from(name)
+}
+
+pub fn append(log: &Path, record: &Record) -> Result<()> {
+    if let Some(dir) = log.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    }
+    if fs::metadata(log).is_ok_and(|metadata| metadata.len() >= MAX_LOG_SIZE) {
+        fs::rename(log, rotated_path(log)).with_context(|| format!("Failed to rotate {}", log.display()))?;
+    }
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(log)
+        .with_context(|| format!("Failed to open {}", log.display()))?;
+    let mut text = record.to_csv();
+    if file.metadata()?.len() == 0 {
+        text.insert_str(0, CSV_HEADER);
+    }
+    file.write_all(text.as_bytes())
+        .with_context(|| format!("Failed to write {}", log.display()))
+}
+
+/// Recorded saves of the catalog at the canonical `path`, oldest first.
+/// Lines that cannot be read are skipped.
+pub fn read(log: &Path, path: &Path) -> Vec<Record> {
+    [rotated_path(log), log.to_path_buf()]
+        .iter()
+        .filter_map(|file| fs::read_to_string(file).ok())
+        .flat_map(|content| content.lines().filter_map(Record::from_csv).collect::<Vec<_>>())
+        .filter(|record| record.path == path)
+        .collect()
+}
+
+/// Headless `poterm stats --history`
+pub fn run(files: &[PathBuf], config: &Config, format: OutputFormat) -> Result<()> {
+    let log = log_path(config).context("No progress log is configured (set `progress_log` in the config file)")?;
+    let mut all = Vec::new();
+    for path in files {
+        let canonical = path.canonicalize().with_context(|| format!("Failed to read {}", path.display()))?;
+        let records = read(&log, &canonical);
+        if format == OutputFormat::Text {
+            println!("{}:", path.display());
+            if records.is_empty() {
+                println!("  No saves recorded");
+            }
+            for record in &records {
+                println!("  {}", summary(record));
+            }
+        }
+        all.extend(records);
+    }
+    if format == OutputFormat::Json {
+        println!("{}", serde_json::to_string_pretty(&all)?);
+    }
+    Ok(())
+}
+
+/// e.g. `2026-10-16 14:02  412/500 translated (82.4%), 30 fuzzy, 1,200 words left`
+fn summary(record: &Record) -> String {
+    format!(
+        "{}  {}/{} translated ({:.1}%), {} fuzzy, {} words left",
+        record.time.format("%Y-%m-%d %H:%M"),
+        format_count(record.translated),
+        format_count(record.total),
+        percent(record.translated, record.total),
+        format_count(record.fuzzy),
+        format_count(record.words_fuzzy + record.words_untranslated)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(path: &Path) -> Record {
+        let po_file = PoFile::parse(
+            "msgid \"Open the file\"\nmsgstr \"Открыть файл\"\n\n#, fuzzy\nmsgid \"Save all\"\nmsgstr \"Сохранить\"\n\nmsgid \"Quit\"\nmsgstr \"\"\n",
+        )
+        .unwrap();
+        let time = DateTime::parse_from_rfc3339("2026-10-16T14:02:00+02:00").unwrap().with_timezone(&Local);
+        Record::new(path.to_path_buf(), &po_file, time)
+    }
+
+    #[test]
+    fn test_append_and_read() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = dir.path().join("state").join("progress.csv");
+        let ru = PathBuf::from("/home/me/po, \"new\"/ru.po");
+        let record = record(&ru);
+        assert_eq!((record.total, record.translated, record.fuzzy, record.untranslated), (3, 1, 1, 1));
+        assert_eq!((record.words, record.words_translated, record.words_fuzzy), (6, 3, 2));
+
+        append(&log, &record).unwrap();
+        append(&log, &Record::new(PathBuf::from("/fr.po"), &PoFile::default(), record.time)).unwrap();
+        append(&log, &record).unwrap();
+        let content = fs::read_to_string(&log).unwrap();
+        assert!(content.starts_with(CSV_HEADER));
+        assert_eq!(content.lines().count(), 4);
+        assert_eq!(read(&log, &ru), vec![record.clone(), record.clone()]);
+        assert_eq!(summary(&record).split_once("  ").unwrap().1, "1/3 translated (33.3%), 1 fuzzy, 3 words left");
+    }
+
+    #[test]
+    fn test_log_is_rotated() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = dir.path().join("progress.csv");
+        let ru = PathBuf::from("/ru.po");
+        fs::write(&log, format!("{}{}", CSV_HEADER, record(&ru).to_csv().repeat(30_000))).unwrap();
+        assert!(fs::metadata(&log).unwrap().len() >= MAX_LOG_SIZE);
+
+        append(&log, &record(&ru)).unwrap();
+        assert_eq!(fs::read_to_string(&log).unwrap().lines().count(), 2);
+        // The rotated part is still read, older records first
+        assert_eq!(read(&log, &ru).len(), 30_001);
+    }
+}
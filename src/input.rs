@@ -0,0 +1,237 @@
+// Poterm - Modern TUI editor for .po translation files
+// Copyright (c) 2025 AnmiTaliDev <anmitali198@gmail.com>
+// Licensed under the Apache License, Version 2.0
+
+use crate::ui::App;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::ops::Range;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthChar;
+
+/// Char ranges of the words of `text` by Unicode word boundaries. Runs of
+/// whitespace and punctuation between them are not words.
+fn words(text: &str) -> Vec<Range<usize>> {
+    let mut words = Vec::new();
+    let mut start = 0;
+    for segment in text.split_word_bounds() {
+        let len = segment.chars().count();
+        if segment.chars().any(char::is_alphanumeric) {
+            words.push(start..start + len);
+        }
+        start += len;
+    }
+    words
+}
+
+/// Start of the word before the char index `cursor`, or of the word it is in
+pub fn previous_word_start(text: &str, cursor: usize) -> usize {
+    words(text).into_iter().rev().find(|word| word.start < cursor).map_or(0, |word| word.start)
+}
+
+/// End of the word after the char index `cursor`, or of the word it is in
+pub fn next_word_end(text: &str, cursor: usize) -> usize {
+    words(text)
+        .into_iter()
+        .find(|word| word.end > cursor)
+        .map_or_else(|| text.chars().count(), |word| word.end)
+}
+
+/// Apply a key that moves or deletes by words or to the end of the line:
+/// Ctrl+Left/Right, Ctrl+Backspace (or Ctrl+W), Ctrl+Delete and Ctrl+K.
+/// `cursor` is a char index into `text`. Returns false for other keys.
+pub fn edit_by_words(text: &mut String, cursor: &mut usize, key: KeyEvent) -> bool {
+    if !key.modifiers.contains(KeyModifiers::CONTROL) {
+        return false;
+    }
+    let delete = |text: &mut String, range: Range<usize>| {
+        let start = App::char_to_byte_index(text, range.start);
+        let end = App::char_to_byte_index(text, range.end);
+        text.replace_range(start..end, "");
+    };
+    match key.code {
+        KeyCode::Left => *cursor = previous_word_start(text, *cursor),
+        KeyCode::Right => *cursor = next_word_end(text, *cursor),
+        KeyCode::Backspace | KeyCode::Char('w') => {
+            let start = previous_word_start(text, *cursor);
+            delete(text, start..*cursor);
+            *cursor = start;
+        }
+        KeyCode::Delete => {
+            let end = next_word_end(text, *cursor);
+            delete(text, *cursor..end);
+        }
+        // At the end of a line the line break goes, joining the next line
+        KeyCode::Char('k') => {
+            let rest = text.chars().skip(*cursor).take_while(|&c| c != '\n').count();
+            let end = if rest == 0 && *cursor < text.chars().count() { *cursor + 1 } else { *cursor + rest };
+            delete(text, *cursor..end);
+        }
+        _ => return false,
+    }
+    true
+}
+
+/// Single-line text input used by prompts and overlays. The cursor is a
+/// character index, like the main edit buffer.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TextInput {
+    pub text: String,
+    pub cursor: usize,
+}
+
+impl TextInput {
+    pub fn new(text: &str) -> Self {
+        Self {
+            text: text.to_string(),
+            cursor: text.chars().count(),
+        }
+    }
+
+    /// Apply an editing key. Returns false for keys that are not handled so
+    /// the caller can interpret them (Enter, Esc, ...).
+    pub fn handle_key(&mut self, key: KeyEvent) -> bool {
+        if edit_by_words(&mut self.text, &mut self.cursor, key) {
+            return true;
+        }
+        match key.code {
+            KeyCode::Char(c) => {
+                App::insert_char_at(&mut self.text, self.cursor, c);
+                self.cursor += 1;
+            }
+            KeyCode::Backspace => {
+                if self.cursor > 0 {
+                    self.cursor -= 1;
+                    App::remove_char_at(&mut self.text, self.cursor);
+                }
+            }
+            KeyCode::Delete => {
+                if self.cursor < self.text.chars().count() {
+                    App::remove_char_at(&mut self.text, self.cursor);
+                }
+            }
+            KeyCode::Left => self.cursor = self.cursor.saturating_sub(1),
+            KeyCode::Right => self.cursor = (self.cursor + 1).min(self.text.chars().count()),
+            KeyCode::Home => self.cursor = 0,
+            KeyCode::End => self.cursor = self.text.chars().count(),
+            _ => return false,
+        }
+        true
+    }
+
+    /// The part of the input that fits in `width` columns with the cursor
+    /// in view, scrolled horizontally as little as possible. The cursor
+    /// after the last character takes a column of its own.
+    pub fn window(&self, width: usize) -> TextInput {
+        let widths: Vec<usize> = self.text.chars().map(|c| c.width().unwrap_or(0)).collect();
+        let cursor = self.cursor.min(widths.len());
+        let cursor_width = widths.get(cursor).copied().unwrap_or(1).max(1);
+        let mut start = 0;
+        while start < cursor && widths[start..cursor].iter().sum::<usize>() + cursor_width > width {
+            start += 1;
+        }
+        let mut used = 0;
+        let end = start
+            + widths[start..]
+                .iter()
+                .take_while(|&&w| {
+                    used += w;
+                    used <= width
+                })
+                .count();
+        TextInput {
+            text: self.text.chars().skip(start).take(end - start).collect(),
+            cursor: cursor - start,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::KeyModifiers;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn test_text_input_editing() {
+        let mut input = TextInput::new("мир");
+        assert_eq!(input.cursor, 3);
+
+        input.handle_key(key(KeyCode::Home));
+        input.handle_key(key(KeyCode::Char('!')));
+        assert_eq!(input.text, "!мир");
+
+        input.handle_key(key(KeyCode::End));
+        input.handle_key(key(KeyCode::Backspace));
+        input.handle_key(key(KeyCode::Left));
+        input.handle_key(key(KeyCode::Delete));
+        assert_eq!(input.text, "!м");
+        assert_eq!(input.cursor, 2);
+
+        assert!(!input.handle_key(key(KeyCode::Enter)));
+    }
+
+    #[test]
+    fn test_word_boundaries() {
+        let text = "Сохранить «файл» как…  日本語";
+        assert_eq!(next_word_end(text, 0), 9);
+        assert_eq!(next_word_end(text, 9), 15);
+        assert_eq!(next_word_end(text, 15), 20);
+        assert_eq!(previous_word_start(text, 20), 17);
+        assert_eq!(previous_word_start(text, 17), 11);
+        assert_eq!(previous_word_start(text, 12), 11);
+        // Each ideograph is a word of its own
+        assert_eq!(previous_word_start(text, 26), 25);
+        assert_eq!(next_word_end(text, 26), 26);
+        assert_eq!(previous_word_start("…", 1), 0);
+        // Apostrophes and combining marks stay inside the word
+        assert_eq!(next_word_end("don't stop", 0), 5);
+        assert_eq!(next_word_end("cafe\u{301} stop", 0), 5);
+    }
+
+    #[test]
+    fn test_word_keys() {
+        let ctrl = |code| KeyEvent::new(code, KeyModifiers::CONTROL);
+        let mut input = TextInput::new("Открыть файл, быстро\nещё");
+        input.cursor = 8;
+        input.handle_key(ctrl(KeyCode::Delete));
+        assert_eq!(input.text, "Открыть , быстро\nещё");
+        input.handle_key(ctrl(KeyCode::Backspace));
+        assert_eq!((input.text.as_str(), input.cursor), (", быстро\nещё", 0));
+        input.handle_key(ctrl(KeyCode::Right));
+        assert_eq!(input.cursor, 8);
+        input.handle_key(ctrl(KeyCode::Char('w')));
+        assert_eq!(input.text, ", \nещё");
+        // Ctrl+K kills the rest of the line, then the line break
+        input.cursor = 1;
+        input.handle_key(ctrl(KeyCode::Char('k')));
+        assert_eq!(input.text, ",\nещё");
+        input.handle_key(ctrl(KeyCode::Char('k')));
+        assert_eq!(input.text, ",ещё");
+        assert!(!edit_by_words(&mut input.text, &mut input.cursor, ctrl(KeyCode::Char('x'))));
+    }
+
+    #[test]
+    fn test_text_input_window() {
+        let mut input = TextInput::new("поиск по строке");
+        // The cursor at the end needs a column after the text
+        assert_eq!(input.window(20), input);
+        let window = input.window(8);
+        assert_eq!((window.text.as_str(), window.cursor), (" строке", 7));
+        assert_eq!(input.window(7).text, "строке");
+        input.cursor = 3;
+        let window = input.window(8);
+        assert_eq!((window.text.as_str(), window.cursor), ("поиск по", 3));
+        input.cursor = 10;
+        let window = input.window(8);
+        assert_eq!((window.text.as_str(), window.cursor), ("ск по ст", 7));
+
+        // Wide characters take two columns, including under the cursor
+        let mut input = TextInput::new("日本語の検索");
+        input.cursor = 3;
+        let window = input.window(5);
+        assert_eq!((window.text.as_str(), window.cursor), ("語の", 1));
+    }
+}
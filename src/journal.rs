@@ -0,0 +1,279 @@
+// Poterm - Modern TUI editor for .po translation files
+// Copyright (c) 2025 AnmiTaliDev <anmitali198@gmail.com>
+// Licensed under the Apache License, Version 2.0
+
+//! Swap file next to the open catalog recording committed entry edits, so
+//! the changes of a session that died before saving can be recovered, and
+//! the recovery file autosave writes the whole unsaved catalog to
+
+use crate::gettext::{EntryKey, PoEntry};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// Appended changes reach the disk at least this often
+const SYNC_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Entry edit as it was committed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Change {
+    pub msgid: String,
+    pub msgstr: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub msgstr_plural: Vec<String>,
+    pub flags: Vec<String>,
+    pub comments: Vec<String>,
+    /// The entry before the edit
+    pub key: EntryKey,
+}
+
+impl Change {
+    pub fn new(before: &PoEntry, after: &PoEntry) -> Self {
+        Self {
+            msgid: after.msgid.clone(),
+            msgstr: after.msgstr.clone(),
+            msgstr_plural: after.msgstr_plural.clone(),
+            flags: after.flags.clone(),
+            comments: after.comments.clone(),
+            key: before.key(),
+        }
+    }
+
+    pub fn apply(&self, entry: &mut PoEntry) {
+        entry.msgid = self.msgid.clone();
+        entry.flags = self.flags.clone();
+        entry.comments = self.comments.clone();
+        entry.msgstr_plural = self.msgstr_plural.clone();
+        entry.set_msgstr(self.msgstr.clone());
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Header {
+    pid: u32,
+    started: DateTime<Local>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Changes {
+    change: Vec<Change>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SwapFile {
+    pid: u32,
+    started: DateTime<Local>,
+    #[serde(default)]
+    change: Vec<Change>,
+}
+
+/// Swap file found when opening a catalog
+#[derive(Debug, Clone, PartialEq)]
+pub struct Swap {
+    pub path: PathBuf,
+    pub pid: u32,
+    pub started: DateTime<Local>,
+    pub changes: Vec<Change>,
+    /// The poterm that wrote it is still running, as far as can be told
+    pub running: bool,
+}
+
+impl Swap {
+    /// The swap file of `catalog`, if there is one. A record cut short by a
+    /// crash is dropped.
+    pub fn find(catalog: &Path) -> Option<Self> {
+        let path = swap_path(catalog);
+        let content = fs::read_to_string(&path).ok()?;
+        let file: SwapFile = toml::from_str(&content).ok().or_else(|| {
+            let complete = &content[..content.rfind("\n[[change]]")?];
+            toml::from_str(complete).ok()
+        })?;
+        Some(Self {
+            running: file.pid != std::process::id() && process_running(file.pid),
+            path,
+            pid: file.pid,
+            started: file.started,
+            changes: file.change,
+        })
+    }
+
+    pub fn remove(&self) -> Result<()> {
+        fs::remove_file(&self.path).with_context(|| format!("Failed to remove {}", self.path.display()))
+    }
+}
+
+/// Copy of the unsaved catalog written by autosave
+#[derive(Debug, Clone, PartialEq)]
+pub struct Recovery {
+    pub path: PathBuf,
+    pub written: DateTime<Local>,
+}
+
+impl Recovery {
+    /// The recovery file of `catalog`, unless the catalog was saved after it
+    pub fn find(catalog: &Path) -> Option<Self> {
+        let path = recovery_path(catalog);
+        let written = fs::metadata(&path).and_then(|metadata| metadata.modified()).ok()?;
+        let saved = fs::metadata(catalog).and_then(|metadata| metadata.modified()).ok();
+        if saved.is_some_and(|saved| saved > written) {
+            return None;
+        }
+        Some(Self { path, written: written.into() })
+    }
+}
+
+/// `po/ru.po` -> `po/.ru.po.poterm-recover`
+pub fn recovery_path(catalog: &Path) -> PathBuf {
+    let name = catalog.file_name().unwrap_or_default().to_string_lossy();
+    catalog.with_file_name(format!(".{}.poterm-recover", name))
+}
+
+/// `po/ru.po` -> `po/.ru.po.poterm-swap`
+pub fn swap_path(catalog: &Path) -> PathBuf {
+    let name = catalog.file_name().unwrap_or_default().to_string_lossy();
+    catalog.with_file_name(format!(".{}.poterm-swap", name))
+}
+
+#[cfg(target_os = "linux")]
+fn process_running(pid: u32) -> bool {
+    Path::new("/proc").join(pid.to_string()).exists()
+}
+
+/// Without a way to tell, a swap file is assumed to be left over
+#[cfg(not(target_os = "linux"))]
+fn process_running(_pid: u32) -> bool {
+    false
+}
+
+/// Swap file this session appends its changes to
+#[derive(Debug)]
+pub struct Journal {
+    path: PathBuf,
+    file: File,
+    unsynced: bool,
+    last_sync: Instant,
+}
+
+impl Journal {
+    /// Start the swap file of `catalog`, replacing any previous one
+    pub fn create(catalog: &Path) -> Result<Self> {
+        let path = swap_path(catalog);
+        let mut journal = Self {
+            file: open_empty(&path)?,
+            path,
+            unsynced: false,
+            last_sync: Instant::now(),
+        };
+        journal.write_header()?;
+        Ok(journal)
+    }
+
+    fn write_header(&mut self) -> Result<()> {
+        let header = Header {
+            pid: std::process::id(),
+            started: Local::now(),
+        };
+        self.append(&toml::to_string(&header)?)
+    }
+
+    fn append(&mut self, text: &str) -> Result<()> {
+        self.file
+            .write_all(text.as_bytes())
+            .with_context(|| format!("Failed to write {}", self.path.display()))?;
+        self.unsynced = true;
+        Ok(())
+    }
+
+    pub fn record(&mut self, change: Change) -> Result<()> {
+        let text = toml::to_string(&Changes { change: vec![change] })?;
+        self.append(&format!("\n{}", text))
+    }
+
+    /// Flush appended changes to the disk if they waited long enough
+    pub fn sync_if_due(&mut self, now: Instant) -> Result<()> {
+        if self.unsynced && now.duration_since(self.last_sync) >= SYNC_INTERVAL {
+            self.file.sync_data().with_context(|| format!("Failed to sync {}", self.path.display()))?;
+            self.unsynced = false;
+            self.last_sync = now;
+        }
+        Ok(())
+    }
+
+    /// Forget the recorded changes once the catalog is saved
+    pub fn clear(&mut self) -> Result<()> {
+        self.file = open_empty(&self.path)?;
+        self.write_header()
+    }
+
+    /// Delete the swap file on a clean close
+    pub fn remove(self) -> Result<()> {
+        fs::remove_file(&self.path).with_context(|| format!("Failed to remove {}", self.path.display()))
+    }
+}
+
+/// `path` emptied and opened for appending
+fn open_empty(path: &Path) -> Result<File> {
+    fs::write(path, "").with_context(|| format!("Failed to create {}", path.display()))?;
+    OpenOptions::new()
+        .append(true)
+        .open(path)
+        .with_context(|| format!("Failed to open {}", path.display()))
+}
+
+/// Index of the entry `change` was made to
+pub fn find_entry(entries: &[PoEntry], change: &Change) -> Option<usize> {
+    entries
+        .iter()
+        .position(|entry| entry.msgctxt == change.key.msgctxt && entry.msgid == change.key.msgid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gettext::PoFile;
+
+    #[test]
+    fn test_swap_path() {
+        assert_eq!(swap_path(Path::new("po/ru.po")), PathBuf::from("po/.ru.po.poterm-swap"));
+        assert_eq!(recovery_path(Path::new("po/ru.po")), PathBuf::from("po/.ru.po.poterm-recover"));
+    }
+
+    #[test]
+    fn test_journal_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let catalog = dir.path().join("ru.po");
+        let po_file = PoFile::parse("msgctxt \"menu\"\nmsgid \"Open\"\nmsgstr \"\"\n\nmsgid \"Quit\"\nmsgstr \"\"\n").unwrap();
+        let before = &po_file.entries[0];
+        let mut after = before.clone();
+        after.set_msgstr("Открыть\n\"файл\"".to_string());
+        after.toggle_fuzzy();
+
+        let mut journal = Journal::create(&catalog).unwrap();
+        journal.record(Change::new(before, &after)).unwrap();
+        let swap = Swap::find(&catalog).unwrap();
+        assert_eq!(swap.pid, std::process::id());
+        // A swap file of this very process is not another instance
+        assert!(!swap.running);
+        assert_eq!(swap.changes.len(), 1);
+        let change = &swap.changes[0];
+        assert_eq!(find_entry(&po_file.entries, change), Some(0));
+        let mut entry = before.clone();
+        change.apply(&mut entry);
+        assert_eq!(entry, after);
+
+        // A change cut short is dropped, the complete ones kept
+        journal.record(Change::new(&po_file.entries[1], &po_file.entries[1])).unwrap();
+        let content = fs::read_to_string(swap_path(&catalog)).unwrap();
+        fs::write(swap_path(&catalog), &content[..content.len() - 10]).unwrap();
+        assert_eq!(Swap::find(&catalog).unwrap().changes.len(), 1);
+
+        journal.clear().unwrap();
+        assert!(Swap::find(&catalog).unwrap().changes.is_empty());
+        journal.remove().unwrap();
+        assert!(Swap::find(&catalog).is_none());
+    }
+}
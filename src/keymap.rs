@@ -0,0 +1,892 @@
+// Poterm - Modern TUI editor for .po translation files
+// Copyright (c) 2025 AnmiTaliDev <anmitali198@gmail.com>
+// Licensed under the Apache License, Version 2.0
+
+use crate::config::{Config, KeymapPreset};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+/// Logical editor commands. Key events are resolved to actions through the
+/// keymap so that features like macros work independently of the bindings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Quit,
+    /// Quit and drop unsaved changes without asking
+    QuitWithoutSaving,
+    Save,
+    SaveAndQuit,
+    SaveCurrentEntry,
+    /// Save to a path typed in a prompt
+    SaveAs,
+    Next,
+    Previous,
+    PageUp,
+    PageDown,
+    First,
+    Last,
+    StartEditing,
+    /// Start editing the translation regardless of the selected field
+    EditTranslation,
+    Cancel,
+    NextField,
+    PreviousField,
+    StartSearch,
+    /// Narrow the entry list as you type
+    QuickFilter,
+    FindNext,
+    FindPrevious,
+    ToggleUntranslatedFilter,
+    ToggleFuzzyFilter,
+    /// Only entries with a given flag
+    FilterByFlag,
+    /// Only entries referenced from a given source file
+    FilterBySource,
+    /// Select an entry by its number
+    GoToEntry,
+    /// Select the next entry with the same msgid and another translation
+    NextInconsistent,
+    /// Select the next entry without a translation, wrapping around
+    NextUntranslated,
+    /// Select the next fuzzy entry, wrapping around
+    NextFuzzy,
+    /// Search and replace in the translations
+    Replace,
+    /// Only entries changed since the last save
+    ToggleChangedFilter,
+    /// Only entries with a mark
+    ToggleMarkedFilter,
+    ToggleHelp,
+    ToggleMetadataMode,
+    /// Remove the selected header field in metadata mode
+    DeleteHeaderField,
+    MoveHeaderFieldUp,
+    MoveHeaderFieldDown,
+    /// Put the header fields in gettext's canonical order
+    SortHeaderFields,
+    ToggleFuzzy,
+    /// Flag the translation for review without making it fuzzy
+    ToggleNeedsWork,
+    /// Only entries flagged for review
+    ToggleNeedsWorkFilter,
+    /// Only entries the QA checks find problems in
+    ToggleIssuesFilter,
+    MarkDone,
+    /// Switch the entry list numbering (file / filter position / off)
+    CycleIndexColumn,
+    /// Show the first source reference of each entry in the list
+    ToggleReferenceColumn,
+    /// Switch the entry list between a flat list and groups by source file
+    ToggleTreeView,
+    /// Close the source file group of the selection (tree view)
+    CollapseGroup,
+    /// Open the selected source file group (tree view)
+    ExpandGroup,
+    /// Show validation issues for the whole catalog
+    ToggleQaPanel,
+    /// Catalog statistics and what was done this session
+    ShowStats,
+    /// Browse for another catalog to open
+    OpenFile,
+    /// Switch to a recently opened catalog
+    RecentFiles,
+    /// Create a .po for a language from the open template
+    CreateFromTemplate,
+    /// Bring the catalog up to date with a regenerated template
+    MergeTemplate,
+    /// Write the catalog as a binary .mo next to it
+    CompileMo,
+    /// Write the entries to a file for another tool, e.g. a spreadsheet
+    Export,
+    /// Read translations back from an exported file
+    Import,
+    /// Run a shell command on the current entry or edit buffer
+    PipeCommand,
+    /// Replace the abbreviation before the cursor with its snippet
+    ExpandSnippet,
+    /// List the configured snippets
+    ShowSnippets,
+    /// Copy the edit buffer, or the selected field when not editing
+    Copy,
+    /// Copy the edit buffer and clear it
+    Cut,
+    /// Insert the clipboard at the edit cursor
+    Paste,
+    /// Read the glossary file again
+    ReloadGlossary,
+    /// Put a translation memory suggestion (1-based) into the entry
+    AcceptSuggestion(usize),
+    /// Read the open file again from disk, dropping unsaved changes
+    Reload,
+    /// Show the translation with sample values for its placeholders
+    ShowPreview,
+    /// Type a character that is hard to enter on a keyboard, e.g. a
+    /// non-breaking space
+    InsertChar(char),
+    /// Pick a character to insert by codepoint or name
+    InsertUnicode,
+    /// Insert the language's quotation marks, or switch the pair around the
+    /// cursor to the next style
+    CycleQuotes,
+    /// Suspend to the shell (Unix job control)
+    Suspend,
+    /// Revert the last entry or header change
+    Undo,
+    /// Make the last undone change again
+    Redo,
+    ToggleMacroRecording,
+    PlayMacro(usize),
+    /// Remember the current entry under a digit
+    SetMark(u8),
+    JumpToMark(u8),
+    /// Literal input for the active edit buffer or search query
+    Input(KeyEvent),
+}
+
+struct Binding {
+    keys: Vec<(KeyModifiers, KeyCode)>,
+    action: Action,
+}
+
+impl Binding {
+    fn new(modifiers: KeyModifiers, code: KeyCode, action: Action) -> Self {
+        Self {
+            keys: vec![(modifiers, code)],
+            action,
+        }
+    }
+
+    /// Multi-key sequence of plain characters, e.g. `gg`
+    fn sequence(chars: &str, action: Action) -> Self {
+        Self {
+            keys: chars.chars().map(|c| (KeyModifiers::NONE, KeyCode::Char(c))).collect(),
+            action,
+        }
+    }
+}
+
+enum Lookup {
+    Found(Action),
+    Prefix,
+    NotFound,
+}
+
+pub struct Keymap {
+    preset: KeymapPreset,
+    /// Bindings that apply in every mode
+    global: Vec<Binding>,
+    /// Bindings that only apply while not editing or searching
+    browse: Vec<Binding>,
+    /// Keys typed so far of a multi-key sequence
+    pending: Vec<KeyEvent>,
+    /// Numeric count prefix typed before a command
+    count: Option<usize>,
+    /// Vim command line being typed after ':'
+    command_line: Option<String>,
+    /// Feedback for the status line, e.g. an unknown ':' command
+    message: Option<String>,
+}
+
+impl Keymap {
+    pub fn new() -> Self {
+        Self::with_preset(KeymapPreset::Default)
+    }
+
+    /// The configured preset with the `[typography]` keys bound on top
+    pub fn from_config(config: &Config) -> Self {
+        let mut keymap = Self::with_preset(config.keymap);
+        for (key, action) in config.typography.bindings().unwrap_or_default() {
+            keymap.bind(key, action);
+        }
+        keymap
+    }
+
+    pub fn with_preset(preset: KeymapPreset) -> Self {
+        let (global, browse) = match preset {
+            KeymapPreset::Default => Self::default_bindings(),
+            KeymapPreset::Vim => Self::vim_bindings(),
+        };
+
+        Self {
+            preset,
+            global,
+            browse,
+            pending: Vec::new(),
+            count: None,
+            command_line: None,
+            message: None,
+        }
+    }
+
+    fn default_bindings() -> (Vec<Binding>, Vec<Binding>) {
+        use Action::*;
+        let none = KeyModifiers::NONE;
+        let ctrl = KeyModifiers::CONTROL;
+        let shift = KeyModifiers::SHIFT;
+        let alt = KeyModifiers::ALT;
+
+        let global = vec![
+            Binding::new(ctrl, KeyCode::Char('q'), Quit),
+            Binding::new(ctrl | shift, KeyCode::Char('Q'), QuitWithoutSaving),
+            Binding::new(ctrl | shift, KeyCode::Char('q'), QuitWithoutSaving),
+            // Most terminals send Ctrl+Shift+Q as Ctrl+Q
+            Binding::new(alt, KeyCode::Char('q'), QuitWithoutSaving),
+            Binding::new(ctrl, KeyCode::Char('s'), Save),
+            Binding::new(ctrl | shift, KeyCode::Char('S'), SaveAs),
+            Binding::new(ctrl | shift, KeyCode::Char('s'), SaveAs),
+            Binding::new(none, KeyCode::F(12), SaveAs),
+            Binding::new(ctrl, KeyCode::Char('p'), SaveCurrentEntry),
+            Binding::new(shift, KeyCode::Char('p'), SaveCurrentEntry),
+            Binding::new(none, KeyCode::Enter, StartEditing),
+            Binding::new(none, KeyCode::Esc, Cancel),
+            Binding::new(none, KeyCode::Tab, NextField),
+            Binding::new(shift, KeyCode::BackTab, PreviousField),
+            Binding::new(ctrl, KeyCode::Char('f'), StartSearch),
+            Binding::new(none, KeyCode::F(3), FindNext),
+            Binding::new(shift, KeyCode::F(3), FindPrevious),
+            Binding::new(ctrl, KeyCode::Char('u'), ToggleUntranslatedFilter),
+            Binding::new(alt, KeyCode::Char('z'), ToggleFuzzyFilter),
+            Binding::new(ctrl, KeyCode::Char('z'), Suspend),
+            // Ctrl+Z is the shell's, so undo takes the CUA Alt+Backspace
+            Binding::new(alt, KeyCode::Backspace, Undo),
+            Binding::new(ctrl, KeyCode::Char('y'), Redo),
+            Binding::new(none, KeyCode::F(1), ToggleHelp),
+            Binding::new(none, KeyCode::F(9), ToggleMetadataMode),
+            Binding::new(none, KeyCode::F(2), ToggleFuzzy),
+            Binding::new(none, KeyCode::F(4), ToggleNeedsWork),
+            Binding::new(ctrl, KeyCode::Char('d'), MarkDone),
+            Binding::new(ctrl, KeyCode::Char('t'), ToggleFuzzy),
+            Binding::new(none, KeyCode::F(8), ToggleQaPanel),
+            Binding::new(none, KeyCode::F(7), ShowStats),
+            Binding::new(ctrl, KeyCode::Char('o'), OpenFile),
+            Binding::new(ctrl, KeyCode::Char('n'), CreateFromTemplate),
+            Binding::new(alt, KeyCode::Char('t'), MergeTemplate),
+            Binding::new(alt, KeyCode::Char('b'), CompileMo),
+            Binding::new(alt, KeyCode::Char('x'), Export),
+            Binding::new(alt, KeyCode::Char('i'), Import),
+            Binding::new(alt, KeyCode::Char('f'), FilterByFlag),
+            Binding::new(alt, KeyCode::Char('s'), FilterBySource),
+            Binding::new(ctrl, KeyCode::Char('g'), GoToEntry),
+            Binding::new(alt, KeyCode::Char('n'), NextInconsistent),
+            Binding::new(alt, KeyCode::Char('d'), ToggleChangedFilter),
+            Binding::new(alt, KeyCode::Char('w'), ToggleNeedsWorkFilter),
+            Binding::new(ctrl, KeyCode::Char('e'), ToggleIssuesFilter),
+            Binding::new(alt, KeyCode::Char('m'), ToggleMarkedFilter),
+            Binding::new(alt, KeyCode::Char('o'), RecentFiles),
+            Binding::new(alt, KeyCode::Char('|'), PipeCommand),
+            Binding::new(alt, KeyCode::Char('e'), ExpandSnippet),
+            Binding::new(none, KeyCode::F(6), ShowSnippets),
+            Binding::new(alt, KeyCode::Char('g'), ReloadGlossary),
+            Binding::new(alt, KeyCode::Char('1'), AcceptSuggestion(1)),
+            Binding::new(alt, KeyCode::Char('2'), AcceptSuggestion(2)),
+            Binding::new(alt, KeyCode::Char('3'), AcceptSuggestion(3)),
+            Binding::new(none, KeyCode::F(5), ShowPreview),
+            Binding::new(shift, KeyCode::F(5), Reload),
+            Binding::new(ctrl | shift, KeyCode::Char('U'), InsertUnicode),
+            Binding::new(ctrl | shift, KeyCode::Char('u'), InsertUnicode),
+            Binding::new(alt, KeyCode::Char('u'), InsertUnicode),
+            Binding::new(ctrl, KeyCode::Char('c'), Copy),
+            Binding::new(ctrl, KeyCode::Char('x'), Cut),
+            Binding::new(ctrl, KeyCode::Char('v'), Paste),
+        ];
+
+        // Plain letters and cursor keys belong to the text while editing
+        // or searching
+        let browse = vec![
+            Binding::new(none, KeyCode::Up, Previous),
+            Binding::new(none, KeyCode::Char('k'), Previous),
+            Binding::new(none, KeyCode::Down, Next),
+            Binding::new(none, KeyCode::Char('j'), Next),
+            Binding::new(none, KeyCode::PageUp, PageUp),
+            Binding::new(none, KeyCode::PageDown, PageDown),
+            Binding::new(none, KeyCode::Home, First),
+            Binding::new(none, KeyCode::End, Last),
+            Binding::new(none, KeyCode::Char('i'), StartEditing),
+            Binding::new(ctrl, KeyCode::Right, NextUntranslated),
+            Binding::new(alt, KeyCode::Right, NextFuzzy),
+            Binding::new(ctrl, KeyCode::Char('r'), Replace),
+            Binding::new(none, KeyCode::Char('/'), QuickFilter),
+            Binding::new(none, KeyCode::Char('|'), PipeCommand),
+            Binding::new(none, KeyCode::Char('#'), CycleIndexColumn),
+            Binding::new(none, KeyCode::Char('R'), ToggleReferenceColumn),
+            Binding::new(none, KeyCode::Char('t'), ToggleTreeView),
+            Binding::new(none, KeyCode::Left, CollapseGroup),
+            Binding::new(none, KeyCode::Right, ExpandGroup),
+            Binding::new(none, KeyCode::Char('d'), DeleteHeaderField),
+            Binding::new(none, KeyCode::Delete, DeleteHeaderField),
+            Binding::new(alt, KeyCode::Up, MoveHeaderFieldUp),
+            Binding::new(alt, KeyCode::Down, MoveHeaderFieldDown),
+            Binding::new(none, KeyCode::Char('S'), SortHeaderFields),
+            Binding::new(none, KeyCode::Char('q'), ToggleMacroRecording),
+            Binding::new(none, KeyCode::Char('@'), PlayMacro(1)),
+        ];
+
+        (global, Self::with_marks(browse))
+    }
+
+    /// Normal mode is the browse context, insert mode is editing. Only
+    /// modifier and function keys stay global so text typed in insert mode
+    /// reaches the editor untouched.
+    fn vim_bindings() -> (Vec<Binding>, Vec<Binding>) {
+        use Action::*;
+        let none = KeyModifiers::NONE;
+        let ctrl = KeyModifiers::CONTROL;
+        let shift = KeyModifiers::SHIFT;
+        let alt = KeyModifiers::ALT;
+
+        let global = vec![
+            Binding::new(ctrl, KeyCode::Char('q'), Quit),
+            Binding::new(ctrl | shift, KeyCode::Char('Q'), QuitWithoutSaving),
+            Binding::new(ctrl | shift, KeyCode::Char('q'), QuitWithoutSaving),
+            Binding::new(alt, KeyCode::Char('q'), QuitWithoutSaving),
+            Binding::new(ctrl, KeyCode::Char('s'), Save),
+            Binding::new(ctrl | shift, KeyCode::Char('S'), SaveAs),
+            Binding::new(ctrl | shift, KeyCode::Char('s'), SaveAs),
+            Binding::new(none, KeyCode::F(12), SaveAs),
+            Binding::new(ctrl, KeyCode::Char('z'), Suspend),
+            Binding::new(none, KeyCode::Esc, Cancel),
+            Binding::new(ctrl, KeyCode::Char('f'), StartSearch),
+            Binding::new(none, KeyCode::F(1), ToggleHelp),
+            Binding::new(none, KeyCode::F(2), ToggleFuzzy),
+            Binding::new(none, KeyCode::F(4), ToggleNeedsWork),
+            Binding::new(none, KeyCode::F(3), FindNext),
+            Binding::new(shift, KeyCode::F(3), FindPrevious),
+            Binding::new(none, KeyCode::F(9), ToggleMetadataMode),
+            Binding::new(ctrl, KeyCode::Char('t'), ToggleFuzzy),
+            Binding::new(none, KeyCode::F(8), ToggleQaPanel),
+            Binding::new(none, KeyCode::F(7), ShowStats),
+            Binding::new(ctrl, KeyCode::Char('o'), OpenFile),
+            Binding::new(ctrl, KeyCode::Char('n'), CreateFromTemplate),
+            Binding::new(alt, KeyCode::Char('t'), MergeTemplate),
+            Binding::new(alt, KeyCode::Char('b'), CompileMo),
+            Binding::new(alt, KeyCode::Char('x'), Export),
+            Binding::new(alt, KeyCode::Char('i'), Import),
+            Binding::new(alt, KeyCode::Char('f'), FilterByFlag),
+            Binding::new(alt, KeyCode::Char('s'), FilterBySource),
+            Binding::new(ctrl, KeyCode::Char('g'), GoToEntry),
+            Binding::new(alt, KeyCode::Char('n'), NextInconsistent),
+            Binding::new(alt, KeyCode::Char('d'), ToggleChangedFilter),
+            Binding::new(alt, KeyCode::Char('w'), ToggleNeedsWorkFilter),
+            Binding::new(ctrl, KeyCode::Char('e'), ToggleIssuesFilter),
+            Binding::new(alt, KeyCode::Char('m'), ToggleMarkedFilter),
+            Binding::new(alt, KeyCode::Char('o'), RecentFiles),
+            Binding::new(alt, KeyCode::Char('|'), PipeCommand),
+            Binding::new(alt, KeyCode::Char('e'), ExpandSnippet),
+            Binding::new(none, KeyCode::F(6), ShowSnippets),
+            Binding::new(alt, KeyCode::Char('g'), ReloadGlossary),
+            Binding::new(alt, KeyCode::Char('1'), AcceptSuggestion(1)),
+            Binding::new(alt, KeyCode::Char('2'), AcceptSuggestion(2)),
+            Binding::new(alt, KeyCode::Char('3'), AcceptSuggestion(3)),
+            Binding::new(none, KeyCode::F(5), ShowPreview),
+            Binding::new(shift, KeyCode::F(5), Reload),
+            Binding::new(ctrl | shift, KeyCode::Char('U'), InsertUnicode),
+            Binding::new(ctrl | shift, KeyCode::Char('u'), InsertUnicode),
+            Binding::new(alt, KeyCode::Char('u'), InsertUnicode),
+            Binding::new(ctrl, KeyCode::Char('c'), Copy),
+            Binding::new(ctrl, KeyCode::Char('x'), Cut),
+            Binding::new(ctrl, KeyCode::Char('v'), Paste),
+        ];
+
+        let browse = vec![
+            Binding::new(none, KeyCode::Char('|'), PipeCommand),
+            Binding::new(none, KeyCode::Char('#'), CycleIndexColumn),
+            Binding::new(none, KeyCode::Char('R'), ToggleReferenceColumn),
+            Binding::new(none, KeyCode::Char('t'), ToggleTreeView),
+            Binding::new(none, KeyCode::Char('h'), CollapseGroup),
+            Binding::new(none, KeyCode::Left, CollapseGroup),
+            Binding::new(none, KeyCode::Char('l'), ExpandGroup),
+            Binding::new(none, KeyCode::Right, ExpandGroup),
+            Binding::new(none, KeyCode::Char('j'), Next),
+            Binding::new(none, KeyCode::Down, Next),
+            Binding::new(none, KeyCode::Char('k'), Previous),
+            Binding::new(none, KeyCode::Up, Previous),
+            Binding::sequence("gg", First),
+            Binding::sequence("]u", NextUntranslated),
+            Binding::sequence("]f", NextFuzzy),
+            Binding::new(alt, KeyCode::Char('r'), Replace),
+            Binding::new(ctrl, KeyCode::Right, NextUntranslated),
+            Binding::new(alt, KeyCode::Right, NextFuzzy),
+            Binding::new(none, KeyCode::Home, First),
+            Binding::new(none, KeyCode::Char('G'), Last),
+            Binding::new(none, KeyCode::End, Last),
+            Binding::new(ctrl, KeyCode::Char('d'), PageDown),
+            Binding::new(none, KeyCode::PageDown, PageDown),
+            Binding::new(ctrl, KeyCode::Char('u'), PageUp),
+            Binding::new(none, KeyCode::PageUp, PageUp),
+            Binding::new(none, KeyCode::Char('/'), StartSearch),
+            Binding::new(none, KeyCode::Char('\\'), QuickFilter),
+            Binding::new(none, KeyCode::Char('n'), FindNext),
+            Binding::new(none, KeyCode::Char('N'), FindPrevious),
+            Binding::new(none, KeyCode::Char('i'), EditTranslation),
+            Binding::new(none, KeyCode::Enter, StartEditing),
+            Binding::new(none, KeyCode::Tab, NextField),
+            Binding::new(shift, KeyCode::BackTab, PreviousField),
+            Binding::new(none, KeyCode::Delete, DeleteHeaderField),
+            Binding::new(alt, KeyCode::Up, MoveHeaderFieldUp),
+            Binding::new(alt, KeyCode::Down, MoveHeaderFieldDown),
+            Binding::new(none, KeyCode::Char('S'), SortHeaderFields),
+            Binding::new(none, KeyCode::Char('u'), Undo),
+            Binding::new(ctrl, KeyCode::Char('r'), Redo),
+            Binding::new(none, KeyCode::Char('q'), ToggleMacroRecording),
+            Binding::new(none, KeyCode::Char('@'), PlayMacro(1)),
+        ];
+
+        (global, Self::with_marks(browse))
+    }
+
+    /// `m<digit>` sets a mark, `'<digit>` jumps to it
+    fn with_marks(mut browse: Vec<Binding>) -> Vec<Binding> {
+        for digit in 0..10u8 {
+            let c = (b'0' + digit) as char;
+            browse.push(Binding::sequence(&format!("m{}", c), Action::SetMark(digit)));
+            browse.push(Binding::sequence(&format!("'{}", c), Action::JumpToMark(digit)));
+        }
+        browse
+    }
+
+    pub fn preset(&self) -> KeymapPreset {
+        self.preset
+    }
+
+    /// Resolve a key event to an action. `editing` is true while an edit
+    /// buffer or the search query receives text input.
+    pub fn resolve(&mut self, key: KeyEvent, editing: bool) -> Option<Action> {
+        if editing {
+            self.reset_pending();
+        } else {
+            if self.command_line.is_some() {
+                return self.resolve_command_line(key);
+            }
+
+            if self.pending.is_empty() && key.modifiers == KeyModifiers::NONE {
+                if let KeyCode::Char(c @ '0'..='9') = key.code {
+                    let digit = c as usize - '0' as usize;
+                    if digit > 0 || self.count.is_some() {
+                        let count = self.count.unwrap_or(0);
+                        self.count = Some(count.saturating_mul(10).saturating_add(digit));
+                        return None;
+                    }
+                }
+
+                if self.preset == KeymapPreset::Vim && key.code == KeyCode::Char(':') {
+                    self.count = None;
+                    self.command_line = Some(String::new());
+                    return None;
+                }
+            }
+        }
+
+        self.pending.push(key);
+        let mut lookup = Self::lookup(&self.global, &self.pending);
+        if !editing {
+            lookup = match (lookup, Self::lookup(&self.browse, &self.pending)) {
+                (Lookup::Found(action), _) => Lookup::Found(action),
+                (_, Lookup::Found(action)) => Lookup::Found(action),
+                (Lookup::Prefix, _) | (_, Lookup::Prefix) => Lookup::Prefix,
+                _ => Lookup::NotFound,
+            };
+        }
+
+        if let Lookup::Prefix = lookup {
+            return None;
+        }
+
+        self.pending.clear();
+        let count = self.count.take();
+        match lookup {
+            Lookup::Found(Action::PlayMacro(_)) => Some(Action::PlayMacro(count.unwrap_or(1).max(1))),
+            Lookup::Found(action) => Some(action),
+            _ if editing => Some(Action::Input(key)),
+            _ => None,
+        }
+    }
+
+    fn resolve_command_line(&mut self, key: KeyEvent) -> Option<Action> {
+        let command = self.command_line.as_mut()?;
+        match key.code {
+            KeyCode::Char(c) => {
+                command.push(c);
+                None
+            }
+            KeyCode::Backspace => {
+                if command.pop().is_none() {
+                    self.command_line = None;
+                }
+                None
+            }
+            KeyCode::Enter => {
+                let command = self.command_line.take().unwrap_or_default();
+                match command.trim() {
+                    "w" => Some(Action::Save),
+                    "q" => Some(Action::Quit),
+                    "q!" => Some(Action::QuitWithoutSaving),
+                    "wq" | "x" => Some(Action::SaveAndQuit),
+                    "saveas" => Some(Action::SaveAs),
+                    "e" | "e!" => Some(Action::Reload),
+                    "merge" => Some(Action::MergeTemplate),
+                    "make" | "compile" => Some(Action::CompileMo),
+                    "export" => Some(Action::Export),
+                    "import" => Some(Action::Import),
+                    "" => None,
+                    other => {
+                        self.message = Some(format!("Not an editor command: {}", other));
+                        None
+                    }
+                }
+            }
+            KeyCode::Esc => {
+                self.command_line = None;
+                None
+            }
+            _ => None,
+        }
+    }
+
+    fn reset_pending(&mut self) {
+        self.pending.clear();
+        self.count = None;
+        self.command_line = None;
+    }
+
+    /// Count prefix typed so far, if any
+    pub fn pending_count(&self) -> Option<usize> {
+        self.count
+    }
+
+    /// Vim command line being typed, without the leading ':'
+    pub fn command_line(&self) -> Option<&str> {
+        self.command_line.as_deref()
+    }
+
+    pub fn take_message(&mut self) -> Option<String> {
+        self.message.take()
+    }
+
+    /// Bind a single key in every mode, replacing what it did before
+    pub fn bind(&mut self, (modifiers, code): (KeyModifiers, KeyCode), action: Action) {
+        self.global.retain(|binding| binding.keys != [(modifiers, code)]);
+        self.browse.retain(|binding| binding.keys != [(modifiers, code)]);
+        self.global.push(Binding::new(modifiers, code, action));
+    }
+
+    fn lookup(bindings: &[Binding], keys: &[KeyEvent]) -> Lookup {
+        // Terminals disagree on whether symbols such as '@' carry SHIFT
+        let matches = |binding: &(KeyModifiers, KeyCode), key: &KeyEvent| {
+            binding.1 == key.code
+                && (binding.0 == key.modifiers
+                    || (matches!(key.code, KeyCode::Char(_))
+                        && key.modifiers.contains(KeyModifiers::SHIFT)
+                        && binding.0 == key.modifiers - KeyModifiers::SHIFT))
+        };
+
+        let mut result = Lookup::NotFound;
+        for binding in bindings {
+            if binding.keys.len() < keys.len()
+                || !binding.keys.iter().zip(keys).all(|(b, k)| matches(b, k))
+            {
+                continue;
+            }
+            if binding.keys.len() == keys.len() {
+                return Lookup::Found(binding.action);
+            }
+            result = Lookup::Prefix;
+        }
+        result
+    }
+}
+
+/// Keys written by name in `parse_key` and `format_key`
+const KEY_NAMES: [(&str, KeyCode); 16] = [
+    ("space", KeyCode::Char(' ')),
+    ("tab", KeyCode::Tab),
+    ("backtab", KeyCode::BackTab),
+    ("enter", KeyCode::Enter),
+    ("backspace", KeyCode::Backspace),
+    ("delete", KeyCode::Delete),
+    ("insert", KeyCode::Insert),
+    ("esc", KeyCode::Esc),
+    ("up", KeyCode::Up),
+    ("down", KeyCode::Down),
+    ("left", KeyCode::Left),
+    ("right", KeyCode::Right),
+    ("home", KeyCode::Home),
+    ("end", KeyCode::End),
+    ("pageup", KeyCode::PageUp),
+    ("pagedown", KeyCode::PageDown),
+];
+
+/// Parse a key such as `alt+space`, `ctrl+t`, `alt+-` or `f5`
+pub fn parse_key(spec: &str) -> Option<(KeyModifiers, KeyCode)> {
+    let (modifiers, key) = match spec.strip_suffix("++") {
+        Some(modifiers) => (modifiers, "+"),
+        None => spec.rsplit_once('+').unwrap_or(("", spec)),
+    };
+
+    let mut parsed = KeyModifiers::NONE;
+    for modifier in modifiers.split('+').filter(|modifier| !modifier.is_empty()) {
+        parsed |= match modifier.to_lowercase().as_str() {
+            "ctrl" => KeyModifiers::CONTROL,
+            "alt" => KeyModifiers::ALT,
+            "shift" => KeyModifiers::SHIFT,
+            _ => return None,
+        };
+    }
+
+    let mut chars = key.chars();
+    let code = match (chars.next(), chars.next()) {
+        (Some(c), None) => KeyCode::Char(c),
+        _ => {
+            let name = key.to_lowercase();
+            match KEY_NAMES.iter().find(|(known, _)| *known == name) {
+                Some(&(_, code)) => code,
+                None => KeyCode::F(name.strip_prefix('f')?.parse().ok().filter(|n| (1..=12).contains(n))?),
+            }
+        }
+    };
+    Some((parsed, code))
+}
+
+/// The key in the notation `parse_key` reads, `None` for keys it has no
+/// name for
+pub fn format_key(modifiers: KeyModifiers, code: KeyCode) -> Option<String> {
+    let key = match code {
+        KeyCode::F(n) => format!("f{}", n),
+        code => match KEY_NAMES.iter().find(|(_, known)| *known == code) {
+            Some((name, _)) => name.to_string(),
+            None => match code {
+                KeyCode::Char(c) => c.to_string(),
+                _ => return None,
+            },
+        },
+    };
+    let mut spec = String::new();
+    for (modifier, name) in [(KeyModifiers::CONTROL, "ctrl+"), (KeyModifiers::ALT, "alt+"), (KeyModifiers::SHIFT, "shift+")] {
+        if modifiers.contains(modifier) {
+            spec.push_str(name);
+        }
+    }
+    spec.push_str(&key);
+    Some(spec)
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(modifiers: KeyModifiers, code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    #[test]
+    fn test_resolve_default_bindings() {
+        let mut keymap = Keymap::new();
+        assert_eq!(
+            keymap.resolve(key(KeyModifiers::CONTROL, KeyCode::Char('q')), false),
+            Some(Action::Quit)
+        );
+        assert_eq!(
+            keymap.resolve(key(KeyModifiers::NONE, KeyCode::Char('j')), false),
+            Some(Action::Next)
+        );
+        assert_eq!(
+            keymap.resolve(key(KeyModifiers::SHIFT, KeyCode::F(3)), false),
+            Some(Action::FindPrevious)
+        );
+        assert_eq!(keymap.resolve(key(KeyModifiers::NONE, KeyCode::Char('x')), false), None);
+    }
+
+    #[test]
+    fn test_unbound_keys_become_input_while_editing() {
+        let mut keymap = Keymap::new();
+        let x = key(KeyModifiers::NONE, KeyCode::Char('x'));
+        assert_eq!(keymap.resolve(x, true), Some(Action::Input(x)));
+
+        // Macro keys are text while editing
+        let q = key(KeyModifiers::NONE, KeyCode::Char('q'));
+        assert_eq!(keymap.resolve(q, true), Some(Action::Input(q)));
+        let digit = key(KeyModifiers::NONE, KeyCode::Char('3'));
+        assert_eq!(keymap.resolve(digit, true), Some(Action::Input(digit)));
+
+        // So are navigation letters and cursor keys; Esc and Ctrl+S are not
+        for code in [KeyCode::Char('j'), KeyCode::Char('k'), KeyCode::Char('i'), KeyCode::Home, KeyCode::End, KeyCode::Down] {
+            let navigation = key(KeyModifiers::NONE, code);
+            assert_eq!(keymap.resolve(navigation, true), Some(Action::Input(navigation)));
+        }
+        assert_eq!(keymap.resolve(key(KeyModifiers::NONE, KeyCode::Esc), true), Some(Action::Cancel));
+        assert_eq!(keymap.resolve(key(KeyModifiers::CONTROL, KeyCode::Char('s')), true), Some(Action::Save));
+    }
+
+    #[test]
+    fn test_count_prefix_for_macro_playback() {
+        let mut keymap = Keymap::new();
+        assert_eq!(keymap.resolve(key(KeyModifiers::NONE, KeyCode::Char('1')), false), None);
+        assert_eq!(keymap.resolve(key(KeyModifiers::NONE, KeyCode::Char('2')), false), None);
+        assert_eq!(keymap.pending_count(), Some(12));
+        assert_eq!(
+            keymap.resolve(key(KeyModifiers::SHIFT, KeyCode::Char('@')), false),
+            Some(Action::PlayMacro(12))
+        );
+        assert_eq!(keymap.pending_count(), None);
+
+        // Without a prefix the macro plays once
+        assert_eq!(
+            keymap.resolve(key(KeyModifiers::NONE, KeyCode::Char('@')), false),
+            Some(Action::PlayMacro(1))
+        );
+
+        // Any other command discards the prefix
+        keymap.resolve(key(KeyModifiers::NONE, KeyCode::Char('5')), false);
+        assert_eq!(keymap.resolve(key(KeyModifiers::NONE, KeyCode::Down), false), Some(Action::Next));
+        assert_eq!(keymap.pending_count(), None);
+    }
+
+    #[test]
+    fn test_vim_normal_mode_navigation() {
+        let mut keymap = Keymap::with_preset(KeymapPreset::Vim);
+        let plain = |c| key(KeyModifiers::NONE, KeyCode::Char(c));
+
+        assert_eq!(keymap.resolve(plain('j'), false), Some(Action::Next));
+        assert_eq!(keymap.resolve(plain('g'), false), None);
+        assert_eq!(keymap.resolve(plain('g'), false), Some(Action::First));
+        assert_eq!(
+            keymap.resolve(key(KeyModifiers::SHIFT, KeyCode::Char('G')), false),
+            Some(Action::Last)
+        );
+        assert_eq!(
+            keymap.resolve(key(KeyModifiers::CONTROL, KeyCode::Char('d')), false),
+            Some(Action::PageDown)
+        );
+        assert_eq!(keymap.resolve(plain('/'), false), Some(Action::StartSearch));
+        assert_eq!(keymap.resolve(plain('i'), false), Some(Action::EditTranslation));
+
+        // An unknown continuation discards the sequence
+        assert_eq!(keymap.resolve(plain('g'), false), None);
+        assert_eq!(keymap.resolve(plain('x'), false), None);
+        assert_eq!(keymap.resolve(plain('j'), false), Some(Action::Next));
+    }
+
+    #[test]
+    fn test_vim_insert_mode_passes_text_through() {
+        let mut keymap = Keymap::with_preset(KeymapPreset::Vim);
+        for c in ['j', 'k', 'g', 'i', ':', '/'] {
+            let k = key(KeyModifiers::NONE, KeyCode::Char(c));
+            assert_eq!(keymap.resolve(k, true), Some(Action::Input(k)));
+        }
+        let up = key(KeyModifiers::NONE, KeyCode::Up);
+        assert_eq!(keymap.resolve(up, true), Some(Action::Input(up)));
+        assert_eq!(keymap.resolve(key(KeyModifiers::NONE, KeyCode::Esc), true), Some(Action::Cancel));
+    }
+
+    #[test]
+    fn test_vim_command_line() {
+        let mut keymap = Keymap::with_preset(KeymapPreset::Vim);
+        let mut run = |command: &str| {
+            let mut result = keymap.resolve(key(KeyModifiers::NONE, KeyCode::Char(':')), false);
+            for c in command.chars() {
+                result = result.or(keymap.resolve(key(KeyModifiers::NONE, KeyCode::Char(c)), false));
+            }
+            result.or(keymap.resolve(key(KeyModifiers::NONE, KeyCode::Enter), false))
+        };
+
+        assert_eq!(run("w"), Some(Action::Save));
+        assert_eq!(run("q"), Some(Action::Quit));
+        assert_eq!(run("wq"), Some(Action::SaveAndQuit));
+        assert_eq!(run("q!"), Some(Action::QuitWithoutSaving));
+        assert_eq!(run("saveas"), Some(Action::SaveAs));
+        assert_eq!(run("e!"), Some(Action::Reload));
+        assert_eq!(run("frobnicate"), None);
+        assert!(keymap.take_message().unwrap().contains("frobnicate"));
+    }
+
+    #[test]
+    fn test_default_keymap_has_no_vim_keys() {
+        let mut keymap = Keymap::new();
+        assert_eq!(keymap.resolve(key(KeyModifiers::NONE, KeyCode::Char(':')), false), None);
+        assert_eq!(keymap.command_line(), None);
+        assert_eq!(keymap.resolve(key(KeyModifiers::NONE, KeyCode::Char('g')), false), None);
+        assert_eq!(
+            keymap.resolve(key(KeyModifiers::CONTROL, KeyCode::Char('u')), false),
+            Some(Action::ToggleUntranslatedFilter)
+        );
+    }
+
+    #[test]
+    fn test_mark_sequences() {
+        for preset in [KeymapPreset::Default, KeymapPreset::Vim] {
+            let mut keymap = Keymap::with_preset(preset);
+            let plain = |c| key(KeyModifiers::NONE, KeyCode::Char(c));
+
+            assert_eq!(keymap.resolve(plain('m'), false), None);
+            assert_eq!(keymap.resolve(plain('3'), false), Some(Action::SetMark(3)));
+            assert_eq!(keymap.pending_count(), None);
+            assert_eq!(keymap.resolve(plain('\''), false), None);
+            assert_eq!(keymap.resolve(plain('0'), false), Some(Action::JumpToMark(0)));
+
+            let m = plain('m');
+            assert_eq!(keymap.resolve(m, true), Some(Action::Input(m)));
+        }
+    }
+
+    #[test]
+    fn test_ctrl_z_suspends() {
+        for preset in [KeymapPreset::Default, KeymapPreset::Vim] {
+            let mut keymap = Keymap::with_preset(preset);
+            let ctrl_z = key(KeyModifiers::CONTROL, KeyCode::Char('z'));
+            assert_eq!(keymap.resolve(ctrl_z, false), Some(Action::Suspend));
+            assert_eq!(keymap.resolve(ctrl_z, true), Some(Action::Suspend));
+        }
+
+        let mut keymap = Keymap::new();
+        assert_eq!(
+            keymap.resolve(key(KeyModifiers::ALT, KeyCode::Char('z')), false),
+            Some(Action::ToggleFuzzyFilter)
+        );
+    }
+
+    #[test]
+    fn test_undo_and_redo_keys() {
+        let mut keymap = Keymap::with_preset(KeymapPreset::Vim);
+        assert_eq!(keymap.resolve(key(KeyModifiers::NONE, KeyCode::Char('u')), false), Some(Action::Undo));
+        assert_eq!(keymap.resolve(key(KeyModifiers::CONTROL, KeyCode::Char('r')), false), Some(Action::Redo));
+
+        let mut keymap = Keymap::new();
+        let alt_backspace = key(KeyModifiers::ALT, KeyCode::Backspace);
+        assert_eq!(keymap.resolve(alt_backspace, false), Some(Action::Undo));
+        assert_eq!(keymap.resolve(alt_backspace, true), Some(Action::Undo));
+        assert_eq!(keymap.resolve(key(KeyModifiers::CONTROL, KeyCode::Char('y')), false), Some(Action::Redo));
+    }
+
+    #[test]
+    fn test_parse_key_and_bind() {
+        assert_eq!(parse_key("alt+space"), Some((KeyModifiers::ALT, KeyCode::Char(' '))));
+        assert_eq!(parse_key("Ctrl+Alt+-"), Some((KeyModifiers::CONTROL | KeyModifiers::ALT, KeyCode::Char('-'))));
+        assert_eq!(parse_key("alt++"), Some((KeyModifiers::ALT, KeyCode::Char('+'))));
+        assert_eq!(parse_key("F5"), Some((KeyModifiers::NONE, KeyCode::F(5))));
+        assert_eq!(parse_key("hyper+x"), None);
+        assert_eq!(parse_key("f13"), None);
+        assert_eq!(parse_key(""), None);
+        assert_eq!(parse_key("shift+PageDown"), Some((KeyModifiers::SHIFT, KeyCode::PageDown)));
+        assert_eq!(parse_key("esc"), Some((KeyModifiers::NONE, KeyCode::Esc)));
+        for (modifiers, code) in [
+            (KeyModifiers::CONTROL | KeyModifiers::ALT, KeyCode::Char('+')),
+            (KeyModifiers::NONE, KeyCode::Char(' ')),
+            (KeyModifiers::SHIFT, KeyCode::BackTab),
+            (KeyModifiers::NONE, KeyCode::PageDown),
+            (KeyModifiers::CONTROL, KeyCode::F(12)),
+        ] {
+            assert_eq!(parse_key(&format_key(modifiers, code).unwrap()), Some((modifiers, code)));
+        }
+        assert_eq!(format_key(KeyModifiers::NONE, KeyCode::Null), None);
+
+        // A bound key replaces the preset's binding in every mode
+        let mut keymap = Keymap::new();
+        keymap.bind((KeyModifiers::NONE, KeyCode::Char('#')), Action::InsertChar('№'));
+        let hash = key(KeyModifiers::NONE, KeyCode::Char('#'));
+        assert_eq!(keymap.resolve(hash, false), Some(Action::InsertChar('№')));
+        assert_eq!(keymap.resolve(hash, true), Some(Action::InsertChar('№')));
+    }
+}
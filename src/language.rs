@@ -0,0 +1,245 @@
+// Poterm - Modern TUI editor for .po translation files
+// Copyright (c) 2025 AnmiTaliDev <anmitali198@gmail.com>
+// Licensed under the Apache License, Version 2.0
+
+//! Built-in table of common languages and their plural rules
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Language {
+    /// ISO 639 code, optionally with a territory: `pt_BR`
+    pub code: &'static str,
+    /// English name
+    pub name: &'static str,
+    /// Standard `Plural-Forms` value
+    pub plural_forms: &'static str,
+}
+
+const ONE: &str = "nplurals=1; plural=0;";
+const NOT_ONE: &str = "nplurals=2; plural=(n != 1);";
+const ABOVE_ONE: &str = "nplurals=2; plural=(n > 1);";
+const EAST_SLAVIC: &str =
+    "nplurals=3; plural=(n%10==1 && n%100!=11 ? 0 : n%10>=2 && n%10<=4 && (n%100<10 || n%100>=20) ? 1 : 2);";
+const WEST_SLAVIC: &str = "nplurals=3; plural=(n==1) ? 0 : (n>=2 && n<=4) ? 1 : 2;";
+
+const fn language(code: &'static str, name: &'static str, plural_forms: &'static str) -> Language {
+    Language {
+        code,
+        name,
+        plural_forms,
+    }
+}
+
+/// Sorted by English name
+pub const LANGUAGES: &[Language] = &[
+    language("af", "Afrikaans", NOT_ONE),
+    language("sq", "Albanian", NOT_ONE),
+    language(
+        "ar",
+        "Arabic",
+        "nplurals=6; plural=(n==0 ? 0 : n==1 ? 1 : n==2 ? 2 : n%100>=3 && n%100<=10 ? 3 : n%100>=11 ? 4 : 5);",
+    ),
+    language("hy", "Armenian", ABOVE_ONE),
+    language("az", "Azerbaijani", NOT_ONE),
+    language("eu", "Basque", NOT_ONE),
+    language("be", "Belarusian", EAST_SLAVIC),
+    language("bn", "Bengali", NOT_ONE),
+    language("bs", "Bosnian", EAST_SLAVIC),
+    language("bg", "Bulgarian", NOT_ONE),
+    language("ca", "Catalan", NOT_ONE),
+    language("zh", "Chinese", ONE),
+    language("zh_CN", "Chinese (China)", ONE),
+    language("zh_TW", "Chinese (Taiwan)", ONE),
+    language("hr", "Croatian", EAST_SLAVIC),
+    language("cs", "Czech", WEST_SLAVIC),
+    language("da", "Danish", NOT_ONE),
+    language("nl", "Dutch", NOT_ONE),
+    language("en", "English", NOT_ONE),
+    language("en_GB", "English (United Kingdom)", NOT_ONE),
+    language("eo", "Esperanto", NOT_ONE),
+    language("et", "Estonian", NOT_ONE),
+    language("fil", "Filipino", ABOVE_ONE),
+    language("fi", "Finnish", NOT_ONE),
+    language("fr", "French", ABOVE_ONE),
+    language("gl", "Galician", NOT_ONE),
+    language("ka", "Georgian", NOT_ONE),
+    language("de", "German", NOT_ONE),
+    language("el", "Greek", NOT_ONE),
+    language("gu", "Gujarati", NOT_ONE),
+    language("he", "Hebrew", NOT_ONE),
+    language("hi", "Hindi", NOT_ONE),
+    language("hu", "Hungarian", NOT_ONE),
+    language("is", "Icelandic", "nplurals=2; plural=(n%10!=1 || n%100==11);"),
+    language("id", "Indonesian", ONE),
+    language("ga", "Irish", "nplurals=5; plural=(n==1 ? 0 : n==2 ? 1 : n<7 ? 2 : n<11 ? 3 : 4);"),
+    language("it", "Italian", NOT_ONE),
+    language("ja", "Japanese", ONE),
+    language("kk", "Kazakh", NOT_ONE),
+    language("km", "Khmer", ONE),
+    language("ko", "Korean", ONE),
+    language("ky", "Kyrgyz", NOT_ONE),
+    language(
+        "lv",
+        "Latvian",
+        "nplurals=3; plural=(n%10==1 && n%100!=11 ? 0 : n != 0 ? 1 : 2);",
+    ),
+    language(
+        "lt",
+        "Lithuanian",
+        "nplurals=3; plural=(n%10==1 && n%100!=11 ? 0 : n%10>=2 && (n%100<10 || n%100>=20) ? 1 : 2);",
+    ),
+    language("mk", "Macedonian", "nplurals=2; plural=(n%10==1 && n%100!=11 ? 0 : 1);"),
+    language("ms", "Malay", ONE),
+    language("mr", "Marathi", NOT_ONE),
+    language("mn", "Mongolian", NOT_ONE),
+    language("nb", "Norwegian Bokmål", NOT_ONE),
+    language("nn", "Norwegian Nynorsk", NOT_ONE),
+    language("fa", "Persian", ABOVE_ONE),
+    language(
+        "pl",
+        "Polish",
+        "nplurals=3; plural=(n==1 ? 0 : n%10>=2 && n%10<=4 && (n%100<10 || n%100>=20) ? 1 : 2);",
+    ),
+    language("pt", "Portuguese", NOT_ONE),
+    language("pt_BR", "Portuguese (Brazil)", ABOVE_ONE),
+    language("pt_PT", "Portuguese (Portugal)", NOT_ONE),
+    language("pa", "Punjabi", NOT_ONE),
+    language(
+        "ro",
+        "Romanian",
+        "nplurals=3; plural=(n==1 ? 0 : (n==0 || (n%100 > 0 && n%100 < 20)) ? 1 : 2);",
+    ),
+    language("ru", "Russian", EAST_SLAVIC),
+    language("sr", "Serbian", EAST_SLAVIC),
+    language("sk", "Slovak", WEST_SLAVIC),
+    language(
+        "sl",
+        "Slovenian",
+        "nplurals=4; plural=(n%100==1 ? 0 : n%100==2 ? 1 : n%100==3 || n%100==4 ? 2 : 3);",
+    ),
+    language("es", "Spanish", NOT_ONE),
+    language("sw", "Swahili", NOT_ONE),
+    language("sv", "Swedish", NOT_ONE),
+    language("ta", "Tamil", NOT_ONE),
+    language("te", "Telugu", NOT_ONE),
+    language("th", "Thai", ONE),
+    language("tr", "Turkish", NOT_ONE),
+    language("uk", "Ukrainian", EAST_SLAVIC),
+    language("ur", "Urdu", NOT_ONE),
+    language("uz", "Uzbek", ABOVE_ONE),
+    language("vi", "Vietnamese", ONE),
+    language("cy", "Welsh", "nplurals=4; plural=(n==1 ? 0 : n==2 ? 1 : n != 8 && n != 11 ? 2 : 3);"),
+];
+
+/// Table entry for a `Language` header value. `pt-BR` and `pt_BR.UTF-8`
+/// find `pt_BR`; `de_AT` falls back to `de`.
+pub fn lookup(code: &str) -> Option<&'static Language> {
+    let code = code.trim().replace('-', "_");
+    let code = code.split(['.', '@']).next().unwrap_or_default();
+    let find = |code: &str| LANGUAGES.iter().find(|language| language.code.eq_ignore_ascii_case(code));
+    find(code).or_else(|| find(code.split('_').next().unwrap_or_default()))
+}
+
+/// CLDR plural categories of the forms of a language's standard
+/// `Plural-Forms`, in form order, as i18next names them. None when the
+/// gettext forms don't line up with CLDR's.
+pub fn plural_categories(code: &str) -> Option<&'static [&'static str]> {
+    let language = lookup(code)?;
+    Some(match language.code.split('_').next().unwrap_or_default() {
+        "ru" | "uk" | "be" | "pl" => &["one", "few", "many"],
+        "bs" | "hr" | "sr" | "cs" | "sk" | "lt" | "ro" => &["one", "few", "other"],
+        "ar" => &["zero", "one", "two", "few", "many", "other"],
+        "ga" => &["one", "two", "few", "many", "other"],
+        "sl" => &["one", "two", "few", "other"],
+        "lv" | "cy" => return None,
+        _ if language.plural_forms == ONE => &["other"],
+        _ => &["one", "other"],
+    })
+}
+
+const ENGLISH_QUOTES: &[(char, char)] = &[('“', '”'), ('‘', '’')];
+const GUILLEMETS: &[(char, char)] = &[('«', '»'), ('„', '“'), ('“', '”')];
+const GERMAN_QUOTES: &[(char, char)] = &[('„', '“'), ('‚', '‘'), ('»', '«')];
+const POLISH_QUOTES: &[(char, char)] = &[('„', '”'), ('«', '»'), ('‚', '’')];
+const NORDIC_QUOTES: &[(char, char)] = &[('”', '”'), ('’', '’'), ('»', '»')];
+const CORNER_BRACKETS: &[(char, char)] = &[('「', '」'), ('『', '』')];
+
+/// Quotation mark pairs for a `Language` header value, the usual primary
+/// quotes first. Unknown languages get English quotes.
+pub fn quotes(code: &str) -> &'static [(char, char)] {
+    let code = lookup(code).map_or("", |language| language.code);
+    match code.split('_').next().unwrap_or_default() {
+        "ru" | "uk" | "be" | "kk" | "ky" | "uz" | "hy" | "fr" | "it" | "es" | "pt" | "ca" | "gl" | "eu" | "el"
+        | "ar" | "fa" | "nb" | "nn" | "sq" => GUILLEMETS,
+        "de" | "cs" | "sk" | "sl" | "bg" | "lt" | "lv" | "et" | "is" | "ka" | "mk" | "sr" | "da" => GERMAN_QUOTES,
+        "pl" | "hu" | "ro" | "hr" | "bs" | "nl" => POLISH_QUOTES,
+        "sv" | "fi" => NORDIC_QUOTES,
+        "ja" => CORNER_BRACKETS,
+        "zh" if code == "zh_TW" => CORNER_BRACKETS,
+        _ => ENGLISH_QUOTES,
+    }
+}
+
+/// Whether a `Language` header value names a right-to-left language
+pub fn is_rtl(code: &str) -> bool {
+    let code = code.trim().replace('-', "_");
+    let language = code.split(['_', '.', '@']).next().unwrap_or_default();
+    ["ar", "he", "fa", "ur", "yi", "ps", "sd", "ug", "dv", "ckb"]
+        .iter()
+        .any(|rtl| rtl.eq_ignore_ascii_case(language))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::plural::PluralForms;
+
+    #[test]
+    fn test_table_plural_forms_are_valid() {
+        for language in LANGUAGES {
+            let forms = PluralForms::parse(language.plural_forms);
+            assert!(forms.is_ok(), "{}: {:?}", language.code, forms);
+        }
+    }
+
+    #[test]
+    fn test_plural_categories() {
+        assert_eq!(plural_categories("ru_RU"), Some(&["one", "few", "many"][..]));
+        assert_eq!(plural_categories("pt-BR"), Some(&["one", "other"][..]));
+        assert_eq!(plural_categories("ja"), Some(&["other"][..]));
+        assert_eq!(plural_categories("cy"), None);
+        for language in LANGUAGES {
+            if let Some(categories) = plural_categories(language.code) {
+                let forms = PluralForms::parse(language.plural_forms).unwrap();
+                assert_eq!(categories.len(), forms.nplurals, "{}", language.code);
+            }
+        }
+    }
+
+    #[test]
+    fn test_lookup() {
+        assert_eq!(lookup("pt-BR").unwrap().name, "Portuguese (Brazil)");
+        assert_eq!(lookup("pt_br.UTF-8").unwrap().code, "pt_BR");
+        assert_eq!(lookup("de_AT").unwrap().code, "de");
+        assert_eq!(lookup("sr@latin").unwrap().code, "sr");
+        assert_eq!(lookup("tlh"), None);
+        assert_eq!(lookup(""), None);
+    }
+
+    #[test]
+    fn test_quotes() {
+        assert_eq!(quotes("ru")[0], ('«', '»'));
+        assert_eq!(quotes("de_AT")[0], ('„', '“'));
+        assert_eq!(quotes("zh_TW")[0], ('「', '」'));
+        assert_eq!(quotes("zh_CN"), ENGLISH_QUOTES);
+        assert_eq!(quotes(""), ENGLISH_QUOTES);
+    }
+
+    #[test]
+    fn test_is_rtl() {
+        assert!(is_rtl("ar"));
+        assert!(is_rtl("he_IL.UTF-8"));
+        assert!(is_rtl("fa-IR"));
+        assert!(!is_rtl("de"));
+        assert!(!is_rtl(""));
+    }
+}
@@ -3,30 +3,90 @@
 // Licensed under the Apache License, Version 2.0
 
 use anyhow::{Context, Result};
-use clap::Parser;
-use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyModifiers};
-use crossterm::terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen};
-use crossterm::ExecutableCommand;
-use ratatui::backend::CrosstermBackend;
+use clap::{ArgGroup, CommandFactory, Parser, Subcommand};
+use clap_complete::Shell;
+use crossterm::event::KeyEvent;
+use ratatui::backend::{Backend, CrosstermBackend, TestBackend};
 use ratatui::Terminal;
 use std::io::{self, stdout};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::Ordering;
+use std::time::Duration;
 
+mod bidi;
+mod browser;
+mod charmap;
+mod check;
+mod clipboard;
+mod columns;
+mod comments;
+mod complete;
+mod config;
+mod exchange;
+mod filter;
 mod gettext;
+mod glossary;
+mod history;
+mod input;
+mod journal;
+mod keymap;
+mod language;
+mod memory;
+mod merge;
+mod mo;
+mod picker;
+mod pipe;
+mod plural;
+mod preview;
+mod reference;
+mod replace;
+mod replay;
+mod snippet;
+mod session;
+mod startup;
+mod state;
+mod stats;
+mod terminal;
+mod tree;
 mod ui;
+mod undo;
+mod validate;
+mod watch;
+mod wrap;
 
+use clipboard::SetClipboard;
+use config::{Config, KeymapPreset, StartAt};
+use exchange::csv::CsvOptions;
+use exchange::json::{JsonOptions, JsonStyle};
+use filter::FilterMode;
 use gettext::PoFile;
-use ui::App;
+use memory::TranslationMemory;
+use replay::{EventSource, Input, Recorder, Script, Snapshots, TerminalEvents};
+use startup::StartupScreen;
+use state::StateStore;
+use terminal::{SendCommand, TerminalGuard, TerminalTitle};
+use ui::{App, Outcome};
+
+/// How long the event loop waits for input before running background work
+const EVENT_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Size of the off-screen terminal `--replay` renders to
+const REPLAY_SIZE: (u16, u16) = (100, 30);
 
 #[derive(Parser)]
 #[command(
     name = "poterm",
     version = env!("CARGO_PKG_VERSION"),
     author = "AnmiTaliDev <anmitali198@gmail.com>",
-    about = "Modern TUI editor for .po translation files"
+    about = "Modern TUI editor for .po translation files",
+    args_conflicts_with_subcommands = true,
+    group(ArgGroup::new("input").multiple(true).args(["file", "from_pot"]))
 )]
 struct Cli {
-    /// Path to the .po file to edit
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Path to the .po file to edit; a compiled .mo opens as a new .po
     #[arg(value_name = "FILE")]
     file: Option<PathBuf>,
 
@@ -37,190 +97,769 @@ struct Cli {
     /// Create .po file from .pot template
     #[arg(long, value_name = "POT_FILE")]
     from_pot: Option<PathBuf>,
+
+    /// Save to PATH instead, leaving FILE untouched; with --from-pot, the
+    /// new catalog goes there. A read-only session (--force) saves nothing.
+    #[arg(short, long, value_name = "PATH", requires = "input", conflicts_with = "force")]
+    output: Option<PathBuf>,
+
+    /// Language header of a file created with --create or --from-pot
+    #[arg(long, value_name = "LANG")]
+    language: Option<String>,
+
+    /// Edit a .pot template like a regular .po file
+    #[arg(long)]
+    force_po: bool,
+
+    /// Refuse to open a file with parse errors, listing them instead
+    #[arg(long)]
+    strict: bool,
+
+    /// With --strict, open a file with parse errors anyway, read-only
+    #[arg(long, requires = "strict")]
+    force: bool,
+
+    /// Save every string on one line instead of wrapping it at 79 columns
+    /// (overrides the config file)
+    #[arg(long)]
+    no_wrap: bool,
+
+    /// Keep the previous contents of the file in FILE.bak when saving
+    /// (overrides the config file)
+    #[arg(long)]
+    backup: bool,
+
+    /// Suggest translations from this catalog; repeat for more
+    #[arg(long = "tm", value_name = "FILE")]
+    memory: Vec<PathBuf>,
+
+    /// Key binding preset (overrides the config file)
+    #[arg(long, value_enum, value_name = "PRESET")]
+    keymap: Option<KeymapPreset>,
+
+    /// Entry to select on opening (overrides the config file)
+    #[arg(long, value_enum, value_name = "WHERE")]
+    start_at: Option<StartAt>,
+
+    /// List only these entries on opening
+    #[arg(long, value_enum, value_name = "STATUS")]
+    filter: Option<FilterMode>,
+
+    /// Print the validation issues of FILE instead of opening it, exiting
+    /// with code 1 on errors (like `poterm check FILE`)
+    #[arg(long, requires = "file")]
+    check: bool,
+
+    /// Read keys from SCRIPT instead of the terminal, drawing off-screen
+    #[arg(long, hide = true, value_name = "SCRIPT", requires = "file", conflicts_with = "record")]
+    replay: Option<PathBuf>,
+
+    /// With --replay, write the screen after each key to DIR
+    #[arg(long, hide = true, value_name = "DIR", requires = "replay")]
+    replay_render: Option<PathBuf>,
+
+    /// Write the keys pressed to SCRIPT, in the format --replay reads
+    #[arg(long, hide = true, value_name = "SCRIPT")]
+    record: Option<PathBuf>,
+
+    /// Panic after drawing the first frame, to check the terminal is restored
+    #[cfg(debug_assertions)]
+    #[arg(long, hide = true)]
+    debug_panic: bool,
 }
 
-fn main() -> Result<()> {
-    let cli = Cli::parse();
+#[derive(Subcommand)]
+enum Command {
+    /// Validate .po files without opening the editor (exit code 1 on errors)
+    Check {
+        /// Files, or directories to search for .po files
+        #[arg(value_name = "FILE", required = true)]
+        files: Vec<PathBuf>,
 
-    // Setup terminal
-    enable_raw_mode().context("Failed to enable raw mode")?;
-    stdout().execute(EnterAlternateScreen).context("Failed to enter alternate screen")?;
-    
-    let backend = CrosstermBackend::new(stdout());
-    let mut terminal = Terminal::new(backend).context("Failed to create terminal")?;
+        /// Only report entries defined more than once
+        #[arg(long)]
+        duplicates: bool,
 
-    let result = run_app(&mut terminal, cli);
+        /// Also list issues acknowledged with a suppression comment
+        #[arg(long)]
+        show_suppressed: bool,
 
-    // Cleanup terminal
-    disable_raw_mode().context("Failed to disable raw mode")?;
-    stdout().execute(LeaveAlternateScreen).context("Failed to leave alternate screen")?;
+        /// Check again whenever a file changes, until Ctrl+C
+        #[arg(long)]
+        watch: bool,
+
+        /// Clear the screen before each run in watch mode
+        #[arg(long, requires = "watch")]
+        clear: bool,
+
+        /// Print plain text or JSON
+        #[arg(long, value_enum, default_value_t, conflicts_with = "watch")]
+        format: history::OutputFormat,
+    },
+    /// Print entry and word statistics for .po files
+    Stats {
+        #[arg(value_name = "FILE", required = true)]
+        files: Vec<PathBuf>,
+
+        /// Print the statistics recorded in the progress log on each save
+        #[arg(long)]
+        history: bool,
+
+        /// Print plain text or JSON
+        #[arg(long, value_enum, default_value_t)]
+        format: history::OutputFormat,
+    },
+    /// Update a catalog from a regenerated template, like msgmerge
+    Merge {
+        /// The .po file to update in place
+        #[arg(value_name = "PO_FILE")]
+        po_file: PathBuf,
+
+        /// The new .pot template
+        #[arg(value_name = "POT_FILE")]
+        template: PathBuf,
+
+        /// Give new entries the translation of an old msgid at least this
+        /// similar (0 to 1), marked fuzzy (overrides the config file)
+        #[arg(long, value_name = "RATIO")]
+        similarity: Option<f64>,
+    },
+    /// Fill untranslated entries with the translations other catalogs have
+    /// for the same msgid
+    Pretranslate {
+        /// The .po file to update in place
+        #[arg(value_name = "PO_FILE")]
+        po_file: PathBuf,
+
+        /// Catalog to take translations from; repeat for more, the first
+        /// with a translation wins
+        #[arg(long = "tm", value_name = "FILE", required = true)]
+        memory: Vec<PathBuf>,
+
+        /// Mark the filled entries fuzzy
+        #[arg(long)]
+        fuzzy: bool,
+    },
+    /// Compile a .po file to a binary .mo file, like msgfmt
+    Compile {
+        #[arg(value_name = "PO_FILE")]
+        file: PathBuf,
+
+        /// Where to write the .mo file (default: next to PO_FILE)
+        #[arg(short, long, value_name = "MO_FILE")]
+        output: Option<PathBuf>,
+    },
+    /// Write the entries of a .po file as CSV, for review in a spreadsheet
+    ExportCsv {
+        #[arg(value_name = "PO_FILE")]
+        file: PathBuf,
+
+        /// Where to write the CSV file (default: next to PO_FILE)
+        #[arg(short, long, value_name = "CSV_FILE")]
+        output: Option<PathBuf>,
+
+        /// Separate fields with this character, e.g. ';'
+        #[arg(long, value_name = "CHAR", default_value_t = ',')]
+        delimiter: char,
+
+        /// Only untranslated and fuzzy entries
+        #[arg(long)]
+        pending: bool,
+    },
+    /// Read translations back from CSV into a .po file, matched by msgctxt
+    /// and msgid
+    ImportCsv {
+        /// The .po file to update in place
+        #[arg(value_name = "PO_FILE")]
+        po_file: PathBuf,
+
+        #[arg(value_name = "CSV_FILE")]
+        input: PathBuf,
+    },
+    /// Write the entries of a .po file as XLIFF 1.2, for translation agencies
+    ExportXliff {
+        #[arg(value_name = "PO_FILE")]
+        file: PathBuf,
+
+        /// Where to write the XLIFF file (default: next to PO_FILE)
+        #[arg(short, long, value_name = "XLIFF_FILE")]
+        output: Option<PathBuf>,
+    },
+    /// Read translations back from XLIFF into a .po file, matched by
+    /// trans-unit id
+    ImportXliff {
+        /// The .po file to update in place
+        #[arg(value_name = "PO_FILE")]
+        po_file: PathBuf,
+
+        #[arg(value_name = "XLIFF_FILE")]
+        input: PathBuf,
+    },
+    /// Write the translations of a .po file as JSON, for web i18n libraries
+    ExportJson {
+        #[arg(value_name = "PO_FILE")]
+        file: PathBuf,
+
+        /// Where to write the JSON file (default: next to PO_FILE)
+        #[arg(short, long, value_name = "JSON_FILE")]
+        output: Option<PathBuf>,
+
+        /// Layout of the file
+        #[arg(long, value_enum, default_value_t)]
+        style: JsonStyle,
 
-    result
+        /// Leave out entries with no translation
+        #[arg(long)]
+        skip_untranslated: bool,
+
+        /// Leave out fuzzy entries
+        #[arg(long)]
+        skip_fuzzy: bool,
+
+        /// Key of entries with a msgctxt, e.g. '{msgctxt}.{msgid}'
+        /// (default: '{msgctxt}|{msgid}', '{msgid}_{msgctxt}' for i18next)
+        #[arg(long, value_name = "FORMAT")]
+        key_format: Option<String>,
+    },
+    /// Read translations back from JSON into a .po file, matched by key
+    ImportJson {
+        /// The .po file to update in place
+        #[arg(value_name = "PO_FILE")]
+        po_file: PathBuf,
+
+        #[arg(value_name = "JSON_FILE")]
+        input: PathBuf,
+
+        /// Layout of the file, as it was exported
+        #[arg(long, value_enum, default_value_t)]
+        style: JsonStyle,
+
+        /// Key of entries with a msgctxt, as it was exported
+        #[arg(long, value_name = "FORMAT")]
+        key_format: Option<String>,
+    },
+    /// Print a shell completion script
+    Completions {
+        #[arg(value_enum)]
+        shell: Shell,
+    },
+    /// Print the man page, or write one page per command into DIR
+    Manpage {
+        #[arg(value_name = "DIR")]
+        dir: Option<PathBuf>,
+    },
 }
 
-fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>, cli: Cli) -> Result<()> {
-    let po_file = match (cli.file, cli.from_pot) {
-        (Some(path), Some(pot_path)) => {
-            // Create .po from .pot template
-            PoFile::from_pot_template(&pot_path, &path)
-                .context("Failed to create .po file from .pot template")?
-        }
-        (Some(path), None) => {
-            if path.exists() {
-                PoFile::from_file(&path).context("Failed to load .po file")?
-            } else if cli.create {
-                PoFile::new(path)
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    // Completions and man pages are generated for packaging, where no
+    // config file should get in the way
+    let mut config = match cli.command {
+        Some(Command::Completions { .. } | Command::Manpage { .. }) => Config::default(),
+        _ => Config::load()?,
+    };
+    match cli.command {
+        Some(Command::Check {
+            files,
+            duplicates,
+            show_suppressed,
+            watch,
+            clear,
+            format,
+        }) => {
+            let passed = if watch {
+                let options = watch::WatchOptions {
+                    duplicates_only: duplicates,
+                    show_suppressed,
+                    clear,
+                };
+                watch::run(&files, &config, &options)?
             } else {
-                anyhow::bail!("File does not exist. Use --create to create a new file or --from-pot to create from template.");
+                let options = check::Options { duplicates_only: duplicates, show_suppressed, format };
+                check::run(&watch::po_files(&files), &config, &options)?
+            };
+            std::process::exit(if passed { 0 } else { 1 });
+        }
+        Some(Command::Stats { files, history: true, format }) => return history::run(&files, &config, format),
+        Some(Command::Stats { files, format, .. }) => {
+            return stats::run(&files, &config.needs_work_flag, &StateStore::load(config.persist_state), format);
+        }
+        Some(Command::Merge { po_file, template, similarity }) => {
+            if let Some(similarity) = similarity {
+                config.merge_similarity = similarity;
             }
+            return merge::run(&po_file, &template, &config);
         }
-        (None, Some(_pot_path)) => {
-            anyhow::bail!("Please specify output .po file path when using --from-pot");
+        Some(Command::Pretranslate { po_file, memory, fuzzy }) => return memory::run(&po_file, &memory, fuzzy, &config),
+        Some(Command::Compile { file, output }) => return mo::run(&file, output.as_deref()),
+        Some(Command::ExportCsv { file, output, delimiter, pending }) => {
+            if !delimiter.is_ascii() {
+                anyhow::bail!("The delimiter must be an ASCII character, not {}", delimiter);
+            }
+            let options = CsvOptions { delimiter, pending_only: pending };
+            return exchange::csv::run_export(&file, output.as_deref(), &options);
         }
-        (None, None) => PoFile::default(),
-    };
+        Some(Command::ImportCsv { po_file, input }) => return exchange::csv::run_import(&po_file, &input, &config),
+        Some(Command::ExportXliff { file, output }) => return exchange::xliff::run_export(&file, output.as_deref()),
+        Some(Command::ImportXliff { po_file, input }) => return exchange::xliff::run_import(&po_file, &input, &config),
+        Some(Command::ExportJson { file, output, style, skip_untranslated, skip_fuzzy, key_format }) => {
+            let options = JsonOptions { skip_untranslated, skip_fuzzy, key_format };
+            return exchange::json::run_export(&file, output.as_deref(), style, &options);
+        }
+        Some(Command::ImportJson { po_file, input, style, key_format }) => {
+            return exchange::json::run_import(&po_file, &input, style, key_format.as_deref(), &config)
+        }
+        Some(Command::Completions { shell }) => {
+            print_completions(shell, &mut stdout());
+            return Ok(());
+        }
+        Some(Command::Manpage { dir: None }) => {
+            return clap_mangen::Man::new(Cli::command()).render(&mut stdout()).context("Failed to write the man page");
+        }
+        Some(Command::Manpage { dir: Some(dir) }) => return write_manpages(&dir),
+        None => {}
+    }
 
-    let mut app = App::new(po_file);
+    if let (true, Some(ref file)) = (cli.check, &cli.file) {
+        let passed = check::run(std::slice::from_ref(file), &config, &check::Options::default())?;
+        std::process::exit(if passed { 0 } else { 1 });
+    }
 
-    loop {
-        terminal.draw(|f| ui::draw(f, &mut app))?;
+    if let Some(keymap) = cli.keymap {
+        config.keymap = keymap;
+    }
+    if let Some(start_at) = cli.start_at {
+        config.start_at = start_at;
+    }
+    if cli.no_wrap {
+        config.wrap = false;
+    }
+    if cli.backup {
+        config.backup = true;
+    }
 
-        if let Event::Key(key) = event::read()? {
-            if handle_key_event(&mut app, key)? {
-                break;
+    // Reported before the alternate screen hides them
+    let read_only = match strict_diagnostics(&cli)? {
+        Some(diagnostics) => {
+            eprintln!("{} parse error(s):", diagnostics.len());
+            for diagnostic in &diagnostics {
+                eprintln!("  {}", diagnostic);
             }
+            if !cli.force {
+                anyhow::bail!("Refusing to open a file with parse errors (--force opens it read-only)");
+            }
+            true
         }
-    }
+        None => false,
+    };
+
+    // Indexed before the terminal is taken over, which may take a moment
+    let memory = (!cli.memory.is_empty()).then(|| TranslationMemory::load(&cli.memory)).transpose()?;
 
-    // Save file if modified
-    if app.is_modified() {
-        app.save().context("Failed to save file")?;
+    if let Some(ref script) = cli.replay {
+        let mut events = Script::from_file(script)?;
+        let snapshots = cli.replay_render.as_deref().map(Snapshots::create).transpose()?;
+        let mut terminal = Terminal::new(TestBackend::new(REPLAY_SIZE.0, REPLAY_SIZE.1))?;
+        config.terminal_title = false;
+        run_app(&mut terminal, &mut events, snapshots, cli, config, read_only, memory)?.print();
+        return Ok(());
     }
+    let mut events: Box<dyn EventSource> = match cli.record {
+        Some(ref path) => Box::new(Recorder::create(TerminalEvents, path)?),
+        None => Box::new(TerminalEvents),
+    };
+
+    // Setup terminal
+    terminal::install_panic_hook();
+    let guard = TerminalGuard::enter().context("Failed to set up the terminal")?;
+
+    let backend = CrosstermBackend::new(stdout());
+    let mut terminal = Terminal::new(backend).context("Failed to create terminal")?;
+
+    let result = run_app(&mut terminal, events.as_mut(), None, cli, config, read_only, memory);
+    drop(events);
+
+    // Cleanup terminal
+    guard.leave().context("Failed to restore the terminal")?;
 
+    result?.print();
     Ok(())
 }
 
-fn handle_key_event(app: &mut App, key: KeyEvent) -> Result<bool> {
-    // Debug: print key events to help diagnose issues
-    // eprintln!("Key: {:?} {:?}", key.modifiers, key.code);
-    
-    match (key.modifiers, key.code) {
-        // Quit
-        (KeyModifiers::CONTROL, KeyCode::Char('q')) => return Ok(true),
-        
-        // Save
-        (KeyModifiers::CONTROL, KeyCode::Char('s')) => {
-            app.save()?;
-        }
-        
-        // Save current entry (Ctrl+Shift+P)
-        (KeyModifiers::CONTROL | KeyModifiers::SHIFT, KeyCode::Char('p')) => {
-            app.save_current_entry()?;
-        }
-        
-        // Navigation
-        (KeyModifiers::NONE, KeyCode::Up) | (KeyModifiers::NONE, KeyCode::Char('k')) => {
-            if app.is_metadata_mode() {
-                app.metadata_previous();
-            } else {
-                app.previous_entry();
-            }
-        }
-        (KeyModifiers::NONE, KeyCode::Down) | (KeyModifiers::NONE, KeyCode::Char('j')) => {
-            if app.is_metadata_mode() {
-                app.metadata_next();
-            } else {
-                app.next_entry();
-            }
-        }
-        (KeyModifiers::NONE, KeyCode::PageUp) => {
-            app.page_up();
+/// What the editor leaves to print once the terminal is restored
+#[derive(Default)]
+struct Closing {
+    /// The time spent in the file, when tracked
+    summary: Option<String>,
+    /// Why the recent files list could not be saved, which doesn't stop
+    /// quitting
+    warning: Option<String>,
+}
+
+impl Closing {
+    fn print(&self) {
+        if let Some(ref summary) = self.summary {
+            println!("{}", summary);
         }
-        (KeyModifiers::NONE, KeyCode::PageDown) => {
-            app.page_down();
+        if let Some(ref warning) = self.warning {
+            eprintln!("Warning: {}", warning);
         }
-        (KeyModifiers::NONE, KeyCode::Home) => {
-            app.go_to_first();
+    }
+}
+
+fn print_completions(shell: Shell, out: &mut dyn io::Write) {
+    clap_complete::generate(shell, &mut Cli::command(), "poterm", out);
+}
+
+/// `poterm.1`, and `poterm-<command>.1` for each subcommand, in `dir`
+fn write_manpages(dir: &Path) -> Result<()> {
+    std::fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    clap_mangen::generate_to(Cli::command(), dir).with_context(|| format!("Failed to write man pages to {}", dir.display()))
+}
+
+/// With `--strict`, the parse errors of the file about to be opened: the
+/// template for `--from-pot`, otherwise the file itself if it exists
+fn strict_diagnostics(cli: &Cli) -> Result<Option<Vec<String>>> {
+    if !cli.strict {
+        return Ok(None);
+    }
+    let path = match (&cli.file, &cli.from_pot) {
+        (_, Some(pot_path)) => pot_path,
+        (Some(path), None) if path.exists() && !mo::is_mo(path) => path,
+        _ => return Ok(None),
+    };
+    let content = std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let (_, diagnostics) = PoFile::parse_with_diagnostics(&content);
+    Ok(Some(diagnostics).filter(|diagnostics| !diagnostics.is_empty()))
+}
+
+/// The catalog named on the command line, None to show the startup screen
+fn open_from_cli(cli: &Cli) -> Result<Option<PoFile>> {
+    let set_language = |mut po_file: PoFile| {
+        if let Some(ref language) = cli.language {
+            po_file.set_header_field("Language".to_string(), language.clone());
         }
-        (KeyModifiers::NONE, KeyCode::End) => {
-            app.go_to_last();
+        po_file
+    };
+    let mut po_file = match (&cli.file, &cli.from_pot) {
+        (Some(path), Some(pot_path)) => {
+            // Create .po from .pot template
+            set_language(
+                PoFile::from_pot_template(pot_path, path)
+                    .context("Failed to create .po file from .pot template")?,
+            )
         }
-        
-        // Edit mode
-        (KeyModifiers::NONE, KeyCode::Enter) | (KeyModifiers::NONE, KeyCode::Char('i')) => {
-            if app.is_metadata_mode() {
-                app.start_editing_selected_metadata();
+        (Some(path), None) => {
+            if mo::is_mo(path) {
+                PoFile::from_mo(path).context("Failed to load .mo file")?
+            } else if path.exists() {
+                PoFile::from_file(path).context("Failed to load .po file")?
+            } else if cli.create {
+                set_language(PoFile::new(path.clone()))
             } else {
-                app.start_editing();
+                anyhow::bail!("File does not exist. Use --create to create a new file or --from-pot to create from template.");
             }
         }
-        (KeyModifiers::NONE, KeyCode::Esc) => {
-            if app.help_visible {
-                app.toggle_help();
-            } else {
-                app.stop_editing();
+        (None, Some(pot_path)) => match cli.output {
+            Some(ref output) => set_language(
+                PoFile::from_pot_template(pot_path, output).context("Failed to create .po file from .pot template")?,
+            ),
+            None => anyhow::bail!("Please specify output .po file path when using --from-pot"),
+        },
+        (None, None) => return Ok(None),
+    };
+    if let Some(ref output) = cli.output {
+        po_file.path = Some(output.clone());
+    }
+    Ok(Some(po_file))
+}
+
+fn run_app<B: Backend + SendCommand>(
+    terminal: &mut Terminal<B>,
+    events: &mut dyn EventSource,
+    mut snapshots: Option<Snapshots>,
+    cli: Cli,
+    config: Config,
+    read_only: bool,
+    memory: Option<TranslationMemory>,
+) -> Result<Closing> {
+    let state = StateStore::load(config.persist_state);
+    let terminated = terminal::termination_signal().context("Failed to set up signal handlers")?;
+    #[cfg(debug_assertions)]
+    let debug_panic = cli.debug_panic;
+    let po_file = match open_from_cli(&cli)? {
+        Some(po_file) => po_file,
+        None => match startup::run(terminal, StartupScreen::new(Path::new("."), state.recent()))? {
+            Some(po_file) => po_file,
+            None => return Ok(Closing::default()),
+        },
+    };
+    // A catalog made from a template is new wherever it is saved
+    let source = cli.output.as_ref().and(cli.file.clone()).filter(|_| cli.from_pot.is_none());
+
+    let mut title = TerminalTitle::new(config.terminal_title);
+    let mut app = App::new(po_file, config)
+        .with_force_po(cli.force_po)
+        .with_read_only(read_only)
+        .with_memory(memory)
+        .with_state(state)
+        .with_filter(cli.filter)
+        .with_source(source)
+        .with_journal();
+    if events.interactive() {
+        app = app.with_system_clipboard();
+    }
+
+    loop {
+        // The file itself is left alone: a signal may come in the middle of
+        // anything, and the swap file stays for recovery too
+        let signal = terminated.load(Ordering::Relaxed);
+        if signal != 0 {
+            let name = terminal::signal_name(signal);
+            match app.emergency_save()? {
+                Some(path) => anyhow::bail!("Received {}; unsaved changes were written to {}", name, path.display()),
+                None => anyhow::bail!("Received {}", name),
             }
         }
-        
-        // Tab switching
-        (KeyModifiers::NONE, KeyCode::Tab) => {
-            app.next_field();
-        }
-        (KeyModifiers::SHIFT, KeyCode::BackTab) => {
-            app.previous_field();
+        title.set(&app.terminal_title())?;
+        let frame = terminal.draw(|f| ui::draw(f, &mut app))?;
+        if let Some(ref mut snapshots) = snapshots {
+            snapshots.capture(frame.buffer)?;
         }
-        
-        // Search
-        (KeyModifiers::CONTROL, KeyCode::Char('f')) => {
-            app.start_search();
+        #[cfg(debug_assertions)]
+        if debug_panic {
+            panic!("--debug-panic");
         }
-        (KeyModifiers::NONE, KeyCode::F(3)) => {
-            app.find_next();
-        }
-        (KeyModifiers::SHIFT, KeyCode::F(3)) => {
-            app.find_previous();
-        }
-        
-        // Toggle fuzzy/untranslated filter
-        (KeyModifiers::CONTROL, KeyCode::Char('u')) => {
-            app.toggle_untranslated_filter();
-        }
-        (KeyModifiers::CONTROL, KeyCode::Char('z')) => {
-            app.toggle_fuzzy_filter();
+
+        match events.next(EVENT_POLL_INTERVAL)? {
+            Input::Key(key) => {
+                let outcome = handle_key_event(&mut app, key)?;
+                if let Some(ref mut snapshots) = snapshots {
+                    snapshots.step();
+                }
+                match outcome {
+                    Outcome::Quit => break,
+                    Outcome::Suspend if events.interactive() => {
+                        terminal::suspend(terminal).context("Failed to suspend")?
+                    }
+                    _ => {}
+                }
+            }
+            Input::Paste(text) => {
+                app.paste_text(&text);
+                if let Some(ref mut snapshots) = snapshots {
+                    snapshots.step();
+                }
+            }
+            Input::Idle => {}
+            Input::End => break,
         }
-        
-        // Help
-        (KeyModifiers::NONE, KeyCode::F(1)) => {
-            app.toggle_help();
+        if let Some(text) = app.take_terminal_copy() {
+            terminal.backend_mut().send(SetClipboard(&text))?;
         }
+        app.tick();
+    }
 
-        // F9 for metadata mode
-        (KeyModifiers::NONE, KeyCode::F(9)) => {
-            app.toggle_metadata_mode();
-        }
+    app.close_journal();
+
+    let warning = app.save_state().err().map(|e| format!("Failed to save recent files: {:#}", e));
+    title.restore()?;
+
+    Ok(Closing {
+        summary: app.session_summary(),
+        warning,
+    })
+}
+
+fn handle_key_event(app: &mut App, key: KeyEvent) -> Result<Outcome> {
+    match app.resolve_key(key) {
+        Some(action) => app.perform(action),
+        None => Ok(Outcome::Blocked),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BROKEN: &str = "msgid \"Open\"\nmsgstr \"Открыть\"\n\nmsgid Old\nmsgstr Старый\n";
+    const CLEAN: &str = "msgid \"Open\"\nmsgstr \"Открыть\"\n";
 
-        // Toggle fuzzy status
-        (KeyModifiers::NONE, KeyCode::F(2)) => {
-            app.toggle_current_entry_fuzzy();
+    fn cli(args: &[&str]) -> Cli {
+        Cli::parse_from(std::iter::once("poterm").chain(args.iter().copied()))
+    }
+
+    #[test]
+    fn test_strict_reports_parse_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        let broken = dir.path().join("broken.po");
+        let clean = dir.path().join("clean.po");
+        std::fs::write(&broken, BROKEN).unwrap();
+        std::fs::write(&clean, CLEAN).unwrap();
+        let broken = broken.to_str().unwrap();
+        let clean = clean.to_str().unwrap();
+
+        assert!(strict_diagnostics(&cli(&[broken])).unwrap().is_none());
+        assert!(strict_diagnostics(&cli(&["--strict", clean])).unwrap().is_none());
+        let diagnostics = strict_diagnostics(&cli(&["--strict", broken])).unwrap().unwrap();
+        assert_eq!(diagnostics.len(), 2);
+        assert!(diagnostics[0].starts_with("Line 4:"));
+
+        assert!(Cli::try_parse_from(["poterm", "--force", broken]).is_err());
+        #[cfg(debug_assertions)]
+        assert!(cli(&["--debug-panic", clean]).debug_panic);
+        assert!(cli(&["--strict", "--force", broken]).force);
+    }
+
+    #[test]
+    fn test_completions_and_man_pages_cover_subcommands() {
+        let names: Vec<String> = Cli::command().get_subcommands().map(|command| command.get_name().to_string()).collect();
+        assert!(names.contains(&"check".to_string()));
+
+        for shell in [Shell::Bash, Shell::Zsh, Shell::Fish, Shell::PowerShell] {
+            let mut script = Vec::new();
+            print_completions(shell, &mut script);
+            let script = String::from_utf8(script).unwrap();
+            for name in &names {
+                assert!(script.contains(name.as_str()), "{} completions miss {}", shell, name);
+            }
+            assert!(script.contains("show-suppressed"), "{} completions miss subcommand flags", shell);
         }
 
-        // Mark entry as done (remove fuzzy flag)
-        (KeyModifiers::CONTROL, KeyCode::Char('d')) => {
-            app.mark_current_entry_done();
+        let mut page = Vec::new();
+        clap_mangen::Man::new(Cli::command()).render(&mut page).unwrap();
+        let page = String::from_utf8(page).unwrap();
+        for name in &names {
+            // roff escapes every hyphen
+            let reference = format!("poterm-{}(1)", name).replace('-', "\\-");
+            assert!(page.contains(&reference), "man page misses {}", name);
         }
 
-        // Alternative fuzzy toggle with Ctrl+T (T for Toggle)
-        (KeyModifiers::CONTROL, KeyCode::Char('t')) => {
-            app.toggle_current_entry_fuzzy();
+        let dir = tempfile::tempdir().unwrap();
+        write_manpages(dir.path()).unwrap();
+        for name in &names {
+            let page = std::fs::read_to_string(dir.path().join(format!("poterm-{}.1", name))).unwrap();
+            assert!(page.contains(".SH SYNOPSIS"), "{}", page);
         }
-        
-        // Handle text input when editing
-        _ => {
-            if app.is_editing() {
-                app.handle_input(key);
-            }
+        assert!(std::fs::read_to_string(dir.path().join("poterm-check.1")).unwrap().contains("show\\-suppressed"));
+    }
+
+    #[test]
+    fn test_replay_script() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ru.po");
+        let script = dir.path().join("search-edit-save.txt");
+        let snapshots = dir.path().join("snapshots");
+        std::fs::write(&path, "msgid \"Quit\"\nmsgstr \"Выход\"\n\nmsgid \"Open\"\nmsgstr \"\"\n").unwrap();
+        std::fs::write(&script, "ctrl+f\ntext:Open\nenter\nenter\ntext:Открыть\nenter\nctrl+s\nctrl+q\n").unwrap();
+        let (path_arg, script_arg, snapshots_arg) =
+            (path.to_str().unwrap(), script.to_str().unwrap(), snapshots.to_str().unwrap());
+
+        let cli = cli(&["--replay", script_arg, "--replay-render", snapshots_arg, path_arg]);
+        let config = Config {
+            persist_state: false,
+            swap_file: false,
+            ..Config::default()
+        };
+        let mut events = Script::from_file(&script).unwrap();
+        let snapshots = Snapshots::create(&snapshots).ok();
+        let mut terminal = Terminal::new(TestBackend::new(REPLAY_SIZE.0, REPLAY_SIZE.1)).unwrap();
+        run_app(&mut terminal, &mut events, snapshots, cli, config, false, None).unwrap();
+
+        let saved = PoFile::from_file(&path).unwrap();
+        assert_eq!(saved.entries[1].msgstr, "Открыть");
+        // The first frame, then one per key but the one that quit
+        let frames = std::fs::read_dir(dir.path().join("snapshots")).unwrap().count();
+        assert_eq!(frames, 17);
+        let after_typing = std::fs::read_to_string(dir.path().join("snapshots").join("0014.txt")).unwrap();
+        assert!(after_typing.contains("Открыть"), "{}", after_typing);
+
+        assert!(Cli::try_parse_from(["poterm", "--replay", script_arg]).is_err());
+        assert!(Cli::try_parse_from(["poterm", "--replay-render", snapshots_arg, path_arg]).is_err());
+    }
+
+    #[test]
+    fn test_output_leaves_source_untouched() {
+        use crate::keymap::Action;
+
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("ru.po");
+        let output = dir.path().join("ru-experiment.po");
+        std::fs::write(&source, CLEAN).unwrap();
+        let modified = std::fs::metadata(&source).unwrap().modified().unwrap();
+        let (source_arg, output_arg) = (source.to_str().unwrap(), output.to_str().unwrap());
+
+        let cli = cli(&["-o", output_arg, source_arg]);
+        let po_file = open_from_cli(&cli).unwrap().unwrap();
+        assert_eq!(po_file.path.as_deref(), Some(output.as_path()));
+        let mut app = App::new(po_file, Config::default()).with_source(Some(source.clone())).with_journal();
+        app.perform(Action::ToggleFuzzy).unwrap();
+        app.perform(Action::Save).unwrap();
+        app.perform(Action::ToggleFuzzy).unwrap();
+        app.perform(Action::Next).unwrap();
+        assert_eq!(app.perform(Action::SaveAndQuit).unwrap(), Outcome::Quit);
+        app.close_journal();
+
+        assert_eq!(std::fs::metadata(&source).unwrap().modified().unwrap(), modified);
+        assert_eq!(std::fs::read_to_string(&source).unwrap(), CLEAN);
+        assert!(std::fs::read_to_string(&output).unwrap().contains("msgstr \"Открыть\""));
+        assert!(!journal::swap_path(&source).exists());
+
+        // Nothing to redirect without a file, or in a read-only session
+        assert!(Cli::try_parse_from(["poterm", "-o", output_arg]).is_err());
+        assert!(Cli::try_parse_from(["poterm", "--strict", "--force", "-o", output_arg, source_arg]).is_err());
+    }
+
+    #[test]
+    fn test_output_with_from_pot() {
+        let dir = tempfile::tempdir().unwrap();
+        let pot = dir.path().join("app.pot");
+        let output = dir.path().join("de.po");
+        std::fs::write(&pot, "msgid \"Open\"\nmsgstr \"\"\n").unwrap();
+        let (pot_arg, output_arg) = (pot.to_str().unwrap(), output.to_str().unwrap());
+
+        for args in [&["poterm", "--from-pot", pot_arg, "-o", output_arg][..], &["poterm", "--from-pot", pot_arg, "-o", output_arg, "ru.po"]] {
+            let po_file = open_from_cli(&Cli::try_parse_from(args).unwrap()).unwrap().unwrap();
+            assert_eq!(po_file.path.as_deref(), Some(output.as_path()));
+            assert_eq!(po_file.entries[0].msgid, "Open");
+            assert!(!po_file.is_template());
         }
+        assert!(!output.exists());
+    }
+
+    #[test]
+    fn test_open_mo() {
+        let dir = tempfile::tempdir().unwrap();
+        // Named like a .po: the magic number tells them apart
+        let path = dir.path().join("ru.po");
+        let output = dir.path().join("recovered.po");
+        mo::write_mo(&PoFile::parse(CLEAN).unwrap(), &path).unwrap();
+        let (path_arg, output_arg) = (path.to_str().unwrap(), output.to_str().unwrap());
+
+        let po_file = open_from_cli(&cli(&[path_arg])).unwrap().unwrap();
+        assert_eq!(po_file.path, None);
+        assert!(po_file.is_modified());
+        assert_eq!(po_file.entries[0].msgstr, "Открыть");
+        let po_file = open_from_cli(&cli(&["-o", output_arg, path_arg])).unwrap().unwrap();
+        assert_eq!(po_file.path.as_deref(), Some(output.as_path()));
+        assert!(strict_diagnostics(&cli(&["--strict", path_arg])).unwrap().is_none());
     }
-    
-    Ok(false)
-}
\ No newline at end of file
+
+    #[test]
+    fn test_strict_with_create_and_from_pot() {
+        let dir = tempfile::tempdir().unwrap();
+        let pot = dir.path().join("app.pot");
+        std::fs::write(&pot, BROKEN).unwrap();
+        let new_po = dir.path().join("de.po");
+        let new_po = new_po.to_str().unwrap();
+
+        // A file about to be created has nothing to report
+        assert!(strict_diagnostics(&cli(&["--strict", "--create", new_po])).unwrap().is_none());
+        // A new file from a template inherits the template's problems
+        let args = ["--strict", "--from-pot", pot.to_str().unwrap(), new_po];
+        assert_eq!(strict_diagnostics(&cli(&args)).unwrap().unwrap().len(), 2);
+        // An existing file is checked even with --create
+        std::fs::write(new_po, BROKEN).unwrap();
+        assert!(strict_diagnostics(&cli(&["--strict", "--create", new_po])).unwrap().is_some());
+    }
+}
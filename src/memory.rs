@@ -0,0 +1,366 @@
+// Poterm - Modern TUI editor for .po translation files
+// Copyright (c) 2025 AnmiTaliDev <anmitali198@gmail.com>
+// Licensed under the Apache License, Version 2.0
+
+//! Translation memory: the translations of other catalogs, looked up by
+//! msgid
+//!
+//! Sources are indexed once by their character trigrams, so a lookup only
+//! measures the edit distance to the few sources that share the most
+//! trigrams with the msgid instead of to every one.
+
+use crate::config::Config;
+use crate::gettext::{PoEntry, PoFile};
+use crate::merge::similarity;
+use crate::mo;
+use anyhow::{Context, Result};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// Sources less alike than this are not suggested
+const MIN_SIMILARITY: f64 = 0.7;
+/// Sources sharing the most trigrams that are compared in full
+pub(crate) const CANDIDATES: usize = 50;
+/// Marks the ends of a source so short ones have trigrams too
+const EDGE: char = '\0';
+
+/// A translation from the memory
+#[derive(Debug, Clone, PartialEq)]
+pub struct Suggestion {
+    /// msgid it translates
+    pub source: String,
+    /// msgstr, or msgstr[0], msgstr[1], ... of a plural entry
+    pub forms: Vec<String>,
+    /// File name of the catalog it is from
+    pub origin: String,
+    /// 1 for the same msgid, down to `MIN_SIMILARITY`
+    pub similarity: f64,
+}
+
+#[derive(Debug, Clone)]
+struct Segment {
+    source: String,
+    forms: Vec<String>,
+    plural: bool,
+    /// Index into `origins`
+    origin: usize,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct TranslationMemory {
+    segments: Vec<Segment>,
+    /// File names of the catalogs, in the order they were added
+    origins: Vec<String>,
+    /// Segments by source
+    exact: HashMap<String, Vec<usize>>,
+    /// Segment sources by their trigrams
+    trigrams: TrigramIndex,
+}
+
+/// Distinct character trigrams of `text` with its ends marked
+fn trigrams(text: &str) -> HashSet<[char; 3]> {
+    let chars: Vec<char> = [EDGE].into_iter().chain(text.chars()).chain([EDGE]).collect();
+    chars.windows(3).map(|window| [window[0], window[1], window[2]]).collect()
+}
+
+/// Texts numbered in the order they were added and indexed by their
+/// trigrams
+#[derive(Debug, Clone, Default)]
+pub(crate) struct TrigramIndex {
+    /// Texts by their distinct trigrams
+    texts: HashMap<[char; 3], Vec<usize>>,
+    /// Length of each text in characters
+    lengths: Vec<usize>,
+}
+
+impl TrigramIndex {
+    pub(crate) fn push(&mut self, text: &str) {
+        let id = self.lengths.len();
+        for trigram in trigrams(text) {
+            self.texts.entry(trigram).or_default().push(id);
+        }
+        self.lengths.push(text.chars().count());
+    }
+
+    /// Texts sharing trigrams with `text` whose length lets them be at
+    /// least `threshold` similar, those sharing the most first
+    pub(crate) fn candidates(&self, text: &str, threshold: f64) -> Vec<usize> {
+        let length = text.chars().count();
+        let mut shared = vec![0u32; self.lengths.len()];
+        for trigram in trigrams(text) {
+            for &id in self.texts.get(&trigram).into_iter().flatten() {
+                shared[id] += 1;
+            }
+        }
+        // The lengths alone rule out most texts without comparing them
+        let mut candidates: Vec<usize> = (0..self.lengths.len())
+            .filter(|&id| shared[id] > 0)
+            .filter(|&id| {
+                let other = self.lengths[id];
+                length.min(other) as f64 >= threshold * length.max(other) as f64
+            })
+            .collect();
+        candidates.sort_by_key(|&id| std::cmp::Reverse(shared[id]));
+        candidates
+    }
+}
+
+impl TranslationMemory {
+    /// Index the translated entries of the .po (or .mo) files at `paths`.
+    /// Earlier files come first among equally good suggestions.
+    pub fn load(paths: &[PathBuf]) -> Result<Self> {
+        let mut memory = Self::default();
+        for path in paths {
+            let po_file = if mo::is_mo(path) { PoFile::from_mo(path) } else { PoFile::from_file(path) }
+                .with_context(|| format!("Failed to load translation memory {}", path.display()))?;
+            let origin = path.file_name().unwrap_or(path.as_os_str()).to_string_lossy();
+            memory.add(&po_file, &origin);
+        }
+        Ok(memory)
+    }
+
+    /// Index the translated entries of `po_file`. A translation the memory
+    /// already has for the same msgid is not added again.
+    fn add(&mut self, po_file: &PoFile, origin: &str) {
+        let origin = match self.origins.iter().position(|known| known == origin) {
+            Some(index) => index,
+            None => {
+                self.origins.push(origin.to_string());
+                self.origins.len() - 1
+            }
+        };
+        for entry in po_file.entries.iter().filter(|entry| entry.is_translated && !entry.msgid.is_empty()) {
+            let forms: Vec<String> = (0..entry.form_count()).map(|form| entry.form(form).to_string()).collect();
+            let known = self.exact.get(&entry.msgid).is_some_and(|ids| {
+                ids.iter().any(|&id| self.segments[id].forms == forms && self.segments[id].plural == entry.is_plural())
+            });
+            if known {
+                continue;
+            }
+            let id = self.segments.len();
+            self.trigrams.push(&entry.msgid);
+            self.exact.entry(entry.msgid.clone()).or_default().push(id);
+            self.segments.push(Segment {
+                source: entry.msgid.clone(),
+                forms,
+                plural: entry.is_plural(),
+                origin,
+            });
+        }
+    }
+
+    /// Number of translations
+    pub fn len(&self) -> usize {
+        self.segments.len()
+    }
+
+    /// Number of catalogs the translations are from
+    pub fn catalogs(&self) -> usize {
+        self.origins.len()
+    }
+
+    fn suggestion(&self, id: usize, similarity: f64) -> Suggestion {
+        let segment = &self.segments[id];
+        Suggestion {
+            source: segment.source.clone(),
+            forms: segment.forms.clone(),
+            origin: self.origins[segment.origin].clone(),
+            similarity,
+        }
+    }
+
+    /// Up to `count` translations for `msgid`: those of the same msgid
+    /// first, then those of the most similar sources by edit distance
+    pub fn suggestions(&self, msgid: &str, count: usize) -> Vec<Suggestion> {
+        let mut found: Vec<Suggestion> = self
+            .exact
+            .get(msgid)
+            .into_iter()
+            .flatten()
+            .take(count)
+            .map(|&id| self.suggestion(id, 1.0))
+            .collect();
+        if found.len() < count && !msgid.is_empty() {
+            let similar = self.similar(msgid);
+            found.extend(similar.into_iter().take(count - found.len()).map(|(id, score)| self.suggestion(id, score)));
+        }
+        found
+    }
+
+    /// Segments with another source at least `MIN_SIMILARITY` like
+    /// `msgid`, the most similar first
+    fn similar(&self, msgid: &str) -> Vec<(usize, f64)> {
+        let target: Vec<char> = msgid.chars().collect();
+        let mut scored: Vec<(usize, f64)> = self
+            .trigrams
+            .candidates(msgid, MIN_SIMILARITY)
+            .into_iter()
+            .filter(|&id| self.segments[id].source != msgid)
+            .take(CANDIDATES)
+            .map(|id| {
+                let source: Vec<char> = self.segments[id].source.chars().collect();
+                (id, similarity(&target, &source))
+            })
+            .filter(|&(_, score)| score >= MIN_SIMILARITY)
+            .collect();
+        scored.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+        scored
+    }
+
+    /// The first translation of the same msgid with as many forms as
+    /// `entry` has
+    fn exact_match(&self, entry: &PoEntry) -> Option<&Segment> {
+        self.exact.get(&entry.msgid)?.iter().map(|&id| &self.segments[id]).find(|segment| {
+            segment.plural == entry.is_plural() && segment.forms.len() == entry.form_count()
+        })
+    }
+}
+
+/// Fill the entries without any translation that have one in `memory` for
+/// the same msgid, marked fuzzy with `fuzzy`. Returns how many were filled.
+pub fn pretranslate(po_file: &mut PoFile, memory: &TranslationMemory, fuzzy: bool) -> usize {
+    let mut filled = 0;
+    for entry in &mut po_file.entries {
+        let untranslated = (0..entry.form_count()).all(|form| entry.form(form).is_empty());
+        if !untranslated || entry.msgid.is_empty() {
+            continue;
+        }
+        let Some(segment) = memory.exact_match(entry) else {
+            continue;
+        };
+        for (form, text) in segment.forms.iter().enumerate() {
+            entry.set_form(form, text.clone());
+        }
+        if fuzzy != entry.is_fuzzy {
+            entry.toggle_fuzzy();
+        }
+        filled += 1;
+    }
+    if filled > 0 {
+        po_file.mark_modified();
+    }
+    filled
+}
+
+/// Headless `poterm pretranslate`: fill the untranslated entries of
+/// `po_path` from the catalogs at `memory_paths`, save it and print how
+/// many were filled
+pub fn run(po_path: &Path, memory_paths: &[PathBuf], fuzzy: bool, config: &Config) -> Result<()> {
+    let memory = TranslationMemory::load(memory_paths)?;
+    let mut po_file = PoFile::from_file(po_path).context("Failed to load .po file")?;
+    po_file.no_wrap = !config.wrap;
+    po_file.backup = config.backup;
+    let filled = pretranslate(&mut po_file, &memory, fuzzy);
+    if filled > 0 {
+        po_file.save()?;
+    }
+    println!("{}: {} entries filled from {} translations", po_path.display(), filled, memory.len());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OTHER: &str = concat!(
+        "msgid \"Open file\"\nmsgstr \"Открыть файл\"\n\n",
+        "msgid \"Open the file\"\nmsgstr \"Открыть этот файл\"\n\n",
+        "msgid \"Open files\"\nmsgstr \"Открыть файлы\"\n\n",
+        "#, fuzzy\nmsgid \"Close file\"\nmsgstr \"Закрыть файл\"\n\n",
+        "msgid \"Quit\"\nmsgstr \"\"\n\n",
+        "msgid \"%d file\"\nmsgid_plural \"%d files\"\nmsgstr[0] \"%d файл\"\nmsgstr[1] \"%d файла\"\nmsgstr[2] \"%d файлов\"\n",
+    );
+    const THIRD: &str = concat!(
+        "msgid \"Open file\"\nmsgstr \"Открыть файл\"\n\n",
+        "msgid \"Open file\"\nmsgstr \"Открытие файла\"\n",
+    );
+
+    fn memory() -> TranslationMemory {
+        let mut memory = TranslationMemory::default();
+        memory.add(&PoFile::parse(OTHER).unwrap(), "other.po");
+        memory.add(&PoFile::parse(THIRD).unwrap(), "third.po");
+        memory
+    }
+
+    #[test]
+    fn test_index() {
+        let memory = memory();
+        // Fuzzy and untranslated entries are left out, repeats kept once
+        assert_eq!((memory.len(), memory.catalogs()), (5, 2));
+        assert_eq!(trigrams("ab"), HashSet::from([[EDGE, 'a', 'b'], ['a', 'b', EDGE]]));
+    }
+
+    #[test]
+    fn test_suggestions() {
+        let memory = memory();
+        let suggestions = memory.suggestions("Open file", 3);
+        let found: Vec<(&str, &str, f64)> = suggestions
+            .iter()
+            .map(|s| (s.forms[0].as_str(), s.origin.as_str(), s.similarity))
+            .collect();
+        assert_eq!(
+            found,
+            [("Открыть файл", "other.po", 1.0), ("Открытие файла", "third.po", 1.0), ("Открыть файлы", "other.po", 0.9)]
+        );
+
+        let suggestions = memory.suggestions("Open a file", 3);
+        let sources: Vec<&str> = suggestions.iter().map(|s| s.source.as_str()).collect();
+        assert_eq!(sources, ["Open file", "Open file", "Open the file"]);
+        assert!(suggestions[0].similarity > suggestions[2].similarity);
+
+        assert!(memory.suggestions("Save everything", 3).is_empty());
+        assert!(memory.suggestions("", 3).is_empty());
+        assert_eq!(memory.suggestions("%d file", 1)[0].forms, ["%d файл", "%d файла", "%d файлов"]);
+    }
+
+    #[test]
+    fn test_many_segments() {
+        let mut po_file = PoFile::default();
+        for n in 0..20_000 {
+            let mut entry = PoEntry::new();
+            entry.msgid = format!("Message number {} of the catalog", n);
+            entry.set_msgstr(format!("Сообщение номер {} каталога", n));
+            po_file.entries.push(entry);
+        }
+        let mut memory = TranslationMemory::default();
+        memory.add(&po_file, "big.po");
+        let suggestions = memory.suggestions("Message number 12345 of this catalog", 3);
+        assert_eq!(suggestions.len(), 3);
+        assert_eq!(suggestions[0].source, "Message number 12345 of the catalog");
+    }
+
+    #[test]
+    fn test_pretranslate() {
+        let mut po_file = PoFile::parse(concat!(
+            "msgid \"\"\nmsgstr \"\"\n\"Plural-Forms: nplurals=3; plural=(n%10==1 && n%100!=11 ? 0 : n%10>=2 && n%10<=4 && (n%100<10 || n%100>=20) ? 1 : 2);\\n\"\n\n",
+            "msgid \"Open file\"\nmsgstr \"\"\n\n",
+            "msgid \"Open the file\"\nmsgstr \"Открыть файл\"\n\n",
+            "msgid \"Open a file\"\nmsgstr \"\"\n\n",
+            "msgid \"%d file\"\nmsgid_plural \"%d files\"\nmsgstr[0] \"\"\nmsgstr[1] \"\"\nmsgstr[2] \"\"\n",
+        ))
+        .unwrap();
+        assert_eq!(pretranslate(&mut po_file, &memory(), false), 2);
+        assert!(po_file.is_modified());
+        assert_eq!(po_file.entries[0].msgstr, "Открыть файл");
+        assert!(po_file.entries[0].is_translated);
+        // Translated entries stay, similar msgids are not filled
+        assert_eq!(po_file.entries[1].msgstr, "Открыть файл");
+        assert_eq!(po_file.entries[2].msgstr, "");
+        assert_eq!(po_file.entries[3].msgstr_plural, ["%d файл", "%d файла", "%d файлов"]);
+
+        let mut po_file = PoFile::parse("msgid \"Open file\"\nmsgstr \"\"\n").unwrap();
+        assert_eq!(pretranslate(&mut po_file, &memory(), true), 1);
+        assert!(po_file.entries[0].is_fuzzy);
+    }
+
+    #[test]
+    fn test_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("other.po");
+        std::fs::write(&path, OTHER).unwrap();
+        let memory = TranslationMemory::load(&[path.clone(), path]).unwrap();
+        assert_eq!((memory.len(), memory.catalogs()), (4, 1));
+        let error = TranslationMemory::load(&[dir.path().join("gone.po")]).unwrap_err();
+        assert!(error.to_string().starts_with("Failed to load translation memory "));
+    }
+}
@@ -0,0 +1,355 @@
+// Poterm - Modern TUI editor for .po translation files
+// Copyright (c) 2025 AnmiTaliDev <anmitali198@gmail.com>
+// Licensed under the Apache License, Version 2.0
+
+//! Merging an updated template into a catalog, as `msgmerge` does
+
+use crate::config::Config;
+use crate::gettext::{EntryKey, PoEntry, PoFile};
+use crate::memory::{TrigramIndex, CANDIDATES};
+use anyhow::{Context, Result};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::path::Path;
+
+/// Flags the template decides on; the catalog's own are kept besides them
+fn is_template_flag(flag: &str) -> bool {
+    flag.ends_with("-format") || flag == "no-wrap" || flag == "wrap" || flag.starts_with("range:")
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct MergeOptions {
+    /// Give new entries the translation of the most similar old msgid,
+    /// marked fuzzy, when at least this similar (0 to 1). None only keeps
+    /// translations of unchanged msgids.
+    pub similarity: Option<f64>,
+}
+
+/// What a merge did to the catalog
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MergeSummary {
+    /// Entries of the template the catalog did not have
+    pub new: usize,
+    /// Entries whose translation needs review because the source changed
+    pub fuzzy: usize,
+    /// Translated entries the template dropped, kept as `#~`
+    pub obsolete: usize,
+}
+
+impl fmt::Display for MergeSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} new, {} fuzzy, {} obsolete", self.new, self.fuzzy, self.obsolete)
+    }
+}
+
+/// Entries and order of `template`, with the translations of `po_file`:
+/// unchanged msgids keep theirs, entries the template lost become obsolete,
+/// and obsolete entries the template has again come back. The header keeps
+/// everything but the template's `POT-Creation-Date`.
+pub fn merge(po_file: &mut PoFile, template: &PoFile, options: &MergeOptions) -> MergeSummary {
+    let mut summary = MergeSummary::default();
+    let old_entries = std::mem::take(&mut po_file.entries);
+    let mut by_key: HashMap<EntryKey, usize> = HashMap::new();
+    for (index, entry) in old_entries.iter().enumerate() {
+        by_key.entry(entry.key()).or_insert(index);
+    }
+    let mut revivable: HashMap<EntryKey, usize> = HashMap::new();
+    for (index, block) in po_file.obsolete_entries.iter().enumerate() {
+        if let Some(entry) = revive(block) {
+            revivable.entry(entry.key()).or_insert(index);
+        }
+    }
+    let mut used = HashSet::new();
+    let mut revived = HashSet::new();
+    let mut old_msgids = TrigramIndex::default();
+    if options.similarity.is_some() {
+        for entry in &old_entries {
+            old_msgids.push(&entry.msgid);
+        }
+    }
+
+    for template_entry in &template.entries {
+        let mut entry = template_entry.clone();
+        entry.line = 0;
+        entry.flags.retain(|flag| is_template_flag(flag));
+        entry.previous_msgctxt = None;
+        entry.previous_msgid = None;
+        entry.previous_msgid_plural = None;
+        let key = entry.key();
+
+        if let Some(&index) = by_key.get(&key) {
+            used.insert(index);
+            if carry_over(&mut entry, &old_entries[index], false) {
+                summary.fuzzy += 1;
+            }
+        } else if let Some(old) = revivable.get(&key).and_then(|&index| {
+            revived.insert(index);
+            revive(&po_file.obsolete_entries[index])
+        }) {
+            carry_over(&mut entry, &old, false);
+        } else if let Some(index) = options.similarity.and_then(|threshold| most_similar(&entry, &old_entries, &old_msgids, threshold)) {
+            used.insert(index);
+            carry_over(&mut entry, &old_entries[index], true);
+            summary.fuzzy += 1;
+        } else {
+            entry.clear_translation();
+            summary.new += 1;
+        }
+        entry.update_status();
+        po_file.entries.push(entry);
+    }
+
+    let mut obsolete: Vec<String> = po_file
+        .obsolete_entries
+        .drain(..)
+        .enumerate()
+        .filter(|(index, _)| !revived.contains(index))
+        .map(|(_, block)| block)
+        .collect();
+    for (index, entry) in old_entries.iter().enumerate() {
+        if !used.contains(&index) && entry.has_translation() {
+            obsolete.push(obsolete_block(entry, po_file.no_wrap));
+            summary.obsolete += 1;
+        }
+    }
+    po_file.obsolete_entries = obsolete;
+
+    if let Some(date) = template.header.get("POT-Creation-Date") {
+        po_file.header.insert("POT-Creation-Date".to_string(), date.clone());
+    }
+    po_file.pad_plural_forms();
+    po_file.mark_modified();
+    summary
+}
+
+/// Give `entry`, fresh from the template, the translation, comments and
+/// flags of `old`. A translation made for other source text is marked
+/// fuzzy, remembering that text as the previous msgid; returns whether
+/// that happened.
+fn carry_over(entry: &mut PoEntry, old: &PoEntry, similar: bool) -> bool {
+    entry.comments = old.comments.clone();
+    for flag in &old.flags {
+        if !is_template_flag(flag) && !entry.has_flag(flag) {
+            entry.flags.push(flag.clone());
+        }
+    }
+    match (entry.is_plural(), old.is_plural()) {
+        (true, true) => entry.msgstr_plural = old.msgstr_plural.clone(),
+        (true, false) => entry.msgstr_plural = vec![old.msgstr.clone()],
+        (false, _) => entry.msgstr_plural.clear(),
+    }
+    entry.msgstr = old.form(0).to_string();
+
+    let changed = similar || entry.msgid_plural != old.msgid_plural;
+    if changed && old.has_translation() {
+        if !entry.has_flag("fuzzy") {
+            entry.flags.push("fuzzy".to_string());
+        }
+        entry.previous_msgctxt = old.msgctxt.clone().filter(|_| old.msgctxt != entry.msgctxt);
+        entry.previous_msgid = Some(old.msgid.clone());
+        entry.previous_msgid_plural = old.msgid_plural.clone();
+    } else if old.has_flag("fuzzy") {
+        entry.previous_msgctxt = old.previous_msgctxt.clone();
+        entry.previous_msgid = old.previous_msgid.clone();
+        entry.previous_msgid_plural = old.previous_msgid_plural.clone();
+    }
+    entry.update_status();
+    changed && old.has_translation()
+}
+
+/// Old entry with a translation whose msgid is most like that of `entry`,
+/// at least `threshold` similar. Only the old msgids sharing the most
+/// trigrams with it are compared; of equally similar ones the first wins.
+fn most_similar(entry: &PoEntry, old_entries: &[PoEntry], old_msgids: &TrigramIndex, threshold: f64) -> Option<usize> {
+    let target: Vec<char> = entry.msgid.chars().collect();
+    let mut best: Option<(usize, f64)> = None;
+    let candidates = old_msgids.candidates(&entry.msgid, threshold).into_iter();
+    for index in candidates.filter(|&index| old_entries[index].has_translation()).take(CANDIDATES) {
+        let candidate: Vec<char> = old_entries[index].msgid.chars().collect();
+        let score = similarity(&target, &candidate);
+        let better = best.is_none_or(|(best_index, best)| score > best || (score == best && index < best_index));
+        if score >= threshold && better {
+            best = Some((index, score));
+        }
+    }
+    best.map(|(index, _)| index)
+}
+
+/// 1 for equal strings down to 0 for nothing in common: the share of
+/// characters the edit distance leaves alone
+pub(crate) fn similarity(a: &[char], b: &[char]) -> f64 {
+    let longer = a.len().max(b.len());
+    if longer == 0 {
+        return 1.0;
+    }
+    1.0 - edit_distance(a, b) as f64 / longer as f64
+}
+
+/// Levenshtein distance in characters
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.iter().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = if ca == cb { diagonal } else { 1 + diagonal.min(above).min(row[j]) };
+            diagonal = above;
+        }
+    }
+    row[b.len()]
+}
+
+/// `entry` written as an obsolete `#~` block. Translator comments and flags
+/// stay; source references and comments go with the source.
+fn obsolete_block(entry: &PoEntry, no_wrap: bool) -> String {
+    let entry = PoEntry {
+        extracted_comments: Vec::new(),
+        references: Vec::new(),
+        ..entry.clone()
+    };
+    let mut po_file = PoFile::default();
+    po_file.entries.push(entry);
+    po_file.no_wrap = no_wrap;
+    let text = po_file.to_string();
+    text.lines()
+        .map(|line| match line.strip_prefix("#| ") {
+            Some(previous) => format!("#~| {}", previous),
+            None if line.starts_with('#') => line.to_string(),
+            None => format!("#~ {}", line),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// The entry an obsolete `#~` block holds
+fn revive(block: &str) -> Option<PoEntry> {
+    let text: Vec<String> = block
+        .lines()
+        .map(|line| {
+            let line = line.trim_start();
+            match (line.strip_prefix("#~|"), line.strip_prefix("#~")) {
+                (Some(previous), _) => format!("#|{}", previous),
+                (None, Some(rest)) => rest.trim_start().to_string(),
+                (None, None) => line.to_string(),
+            }
+        })
+        .collect();
+    let mut entries = PoFile::parse(&text.join("\n")).ok()?.entries;
+    (entries.len() == 1).then(|| entries.remove(0))
+}
+
+/// Headless `poterm merge`: merge `template` into the catalog at `po_path`,
+/// save it and print what changed
+pub fn run(po_path: &Path, template: &Path, config: &Config) -> Result<()> {
+    let mut po_file = PoFile::from_file(po_path).context("Failed to load .po file")?;
+    po_file.no_wrap = !config.wrap;
+    po_file.backup = config.backup;
+    let summary = po_file.merge_from_pot(template, &config.merge_options())?;
+    po_file.save()?;
+    println!("{}: {}", po_path.display(), summary);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CATALOG: &str = concat!(
+        "msgid \"\"\nmsgstr \"\"\n\"POT-Creation-Date: 2025-01-01 10:00+0000\\n\"\n\"Language: ru\\n\"\n",
+        "\"Plural-Forms: nplurals=3; plural=(n%10==1 && n%100!=11 ? 0 : n%10>=2 && n%10<=4 && (n%100<10 || n%100>=20) ? 1 : 2);\\n\"\n\n",
+        "# Keep it short\n#: src/main.c:10\n#, c-format\nmsgid \"Open %s\"\nmsgstr \"Открыть %s\"\n\n",
+        "msgid \"Save the current file\"\nmsgstr \"Сохранить текущий файл\"\n\n",
+        "msgid \"Quit\"\nmsgstr \"Выход\"\n\n",
+        "msgid \"Unused\"\nmsgstr \"\"\n\n",
+        "#~ msgid \"Close\"\n#~ msgstr \"Закрыть\"\n",
+    );
+
+    const TEMPLATE: &str = concat!(
+        "msgid \"\"\nmsgstr \"\"\n\"POT-Creation-Date: 2025-06-01 12:00+0000\\n\"\n\n",
+        "#: src/main.c:12\n#, c-format\nmsgid \"Open %s\"\nmsgstr \"\"\n\n",
+        "msgid \"Save the current files\"\nmsgstr \"\"\n\n",
+        "msgid \"Close\"\nmsgstr \"\"\n\n",
+        "msgid \"Print\"\nmsgstr \"\"\n",
+    );
+
+    fn merged(options: MergeOptions) -> (PoFile, MergeSummary) {
+        let mut po_file = PoFile::parse(CATALOG).unwrap();
+        let summary = merge(&mut po_file, &PoFile::parse(TEMPLATE).unwrap(), &options);
+        (po_file, summary)
+    }
+
+    #[test]
+    fn test_merge_exact_matches() {
+        let (po_file, summary) = merged(MergeOptions::default());
+        assert_eq!(summary, MergeSummary { new: 2, fuzzy: 0, obsolete: 2 });
+        assert_eq!(summary.to_string(), "2 new, 0 fuzzy, 2 obsolete");
+        assert!(po_file.is_modified());
+
+        let msgids: Vec<&str> = po_file.entries.iter().map(|entry| entry.msgid.as_str()).collect();
+        assert_eq!(msgids, ["Open %s", "Save the current files", "Close", "Print"]);
+        let open = &po_file.entries[0];
+        assert_eq!(open.msgstr, "Открыть %s");
+        assert_eq!(open.references, ["src/main.c:12"]);
+        assert_eq!(open.comments, ["Keep it short"]);
+        assert_eq!(open.flags, ["c-format"]);
+        // Revived from the obsolete entries
+        assert_eq!(po_file.entries[2].msgstr, "Закрыть");
+        assert!(po_file.entries[2].is_translated);
+        assert_eq!(po_file.entries[3].msgstr, "");
+
+        // Untranslated entries the template lost are dropped
+        assert_eq!(
+            po_file.obsolete_entries,
+            [
+                "#~ msgid \"Save the current file\"\n#~ msgstr \"Сохранить текущий файл\"",
+                "#~ msgid \"Quit\"\n#~ msgstr \"Выход\"",
+            ]
+        );
+        assert_eq!(po_file.header["POT-Creation-Date"], "2025-06-01 12:00+0000");
+        assert_eq!(po_file.language(), Some("ru"));
+    }
+
+    #[test]
+    fn test_merge_similar_msgids() {
+        let (po_file, summary) = merged(MergeOptions { similarity: Some(0.8) });
+        assert_eq!(summary, MergeSummary { new: 1, fuzzy: 1, obsolete: 1 });
+        let save = &po_file.entries[1];
+        assert!(save.is_fuzzy);
+        assert_eq!(save.msgstr, "Сохранить текущий файл");
+        assert_eq!(save.previous_msgid.as_deref(), Some("Save the current file"));
+        assert!(po_file.to_string().contains("#, fuzzy\n#| msgid \"Save the current file\"\nmsgid \"Save the current files\""));
+        assert_eq!(po_file.obsolete_entries.last().unwrap(), "#~ msgid \"Quit\"\n#~ msgstr \"Выход\"");
+    }
+
+    #[test]
+    fn test_merge_plural_change_is_fuzzy() {
+        let mut po_file = PoFile::parse("msgid \"File\"\nmsgstr \"Файл\"\n").unwrap();
+        let template = PoFile::parse("msgid \"File\"\nmsgid_plural \"Files\"\nmsgstr[0] \"\"\nmsgstr[1] \"\"\n").unwrap();
+        let summary = merge(&mut po_file, &template, &MergeOptions::default());
+        assert_eq!(summary.fuzzy, 1);
+        let entry = &po_file.entries[0];
+        assert!(entry.is_fuzzy);
+        assert_eq!(entry.msgstr_plural[0], "Файл");
+        assert_eq!(entry.previous_msgid.as_deref(), Some("File"));
+    }
+
+    #[test]
+    fn test_obsolete_block_round_trip() {
+        let entry = PoFile::parse("# Old\n#, fuzzy\n#| msgid \"Exit\"\nmsgctxt \"menu\"\nmsgid \"Quit\"\nmsgstr \"Выход\"\n").unwrap().entries.remove(0);
+        let block = obsolete_block(&entry, false);
+        assert_eq!(block, "# Old\n#, fuzzy\n#~| msgid \"Exit\"\n#~ msgctxt \"menu\"\n#~ msgid \"Quit\"\n#~ msgstr \"Выход\"");
+        let revived = revive(&block).unwrap();
+        assert_eq!((revived.msgctxt, revived.msgid, revived.msgstr), (Some("menu".to_string()), "Quit".to_string(), "Выход".to_string()));
+        assert_eq!(revived.previous_msgid.as_deref(), Some("Exit"));
+    }
+
+    #[test]
+    fn test_similarity() {
+        let chars = |s: &str| s.chars().collect::<Vec<_>>();
+        assert_eq!(similarity(&chars("Open"), &chars("Open")), 1.0);
+        assert_eq!(similarity(&chars("Open"), &chars("Opens")), 0.8);
+        assert_eq!(similarity(&chars("abc"), &chars("xyz")), 0.0);
+        assert_eq!(similarity(&chars(""), &chars("")), 1.0);
+    }
+}
@@ -0,0 +1,483 @@
+// Poterm - Modern TUI editor for .po translation files
+// Copyright (c) 2025 AnmiTaliDev <anmitali198@gmail.com>
+// Licensed under the Apache License, Version 2.0
+
+//! GNU MO binary catalogs, as `msgfmt` writes them
+//!
+//! A little-endian header is followed by the tables of original and
+//! translated strings (length and offset of each, sorted by original), a
+//! hash table for lookups and the strings themselves, each ending in NUL.
+//! Catalogs compiled on big-endian machines are read too.
+
+use crate::gettext::{PoEntry, PoFile};
+use anyhow::{Context, Result};
+use std::fmt;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+const MAGIC: u32 = 0x9504_12de;
+/// Bytes of the header: magic, revision, string count and the offsets and
+/// size of the tables
+const HEADER_SIZE: u32 = 28;
+/// Separates msgctxt from msgid in an original string
+const CONTEXT_SEPARATOR: char = '\u{4}';
+/// Ends the static pieces of a system-dependent string
+const SEGMENTS_END: u32 = u32::MAX;
+
+/// What compiling a catalog left in and out
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CompileSummary {
+    /// Messages written, the header included
+    pub messages: usize,
+    pub fuzzy: usize,
+    pub untranslated: usize,
+}
+
+impl fmt::Display for CompileSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} messages", self.messages)?;
+        if self.fuzzy + self.untranslated > 0 {
+            write!(f, ", {} fuzzy and {} untranslated left out", self.fuzzy, self.untranslated)?;
+        }
+        Ok(())
+    }
+}
+
+/// `<name>.mo` next to the catalog at `path`
+pub fn mo_path(path: &Path) -> PathBuf {
+    path.with_extension("mo")
+}
+
+/// Original string of `entry`: msgctxt and msgid joined by EOT, then
+/// msgid_plural after a NUL
+fn original(entry: &PoEntry) -> String {
+    let mut original = match entry.msgctxt {
+        Some(ref msgctxt) => format!("{}{}{}", msgctxt, CONTEXT_SEPARATOR, entry.msgid),
+        None => entry.msgid.clone(),
+    };
+    if let Some(ref plural) = entry.msgid_plural {
+        original.push('\0');
+        original.push_str(plural);
+    }
+    original
+}
+
+/// Translated string of `entry`: its forms joined by NUL
+fn translation(entry: &PoEntry) -> String {
+    if entry.is_plural() {
+        entry.msgstr_plural.join("\0")
+    } else {
+        entry.msgstr.clone()
+    }
+}
+
+/// The messages msgfmt compiles, sorted by original string: the header and
+/// every entry with a first translation form that is not fuzzy. A message
+/// defined twice is an error, as for msgfmt.
+fn messages(po_file: &PoFile) -> Result<(Vec<(String, String)>, CompileSummary)> {
+    let mut summary = CompileSummary::default();
+    let mut messages = Vec::new();
+    if !po_file.header.is_empty() {
+        messages.push((String::new(), po_file.header_text()));
+    }
+    for entry in &po_file.entries {
+        if entry.is_fuzzy {
+            summary.fuzzy += 1;
+        } else if entry.form(0).is_empty() {
+            summary.untranslated += 1;
+        } else {
+            messages.push((original(entry), translation(entry)));
+        }
+    }
+    messages.sort_by(|a, b| a.0.as_bytes().cmp(b.0.as_bytes()));
+    if let Some(pair) = messages.windows(2).find(|pair| pair[0].0 == pair[1].0) {
+        let msgid = pair[0].0.replace(CONTEXT_SEPARATOR, " | ").replace('\0', " / ");
+        anyhow::bail!("\"{}\" is defined more than once", msgid);
+    }
+    summary.messages = messages.len();
+    Ok((messages, summary))
+}
+
+/// The catalog compiled to MO, byte for byte what msgfmt writes
+pub fn to_mo(po_file: &PoFile) -> Result<(Vec<u8>, CompileSummary)> {
+    let (messages, summary) = messages(po_file)?;
+    let count = messages.len() as u32;
+    let hash_size = hash_table_size(messages.len());
+    let originals_offset = HEADER_SIZE;
+    let translations_offset = originals_offset + 8 * count;
+    let hash_offset = translations_offset + 8 * count;
+    let mut string_offset = hash_offset + 4 * hash_size;
+
+    let mut out = Vec::new();
+    for word in [MAGIC, 0, count, originals_offset, translations_offset, hash_size, hash_offset] {
+        out.extend_from_slice(&word.to_le_bytes());
+    }
+    let strings = messages
+        .iter()
+        .map(|(original, _)| original)
+        .chain(messages.iter().map(|(_, translation)| translation));
+    let mut data = Vec::new();
+    for string in strings.clone() {
+        out.extend_from_slice(&(string.len() as u32).to_le_bytes());
+        out.extend_from_slice(&string_offset.to_le_bytes());
+        string_offset += string.len() as u32 + 1;
+    }
+    for string in strings {
+        data.extend_from_slice(string.as_bytes());
+        data.push(0);
+    }
+    for slot in hash_table(&messages, hash_size) {
+        out.extend_from_slice(&slot.to_le_bytes());
+    }
+    out.extend_from_slice(&data);
+    Ok((out, summary))
+}
+
+/// Compile the catalog to the MO file at `path`
+pub fn write_mo(po_file: &PoFile, path: &Path) -> Result<CompileSummary> {
+    let (bytes, summary) = to_mo(po_file)?;
+    fs::write(path, bytes).with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(summary)
+}
+
+/// Whether the file at `path` starts with the MO magic number, in either
+/// byte order, whatever its extension
+pub fn is_mo(path: &Path) -> bool {
+    let mut magic = [0u8; 4];
+    fs::File::open(path).and_then(|mut file| file.read_exact(&mut magic)).is_ok() && big_endian(magic).is_some()
+}
+
+/// Byte order the magic number is written in, None when it is not there
+fn big_endian(magic: [u8; 4]) -> Option<bool> {
+    match u32::from_le_bytes(magic) {
+        MAGIC => Some(false),
+        word if word.swap_bytes() == MAGIC => Some(true),
+        _ => None,
+    }
+}
+
+/// Words and strings of a MO file, in the byte order it was written in
+struct Reader<'a> {
+    bytes: &'a [u8],
+    big_endian: bool,
+}
+
+impl<'a> Reader<'a> {
+    fn word(&self, offset: usize) -> Result<u32> {
+        let bytes = self
+            .bytes
+            .get(offset..offset + 4)
+            .context("The file is truncated")?
+            .try_into()
+            .expect("four bytes");
+        Ok(if self.big_endian { u32::from_be_bytes(bytes) } else { u32::from_le_bytes(bytes) })
+    }
+
+    /// String `index` of the table of lengths and offsets at `table`
+    fn string(&self, table: usize, index: usize) -> Result<&'a str> {
+        let len = self.word(table + 8 * index)? as usize;
+        let offset = self.word(table + 8 * index + 4)? as usize;
+        let bytes = self
+            .bytes
+            .get(offset..offset + len)
+            .with_context(|| format!("String {} is past the end of the file", index))?;
+        std::str::from_utf8(bytes).with_context(|| format!("String {} is not UTF-8", index))
+    }
+
+    /// System-dependent string `index` of the table of offsets at `table`,
+    /// as it was in the .po: its static pieces with the segments between
+    /// them written back as `<PRIu64>` and the like
+    fn sysdep_string(&self, table: usize, index: usize, segments: usize) -> Result<String> {
+        let mut entry = self.word(table + 4 * index)? as usize;
+        let mut offset = self.word(entry)? as usize;
+        let mut bytes = Vec::new();
+        loop {
+            let size = self.word(entry + 4)? as usize;
+            let segment = self.word(entry + 8)?;
+            let piece = self.bytes.get(offset..offset + size);
+            bytes.extend_from_slice(piece.with_context(|| format!("String {} is past the end of the file", index))?);
+            if segment == SEGMENTS_END {
+                break;
+            }
+            let name = self.string(segments, segment as usize)?.trim_end_matches('\0');
+            match name {
+                "I" => bytes.extend_from_slice(name.as_bytes()),
+                _ => bytes.extend_from_slice(format!("<{}>", name).as_bytes()),
+            }
+            offset += size;
+            entry += 8;
+        }
+        // The static pieces end with the NUL of the string
+        if bytes.last() == Some(&0) {
+            bytes.pop();
+        }
+        String::from_utf8(bytes).with_context(|| format!("String {} is not UTF-8", index))
+    }
+}
+
+/// Entry of the original string `original` and its translation: msgctxt
+/// before EOT, msgid_plural and the plural forms after NULs
+fn entry(original: &str, translation: &str) -> PoEntry {
+    let mut entry = PoEntry::new();
+    let msgids = match original.split_once(CONTEXT_SEPARATOR) {
+        Some((msgctxt, msgids)) => {
+            entry.msgctxt = Some(msgctxt.to_string());
+            msgids
+        }
+        None => original,
+    };
+    match msgids.split_once('\0') {
+        Some((msgid, plural)) => {
+            entry.msgid = msgid.to_string();
+            entry.msgid_plural = Some(plural.to_string());
+            entry.msgstr_plural = translation.split('\0').map(str::to_string).collect();
+            entry.msgstr = entry.msgstr_plural[0].clone();
+        }
+        None => {
+            entry.msgid = msgids.to_string();
+            entry.msgstr = translation.to_string();
+        }
+    }
+    entry.update_status();
+    entry
+}
+
+/// The catalog compiled into `bytes`, in the order of its original strings,
+/// the system-dependent ones of revision 1 included, as msgunfmt lists them.
+/// The translation of the empty msgid becomes the header.
+pub fn from_bytes(bytes: &[u8]) -> Result<PoFile> {
+    let magic = bytes.get(..4).and_then(|magic| magic.try_into().ok());
+    let Some(big_endian) = magic.and_then(big_endian) else {
+        anyhow::bail!("Not a .mo file");
+    };
+    let reader = Reader { bytes, big_endian };
+    let revision = reader.word(4)?;
+    if revision >> 16 > 1 {
+        anyhow::bail!("Unsupported .mo revision {}.{}", revision >> 16, revision & 0xffff);
+    }
+    let count = reader.word(8)? as usize;
+    let originals = reader.word(12)? as usize;
+    let translations = reader.word(16)? as usize;
+
+    let mut messages = Vec::new();
+    for index in 0..count {
+        messages.push((reader.string(originals, index)?.to_string(), reader.string(translations, index)?.to_string()));
+    }
+    if revision & 0xffff >= 1 {
+        let segments = reader.word(32)? as usize;
+        let count = reader.word(36)? as usize;
+        let originals = reader.word(40)? as usize;
+        let translations = reader.word(44)? as usize;
+        for index in 0..count {
+            messages.push((
+                reader.sysdep_string(originals, index, segments)?,
+                reader.sysdep_string(translations, index, segments)?,
+            ));
+        }
+        messages.sort_by(|a, b| a.0.as_bytes().cmp(b.0.as_bytes()));
+    }
+
+    let mut po_file = PoFile::default();
+    for (original, translation) in messages {
+        if original.is_empty() {
+            po_file.read_header(&translation);
+        } else {
+            po_file.entries.push(entry(&original, &translation));
+        }
+    }
+    po_file.pad_plural_forms();
+    Ok(po_file)
+}
+
+/// Headless `poterm compile`: write `po_path` as `output`, or as the `.mo`
+/// next to it
+pub fn run(po_path: &Path, output: Option<&Path>) -> Result<()> {
+    let po_file = PoFile::from_file(po_path).context("Failed to load .po file")?;
+    let output = output.map_or_else(|| mo_path(po_path), Path::to_path_buf);
+    let summary = write_mo(&po_file, &output)?;
+    println!("{}: {}", output.display(), summary);
+    Ok(())
+}
+
+/// Slots of the hash table msgfmt writes for `count` messages: the next
+/// prime after 4/3 of them, and at least 3
+fn hash_table_size(count: usize) -> u32 {
+    let mut size = (count as u32 * 4 / 3) | 1;
+    while !is_prime(size) {
+        size += 2;
+    }
+    size.max(3)
+}
+
+/// For the odd numbers `hash_table_size` tries, as gettext tests them
+fn is_prime(candidate: u32) -> bool {
+    let (mut divisor, mut square) = (3u32, 9u32);
+    while square < candidate && !candidate.is_multiple_of(divisor) {
+        divisor += 1;
+        square += 4 * divisor;
+        divisor += 1;
+    }
+    !candidate.is_multiple_of(divisor)
+}
+
+/// gettext's `hash_string`, over the original string up to its first NUL
+fn hash_string(text: &str) -> u32 {
+    let mut hash: u32 = 0;
+    for &byte in text.as_bytes().iter().take_while(|&&byte| byte != 0) {
+        hash = (hash << 4).wrapping_add(byte as u32);
+        let high = hash & 0xf000_0000;
+        if high != 0 {
+            hash ^= high >> 24;
+            hash ^= high;
+        }
+    }
+    hash
+}
+
+/// 1-based message numbers placed by open addressing with double hashing
+fn hash_table(messages: &[(String, String)], size: u32) -> Vec<u32> {
+    let mut table = vec![0u32; size as usize];
+    for (number, (original, _)) in messages.iter().enumerate() {
+        let hash = hash_string(original);
+        let mut index = hash % size;
+        if table[index as usize] != 0 {
+            let increment = 1 + hash % (size - 2);
+            while table[index as usize] != 0 {
+                if index >= size - increment {
+                    index -= size - increment;
+                } else {
+                    index += increment;
+                }
+            }
+        }
+        table[index as usize] = number as u32 + 1;
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+
+    const FIXTURE: &str = concat!(
+        "msgid \"\"\nmsgstr \"\"\n\"Project-Id-Version: app 1.0\\n\"\n\"Language: ru\\n\"\n",
+        "\"MIME-Version: 1.0\\n\"\n\"Content-Type: text/plain; charset=UTF-8\\n\"\n",
+        "\"Content-Transfer-Encoding: 8bit\\n\"\n",
+        "\"Plural-Forms: nplurals=3; plural=(n%10==1 && n%100!=11 ? 0 : n%10>=2 && n%10<=4 && (n%100<10 || n%100>=20) ? 1 : 2);\\n\"\n\n",
+        "msgid \"Open\"\nmsgstr \"Открыть\"\n\n",
+        "msgctxt \"menu\"\nmsgid \"Open\"\nmsgstr \"Открыть…\"\n\n",
+        "msgid \"%d file\"\nmsgid_plural \"%d files\"\nmsgstr[0] \"%d файл\"\nmsgstr[1] \"%d файла\"\nmsgstr[2] \"%d файлов\"\n\n",
+        "#, fuzzy\nmsgid \"Quit\"\nmsgstr \"Выход\"\n\n",
+        "msgid \"Help\"\nmsgstr \"\"\n\n",
+        "msgid \"About\"\nmsgstr \"О программе\"\n\n",
+        "msgid \"Line one\\nline two\"\nmsgstr \"Строка один\\nстрока два\"\n\n",
+        "#~ msgid \"Old\"\n#~ msgstr \"Старое\"\n",
+    );
+
+    #[test]
+    fn test_compile_summary() {
+        let po_file = PoFile::parse(FIXTURE).unwrap();
+        let (messages, summary) = messages(&po_file).unwrap();
+        assert_eq!(summary, CompileSummary { messages: 6, fuzzy: 1, untranslated: 1 });
+        assert_eq!(summary.to_string(), "6 messages, 1 fuzzy and 1 untranslated left out");
+        let originals: Vec<&str> = messages.iter().map(|(original, _)| original.as_str()).collect();
+        assert_eq!(originals, ["", "%d file\0%d files", "About", "Line one\nline two", "Open", "menu\u{4}Open"]);
+        assert_eq!(messages[1].1, "%d файл\0%d файла\0%d файлов");
+    }
+
+    #[test]
+    fn test_duplicate_message() {
+        let po_file = PoFile::parse("msgid \"Open\"\nmsgstr \"Открыть\"\n\nmsgid \"Open\"\nmsgstr \"Открыть\"\n").unwrap();
+        assert_eq!(to_mo(&po_file).unwrap_err().to_string(), "\"Open\" is defined more than once");
+    }
+
+    #[test]
+    fn test_hash_table_size() {
+        assert_eq!(hash_table_size(0), 3);
+        assert_eq!(hash_table_size(3), 5);
+        assert_eq!(hash_table_size(6), 11);
+        assert_eq!(hash_table_size(100), 137);
+    }
+
+    #[test]
+    fn test_read_back() {
+        let po_file = PoFile::parse(FIXTURE).unwrap();
+        let (bytes, _) = to_mo(&po_file).unwrap();
+        let read = from_bytes(&bytes).unwrap();
+        assert_eq!(read.header, po_file.header);
+        let msgids: Vec<&str> = read.entries.iter().map(|entry| entry.msgid.as_str()).collect();
+        assert_eq!(msgids, ["%d file", "About", "Line one\nline two", "Open", "Open"]);
+        let plural = &read.entries[0];
+        assert_eq!(plural.msgid_plural.as_deref(), Some("%d files"));
+        assert_eq!(plural.msgstr_plural, ["%d файл", "%d файла", "%d файлов"]);
+        assert!(plural.is_translated);
+        assert_eq!(read.entries[4].msgctxt.as_deref(), Some("menu"));
+        assert_eq!(read.entries[4].msgstr, "Открыть…");
+        // What it reads compiles to the same file
+        assert_eq!(to_mo(&read).unwrap().0, bytes);
+    }
+
+    #[test]
+    fn test_read_big_endian() {
+        let (mut bytes, _) = to_mo(&PoFile::parse(FIXTURE).unwrap()).unwrap();
+        // Every word before the strings
+        let strings = u32::from_le_bytes(bytes[24..28].try_into().unwrap()) as usize
+            + 4 * u32::from_le_bytes(bytes[20..24].try_into().unwrap()) as usize;
+        for word in bytes[..strings].chunks_mut(4) {
+            word.reverse();
+        }
+        let read = from_bytes(&bytes).unwrap();
+        assert_eq!(read.entries.len(), 5);
+        assert_eq!(read.header.get("Language").map(String::as_str), Some("ru"));
+    }
+
+    #[test]
+    fn test_read_broken() {
+        let (bytes, _) = to_mo(&PoFile::parse(FIXTURE).unwrap()).unwrap();
+        assert_eq!(from_bytes(FIXTURE.as_bytes()).unwrap_err().to_string(), "Not a .mo file");
+        assert_eq!(from_bytes(&bytes[..20]).unwrap_err().to_string(), "The file is truncated");
+        let truncated = &bytes[..bytes.len() - 10];
+        assert!(from_bytes(truncated).unwrap_err().to_string().ends_with("is past the end of the file"));
+    }
+
+    /// System-dependent strings as msgfmt writes them, where it is installed
+    #[test]
+    fn test_read_system_dependent() {
+        let dir = tempfile::tempdir().unwrap();
+        let po_path = dir.path().join("pl.po");
+        let mo_path = dir.path().join("pl.mo");
+        let content = concat!(
+            "msgid \"\"\nmsgstr \"Content-Type: text/plain; charset=UTF-8\\n\"\n\n",
+            "#, c-format\nmsgid \"Using up to %<PRIu32> threads.\"\nmsgstr \"Wątków: %<PRIu32>.\"\n\n",
+            "msgid \"Open\"\nmsgstr \"Otwórz\"\n",
+        );
+        fs::write(&po_path, content).unwrap();
+        let status = Command::new("msgfmt").arg("-o").arg(&mo_path).arg(&po_path).status();
+        if !status.is_ok_and(|status| status.success()) {
+            return;
+        }
+        let read = from_bytes(&fs::read(&mo_path).unwrap()).unwrap();
+        let pairs: Vec<(&str, &str)> = read.entries.iter().map(|entry| (entry.msgid.as_str(), entry.msgstr.as_str())).collect();
+        assert_eq!(pairs[..2], [("Open", "Otwórz"), ("Using up to %<PRIu32> threads.", "Wątków: %<PRIu32>.")]);
+        // Newer versions also write the string as it expands here, which
+        // msgunfmt lists too
+        assert!(pairs[2..].iter().all(|&(msgid, _)| msgid == "Using up to %u threads."));
+    }
+
+    /// Compare with the output of msgfmt, where it is installed
+    #[test]
+    fn test_same_as_msgfmt() {
+        let dir = tempfile::tempdir().unwrap();
+        let po_path = dir.path().join("ru.po");
+        let expected = dir.path().join("expected.mo");
+        fs::write(&po_path, FIXTURE).unwrap();
+        let status = Command::new("msgfmt").arg("-o").arg(&expected).arg(&po_path).status();
+        if !status.is_ok_and(|status| status.success()) {
+            return;
+        }
+        let (bytes, _) = to_mo(&PoFile::from_file(&po_path).unwrap()).unwrap();
+        assert_eq!(bytes, fs::read(&expected).unwrap());
+    }
+}
@@ -0,0 +1,190 @@
+// Poterm - Modern TUI editor for .po translation files
+// Copyright (c) 2025 AnmiTaliDev <anmitali198@gmail.com>
+// Licensed under the Apache License, Version 2.0
+
+//! Searchable list of values that also accepts free-form input
+
+use crate::input::TextInput;
+use crossterm::event::{KeyCode, KeyEvent};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PickerItem {
+    pub label: String,
+    /// Shown dimmed after the label and searched as well, e.g. a code
+    pub detail: String,
+    /// Returned when the item is picked
+    pub value: String,
+}
+
+/// What the picker wants the editor to do after a key press
+#[derive(Debug, Clone, PartialEq)]
+pub enum PickerEvent {
+    None,
+    /// Either the value of the selected item or, when nothing matches, the
+    /// typed text
+    Pick(String),
+    Close,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Picker {
+    pub items: Vec<PickerItem>,
+    pub input: TextInput,
+    /// Index into `visible()`
+    pub selected: usize,
+    /// Why the last pick was rejected
+    pub error: Option<String>,
+}
+
+impl Picker {
+    pub fn new(items: Vec<PickerItem>) -> Self {
+        Self {
+            items,
+            input: TextInput::default(),
+            selected: 0,
+            error: None,
+        }
+    }
+
+    /// Select the first item whose detail is `detail`
+    pub fn select_detail(&mut self, detail: &str) {
+        if let Some(position) = self.visible().iter().position(|item| item.detail == detail) {
+            self.selected = position;
+        }
+    }
+
+    /// Items matching the input, best matches first: exact detail, then
+    /// prefix, substring and finally subsequence matches
+    pub fn visible(&self) -> Vec<&PickerItem> {
+        let query = self.input.text.trim().to_lowercase();
+        let mut matches: Vec<_> = self
+            .items
+            .iter()
+            .filter_map(|item| match_rank(&query, item).map(|rank| (rank, item)))
+            .collect();
+        matches.sort_by_key(|(rank, _)| *rank);
+        matches.into_iter().map(|(_, item)| item).collect()
+    }
+
+    pub fn selected_item(&self) -> Option<&PickerItem> {
+        self.visible().get(self.selected).copied()
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> PickerEvent {
+        match key.code {
+            KeyCode::Esc => return PickerEvent::Close,
+            KeyCode::Up => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down if self.selected + 1 < self.visible().len() => self.selected += 1,
+            // Copy the selected value into the input to adjust it by hand
+            KeyCode::Tab => {
+                if let Some(value) = self.selected_item().map(|item| item.value.clone()) {
+                    self.input = TextInput::new(&value);
+                    self.selected = 0;
+                }
+            }
+            KeyCode::Enter => {
+                let value = match self.selected_item() {
+                    Some(item) => item.value.clone(),
+                    None => self.input.text.trim().to_string(),
+                };
+                if !value.is_empty() {
+                    return PickerEvent::Pick(value);
+                }
+            }
+            _ => {
+                if self.input.handle_key(key) {
+                    self.selected = 0;
+                    self.error = None;
+                }
+            }
+        }
+        PickerEvent::None
+    }
+}
+
+fn match_rank(query: &str, item: &PickerItem) -> Option<u8> {
+    if query.is_empty() {
+        return Some(0);
+    }
+    let label = item.label.to_lowercase();
+    let detail = item.detail.to_lowercase();
+    if detail == query {
+        Some(0)
+    } else if label.starts_with(query) || detail.starts_with(query) {
+        Some(1)
+    } else if label.contains(query) || detail.contains(query) {
+        Some(2)
+    } else if is_subsequence(query, &format!("{} {}", label, detail)) {
+        Some(3)
+    } else {
+        None
+    }
+}
+
+/// Whether the characters of `query` appear in `text` in order
+fn is_subsequence(query: &str, text: &str) -> bool {
+    let mut chars = text.chars();
+    query.chars().all(|q| chars.any(|c| c == q))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::KeyModifiers;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    fn picker() -> Picker {
+        let item = |label: &str, detail: &str| PickerItem {
+            label: label.to_string(),
+            detail: detail.to_string(),
+            value: format!("value of {}", detail),
+        };
+        Picker::new(vec![
+            item("German", "de"),
+            item("Portuguese", "pt"),
+            item("Portuguese (Brazil)", "pt_BR"),
+            item("Spanish", "es"),
+        ])
+    }
+
+    fn details(picker: &Picker) -> Vec<&str> {
+        picker.visible().iter().map(|item| item.detail.as_str()).collect()
+    }
+
+    #[test]
+    fn test_fuzzy_filter_and_pick() {
+        let mut picker = picker();
+        picker.select_detail("es");
+        assert_eq!(picker.selected_item().unwrap().label, "Spanish");
+
+        for c in "port".chars() {
+            picker.handle_key(key(KeyCode::Char(c)));
+        }
+        assert_eq!(details(&picker), vec!["pt", "pt_BR"]);
+        assert_eq!(picker.selected, 0);
+
+        picker.input = TextInput::new("ptbr");
+        assert_eq!(details(&picker), vec!["pt_BR"]);
+        picker.input = TextInput::new("es");
+        assert_eq!(details(&picker), vec!["es", "pt", "pt_BR"]);
+
+        picker.handle_key(key(KeyCode::Down));
+        assert_eq!(picker.handle_key(key(KeyCode::Enter)), PickerEvent::Pick("value of pt".to_string()));
+    }
+
+    #[test]
+    fn test_free_form_input() {
+        let mut picker = picker();
+        picker.select_detail("de");
+        picker.handle_key(key(KeyCode::Tab));
+        assert_eq!(picker.input.text, "value of de");
+        assert!(picker.visible().is_empty());
+
+        picker.input = TextInput::new(" x-klingon ");
+        assert_eq!(picker.handle_key(key(KeyCode::Enter)), PickerEvent::Pick("x-klingon".to_string()));
+        assert_eq!(picker.handle_key(key(KeyCode::Esc)), PickerEvent::Close);
+    }
+}
@@ -0,0 +1,186 @@
+// Poterm - Modern TUI editor for .po translation files
+// Copyright (c) 2025 AnmiTaliDev <anmitali198@gmail.com>
+// Licensed under the Apache License, Version 2.0
+
+use std::io::{Read, Write};
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PipeOutput {
+    Success(String),
+    Failed { code: Option<i32>, stderr: String },
+    TimedOut,
+    Cancelled,
+    Error(String),
+}
+
+/// A shell command running in the background with text on its stdin
+pub struct PipeJob {
+    pub command: String,
+    started: Instant,
+    receiver: Receiver<PipeOutput>,
+    cancel: Arc<AtomicBool>,
+}
+
+impl PipeJob {
+    pub fn spawn(command: &str, input: String, timeout: Duration) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let cancel = Arc::new(AtomicBool::new(false));
+        let worker_cancel = Arc::clone(&cancel);
+        let worker_command = command.to_string();
+
+        thread::spawn(move || {
+            let output = run(&worker_command, input, timeout, &worker_cancel);
+            let _ = sender.send(output);
+        });
+
+        Self {
+            command: command.to_string(),
+            started: Instant::now(),
+            receiver,
+            cancel,
+        }
+    }
+
+    /// Result of the command once it has finished
+    pub fn poll(&self) -> Option<PipeOutput> {
+        match self.receiver.try_recv() {
+            Ok(output) => Some(output),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => Some(PipeOutput::Error("Command worker stopped".to_string())),
+        }
+    }
+
+    pub fn cancel(&self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+}
+
+fn shell(command: &str) -> Command {
+    if cfg!(windows) {
+        let mut shell = Command::new("cmd");
+        shell.arg("/C").arg(command);
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        shell.arg("-c").arg(command);
+        shell
+    }
+}
+
+fn read_to_end<R: Read + Send + 'static>(reader: Option<R>) -> thread::JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut buffer = Vec::new();
+        if let Some(mut reader) = reader {
+            let _ = reader.read_to_end(&mut buffer);
+        }
+        buffer
+    })
+}
+
+fn run(command: &str, input: String, timeout: Duration, cancel: &AtomicBool) -> PipeOutput {
+    let mut child: Child = match shell(command)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+    {
+        Ok(child) => child,
+        Err(e) => return PipeOutput::Error(format!("Failed to run command: {}", e)),
+    };
+
+    // Feed stdin and drain the outputs concurrently so large texts can't
+    // deadlock on full pipe buffers
+    let stdin = child.stdin.take();
+    thread::spawn(move || {
+        if let Some(mut stdin) = stdin {
+            let _ = stdin.write_all(input.as_bytes());
+        }
+    });
+    let stdout = read_to_end(child.stdout.take());
+    let stderr = read_to_end(child.stderr.take());
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) => {}
+            Err(e) => return PipeOutput::Error(format!("Failed to wait for command: {}", e)),
+        }
+        if cancel.load(Ordering::Relaxed) || Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            return if cancel.load(Ordering::Relaxed) {
+                PipeOutput::Cancelled
+            } else {
+                PipeOutput::TimedOut
+            };
+        }
+        thread::sleep(POLL_INTERVAL);
+    };
+
+    let stdout = String::from_utf8_lossy(&stdout.join().unwrap_or_default()).into_owned();
+    let stderr = String::from_utf8_lossy(&stderr.join().unwrap_or_default()).into_owned();
+
+    if status.success() {
+        PipeOutput::Success(stdout)
+    } else {
+        PipeOutput::Failed {
+            code: status.code(),
+            stderr,
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    fn wait(job: &PipeJob) -> PipeOutput {
+        loop {
+            if let Some(output) = job.poll() {
+                return output;
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+    }
+
+    #[test]
+    fn test_pipe_success() {
+        let job = PipeJob::spawn("tr a-z A-Z", "hello мир".to_string(), Duration::from_secs(5));
+        assert_eq!(wait(&job), PipeOutput::Success("HELLO мир".to_string()));
+    }
+
+    #[test]
+    fn test_pipe_failure_reports_stderr() {
+        let job = PipeJob::spawn("echo oops >&2; exit 3", String::new(), Duration::from_secs(5));
+        assert_eq!(
+            wait(&job),
+            PipeOutput::Failed {
+                code: Some(3),
+                stderr: "oops\n".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_pipe_timeout_and_cancel() {
+        let job = PipeJob::spawn("sleep 5", String::new(), Duration::from_millis(100));
+        assert_eq!(wait(&job), PipeOutput::TimedOut);
+
+        let job = PipeJob::spawn("sleep 5", String::new(), Duration::from_secs(5));
+        job.cancel();
+        assert_eq!(wait(&job), PipeOutput::Cancelled);
+    }
+}
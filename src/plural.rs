@@ -0,0 +1,304 @@
+// Poterm - Modern TUI editor for .po translation files
+// Copyright (c) 2025 AnmiTaliDev <anmitali198@gmail.com>
+// Licensed under the Apache License, Version 2.0
+
+//! Parser and evaluator for `Plural-Forms` headers
+
+/// Values of `n` checked when validating an expression
+const CHECKED_RANGE: std::ops::RangeInclusive<u64> = 0..=1000;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Op {
+    Or,
+    And,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    N,
+    Number(u64),
+    Not(Box<Expr>),
+    Binary(Op, Box<Expr>, Box<Expr>),
+    Ternary(Box<Expr>, Box<Expr>, Box<Expr>),
+}
+
+impl Expr {
+    /// None on division by zero
+    fn eval(&self, n: u64) -> Option<u64> {
+        Some(match self {
+            Expr::N => n,
+            Expr::Number(value) => *value,
+            Expr::Not(expr) => (expr.eval(n)? == 0) as u64,
+            Expr::Ternary(cond, then, otherwise) => {
+                if cond.eval(n)? != 0 {
+                    then.eval(n)?
+                } else {
+                    otherwise.eval(n)?
+                }
+            }
+            Expr::Binary(op, left, right) => {
+                let a = left.eval(n)?;
+                // Short-circuit like C so `n != 0 && 10 / n` is fine
+                match op {
+                    Op::Or if a != 0 => return Some(1),
+                    Op::And if a == 0 => return Some(0),
+                    _ => {}
+                }
+                let b = right.eval(n)?;
+                match op {
+                    Op::Or | Op::And => (b != 0) as u64,
+                    Op::Eq => (a == b) as u64,
+                    Op::Ne => (a != b) as u64,
+                    Op::Lt => (a < b) as u64,
+                    Op::Le => (a <= b) as u64,
+                    Op::Gt => (a > b) as u64,
+                    Op::Ge => (a >= b) as u64,
+                    Op::Add => a.wrapping_add(b),
+                    Op::Sub => a.wrapping_sub(b),
+                    Op::Mul => a.wrapping_mul(b),
+                    Op::Div => a.checked_div(b)?,
+                    Op::Rem => a.checked_rem(b)?,
+                }
+            }
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    N,
+    Number(u64),
+    Symbol(&'static str),
+}
+
+const SYMBOLS: [&str; 19] = [
+    "||", "&&", "==", "!=", "<=", ">=", "<", ">", "+", "-", "*", "/", "%", "!", "?", ":", "(", ")", ";",
+];
+
+fn tokenize(text: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut rest = text.trim_start();
+    while !rest.is_empty() {
+        if let Some(symbol) = SYMBOLS.iter().find(|symbol| rest.starts_with(**symbol)) {
+            tokens.push(Token::Symbol(symbol));
+            rest = &rest[symbol.len()..];
+        } else if rest.starts_with(|c: char| c.is_ascii_digit()) {
+            let end = rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len());
+            let value = rest[..end].parse().map_err(|_| format!("Number too large: {}", &rest[..end]))?;
+            tokens.push(Token::Number(value));
+            rest = &rest[end..];
+        } else if rest.starts_with('n') && !rest[1..].starts_with(|c: char| c.is_alphanumeric() || c == '_') {
+            tokens.push(Token::N);
+            rest = &rest[1..];
+        } else {
+            let c = rest.chars().next().unwrap_or_default();
+            return Err(format!("Unexpected `{}` in plural expression", c));
+        }
+        rest = rest.trim_start();
+    }
+    Ok(tokens)
+}
+
+/// Recursive descent over the C subset allowed in `plural=`
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn eat(&mut self, symbol: &str) -> bool {
+        if matches!(self.peek(), Some(Token::Symbol(s)) if *s == symbol) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, symbol: &str) -> Result<(), String> {
+        if self.eat(symbol) {
+            Ok(())
+        } else {
+            Err(format!("Expected `{}` in plural expression", symbol))
+        }
+    }
+
+    fn ternary(&mut self) -> Result<Expr, String> {
+        let cond = self.binary(0)?;
+        if !self.eat("?") {
+            return Ok(cond);
+        }
+        let then = self.ternary()?;
+        self.expect(":")?;
+        let otherwise = self.ternary()?;
+        Ok(Expr::Ternary(Box::new(cond), Box::new(then), Box::new(otherwise)))
+    }
+
+    /// Operators by precedence, loosest first
+    const LEVELS: [&'static [(&'static str, Op)]; 6] = [
+        &[("||", Op::Or)],
+        &[("&&", Op::And)],
+        &[("==", Op::Eq), ("!=", Op::Ne)],
+        &[("<=", Op::Le), (">=", Op::Ge), ("<", Op::Lt), (">", Op::Gt)],
+        &[("+", Op::Add), ("-", Op::Sub)],
+        &[("*", Op::Mul), ("/", Op::Div), ("%", Op::Rem)],
+    ];
+
+    fn binary(&mut self, level: usize) -> Result<Expr, String> {
+        if level == Self::LEVELS.len() {
+            return self.unary();
+        }
+        let mut left = self.binary(level + 1)?;
+        'operators: loop {
+            for &(symbol, op) in Self::LEVELS[level] {
+                if self.eat(symbol) {
+                    let right = self.binary(level + 1)?;
+                    left = Expr::Binary(op, Box::new(left), Box::new(right));
+                    continue 'operators;
+                }
+            }
+            return Ok(left);
+        }
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        if self.eat("!") {
+            return Ok(Expr::Not(Box::new(self.unary()?)));
+        }
+        match self.peek().cloned() {
+            Some(Token::N) => {
+                self.pos += 1;
+                Ok(Expr::N)
+            }
+            Some(Token::Number(value)) => {
+                self.pos += 1;
+                Ok(Expr::Number(value))
+            }
+            Some(Token::Symbol("(")) => {
+                self.pos += 1;
+                let expr = self.ternary()?;
+                self.expect(")")?;
+                Ok(expr)
+            }
+            Some(Token::Symbol(symbol)) => Err(format!("Unexpected `{}` in plural expression", symbol)),
+            None => Err("Plural expression ends too early".to_string()),
+        }
+    }
+}
+
+/// Parsed `nplurals=N; plural=EXPR;` header value
+#[derive(Debug, Clone, PartialEq)]
+pub struct PluralForms {
+    pub nplurals: usize,
+    expr: Expr,
+}
+
+impl PluralForms {
+    /// Parse and check a header value. The expression must give a form
+    /// below `nplurals` for every checked `n`.
+    pub fn parse(value: &str) -> Result<Self, String> {
+        let mut nplurals = None;
+        let mut plural = None;
+        for part in value.split(';').map(str::trim).filter(|part| !part.is_empty()) {
+            match part.split_once('=').map(|(key, value)| (key.trim(), value.trim())) {
+                Some(("nplurals", count)) => {
+                    let count: usize = count.parse().map_err(|_| format!("nplurals is not a number: {}", count))?;
+                    nplurals = Some(count);
+                }
+                Some(("plural", expr)) => plural = Some(expr),
+                _ => return Err(format!("Unexpected `{}` in Plural-Forms", part)),
+            }
+        }
+        let nplurals = nplurals.ok_or("Plural-Forms has no nplurals=")?;
+        if nplurals == 0 {
+            return Err("nplurals must be at least 1".to_string());
+        }
+        let plural = plural.ok_or("Plural-Forms has no plural=")?;
+
+        let mut parser = Parser {
+            tokens: tokenize(plural)?,
+            pos: 0,
+        };
+        let expr = parser.ternary()?;
+        if let Some(token) = parser.peek() {
+            return Err(format!("Unexpected {:?} after the plural expression", token));
+        }
+
+        let forms = Self { nplurals, expr };
+        for n in CHECKED_RANGE {
+            match forms.form(n) {
+                Some(form) if form < nplurals => {}
+                Some(form) => {
+                    return Err(format!("plural= gives form {} for n = {}, but nplurals is {}", form, n, nplurals))
+                }
+                None => return Err(format!("plural= divides by zero for n = {}", n)),
+            }
+        }
+        Ok(forms)
+    }
+
+    /// Index of the plural form used for `n`
+    pub fn form(&self, n: u64) -> Option<usize> {
+        self.expr.eval(n).map(|form| form as usize)
+    }
+
+    /// The first `count` numbers up to 1000 that use `form`
+    pub fn examples(&self, form: usize, count: usize) -> Vec<u64> {
+        (0..=1000).filter(|&n| self.form(n) == Some(form)).take(count).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_evaluate_common_rules() {
+        let ru = PluralForms::parse(
+            "nplurals=3; plural=(n%10==1 && n%100!=11 ? 0 : n%10>=2 && n%10<=4 && (n%100<10 || n%100>=20) ? 1 : 2);",
+        )
+        .unwrap();
+        assert_eq!(ru.nplurals, 3);
+        let forms: Vec<_> = [1, 2, 5, 11, 21, 22, 111].iter().map(|&n| ru.form(n).unwrap()).collect();
+        assert_eq!(forms, vec![0, 1, 2, 2, 0, 1, 2]);
+
+        let fr = PluralForms::parse("nplurals=2; plural=(n > 1)").unwrap();
+        assert_eq!((fr.form(0), fr.form(1), fr.form(2)), (Some(0), Some(0), Some(1)));
+
+        assert_eq!(ru.examples(0, 3), vec![1, 21, 31]);
+        assert_eq!(ru.examples(1, 4), vec![2, 3, 4, 22]);
+
+        let ja = PluralForms::parse("nplurals=1; plural=0;").unwrap();
+        assert_eq!(ja.form(7), Some(0));
+
+        assert_eq!(PluralForms::parse("plural=!n; nplurals=2;").unwrap().form(0), Some(1));
+    }
+
+    #[test]
+    fn test_invalid_headers_are_explained() {
+        let error = |value: &str| PluralForms::parse(value).unwrap_err();
+        assert_eq!(error("nplurals=INTEGER; plural=EXPRESSION;"), "nplurals is not a number: INTEGER");
+        assert_eq!(error("nplurals=2;"), "Plural-Forms has no plural=");
+        assert_eq!(error("nplurals=2; plural=(n != 1"), "Expected `)` in plural expression");
+        assert_eq!(error("nplurals=2; plural=n ? 1"), "Expected `:` in plural expression");
+        assert_eq!(error("nplurals=2; plural=m"), "Unexpected `m` in plural expression");
+        assert_eq!(error("nplurals=2; plural=n"), "plural= gives form 2 for n = 2, but nplurals is 2");
+        assert_eq!(error("nplurals=2; plural=1 % n"), "plural= divides by zero for n = 0");
+        assert!(PluralForms::parse("nplurals=2; plural=n != 0 && 10 / n > 1").is_ok());
+    }
+}
@@ -0,0 +1,121 @@
+// Poterm - Modern TUI editor for .po translation files
+// Copyright (c) 2025 AnmiTaliDev <anmitali198@gmail.com>
+// Licensed under the Apache License, Version 2.0
+
+//! A translation as the program would show it: placeholders filled in with
+//! sample values and tabs expanded. Line breaks are already real ones,
+//! since the parser interprets escape sequences.
+
+/// Columns between tab stops
+const TAB_WIDTH: usize = 8;
+
+/// Number shown for `%d` and the like
+pub const SAMPLE_NUMBER: u64 = 42;
+
+/// `msgstr` with printf and brace placeholders replaced by sample values;
+/// `n` stands in for numbers
+pub fn render(msgstr: &str, n: u64) -> String {
+    let chars: Vec<char> = msgstr.chars().collect();
+    let mut text = String::new();
+    let mut i = 0;
+    while i < chars.len() {
+        match placeholder(&chars[i..], n) {
+            Some((len, value)) => {
+                text.push_str(&value);
+                i += len;
+            }
+            None => {
+                text.push(chars[i]);
+                i += 1;
+            }
+        }
+    }
+    expand_tabs(&text)
+}
+
+/// Length in chars of the placeholder starting at `chars[0]` and its sample
+/// value
+fn placeholder(chars: &[char], n: u64) -> Option<(usize, String)> {
+    match chars.first()? {
+        '%' if chars.get(1) == Some(&'%') => Some((2, "%".to_string())),
+        '%' => {
+            // %s, %1$s, %-5.2f, %lld
+            let mut len = 1;
+            while chars.get(len).is_some_and(|c| c.is_ascii_digit() || "$-+ #0.*'".contains(*c)) {
+                len += 1;
+            }
+            while chars.get(len).is_some_and(|c| "hlLqjzt".contains(*c)) {
+                len += 1;
+            }
+            let value = match chars.get(len)? {
+                's' | '@' => "example".to_string(),
+                'd' | 'i' | 'u' => n.to_string(),
+                'f' | 'g' | 'e' => "3.14".to_string(),
+                'x' => format!("{:x}", n),
+                'X' => format!("{:X}", n),
+                'c' => "x".to_string(),
+                _ => return None,
+            };
+            Some((len + 1, value))
+        }
+        '{' => {
+            let end = chars.iter().position(|&c| c == '}')?;
+            let name: String = chars[1..end].iter().collect();
+            if name.chars().any(|c| c.is_whitespace() || c == '{') {
+                return None;
+            }
+            // {}, {0} and {0:>5} have no name to show
+            let name = name.split(':').next().unwrap_or_default();
+            let value = if name.is_empty() || name.chars().all(|c| c.is_ascii_digit()) {
+                "example".to_string()
+            } else {
+                name.to_string()
+            };
+            Some((end + 1, value))
+        }
+        _ => None,
+    }
+}
+
+fn expand_tabs(text: &str) -> String {
+    let mut expanded = String::new();
+    let mut column = 0;
+    for c in text.chars() {
+        match c {
+            '\t' => {
+                let spaces = TAB_WIDTH - column % TAB_WIDTH;
+                expanded.push_str(&" ".repeat(spaces));
+                column += spaces;
+            }
+            '\n' => {
+                expanded.push(c);
+                column = 0;
+            }
+            _ => {
+                expanded.push(c);
+                column += 1;
+            }
+        }
+    }
+    expanded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_placeholders() {
+        assert_eq!(render("Open %s", 42), "Open example");
+        assert_eq!(render("%1$s: %2$d files, 100%%", 42), "example: 42 files, 100%");
+        assert_eq!(render("%ld of %.1f%%", 5), "5 of 3.14%");
+        assert_eq!(render("Hello {name}, {} and {0}", 42), "Hello name, example and example");
+        // Not placeholders
+        assert_eq!(render("50% off {a b} %", 42), "50% off {a b} %");
+    }
+
+    #[test]
+    fn test_tabs_and_lines() {
+        assert_eq!(render("Name:\tValue\nab\tc", 1), "Name:   Value\nab      c");
+    }
+}
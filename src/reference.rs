@@ -0,0 +1,177 @@
+// Poterm - Modern TUI editor for .po translation files
+// Copyright (c) 2025 AnmiTaliDev <anmitali198@gmail.com>
+// Licensed under the Apache License, Version 2.0
+
+//! Finding entries by a source location such as `src/export.c:142`, as
+//! printed by compilers and CI logs
+
+use crate::gettext::PoEntry;
+
+/// How well a reference matches the location, best first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Rank {
+    /// Same file and line
+    Exact,
+    /// Same file, this many lines away
+    Near(usize),
+    /// Same file, no line to compare
+    File,
+}
+
+/// `path`, `path:line`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Location {
+    pub path: String,
+    pub line: Option<usize>,
+}
+
+impl Location {
+    /// `None` for empty text. A trailing column (`file.c:12:5`) is ignored.
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.trim();
+        let (path, line) = split_line(text);
+        let (path, line) = match line {
+            // `path:line:column`
+            Some(column) => match split_line(path) {
+                (file, Some(line)) => (file, Some(line)),
+                _ => (path, Some(column)),
+            },
+            None => (path, None),
+        };
+        let path = normalize(path);
+        (!path.is_empty()).then(|| Self {
+            path: path.to_string(),
+            line,
+        })
+    }
+
+    fn rank(&self, reference: &str) -> Option<Rank> {
+        let (path, line) = split_line(reference);
+        if !same_file(normalize(path), &self.path) {
+            return None;
+        }
+        Some(match (self.line, line) {
+            (Some(wanted), Some(line)) if wanted == line => Rank::Exact,
+            (Some(wanted), Some(line)) => Rank::Near(wanted.abs_diff(line)),
+            _ => Rank::File,
+        })
+    }
+}
+
+/// `src/window.c:215` -> (`src/window.c`, Some(215))
+fn split_line(reference: &str) -> (&str, Option<usize>) {
+    match reference.rsplit_once(':') {
+        Some((path, line)) if !line.is_empty() && line.chars().all(|c| c.is_ascii_digit()) => {
+            (path, line.parse().ok())
+        }
+        _ => (reference, None),
+    }
+}
+
+/// `./src/window.c` -> `src/window.c`
+fn normalize(path: &str) -> &str {
+    let mut path = path;
+    while let Some(rest) = path.strip_prefix("./") {
+        path = rest;
+    }
+    path
+}
+
+/// Paths naming the same file when one is a trailing part of the other, e.g.
+/// `window.c`, `gtk/window.c` and `/home/me/app/src/gtk/window.c` for
+/// `src/gtk/window.c`
+fn same_file(a: &str, b: &str) -> bool {
+    let (short, long) = if a.len() <= b.len() { (a, b) } else { (b, a) };
+    long.strip_suffix(short)
+        .is_some_and(|rest| rest.is_empty() || rest.ends_with('/'))
+}
+
+/// Indices of the entries whose references match `location` best, in file
+/// order. Empty when no entry references the file.
+pub fn best_matches(entries: &[PoEntry], location: &Location) -> Vec<usize> {
+    let ranked: Vec<(usize, Rank)> = entries
+        .iter()
+        .enumerate()
+        .filter_map(|(index, entry)| {
+            entry
+                .references
+                .iter()
+                .flat_map(|line| line.split_whitespace())
+                .filter_map(|reference| location.rank(reference))
+                .min()
+                .map(|rank| (index, rank))
+        })
+        .collect();
+    let Some(best) = ranked.iter().map(|(_, rank)| *rank).min() else {
+        return Vec::new();
+    };
+    ranked
+        .into_iter()
+        .filter(|(_, rank)| *rank == best)
+        .map(|(index, _)| index)
+        .collect()
+}
+
+/// The reference of `entry` that matches `location` best, for the
+/// disambiguation list
+pub fn matching_reference<'a>(entry: &'a PoEntry, location: &Location) -> Option<&'a str> {
+    entry
+        .references
+        .iter()
+        .flat_map(|line| line.split_whitespace())
+        .filter_map(|reference| location.rank(reference).map(|rank| (rank, reference)))
+        .min_by_key(|(rank, _)| *rank)
+        .map(|(_, reference)| reference)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gettext::PoFile;
+
+    fn catalog() -> PoFile {
+        PoFile::parse(concat!(
+            "#: src/export.c:142 src/export.c:300\nmsgid \"Export\"\nmsgstr \"\"\n\n",
+            "#: src/export.c:150\nmsgid \"Exporting\"\nmsgstr \"\"\n\n",
+            "#: src/gtk/window.c:10\nmsgid \"Window\"\nmsgstr \"\"\n\n",
+            "#: src/qt/window.c:10\nmsgid \"Qt window\"\nmsgstr \"\"\n\n",
+            "#: src/export.c:134\nmsgid \"Before\"\nmsgstr \"\"\n",
+        ))
+        .unwrap()
+    }
+
+    fn find(text: &str) -> Vec<usize> {
+        best_matches(&catalog().entries, &Location::parse(text).unwrap())
+    }
+
+    #[test]
+    fn test_parse_location() {
+        let location = |path: &str, line| Location {
+            path: path.to_string(),
+            line,
+        };
+        assert_eq!(Location::parse(" ./src/export.c:142 "), Some(location("src/export.c", Some(142))));
+        assert_eq!(Location::parse("src/export.c:142:7"), Some(location("src/export.c", Some(142))));
+        assert_eq!(Location::parse("export.c"), Some(location("export.c", None)));
+        assert_eq!(Location::parse("C:/src/export.c"), Some(location("C:/src/export.c", None)));
+        assert_eq!(Location::parse("  "), None);
+    }
+
+    #[test]
+    fn test_best_matches() {
+        // Exact line, also when it is not the first reference of the entry
+        assert_eq!(find("src/export.c:142"), vec![0]);
+        assert_eq!(find("./src/export.c:300"), vec![0]);
+        // Nearest line of the same file, ties are all returned
+        assert_eq!(find("src/export.c:149"), vec![1]);
+        assert_eq!(find("src/export.c:138"), vec![0, 4]);
+        // Without a line every entry of the file is as good
+        assert_eq!(find("export.c"), vec![0, 1, 4]);
+        // Partial and longer paths match on whole components only
+        assert_eq!(find("gtk/window.c:10"), vec![2]);
+        assert_eq!(find("/home/me/app/src/qt/window.c:10"), vec![3]);
+        assert_eq!(find("window.c:10"), vec![2, 3]);
+        assert!(find("ow.c:10").is_empty());
+        assert!(find("src/import.c:1").is_empty());
+    }
+}
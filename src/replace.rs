@@ -0,0 +1,210 @@
+// Poterm - Modern TUI editor for .po translation files
+// Copyright (c) 2025 AnmiTaliDev <anmitali198@gmail.com>
+// Licensed under the Apache License, Version 2.0
+
+//! Search and replace in translations
+
+use crate::input::TextInput;
+use anyhow::Result;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use regex::{Captures, NoExpand, Regex, RegexBuilder};
+use std::ops::Range;
+
+/// What the replace form asks the editor to do
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReplaceEvent {
+    None,
+    /// Go from match to match, asking before each replacement
+    StepThrough,
+    /// Replace every match in the listed entries
+    ReplaceAll,
+    /// Replace every match in the selected entry
+    ReplaceInEntry,
+    Close,
+}
+
+/// Overlay state for typing the pattern and the replacement
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ReplaceForm {
+    pub pattern: TextInput,
+    pub replacement: TextInput,
+    /// Typing goes to the replacement rather than the pattern
+    pub replacement_focused: bool,
+    pub case_sensitive: bool,
+    /// The pattern is a regular expression and `$1`, `${name}` in the
+    /// replacement refer to its groups
+    pub regex: bool,
+    /// Why the last request could not run, e.g. an invalid regex
+    pub error: Option<String>,
+}
+
+impl ReplaceForm {
+    pub fn handle_key(&mut self, key: KeyEvent) -> ReplaceEvent {
+        self.error = None;
+        if key.modifiers.contains(KeyModifiers::ALT) {
+            match key.code {
+                KeyCode::Char('c') => self.case_sensitive = !self.case_sensitive,
+                KeyCode::Char('r') => self.regex = !self.regex,
+                KeyCode::Char('a') => return ReplaceEvent::ReplaceAll,
+                KeyCode::Char('e') => return ReplaceEvent::ReplaceInEntry,
+                _ => {}
+            }
+            return ReplaceEvent::None;
+        }
+        match key.code {
+            KeyCode::Esc => return ReplaceEvent::Close,
+            KeyCode::Enter => return ReplaceEvent::StepThrough,
+            KeyCode::Tab | KeyCode::BackTab | KeyCode::Up | KeyCode::Down => {
+                self.replacement_focused = !self.replacement_focused;
+            }
+            _ if self.replacement_focused => {
+                self.replacement.handle_key(key);
+            }
+            _ => {
+                self.pattern.handle_key(key);
+            }
+        }
+        ReplaceEvent::None
+    }
+
+    pub fn replacer(&self) -> Result<Replacer> {
+        Replacer::new(&self.pattern.text, &self.replacement.text, self.case_sensitive, self.regex)
+    }
+}
+
+/// A compiled pattern with its replacement
+#[derive(Debug, Clone)]
+pub struct Replacer {
+    regex: Regex,
+    replacement: String,
+    /// Expand `$1` and the like in the replacement
+    expand: bool,
+}
+
+impl PartialEq for Replacer {
+    fn eq(&self, other: &Self) -> bool {
+        self.regex.as_str() == other.regex.as_str()
+            && self.replacement == other.replacement
+            && self.expand == other.expand
+    }
+}
+
+impl Replacer {
+    pub fn new(pattern: &str, replacement: &str, case_sensitive: bool, regex: bool) -> Result<Self> {
+        if pattern.is_empty() {
+            anyhow::bail!("Type what to replace");
+        }
+        let source = if regex { pattern.to_string() } else { regex::escape(pattern) };
+        let compiled = RegexBuilder::new(&source)
+            .case_insensitive(!case_sensitive)
+            .build()
+            .map_err(|e| {
+                let message = e.to_string();
+                anyhow::anyhow!("Invalid regex: {}", message.lines().last().unwrap_or_default().trim())
+            })?;
+        Ok(Self {
+            regex: compiled,
+            replacement: replacement.to_string(),
+            expand: regex,
+        })
+    }
+
+    /// The first match in `text` starting at byte `start` or later
+    pub fn find_at(&self, text: &str, start: usize) -> Option<Range<usize>> {
+        self.regex.find_at(text, start).map(|m| m.range())
+    }
+
+    pub fn count(&self, text: &str) -> usize {
+        self.regex.find_iter(text).count()
+    }
+
+    /// `text` with the first match at `start` or later replaced, and the
+    /// byte offset just after the inserted replacement
+    pub fn replace_at(&self, text: &str, start: usize) -> Option<(String, usize)> {
+        let captures = self.regex.captures_at(text, start)?;
+        let range = captures.get(0)?.range();
+        let inserted = self.expanded(&captures);
+        let end = range.start + inserted.len();
+        Some((format!("{}{}{}", &text[..range.start], inserted, &text[range.end..]), end))
+    }
+
+    /// The replacement for one match, as it is inserted
+    pub fn expanded(&self, captures: &Captures) -> String {
+        if self.expand {
+            let mut inserted = String::new();
+            captures.expand(&self.replacement, &mut inserted);
+            inserted
+        } else {
+            self.replacement.clone()
+        }
+    }
+
+    /// The replacement for the match of `text` at `range`
+    pub fn replacement_for(&self, text: &str, range: Range<usize>) -> String {
+        match self.regex.captures_at(text, range.start) {
+            Some(captures) => self.expanded(&captures),
+            None => self.replacement.clone(),
+        }
+    }
+
+    pub fn replace_all(&self, text: &str) -> String {
+        if self.expand {
+            self.regex.replace_all(text, self.replacement.as_str()).into_owned()
+        } else {
+            self.regex.replace_all(text, NoExpand(&self.replacement)).into_owned()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plain_replacement() {
+        let replacer = Replacer::new("файл", "документ ($1)", false, false).unwrap();
+        assert_eq!(replacer.replace_all("Файл и файл."), "документ ($1) и документ ($1).");
+        assert_eq!(replacer.count("Файл и файл."), 2);
+
+        let text = "Файл и файл.";
+        let first = replacer.find_at(text, 0).unwrap();
+        let (text, end) = replacer.replace_at(text, first.end).unwrap();
+        assert_eq!(text, "Файл и документ ($1).");
+        assert_eq!(&text[..end], "Файл и документ ($1)");
+
+        let exact = Replacer::new("файл", "документ", true, false).unwrap();
+        assert_eq!(exact.replace_all("Файл и файл"), "Файл и документ");
+        // Regex syntax is taken literally
+        let literal = Replacer::new("a.b", "x", true, false).unwrap();
+        assert_eq!(literal.replace_all("a.b axb"), "x axb");
+    }
+
+    #[test]
+    fn test_regex_replacement() {
+        let replacer = Replacer::new(r"(\d+) (files?)", "$2: $1", true, true).unwrap();
+        assert_eq!(replacer.replace_all("3 files, 1 file"), "files: 3, file: 1");
+        assert_eq!(replacer.replace_at("3 files, 1 file", 1).unwrap(), ("3 files, file: 1".to_string(), 16));
+
+        assert_eq!(Replacer::new("(", "", true, true).unwrap_err().to_string(), "Invalid regex: error: unclosed group");
+        assert!(Replacer::new("", "x", true, false).is_err());
+    }
+
+    #[test]
+    fn test_form_keys() {
+        let key = |modifiers, code| KeyEvent::new(code, modifiers);
+        let mut form = ReplaceForm::default();
+        for c in "foo".chars() {
+            form.handle_key(key(KeyModifiers::NONE, KeyCode::Char(c)));
+        }
+        form.handle_key(key(KeyModifiers::NONE, KeyCode::Tab));
+        form.handle_key(key(KeyModifiers::NONE, KeyCode::Char('x')));
+        assert_eq!((form.pattern.text.as_str(), form.replacement.text.as_str()), ("foo", "x"));
+
+        form.handle_key(key(KeyModifiers::ALT, KeyCode::Char('c')));
+        form.handle_key(key(KeyModifiers::ALT, KeyCode::Char('r')));
+        assert!(form.case_sensitive && form.regex);
+        assert_eq!(form.handle_key(key(KeyModifiers::ALT, KeyCode::Char('a'))), ReplaceEvent::ReplaceAll);
+        assert_eq!(form.handle_key(key(KeyModifiers::NONE, KeyCode::Enter)), ReplaceEvent::StepThrough);
+        assert_eq!(form.handle_key(key(KeyModifiers::NONE, KeyCode::Esc)), ReplaceEvent::Close);
+    }
+}
@@ -0,0 +1,324 @@
+// Poterm - Modern TUI editor for .po translation files
+// Copyright (c) 2025 AnmiTaliDev <anmitali198@gmail.com>
+// Licensed under the Apache License, Version 2.0
+
+//! Where the editor loop gets its keys from: the terminal, or a script of
+//! key presses for automated runs and reproducible bug reports.
+//!
+//! A script has one step per line: a key written as in the config
+//! (`ctrl+f`, `enter`, `f2`), `text:` followed by characters typed one by
+//! one, `paste:` followed by text pasted at once (`\n` for a line break,
+//! `\\` for a backslash), or `wait:` and a number of milliseconds to let
+//! background work finish. Blank lines and lines starting with `#` are
+//! skipped.
+
+use crate::keymap::{format_key, parse_key};
+use anyhow::{Context, Result};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyModifiers};
+use ratatui::buffer::Buffer;
+use std::collections::VecDeque;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+
+/// What the editor loop does next
+#[derive(Debug, Clone, PartialEq)]
+pub enum Input {
+    Key(KeyEvent),
+    /// Text pasted into the terminal, line breaks included
+    Paste(String),
+    /// Nothing to handle; background work runs
+    Idle,
+    /// The input is exhausted and the editor closes
+    End,
+}
+
+pub trait EventSource {
+    /// The next input, waiting at most `timeout` for a key
+    fn next(&mut self, timeout: Duration) -> Result<Input>;
+
+    /// Whether a user sits at a real terminal, e.g. to suspend to the shell
+    fn interactive(&self) -> bool {
+        true
+    }
+}
+
+/// Key presses read from the terminal
+pub struct TerminalEvents;
+
+impl EventSource for TerminalEvents {
+    fn next(&mut self, timeout: Duration) -> Result<Input> {
+        if event::poll(timeout)? {
+            match event::read()? {
+                Event::Key(key) => return Ok(Input::Key(key)),
+                Event::Paste(text) => return Ok(Input::Paste(text)),
+                // The loop redraws after every input, at the new size
+                Event::Resize(..) => return Ok(Input::Idle),
+                _ => {}
+            }
+        }
+        Ok(Input::Idle)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Step {
+    Key(KeyEvent),
+    Paste(String),
+    Wait(Duration),
+}
+
+/// `text` on one line of a script
+fn escape_paste(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('\n', "\\n")
+}
+
+fn unescape_paste(line: &str) -> String {
+    let mut text = String::new();
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match (c, chars.clone().next()) {
+            ('\\', Some('n')) => {
+                text.push('\n');
+                chars.next();
+            }
+            ('\\', Some('\\')) => {
+                text.push('\\');
+                chars.next();
+            }
+            _ => text.push(c),
+        }
+    }
+    text
+}
+
+/// Keys replayed from a script, see the module documentation
+#[derive(Debug, Clone, PartialEq)]
+pub struct Script {
+    steps: VecDeque<Step>,
+}
+
+impl Script {
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+        Self::parse(&content).with_context(|| format!("Invalid script: {}", path.display()))
+    }
+
+    pub fn parse(content: &str) -> Result<Self> {
+        let mut steps = VecDeque::new();
+        for (number, line) in content.lines().enumerate() {
+            // Typed text is taken as is, spaces included
+            if let Some(text) = line.strip_prefix("text:") {
+                steps.extend(text.chars().map(|c| Step::Key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE))));
+                continue;
+            }
+            if let Some(text) = line.strip_prefix("paste:") {
+                steps.push_back(Step::Paste(unescape_paste(text)));
+                continue;
+            }
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Some(ms) = line.strip_prefix("wait:") {
+                let ms = ms.trim().parse().with_context(|| format!("line {}: not a number of milliseconds", number + 1))?;
+                steps.push_back(Step::Wait(Duration::from_millis(ms)));
+                continue;
+            }
+            let (modifiers, code) = parse_key(line).with_context(|| format!("line {}: unknown key `{}`", number + 1, line))?;
+            // Terminals report Shift+Tab as BackTab
+            let code = match code {
+                KeyCode::Tab if modifiers.contains(KeyModifiers::SHIFT) => KeyCode::BackTab,
+                code => code,
+            };
+            steps.push_back(Step::Key(KeyEvent::new(code, modifiers)));
+        }
+        Ok(Self { steps })
+    }
+}
+
+impl EventSource for Script {
+    fn next(&mut self, _timeout: Duration) -> Result<Input> {
+        match self.steps.pop_front() {
+            Some(Step::Key(key)) => Ok(Input::Key(key)),
+            Some(Step::Paste(text)) => Ok(Input::Paste(text)),
+            Some(Step::Wait(duration)) => {
+                thread::sleep(duration);
+                Ok(Input::Idle)
+            }
+            None => Ok(Input::End),
+        }
+    }
+
+    fn interactive(&self) -> bool {
+        false
+    }
+}
+
+/// Passes the keys of `source` on and writes them to a script. Typed
+/// characters are joined into `text:` lines.
+pub struct Recorder<S: EventSource> {
+    source: S,
+    file: BufWriter<File>,
+    text: String,
+}
+
+impl<S: EventSource> Recorder<S> {
+    pub fn create(source: S, path: &Path) -> Result<Self> {
+        let file = File::create(path).with_context(|| format!("Failed to create {}", path.display()))?;
+        Ok(Self {
+            source,
+            file: BufWriter::new(file),
+            text: String::new(),
+        })
+    }
+
+    fn record(&mut self, key: KeyEvent) -> Result<()> {
+        if let KeyCode::Char(c) = key.code {
+            if (key.modifiers - KeyModifiers::SHIFT).is_empty() {
+                self.text.push(c);
+                return Ok(());
+            }
+        }
+        self.flush_text()?;
+        if let Some(spec) = format_key(key.modifiers, key.code) {
+            writeln!(self.file, "{}", spec)?;
+        }
+        Ok(())
+    }
+
+    fn flush_text(&mut self) -> Result<()> {
+        if !self.text.is_empty() {
+            writeln!(self.file, "text:{}", self.text)?;
+            self.text.clear();
+        }
+        self.file.flush()?;
+        Ok(())
+    }
+}
+
+impl<S: EventSource> EventSource for Recorder<S> {
+    fn next(&mut self, timeout: Duration) -> Result<Input> {
+        let input = self.source.next(timeout)?;
+        match input {
+            Input::Key(key) => self.record(key)?,
+            Input::Paste(ref text) => {
+                self.flush_text()?;
+                writeln!(self.file, "paste:{}", escape_paste(text))?;
+            }
+            _ => {}
+        }
+        Ok(input)
+    }
+
+    fn interactive(&self) -> bool {
+        self.source.interactive()
+    }
+}
+
+impl<S: EventSource> Drop for Recorder<S> {
+    fn drop(&mut self) {
+        let _ = self.flush_text();
+    }
+}
+
+/// Screens written to `<dir>/0000.txt`, `0001.txt`, ... for golden-file
+/// comparison: the first frame, then one after each key
+pub struct Snapshots {
+    dir: PathBuf,
+    step: usize,
+    pending: bool,
+}
+
+impl Snapshots {
+    pub fn create(dir: &Path) -> Result<Self> {
+        fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            step: 0,
+            pending: true,
+        })
+    }
+
+    /// A key was handled; the next frame is written
+    pub fn step(&mut self) {
+        self.pending = true;
+    }
+
+    pub fn capture(&mut self, buffer: &Buffer) -> Result<()> {
+        if !self.pending {
+            return Ok(());
+        }
+        let path = self.dir.join(format!("{:04}.txt", self.step));
+        fs::write(&path, buffer_text(buffer)).with_context(|| format!("Failed to write {}", path.display()))?;
+        self.step += 1;
+        self.pending = false;
+        Ok(())
+    }
+}
+
+/// Rows of `buffer` without trailing spaces
+fn buffer_text(buffer: &Buffer) -> String {
+    let area = buffer.area;
+    let mut text = String::new();
+    for y in area.top()..area.bottom() {
+        let row: String = (area.left()..area.right()).map(|x| buffer[(x, y)].symbol()).collect();
+        text.push_str(row.trim_end());
+        text.push('\n');
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(modifiers: KeyModifiers, code: KeyCode) -> Input {
+        Input::Key(KeyEvent::new(code, modifiers))
+    }
+
+    #[test]
+    fn test_parse_script() {
+        let mut script = Script::parse("# search\nctrl+f\ntext:a b\n\n  shift+tab \nwait:0\nF2\npaste:Две\\nстроки \\\\n\n").unwrap();
+        let mut inputs = Vec::new();
+        loop {
+            match script.next(Duration::ZERO).unwrap() {
+                Input::End => break,
+                input => inputs.push(input),
+            }
+        }
+        assert_eq!(
+            inputs,
+            vec![
+                key(KeyModifiers::CONTROL, KeyCode::Char('f')),
+                key(KeyModifiers::NONE, KeyCode::Char('a')),
+                key(KeyModifiers::NONE, KeyCode::Char(' ')),
+                key(KeyModifiers::NONE, KeyCode::Char('b')),
+                key(KeyModifiers::SHIFT, KeyCode::BackTab),
+                Input::Idle,
+                key(KeyModifiers::NONE, KeyCode::F(2)),
+                Input::Paste("Две\nстроки \\n".to_string()),
+            ]
+        );
+
+        let error = Script::parse("enter\nctrl+nope\n").unwrap_err();
+        assert_eq!(error.to_string(), "line 2: unknown key `ctrl+nope`");
+        assert!(Script::parse("wait:soon").is_err());
+    }
+
+    #[test]
+    fn test_recorded_script_replays() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("keys.txt");
+        let pressed = Script::parse("ctrl+f\ntext:Open file\nenter\nalt+z\ntext:#\npaste:a\\nb \\\\n\nctrl+q\n").unwrap();
+        let mut recorder = Recorder::create(pressed.clone(), &path).unwrap();
+        while recorder.next(Duration::ZERO).unwrap() != Input::End {}
+        drop(recorder);
+
+        let recorded = fs::read_to_string(&path).unwrap();
+        assert_eq!(recorded, "ctrl+f\ntext:Open file\nenter\nalt+z\ntext:#\npaste:a\\nb \\\\n\nctrl+q\n");
+        assert_eq!(Script::from_file(&path).unwrap(), pressed);
+    }
+}
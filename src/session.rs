@@ -0,0 +1,196 @@
+// Poterm - Modern TUI editor for .po translation files
+// Copyright (c) 2025 AnmiTaliDev <anmitali198@gmail.com>
+// Licensed under the Apache License, Version 2.0
+
+use crate::gettext::{count_words, EntryKey, PoEntry};
+use crate::stats::format_count;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// Gaps between key presses longer than this count as a break
+const IDLE_LIMIT: Duration = Duration::from_secs(3 * 60);
+
+/// Gaps longer than this don't count toward the time spent in a file
+const FILE_IDLE_LIMIT: Duration = Duration::from_secs(2 * 60);
+
+/// What the user got done since poterm was started
+#[derive(Debug, Clone, Default)]
+pub struct SessionStats {
+    /// Time between key presses, idle gaps excluded
+    active: Duration,
+    last_activity: Option<Instant>,
+    /// Entries that became translated, with their msgid word counts. Keys
+    /// include the catalog so switching files doesn't merge entries.
+    completed: HashMap<(PathBuf, EntryKey), usize>,
+    edited: HashSet<(PathBuf, EntryKey)>,
+    /// Time spent in each file this session, when time tracking is on
+    file_time: HashMap<PathBuf, Duration>,
+}
+
+impl SessionStats {
+    /// Count the time since the last key press. With a `file` it is also
+    /// added to the time spent in that file; returns how much was.
+    pub fn record_activity(&mut self, now: Instant, file: Option<&Path>) -> Duration {
+        let mut file_gap = Duration::ZERO;
+        if let Some(last) = self.last_activity {
+            let gap = now.saturating_duration_since(last);
+            if gap <= IDLE_LIMIT {
+                self.active += gap;
+            }
+            if let Some(file) = file.filter(|_| gap <= FILE_IDLE_LIMIT) {
+                *self.file_time.entry(file.to_path_buf()).or_default() += gap;
+                file_gap = gap;
+            }
+        }
+        self.last_activity = Some(now);
+        file_gap
+    }
+
+    /// Time spent in `file` this session
+    pub fn file_time(&self, file: &Path) -> Duration {
+        self.file_time.get(file).copied().unwrap_or_default()
+    }
+
+    pub fn reset_file_time(&mut self, file: &Path) {
+        self.file_time.remove(file);
+    }
+
+    /// Account for an entry of `file` going from `before` to `after`. An
+    /// entry that loses its translated status again stops counting as
+    /// completed.
+    pub fn record_change(&mut self, file: &Path, before: &PoEntry, after: &PoEntry) {
+        if before == after {
+            return;
+        }
+        let key = (file.to_path_buf(), after.key());
+        if !before.is_translated && after.is_translated {
+            self.completed.insert(key.clone(), count_words(&after.msgid));
+        } else if before.is_translated && !after.is_translated {
+            self.completed.remove(&(file.to_path_buf(), before.key()));
+        }
+        self.edited.insert(key);
+    }
+
+    pub fn completed(&self) -> usize {
+        self.completed.len()
+    }
+
+    pub fn words_translated(&self) -> usize {
+        self.completed.values().sum()
+    }
+
+    pub fn edited(&self) -> usize {
+        self.edited.len()
+    }
+
+    pub fn active_time(&self) -> Duration {
+        self.active
+    }
+
+    /// One-line report printed on exit, None when nothing was changed
+    pub fn summary(&self) -> Option<String> {
+        if self.edited.is_empty() {
+            return None;
+        }
+        Some(format!(
+            "Session: translated {} strings ({} words), edited {} entries, {} active",
+            format_count(self.completed()),
+            format_count(self.words_translated()),
+            format_count(self.edited()),
+            format_duration(self.active)
+        ))
+    }
+}
+
+/// `1h 12m`, `5m`, `<1m`
+pub fn format_duration(duration: Duration) -> String {
+    let minutes = duration.as_secs() / 60;
+    match (minutes / 60, minutes % 60) {
+        (0, 0) => "<1m".to_string(),
+        (0, m) => format!("{}m", m),
+        (h, m) => format!("{}h {}m", h, m),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(msgid: &str, msgstr: &str) -> PoEntry {
+        let mut entry = PoEntry::new();
+        entry.msgid = msgid.to_string();
+        entry.set_msgstr(msgstr.to_string());
+        entry
+    }
+
+    #[test]
+    fn test_completed_and_edited_entries() {
+        let mut session = SessionStats::default();
+        let file = Path::new("ru.po");
+        let empty = entry("Open the file", "");
+        let done = entry("Open the file", "Открыть файл");
+
+        session.record_change(file, &empty, &done);
+        assert_eq!(session.completed(), 1);
+        assert_eq!(session.words_translated(), 3);
+
+        // Rewording a translated entry is an edit, not a completion
+        session.record_change(file, &done, &entry("Open the file", "Открыть"));
+        assert_eq!(session.completed(), 1);
+        assert_eq!(session.edited(), 1);
+
+        let mut fuzzy = done.clone();
+        fuzzy.toggle_fuzzy();
+        session.record_change(file, &done, &fuzzy);
+        assert_eq!(session.completed(), 0);
+
+        session.record_change(file, &fuzzy, &fuzzy);
+        assert_eq!(session.edited(), 1);
+
+        // The same entry in another catalog counts separately
+        session.record_change(Path::new("fr.po"), &empty, &done);
+        assert_eq!(session.completed(), 1);
+        assert_eq!(session.edited(), 2);
+        assert!(session.summary().unwrap().starts_with("Session: translated 1 strings (3 words), edited 2 entries"));
+    }
+
+    #[test]
+    fn test_idle_gaps_are_not_counted() {
+        let mut session = SessionStats::default();
+        let start = Instant::now();
+        session.record_activity(start, None);
+        session.record_activity(start + Duration::from_secs(60), None);
+        session.record_activity(start + Duration::from_secs(60 + 600), None);
+        session.record_activity(start + Duration::from_secs(60 + 600 + 30), None);
+        assert_eq!(session.active_time(), Duration::from_secs(90));
+        assert_eq!(session.summary(), None);
+    }
+
+    #[test]
+    fn test_time_per_file() {
+        let mut session = SessionStats::default();
+        let (ru, fr) = (Path::new("ru.po"), Path::new("fr.po"));
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+        assert_eq!(session.record_activity(start, Some(ru)), Duration::ZERO);
+        assert_eq!(session.record_activity(at(60), Some(ru)), Duration::from_secs(60));
+        // A pause of two and a half minutes is active time, but idle for the file
+        assert_eq!(session.record_activity(at(210), Some(ru)), Duration::ZERO);
+        session.record_activity(at(240), Some(fr));
+        session.record_activity(at(250), None);
+        assert_eq!(session.file_time(ru), Duration::from_secs(60));
+        assert_eq!(session.file_time(fr), Duration::from_secs(30));
+        assert_eq!(session.active_time(), Duration::from_secs(250));
+
+        session.reset_file_time(ru);
+        assert_eq!(session.file_time(ru), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(Duration::from_secs(20)), "<1m");
+        assert_eq!(format_duration(Duration::from_secs(5 * 60)), "5m");
+        assert_eq!(format_duration(Duration::from_secs(72 * 60)), "1h 12m");
+    }
+}
@@ -0,0 +1,86 @@
+// Poterm - Modern TUI editor for .po translation files
+// Copyright (c) 2025 AnmiTaliDev <anmitali198@gmail.com>
+// Licensed under the Apache License, Version 2.0
+
+//! Abbreviations from the `[snippets]` config section
+
+use crate::gettext::PoEntry;
+use std::collections::BTreeMap;
+
+/// The longest abbreviation that `before_cursor` ends with. An abbreviation
+/// starting with a letter or digit only counts at the start of a word, so
+/// `tm` does not fire inside `atm`.
+pub fn find<'a>(snippets: &'a BTreeMap<String, String>, before_cursor: &str) -> Option<(&'a str, &'a str)> {
+    snippets
+        .iter()
+        .filter(|(abbreviation, _)| !abbreviation.is_empty() && before_cursor.ends_with(abbreviation.as_str()))
+        .filter(|(abbreviation, _)| {
+            let starts_word = abbreviation.starts_with(|c: char| c.is_alphanumeric());
+            let previous = before_cursor[..before_cursor.len() - abbreviation.len()].chars().next_back();
+            !starts_word || !previous.is_some_and(char::is_alphanumeric)
+        })
+        .max_by_key(|(abbreviation, _)| abbreviation.len())
+        .map(|(abbreviation, expansion)| (abbreviation.as_str(), expansion.as_str()))
+}
+
+/// Substitute `{msgid}` and `{msgctxt}` with values from `entry`, in one
+/// pass so a msgid containing `{msgctxt}` is kept as it is
+pub fn expand(expansion: &str, entry: Option<&PoEntry>) -> String {
+    let msgid = entry.map(|entry| entry.msgid.as_str()).unwrap_or_default();
+    let msgctxt = entry.and_then(|entry| entry.msgctxt.as_deref()).unwrap_or_default();
+    let placeholders = [("{msgid}", msgid), ("{msgctxt}", msgctxt)];
+
+    let mut expanded = String::with_capacity(expansion.len());
+    let mut rest = expansion;
+    while let Some(start) = rest.find('{') {
+        expanded.push_str(&rest[..start]);
+        rest = &rest[start..];
+        match placeholders.iter().find(|(placeholder, _)| rest.starts_with(placeholder)) {
+            Some((placeholder, value)) => {
+                expanded.push_str(value);
+                rest = &rest[placeholder.len()..];
+            }
+            None => {
+                expanded.push('{');
+                rest = &rest[1..];
+            }
+        }
+    }
+    expanded.push_str(rest);
+    expanded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snippets() -> BTreeMap<String, String> {
+        [(";np", "&nbsp;"), (";n", "\n"), ("tm", "™"), ("sig", "-- {msgid} ({msgctxt})")]
+            .iter()
+            .map(|(abbreviation, expansion)| (abbreviation.to_string(), expansion.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_find_longest_at_word_start() {
+        let snippets = snippets();
+        assert_eq!(find(&snippets, "5;np"), Some((";np", "&nbsp;")));
+        assert_eq!(find(&snippets, "a;n"), Some((";n", "\n")));
+        assert_eq!(find(&snippets, "Poterm tm"), Some(("tm", "™")));
+        assert_eq!(find(&snippets, "atm"), None);
+        assert_eq!(find(&snippets, "Привет"), None);
+        assert_eq!(find(&snippets, ""), None);
+    }
+
+    #[test]
+    fn test_placeholders() {
+        let mut entry = PoEntry::new();
+        entry.msgid = "Open".to_string();
+        entry.msgctxt = Some("menu".to_string());
+        assert_eq!(expand("-- {msgid} ({msgctxt})", Some(&entry)), "-- Open (menu)");
+        assert_eq!(expand("{msgid}|{other}", None), "|{other}");
+
+        entry.msgid = "Use {msgctxt}".to_string();
+        assert_eq!(expand("{msgid} {msgctxt}", Some(&entry)), "Use {msgctxt} menu");
+    }
+}
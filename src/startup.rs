@@ -0,0 +1,401 @@
+// Poterm - Modern TUI editor for .po translation files
+// Copyright (c) 2025 AnmiTaliDev <anmitali198@gmail.com>
+// Licensed under the Apache License, Version 2.0
+
+//! Screen shown when poterm is started without a file
+
+use crate::gettext::PoFile;
+use crate::input::TextInput;
+use crate::state::RecentFile;
+use crate::stats::percent;
+use crate::ui::{relative_time, text_input_line};
+use anyhow::Result;
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph},
+    Frame, Terminal,
+};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Catalog offered in the startup list
+#[derive(Debug, Clone, PartialEq)]
+struct CatalogItem {
+    path: PathBuf,
+    /// Translated percentage, None for templates and unreadable files
+    progress: Option<f64>,
+    note: Option<&'static str>,
+    /// Set for entries from the recent files list
+    last_opened: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl CatalogItem {
+    fn load(path: PathBuf) -> Self {
+        let (progress, note) = if is_template(&path) {
+            (None, Some("template"))
+        } else {
+            match PoFile::from_file(&path) {
+                Ok(po_file) => {
+                    let (total, translated, _) = po_file.get_stats();
+                    (Some(percent(translated, total)), None)
+                }
+                Err(_) => (None, Some("unreadable")),
+            }
+        };
+        Self { path, progress, note, last_opened: None }
+    }
+
+    fn recent(file: &RecentFile) -> Self {
+        Self {
+            path: file.path.clone(),
+            progress: Some(file.progress),
+            note: None,
+            last_opened: Some(file.last_opened),
+        }
+    }
+}
+
+/// Confirmation for a path that does not exist yet
+#[derive(Debug, Clone, PartialEq)]
+struct PendingCreate {
+    path: PathBuf,
+    /// Prefilled from the file name, e.g. `ru.po` -> `ru`
+    language: TextInput,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StartupScreen {
+    input: TextInput,
+    /// Recent files first, then the catalogs of the directory
+    catalogs: Vec<CatalogItem>,
+    recent_count: usize,
+    selected: usize,
+    create: Option<PendingCreate>,
+    message: Option<String>,
+}
+
+enum Step {
+    Continue,
+    Open(Box<PoFile>),
+    Quit,
+}
+
+impl StartupScreen {
+    /// Lists the recent files and the catalogs in `dir`
+    pub fn new(dir: &Path, recent: &[RecentFile]) -> Self {
+        let mut catalogs: Vec<CatalogItem> = recent.iter().map(CatalogItem::recent).collect();
+        catalogs.extend(list_catalogs(dir).into_iter().map(CatalogItem::load));
+        Self {
+            input: TextInput::default(),
+            catalogs,
+            recent_count: recent.len(),
+            selected: 0,
+            create: None,
+            message: None,
+        }
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) -> Step {
+        self.message = None;
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('q') {
+            return Step::Quit;
+        }
+        if let Some(mut create) = self.create.take() {
+            match key.code {
+                KeyCode::Esc => {}
+                KeyCode::Enter => return Step::Open(Box::new(new_catalog(create.path, &create.language.text))),
+                _ => {
+                    create.language.handle_key(key);
+                    self.create = Some(create);
+                }
+            }
+            return Step::Continue;
+        }
+
+        match key.code {
+            KeyCode::Esc => return Step::Quit,
+            KeyCode::Up => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down if self.selected + 1 < self.catalogs.len() => self.selected += 1,
+            KeyCode::Tab => match complete_path(&self.input.text) {
+                Some(completed) => self.input = TextInput::new(&completed),
+                None => self.message = Some("No completion".to_string()),
+            },
+            KeyCode::Enter => {
+                let path = if self.input.text.is_empty() {
+                    match self.catalogs.get(self.selected) {
+                        Some(item) => item.path.clone(),
+                        None => return Step::Continue,
+                    }
+                } else {
+                    PathBuf::from(&self.input.text)
+                };
+                return self.open(path);
+            }
+            _ => {
+                self.input.handle_key(key);
+            }
+        }
+        Step::Continue
+    }
+
+    fn open(&mut self, path: PathBuf) -> Step {
+        if path.is_dir() {
+            self.message = Some(format!("{} is a directory", path.display()));
+        } else if path.exists() {
+            match PoFile::from_file(&path) {
+                Ok(po_file) => return Step::Open(Box::new(po_file)),
+                Err(e) => self.message = Some(format!("{:#}", e)),
+            }
+        } else {
+            let language = language_from_path(&path).unwrap_or_default();
+            self.create = Some(PendingCreate { path, language: TextInput::new(&language) });
+        }
+        Step::Continue
+    }
+}
+
+/// Run the startup screen until a catalog is chosen. None means quit.
+pub fn run<B: Backend>(terminal: &mut Terminal<B>, mut screen: StartupScreen) -> Result<Option<PoFile>> {
+    loop {
+        terminal.draw(|f| draw(f, &screen))?;
+        if let Event::Key(key) = event::read()? {
+            match screen.handle_key(key) {
+                Step::Continue => {}
+                Step::Open(po_file) => return Ok(Some(*po_file)),
+                Step::Quit => return Ok(None),
+            }
+        }
+    }
+}
+
+fn is_template(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "pot")
+}
+
+/// `*.po` and `*.pot` files in `dir`, sorted by name. Unreadable
+/// directories simply have no catalogs.
+fn list_catalogs(dir: &Path) -> Vec<PathBuf> {
+    let Ok(read_dir) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut paths: Vec<PathBuf> = read_dir
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "po" || ext == "pot"))
+        .map(|path| path.strip_prefix(".").map(Path::to_path_buf).unwrap_or(path))
+        .collect();
+    paths.sort();
+    paths
+}
+
+/// Language code guessed from a catalog file name: `ru.po`, `pt_BR.po`,
+/// `po/de/messages.po`
+fn language_from_path(path: &Path) -> Option<String> {
+    let looks_like_language = |name: &str| {
+        let mut parts = name.splitn(2, ['_', '-', '@']);
+        let primary = parts.next().unwrap_or("");
+        (2..=3).contains(&primary.len()) && primary.chars().all(|c| c.is_ascii_lowercase())
+    };
+
+    let stem = path.file_stem()?.to_str()?;
+    if looks_like_language(stem) {
+        return Some(stem.to_string());
+    }
+    let parent = path.parent()?.file_name()?.to_str()?;
+    looks_like_language(parent).then(|| parent.to_string())
+}
+
+fn new_catalog(path: PathBuf, language: &str) -> PoFile {
+    let mut po_file = PoFile::new(path);
+    if !language.trim().is_empty() {
+        po_file.header.insert("Language".to_string(), language.trim().to_string());
+    }
+    po_file
+}
+
+/// Complete the last component of `input` against the file system: a
+/// single match is completed fully (directories get a trailing `/`),
+/// several matches up to their common prefix.
+fn complete_path(input: &str) -> Option<String> {
+    let (dir, prefix) = match input.rfind('/') {
+        Some(pos) => (&input[..=pos], &input[pos + 1..]),
+        None => ("", input),
+    };
+    let read_dir = fs::read_dir(if dir.is_empty() { "." } else { dir }).ok()?;
+
+    let mut matches: Vec<(String, bool)> = read_dir
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let name = entry.file_name().into_string().ok()?;
+            let hidden = name.starts_with('.') && !prefix.starts_with('.');
+            (name.starts_with(prefix) && !hidden).then(|| (name, entry.path().is_dir()))
+        })
+        .collect();
+    matches.sort();
+
+    let completed = match matches.as_slice() {
+        [] => return None,
+        [(name, is_dir)] => format!("{}{}", name, if *is_dir { "/" } else { "" }),
+        [(first, _), rest @ ..] => {
+            let mut common = first.clone();
+            for (name, _) in rest {
+                while !name.starts_with(&common) {
+                    common.pop();
+                }
+            }
+            common
+        }
+    };
+    let result = format!("{}{}", dir, completed);
+    (result != input).then_some(result)
+}
+
+fn draw(f: &mut Frame, screen: &StartupScreen) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(3), Constraint::Length(3)])
+        .split(f.area());
+
+    let input_block = Block::default()
+        .title("Poterm - open or create a file (Tab completes)")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan));
+    let input = Paragraph::new(text_input_line(&screen.input, Style::default().fg(Color::White))).block(input_block);
+    f.render_widget(input, chunks[0]);
+
+    let (recent, local) = screen.catalogs.split_at(screen.recent_count);
+    let list_area = if recent.is_empty() {
+        chunks[1]
+    } else {
+        let areas = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(recent.len() as u16 + 2), Constraint::Min(3)])
+            .split(chunks[1]);
+        let selected = (screen.selected < recent.len()).then_some(screen.selected);
+        draw_catalogs(f, areas[0], "Recent files", recent, selected);
+        areas[1]
+    };
+
+    let block = Block::default().title("Catalogs in this directory").borders(Borders::ALL);
+    if local.is_empty() {
+        let empty = Paragraph::new("No .po or .pot files here")
+            .block(block)
+            .style(Style::default().fg(Color::DarkGray));
+        f.render_widget(empty, list_area);
+    } else {
+        let selected = screen.selected.checked_sub(recent.len());
+        draw_catalogs(f, list_area, "Catalogs in this directory", local, selected);
+    }
+
+    let footer = match (&screen.create, &screen.message) {
+        (Some(create), _) => {
+            let mut spans = vec![Span::styled(
+                format!("Create {}? Language: ", create.path.display()),
+                Style::default().fg(Color::Yellow),
+            )];
+            spans.extend(text_input_line(&create.language, Style::default().fg(Color::White)).spans);
+            spans.push(Span::styled("  (Enter: Create, Esc: Cancel)", Style::default().fg(Color::DarkGray)));
+            Line::from(spans)
+        }
+        (None, Some(message)) => Line::styled(message.as_str(), Style::default().fg(Color::Red)),
+        (None, None) => Line::styled(
+            "Enter: Open  ↑/↓: Select  Tab: Complete path  Esc/Ctrl+Q: Quit",
+            Style::default().fg(Color::DarkGray),
+        ),
+    };
+    f.render_widget(Paragraph::new(footer).block(Block::default().borders(Borders::ALL)), chunks[2]);
+}
+
+fn draw_catalogs(f: &mut Frame, area: Rect, title: &str, catalogs: &[CatalogItem], selected: Option<usize>) {
+    let now = chrono::Utc::now();
+    let items: Vec<ListItem> = catalogs
+        .iter()
+        .map(|item| {
+            let mut status = match (item.progress, item.note) {
+                (Some(progress), _) => format!("{:5.1}%", progress),
+                (None, Some(note)) => note.to_string(),
+                (None, None) => String::new(),
+            };
+            if let Some(last_opened) = item.last_opened {
+                status.push_str(&format!(", {}", relative_time(last_opened, now)));
+            }
+            ListItem::new(Line::from(vec![
+                Span::raw(format!("{}  ", item.path.display())),
+                Span::styled(status, Style::default().fg(Color::DarkGray)),
+            ]))
+        })
+        .collect();
+    let list = List::new(items)
+        .block(Block::default().title(title).borders(Borders::ALL))
+        .highlight_style(Style::default().bg(Color::DarkGray).add_modifier(Modifier::BOLD))
+        .highlight_symbol("► ");
+    let mut state = ListState::default().with_selected(selected);
+    f.render_stateful_widget(list, area, &mut state);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn test_language_from_path() {
+        assert_eq!(language_from_path(Path::new("ru.po")).as_deref(), Some("ru"));
+        assert_eq!(language_from_path(Path::new("po/pt_BR.po")).as_deref(), Some("pt_BR"));
+        assert_eq!(language_from_path(Path::new("locale/de/messages.po")).as_deref(), Some("de"));
+        assert_eq!(language_from_path(Path::new("messages.po")), None);
+    }
+
+    #[test]
+    fn test_complete_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let base = format!("{}/", dir.path().display());
+        fs::create_dir(dir.path().join("locale")).unwrap();
+        fs::write(dir.path().join("ru.po"), "").unwrap();
+        fs::write(dir.path().join("ro.po"), "").unwrap();
+        fs::write(dir.path().join(".hidden.po"), "").unwrap();
+
+        assert_eq!(complete_path(&format!("{}lo", base)), Some(format!("{}locale/", base)));
+        assert_eq!(complete_path(&format!("{}ru", base)), Some(format!("{}ru.po", base)));
+        // Ambiguous: nothing beyond the common prefix to add
+        assert_eq!(complete_path(&format!("{}r", base)), None);
+        assert_eq!(complete_path(&format!("{}.h", base)), Some(format!("{}.hidden.po", base)));
+        assert_eq!(complete_path(&format!("{}x", base)), None);
+    }
+
+    #[test]
+    fn test_open_from_list_and_create() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("de.po"), "msgid \"a\"\nmsgstr \"b\"\n\nmsgid \"c\"\nmsgstr \"\"\n").unwrap();
+        fs::write(dir.path().join("app.pot"), "").unwrap();
+        fs::write(dir.path().join("notes.txt"), "").unwrap();
+
+        let mut screen = StartupScreen::new(dir.path(), &[]);
+        assert_eq!(screen.catalogs.len(), 2);
+        assert_eq!(screen.catalogs[0].note, Some("template"));
+        assert_eq!(screen.catalogs[1].progress, Some(50.0));
+
+        screen.handle_key(key(KeyCode::Down));
+        assert!(matches!(screen.handle_key(key(KeyCode::Enter)), Step::Open(po) if po.entries.len() == 2));
+
+        let target = dir.path().join("fr.po");
+        screen.input = TextInput::new(&target.display().to_string());
+        assert!(matches!(screen.handle_key(key(KeyCode::Enter)), Step::Continue));
+        assert_eq!(screen.create.as_ref().unwrap().language.text, "fr");
+        match screen.handle_key(key(KeyCode::Enter)) {
+            Step::Open(po_file) => {
+                assert_eq!(po_file.language(), Some("fr"));
+                assert_eq!(po_file.path.as_deref(), Some(target.as_path()));
+            }
+            _ => panic!("expected the new catalog to open"),
+        }
+
+        assert!(matches!(screen.handle_key(key(KeyCode::Esc)), Step::Quit));
+    }
+}
@@ -0,0 +1,241 @@
+// Poterm - Modern TUI editor for .po translation files
+// Copyright (c) 2025 AnmiTaliDev <anmitali198@gmail.com>
+// Licensed under the Apache License, Version 2.0
+
+//! State kept between runs: recently opened files and where the cursor was
+
+use crate::gettext::EntryKey;
+use crate::filter::FilterMode;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Recent files beyond this are forgotten
+const MAX_RECENT: usize = 20;
+
+/// Catalog in the recent files list with the view to restore
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecentFile {
+    /// Canonical path
+    pub path: PathBuf,
+    pub last_opened: DateTime<Utc>,
+    /// Translated percentage when the file was last closed
+    pub progress: f64,
+    pub filter: FilterMode,
+    #[serde(default)]
+    pub search: String,
+    /// Entry under the cursor
+    pub entry: Option<EntryKey>,
+}
+
+/// Time spent editing a catalog, summed over sessions
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct FileTime {
+    /// Canonical path
+    path: PathBuf,
+    seconds: u64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+struct StateFile {
+    recent: Vec<RecentFile>,
+    time: Vec<FileTime>,
+}
+
+/// Recent files and time spent per file, persisted to `$XDG_STATE_HOME/poterm/state.toml` (or
+/// `~/.local/state/poterm/state.toml`) unless persistence is disabled
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StateStore {
+    /// None keeps the list in memory only
+    path: Option<PathBuf>,
+    recent: Vec<RecentFile>,
+    /// Kept for files that no longer exist too; only a reset removes them
+    time: BTreeMap<PathBuf, Duration>,
+}
+
+impl StateStore {
+    pub fn default_path() -> Option<PathBuf> {
+        let base = match env::var_os("XDG_STATE_HOME") {
+            Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+            _ => PathBuf::from(env::var_os("HOME")?).join(".local").join("state"),
+        };
+        Some(base.join("poterm").join("state.toml"))
+    }
+
+    /// Load the store from the default location. A missing or unreadable
+    /// state file starts an empty list rather than failing.
+    pub fn load(persist: bool) -> Self {
+        match Self::default_path() {
+            Some(path) if persist => Self::from_file(path),
+            _ => Self::default(),
+        }
+    }
+
+    pub fn from_file(path: PathBuf) -> Self {
+        let state: StateFile = fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| toml::from_str(&content).ok())
+            .unwrap_or_default();
+        let mut store = Self {
+            path: Some(path),
+            recent: state.recent,
+            time: state
+                .time
+                .into_iter()
+                .map(|file| (file.path, Duration::from_secs(file.seconds)))
+                .collect(),
+        };
+        store.prune();
+        store
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let Some(ref path) = self.path else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+        }
+        let state = StateFile {
+            recent: self.recent.clone(),
+            time: self
+                .time
+                .iter()
+                .map(|(path, time)| FileTime {
+                    path: path.clone(),
+                    seconds: time.as_secs(),
+                })
+                .collect(),
+        };
+        fs::write(path, toml::to_string(&state)?)
+            .with_context(|| format!("Failed to write state file: {}", path.display()))
+    }
+
+    /// Most recently opened first
+    pub fn recent(&self) -> &[RecentFile] {
+        &self.recent
+    }
+
+    pub fn get(&self, path: &Path) -> Option<&RecentFile> {
+        let path = path.canonicalize().ok()?;
+        self.recent.iter().find(|file| file.path == path)
+    }
+
+    /// Move `file` to the front of the list
+    pub fn record(&mut self, file: RecentFile) {
+        self.recent.retain(|recent| recent.path != file.path);
+        self.recent.insert(0, file);
+        self.recent.truncate(MAX_RECENT);
+    }
+
+    /// Time spent in the file at `path` over all sessions
+    pub fn time_in(&self, path: &Path) -> Option<Duration> {
+        self.time.get(&path.canonicalize().ok()?).copied()
+    }
+
+    /// Add to the time spent in the file at the canonical `path`
+    pub fn add_time(&mut self, path: PathBuf, time: Duration) {
+        *self.time.entry(path).or_default() += time;
+    }
+
+    pub fn reset_time(&mut self, path: &Path) {
+        if let Ok(path) = path.canonicalize() {
+            self.time.remove(&path);
+        }
+    }
+
+    /// Forget files that no longer exist
+    fn prune(&mut self) {
+        self.recent.retain(|file| file.path.exists());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recent(path: PathBuf) -> RecentFile {
+        RecentFile {
+            path,
+            last_opened: Utc::now(),
+            progress: 50.0,
+            filter: FilterMode::Fuzzy,
+            search: "file".to_string(),
+            entry: Some(EntryKey {
+                msgctxt: Some("menu".to_string()),
+                msgid: "Open".to_string(),
+            }),
+        }
+    }
+
+    #[test]
+    fn test_round_trip_and_prune() {
+        let dir = tempfile::tempdir().unwrap();
+        let state_path = dir.path().join("state").join("state.toml");
+        let ru = dir.path().join("ru.po");
+        let fr = dir.path().join("fr.po");
+        fs::write(&ru, "").unwrap();
+        fs::write(&fr, "").unwrap();
+        let (ru, fr) = (ru.canonicalize().unwrap(), fr.canonicalize().unwrap());
+
+        let mut store = StateStore::from_file(state_path.clone());
+        store.record(recent(ru.clone()));
+        store.record(recent(fr.clone()));
+        store.record(recent(ru.clone()));
+        store.save().unwrap();
+
+        let loaded = StateStore::from_file(state_path.clone());
+        assert_eq!(loaded.recent(), store.recent());
+        assert_eq!(loaded.recent()[0].path, ru);
+        assert_eq!(loaded.recent().len(), 2);
+        assert_eq!(loaded.get(&ru).unwrap().filter, FilterMode::Fuzzy);
+
+        fs::remove_file(&fr).unwrap();
+        assert_eq!(StateStore::from_file(state_path).recent().len(), 1);
+    }
+
+    #[test]
+    fn test_time_per_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let state_path = dir.path().join("state.toml");
+        let ru = dir.path().join("ru.po");
+        fs::write(&ru, "").unwrap();
+
+        let mut store = StateStore::from_file(state_path.clone());
+        assert_eq!(store.time_in(&ru), None);
+        store.add_time(ru.canonicalize().unwrap(), Duration::from_millis(90_500));
+        store.add_time(ru.canonicalize().unwrap(), Duration::from_secs(30));
+        store.save().unwrap();
+
+        let mut loaded = StateStore::from_file(state_path);
+        assert_eq!(loaded.time_in(&ru), Some(Duration::from_secs(120)));
+        loaded.reset_time(&ru);
+        assert_eq!(loaded.time_in(&ru), None);
+    }
+
+    #[test]
+    fn test_list_is_capped() {
+        let mut store = StateStore::default();
+        for i in 0..MAX_RECENT + 5 {
+            store.record(recent(PathBuf::from(format!("/tmp/{}.po", i))));
+        }
+        assert_eq!(store.recent().len(), MAX_RECENT);
+        assert_eq!(store.recent()[0].path, PathBuf::from(format!("/tmp/{}.po", MAX_RECENT + 4)));
+        // Without a path nothing is written
+        store.save().unwrap();
+    }
+
+    #[test]
+    fn test_broken_state_file_is_ignored() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state.toml");
+        fs::write(&path, "recent = 5").unwrap();
+        assert!(StateStore::from_file(path).recent().is_empty());
+    }
+}
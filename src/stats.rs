@@ -0,0 +1,186 @@
+// Poterm - Modern TUI editor for .po translation files
+// Copyright (c) 2025 AnmiTaliDev <anmitali198@gmail.com>
+// Licensed under the Apache License, Version 2.0
+
+use crate::gettext::{PoFile, WordStats};
+use crate::history::OutputFormat;
+use crate::session::format_duration;
+use crate::state::StateStore;
+use anyhow::Result;
+use serde::Serialize;
+use std::path::PathBuf;
+
+/// `4300` -> `4,300`
+pub fn format_count(n: usize) -> String {
+    let digits = n.to_string();
+    let mut result = String::new();
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            result.push(',');
+        }
+        result.push(c);
+    }
+    result
+}
+
+/// e.g. `≈ 4,300 source words remaining (1,200 in fuzzy)`
+pub fn remaining_summary(words: &WordStats) -> String {
+    let mut summary = format!("≈ {} source words remaining", format_count(words.remaining()));
+    if words.fuzzy > 0 {
+        summary.push_str(&format!(" ({} in fuzzy)", format_count(words.fuzzy)));
+    }
+    summary
+}
+
+pub fn percent(part: usize, total: usize) -> f64 {
+    if total > 0 {
+        (part as f64 / total as f64) * 100.0
+    } else {
+        0.0
+    }
+}
+
+/// `needs_work_flag` marks translations that need review
+fn summary(po_file: &PoFile, needs_work_flag: &str) -> String {
+    let (total, translated, fuzzy) = po_file.get_stats();
+    let words = po_file.word_stats();
+    let needs_review = needs_review(po_file, needs_work_flag);
+    let mut summary = format!(
+        "{} entries: {} translated ({:.1}%), {} fuzzy, {} untranslated\n\
+         {} source words: {} translated ({:.1}%), {}",
+        format_count(total),
+        format_count(translated),
+        percent(translated, total),
+        format_count(fuzzy),
+        format_count(total - translated - fuzzy),
+        format_count(words.total),
+        format_count(words.translated),
+        percent(words.translated, words.total),
+        remaining_summary(&words)
+    );
+    if needs_review > 0 {
+        summary.push_str(&format!("\nNeeds review: {}", format_count(needs_review)));
+    }
+    summary
+}
+
+/// Statistics of one file in `poterm stats --format json`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+struct FileStats {
+    path: PathBuf,
+    total: usize,
+    translated: usize,
+    fuzzy: usize,
+    untranslated: usize,
+    needs_review: usize,
+    words: usize,
+    words_translated: usize,
+    words_fuzzy: usize,
+    words_untranslated: usize,
+    /// Recorded time spent in the file, only when there is one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    time_seconds: Option<u64>,
+}
+
+impl FileStats {
+    fn new(path: PathBuf, po_file: &PoFile, needs_work_flag: &str, state: &StateStore) -> Self {
+        let (total, translated, fuzzy) = po_file.get_stats();
+        let words = po_file.word_stats();
+        let time_seconds = state.time_in(&path).map(|time| time.as_secs());
+        Self {
+            path,
+            total,
+            translated,
+            fuzzy,
+            untranslated: total - translated - fuzzy,
+            needs_review: needs_review(po_file, needs_work_flag),
+            words: words.total,
+            words_translated: words.translated,
+            words_fuzzy: words.fuzzy,
+            words_untranslated: words.untranslated,
+            time_seconds,
+        }
+    }
+}
+
+/// Translated entries flagged with `needs_work_flag`
+fn needs_review(po_file: &PoFile, needs_work_flag: &str) -> usize {
+    po_file
+        .entries
+        .iter()
+        .filter(|entry| entry.is_translated && entry.has_flag(needs_work_flag))
+        .count()
+}
+
+/// Headless `poterm stats`: print entry and word statistics per file, and
+/// the time spent in it when `state` has recorded any
+pub fn run(files: &[PathBuf], needs_work_flag: &str, state: &StateStore, format: OutputFormat) -> Result<()> {
+    let mut all = Vec::new();
+    for path in files {
+        let po_file = PoFile::from_file(path)?;
+        if format == OutputFormat::Json {
+            all.push(FileStats::new(path.clone(), &po_file, needs_work_flag, state));
+            continue;
+        }
+        println!("{}:", path.display());
+        for line in summary(&po_file, needs_work_flag).lines() {
+            println!("  {}", line);
+        }
+        if let Some(time) = state.time_in(path) {
+            println!("  Time in file: {}", format_duration(time));
+        }
+    }
+    if format == OutputFormat::Json {
+        println!("{}", serde_json::to_string_pretty(&all)?);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_count() {
+        assert_eq!(format_count(0), "0");
+        assert_eq!(format_count(999), "999");
+        assert_eq!(format_count(4300), "4,300");
+        assert_eq!(format_count(1234567), "1,234,567");
+    }
+
+    #[test]
+    fn test_summary() {
+        let content = "msgid \"Open the file\"\nmsgstr \"Открыть файл\"\n\n#, fuzzy\nmsgid \"Save all\"\nmsgstr \"Сохранить\"\n\nmsgid \"Quit\"\nmsgstr \"\"\n";
+        let mut po_file = PoFile::parse(content).unwrap();
+        assert_eq!(
+            summary(&po_file, "poterm-needs-work"),
+            "3 entries: 1 translated (33.3%), 1 fuzzy, 1 untranslated\n\
+             6 source words: 3 translated (50.0%), ≈ 3 source words remaining (2 in fuzzy)"
+        );
+
+        // Translations needing review still count as translated
+        po_file.entries[0].toggle_flag("poterm-needs-work");
+        let summary = summary(&po_file, "poterm-needs-work");
+        assert!(summary.starts_with("3 entries: 1 translated (33.3%)"));
+        assert!(summary.ends_with("\nNeeds review: 1"));
+    }
+
+    #[test]
+    fn test_json_time_only_when_recorded() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ru.po");
+        std::fs::write(&path, "msgid \"Open\"\nmsgstr \"Открыть\"\n\nmsgid \"Quit\"\nmsgstr \"\"\n").unwrap();
+        let po_file = PoFile::from_file(&path).unwrap();
+        let mut state = StateStore::default();
+
+        let stats = FileStats::new(path.clone(), &po_file, "poterm-needs-work", &state);
+        let json = serde_json::to_value(&stats).unwrap();
+        assert_eq!(json["translated"], 1);
+        assert_eq!(json["words_untranslated"], 1);
+        assert!(json.get("time_seconds").is_none());
+
+        state.add_time(path.canonicalize().unwrap(), std::time::Duration::from_secs(90));
+        let stats = FileStats::new(path, &po_file, "poterm-needs-work", &state);
+        assert_eq!(serde_json::to_value(&stats).unwrap()["time_seconds"], 90);
+    }
+}
@@ -0,0 +1,291 @@
+// Poterm - Modern TUI editor for .po translation files
+// Copyright (c) 2025 AnmiTaliDev <anmitali198@gmail.com>
+// Licensed under the Apache License, Version 2.0
+
+use crossterm::cursor::Show;
+use crossterm::event::{DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste};
+use crossterm::terminal::{EnterAlternateScreen, LeaveAlternateScreen, SetTitle};
+use crossterm::{Command, ExecutableCommand, QueueableCommand};
+use ratatui::backend::{Backend, CrosstermBackend, TestBackend};
+use ratatui::Terminal;
+use std::fmt;
+use std::io::{self, IsTerminal, Stdout, Write};
+use std::sync::atomic::AtomicUsize;
+use std::sync::Arc;
+
+/// Shown after a crash
+const REPORT_NOTE: &str = "poterm crashed; please report this at https://github.com/AnmiTaliDev/poterm/issues \
+                           with the message above";
+
+/// Save the current window title on the terminal's title stack (xterm)
+struct PushTitle;
+
+impl Command for PushTitle {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        f.write_str("\x1b[22;0t")
+    }
+}
+
+/// Restore the window title saved by `PushTitle`
+struct PopTitle;
+
+impl Command for PopTitle {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        f.write_str("\x1b[23;0t")
+    }
+}
+
+/// A backend that also passes on escape sequences other than drawing, such
+/// as a clipboard copy. The headless backend of `--replay` has no terminal
+/// to send them to.
+pub trait SendCommand {
+    fn send(&mut self, command: impl Command) -> io::Result<()>;
+}
+
+impl<W: Write> SendCommand for CrosstermBackend<W> {
+    fn send(&mut self, command: impl Command) -> io::Result<()> {
+        self.execute(command).map(|_| ())
+    }
+}
+
+impl SendCommand for TestBackend {
+    fn send(&mut self, _command: impl Command) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Keeps the terminal title in sync with the open file. Escape sequences
+/// are only written when the title changes and never when stdout is not a
+/// terminal.
+pub struct TerminalTitle {
+    enabled: bool,
+    current: Option<String>,
+}
+
+impl TerminalTitle {
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled: enabled && io::stdout().is_terminal(),
+            current: None,
+        }
+    }
+
+    pub fn set(&mut self, title: &str) -> io::Result<()> {
+        if !self.enabled || self.current.as_deref() == Some(title) {
+            return Ok(());
+        }
+
+        let mut stdout = io::stdout();
+        if self.current.is_none() {
+            stdout.queue(PushTitle)?;
+        }
+        stdout.queue(SetTitle(title))?;
+        stdout.flush()?;
+        self.current = Some(title.to_string());
+        Ok(())
+    }
+
+    /// Restore the title the terminal had before the first `set`
+    pub fn restore(&mut self) -> io::Result<()> {
+        if self.current.take().is_some() {
+            let mut stdout = io::stdout();
+            stdout.queue(PopTitle)?;
+            stdout.flush()?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for TerminalTitle {
+    fn drop(&mut self) {
+        let _ = self.restore();
+    }
+}
+
+/// Undo what the editor did to the terminal. Errors are ignored: this runs
+/// when something already went wrong.
+pub fn restore() {
+    let _ = crossterm::terminal::disable_raw_mode();
+    let _ = write_restore(&mut io::stdout());
+}
+
+fn write_restore(out: &mut impl Write) -> io::Result<()> {
+    out.queue(DisableMouseCapture)?
+        .queue(DisableBracketedPaste)?
+        .queue(LeaveAlternateScreen)?
+        .queue(Show)?;
+    out.flush()
+}
+
+/// Ask the terminal to mark pasted text, so that it arrives as one paste
+/// instead of key presses. Returns false where that isn't supported, e.g.
+/// in the legacy Windows console; pasting then types the text as before.
+pub fn enable_bracketed_paste() -> bool {
+    io::stdout().execute(EnableBracketedPaste).is_ok()
+}
+
+/// Sets the terminal up for the editor and puts it back when dropped, so
+/// that an error returned early or a panic unwinding leaves a usable shell
+pub struct TerminalGuard<W: Write> {
+    out: W,
+    active: bool,
+}
+
+impl TerminalGuard<Stdout> {
+    /// Raw mode, the alternate screen and bracketed paste
+    pub fn enter() -> io::Result<Self> {
+        crossterm::terminal::enable_raw_mode()?;
+        let mut guard = Self::new(io::stdout());
+        guard.out.execute(EnterAlternateScreen)?;
+        enable_bracketed_paste();
+        Ok(guard)
+    }
+}
+
+impl<W: Write> TerminalGuard<W> {
+    fn new(out: W) -> Self {
+        Self { out, active: true }
+    }
+
+    /// Restore the terminal, reporting what went wrong
+    pub fn leave(mut self) -> io::Result<()> {
+        self.active = false;
+        crossterm::terminal::disable_raw_mode()?;
+        write_restore(&mut self.out)
+    }
+}
+
+impl<W: Write> Drop for TerminalGuard<W> {
+    fn drop(&mut self) {
+        if self.active {
+            let _ = crossterm::terminal::disable_raw_mode();
+            let _ = write_restore(&mut self.out);
+        }
+    }
+}
+
+/// Restore the terminal before a panic message is printed, so that the
+/// message is visible and the shell usable afterwards
+pub fn install_panic_hook() {
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        restore();
+        previous(info);
+        eprintln!("{}", REPORT_NOTE);
+    }));
+}
+
+/// Set to the number of the first SIGTERM or SIGHUP received, 0 before
+#[cfg(unix)]
+pub fn termination_signal() -> io::Result<Arc<AtomicUsize>> {
+    use signal_hook::consts::{SIGHUP, SIGTERM};
+
+    let signal = Arc::new(AtomicUsize::new(0));
+    for number in [SIGTERM, SIGHUP] {
+        signal_hook::flag::register_usize(number, Arc::clone(&signal), number as usize)?;
+    }
+    Ok(signal)
+}
+
+#[cfg(not(unix))]
+pub fn termination_signal() -> io::Result<Arc<AtomicUsize>> {
+    Ok(Arc::new(AtomicUsize::new(0)))
+}
+
+pub fn signal_name(number: usize) -> String {
+    #[cfg(unix)]
+    {
+        use signal_hook::consts::{SIGHUP, SIGTERM};
+        match number as i32 {
+            SIGTERM => return "SIGTERM".to_string(),
+            SIGHUP => return "SIGHUP".to_string(),
+            _ => {}
+        }
+    }
+    format!("signal {}", number)
+}
+
+/// Stop the process like a shell's Ctrl+Z would, restoring the terminal
+/// first and setting it up again once the process is continued with `fg`.
+#[cfg(unix)]
+pub fn suspend<B: Backend>(terminal: &mut Terminal<B>) -> io::Result<()> {
+    use crossterm::terminal::{disable_raw_mode, enable_raw_mode};
+
+    disable_raw_mode()?;
+    let _ = io::stdout().execute(DisableBracketedPaste);
+    io::stdout().execute(LeaveAlternateScreen)?;
+
+    // Raw mode disables ISIG, so Ctrl+Z arrives as a key and the stop signal
+    // is raised here. This returns once SIGCONT resumes the process.
+    signal_hook::low_level::raise(signal_hook::consts::SIGTSTP)?;
+
+    enable_raw_mode()?;
+    io::stdout().execute(EnterAlternateScreen)?;
+    enable_bracketed_paste();
+    terminal.clear()
+}
+
+#[cfg(not(unix))]
+pub fn suspend<B: Backend>(_terminal: &mut Terminal<B>) -> io::Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::panic::{self, AssertUnwindSafe};
+    use std::sync::Mutex;
+
+    #[test]
+    fn test_restore_sequence() {
+        let mut out = Vec::new();
+        write_restore(&mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        // Mouse reporting and bracketed paste off, main screen back, cursor
+        // shown
+        assert!(out.contains("\x1b[?1000l"), "{:?}", out);
+        assert!(out.contains("\x1b[?2004l"), "{:?}", out);
+        assert!(out.contains("\x1b[?1049l"), "{:?}", out);
+        assert!(out.ends_with("\x1b[?25h"), "{:?}", out);
+    }
+
+    /// Output shared with the test after the guard is gone
+    #[derive(Clone, Default)]
+    struct SharedOutput(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedOutput {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_guard_restores_on_panic() {
+        let out = SharedOutput::default();
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            let _guard = TerminalGuard::new(out.clone());
+            panic!("simulated crash");
+        }));
+        assert!(result.is_err());
+        let written = String::from_utf8(out.0.lock().unwrap().clone()).unwrap();
+        assert!(written.contains("\x1b[?1049l"), "{:?}", written);
+        assert!(written.ends_with("\x1b[?25h"), "{:?}", written);
+
+        // Restored once when left normally
+        let out = SharedOutput::default();
+        TerminalGuard::new(out.clone()).leave().unwrap();
+        let written = String::from_utf8(out.0.lock().unwrap().clone()).unwrap();
+        assert_eq!(written.matches("\x1b[?1049l").count(), 1);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_signal_name() {
+        assert_eq!(signal_name(signal_hook::consts::SIGTERM as usize), "SIGTERM");
+        assert_eq!(signal_name(signal_hook::consts::SIGHUP as usize), "SIGHUP");
+    }
+}
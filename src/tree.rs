@@ -0,0 +1,202 @@
+// Poterm - Modern TUI editor for .po translation files
+// Copyright (c) 2025 AnmiTaliDev <anmitali198@gmail.com>
+// Licensed under the Apache License, Version 2.0
+
+//! Entry list grouped by the source file of each entry's first reference
+
+use crate::gettext::PoEntry;
+use std::collections::{BTreeMap, HashSet};
+
+/// Group of entries without references
+pub const NO_REFERENCE: &str = "(no reference)";
+
+/// One line of the tree
+#[derive(Debug, Clone, PartialEq)]
+pub enum Row {
+    Group {
+        file: String,
+        /// Translated and total entries of the file, listed or not
+        translated: usize,
+        total: usize,
+        expanded: bool,
+        /// Listed entries, as positions in the filtered list
+        positions: Vec<usize>,
+    },
+    /// Position in the filtered list
+    Entry(usize),
+}
+
+/// Tree view state: which groups are collapsed and whether a group line
+/// rather than an entry is selected
+#[derive(Debug, Clone, Default)]
+pub struct EntryTree {
+    collapsed: HashSet<String>,
+    /// Entry index the selected group line stands for. The selection moves
+    /// to the entry itself once anything else selects another entry.
+    group_selected: Option<usize>,
+}
+
+/// `src/window.c:215` -> `src/window.c`
+pub fn source_file(entry: &PoEntry) -> &str {
+    match entry.first_reference() {
+        Some(reference) => match reference.rsplit_once(':') {
+            Some((file, line)) if line.chars().all(|c| c.is_ascii_digit()) => file,
+            _ => reference,
+        },
+        None => NO_REFERENCE,
+    }
+}
+
+impl EntryTree {
+    /// Groups of the listed entries sorted by file, with the entries of
+    /// expanded groups below them. Groups without listed entries are left
+    /// out.
+    pub fn rows(&self, entries: &[PoEntry], filtered: &[usize]) -> Vec<Row> {
+        let mut totals: BTreeMap<&str, (usize, usize)> = BTreeMap::new();
+        for entry in entries {
+            let (translated, total) = totals.entry(source_file(entry)).or_default();
+            *total += 1;
+            if entry.is_translated && !entry.is_fuzzy {
+                *translated += 1;
+            }
+        }
+        let mut listed: BTreeMap<&str, Vec<usize>> = BTreeMap::new();
+        for (position, &index) in filtered.iter().enumerate() {
+            listed.entry(source_file(&entries[index])).or_default().push(position);
+        }
+
+        // Files in name order, unreferenced entries last
+        let mut groups: Vec<(&str, Vec<usize>)> = listed.into_iter().collect();
+        groups.sort_by_key(|(file, _)| *file == NO_REFERENCE);
+
+        let mut rows = Vec::new();
+        for (file, positions) in groups {
+            let (translated, total) = totals[file];
+            let expanded = !self.collapsed.contains(file);
+            rows.push(Row::Group {
+                file: file.to_string(),
+                translated,
+                total,
+                expanded,
+                positions: positions.clone(),
+            });
+            if expanded {
+                rows.extend(positions.into_iter().map(Row::Entry));
+            }
+        }
+        rows
+    }
+
+    /// Row of the selection: the entry at `position`, or its group line.
+    /// A collapsed group hiding the entry is opened.
+    pub fn selected_row(&mut self, entries: &[PoEntry], filtered: &[usize], position: usize) -> Option<usize> {
+        let index = *filtered.get(position)?;
+        if self.group_selected != Some(index) {
+            self.group_selected = None;
+            self.collapsed.remove(source_file(&entries[index]));
+        }
+        let rows = self.rows(entries, filtered);
+        rows.iter().position(|row| match row {
+            Row::Group { positions, .. } => self.group_selected.is_some() && positions.contains(&position),
+            Row::Entry(p) => *p == position,
+        })
+    }
+
+    /// Select `row` of `rows`, returning the filtered position of the entry
+    /// to show
+    pub fn select(&mut self, rows: &[Row], row: usize, filtered: &[usize]) -> Option<usize> {
+        match rows.get(row)? {
+            Row::Group { positions, .. } => {
+                let position = *positions.first()?;
+                self.group_selected = Some(filtered[position]);
+                Some(position)
+            }
+            Row::Entry(position) => {
+                self.group_selected = None;
+                Some(*position)
+            }
+        }
+    }
+
+    pub fn set_expanded(&mut self, file: &str, expanded: bool) {
+        if expanded {
+            self.collapsed.remove(file);
+        } else {
+            self.collapsed.insert(file.to_string());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(msgid: &str, reference: Option<&str>, translated: bool) -> PoEntry {
+        let mut entry = PoEntry::new();
+        entry.msgid = msgid.to_string();
+        entry.references.extend(reference.map(str::to_string));
+        if translated {
+            entry.set_msgstr("x".to_string());
+        }
+        entry
+    }
+
+    fn groups(rows: &[Row]) -> Vec<(String, usize, usize, Vec<usize>)> {
+        rows.iter()
+            .filter_map(|row| match row {
+                Row::Group { file, translated, total, positions, .. } => {
+                    Some((file.clone(), *translated, *total, positions.clone()))
+                }
+                Row::Entry(_) => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_rows_group_by_first_reference() {
+        let entries = vec![
+            entry("Open", Some("src/window.c:10 src/menu.c:4"), true),
+            entry("Free", None, false),
+            entry("Close", Some("src/app.c:3"), false),
+            entry("Quit", Some("src/window.c:99"), false),
+        ];
+        let tree = EntryTree::default();
+        let rows = tree.rows(&entries, &[0, 1, 2, 3]);
+        assert_eq!(
+            groups(&rows),
+            vec![
+                ("src/app.c".to_string(), 0, 1, vec![2]),
+                ("src/window.c".to_string(), 1, 2, vec![0, 3]),
+                (NO_REFERENCE.to_string(), 0, 1, vec![1]),
+            ]
+        );
+        assert_eq!(rows.len(), 7);
+
+        // Filtering prunes empty groups but keeps the file's totals; the
+        // positions are within the filtered list
+        let rows = tree.rows(&entries, &[1, 3]);
+        assert_eq!(
+            groups(&rows),
+            vec![("src/window.c".to_string(), 1, 2, vec![1]), (NO_REFERENCE.to_string(), 0, 1, vec![0])]
+        );
+    }
+
+    #[test]
+    fn test_collapse_and_select() {
+        let entries = vec![entry("A", Some("a.c:1"), false), entry("B", Some("b.c:1"), false)];
+        let filtered = [0, 1];
+        let mut tree = EntryTree::default();
+        tree.set_expanded("a.c", false);
+        let rows = tree.rows(&entries, &filtered);
+        assert_eq!(rows.len(), 3);
+
+        // Selecting a group line shows its first entry
+        assert_eq!(tree.select(&rows, 1, &filtered), Some(1));
+        assert_eq!(tree.selected_row(&entries, &filtered, 1), Some(1));
+
+        // Another entry selected elsewhere opens its group
+        assert_eq!(tree.selected_row(&entries, &filtered, 0), Some(1));
+        assert_eq!(tree.selected_row(&entries, &filtered, 1), Some(3));
+        assert_eq!(tree.rows(&entries, &filtered).len(), 4);
+    }
+}
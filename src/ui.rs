@@ -2,27 +2,89 @@
 // Copyright (c) 2025 AnmiTaliDev <anmitali198@gmail.com>
 // Licensed under the Apache License, Version 2.0
 
-use crate::gettext::{PoEntry, PoFile};
-use anyhow::Result;
-use crossterm::event::{KeyCode, KeyEvent};
+use crate::bidi;
+use crate::browser::{self, BrowserEvent, FileBrowser};
+use crate::charmap;
+use crate::columns::{fit_reference, reference_label, truncate_to_width, Column, ColumnLayout, IndexMode, PreviewCell};
+use crate::comments::{CommentEvent, CommentManager, CommentSection};
+use crate::complete::{self, Completion, WordIndex};
+use crate::filter::{FilterMode, FilterSet, SearchOptions, SearchStatus, TextQuery};
+use crate::config::{Config, CursorMovement, FieldSwitch, KeymapPreset, ListConfig, ProgressMetric, StartAt};
+use crate::gettext::{self, EntryKey, PoEntry, PoFile, CANONICAL_HEADER_KEYS};
+use crate::exchange::{self, csv::CsvOptions};
+use crate::glossary::Glossary;
+use crate::memory::{Suggestion, TranslationMemory};
+use crate::history;
+use crate::clipboard::{self, Clipboard};
+use crate::input::{self, TextInput};
+use crate::journal::{self, Change, Journal, Recovery, Swap};
+use crate::language::{self, LANGUAGES};
+use crate::mo;
+use crate::picker::{Picker, PickerEvent, PickerItem};
+use crate::plural::PluralForms;
+use crate::preview;
+use crate::reference::{self, Location};
+use crate::replace::{ReplaceEvent, ReplaceForm, Replacer};
+use crate::snippet;
+use crate::pipe::{PipeJob, PipeOutput};
+use crate::session::{format_duration, SessionStats};
+use crate::state::{RecentFile, StateStore};
+use crate::stats::{format_count, percent, remaining_summary};
+use crate::tree::{EntryTree, Row as TreeRow};
+use crate::undo::{Edit, UndoHistory};
+use crate::validate::{self, consistency, run_checks, Checker, EntryIssue, Severity, ValidationIssue};
+use crate::wrap::{self, Row};
+use crate::keymap::{Action, Keymap};
+use anyhow::{Context, Result};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use ratatui::{
     layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
-    text::{Line, Span},
+    text::{Line, Span, Text},
     widgets::{
         Block, Borders, Clear, List, ListItem, ListState, Paragraph, Wrap,
     },
     Frame,
 };
-use std::cmp::min;
-use unicode_width::UnicodeWidthStr;
+use std::cell::{Cell, RefCell};
+use std::cmp::{min, Reverse};
+use std::collections::HashMap;
+use std::ops::Range;
+use indexmap::IndexMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
 // UI Constants
 const ENTRY_LIST_WIDTH_PERCENT: u16 = 40;
 const ENTRY_DETAILS_WIDTH_PERCENT: u16 = 60;
 const PAGE_SIZE: usize = 10;
+/// Smallest terminal the full interface is drawn in
+const MIN_WIDTH: u16 = 40;
+const MIN_HEIGHT: u16 = 10;
+const SUPERSCRIPT_DIGITS: [char; 10] = ['⁰', '¹', '²', '³', '⁴', '⁵', '⁶', '⁷', '⁸', '⁹'];
+const TEMPLATE_MESSAGE: &str = "Templates (.pot) hold no translations; Ctrl+N creates a .po for a language";
+const READ_ONLY_MESSAGE: &str = "Opened read-only because of parse errors (--force)";
+/// Buttons of the quit dialog
+const QUIT_CHOICES: [&str; 3] = ["Save", "Discard", "Cancel"];
+/// Buttons of the dialog shown when saving over a file changed on disk
+const CONFLICT_CHOICES: [&str; 4] = ["Overwrite", "Reload", "Save as", "Cancel"];
+/// How often the open file is looked at for changes made by other programs
+const DISK_CHECK_INTERVAL: Duration = Duration::from_secs(2);
+/// Fields gettext tools need; deleting them asks for the field name
+const REQUIRED_HEADER_KEYS: [&str; 3] = ["Project-Id-Version", "MIME-Version", "Content-Type"];
+/// Characters kept in the character picker's recent list
+const RECENT_CHARS: usize = 8;
+/// Characters the character picker lists for a name typed into it
+const CHAR_SEARCH_LIMIT: usize = 200;
+/// Translation memory suggestions shown, one per Alt+digit
+const SUGGESTIONS: usize = 3;
+/// How long a status message stays in the footer; errors stay until the
+/// next key
+const STATUS_TIMEOUT: Duration = Duration::from_secs(5);
+const SPINNER_FRAMES: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum EditField {
     Msgid,
     Msgstr,
@@ -30,11 +92,161 @@ pub enum EditField {
     Metadata,
 }
 
+/// Cursor positions of the fields of one entry, kept while that entry
+/// stays selected. Each plural form of the msgstr counts as a field of its
+/// own.
+#[derive(Debug, Default)]
+struct FieldMemory {
+    entry: Option<usize>,
+    /// Plural form shown in the msgstr pane
+    form: usize,
+    cursors: HashMap<(EditField, usize), usize>,
+}
+
+/// Modal panel drawn on top of the main view that receives all key input
+#[derive(Debug, Clone, PartialEq)]
+enum Overlay {
+    Comments(CommentManager),
+    Prompt(Prompt),
+    Qa(QaPanel),
+    /// Catalog and session statistics
+    Stats,
+    FileBrowser(FileBrowser),
+    /// Confirm unlocking msgid editing
+    UnlockMsgid,
+    /// Save or discard the current file before opening `path`
+    UnsavedChanges { path: PathBuf },
+    /// Confirm dropping the unsaved changes to read the file again
+    ConfirmReload,
+    /// Save, discard or keep the unsaved changes when quitting, `selected`
+    /// indexing `QUIT_CHOICES`
+    QuitUnsaved { selected: usize },
+    /// The file changed on disk since it was read: overwrite it, reload it
+    /// or save elsewhere, `selected` indexing `CONFLICT_CHOICES`
+    ChangedOnDisk { selected: usize },
+    /// Quick switch between recently opened files
+    Recent { selected: usize },
+    /// Dismissible message, e.g. the stderr of a failed command
+    Message { title: String, text: String },
+    /// Choose a header value from a list, e.g. a Plural-Forms preset
+    Picker { kind: PickerKind, picker: Picker },
+    /// Offer header values matching a newly picked language
+    LanguageDefaults { changes: Vec<(String, String)> },
+    /// Confirm removing a header field. Required fields need the field
+    /// name typed into `confirm`.
+    DeleteHeaderField { key: String, confirm: Option<TextInput> },
+    /// Configured snippets
+    Snippets,
+    /// The current translation with sample values, read-only
+    Preview,
+    /// Apply or discard a changed edit buffer before running `then`, e.g.
+    /// quitting or opening another file
+    UncommittedEdit { then: Action },
+    /// Replay or discard a swap file left next to the opened file
+    Recover(Swap),
+    /// Restore or discard a recovery file autosave left next to the opened
+    /// file
+    RestoreAutosave(Recovery),
+    /// Pattern and replacement for search and replace
+    Replace(ReplaceForm),
+    /// Replace or skip one match, stepping through them
+    ReplaceMatch(ReplaceStep),
+}
+
+/// Search and replace going from match to match
+#[derive(Debug, Clone, PartialEq)]
+struct ReplaceStep {
+    replacer: Replacer,
+    /// Listed entry, translation form and byte range of the current match
+    position: usize,
+    form: usize,
+    range: Range<usize>,
+    /// Listed entry the search started at; reaching it again ends it
+    origin: usize,
+    wrapped: bool,
+    replaced: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum PickerKind {
+    PluralForms,
+    Language,
+    /// Character to insert into the edit buffer
+    Character,
+    /// Entry among several matching a source location equally well
+    Reference,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum PromptKind {
+    PipeCommand,
+    /// Language of a new .po created from the open template
+    TemplateLanguage,
+    FlagFilter,
+    SourceFilter,
+    /// Entry number in the list's numbering
+    GoToEntry,
+    /// Source location such as `src/export.c:142`
+    GoToReference,
+    /// Path to save the open file to
+    SaveAs,
+    /// Template to merge into the open catalog
+    MergeTemplate,
+    /// File to export the entries to
+    Export,
+    /// Exported file to read translations back from
+    Import,
+}
+
+/// Single-line input overlay
+#[derive(Debug, Clone, PartialEq)]
+struct Prompt {
+    kind: PromptKind,
+    title: String,
+    input: TextInput,
+}
+
+/// List of validation issues across the catalog
+#[derive(Debug, Clone, PartialEq)]
+struct QaPanel {
+    issues: Vec<EntryIssue>,
+    selected: usize,
+    /// List acknowledged issues too
+    show_suppressed: bool,
+}
+
+/// Inline type-to-filter started with '/'
+#[derive(Debug, Clone, PartialEq)]
+struct QuickFilter {
+    input: TextInput,
+    /// Keys go to the filter until Enter freezes it
+    typing: bool,
+    /// Entry selected before the filter started, restored on Esc
+    previous_entry: Option<usize>,
+}
+
+/// View before the search overlay opened, restored on Esc
+struct SearchSnapshot {
+    filters: FilterSet,
+    selected: Option<usize>,
+}
+
+/// Where the output of a piped shell command goes
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum PipeTarget {
+    Msgstr(usize),
+    EditBuffer,
+}
+
+/// Result of performing an action
 #[derive(Debug, Clone, Copy, PartialEq)]
-pub enum FilterMode {
-    All,
-    Untranslated,
-    Fuzzy,
+pub enum Outcome {
+    Done,
+    /// The action had no effect (e.g. navigation at the end of the list)
+    Blocked,
+    Quit,
+    /// The terminal should be restored and the process stopped
+    Suspend,
 }
 
 pub struct App {
@@ -45,31 +257,122 @@ pub struct App {
     edit_field: EditField,
     edit_text: String,
     edit_cursor: usize,
+    /// Width of the field being edited as last drawn, for Up and Down to
+    /// move between its rows
+    edit_width: Cell<u16>,
+    /// Column Up and Down keep to across shorter rows
+    goal_column: Option<u16>,
+    field_memory: FieldMemory,
+    /// Buffers Tab put aside, by entry index and field, until that field
+    /// is edited again
+    stashed_edits: HashMap<(usize, EditField, usize), String>,
     search_mode: bool,
-    search_query: String,
     search_cursor: usize,
-    filter_mode: FilterMode,
+    search_snapshot: Option<SearchSnapshot>,
+    /// The previous query is selected, so typing replaces it
+    search_selected: bool,
+    search_options: SearchOptions,
+    /// `filters.text` compiled with `search_options`
+    text_query: Option<TextQuery>,
+    /// Why the search text is matched as plain text, e.g. an invalid regex
+    search_error: Option<String>,
+    /// The last query searched for, which F3 and Shift+F3 jump between the
+    /// matches of after the search closed
+    find_query: String,
+    /// `find_query` compiled, so drawing does not compile it every frame
+    find_text_query: RefCell<Option<TextQuery>>,
+    filters: FilterSet,
     filtered_indices: Vec<usize>,
     pub help_visible: bool,
     metadata_mode: bool,
     metadata_key: String,
     metadata_keys: Vec<String>,
     metadata_selected: usize,
+    keymap: Keymap,
+    macro_recording: Option<Vec<Action>>,
+    macro_register: Vec<Action>,
+    status_message: Option<String>,
+    /// The status message when it reports an error
+    status_error: Option<String>,
+    /// The status message on screen and when it first was, to expire it
+    status_shown: Option<(String, Instant)>,
+    /// When the file was last saved or opened, for autosave
+    last_save: Instant,
+    /// Another program wrote the file since it was read or saved, as of
+    /// `disk_checked`
+    disk_changed: bool,
+    disk_checked: Instant,
+    overlay: Option<Overlay>,
+    config: Config,
+    pipe_job: Option<(PipeJob, PipeTarget)>,
+    last_pipe_command: String,
+    /// Quick-jump marks set with `m<digit>`
+    marks: [Option<EntryKey>; 10],
+    quick_filter: Option<QuickFilter>,
+    /// Entry list columns, starting from the config
+    list_columns: ListConfig,
+    /// Entries as last loaded or saved by key, to mark changed entries
+    /// wherever a merge moved them
+    saved_entries: HashMap<EntryKey, PoEntry>,
+    undo: UndoHistory,
+    /// The replace form as last used, to open it again
+    last_replace: ReplaceForm,
+    /// Swap file recording unsaved entry edits
+    journal: Option<Journal>,
+    /// Recovery file autosave writes to; none while one from another
+    /// session is left for later
+    recovery_file: Option<PathBuf>,
+    /// File revision last written to the recovery file
+    recovery_revision: Option<u64>,
+    clipboard: Clipboard,
+    /// File the catalog was read from when saves go elsewhere (--output)
+    source: Option<PathBuf>,
+    /// QA issue counts per entry for the file revision they were computed at
+    issue_counts: Option<(u64, Vec<usize>)>,
+    /// Entry indices by msgid for the file revision they were computed at
+    msgid_index: Option<(u64, HashMap<String, Vec<usize>>)>,
+    /// Terms of the configured glossary
+    glossary: Option<Glossary>,
+    /// Translations of other catalogs (`--tm`)
+    memory: Option<TranslationMemory>,
+    /// Suggestions from the memory for the msgid they were looked up for
+    suggestions: Option<(String, Vec<Suggestion>)>,
+    session: SessionStats,
+    /// Recent files and their last view, kept between runs
+    state: StateStore,
+    /// The open file is a .pot template: translations can't be edited
+    template_mode: bool,
+    /// Treat templates as regular catalogs (`--force-po`)
+    force_po: bool,
+    /// Entry list grouped by source file, None for the flat list
+    tree: Option<EntryTree>,
+    /// The file did not parse cleanly and was opened anyway; saving would
+    /// drop what the parser skipped
+    read_only: bool,
+    /// msgid can be edited, after confirmation or `allow_msgid_edit`
+    msgid_unlocked: bool,
+    /// PO-Revision-Date was edited by hand since the last save
+    revision_date_set: bool,
+    /// Esc was pressed once on a changed edit buffer
+    discard_armed: bool,
+    /// The edit buffer holds a translation memory suggestion, so applying
+    /// it marks the entry fuzzy
+    suggestion_pending: bool,
+    /// Words of the catalog's translations, for completion
+    words: WordIndex,
+    completion: Option<Completion>,
+    /// Characters inserted through the character picker, latest first
+    recent_chars: Vec<char>,
 }
 
 impl App {
     // Helper function to convert character index to byte index
-    fn char_to_byte_index(text: &str, char_idx: usize) -> usize {
+    pub(crate) fn char_to_byte_index(text: &str, char_idx: usize) -> usize {
         text.char_indices().nth(char_idx).map(|(i, _)| i).unwrap_or(text.len())
     }
     
-    // Helper function to convert byte index to character index
-    fn byte_to_char_index(text: &str, byte_idx: usize) -> usize {
-        text.char_indices().take_while(|(i, _)| *i < byte_idx).count()
-    }
-    
     // Optimized helper to remove character at specific index
-    fn remove_char_at(text: &mut String, char_idx: usize) {
+    pub(crate) fn remove_char_at(text: &mut String, char_idx: usize) {
         if let Some((start_byte, ch)) = text.char_indices().nth(char_idx) {
             let char_len = ch.len_utf8();
             text.drain(start_byte..start_byte + char_len);
@@ -77,12 +380,18 @@ impl App {
     }
     
     // Helper to insert character at specific position
-    fn insert_char_at(text: &mut String, char_idx: usize, ch: char) {
+    pub(crate) fn insert_char_at(text: &mut String, char_idx: usize, ch: char) {
         let byte_pos = Self::char_to_byte_index(text, char_idx);
         text.insert(byte_pos, ch);
     }
 
-    pub fn new(po_file: PoFile) -> Self {
+    pub fn new(mut po_file: PoFile, config: Config) -> Self {
+        po_file.no_wrap = !config.wrap;
+        po_file.backup = config.backup;
+        let saved_entries = by_key(&po_file.entries);
+        let template_mode = po_file.is_template();
+        let metadata_keys = metadata_keys(&po_file.header);
+        let words = WordIndex::build(&po_file.entries);
         let mut app = Self {
             po_file,
             current_entry: 0,
@@ -91,53 +400,120 @@ impl App {
             edit_field: EditField::Msgstr,
             edit_text: String::new(),
             edit_cursor: 0,
+            edit_width: Cell::new(u16::MAX),
+            goal_column: None,
+            field_memory: FieldMemory::default(),
+            stashed_edits: HashMap::new(),
             search_mode: false,
-            search_query: String::new(),
             search_cursor: 0,
-            filter_mode: FilterMode::All,
+            search_snapshot: None,
+            search_selected: false,
+            search_options: SearchOptions::default(),
+            text_query: None,
+            search_error: None,
+            find_query: String::new(),
+            find_text_query: RefCell::new(None),
+            filters: FilterSet::default(),
             filtered_indices: Vec::new(),
             help_visible: false,
             metadata_mode: false,
             metadata_key: String::new(),
-            metadata_keys: vec![
-                "Project-Id-Version".to_string(),
-                "Language".to_string(),
-                "Language-Team".to_string(),
-                "Last-Translator".to_string(),
-                "Report-Msgid-Bugs-To".to_string(),
-                "POT-Creation-Date".to_string(),
-                "PO-Revision-Date".to_string(),
-                "MIME-Version".to_string(),
-                "Content-Type".to_string(),
-                "Content-Transfer-Encoding".to_string(),
-                "Plural-Forms".to_string(),
-            ],
+            metadata_keys,
             metadata_selected: 0,
+            keymap: Keymap::from_config(&config),
+            list_columns: config.list.clone(),
+            saved_entries,
+            undo: UndoHistory::default(),
+            last_replace: ReplaceForm::default(),
+            issue_counts: None,
+            msgid_index: None,
+            glossary: None,
+            memory: None,
+            suggestions: None,
+            session: SessionStats::default(),
+            state: StateStore::default(),
+            template_mode,
+            force_po: false,
+            read_only: false,
+            tree: None,
+            msgid_unlocked: config.allow_msgid_edit,
+            revision_date_set: false,
+            discard_armed: false,
+            suggestion_pending: false,
+            words,
+            completion: None,
+            recent_chars: Vec::new(),
+            macro_recording: None,
+            macro_register: Vec::new(),
+            status_message: None,
+            status_error: None,
+            status_shown: None,
+            last_save: Instant::now(),
+            disk_changed: false,
+            disk_checked: Instant::now(),
+            overlay: None,
+            config,
+            pipe_job: None,
+            journal: None,
+            recovery_file: None,
+            recovery_revision: None,
+            clipboard: Clipboard::default(),
+            source: None,
+            last_pipe_command: String::new(),
+            marks: Default::default(),
+            quick_filter: None,
         };
         
+        app.load_glossary();
         app.update_filtered_indices();
         app.update_list_state();
+        app.report_parse_issues();
         app
     }
 
+    /// Read the glossary configured for the open file. A glossary that
+    /// cannot be read is reported and no terms are checked.
+    fn load_glossary(&mut self) {
+        self.glossary = None;
+        self.issue_counts = None;
+        let Some(path) = self.config.glossary_path(self.po_file.path.as_deref()) else {
+            return;
+        };
+        match Glossary::from_file(&path) {
+            Ok(glossary) => self.glossary = Some(glossary),
+            Err(e) => self.report_error(format!("{:#}", e)),
+        }
+    }
+
+    pub fn reload_glossary(&mut self) {
+        if self.config.glossary.is_none() {
+            self.status_message = Some("No glossary configured".to_string());
+            return;
+        }
+        self.load_glossary();
+        if let Some(ref glossary) = self.glossary {
+            self.status_message = Some(format!("Glossary reloaded: {} terms", glossary.len()));
+        }
+    }
+
     fn update_filtered_indices(&mut self) {
         self.filtered_indices.clear();
+        if !self.text_query.as_ref().is_some_and(|query| query.is_for(&self.filters.text, self.search_options)) {
+            let (query, error) = TextQuery::new(&self.filters.text, self.search_options);
+            self.text_query = Some(query);
+            self.search_error = error.filter(|_| !self.filters.text.is_empty());
+        }
+        let issue_counts = if self.filters.status == FilterMode::Issues {
+            self.issue_counts().to_vec()
+        } else {
+            Vec::new()
+        };
         
         for (i, entry) in self.po_file.entries.iter().enumerate() {
-            let matches_filter = match self.filter_mode {
-                FilterMode::All => true,
-                FilterMode::Untranslated => !entry.is_translated,
-                FilterMode::Fuzzy => entry.is_fuzzy,
-            };
-            
-            let matches_search = if self.search_query.is_empty() {
-                true
-            } else {
-                entry.msgid.to_lowercase().contains(&self.search_query.to_lowercase()) ||
-                entry.msgstr.to_lowercase().contains(&self.search_query.to_lowercase())
-            };
-            
-            if matches_filter && matches_search {
+            let changed = self.is_entry_changed(i);
+            let marked = self.mark_for(entry).is_some();
+            let has_issues = issue_counts.get(i).is_some_and(|&count| count > 0);
+            if self.filters.matches(entry, changed, marked, self.needs_work(entry), has_issues, self.text_query.as_ref()) {
                 self.filtered_indices.push(i);
             }
         }
@@ -148,8 +524,30 @@ impl App {
         }
     }
 
+    /// Recompute the list after a filter changed. The selected entry stays
+    /// selected while it is listed, otherwise the next listed entry after
+    /// it (or the last one) is selected.
+    fn refilter(&mut self) {
+        let selected = self.filtered_indices.get(self.current_entry).copied();
+        self.update_filtered_indices();
+        if let Some(selected) = selected {
+            self.current_entry = self
+                .filtered_indices
+                .iter()
+                .position(|&i| i >= selected)
+                .unwrap_or(self.filtered_indices.len().saturating_sub(1));
+        }
+        self.update_list_state();
+    }
+
     fn update_list_state(&mut self) {
-        if !self.filtered_indices.is_empty() {
+        if self.field_memory.entry != self.filtered_indices.get(self.current_entry).copied() {
+            self.field_memory = FieldMemory::default();
+        }
+        if let Some(ref mut tree) = self.tree {
+            let row = tree.selected_row(&self.po_file.entries, &self.filtered_indices, self.current_entry);
+            self.list_state.select(row);
+        } else if !self.filtered_indices.is_empty() {
             self.list_state.select(Some(self.current_entry));
         } else {
             self.list_state.select(None);
@@ -201,25 +599,158 @@ impl App {
     pub fn start_editing(&mut self) {
         if !self.filtered_indices.is_empty() && !self.search_mode {
             let actual_index = self.filtered_indices[self.current_entry];
+            if self.edit_field == EditField::Comments {
+                self.open_comment_manager(actual_index);
+                return;
+            }
+            if self.template_mode && self.edit_field == EditField::Msgstr {
+                self.status_message = Some(TEMPLATE_MESSAGE.to_string());
+                return;
+            }
+            if self.edit_field == EditField::Msgid && !self.msgid_unlocked {
+                self.overlay = Some(Overlay::UnlockMsgid);
+                return;
+            }
+            let form = self.plural_form();
             if let Some(entry) = self.po_file.entries.get(actual_index) {
                 self.editing = true;
+                self.discard_armed = false;
+                self.suggestion_pending = false;
+                self.completion = None;
                 self.edit_text = match self.edit_field {
                     EditField::Msgid => entry.msgid.clone(),
-                    EditField::Msgstr => entry.msgstr.clone(),
-                    EditField::Comments => entry.comments.join("\n"),
-                    EditField::Metadata => String::new(), // Handled in metadata mode
+                    EditField::Msgstr => entry.form(form).to_string(),
+                    // Comments are managed in their own overlay, metadata in metadata mode
+                    EditField::Comments | EditField::Metadata => String::new(),
                 };
-                self.edit_cursor = self.edit_text.len();
+                let key = self.memory_key();
+                let cursor = self.field_memory(actual_index).cursors.get(&key).copied();
+                if let Some(text) = self.stashed_edits.remove(&(actual_index, key.0, key.1)) {
+                    self.edit_text = text;
+                }
+                let len = self.edit_text.chars().count();
+                self.edit_cursor = cursor.map_or(len, |cursor| cursor.min(len));
             }
         }
     }
 
     pub fn stop_editing(&mut self) {
+        self.completion = None;
+        self.goal_column = None;
         if self.editing {
+            self.remember_cursor();
             self.apply_edit();
             self.editing = false;
         } else if self.search_mode {
             self.search_mode = false;
+            self.search_snapshot = None;
+            self.search_selected = false;
+            self.remember_find_query();
+        }
+    }
+
+    /// Leave the edit without applying the buffer
+    pub fn cancel_editing(&mut self) {
+        if self.editing {
+            self.remember_cursor();
+        }
+        self.completion = None;
+        self.goal_column = None;
+        self.editing = false;
+        self.discard_armed = false;
+    }
+
+    /// What Ctrl+C copies when not editing: the msgid as the details pane
+    /// shows it, or the selected header field's value in metadata mode
+    fn displayed_field_text(&self) -> Option<String> {
+        if self.metadata_mode {
+            let key = self.metadata_keys.get(self.metadata_selected)?;
+            return Some(self.po_file.get_header().get(key).cloned().unwrap_or_default());
+        }
+        let entry = self.get_current_entry()?;
+        Some(match entry.msgid_plural {
+            Some(ref plural) if self.plural_form() > 0 => plural.clone(),
+            _ => entry.msgid.clone(),
+        })
+    }
+
+    fn copy_to_clipboard(&mut self, text: &str) {
+        let via = self.clipboard.copy(text);
+        let copied = plural_count(text.chars().count(), "character", "characters");
+        self.status_message = Some(format!("Copied {} to the clipboard (via {})", copied, via));
+    }
+
+    /// Text copied to the clipboard that the terminal should receive as an
+    /// OSC 52 sequence
+    pub fn take_terminal_copy(&mut self) -> Option<String> {
+        self.clipboard.take_terminal_copy()
+    }
+
+    /// Insert pasted text at the cursor of the edit buffer, the search or
+    /// the quick filter as it is, rather than as key presses. Single-line
+    /// fields get spaces for its line breaks.
+    pub fn paste_text(&mut self, text: &str) -> Outcome {
+        let text = clipboard::normalize_newlines(text);
+        let single_line = || text.replace('\n', " ");
+        if self.overlay.is_some() {
+            self.status_message = Some("Paste into the edit field, the search or the quick filter".to_string());
+            return Outcome::Blocked;
+        }
+        if self.editing {
+            self.insert_text_at_cursor(&text);
+        } else if self.search_mode {
+            if std::mem::take(&mut self.search_selected) {
+                self.filters.text.clear();
+                self.search_cursor = 0;
+            }
+            let text = single_line();
+            let at = Self::char_to_byte_index(&self.filters.text, self.search_cursor);
+            self.filters.text.insert_str(at, &text);
+            self.search_cursor += text.chars().count();
+            self.refilter();
+        } else if let Some(filter) = self.quick_filter.as_mut().filter(|filter| filter.typing) {
+            let text = single_line();
+            let at = Self::char_to_byte_index(&filter.input.text, filter.input.cursor);
+            filter.input.text.insert_str(at, &text);
+            filter.input.cursor += text.chars().count();
+            self.filters.text = filter.input.text.clone();
+            self.refilter();
+        } else {
+            self.status_message = Some("Start editing a field to paste into it".to_string());
+            return Outcome::Blocked;
+        }
+        Outcome::Done
+    }
+
+    /// Value of the field being edited as it is stored in the file
+    fn stored_edit_value(&self) -> Option<&str> {
+        if self.edit_field == EditField::Metadata {
+            let value = self.po_file.get_header().get(&self.metadata_key);
+            return Some(value.map(String::as_str).unwrap_or_default());
+        }
+        let entry = self.get_current_entry()?;
+        match self.edit_field {
+            EditField::Msgid => Some(&entry.msgid),
+            EditField::Msgstr => Some(entry.form(self.plural_form())),
+            EditField::Comments | EditField::Metadata => None,
+        }
+    }
+
+    /// An edit is open and its buffer differs from the stored value
+    pub fn has_uncommitted_edit(&self) -> bool {
+        self.editing && self.stored_edit_value().is_some_and(|value| value != self.edit_text)
+    }
+
+    /// Esc while editing: apply with `esc_commits`, otherwise discard. A
+    /// changed buffer is only discarded by a second Esc.
+    fn escape_edit(&mut self) {
+        if self.config.esc_commits() {
+            self.stop_editing();
+        } else if self.has_uncommitted_edit() && !self.discard_armed {
+            self.discard_armed = true;
+            self.status_message = Some("Press Esc again to discard your changes, Enter to apply them".to_string());
+        } else {
+            self.cancel_editing();
         }
     }
 
@@ -227,89 +758,413 @@ impl App {
         if self.edit_field == EditField::Metadata {
             self.apply_metadata_edit();
         } else if let Some(&actual_index) = self.filtered_indices.get(self.current_entry) {
+            let form = self.plural_form();
             if let Some(entry) = self.po_file.entries.get_mut(actual_index) {
+                let before = entry.clone();
                 match self.edit_field {
                     EditField::Msgid => {
                         entry.msgid = self.edit_text.clone();
                     }
                     EditField::Msgstr => {
-                        entry.set_msgstr(self.edit_text.clone());
+                        entry.set_form(form, self.edit_text.clone());
+                        if std::mem::take(&mut self.suggestion_pending) && !entry.is_fuzzy {
+                            entry.toggle_fuzzy();
+                        }
                     }
-                    EditField::Comments => {
-                        entry.comments = self.edit_text.lines().map(|s| s.to_string()).collect();
+                    EditField::Comments | EditField::Metadata => {
+                        // Handled by the comment manager and above
                     }
-                    EditField::Metadata => {
-                        // Handled above
+                }
+                if self.entry_changed(actual_index, &before) {
+                    self.report_entry_issues(actual_index);
+                    self.refilter_issues();
+                }
+            }
+        }
+    }
+
+    /// Cursors of `entry`'s fields, forgetting those of the entry selected
+    /// before
+    fn field_memory(&mut self, entry: usize) -> &mut FieldMemory {
+        if self.field_memory.entry != Some(entry) {
+            self.field_memory = FieldMemory {
+                entry: Some(entry),
+                ..FieldMemory::default()
+            };
+        }
+        &mut self.field_memory
+    }
+
+    /// Plural form of the selected entry shown in the msgstr pane, 0 for
+    /// singular entries
+    fn plural_form(&self) -> usize {
+        let selected = self.filtered_indices.get(self.current_entry).copied();
+        match self.field_memory.entry {
+            Some(entry) if Some(entry) == selected => self.field_memory.form,
+            _ => 0,
+        }
+    }
+
+    fn set_plural_form(&mut self, form: usize) {
+        if let Some(&actual_index) = self.filtered_indices.get(self.current_entry) {
+            self.field_memory(actual_index).form = form;
+        }
+    }
+
+    /// Key of the edited field in the field memory
+    fn memory_key(&self) -> (EditField, usize) {
+        match self.edit_field {
+            EditField::Msgstr => (EditField::Msgstr, self.plural_form()),
+            field => (field, 0),
+        }
+    }
+
+    fn remember_cursor(&mut self) {
+        if !matches!(self.edit_field, EditField::Msgid | EditField::Msgstr) {
+            return;
+        }
+        if let Some(&actual_index) = self.filtered_indices.get(self.current_entry) {
+            let (key, cursor) = (self.memory_key(), self.edit_cursor);
+            self.field_memory(actual_index).cursors.insert(key, cursor);
+        }
+    }
+
+    /// Tab while editing: commit or stash the buffer as configured, then
+    /// edit `field` (and plural `form`) of the same entry
+    fn switch_editing_field(&mut self, field: EditField, form: usize) {
+        match self.config.field_switch {
+            FieldSwitch::Commit => self.stop_editing(),
+            FieldSwitch::Stash => {
+                if let Some(&actual_index) = self.filtered_indices.get(self.current_entry) {
+                    let (field, form) = self.memory_key();
+                    if self.has_uncommitted_edit() {
+                        self.stashed_edits.insert((actual_index, field, form), self.edit_text.clone());
+                    } else {
+                        self.stashed_edits.remove(&(actual_index, field, form));
                     }
                 }
-                self.po_file.mark_modified();
+                self.cancel_editing();
             }
         }
+        self.edit_field = field;
+        self.set_plural_form(form);
+        self.start_editing();
+    }
+
+    /// `Translation (msgstr[1] of 3, n = 2, 3, 4…)` for plural entries, with
+    /// numbers using the form when the Plural-Forms header can tell
+    fn translation_title(&self, entry: &PoEntry, form: usize) -> String {
+        if !entry.is_plural() {
+            return "Translation (msgstr)".to_string();
+        }
+        let examples = self
+            .po_file
+            .get_header()
+            .get("Plural-Forms")
+            .and_then(|value| PluralForms::parse(value).ok())
+            .map(|forms| forms.examples(form, 3))
+            .filter(|examples| !examples.is_empty());
+        let numbers = match examples {
+            Some(examples) => {
+                let numbers: Vec<String> = examples.iter().map(u64::to_string).collect();
+                format!(", n = {}…", numbers.join(", "))
+            }
+            None => String::new(),
+        };
+        format!("Translation (msgstr[{}] of {}{})", form, entry.form_count(), numbers)
     }
 
+    /// Plural forms of the selected entry, 1 for singular entries
+    fn form_count(&self) -> usize {
+        self.get_current_entry().map_or(1, PoEntry::form_count)
+    }
+
+    /// The plural forms of the msgstr come one after another
     pub fn next_field(&mut self) {
-        if !self.editing && !self.metadata_mode {
-            self.edit_field = match self.edit_field {
-                EditField::Msgid => EditField::Msgstr,
-                EditField::Msgstr => EditField::Comments,
-                EditField::Comments => EditField::Msgid,
-                EditField::Metadata => EditField::Metadata, // Stay in metadata mode
+        if !self.metadata_mode {
+            let form = self.plural_form();
+            let (field, form) = match self.edit_field {
+                EditField::Msgstr if form + 1 < self.form_count() => (EditField::Msgstr, form + 1),
+                EditField::Msgid => (EditField::Msgstr, 0),
+                EditField::Msgstr => (EditField::Comments, 0),
+                EditField::Comments => (EditField::Msgid, 0),
+                EditField::Metadata => (EditField::Metadata, 0), // Stay in metadata mode
             };
+            self.move_to_field(field, form);
         }
     }
 
     pub fn previous_field(&mut self) {
-        if !self.editing && !self.metadata_mode {
-            self.edit_field = match self.edit_field {
-                EditField::Msgid => EditField::Comments,
-                EditField::Msgstr => EditField::Msgid,
-                EditField::Comments => EditField::Msgstr,
-                EditField::Metadata => EditField::Metadata, // Stay in metadata mode
+        if !self.metadata_mode {
+            let form = self.plural_form();
+            let (field, form) = match self.edit_field {
+                EditField::Msgstr if form > 0 => (EditField::Msgstr, form - 1),
+                EditField::Msgid => (EditField::Comments, 0),
+                EditField::Msgstr => (EditField::Msgid, 0),
+                EditField::Comments => (EditField::Msgstr, self.form_count() - 1),
+                EditField::Metadata => (EditField::Metadata, 0), // Stay in metadata mode
             };
+            self.move_to_field(field, form);
+        }
+    }
+
+    fn move_to_field(&mut self, field: EditField, form: usize) {
+        if self.editing {
+            self.switch_editing_field(field, form);
+        } else {
+            self.edit_field = field;
+            self.set_plural_form(form);
         }
     }
 
     pub fn start_search(&mut self) {
+        self.search_snapshot = Some(SearchSnapshot {
+            filters: self.filters.clone(),
+            selected: self.filtered_indices.get(self.current_entry).copied(),
+        });
         self.search_mode = true;
-        self.search_cursor = self.search_query.len();
+        self.search_cursor = self.filters.text.chars().count();
+        self.search_selected = !self.filters.text.is_empty();
     }
 
-    pub fn find_next(&mut self) {
-        if !self.search_query.is_empty() {
-            self.update_filtered_indices();
-            self.next_entry();
-            self.update_list_state();
+    /// Close the search overlay, restoring the filters and selected entry
+    /// from before it opened
+    fn cancel_search(&mut self) {
+        self.search_mode = false;
+        self.search_selected = false;
+        self.remember_find_query();
+        let Some(snapshot) = self.search_snapshot.take() else {
+            return;
+        };
+        self.filters = snapshot.filters;
+        self.search_cursor = 0;
+        self.update_filtered_indices();
+        if let Some(position) = snapshot
+            .selected
+            .and_then(|selected| self.filtered_indices.iter().position(|&i| i == selected))
+        {
+            self.current_entry = position;
+        }
+        self.update_list_state();
+    }
+
+    fn remember_find_query(&mut self) {
+        if !self.filters.text.is_empty() {
+            self.find_query = self.filters.text.clone();
+        }
+    }
+
+    /// The query F3 looks for and the details pane highlights: the search
+    /// or quick filter text, or else the last query searched for
+    fn find_query(&self) -> Option<TextQuery> {
+        let text = if self.filters.text.is_empty() { &self.find_query } else { &self.filters.text };
+        match self.text_query {
+            _ if text.is_empty() => None,
+            Some(ref query) if query.is_for(text, self.search_options) => Some(query.clone()),
+            _ => {
+                let mut cached = self.find_text_query.borrow_mut();
+                if !cached.as_ref().is_some_and(|query| query.is_for(text, self.search_options)) {
+                    *cached = Some(TextQuery::new(text, self.search_options).0);
+                }
+                cached.clone()
+            }
         }
     }
 
+    pub fn find_next(&mut self) {
+        self.find_match(true);
+    }
+
     pub fn find_previous(&mut self) {
-        if !self.search_query.is_empty() {
-            self.update_filtered_indices();
-            self.previous_entry();
-            self.update_list_state();
+        self.find_match(false);
+    }
+
+    /// Select the next (or previous) listed entry matching the find query,
+    /// wrapping around at the ends of the list
+    fn find_match(&mut self, forward: bool) -> bool {
+        let Some(query) = self.find_query() else {
+            self.status_message = Some("Nothing to find, search with Ctrl+F first".to_string());
+            return false;
+        };
+        // The selected entry may leave the list, e.g. once translated with
+        // the untranslated filter on; the search goes on from where it was
+        let selected = self.filtered_indices.get(self.current_entry).copied();
+        self.update_filtered_indices();
+        let len = self.filtered_indices.len();
+        let located = match selected {
+            Some(selected) => self.filtered_indices.binary_search(&selected),
+            None => Err(self.current_entry.min(len)),
+        };
+        let (before, after) = match located {
+            Ok(position) => (position, position + 1),
+            Err(position) => (position, position),
+        };
+        // Positions past the end of the list or before its start wrap around
+        let found = (0..len)
+            .map(|offset| if forward { after + offset } else { before + len - 1 - offset })
+            .find(|&unwrapped| query.matches(&self.po_file.entries[self.filtered_indices[unwrapped % len]]));
+        let Some(unwrapped) = found else {
+            self.status_message = Some(format!("No matches for \"{}\"", query.text()));
+            return false;
+        };
+        if (forward && unwrapped >= len) || (!forward && unwrapped < len) {
+            self.status_message = Some(
+                if forward { "Search wrapped around to the top" } else { "Search wrapped around to the bottom" }.to_string(),
+            );
         }
+        self.current_entry = unwrapped % len;
+        self.update_list_state();
+        true
     }
 
     pub fn toggle_untranslated_filter(&mut self) {
-        self.filter_mode = match self.filter_mode {
+        self.filters.status = match self.filters.status {
             FilterMode::Untranslated => FilterMode::All,
             _ => FilterMode::Untranslated,
         };
-        self.update_filtered_indices();
-        self.update_list_state();
+        self.refilter();
     }
 
     pub fn toggle_fuzzy_filter(&mut self) {
-        self.filter_mode = match self.filter_mode {
+        self.filters.status = match self.filters.status {
             FilterMode::Fuzzy => FilterMode::All,
             _ => FilterMode::Fuzzy,
         };
+        self.refilter();
+    }
+
+    pub fn toggle_needs_work_filter(&mut self) {
+        self.filters.status = match self.filters.status {
+            FilterMode::NeedsWork => FilterMode::All,
+            _ => FilterMode::NeedsWork,
+        };
+        self.refilter();
+    }
+
+    pub fn toggle_issues_filter(&mut self) {
+        self.filters.status = match self.filters.status {
+            FilterMode::Issues => FilterMode::All,
+            _ => FilterMode::Issues,
+        };
+        self.refilter();
+    }
+
+    /// With the issues filter on, drop entries that no longer have any.
+    /// Other filters leave an edited entry listed until they change.
+    fn refilter_issues(&mut self) {
+        if self.filters.status == FilterMode::Issues {
+            self.refilter();
+        }
+    }
+
+    /// The entry carries the configured needs-work flag
+    fn needs_work(&self, entry: &PoEntry) -> bool {
+        entry.has_flag(&self.config.needs_work_flag)
+    }
+
+    pub fn toggle_changed_filter(&mut self) {
+        self.filters.changed = !self.filters.changed;
+        self.refilter();
+    }
+
+    pub fn toggle_marked_filter(&mut self) {
+        self.filters.marked = !self.filters.marked;
+        self.refilter();
+    }
+
+    fn start_filter_prompt(&mut self, kind: PromptKind) {
+        if self.editing || self.search_mode || self.metadata_mode {
+            return;
+        }
+        let (title, current) = match kind {
+            PromptKind::FlagFilter => ("Show entries with flag (empty clears)", &self.filters.flag),
+            _ => ("Show entries from source file (empty clears)", &self.filters.source),
+        };
+        self.overlay = Some(Overlay::Prompt(Prompt {
+            kind,
+            title: title.to_string(),
+            input: TextInput::new(current.as_deref().unwrap_or("")),
+        }));
+    }
+
+    pub fn start_quick_filter(&mut self) {
+        if self.editing || self.search_mode || self.metadata_mode {
+            return;
+        }
+        // Reopening a frozen filter continues editing it
+        if let Some(ref mut filter) = self.quick_filter {
+            filter.typing = true;
+            // The search overlay edits the same query
+            if filter.input.text != self.filters.text {
+                filter.input = TextInput::new(&self.filters.text);
+            }
+            return;
+        }
+        self.quick_filter = Some(QuickFilter {
+            input: TextInput::new(&self.filters.text),
+            typing: true,
+            previous_entry: self.filtered_indices.get(self.current_entry).copied(),
+        });
+    }
+
+    fn is_quick_filter_typing(&self) -> bool {
+        self.quick_filter.as_ref().is_some_and(|f| f.typing)
+    }
+
+    fn handle_quick_filter_input(&mut self, key: KeyEvent) {
+        let Some(ref mut filter) = self.quick_filter else {
+            return;
+        };
+        match key.code {
+            KeyCode::Enter => filter.typing = false,
+            KeyCode::Esc => self.clear_quick_filter(),
+            _ => {
+                if filter.input.handle_key(key) {
+                    self.filters.text = filter.input.text.clone();
+                    self.refilter();
+                }
+            }
+        }
+    }
+
+    /// Remove the quick filter and select the entry that was selected
+    /// before it started
+    fn clear_quick_filter(&mut self) {
+        let Some(filter) = self.quick_filter.take() else {
+            return;
+        };
+        self.filters.text.clear();
+        self.update_filtered_indices();
+        if let Some(position) = filter
+            .previous_entry
+            .and_then(|previous| self.filtered_indices.iter().position(|&i| i == previous))
+        {
+            self.current_entry = position;
+        }
+        self.update_list_state();
+    }
+
+    /// Reset every filter, keeping the selected entry
+    pub fn clear_filters(&mut self) -> bool {
+        if self.filters.is_empty() && self.quick_filter.is_none() {
+            return false;
+        }
+        let selected = self.filtered_indices.get(self.current_entry).copied();
+        self.filters = FilterSet::default();
+        self.quick_filter = None;
+        self.search_cursor = 0;
         self.update_filtered_indices();
+        if let Some(position) = selected.and_then(|s| self.filtered_indices.iter().position(|&i| i == s)) {
+            self.current_entry = position;
+        }
         self.update_list_state();
+        true
     }
 
     pub fn handle_input(&mut self, key: KeyEvent) {
-        if self.search_mode {
+        if self.is_quick_filter_typing() {
+            self.handle_quick_filter_input(key);
+        } else if self.search_mode {
             self.handle_search_input(key);
         } else if self.editing {
             self.handle_edit_input(key);
@@ -317,52 +1172,108 @@ impl App {
     }
 
     fn handle_search_input(&mut self, key: KeyEvent) {
+        if key.modifiers.contains(KeyModifiers::ALT) {
+            let options = &mut self.search_options;
+            match key.code {
+                KeyCode::Char('c') => options.case_sensitive = !options.case_sensitive,
+                KeyCode::Char('r') => options.regex = !options.regex,
+                KeyCode::Char('x') => options.context_and_comments = !options.context_and_comments,
+                _ => return,
+            }
+            self.refilter();
+            return;
+        }
+        let had_query = !self.filters.text.is_empty();
+        let before = self.filters.text.clone();
+        if input::edit_by_words(&mut self.filters.text, &mut self.search_cursor, key) {
+            self.search_selected = false;
+            if self.filters.text != before {
+                self.refilter();
+            }
+            if had_query && self.filters.text.is_empty() {
+                self.filters.search_status = SearchStatus::All;
+            }
+            return;
+        }
+        // Typing or deleting replaces a selected previous query
+        if std::mem::take(&mut self.search_selected) && matches!(key.code, KeyCode::Char(_) | KeyCode::Backspace) {
+            self.filters.text.clear();
+            self.search_cursor = 0;
+            if key.code == KeyCode::Backspace {
+                self.filters.search_status = SearchStatus::All;
+                self.refilter();
+                return;
+            }
+        }
         match key.code {
             KeyCode::Char(c) => {
-                Self::insert_char_at(&mut self.search_query, self.search_cursor, c);
+                Self::insert_char_at(&mut self.filters.text, self.search_cursor, c);
                 self.search_cursor += 1;
-                self.update_filtered_indices();
-                self.current_entry = 0;
-                self.update_list_state();
+                self.refilter();
             }
-            KeyCode::Backspace => {
-                if self.search_cursor > 0 {
-                    self.search_cursor -= 1;
-                    Self::remove_char_at(&mut self.search_query, self.search_cursor);
-                    self.update_filtered_indices();
-                    self.current_entry = 0;
-                    self.update_list_state();
-                }
+            KeyCode::Backspace if self.search_cursor > 0 => {
+                self.search_cursor -= 1;
+                Self::remove_char_at(&mut self.filters.text, self.search_cursor);
+                self.refilter();
             }
-            KeyCode::Left => {
-                if self.search_cursor > 0 {
-                    self.search_cursor -= 1;
-                }
+            KeyCode::Left if self.search_cursor > 0 => {
+                self.search_cursor -= 1;
             }
             KeyCode::Right => {
-                let char_len = self.search_query.chars().count();
+                let char_len = self.filters.text.chars().count();
                 if self.search_cursor < char_len {
                     self.search_cursor += 1;
                 }
             }
-            KeyCode::Enter => {
-                self.search_mode = false;
+            KeyCode::Home => self.search_cursor = 0,
+            KeyCode::End => self.search_cursor = self.filters.text.chars().count(),
+            KeyCode::Tab => {
+                self.filters.search_status = self.filters.search_status.next();
+                self.refilter();
             }
+            KeyCode::Enter => self.stop_editing(),
             _ => {}
         }
+        // Clearing the query starts the next search over all entries
+        if had_query && self.filters.text.is_empty() {
+            self.filters.search_status = SearchStatus::All;
+        }
     }
 
     fn handle_edit_input(&mut self, key: KeyEvent) {
+        self.discard_armed = false;
+        // Any key other than those picking a completion closes the popup
+        if let Some(mut completion) = self.completion.take() {
+            match key.code {
+                KeyCode::Tab | KeyCode::Down => completion.next(),
+                KeyCode::BackTab | KeyCode::Up => completion.previous(),
+                KeyCode::Enter | KeyCode::Right => {
+                    self.accept_completion(&completion);
+                    return;
+                }
+                KeyCode::Esc => return,
+                _ => completion.words.clear(),
+            }
+            if !completion.words.is_empty() {
+                self.completion = Some(completion);
+                return;
+            }
+        }
+
+        if input::edit_by_words(&mut self.edit_text, &mut self.edit_cursor, key) {
+            self.goal_column = None;
+            return;
+        }
         match key.code {
+            // The space that completes an abbreviation is not inserted
+            KeyCode::Char(' ') if self.expand_snippet() => {}
             KeyCode::Char(c) => {
                 Self::insert_char_at(&mut self.edit_text, self.edit_cursor, c);
                 self.edit_cursor += 1;
             }
-            KeyCode::Backspace => {
-                if self.edit_cursor > 0 {
-                    self.edit_cursor -= 1;
-                    Self::remove_char_at(&mut self.edit_text, self.edit_cursor);
-                }
+            KeyCode::Backspace if self.edit_cursor > 0 => {
+                self.edit_cursor -= 1;
+                Self::remove_char_at(&mut self.edit_text, self.edit_cursor);
             }
             KeyCode::Delete => {
                 let char_len = self.edit_text.chars().count();
@@ -370,10 +1281,11 @@ impl App {
                     Self::remove_char_at(&mut self.edit_text, self.edit_cursor);
                 }
             }
-            KeyCode::Left => {
-                if self.edit_cursor > 0 {
-                    self.edit_cursor -= 1;
-                }
+            KeyCode::Left | KeyCode::Right if self.visual_cursor_movement() => {
+                self.visual_step(key.code == KeyCode::Left);
+            }
+            KeyCode::Left if self.edit_cursor > 0 => {
+                self.edit_cursor -= 1;
             }
             KeyCode::Right => {
                 let char_len = self.edit_text.chars().count();
@@ -387,892 +1299,8612 @@ impl App {
             KeyCode::End => {
                 self.edit_cursor = self.edit_text.chars().count();
             }
+            KeyCode::Up | KeyCode::Down => {
+                self.move_cursor_vertically(key.code == KeyCode::Up);
+                return;
+            }
             KeyCode::Enter => {
-                if self.edit_field == EditField::Comments {
-                    Self::insert_char_at(&mut self.edit_text, self.edit_cursor, '\n');
-                    self.edit_cursor += 1;
-                } else {
-                    self.apply_edit();
-                    self.editing = false;
-                }
+                self.apply_edit();
+                self.editing = false;
             }
             _ => {}
         }
+        self.goal_column = None;
+
+        if matches!(key.code, KeyCode::Char(_) | KeyCode::Backspace) {
+            self.update_completion();
+        }
     }
 
-    pub fn is_editing(&self) -> bool {
-        self.editing || self.search_mode
+    /// Replace the abbreviation before the cursor with its snippet. Returns
+    /// whether there was one. Snippets are for translations, so only the
+    /// msgstr is expanded.
+    fn expand_snippet(&mut self) -> bool {
+        if self.edit_field != EditField::Msgstr {
+            return false;
+        }
+        let cursor = Self::char_to_byte_index(&self.edit_text, self.edit_cursor);
+        let Some((abbreviation, expansion)) = snippet::find(&self.config.snippets, &self.edit_text[..cursor]) else {
+            return false;
+        };
+        let text = snippet::expand(expansion, self.get_current_entry());
+        let status = format!("Expanded {}", abbreviation);
+
+        let start = cursor - abbreviation.len();
+        self.edit_cursor = self.edit_text[..start].chars().count() + text.chars().count();
+        self.edit_text.replace_range(start..cursor, &text);
+        self.status_message = Some(status);
+        true
     }
 
-    pub fn is_metadata_mode(&self) -> bool {
-        self.metadata_mode
+    fn insert_at_cursor(&mut self, c: char) {
+        Self::insert_char_at(&mut self.edit_text, self.edit_cursor, c);
+        self.edit_cursor += 1;
+        self.completion = None;
     }
 
-    pub fn is_modified(&self) -> bool {
-        self.po_file.is_modified()
+    /// Insert `text` into the edit buffer at the cursor, on one line when
+    /// editing a metadata value
+    fn insert_text_at_cursor(&mut self, text: &str) {
+        let text = if self.edit_field == EditField::Metadata { text.replace('\n', " ") } else { text.to_string() };
+        let at = Self::char_to_byte_index(&self.edit_text, self.edit_cursor);
+        self.edit_text.insert_str(at, &text);
+        self.edit_cursor += text.chars().count();
+        self.goal_column = None;
+        self.completion = None;
     }
 
-    pub fn save(&mut self) -> Result<()> {
-        self.po_file.save()
+    fn open_char_picker(&mut self) {
+        self.overlay = Some(Overlay::Picker {
+            kind: PickerKind::Character,
+            picker: Picker::new(self.char_picker_items("")),
+        });
     }
-    
-    pub fn save_current_entry(&mut self) -> Result<()> {
-        self.apply_edit();
-        self.po_file.save()
+
+    /// The recent characters, then the common ones or, once something is
+    /// typed, those whose name matches it
+    fn char_picker_items(&self, query: &str) -> Vec<PickerItem> {
+        let item = |c: char, recent: bool| PickerItem {
+            label: format!("{}  {}", c, charmap::name(c).unwrap_or_default()),
+            detail: if recent { format!("{} recent", charmap::codepoint(c)) } else { charmap::codepoint(c) },
+            value: c.to_string(),
+        };
+        let found = if query.trim().is_empty() { charmap::COMMON.to_vec() } else { charmap::search(query, CHAR_SEARCH_LIMIT) };
+        self.recent_chars
+            .iter()
+            .map(|&c| item(c, true))
+            .chain(found.into_iter().filter(|c| !self.recent_chars.contains(c)).map(|c| item(c, false)))
+            .collect()
     }
 
-    pub fn toggle_help(&mut self) {
-        self.help_visible = !self.help_visible;
+    /// Insert a character picked by codepoint or name and remember it
+    fn insert_picked_char(&mut self, c: char) {
+        self.insert_at_cursor(c);
+        self.recent_chars.retain(|&recent| recent != c);
+        self.recent_chars.insert(0, c);
+        self.recent_chars.truncate(RECENT_CHARS);
+        self.status_message = Some(format!("Inserted {}", charmap::codepoint(c)));
     }
 
-    pub fn toggle_metadata_mode(&mut self) {
-        if self.editing {
-            return;
+    /// Insert the language's quotation marks around the cursor. With a pair
+    /// already around the cursor, switch it to the next style.
+    fn cycle_quotes(&mut self) {
+        self.completion = None;
+        let language = self.po_file.get_header().get("Language").map(String::as_str).unwrap_or_default();
+        let styles = language::quotes(language);
+        let chars: Vec<char> = self.edit_text.chars().collect();
+        let around = self
+            .edit_cursor
+            .checked_sub(1)
+            .and_then(|before| Some((*chars.get(before)?, *chars.get(self.edit_cursor)?)));
+
+        match around.and_then(|pair| styles.iter().position(|style| *style == pair)) {
+            Some(style) => {
+                let (open, close) = styles[(style + 1) % styles.len()];
+                let before = self.edit_cursor - 1;
+                Self::remove_char_at(&mut self.edit_text, self.edit_cursor);
+                Self::remove_char_at(&mut self.edit_text, before);
+                Self::insert_char_at(&mut self.edit_text, before, open);
+                Self::insert_char_at(&mut self.edit_text, self.edit_cursor, close);
+            }
+            None => {
+                let (open, close) = styles[0];
+                Self::insert_char_at(&mut self.edit_text, self.edit_cursor, open);
+                Self::insert_char_at(&mut self.edit_text, self.edit_cursor + 1, close);
+                self.edit_cursor += 1;
+            }
         }
-        
-        self.metadata_mode = !self.metadata_mode;
-        if self.metadata_mode {
-            self.edit_field = EditField::Metadata;
-        } else {
-            self.edit_field = EditField::Msgstr;
+    }
+
+    /// Paragraph direction of msgstrs when they are drawn in display order:
+    /// right-to-left for right-to-left languages
+    fn bidi_base(&self) -> Option<bidi::Direction> {
+        self.config.bidi.then(|| {
+            if self.po_file.language().is_some_and(language::is_rtl) {
+                bidi::Direction::Rtl
+            } else {
+                bidi::Direction::Ltr
+            }
+        })
+    }
+
+    /// Left and Right follow the display in a msgstr line with
+    /// right-to-left text
+    fn visual_cursor_movement(&self) -> bool {
+        if self.config.cursor_movement != CursorMovement::Visual
+            || self.metadata_mode
+            || self.edit_field != EditField::Msgstr
+            || self.bidi_base().is_none()
+        {
+            return false;
         }
+        let (line, _) = self.cursor_line();
+        bidi::has_rtl(line)
     }
 
-    pub fn start_metadata_editing(&mut self, key: String) {
-        if !self.metadata_mode {
+    /// Move the edit cursor to the row above or below as the field shows
+    /// it, keeping its column. Past the first or last row it goes to the
+    /// start or end of the text.
+    fn move_cursor_vertically(&mut self, up: bool) {
+        let width = self.edit_width.get();
+        let rows = wrap::wrap(&self.edit_text, width);
+        let (row, column) = wrap::position(&rows, self.edit_cursor, width);
+        let column = *self.goal_column.get_or_insert(column);
+        self.edit_cursor = match (up, row) {
+            (true, 0) => 0,
+            (true, row) => wrap::index_at(&rows, row - 1, column),
+            (false, row) if row + 1 >= rows.len() => self.edit_text.chars().count(),
+            (false, row) => wrap::index_at(&rows, row + 1, column),
+        };
+    }
+
+    /// Line of the edit buffer holding the cursor, with its first char index
+    fn cursor_line(&self) -> (&str, usize) {
+        let mut start = 0;
+        for line in self.edit_text.split('\n') {
+            let len = line.chars().count();
+            if self.edit_cursor <= start + len {
+                return (line, start);
+            }
+            start += len + 1;
+        }
+        ("", start)
+    }
+
+    fn visual_step(&mut self, left: bool) {
+        let fallback = self.bidi_base().unwrap_or(bidi::Direction::Ltr);
+        let (line, start) = self.cursor_line();
+        let base = bidi::base_direction(line, fallback);
+        self.edit_cursor = start + bidi::visual_step(line, base, self.edit_cursor - start, left);
+    }
+
+    /// Offer completions for the word before the cursor in a msgstr
+    fn update_completion(&mut self) {
+        self.completion = None;
+        if !self.config.autocomplete || !self.editing || self.edit_field != EditField::Msgstr {
             return;
         }
-        
-        self.metadata_key = key.clone();
-        self.edit_text = self.po_file.get_header()
-            .get(&key)
-            .cloned()
-            .unwrap_or_default();
-        self.edit_cursor = self.edit_text.chars().count();
-        self.editing = true;
+        let start = complete::word_start(&self.edit_text, self.edit_cursor);
+        if self.edit_cursor - start < complete::MIN_PREFIX_LEN {
+            return;
+        }
+        let prefix: String = self.edit_text.chars().skip(start).take(self.edit_cursor - start).collect();
+        let words = self.words.complete(&prefix);
+        if !words.is_empty() {
+            self.completion = Some(Completion { start, words, selected: 0 });
+        }
     }
 
-    pub fn start_editing_selected_metadata(&mut self) {
-        if self.metadata_mode && !self.metadata_keys.is_empty() && !self.editing {
-            let key = self.metadata_keys[self.metadata_selected].clone();
-            self.start_metadata_editing(key);
+    /// Type the rest of the selected word
+    fn accept_completion(&mut self, completion: &Completion) {
+        let typed = self.edit_cursor - completion.start;
+        for c in completion.selected_word().chars().skip(typed) {
+            Self::insert_char_at(&mut self.edit_text, self.edit_cursor, c);
+            self.edit_cursor += 1;
         }
     }
 
-    pub fn metadata_next(&mut self) {
-        if self.metadata_mode && !self.editing {
-            if self.metadata_selected + 1 < self.metadata_keys.len() {
-                self.metadata_selected += 1;
+    pub fn is_editing(&self) -> bool {
+        self.editing || self.search_mode || self.is_quick_filter_typing()
+    }
+
+    pub fn is_metadata_mode(&self) -> bool {
+        self.metadata_mode
+    }
+
+    pub fn is_modified(&self) -> bool {
+        self.po_file.is_modified()
+    }
+
+    /// On a termination signal: write the unsaved changes, an open edit
+    /// included, to `<file>.recovered` next to the file, which is left as
+    /// it was. Returns where they went, if anywhere.
+    pub fn emergency_save(&mut self) -> Result<Option<PathBuf>> {
+        if self.has_uncommitted_edit() {
+            self.apply_edit();
+        }
+        if !self.is_modified() || self.read_only {
+            return Ok(None);
+        }
+        let path = match self.po_file.path {
+            Some(ref path) => {
+                let mut name = path.file_name().unwrap_or_default().to_os_string();
+                name.push(".recovered");
+                path.with_file_name(name)
             }
+            None => PathBuf::from("untitled.po.recovered"),
+        };
+        // A second signal during the write leaves no half-written copy
+        gettext::write_atomic(&path, &self.po_file.to_string(), false)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        Ok(Some(path))
+    }
+
+    /// Title for the terminal window, e.g. `poterm — ru.po*`
+    pub fn terminal_title(&self) -> String {
+        let name = match self.po_file.path {
+            Some(ref path) => path.file_name().unwrap_or_default().to_string_lossy().into_owned(),
+            None => "New File".to_string(),
+        };
+        let modified = if self.po_file.is_modified() { "*" } else { "" };
+        format!("poterm — {}{}", name, modified)
+    }
+
+    pub fn save(&mut self) -> Result<()> {
+        self.save_to(None)
+    }
+
+    /// Save to `path`, or where the file was read from
+    fn save_to(&mut self, path: Option<PathBuf>) -> Result<()> {
+        // A date typed into the header is kept as is
+        if self.config.revision_date_on_save && self.po_file.is_modified() && !self.template_mode && !self.revision_date_set {
+            self.po_file.update_revision_date();
         }
+        let had_parse_issues = !self.po_file.parse_issues().is_empty();
+        match path {
+            Some(path) => self.po_file.save_as(path)?,
+            None => self.po_file.save()?,
+        }
+        if had_parse_issues {
+            self.issue_counts = None;
+        }
+        self.last_save = Instant::now();
+        self.disk_changed = false;
+        self.saved_entries = by_key(&self.po_file.entries);
+        self.undo.mark_saved();
+        self.revision_date_set = false;
+        self.remove_recovery_file();
+        // The file is saved either way, so this is only reported
+        let journal_error = self.journal.as_mut().and_then(|journal| journal.clear().err());
+        let name = self.po_file.path.as_deref().and_then(Path::file_name).unwrap_or_default();
+        let entries = plural_count(self.po_file.entries.len(), "entry", "entries");
+        // Saved anyway, but msgfmt -c would reject the file
+        let checker = Checker::new(&self.config.style, &self.po_file).with_glossary(self.glossary.as_ref());
+        let with_errors = self
+            .po_file
+            .entries
+            .iter()
+            .filter(|entry| checker.validate_entry(entry).iter().any(|issue| issue.severity == Severity::Error))
+            .count();
+        let errors = match with_errors {
+            0 => String::new(),
+            count => format!(", {} with errors (F8 lists them)", plural_count(count, "entry", "entries")),
+        };
+        self.status_message = Some(match journal_error {
+            None => format!("Saved {} ({}){}", name.to_string_lossy(), entries, errors),
+            Some(e) => format!("Saved {} ({}), but the swap file was not emptied: {:#}", name.to_string_lossy(), entries, e),
+        });
+        self.log_progress();
+        Ok(())
     }
 
-    pub fn metadata_previous(&mut self) {
-        if self.metadata_mode && !self.editing {
-            if self.metadata_selected > 0 {
-                self.metadata_selected -= 1;
+    /// Save, reporting the result in the status line instead of failing.
+    /// Returns whether the file is saved; a file without a path asks for
+    /// one first.
+    fn try_save(&mut self) -> bool {
+        if self.po_file.path.is_none() {
+            self.start_save_as_prompt();
+            return false;
+        }
+        let on_disk = self.po_file.path.as_deref().is_some_and(Path::exists);
+        if !self.po_file.is_modified() && on_disk {
+            self.status_message = Some("Nothing to save".to_string());
+            return true;
+        }
+        if self.po_file.changed_on_disk() {
+            self.overlay = Some(Overlay::ChangedOnDisk { selected: 0 });
+            return false;
+        }
+        self.overwrite()
+    }
+
+    /// Save over whatever is on disk, reporting the result in the status
+    /// line
+    fn overwrite(&mut self) -> bool {
+        match self.save() {
+            Ok(()) => true,
+            Err(e) => {
+                self.report_error(format!("Save failed: {:#}", e));
+                false
             }
         }
     }
 
-    fn apply_metadata_edit(&mut self) {
-        if self.metadata_mode && !self.metadata_key.is_empty() {
-            self.po_file.set_header_field(self.metadata_key.clone(), self.edit_text.clone());
-            self.po_file.update_revision_date();
+    pub fn start_save_as_prompt(&mut self) {
+        let path = self.po_file.path.as_deref().map(|path| path.display().to_string()).unwrap_or_default();
+        self.overlay = Some(Overlay::Prompt(Prompt {
+            kind: PromptKind::SaveAs,
+            title: "Save as (Tab completes the path)".to_string(),
+            input: TextInput::new(&path),
+        }));
+    }
+
+    /// Write the open file to `text` and go on editing it there
+    fn save_as(&mut self, text: &str) {
+        let path = PathBuf::from(text.trim());
+        if path.as_os_str().is_empty() {
+            return;
+        }
+        if path.is_dir() {
+            self.report_error(format!("{} is a directory", path.display()));
+            return;
+        }
+        if path.exists() && self.po_file.path.as_ref() != Some(&path) {
+            self.report_error(format!("{} already exists", path.display()));
+            return;
+        }
+        if let Err(e) = self.save_to(Some(path.clone())) {
+            self.report_error(format!("Save failed: {:#}", e));
+            return;
+        }
+        // The swap file follows the file to its new name
+        let journaled = self.journal.is_some();
+        self.close_journal();
+        if journaled {
+            self.start_journal(&path);
         }
+        if self.recovery_file.is_some() {
+            self.recovery_file = Some(journal::recovery_path(&path));
+        }
+        self.source = None;
+        self.template_mode = !self.force_po && self.po_file.is_template();
     }
 
-    pub fn toggle_current_entry_fuzzy(&mut self) {
-        if !self.filtered_indices.is_empty() && !self.editing && !self.search_mode {
-            let actual_index = self.filtered_indices[self.current_entry];
-            if let Some(entry) = self.po_file.entries.get_mut(actual_index) {
-                // Don't toggle fuzzy status for empty entries (no translation)
-                if entry.msgstr.is_empty() {
-                    return;
-                }
-                
-                entry.toggle_fuzzy();
-                self.po_file.mark_modified();
-                self.po_file.update_revision_date();
+    /// Write a changed file to its recovery file, or save it with
+    /// `autosave_in_place`, once `autosave` seconds have passed since the
+    /// last save. A failure is reported and tried again after as long.
+    fn autosave_if_due(&mut self, now: Instant) {
+        let interval = Duration::from_secs(self.config.autosave);
+        if interval.is_zero()
+            || !self.is_modified()
+            || self.read_only
+            || self.template_mode
+            || self.po_file.path.is_none()
+            || now.duration_since(self.last_save) < interval
+        {
+            return;
+        }
+        if !self.config.autosave_in_place {
+            self.write_recovery_file(now);
+            return;
+        }
+        if self.po_file.changed_on_disk() {
+            self.last_save = now;
+            self.report_error("Not autosaved: the file changed on disk, Ctrl+S to resolve".to_string());
+            return;
+        }
+        match self.save() {
+            Ok(()) => {
+                let name = self.po_file.path.as_deref().and_then(Path::file_name).unwrap_or_default();
+                self.status_message = Some(format!("Autosaved {}", name.to_string_lossy()));
+            }
+            Err(e) => {
+                self.last_save = now;
+                self.report_error(format!("Autosave failed: {:#}", e));
             }
         }
     }
 
-    pub fn mark_current_entry_done(&mut self) {
-        if !self.filtered_indices.is_empty() && !self.editing && !self.search_mode {
-            let actual_index = self.filtered_indices[self.current_entry];
-            if let Some(entry) = self.po_file.entries.get_mut(actual_index) {
-                // Only mark as done if there's a translation
-                if !entry.msgstr.is_empty() {
-                    entry.flags.retain(|flag| flag != "fuzzy");
-                    entry.update_status();
-                    self.po_file.mark_modified();
-                    self.po_file.update_revision_date();
-                }
+    /// Write the whole file to the recovery file, unless it holds this
+    /// revision already
+    fn write_recovery_file(&mut self, now: Instant) {
+        let Some(path) = self.recovery_file.clone() else {
+            return;
+        };
+        if self.recovery_revision == Some(self.po_file.revision) {
+            return;
+        }
+        self.last_save = now;
+        match gettext::write_atomic(&path, &self.po_file.to_string(), false) {
+            Ok(()) => {
+                self.recovery_revision = Some(self.po_file.revision);
+                let name = path.file_name().unwrap_or_default();
+                self.status_message = Some(format!("Autosaved to {}", name.to_string_lossy()));
             }
+            Err(e) => self.report_error(format!("Autosave failed: {}: {:#}", path.display(), e)),
         }
     }
 
-    fn get_current_entry(&self) -> Option<&PoEntry> {
-        if let Some(&actual_index) = self.filtered_indices.get(self.current_entry) {
-            self.po_file.entries.get(actual_index)
-        } else {
-            None
+    /// Delete the recovery file once its contents are saved or discarded.
+    /// One left behind is older than the file, so it is never offered.
+    fn remove_recovery_file(&mut self) {
+        if self.recovery_revision.take().is_some() {
+            if let Some(ref path) = self.recovery_file {
+                let _ = std::fs::remove_file(path);
+            }
         }
     }
-}
-
-pub fn draw(f: &mut Frame, app: &mut App) {
-    let chunks = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([
-            Constraint::Length(3), // Header
-            Constraint::Min(0),    // Main content
-            Constraint::Length(3), // Footer
-        ])
-        .split(f.area());
 
-    // Draw header
-    draw_header(f, chunks[0], app);
+    /// Look at the open file every `DISK_CHECK_INTERVAL` and warn once
+    /// when another program wrote it
+    fn check_disk_if_due(&mut self, now: Instant) {
+        if now.duration_since(self.disk_checked) < DISK_CHECK_INTERVAL {
+            return;
+        }
+        self.disk_checked = now;
+        let changed = self.po_file.changed_on_disk();
+        if changed && !self.disk_changed {
+            let name = self.po_file.path.as_deref().and_then(Path::file_name).unwrap_or_default();
+            self.report_error(format!("{} changed on disk", name.to_string_lossy()));
+        }
+        self.disk_changed = changed;
+    }
 
-    // Draw main content based on mode
-    if app.metadata_mode {
-        draw_metadata_panel(f, chunks[1], app);
-    } else {
-        let main_chunks = Layout::default()
-            .direction(Direction::Horizontal)
-            .constraints([
-                Constraint::Percentage(ENTRY_LIST_WIDTH_PERCENT), 
-                Constraint::Percentage(ENTRY_DETAILS_WIDTH_PERCENT)
-            ])
-            .split(chunks[1]);
+    /// Save over the changed file, reload it or save elsewhere
+    fn handle_conflict_input(&mut self, selected: usize, key: KeyEvent) {
+        let choice = match key.code {
+            KeyCode::Left | KeyCode::Up | KeyCode::BackTab => {
+                self.overlay = Some(Overlay::ChangedOnDisk { selected: selected.saturating_sub(1) });
+                return;
+            }
+            KeyCode::Right | KeyCode::Down | KeyCode::Tab => {
+                self.overlay = Some(Overlay::ChangedOnDisk { selected: min(selected + 1, CONFLICT_CHOICES.len() - 1) });
+                return;
+            }
+            KeyCode::Enter => selected,
+            KeyCode::Char('o') => 0,
+            KeyCode::Char('r') => 1,
+            KeyCode::Char('a') => 2,
+            KeyCode::Char('c') | KeyCode::Esc => 3,
+            _ => {
+                self.overlay = Some(Overlay::ChangedOnDisk { selected });
+                return;
+            }
+        };
+        match choice {
+            0 => {
+                self.overwrite();
+            }
+            1 => self.reload(),
+            2 => self.start_save_as_prompt(),
+            _ => {}
+        }
+    }
 
-        draw_entry_list(f, main_chunks[0], app);
-        draw_entry_details(f, main_chunks[1], app);
+    /// Show `message` as an error, kept until the next key
+    fn report_error(&mut self, message: String) {
+        self.status_error = Some(message.clone());
+        self.status_message = Some(message);
     }
 
-    // Draw footer
-    draw_footer(f, chunks[2], app);
+    fn status_is_error(&self) -> bool {
+        self.status_message.is_some() && self.status_message == self.status_error
+    }
 
-    // Draw search overlay
-    if app.search_mode {
-        draw_search_overlay(f, app);
+    /// Problems met parsing the file just opened; saving loses what they
+    /// describe
+    fn report_parse_issues(&mut self) {
+        let issues = self.po_file.parse_issues();
+        if let Some(first) = issues.first() {
+            let count = plural_count(issues.len(), "parse warning", "parse warnings");
+            self.report_error(format!("{} (F8 lists them), saving drops the lines skipped: {}", count, first));
+        }
     }
 
-    // Draw help overlay
-    if app.help_visible {
-        draw_help_overlay(f);
+    /// Clear a status message that has been shown for `STATUS_TIMEOUT`,
+    /// unless it is an error
+    fn expire_status(&mut self, now: Instant) {
+        let Some(ref message) = self.status_message else {
+            self.status_shown = None;
+            return;
+        };
+        match self.status_shown {
+            Some((ref shown, since)) if shown == message => {
+                if !self.status_is_error() && now.duration_since(since) >= STATUS_TIMEOUT {
+                    self.status_message = None;
+                    self.status_shown = None;
+                }
+            }
+            _ => self.status_shown = Some((message.clone(), now)),
+        }
     }
-}
 
-fn draw_header(f: &mut Frame, area: Rect, app: &App) {
-    let (total, translated, fuzzy) = app.po_file.get_stats();
-    let untranslated = total - translated - fuzzy;
+    /// Append the saved catalog's statistics to the progress log. Failing
+    /// to is reported without failing the save.
+    fn log_progress(&mut self) {
+        let (Some(log), Some(path)) = (history::log_path(&self.config), self.po_file.path.as_ref()) else {
+            return;
+        };
+        if self.template_mode {
+            return;
+        }
+        let path = path.canonicalize().unwrap_or_else(|_| path.clone());
+        let record = history::Record::new(path, &self.po_file, chrono::Local::now());
+        if let Err(error) = history::append(&log, &record) {
+            self.report_error(format!("Saved, but the progress log failed: {:#}", error));
+        }
+    }
     
-    let progress = if total > 0 {
-        (translated as f64 / total as f64) * 100.0
-    } else {
-        0.0
-    };
-
-    let title = if let Some(ref path) = app.po_file.path {
-        format!(
-            "Poterm - {} {}",
-            path.file_name().unwrap_or_default().to_string_lossy(),
-            if app.po_file.is_modified() { "*" } else { "" }
-        )
-    } else {
-        "Poterm - New File".to_string()
-    };
+    pub fn save_current_entry(&mut self) -> bool {
+        self.apply_edit();
+        self.try_save()
+    }
 
-    let stats = format!(
-        "Total: {} | Translated: {} ({:.1}%) | Fuzzy: {} | Untranslated: {}",
-        total, translated, progress, fuzzy, untranslated
-    );
+    pub fn toggle_tree_view(&mut self) {
+        self.tree = match self.tree {
+            Some(_) => None,
+            None => Some(EntryTree::default()),
+        };
+        self.update_list_state();
+        let view = if self.tree.is_some() { "grouped by source file" } else { "flat" };
+        self.status_message = Some(format!("Entry list: {}", view));
+    }
 
-    let block = Block::default()
-        .title(title)
-        .borders(Borders::ALL)
-        .border_style(Style::default().fg(Color::Cyan));
+    /// Rows of the tree view, empty in the flat list
+    fn tree_rows(&self) -> Vec<TreeRow> {
+        match self.tree {
+            Some(ref tree) => tree.rows(&self.po_file.entries, &self.filtered_indices),
+            None => Vec::new(),
+        }
+    }
 
-    let paragraph = Paragraph::new(stats)
-        .block(block)
-        .alignment(Alignment::Center)
-        .style(Style::default().fg(Color::White));
+    /// Move the tree view selection to `row`, clamped to the rows
+    fn select_tree_row(&mut self, row: usize) {
+        let rows = self.tree_rows();
+        let Some(ref mut tree) = self.tree else {
+            return;
+        };
+        if let Some(position) = tree.select(&rows, row.min(rows.len().saturating_sub(1)), &self.filtered_indices) {
+            self.current_entry = position;
+        }
+        self.update_list_state();
+    }
 
-    f.render_widget(paragraph, area);
-}
+    /// Move the tree view selection by `offset` rows
+    fn move_in_tree(&mut self, offset: isize) {
+        if let Some(row) = self.list_state.selected() {
+            self.select_tree_row(row.saturating_add_signed(offset));
+        }
+    }
 
-fn draw_entry_list(f: &mut Frame, area: Rect, app: &mut App) {
-    let items: Vec<ListItem> = app
-        .filtered_indices
-        .iter()
-        .enumerate()
-        .map(|(_i, &actual_index)| {
-            let entry = &app.po_file.entries[actual_index];
-            let status_char = if entry.is_fuzzy {
-                "~"
-            } else if entry.is_translated {
-                "✓"
-            } else {
-                "○"
-            };
+    /// The selected row if it is a group line: its file and whether it is
+    /// open
+    fn selected_group(&self) -> Option<(String, bool)> {
+        let row = self.list_state.selected()?;
+        match self.tree_rows().into_iter().nth(row)? {
+            TreeRow::Group { file, expanded, .. } => Some((file, expanded)),
+            TreeRow::Entry(_) => None,
+        }
+    }
 
-            let color = if entry.is_fuzzy {
-                Color::Yellow
-            } else if entry.is_translated {
-                Color::Green
-            } else {
-                Color::Red
-            };
+    /// Close the selected group, or the group of the selected entry and
+    /// select its line
+    fn collapse_group(&mut self) -> bool {
+        let Some(row) = self.list_state.selected().filter(|_| self.tree.is_some()) else {
+            return false;
+        };
+        let rows = self.tree_rows();
+        let Some(group_row) = rows[..=row].iter().rposition(|row| matches!(row, TreeRow::Group { .. })) else {
+            return false;
+        };
+        let TreeRow::Group { ref file, expanded, .. } = rows[group_row] else {
+            return false;
+        };
+        if !expanded {
+            return false;
+        }
+        if let Some(ref mut tree) = self.tree {
+            tree.set_expanded(file, false);
+        }
+        self.select_tree_row(group_row);
+        true
+    }
 
-            let msgid_preview = if entry.msgid.len() > 35 {
-                format!("{}...", &entry.msgid[..32])
-            } else {
-                entry.msgid.clone()
-            };
+    /// Open the selected group, or step into it when it is open
+    fn expand_group(&mut self) -> bool {
+        let Some((file, expanded)) = self.selected_group() else {
+            return false;
+        };
+        if expanded {
+            self.move_in_tree(1);
+        } else if let Some(ref mut tree) = self.tree {
+            tree.set_expanded(&file, true);
+            self.update_list_state();
+        }
+        true
+    }
 
-            let line = Line::from(vec![
-                Span::styled(format!("{} ", status_char), Style::default().fg(color)),
-                Span::raw(format!("{:3} ", actual_index + 1)),
-                Span::raw(msgid_preview),
-            ]);
+    pub fn toggle_reference_column(&mut self) {
+        self.list_columns.reference = !self.list_columns.reference;
+        let state = if self.list_columns.reference { "on" } else { "off" };
+        self.status_message = Some(format!("Source reference column: {}", state));
+    }
 
-            ListItem::new(line)
-        })
-        .collect();
+    pub fn cycle_index_column(&mut self) {
+        self.list_columns.index = self.list_columns.index.next();
+        self.status_message = Some(format!("Entry numbering: {}", self.list_columns.index.name()));
+    }
 
-    let filter_text = match app.filter_mode {
-        FilterMode::All => "All",
-        FilterMode::Untranslated => "Untranslated",
-        FilterMode::Fuzzy => "Fuzzy",
-    };
+    fn is_entry_changed(&self, actual_index: usize) -> bool {
+        let Some(entry) = self.po_file.entries.get(actual_index) else {
+            return false;
+        };
+        match self.saved_entries.get(&entry.key()) {
+            None => true,
+            // A merge takes entries out of the file without changing them
+            Some(saved) if saved.line != entry.line => *saved != PoEntry { line: saved.line, ..entry.clone() },
+            Some(saved) => saved != entry,
+        }
+    }
 
-    let title = format!("Entries [{}]", filter_text);
-    let block = Block::default()
-        .title(title)
-        .borders(Borders::ALL)
-        .border_style(Style::default().fg(Color::Blue));
+    /// Rebuild the msgid index if the file changed since it was built
+    fn refresh_msgid_index(&mut self) {
+        let revision = self.po_file.revision;
+        if !matches!(self.msgid_index, Some((cached, _)) if cached == revision) {
+            let index = consistency::msgid_index(&self.po_file.entries)
+                .into_iter()
+                .map(|(msgid, indices)| (msgid.to_string(), indices))
+                .collect();
+            self.msgid_index = Some((revision, index));
+        }
+    }
 
-    let list = List::new(items)
+    /// Entries translating the current msgid differently, by the index
+    /// built by `refresh_msgid_index`
+    fn inconsistent_translations(&self) -> Vec<usize> {
+        let Some(&actual_index) = self.filtered_indices.get(self.current_entry) else {
+            return Vec::new();
+        };
+        let entries = &self.po_file.entries;
+        match self.msgid_index {
+            Some((_, ref index)) => index
+                .get(&entries[actual_index].msgid)
+                .map(|same_msgid| consistency::differing(entries, same_msgid, actual_index))
+                .unwrap_or_default(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Select the next entry translating the current msgid differently
+    fn next_inconsistent_translation(&mut self) -> bool {
+        self.refresh_msgid_index();
+        let others = self.inconsistent_translations();
+        let Some(&actual_index) = self.filtered_indices.get(self.current_entry) else {
+            return false;
+        };
+        let Some(&next) = others.iter().find(|&&i| i > actual_index).or(others.first()) else {
+            self.status_message = Some("No other translation of this msgid".to_string());
+            return false;
+        };
+        if !self.select_actual_entry(next) {
+            let line = self.po_file.entries[next].line;
+            self.status_message = Some(format!("Line {} is hidden by the current filter", line));
+            return false;
+        }
+        true
+    }
+
+    pub fn goto_next_untranslated(&mut self) -> bool {
+        self.goto_next_matching("untranslated", |entry| !entry.is_translated)
+    }
+
+    pub fn goto_next_fuzzy(&mut self) -> bool {
+        self.goto_next_matching("fuzzy", |entry| entry.is_fuzzy)
+    }
+
+    /// Select the next listed entry after the current one matching
+    /// `predicate`, wrapping around, without touching the filters
+    fn goto_next_matching(&mut self, what: &str, predicate: impl Fn(&PoEntry) -> bool) -> bool {
+        let len = self.filtered_indices.len();
+        let next = (1..len)
+            .map(|offset| self.filtered_indices[(self.current_entry + offset) % len])
+            .find(|&index| predicate(&self.po_file.entries[index]));
+        match next {
+            Some(index) => self.select_actual_entry(index),
+            None => {
+                self.status_message = Some(format!("No more {} entries", what));
+                false
+            }
+        }
+    }
+
+    /// Issues the per-entry checks find in `entry`
+    fn entry_issues(&self, entry: &PoEntry) -> Vec<ValidationIssue> {
+        Checker::new(&self.config.style, &self.po_file)
+            .with_glossary(self.glossary.as_ref())
+            .validate_entry(entry)
+    }
+
+    /// Report the worst issue of an entry just edited in the status line
+    fn report_entry_issues(&mut self, actual_index: usize) {
+        let mut issues = self.entry_issues(&self.po_file.entries[actual_index]);
+        issues.sort_by_key(|issue| Reverse(issue.severity));
+        let Some(first) = issues.first() else {
+            return;
+        };
+        let more = match issues.len() {
+            1 => String::new(),
+            count => format!(" (+{} more)", count - 1),
+        };
+        let message = format!("{}: {} [{}]{}", first.severity, first.message, first.check, more);
+        if first.severity == Severity::Error {
+            self.report_error(message);
+        } else {
+            self.status_message = Some(message);
+        }
+    }
+
+    /// Issue count per entry, recomputed only when the file has changed
+    fn issue_counts(&mut self) -> &[usize] {
+        let revision = self.po_file.revision;
+        if !matches!(self.issue_counts, Some((cached, _)) if cached == revision) {
+            let checker = Checker::new(&self.config.style, &self.po_file).with_glossary(self.glossary.as_ref());
+            let counts = self
+                .po_file
+                .entries
+                .iter()
+                .map(|entry| checker.validate_entry(entry).len())
+                .collect();
+            self.issue_counts = Some((revision, counts));
+        }
+        self.issue_counts.as_ref().map(|(_, counts)| counts.as_slice()).unwrap_or_default()
+    }
+
+    pub fn toggle_help(&mut self) {
+        self.help_visible = !self.help_visible;
+    }
+
+    pub fn toggle_metadata_mode(&mut self) {
+        // An open edit would apply to the wrong field after switching
+        self.stop_editing();
+
+        self.metadata_mode = !self.metadata_mode;
+        if self.metadata_mode {
+            self.edit_field = EditField::Metadata;
+            self.refresh_metadata_keys();
+        } else {
+            self.edit_field = EditField::Msgstr;
+        }
+    }
+
+    pub fn start_metadata_editing(&mut self, key: String) {
+        if !self.metadata_mode {
+            return;
+        }
+        
+        self.metadata_key = key.clone();
+        self.edit_text = self.po_file.get_header()
+            .get(&key)
+            .cloned()
+            .unwrap_or_default();
+        self.edit_cursor = self.edit_text.chars().count();
+        self.editing = true;
+        self.discard_armed = false;
+    }
+
+    pub fn start_editing_selected_metadata(&mut self) {
+        if self.metadata_mode && !self.metadata_keys.is_empty() && !self.editing {
+            let key = self.metadata_keys[self.metadata_selected].clone();
+            if key == "Plural-Forms" {
+                self.open_plural_forms_picker();
+            } else if key == "Language" {
+                self.open_language_picker();
+            } else {
+                self.start_metadata_editing(key);
+            }
+        }
+    }
+
+    /// Offer the plural rules of known languages, preselecting the one of
+    /// the file's language
+    fn open_plural_forms_picker(&mut self) {
+        let items = LANGUAGES
+            .iter()
+            .map(|language| PickerItem {
+                label: language.name.to_string(),
+                detail: language.code.to_string(),
+                value: language.plural_forms.to_string(),
+            })
+            .collect();
+        let mut picker = Picker::new(items);
+        if let Some(language) = self.po_file.language().and_then(language::lookup) {
+            picker.select_detail(language.code);
+        }
+        self.overlay = Some(Overlay::Picker {
+            kind: PickerKind::PluralForms,
+            picker,
+        });
+    }
+
+    fn open_language_picker(&mut self) {
+        let items = LANGUAGES
+            .iter()
+            .map(|language| PickerItem {
+                label: language.name.to_string(),
+                detail: language.code.to_string(),
+                value: language.code.to_string(),
+            })
+            .collect();
+        let mut picker = Picker::new(items);
+        if let Some(language) = self.po_file.language().and_then(language::lookup) {
+            picker.select_detail(language.code);
+        }
+        self.overlay = Some(Overlay::Picker {
+            kind: PickerKind::Language,
+            picker,
+        });
+    }
+
+    /// Set the Language header and offer matching Plural-Forms and
+    /// Language-Team values. Codes missing from the table are kept with a
+    /// warning.
+    fn apply_language(&mut self, code: String) {
+        self.set_header_value("Language", code.clone());
+        let Some(language) = language::lookup(&code) else {
+            self.status_message = Some(format!("`{}` is not a known language code", code));
+            return;
+        };
+
+        let header = self.po_file.get_header();
+        let current = |key: &str| header.get(key).map(String::as_str).unwrap_or_default();
+        // Keep the team's address, except the template placeholder
+        let team = match current("Language-Team").split_once('<') {
+            Some((_, address)) if address != "LL@li.org>" => format!("{} <{}", language.name, address),
+            _ => language.name.to_string(),
+        };
+        let changes: Vec<(String, String)> = [("Plural-Forms", language.plural_forms.to_string()), ("Language-Team", team)]
+            .into_iter()
+            .filter(|(key, value)| current(key) != value)
+            .map(|(key, value)| (key.to_string(), value))
+            .collect();
+        if !changes.is_empty() {
+            self.overlay = Some(Overlay::LanguageDefaults { changes });
+        }
+    }
+
+    fn handle_picker_input(&mut self, kind: PickerKind, mut picker: Picker, key: KeyEvent) {
+        // A typed codepoint wins over names that happen to match it
+        if kind == PickerKind::Character && key.code == KeyCode::Enter {
+            match charmap::parse_codepoint(&picker.input.text) {
+                Some(Ok(c)) => return self.insert_picked_char(c),
+                Some(Err(error)) => {
+                    picker.error = Some(error);
+                    self.overlay = Some(Overlay::Picker { kind, picker });
+                    return;
+                }
+                None => {}
+            }
+        }
+        let query = picker.input.text.clone();
+        let value = match picker.handle_key(key) {
+            PickerEvent::None => {
+                if kind == PickerKind::Character && picker.input.text != query {
+                    picker.items = self.char_picker_items(&picker.input.text);
+                }
+                self.overlay = Some(Overlay::Picker { kind, picker });
+                return;
+            }
+            PickerEvent::Close => return,
+            PickerEvent::Pick(value) => value,
+        };
+        match kind {
+            PickerKind::PluralForms => match PluralForms::parse(&value) {
+                Ok(_) => {
+                    self.set_header_value("Plural-Forms", value);
+                }
+                Err(error) => {
+                    picker.error = Some(error);
+                    self.overlay = Some(Overlay::Picker { kind, picker });
+                }
+            },
+            PickerKind::Language => self.apply_language(value),
+            PickerKind::Character => match value.chars().next() {
+                Some(c) if value.chars().count() == 1 => self.insert_picked_char(c),
+                _ => {
+                    picker.error = Some(format!("No character is named `{}`", value));
+                    self.overlay = Some(Overlay::Picker { kind, picker });
+                }
+            },
+            PickerKind::Reference => match value.parse() {
+                Ok(index) if picker.items.iter().any(|item| item.value == value) => self.reveal_entry(index),
+                _ => {
+                    picker.error = Some("No listed entry matches".to_string());
+                    self.overlay = Some(Overlay::Picker { kind, picker });
+                }
+            },
+        }
+    }
+
+    pub fn metadata_next(&mut self) {
+        if self.metadata_mode && !self.editing && self.metadata_selected + 1 < self.metadata_keys.len() {
+            self.metadata_selected += 1;
+        }
+    }
+
+    pub fn metadata_previous(&mut self) {
+        if self.metadata_mode && !self.editing && self.metadata_selected > 0 {
+            self.metadata_selected -= 1;
+        }
+    }
+
+    fn refresh_metadata_keys(&mut self) {
+        self.metadata_keys = metadata_keys(&self.po_file.header);
+        self.metadata_selected = self.metadata_selected.min(self.metadata_keys.len().saturating_sub(1));
+    }
+
+    /// Ask before removing the selected header field
+    pub fn delete_selected_metadata(&mut self) -> bool {
+        if !self.metadata_mode || self.editing {
+            return false;
+        }
+        let Some(key) = self.metadata_keys.get(self.metadata_selected).cloned() else {
+            return false;
+        };
+        if !self.po_file.get_header().contains_key(&key) {
+            self.status_message = Some(format!("{} is not set", key));
+            return false;
+        }
+        let confirm = REQUIRED_HEADER_KEYS.contains(&key.as_str()).then(TextInput::default);
+        self.overlay = Some(Overlay::DeleteHeaderField { key, confirm });
+        true
+    }
+
+    /// Move the selected header field one row up or down
+    pub fn move_selected_metadata(&mut self, up: bool) -> bool {
+        if !self.metadata_mode || self.editing {
+            return false;
+        }
+        // Unset fields are listed after the header and can't move
+        let index = self.metadata_selected;
+        let before = self.po_file.header.clone();
+        if !self.po_file.move_header_field(index, up) {
+            return false;
+        }
+        self.metadata_selected = if up { index - 1 } else { index + 1 };
+        self.touch_revision_date();
+        self.header_changed(before);
+        self.refresh_metadata_keys();
+        true
+    }
+
+    pub fn sort_metadata(&mut self) -> bool {
+        if !self.metadata_mode || self.editing {
+            return false;
+        }
+        let selected = self.metadata_keys.get(self.metadata_selected).cloned();
+        let before = self.po_file.header.clone();
+        if !self.po_file.sort_header() {
+            self.status_message = Some("Header is already in canonical order".to_string());
+            return false;
+        }
+        self.touch_revision_date();
+        self.header_changed(before);
+        self.refresh_metadata_keys();
+        if let Some(position) = selected.and_then(|key| self.metadata_keys.iter().position(|k| *k == key)) {
+            self.metadata_selected = position;
+        }
+        true
+    }
+
+    fn delete_header_field(&mut self, key: &str) {
+        let before = self.po_file.header.clone();
+        if self.po_file.remove_header_field(key) {
+            if key != "PO-Revision-Date" {
+                self.touch_revision_date();
+            }
+            self.header_changed(before);
+            self.refresh_metadata_keys();
+            self.status_message = Some(format!("Deleted {}", key));
+        }
+    }
+
+    fn apply_metadata_edit(&mut self) {
+        if self.metadata_mode && !self.metadata_key.is_empty() {
+            self.set_header_value(&self.metadata_key.clone(), self.edit_text.clone());
+        }
+    }
+
+    /// Set a header field if `value` differs from what it holds. Returns
+    /// false, leaving the file unmodified, when nothing changed.
+    fn set_header_value(&mut self, key: &str, value: String) -> bool {
+        match self.po_file.get_header().get(key) {
+            Some(current) if *current == value => return false,
+            None if value.is_empty() => return false,
+            _ => {}
+        }
+        let before = self.po_file.header.clone();
+        self.po_file.set_header_field(key.to_string(), value);
+        if key == "PO-Revision-Date" {
+            self.revision_date_set = true;
+        } else {
+            self.touch_revision_date();
+        }
+        self.header_changed(before);
+        true
+    }
+
+    pub fn toggle_current_entry_fuzzy(&mut self) {
+        if !self.filtered_indices.is_empty() && !self.editing && !self.search_mode {
+            let actual_index = self.filtered_indices[self.current_entry];
+            if let Some(entry) = self.po_file.entries.get_mut(actual_index) {
+                // Don't toggle fuzzy status for empty entries (no translation)
+                if !entry.has_translation() {
+                    return;
+                }
+                
+                let before = entry.clone();
+                entry.toggle_fuzzy();
+                if self.entry_changed(actual_index, &before) {
+                    self.touch_revision_date();
+                }
+            }
+        }
+    }
+
+    pub fn toggle_current_entry_needs_work(&mut self) {
+        if !self.filtered_indices.is_empty() && !self.editing && !self.search_mode {
+            let actual_index = self.filtered_indices[self.current_entry];
+            let flag = self.config.needs_work_flag.clone();
+            if let Some(entry) = self.po_file.entries.get_mut(actual_index) {
+                // Only translations can need review
+                if !entry.has_translation() {
+                    return;
+                }
+
+                let before = entry.clone();
+                entry.toggle_flag(&flag);
+                if self.entry_changed(actual_index, &before) {
+                    self.touch_revision_date();
+                }
+            }
+        }
+    }
+
+    pub fn mark_current_entry_done(&mut self) {
+        if !self.filtered_indices.is_empty() && !self.editing && !self.search_mode {
+            let actual_index = self.filtered_indices[self.current_entry];
+            if let Some(entry) = self.po_file.entries.get_mut(actual_index) {
+                // Only mark as done if there's a translation
+                if entry.has_translation() {
+                    let before = entry.clone();
+                    let needs_work = &self.config.needs_work_flag;
+                    entry.flags.retain(|flag| flag != "fuzzy" && flag != needs_work);
+                    entry.update_status();
+                    if self.entry_changed(actual_index, &before) {
+                        self.touch_revision_date();
+                    }
+                }
+            }
+        }
+    }
+
+    /// Stamp `PO-Revision-Date` after a change, except in templates where
+    /// it stays a placeholder. With `revision_date_on_save` the date is
+    /// stamped by `save` instead.
+    fn touch_revision_date(&mut self) {
+        if !self.template_mode && !self.config.revision_date_on_save {
+            self.po_file.update_revision_date();
+        }
+    }
+
+    /// Mark the file modified if the entry at `actual_index` differs from
+    /// `before`, count the change for the session and keep it for undo.
+    /// Returns whether anything changed.
+    fn entry_changed(&mut self, actual_index: usize, before: &PoEntry) -> bool {
+        if !self.note_entry_change(actual_index, before) {
+            return false;
+        }
+        let after = self.po_file.entries[actual_index].clone();
+        self.undo.push(Edit::Entry {
+            index: actual_index,
+            before: Box::new(before.clone()),
+            after: Box::new(after),
+        });
+        true
+    }
+
+    /// `entry_changed` without recording the change for undo
+    fn note_entry_change(&mut self, actual_index: usize, before: &PoEntry) -> bool {
+        let Some(after) = self.po_file.entries.get(actual_index) else {
+            return false;
+        };
+        if after == before {
+            return false;
+        }
+        self.words.remove(&before.msgstr);
+        self.words.add(&after.msgstr);
+        if let Some(ref mut journal) = self.journal {
+            if let Err(e) = journal.record(Change::new(before, after)) {
+                self.status_message = Some(format!("{:#}", e));
+            }
+        }
+        let file = self.po_file.path.as_deref().unwrap_or(Path::new(""));
+        self.session.record_change(file, before, after);
+        self.po_file.mark_modified();
+        true
+    }
+
+    /// Keep a header change for undo, given the header before it
+    fn header_changed(&mut self, before: IndexMap<String, String>) {
+        // IndexMap equality ignores the order, which moves change
+        if !before.iter().eq(self.po_file.header.iter()) {
+            self.undo.push(Edit::Header {
+                before,
+                after: self.po_file.header.clone(),
+            });
+        }
+    }
+
+    /// Revert the last change, or with `redo` make the last undone one
+    /// again. Returns false when there is none.
+    pub fn undo(&mut self, redo: bool) -> bool {
+        let edit = if redo { self.undo.redo() } else { self.undo.undo() };
+        let Some(edit) = edit else {
+            self.status_message = Some(format!("Nothing to {}", if redo { "redo" } else { "undo" }));
+            return false;
+        };
+        self.apply_undo_edit(edit);
+        if self.undo.is_at_saved() {
+            self.po_file.mark_unmodified();
+        }
+        true
+    }
+
+    fn apply_undo_edit(&mut self, edit: Edit) {
+        match edit {
+            Edit::Entry { index, before, after } => {
+                if let Some(entry) = self.po_file.entries.get_mut(index) {
+                    *entry = *after;
+                }
+                self.note_entry_change(index, &before);
+                if !self.metadata_mode && !self.select_actual_entry(index) {
+                    self.status_message = Some("The changed entry is hidden by the filters".to_string());
+                }
+            }
+            Edit::Header { after, .. } => {
+                self.po_file.header = after;
+                self.po_file.mark_modified();
+                self.refresh_metadata_keys();
+            }
+            Edit::Group(edits) => {
+                for edit in edits {
+                    self.apply_undo_edit(edit);
+                }
+            }
+        }
+    }
+
+    /// Line printed after the terminal is restored, if anything changed
+    pub fn session_summary(&self) -> Option<String> {
+        self.session.summary()
+    }
+
+    fn get_current_entry(&self) -> Option<&PoEntry> {
+        if let Some(&actual_index) = self.filtered_indices.get(self.current_entry) {
+            self.po_file.entries.get(actual_index)
+        } else {
+            None
+        }
+    }
+
+    pub fn resolve_key(&mut self, key: KeyEvent) -> Option<Action> {
+        let tracked = self.tracked_path();
+        let time = self.session.record_activity(Instant::now(), tracked.as_deref());
+        if let Some(path) = tracked.filter(|_| !time.is_zero()) {
+            self.state.add_time(path, time);
+        }
+
+        // A running command blocks input until it finishes or is cancelled
+        if let Some((ref job, _)) = self.pipe_job {
+            if key.code == KeyCode::Esc {
+                job.cancel();
+            }
+            return None;
+        }
+
+        // Overlays are modal and interpret keys themselves
+        if self.overlay.is_some() {
+            return Some(Action::Input(key));
+        }
+
+        // The completion popup takes the keys that pick from it
+        if self.completion.is_some()
+            && matches!(
+                (key.modifiers, key.code),
+                (KeyModifiers::NONE, KeyCode::Tab | KeyCode::Up | KeyCode::Down | KeyCode::Enter | KeyCode::Right | KeyCode::Esc)
+                    | (_, KeyCode::BackTab)
+            )
+        {
+            return Some(Action::Input(key));
+        }
+
+        // Tab switches the statuses the search looks at, Alt+C, Alt+R and
+        // Alt+X its options
+        if self.search_mode
+            && matches!(
+                (key.modifiers, key.code),
+                (KeyModifiers::NONE, KeyCode::Tab) | (KeyModifiers::ALT, KeyCode::Char('c' | 'r' | 'x'))
+            )
+        {
+            return Some(Action::Input(key));
+        }
+
+        // Up and Down step through the results while the query is typed
+        if self.search_mode && key.modifiers == KeyModifiers::NONE {
+            match key.code {
+                KeyCode::Up => return Some(Action::Previous),
+                KeyCode::Down => return Some(Action::Next),
+                _ => {}
+            }
+        }
+
+        // Plain keys all belong to the quick filter while it is typed into,
+        // modified keys such as Ctrl+U still reach the keymap
+        if self.is_quick_filter_typing() && !key.modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) {
+            return Some(Action::Input(key));
+        }
+
+        let editing = self.is_editing();
+        let action = self.keymap.resolve(key, editing);
+        if let Some(message) = self.keymap.take_message() {
+            self.status_message = Some(message);
+        }
+        action
+    }
+
+    /// Perform an action triggered by the user, recording it if a macro is
+    /// being recorded.
+    pub fn perform(&mut self, action: Action) -> Result<Outcome> {
+        self.status_message = None;
+
+        if let Some(ref mut recording) = self.macro_recording {
+            if !matches!(
+                action,
+                Action::Quit
+                    | Action::QuitWithoutSaving
+                    | Action::Suspend
+                    | Action::ToggleMacroRecording
+                    | Action::PlayMacro(_)
+            ) {
+                recording.push(action);
+            }
+        }
+
+        match action {
+            Action::ToggleMacroRecording => {
+                self.toggle_macro_recording();
+                Ok(Outcome::Done)
+            }
+            Action::PlayMacro(times) => self.play_macro(times),
+            _ => self.apply_action(action),
+        }
+    }
+
+    fn apply_action(&mut self, action: Action) -> Result<Outcome> {
+        let position = (self.current_entry, self.metadata_selected, self.list_state.selected());
+        let moved = |app: &Self| {
+            if (app.current_entry, app.metadata_selected, app.list_state.selected()) != position {
+                Outcome::Done
+            } else {
+                Outcome::Blocked
+            }
+        };
+
+        if self.read_only && self.modifies_file(action) {
+            self.status_message = Some(READ_ONLY_MESSAGE.to_string());
+            return Ok(Outcome::Blocked);
+        }
+
+        if self.has_uncommitted_edit() && leaves_edit(action) {
+            self.overlay = Some(Overlay::UncommittedEdit { then: action });
+            return Ok(Outcome::Done);
+        }
+
+        match action {
+            Action::Quit if self.is_modified() && !self.read_only => {
+                self.overlay = Some(Overlay::QuitUnsaved { selected: 0 });
+            }
+            Action::Quit | Action::QuitWithoutSaving => return Ok(Outcome::Quit),
+            Action::Save => {
+                self.try_save();
+            }
+            Action::SaveAndQuit => {
+                if self.try_save() {
+                    return Ok(Outcome::Quit);
+                }
+            }
+            Action::SaveCurrentEntry => {
+                self.save_current_entry();
+            }
+            Action::SaveAs => self.start_save_as_prompt(),
+            Action::Next => {
+                if self.is_metadata_mode() {
+                    self.metadata_next();
+                } else if self.tree.is_some() {
+                    self.move_in_tree(1);
+                } else {
+                    self.next_entry();
+                }
+                return Ok(moved(self));
+            }
+            Action::Previous => {
+                if self.is_metadata_mode() {
+                    self.metadata_previous();
+                } else if self.tree.is_some() {
+                    self.move_in_tree(-1);
+                } else {
+                    self.previous_entry();
+                }
+                return Ok(moved(self));
+            }
+            Action::PageUp if self.tree.is_some() => {
+                self.move_in_tree(-(PAGE_SIZE as isize));
+                return Ok(moved(self));
+            }
+            Action::PageDown if self.tree.is_some() => {
+                self.move_in_tree(PAGE_SIZE as isize);
+                return Ok(moved(self));
+            }
+            Action::First if self.tree.is_some() => {
+                self.select_tree_row(0);
+                return Ok(moved(self));
+            }
+            Action::Last if self.tree.is_some() => {
+                self.select_tree_row(usize::MAX);
+                return Ok(moved(self));
+            }
+            Action::PageUp => {
+                self.page_up();
+                return Ok(moved(self));
+            }
+            Action::PageDown => {
+                self.page_down();
+                return Ok(moved(self));
+            }
+            Action::First => {
+                self.go_to_first();
+                return Ok(moved(self));
+            }
+            Action::Last => {
+                self.go_to_last();
+                return Ok(moved(self));
+            }
+            // Enter applies the open edit or keeps the searched view
+            Action::StartEditing if self.editing || self.search_mode => self.stop_editing(),
+            // Enter on a group line opens or closes it
+            Action::StartEditing if !self.is_metadata_mode() && self.selected_group().is_some() => {
+                if let (Some((file, expanded)), Some(ref mut tree)) = (self.selected_group(), self.tree.as_mut()) {
+                    tree.set_expanded(&file, !expanded);
+                }
+                self.update_list_state();
+            }
+            Action::StartEditing => {
+                if self.is_metadata_mode() {
+                    self.start_editing_selected_metadata();
+                } else {
+                    self.start_editing();
+                }
+                if !self.editing && self.overlay.is_none() {
+                    return Ok(Outcome::Blocked);
+                }
+            }
+            Action::EditTranslation => {
+                if self.is_metadata_mode() {
+                    self.start_editing_selected_metadata();
+                } else if !self.editing {
+                    self.edit_field = EditField::Msgstr;
+                    self.start_editing();
+                }
+                if !self.editing {
+                    return Ok(Outcome::Blocked);
+                }
+            }
+            Action::Cancel => {
+                if self.help_visible {
+                    self.toggle_help();
+                } else if self.editing {
+                    self.escape_edit();
+                } else if self.search_mode {
+                    self.cancel_search();
+                } else if !self.metadata_mode && !self.clear_filters() {
+                    return Ok(Outcome::Blocked);
+                }
+            }
+            Action::NextField => self.next_field(),
+            Action::PreviousField => self.previous_field(),
+            Action::StartSearch => self.start_search(),
+            Action::QuickFilter => self.start_quick_filter(),
+            Action::FindNext => {
+                self.find_next();
+                return Ok(moved(self));
+            }
+            Action::FindPrevious => {
+                self.find_previous();
+                return Ok(moved(self));
+            }
+            Action::ToggleUntranslatedFilter => self.toggle_untranslated_filter(),
+            Action::ToggleFuzzyFilter => self.toggle_fuzzy_filter(),
+            Action::FilterByFlag => self.start_filter_prompt(PromptKind::FlagFilter),
+            Action::FilterBySource => self.start_filter_prompt(PromptKind::SourceFilter),
+            Action::GoToEntry => self.start_go_to_prompt(),
+            Action::NextInconsistent => {
+                if !self.next_inconsistent_translation() {
+                    return Ok(Outcome::Blocked);
+                }
+            }
+            Action::NextUntranslated => {
+                if !self.goto_next_untranslated() {
+                    return Ok(Outcome::Blocked);
+                }
+            }
+            Action::NextFuzzy => {
+                if !self.goto_next_fuzzy() {
+                    return Ok(Outcome::Blocked);
+                }
+            }
+            Action::ToggleChangedFilter => self.toggle_changed_filter(),
+            Action::ToggleMarkedFilter => self.toggle_marked_filter(),
+            Action::ToggleHelp => self.toggle_help(),
+            Action::ToggleMetadataMode => self.toggle_metadata_mode(),
+            Action::DeleteHeaderField => {
+                if !self.delete_selected_metadata() {
+                    return Ok(Outcome::Blocked);
+                }
+            }
+            Action::MoveHeaderFieldUp | Action::MoveHeaderFieldDown => {
+                if !self.move_selected_metadata(action == Action::MoveHeaderFieldUp) {
+                    return Ok(Outcome::Blocked);
+                }
+            }
+            Action::SortHeaderFields => {
+                if !self.sort_metadata() {
+                    return Ok(Outcome::Blocked);
+                }
+            }
+            Action::ToggleFuzzy => {
+                let was_fuzzy = self.get_current_entry().map(|e| e.is_fuzzy);
+                self.toggle_current_entry_fuzzy();
+                if self.get_current_entry().map(|e| e.is_fuzzy) == was_fuzzy {
+                    return Ok(Outcome::Blocked);
+                }
+            }
+            Action::ToggleNeedsWork => {
+                let was_flagged = self.get_current_entry().map(|e| self.needs_work(e));
+                self.toggle_current_entry_needs_work();
+                if self.get_current_entry().map(|e| self.needs_work(e)) == was_flagged {
+                    return Ok(Outcome::Blocked);
+                }
+            }
+            Action::ToggleNeedsWorkFilter => self.toggle_needs_work_filter(),
+            Action::ToggleIssuesFilter => self.toggle_issues_filter(),
+            Action::MarkDone => self.mark_current_entry_done(),
+            Action::PipeCommand => self.start_pipe_prompt(),
+            Action::Replace => {
+                if self.template_mode {
+                    self.status_message = Some(TEMPLATE_MESSAGE.to_string());
+                    return Ok(Outcome::Blocked);
+                }
+                self.overlay = Some(Overlay::Replace(self.last_replace.clone()));
+            }
+            Action::ToggleQaPanel => self.open_qa_panel(),
+            Action::ShowStats => self.overlay = Some(Overlay::Stats),
+            Action::ExpandSnippet => {
+                if self.editing && !self.expand_snippet() {
+                    self.status_message = Some("No snippet abbreviation before the cursor".to_string());
+                }
+            }
+            Action::ShowSnippets => self.overlay = Some(Overlay::Snippets),
+            Action::Copy => {
+                let text = if self.editing { Some(self.edit_text.clone()) } else { self.displayed_field_text() };
+                let Some(text) = text else {
+                    return Ok(Outcome::Blocked);
+                };
+                self.copy_to_clipboard(&text);
+            }
+            Action::Cut => {
+                if !self.editing {
+                    self.status_message = Some("Cut works on the edit buffer; Ctrl+C copies the field".to_string());
+                    return Ok(Outcome::Blocked);
+                }
+                let text = std::mem::take(&mut self.edit_text);
+                self.edit_cursor = 0;
+                self.copy_to_clipboard(&text);
+            }
+            Action::Paste => match self.clipboard.paste() {
+                Some(text) => return Ok(self.paste_text(&text)),
+                None => {
+                    self.status_message = Some("The clipboard is empty".to_string());
+                    return Ok(Outcome::Blocked);
+                }
+            },
+            Action::ShowPreview => {
+                if self.get_current_entry().is_some() && !self.metadata_mode {
+                    self.overlay = Some(Overlay::Preview);
+                }
+            }
+            Action::ReloadGlossary => self.reload_glossary(),
+            Action::AcceptSuggestion(number) => {
+                if !self.accept_suggestion(number) {
+                    return Ok(Outcome::Blocked);
+                }
+            }
+            Action::InsertChar(c) if self.editing => self.insert_at_cursor(c),
+            Action::CycleQuotes if self.editing => self.cycle_quotes(),
+            Action::InsertUnicode if self.editing => self.open_char_picker(),
+            Action::InsertChar(_) | Action::CycleQuotes | Action::InsertUnicode => return Ok(Outcome::Blocked),
+            Action::OpenFile => self.open_file_browser(),
+            Action::RecentFiles => self.open_recent_files(),
+            Action::Reload => self.request_reload(),
+            Action::CreateFromTemplate => self.start_template_prompt(),
+            Action::MergeTemplate => self.start_merge_prompt(),
+            Action::CompileMo => self.compile_mo(),
+            Action::Export => self.start_export_prompt(),
+            Action::Import => self.start_import_prompt(),
+            Action::CycleIndexColumn => self.cycle_index_column(),
+            Action::ToggleReferenceColumn => self.toggle_reference_column(),
+            Action::ToggleTreeView => self.toggle_tree_view(),
+            Action::CollapseGroup => {
+                if !self.collapse_group() {
+                    return Ok(Outcome::Blocked);
+                }
+            }
+            Action::ExpandGroup => {
+                if !self.expand_group() {
+                    return Ok(Outcome::Blocked);
+                }
+            }
+            Action::SetMark(digit) => {
+                if !self.set_mark(digit) {
+                    return Ok(Outcome::Blocked);
+                }
+            }
+            Action::JumpToMark(digit) => {
+                if !self.jump_to_mark(digit) {
+                    return Ok(Outcome::Blocked);
+                }
+            }
+            Action::Undo | Action::Redo => {
+                if self.editing || self.search_mode || !self.undo(action == Action::Redo) {
+                    return Ok(Outcome::Blocked);
+                }
+            }
+            Action::Suspend => {
+                if cfg!(unix) {
+                    return Ok(Outcome::Suspend);
+                }
+                return Ok(Outcome::Blocked);
+            }
+            Action::Input(key) => {
+                if let Some(Overlay::UncommittedEdit { then }) = self.overlay {
+                    return self.handle_uncommitted_edit_input(then, key);
+                } else if let Some(Overlay::QuitUnsaved { selected }) = self.overlay {
+                    return self.handle_quit_input(selected, key);
+                } else if matches!(self.overlay, Some(Overlay::Recover(Swap { running: true, .. }))) {
+                    return Ok(self.handle_running_swap_input(key));
+                } else if self.overlay.is_some() {
+                    self.handle_overlay_input(key);
+                } else if self.is_editing() {
+                    self.handle_input(key);
+                } else {
+                    return Ok(Outcome::Blocked);
+                }
+            }
+            Action::ToggleMacroRecording | Action::PlayMacro(_) => {}
+        }
+
+        Ok(Outcome::Done)
+    }
+
+    pub fn set_mark(&mut self, digit: u8) -> bool {
+        let Some(key) = self.get_current_entry().map(|e| e.key()) else {
+            return false;
+        };
+        // A digit marks one entry, and an entry carries at most one digit
+        for mark in self.marks.iter_mut() {
+            if mark.as_ref() == Some(&key) {
+                *mark = None;
+            }
+        }
+        self.marks[digit as usize] = Some(key);
+        self.status_message = Some(format!("Mark {} set", digit));
+        true
+    }
+
+    pub fn jump_to_mark(&mut self, digit: u8) -> bool {
+        let Some(key) = self.marks[digit as usize].as_ref() else {
+            self.status_message = Some(format!("Mark {} is not set", digit));
+            return false;
+        };
+        let found = self
+            .po_file
+            .entries
+            .iter()
+            .position(|e| e.msgctxt == key.msgctxt && e.msgid == key.msgid);
+        let Some(actual_index) = found else {
+            self.status_message = Some(format!("Entry for mark {} no longer exists", digit));
+            return false;
+        };
+        if !self.select_actual_entry(actual_index) {
+            self.status_message = Some(format!("Entry for mark {} is hidden by the current filter", digit));
+            return false;
+        }
+        true
+    }
+
+    /// Ask for the template to merge, starting in the catalog's directory
+    pub fn start_merge_prompt(&mut self) {
+        if self.template_mode {
+            self.status_message = Some("Templates are merged into .po files, open one first".to_string());
+            return;
+        }
+        let dir = match self.po_file.path.as_deref().and_then(Path::parent) {
+            Some(dir) if !dir.as_os_str().is_empty() => format!("{}/", dir.display()),
+            _ => String::new(),
+        };
+        self.overlay = Some(Overlay::Prompt(Prompt {
+            kind: PromptKind::MergeTemplate,
+            title: "Merge template (Tab completes the path)".to_string(),
+            input: TextInput::new(&dir),
+        }));
+    }
+
+    /// Merge the template at `text` into the catalog, as msgmerge does. The
+    /// selected entry stays selected while the template still has it.
+    fn merge_template(&mut self, text: &str) {
+        let path = PathBuf::from(text.trim());
+        if path.as_os_str().is_empty() {
+            return;
+        }
+        let selected = self.get_current_entry().map(PoEntry::key);
+        let stashed: Vec<_> = self
+            .stashed_edits
+            .iter()
+            .filter_map(|(&(index, field, form), text)| Some((self.po_file.entries.get(index)?.key(), field, form, text.clone())))
+            .collect();
+        let summary = match self.po_file.merge_from_pot(&path, &self.config.merge_options()) {
+            Ok(summary) => summary,
+            Err(e) => {
+                self.report_error(format!("Merge failed: {:#}", e));
+                return;
+            }
+        };
+        // Entries moved, so the undo steps no longer point at theirs
+        self.undo = UndoHistory::default();
+        self.field_memory = FieldMemory::default();
+        // Stashed edits follow their entries, those of entries that became obsolete are dropped
+        self.stashed_edits.clear();
+        let mut dropped = 0;
+        for (key, field, form, text) in stashed {
+            match self.po_file.entries.iter().position(|entry| entry.key() == key) {
+                Some(index) => {
+                    self.stashed_edits.insert((index, field, form), text);
+                }
+                None => dropped += 1,
+            }
+        }
+        self.words = WordIndex::build(&self.po_file.entries);
+        self.issue_counts = None;
+        self.msgid_index = None;
+        self.update_filtered_indices();
+        self.current_entry = 0;
+        let actual_index = selected.and_then(|key| self.po_file.entries.iter().position(|entry| entry.key() == key));
+        if !actual_index.is_some_and(|index| self.select_actual_entry(index)) {
+            self.update_list_state();
+        }
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        self.status_message = Some(match dropped {
+            0 => format!("Merged {}: {}", name, summary),
+            count => format!(
+                "Merged {}: {}; discarded {} of entries no longer in the template",
+                name,
+                summary,
+                plural_count(count, "stashed edit", "stashed edits")
+            ),
+        });
+    }
+
+    /// Write the catalog as it is in the editor to the `.mo` next to it
+    pub fn compile_mo(&mut self) {
+        if self.template_mode {
+            self.status_message = Some("Templates have no translations to compile".to_string());
+            return;
+        }
+        let Some(path) = self.po_file.path.as_deref().map(mo::mo_path) else {
+            self.status_message = Some("Save the file before compiling it".to_string());
+            return;
+        };
+        match mo::write_mo(&self.po_file, &path) {
+            Ok(summary) => {
+                let name = path.file_name().unwrap_or_default().to_string_lossy();
+                self.status_message = Some(format!("Compiled {}: {}", name, summary));
+            }
+            Err(e) => self.report_error(format!("Compile failed: {:#}", e)),
+        }
+    }
+
+    /// Ask where to export the entries, offering the `.csv` next to the
+    /// catalog
+    pub fn start_export_prompt(&mut self) {
+        let path = self.po_file.path.as_deref().map(|path| path.with_extension("csv").display().to_string());
+        self.overlay = Some(Overlay::Prompt(Prompt {
+            kind: PromptKind::Export,
+            title: "Export to .csv, .xlf or .json (Tab completes the path)".to_string(),
+            input: TextInput::new(&path.unwrap_or_default()),
+        }));
+    }
+
+    fn export(&mut self, text: &str) {
+        let path = PathBuf::from(text.trim());
+        if path.as_os_str().is_empty() {
+            return;
+        }
+        match exchange::export(&self.po_file, &path, &CsvOptions::default()) {
+            Ok(count) => {
+                let name = path.file_name().unwrap_or_default().to_string_lossy();
+                self.status_message = Some(format!("Exported {} to {}", plural_count(count, "entry", "entries"), name));
+            }
+            Err(e) => self.report_error(format!("Export failed: {:#}", e)),
+        }
+    }
+
+    /// Ask for the exported file to import, starting in the catalog's
+    /// directory
+    pub fn start_import_prompt(&mut self) {
+        if self.template_mode {
+            self.status_message = Some("Translations are imported into .po files, open one first".to_string());
+            return;
+        }
+        let dir = match self.po_file.path.as_deref().and_then(Path::parent) {
+            Some(dir) if !dir.as_os_str().is_empty() => format!("{}/", dir.display()),
+            _ => String::new(),
+        };
+        self.overlay = Some(Overlay::Prompt(Prompt {
+            kind: PromptKind::Import,
+            title: "Import translations from .csv, .xlf or .json (Tab completes the path)".to_string(),
+            input: TextInput::new(&dir),
+        }));
+    }
+
+    /// Read the translations in the file at `text` into their entries, as
+    /// one undo step
+    fn import(&mut self, text: &str) {
+        let path = PathBuf::from(text.trim());
+        if path.as_os_str().is_empty() {
+            return;
+        }
+        let before = self.po_file.entries.clone();
+        let summary = match exchange::import(&mut self.po_file, &path) {
+            Ok(summary) => summary,
+            Err(e) => {
+                self.report_error(format!("Import failed: {:#}", e));
+                return;
+            }
+        };
+        self.undo.begin_group();
+        let mut changed = false;
+        for (index, before) in before.iter().enumerate() {
+            changed |= self.entry_changed(index, before);
+        }
+        self.undo.end_group();
+        if changed {
+            self.touch_revision_date();
+        }
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        self.status_message = Some(format!("Imported {}: {}", name, summary));
+    }
+
+    pub fn start_go_to_prompt(&mut self) {
+        if self.editing || self.search_mode || self.metadata_mode || self.filtered_indices.is_empty() {
+            return;
+        }
+        self.overlay = Some(Overlay::Prompt(Prompt {
+            kind: PromptKind::GoToEntry,
+            title: format!("Go to entry by {}", self.go_to_numbering().name()),
+            input: TextInput::default(),
+        }));
+    }
+
+    /// Numbering entry numbers are read in: the one the list shows, file
+    /// positions when the list shows none
+    fn go_to_numbering(&self) -> IndexMode {
+        match self.list_columns.index {
+            IndexMode::Off => IndexMode::Original,
+            mode => mode,
+        }
+    }
+
+    fn go_to_entry(&mut self, text: &str) {
+        let numbering = self.go_to_numbering();
+        let Some(number) = text.trim().parse::<usize>().ok().filter(|&n| n > 0) else {
+            // A pasted `file:line` works here too
+            if text.trim().chars().any(|c| !c.is_ascii_digit()) {
+                return self.go_to_reference(text);
+            }
+            self.status_message = Some(format!("Not an entry number: {}", text.trim()));
+            return;
+        };
+        let found = match numbering {
+            IndexMode::Filtered if number <= self.filtered_indices.len() => {
+                self.current_entry = number - 1;
+                self.update_list_state();
+                true
+            }
+            IndexMode::Filtered => false,
+            _ => number <= self.po_file.entries.len() && self.select_actual_entry(number - 1),
+        };
+        self.status_message = Some(if found {
+            format!("Entry {} by {}", number, numbering.name())
+        } else if numbering != IndexMode::Filtered && number <= self.po_file.entries.len() {
+            format!("Entry {} is hidden by the current filter", number)
+        } else {
+            format!("No entry {} by {}", number, numbering.name())
+        });
+    }
+
+    /// Select the entry referencing the source location in `text`, or let
+    /// the user choose when several match equally well
+    fn go_to_reference(&mut self, text: &str) {
+        let Some(location) = Location::parse(text) else {
+            return;
+        };
+        let matches = reference::best_matches(&self.po_file.entries, &location);
+        match matches[..] {
+            [] => self.status_message = Some(format!("No entry references {}", text.trim())),
+            [index] => self.reveal_entry(index),
+            _ => {
+                let items = matches
+                    .iter()
+                    .map(|&index| {
+                        let entry = &self.po_file.entries[index];
+                        PickerItem {
+                            label: truncate_to_width(&entry.msgid, 48),
+                            detail: reference::matching_reference(entry, &location).unwrap_or_default().to_string(),
+                            value: index.to_string(),
+                        }
+                    })
+                    .collect();
+                self.overlay = Some(Overlay::Picker {
+                    kind: PickerKind::Reference,
+                    picker: Picker::new(items),
+                });
+            }
+        }
+    }
+
+    /// Select an entry by its index in the file, clearing the filters when
+    /// they hide it
+    fn reveal_entry(&mut self, actual_index: usize) {
+        if !self.select_actual_entry(actual_index) {
+            self.clear_filters();
+            self.select_actual_entry(actual_index);
+            self.status_message = Some("Filters cleared to show the entry".to_string());
+        }
+    }
+
+    /// Select an entry by its index in the file. Returns false when the
+    /// entry is hidden by the active filters.
+    fn select_actual_entry(&mut self, actual_index: usize) -> bool {
+        match self.filtered_indices.iter().position(|&i| i == actual_index) {
+            Some(position) => {
+                self.current_entry = position;
+                self.update_list_state();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Digit of the mark set on an entry, if any
+    fn mark_for(&self, entry: &PoEntry) -> Option<usize> {
+        self.marks.iter().position(|mark| {
+            mark.as_ref()
+                .is_some_and(|key| key.msgctxt == entry.msgctxt && key.msgid == entry.msgid)
+        })
+    }
+
+    pub fn open_file_browser(&mut self) {
+        if self.editing || self.search_mode {
+            return;
+        }
+        let dir = match self.po_file.path.as_deref().and_then(Path::parent) {
+            Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+            _ => PathBuf::from("."),
+        };
+        self.overlay = Some(Overlay::FileBrowser(FileBrowser::new(dir)));
+    }
+
+    /// Open `path`, asking what to do with unsaved changes first
+    fn request_open(&mut self, path: PathBuf) {
+        if self.is_modified() {
+            self.overlay = Some(Overlay::UnsavedChanges { path });
+        } else {
+            self.open_path(&path);
+        }
+    }
+
+    /// Read the file again, asking first when that drops unsaved changes
+    pub fn request_reload(&mut self) {
+        if self.is_modified() {
+            self.overlay = Some(Overlay::ConfirmReload);
+        } else {
+            self.reload();
+        }
+    }
+
+    /// Replace the catalog with what is on disk now. The filters, the
+    /// search, metadata mode and marks stay, and the selected entry stays
+    /// selected while it is still there and listed.
+    fn reload(&mut self) {
+        let Some(path) = self.po_file.path.clone() else {
+            self.status_message = Some("Nothing to reload: the file was never saved".to_string());
+            return;
+        };
+        // With --output the catalog is read from its source until the first save
+        let read_from = match self.source {
+            Some(ref source) if !path.exists() => source.clone(),
+            _ => path.clone(),
+        };
+        let po_file = match PoFile::from_file(&read_from) {
+            Ok(mut po_file) => {
+                po_file.path = Some(path.clone());
+                po_file
+            }
+            Err(e) => {
+                self.overlay = Some(Overlay::Message {
+                    title: format!("Could not reload {}", read_from.display()),
+                    text: format!("{:#}", e),
+                });
+                return;
+            }
+        };
+        let selected = self.get_current_entry().map(PoEntry::key);
+        let filters = self.filters.clone();
+        let quick_filter = self.quick_filter.take();
+        let (metadata_mode, metadata_selected) = (self.metadata_mode, self.metadata_selected);
+        let marks = self.marks.clone();
+
+        self.load_file(po_file);
+        self.filters = filters;
+        self.quick_filter = quick_filter;
+        self.search_cursor = self.filters.text.chars().count();
+        self.metadata_mode = metadata_mode;
+        self.metadata_selected = metadata_selected;
+        self.refresh_metadata_keys();
+        self.marks = marks;
+        self.update_filtered_indices();
+        self.current_entry = 0;
+        let actual_index = selected.and_then(|key| self.po_file.entries.iter().position(|entry| entry.key() == key));
+        if !actual_index.is_some_and(|index| self.select_actual_entry(index)) {
+            self.update_list_state();
+        }
+
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        let entries = plural_count(self.po_file.entries.len(), "entry", "entries");
+        self.status_message = Some(format!("Reloaded {} ({})", name, entries));
+        self.report_parse_issues();
+    }
+
+    fn open_path(&mut self, path: &Path) {
+        match PoFile::from_file(path) {
+            Ok(po_file) => {
+                self.source = None;
+                self.read_only = false;
+                self.load_file(po_file);
+                self.status_message = Some(format!("Opened {}", path.display()));
+                self.report_parse_issues();
+            }
+            Err(e) => {
+                self.overlay = Some(Overlay::Message {
+                    title: format!("Could not open {}", path.display()),
+                    text: format!("{:#}", e),
+                });
+            }
+        }
+    }
+
+    /// Edit templates like regular catalogs
+    pub fn with_force_po(mut self, force_po: bool) -> Self {
+        self.force_po = force_po;
+        self.template_mode = !force_po && self.po_file.is_template();
+        self
+    }
+
+    /// The catalog was read from `source` but saves to its own path
+    pub fn with_source(mut self, source: Option<PathBuf>) -> Self {
+        self.source = source;
+        self
+    }
+
+    /// Refuse every change to the open file
+    pub fn with_read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self
+    }
+
+    /// Suggest translations from `memory` for the selected entry
+    pub fn with_memory(mut self, memory: Option<TranslationMemory>) -> Self {
+        if let Some(ref memory) = memory {
+            self.status_message = Some(format!(
+                "Translation memory: {} translations from {}",
+                memory.len(),
+                plural_count(memory.catalogs(), "catalog", "catalogs")
+            ));
+        }
+        self.memory = memory;
+        self
+    }
+
+    /// Look up the suggestions for the selected entry, unless they are for
+    /// its msgid already
+    fn refresh_suggestions(&mut self) {
+        let Some(ref memory) = self.memory else {
+            return;
+        };
+        let msgid = match self.get_current_entry() {
+            Some(entry) if !self.template_mode => entry.msgid.as_str(),
+            _ => "",
+        };
+        if self.suggestions.as_ref().is_some_and(|(looked_up, _)| looked_up == msgid) {
+            return;
+        }
+        let suggestions = if msgid.is_empty() { Vec::new() } else { memory.suggestions(msgid, SUGGESTIONS) };
+        self.suggestions = Some((msgid.to_string(), suggestions));
+    }
+
+    /// The suggestions shown for the selected entry
+    fn current_suggestions(&self) -> &[Suggestion] {
+        self.suggestions.as_ref().map(|(_, suggestions)| suggestions.as_slice()).unwrap_or_default()
+    }
+
+    /// Put suggestion `number` (1-based) from the translation memory into
+    /// the selected entry, marked fuzzy for review. While the translation
+    /// is edited it replaces the edit buffer instead, and applying the
+    /// buffer marks the entry fuzzy.
+    pub fn accept_suggestion(&mut self, number: usize) -> bool {
+        if self.search_mode || self.metadata_mode || self.filtered_indices.is_empty() {
+            return false;
+        }
+        if self.editing && self.edit_field != EditField::Msgstr {
+            self.status_message = Some("Suggestions go into the translation".to_string());
+            return false;
+        }
+        if self.template_mode {
+            self.status_message = Some(TEMPLATE_MESSAGE.to_string());
+            return false;
+        }
+        self.refresh_suggestions();
+        let Some(suggestion) = number.checked_sub(1).and_then(|i| self.current_suggestions().get(i)).cloned() else {
+            self.status_message = Some(match self.memory {
+                Some(_) => format!("No translation memory suggestion {}", number),
+                None => "No translation memory, open the file with --tm".to_string(),
+            });
+            return false;
+        };
+        if self.editing {
+            let form = self.plural_form();
+            self.edit_text = suggestion.forms.get(form).or(suggestion.forms.first()).cloned().unwrap_or_default();
+            self.edit_cursor = self.edit_text.chars().count();
+            self.goal_column = None;
+            self.completion = None;
+            self.suggestion_pending = true;
+            self.status_message = Some(format!("Used the translation from {}; Enter applies it, marked fuzzy", suggestion.origin));
+            return true;
+        }
+        let actual_index = self.filtered_indices[self.current_entry];
+        let entry = &mut self.po_file.entries[actual_index];
+        let before = entry.clone();
+        let form_count = entry.form_count();
+        for (form, text) in suggestion.forms.into_iter().enumerate().take(form_count) {
+            entry.set_form(form, text);
+        }
+        if !entry.is_fuzzy {
+            entry.toggle_fuzzy();
+        }
+        if self.entry_changed(actual_index, &before) {
+            self.touch_revision_date();
+        }
+        self.status_message = Some(format!("Used the translation from {}, marked fuzzy", suggestion.origin));
+        true
+    }
+
+    pub fn start_template_prompt(&mut self) {
+        if !self.template_mode {
+            self.status_message = Some("Only templates (.pot) can be turned into a new .po".to_string());
+            return;
+        }
+        if self.editing || self.search_mode {
+            return;
+        }
+        self.overlay = Some(Overlay::Prompt(Prompt {
+            kind: PromptKind::TemplateLanguage,
+            title: "Create .po from this template for language (e.g. de, pt_BR)".to_string(),
+            input: TextInput::default(),
+        }));
+    }
+
+    /// Write `<language>.po` next to the template and open it
+    fn create_from_template(&mut self, language: &str) {
+        let language = language.trim();
+        if language.is_empty() {
+            return;
+        }
+        let Some(path) = self.po_file.path.as_ref().map(|p| p.with_file_name(format!("{}.po", language))) else {
+            return;
+        };
+        if path.exists() {
+            self.status_message = Some(format!("{} already exists", path.display()));
+            return;
+        }
+
+        let mut po_file = self.po_file.instantiate(&path);
+        po_file.set_header_field("Language".to_string(), language.to_string());
+        match po_file.save() {
+            Ok(()) => {
+                self.request_open(path.clone());
+                if self.overlay.is_none() {
+                    self.status_message = Some(format!("Created {}", path.display()));
+                }
+            }
+            Err(e) => {
+                self.overlay = Some(Overlay::Message {
+                    title: format!("Could not create {}", path.display()),
+                    text: format!("{:#}", e),
+                });
+            }
+        }
+    }
+
+    /// Use `state` for recent files and restore the last view of the open
+    /// file from it
+    pub fn with_state(mut self, state: StateStore) -> Self {
+        self.state = state;
+        self.restore_view();
+        self
+    }
+
+    /// Canonical path of the open file when time spent in it is recorded
+    fn tracked_path(&self) -> Option<PathBuf> {
+        if !self.config.track_time {
+            return None;
+        }
+        self.po_file.path.as_deref()?.canonicalize().ok()
+    }
+
+    /// Time spent in the open file: over all sessions and in this one
+    fn time_in_file(&self) -> Option<(Duration, Duration)> {
+        let path = self.tracked_path()?;
+        Some((self.state.time_in(&path).unwrap_or_default(), self.session.file_time(&path)))
+    }
+
+    /// Start counting the time spent in the open file from zero
+    fn reset_file_time(&mut self) {
+        if let Some(path) = self.tracked_path() {
+            self.state.reset_time(&path);
+            self.session.reset_file_time(&path);
+            self.status_message = Some("Time in file reset".to_string());
+        }
+    }
+
+    /// Remember the open file and write the recent files list
+    pub fn save_state(&mut self) -> Result<()> {
+        self.remember_file();
+        self.state.save()
+    }
+
+    /// Put the open file at the top of the recent files list, with the
+    /// current filter and cursor. Files not on disk yet are skipped.
+    fn remember_file(&mut self) {
+        let Some(path) = self.po_file.path.as_deref().and_then(|p| p.canonicalize().ok()) else {
+            return;
+        };
+        let (total, translated, _) = self.po_file.get_stats();
+        self.state.record(RecentFile {
+            path,
+            last_opened: chrono::Utc::now(),
+            progress: percent(translated, total),
+            filter: self.filters.status,
+            search: self.filters.text.clone(),
+            entry: self.get_current_entry().map(PoEntry::key),
+        });
+    }
+
+    fn restore_view(&mut self) {
+        self.restore_saved_view();
+        self.select_start_entry();
+    }
+
+    fn restore_saved_view(&mut self) {
+        let Some(recent) = self.po_file.path.as_deref().and_then(|p| self.state.get(p)).cloned() else {
+            return;
+        };
+        self.filters.status = recent.filter;
+        self.filters.text = recent.search;
+        self.search_cursor = self.filters.text.chars().count();
+        self.update_filtered_indices();
+        self.current_entry = 0;
+        let actual_index = recent
+            .entry
+            .and_then(|key| self.po_file.entries.iter().position(|entry| entry.key() == key));
+        if let Some(actual_index) = actual_index {
+            if !self.select_actual_entry(actual_index) {
+                self.clear_filters();
+                self.select_actual_entry(actual_index);
+            }
+        }
+        self.update_list_state();
+    }
+
+    /// Select the entry `start_at` asks for among the listed ones
+    fn select_start_entry(&mut self) {
+        let listed = |predicate: fn(&PoEntry) -> bool| {
+            self.filtered_indices.iter().position(|&i| predicate(&self.po_file.entries[i]))
+        };
+        match self.config.start_at {
+            StartAt::LastSession => return,
+            StartAt::First => self.current_entry = 0,
+            StartAt::FirstUntranslated => {
+                self.current_entry = listed(|entry| !entry.is_translated && !entry.is_fuzzy)
+                    .or_else(|| listed(|entry| entry.is_fuzzy))
+                    .unwrap_or(0);
+            }
+        }
+        self.update_list_state();
+    }
+
+    /// List only entries with `status` (`--filter`), then select the start
+    /// entry among them
+    pub fn with_filter(mut self, status: Option<FilterMode>) -> Self {
+        if let Some(status) = status {
+            self.filters.status = status;
+            self.refilter();
+            self.select_start_entry();
+        }
+        self
+    }
+
+    pub fn open_recent_files(&mut self) {
+        if self.editing || self.search_mode {
+            return;
+        }
+        self.overlay = Some(Overlay::Recent { selected: 0 });
+    }
+
+    fn handle_recent_input(&mut self, mut selected: usize, key: KeyEvent) {
+        let count = self.state.recent().len();
+        match key.code {
+            KeyCode::Esc => return,
+            KeyCode::Up | KeyCode::Char('k') => selected = selected.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') if selected + 1 < count => selected += 1,
+            KeyCode::Enter => {
+                if let Some(recent) = self.state.recent().get(selected) {
+                    let path = recent.path.clone();
+                    self.request_open(path);
+                }
+                return;
+            }
+            _ => {}
+        }
+        self.overlay = Some(Overlay::Recent { selected });
+    }
+
+    /// Replace the open catalog. Per-file state is reset, or restored from
+    /// the recent files list; the config and session counters carry over,
+    /// and so do read-only mode and the --output source.
+    fn load_file(&mut self, mut po_file: PoFile) {
+        po_file.no_wrap = !self.config.wrap;
+        po_file.backup = self.config.backup;
+        self.remember_file();
+        self.close_journal();
+        self.saved_entries = by_key(&po_file.entries);
+        self.undo = UndoHistory::default();
+        self.stashed_edits.clear();
+        self.po_file = po_file;
+        self.last_save = Instant::now();
+        self.disk_changed = false;
+        self.words = WordIndex::build(&self.po_file.entries);
+        self.completion = None;
+        self.current_entry = 0;
+        self.editing = false;
+        self.metadata_mode = false;
+        self.metadata_selected = 0;
+        self.refresh_metadata_keys();
+        self.revision_date_set = false;
+        self.filters = FilterSet::default();
+        self.search_cursor = 0;
+        self.quick_filter = None;
+        self.marks = Default::default();
+        self.issue_counts = None;
+        self.msgid_index = None;
+        self.load_glossary();
+        self.template_mode = !self.force_po && self.po_file.is_template();
+        self.msgid_unlocked = self.config.allow_msgid_edit;
+        self.update_filtered_indices();
+        self.update_list_state();
+        self.restore_view();
+        self.open_journal();
+    }
+
+    pub fn with_system_clipboard(mut self) -> Self {
+        self.clipboard = Clipboard::system();
+        self
+    }
+
+    /// Keep a swap file of the unsaved edits to the open file
+    pub fn with_journal(mut self) -> Self {
+        self.open_journal();
+        self
+    }
+
+    /// Ask about a recovery file newer than the open file, then start its
+    /// swap file, or ask about one that is already there
+    fn open_journal(&mut self) {
+        self.recovery_file = None;
+        if self.read_only {
+            return;
+        }
+        let Some(path) = self.po_file.path.clone() else {
+            return;
+        };
+        match Recovery::find(&path) {
+            Some(recovery) => self.overlay = Some(Overlay::RestoreAutosave(recovery)),
+            None => {
+                self.recovery_file = Some(journal::recovery_path(&path));
+                self.open_swap(&path);
+            }
+        }
+    }
+
+    fn open_swap(&mut self, path: &Path) {
+        if !self.config.swap_file {
+            return;
+        }
+        match Swap::find(path) {
+            Some(swap) if swap.running || !swap.changes.is_empty() => self.overlay = Some(Overlay::Recover(swap)),
+            _ => self.start_journal(path),
+        }
+    }
+
+    fn start_journal(&mut self, path: &Path) {
+        match Journal::create(path) {
+            Ok(journal) => self.journal = Some(journal),
+            Err(e) => self.status_message = Some(format!("No swap file: {:#}", e)),
+        }
+    }
+
+    /// Remove the swap file and the recovery file of the open file; its
+    /// edits were saved or discarded
+    pub fn close_journal(&mut self) {
+        self.remove_recovery_file();
+        if let Some(journal) = self.journal.take() {
+            if let Err(e) = journal.remove() {
+                self.status_message = Some(format!("{:#}", e));
+            }
+        }
+    }
+
+    /// Apply the edits of `swap` to the open file as if made again, and
+    /// continue in a new swap file
+    fn recover(&mut self, swap: &Swap) {
+        if let Some(path) = self.po_file.path.clone() {
+            self.start_journal(&path);
+        }
+        let mut missing = 0;
+        for change in &swap.changes {
+            let Some(index) = journal::find_entry(&self.po_file.entries, change) else {
+                missing += 1;
+                continue;
+            };
+            let before = self.po_file.entries[index].clone();
+            change.apply(&mut self.po_file.entries[index]);
+            self.entry_changed(index, &before);
+        }
+        self.update_filtered_indices();
+        self.update_list_state();
+        let recovered = swap.changes.len() - missing;
+        self.status_message = Some(match missing {
+            0 => format!("Recovered {} changes", recovered),
+            _ => format!("Recovered {} changes; {} were to entries no longer in the file", recovered, missing),
+        });
+    }
+
+    /// The swap file belongs to a poterm that is still running. Recovering
+    /// or discarding would start a journal over it, so this session can
+    /// only go on without one, or quit.
+    fn handle_running_swap_input(&mut self, key: KeyEvent) -> Outcome {
+        let Some(Overlay::Recover(swap)) = self.overlay.take() else {
+            return Outcome::Done;
+        };
+        match key.code {
+            KeyCode::Char('o') | KeyCode::Esc => {
+                self.status_message = Some(format!("{} was left alone; this session has no swap file", swap.path.display()));
+                Outcome::Done
+            }
+            KeyCode::Char('q') => Outcome::Quit,
+            _ => {
+                self.overlay = Some(Overlay::Recover(swap));
+                Outcome::Done
+            }
+        }
+    }
+
+    fn handle_recover_input(&mut self, swap: Swap, key: KeyEvent) {
+        match key.code {
+            KeyCode::Char('r') => {
+                self.recover(&swap);
+            }
+            KeyCode::Char('d') => {
+                if let Err(e) = swap.remove() {
+                    self.status_message = Some(format!("{:#}", e));
+                }
+                if let Some(path) = self.po_file.path.clone() {
+                    self.start_journal(&path);
+                }
+            }
+            KeyCode::Esc => {
+                self.status_message = Some(format!("{} was left alone; this session has no swap file", swap.path.display()));
+            }
+            _ => self.overlay = Some(Overlay::Recover(swap)),
+        }
+    }
+
+    fn handle_restore_autosave_input(&mut self, recovery: Recovery, key: KeyEvent) {
+        match key.code {
+            KeyCode::Char('r') => {
+                if self.restore_autosave(&recovery) {
+                    self.recovery_file = Some(recovery.path.clone());
+                    if let Some(path) = self.po_file.path.clone() {
+                        self.replace_swap(&path);
+                    }
+                    return;
+                }
+            }
+            KeyCode::Char('d') => {
+                if let Err(e) = std::fs::remove_file(&recovery.path) {
+                    self.report_error(format!("Failed to remove {}: {}", recovery.path.display(), e));
+                }
+            }
+            KeyCode::Esc => {
+                self.status_message = Some(format!("{} was left alone; this session does not autosave", recovery.path.display()));
+            }
+            _ => {
+                self.overlay = Some(Overlay::RestoreAutosave(recovery));
+                return;
+            }
+        }
+        if key.code != KeyCode::Esc {
+            self.recovery_file = Some(recovery.path.clone());
+        }
+        if let Some(path) = self.po_file.path.clone() {
+            self.open_swap(&path);
+        }
+    }
+
+    /// Start a swap file over one the restored session left, whose edits
+    /// would otherwise be replayed on top of the restored entries. One
+    /// that is still in use is asked about as usual.
+    fn replace_swap(&mut self, path: &Path) {
+        if !self.config.swap_file {
+            return;
+        }
+        match Swap::find(path) {
+            Some(swap) if swap.running => self.overlay = Some(Overlay::Recover(swap)),
+            _ => self.start_journal(path),
+        }
+    }
+
+    /// Replace the entries with those of the recovery file, which is kept
+    /// until the restored entries are saved. Returns whether it was read.
+    fn restore_autosave(&mut self, recovery: &Recovery) -> bool {
+        let restored = std::fs::read_to_string(&recovery.path)
+            .map_err(anyhow::Error::from)
+            .and_then(|content| PoFile::parse(&content));
+        let restored = match restored {
+            Ok(restored) => restored,
+            Err(e) => {
+                self.report_error(format!("Failed to restore {}: {:#}", recovery.path.display(), e));
+                return false;
+            }
+        };
+        self.po_file.header = restored.header;
+        self.po_file.entries = restored.entries;
+        self.po_file.obsolete_entries = restored.obsolete_entries;
+        self.po_file.mark_modified();
+        self.recovery_revision = Some(self.po_file.revision);
+        self.undo = UndoHistory::default();
+        self.words = WordIndex::build(&self.po_file.entries);
+        self.issue_counts = None;
+        self.msgid_index = None;
+        self.refresh_metadata_keys();
+        self.current_entry = self.current_entry.min(self.po_file.entries.len().saturating_sub(1));
+        self.update_filtered_indices();
+        self.update_list_state();
+        self.status_message = Some(format!(
+            "Restored {} autosaved at {}",
+            plural_count(self.po_file.entries.len(), "entry", "entries"),
+            recovery.written.format("%H:%M")
+        ));
+        true
+    }
+
+    /// `action` would change or write the open file
+    fn modifies_file(&self, action: Action) -> bool {
+        match action {
+            Action::Save
+            | Action::SaveAndQuit
+            | Action::SaveCurrentEntry
+            | Action::SaveAs
+            | Action::EditTranslation
+            | Action::DeleteHeaderField
+            | Action::MoveHeaderFieldUp
+            | Action::MoveHeaderFieldDown
+            | Action::SortHeaderFields
+            | Action::ToggleFuzzy
+            | Action::ToggleNeedsWork
+            | Action::MarkDone
+            | Action::Undo
+            | Action::Redo
+            | Action::PipeCommand
+            | Action::MergeTemplate
+            | Action::Import
+            | Action::AcceptSuggestion(_)
+            | Action::Replace => true,
+            // Enter also finishes a search and confirms overlays
+            Action::StartEditing => {
+                !self.search_mode
+                    && !self.is_quick_filter_typing()
+                    && self.overlay.is_none()
+                    && self.selected_group().is_none()
+            }
+            // Fixes and acknowledgements from the QA panel
+            Action::Input(key) => {
+                matches!(self.overlay, Some(Overlay::Qa(_))) && matches!(key.code, KeyCode::Char('f') | KeyCode::Char('a'))
+            }
+            _ => false,
+        }
+    }
+
+    pub fn open_qa_panel(&mut self) {
+        if self.editing || self.search_mode {
+            return;
+        }
+        self.overlay = Some(Overlay::Qa(QaPanel {
+            issues: self.qa_issues(false),
+            selected: 0,
+            show_suppressed: false,
+        }));
+    }
+
+    fn qa_issues(&self, show_suppressed: bool) -> Vec<EntryIssue> {
+        let mut issues = run_checks(&self.po_file, &self.config.style, self.glossary.as_ref());
+        issues.retain(|found| show_suppressed || !found.suppressed);
+        issues
+    }
+
+    /// Check the catalog again, keeping the selection in range
+    fn refresh_qa_panel(&self, panel: &mut QaPanel) {
+        panel.issues = self.qa_issues(panel.show_suppressed);
+        panel.selected = panel.selected.min(panel.issues.len().saturating_sub(1));
+    }
+
+    fn handle_qa_input(&mut self, mut panel: QaPanel, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc | KeyCode::F(8) => return,
+            KeyCode::Up | KeyCode::Char('k') => panel.selected = panel.selected.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') if panel.selected + 1 < panel.issues.len() => {
+                panel.selected += 1;
+            }
+            KeyCode::Char('f') => {
+                let fix = panel
+                    .issues
+                    .get(panel.selected)
+                    .and_then(|found| Some((found.entry, found.issue.fix.clone()?)));
+                match fix {
+                    Some((actual_index, msgstr)) => {
+                        let before = self.po_file.entries[actual_index].clone();
+                        self.po_file.entries[actual_index].set_msgstr(msgstr);
+                        self.entry_changed(actual_index, &before);
+                        self.refilter_issues();
+                        self.refresh_qa_panel(&mut panel);
+                        self.status_message = Some("Fix applied".to_string());
+                    }
+                    None => self.status_message = Some("No automatic fix for this issue".to_string()),
+                }
+            }
+            KeyCode::Char('a') => {
+                if let Some(found) = panel.issues.get(panel.selected).cloned() {
+                    let before = self.po_file.entries[found.entry].clone();
+                    let entry = &mut self.po_file.entries[found.entry];
+                    if found.suppressed {
+                        validate::unsuppress(entry, found.issue.check);
+                        self.status_message = Some(format!("{} no longer acknowledged", found.issue.check));
+                    } else {
+                        validate::suppress(entry, found.issue.check);
+                        self.status_message = Some(format!("{} acknowledged for this entry", found.issue.check));
+                    }
+                    self.entry_changed(found.entry, &before);
+                    self.refresh_qa_panel(&mut panel);
+                }
+            }
+            KeyCode::Char('s') => {
+                panel.show_suppressed = !panel.show_suppressed;
+                self.refresh_qa_panel(&mut panel);
+            }
+            KeyCode::Enter => {
+                if let Some(found) = panel.issues.get(panel.selected) {
+                    let actual_index = found.entry;
+                    if !self.select_actual_entry(actual_index) {
+                        self.clear_filters();
+                        self.select_actual_entry(actual_index);
+                    }
+                    self.metadata_mode = false;
+                    return;
+                }
+            }
+            _ => {}
+        }
+        self.overlay = Some(Overlay::Qa(panel));
+    }
+
+    pub fn open_comment_manager(&mut self, actual_index: usize) {
+        if actual_index < self.po_file.entries.len() {
+            self.overlay = Some(Overlay::Comments(CommentManager {
+                esc_commits: self.config.esc_commits(),
+                ..CommentManager::new(actual_index, self.config.allow_extracted_comment_edit)
+            }));
+        }
+    }
+
+    fn handle_overlay_input(&mut self, key: KeyEvent) {
+        match self.overlay.take() {
+            Some(Overlay::Replace(mut form)) => {
+                let event = form.handle_key(key);
+                self.last_replace = ReplaceForm { error: None, ..form.clone() };
+                let replacer = match event {
+                    ReplaceEvent::None => {
+                        self.overlay = Some(Overlay::Replace(form));
+                        return;
+                    }
+                    ReplaceEvent::Close => return,
+                    _ => form.replacer(),
+                };
+                match (event, replacer) {
+                    (_, Err(e)) => {
+                        form.error = Some(e.to_string());
+                        self.overlay = Some(Overlay::Replace(form));
+                    }
+                    (ReplaceEvent::StepThrough, Ok(replacer)) => self.start_replace_steps(replacer),
+                    (ReplaceEvent::ReplaceInEntry, Ok(replacer)) => {
+                        let positions = self.current_entry..(self.current_entry + 1).min(self.filtered_indices.len());
+                        self.replace_in(&replacer, positions);
+                    }
+                    (_, Ok(replacer)) => self.replace_in(&replacer, 0..self.filtered_indices.len()),
+                }
+            }
+            Some(Overlay::ReplaceMatch(step)) => self.handle_replace_step_input(step, key),
+            Some(Overlay::Comments(mut manager)) => {
+                let Some(entry) = self.po_file.entries.get_mut(manager.entry_index) else {
+                    return;
+                };
+                let before = entry.clone();
+                match manager.handle_key(key, entry) {
+                    CommentEvent::Changed => {
+                        self.entry_changed(manager.entry_index, &before);
+                    }
+                    CommentEvent::Message(message) => self.status_message = Some(message),
+                    CommentEvent::Close => return,
+                    CommentEvent::None => {}
+                }
+                self.overlay = Some(Overlay::Comments(manager));
+            }
+            Some(Overlay::Qa(panel)) => self.handle_qa_input(panel, key),
+            Some(Overlay::Picker { kind, picker }) => self.handle_picker_input(kind, picker, key),
+            Some(Overlay::Prompt(mut prompt)) => match key.code {
+                KeyCode::Esc => {}
+                KeyCode::Enter => self.submit_prompt(prompt.kind, prompt.input.text),
+                // Ctrl+G twice looks up a source location instead
+                KeyCode::Char('g')
+                    if prompt.kind == PromptKind::GoToEntry && key.modifiers.contains(KeyModifiers::CONTROL) =>
+                {
+                    prompt.kind = PromptKind::GoToReference;
+                    prompt.title = "Go to source reference (file or file:line)".to_string();
+                    self.overlay = Some(Overlay::Prompt(prompt));
+                }
+                KeyCode::Tab
+                    if matches!(
+                        prompt.kind,
+                        PromptKind::SaveAs | PromptKind::MergeTemplate | PromptKind::Export | PromptKind::Import
+                    ) =>
+                {
+                    if let Some(path) = browser::complete_path(&prompt.input.text) {
+                        prompt.input = TextInput::new(&path);
+                    }
+                    self.overlay = Some(Overlay::Prompt(prompt));
+                }
+                _ => {
+                    prompt.input.handle_key(key);
+                    self.overlay = Some(Overlay::Prompt(prompt));
+                }
+            },
+            Some(Overlay::FileBrowser(mut browser)) => match browser.handle_key(key) {
+                BrowserEvent::None => self.overlay = Some(Overlay::FileBrowser(browser)),
+                BrowserEvent::Open(path) => self.request_open(path),
+                BrowserEvent::Close => {}
+            },
+            Some(Overlay::UnsavedChanges { path }) => match key.code {
+                KeyCode::Char('s') | KeyCode::Char('y') => {
+                    if self.try_save() {
+                        self.open_path(&path);
+                    }
+                }
+                KeyCode::Char('d') | KeyCode::Char('n') => self.open_path(&path),
+                KeyCode::Esc => {}
+                _ => self.overlay = Some(Overlay::UnsavedChanges { path }),
+            },
+            Some(Overlay::Recent { selected }) => self.handle_recent_input(selected, key),
+            Some(Overlay::ChangedOnDisk { selected }) => self.handle_conflict_input(selected, key),
+            Some(Overlay::Recover(swap)) => self.handle_recover_input(swap, key),
+            Some(Overlay::RestoreAutosave(recovery)) => self.handle_restore_autosave_input(recovery, key),
+            Some(Overlay::DeleteHeaderField { key: field, confirm: None }) => match key.code {
+                KeyCode::Char('y') => self.delete_header_field(&field),
+                KeyCode::Char('n') | KeyCode::Esc => {}
+                _ => self.overlay = Some(Overlay::DeleteHeaderField { key: field, confirm: None }),
+            },
+            Some(Overlay::DeleteHeaderField { key: field, confirm: Some(mut input) }) => match key.code {
+                KeyCode::Esc => {}
+                KeyCode::Enter if input.text.trim() == field => self.delete_header_field(&field),
+                KeyCode::Enter => self.status_message = Some(format!("{} was kept", field)),
+                _ => {
+                    input.handle_key(key);
+                    self.overlay = Some(Overlay::DeleteHeaderField { key: field, confirm: Some(input) });
+                }
+            },
+            Some(Overlay::LanguageDefaults { changes }) => match key.code {
+                KeyCode::Char('y') | KeyCode::Enter => {
+                    for (key, value) in changes {
+                        self.set_header_value(&key, value);
+                    }
+                }
+                KeyCode::Char('n') | KeyCode::Esc => {}
+                _ => self.overlay = Some(Overlay::LanguageDefaults { changes }),
+            },
+            Some(Overlay::ConfirmReload) => match key.code {
+                KeyCode::Char('y') => self.reload(),
+                KeyCode::Char('n') | KeyCode::Esc => {}
+                _ => self.overlay = Some(Overlay::ConfirmReload),
+            },
+            Some(Overlay::UnlockMsgid) => match key.code {
+                KeyCode::Char('y') => {
+                    self.msgid_unlocked = true;
+                    self.start_editing();
+                }
+                KeyCode::Char('n') | KeyCode::Esc => {
+                    self.status_message = Some("msgid stays read-only".to_string());
+                }
+                _ => self.overlay = Some(Overlay::UnlockMsgid),
+            },
+            Some(Overlay::Snippets) if !matches!(key.code, KeyCode::Esc | KeyCode::Enter | KeyCode::F(6) | KeyCode::Char('q')) => {
+                self.overlay = Some(Overlay::Snippets);
+            }
+            Some(Overlay::Preview) if !matches!(key.code, KeyCode::Esc | KeyCode::Enter | KeyCode::F(5) | KeyCode::Char('q')) => {
+                self.overlay = Some(Overlay::Preview);
+            }
+            Some(Overlay::Stats) if key.code == KeyCode::Char('r') => {
+                self.reset_file_time();
+                self.overlay = Some(Overlay::Stats);
+            }
+            Some(Overlay::Stats) if !matches!(key.code, KeyCode::Esc | KeyCode::Enter | KeyCode::F(7) | KeyCode::Char('q')) => {
+                self.overlay = Some(Overlay::Stats);
+            }
+            Some(Overlay::Message { title, text })
+                if !matches!(key.code, KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q')) =>
+            {
+                self.overlay = Some(Overlay::Message { title, text });
+            }
+            _ => {}
+        }
+    }
+
+    /// Apply or discard the edit buffer and go on with `then`, or stay in
+    /// the edit
+    fn handle_uncommitted_edit_input(&mut self, then: Action, key: KeyEvent) -> Result<Outcome> {
+        self.overlay = None;
+        match key.code {
+            KeyCode::Char('a') | KeyCode::Char('y') | KeyCode::Enter => self.stop_editing(),
+            KeyCode::Char('d') | KeyCode::Char('n') => self.cancel_editing(),
+            KeyCode::Esc => return Ok(Outcome::Done),
+            _ => {
+                self.overlay = Some(Overlay::UncommittedEdit { then });
+                return Ok(Outcome::Done);
+            }
+        }
+        self.apply_action(then)
+    }
+
+    fn handle_quit_input(&mut self, selected: usize, key: KeyEvent) -> Result<Outcome> {
+        let choice = match key.code {
+            KeyCode::Left | KeyCode::Up | KeyCode::BackTab => {
+                self.overlay = Some(Overlay::QuitUnsaved { selected: selected.saturating_sub(1) });
+                return Ok(Outcome::Done);
+            }
+            KeyCode::Right | KeyCode::Down | KeyCode::Tab => {
+                self.overlay = Some(Overlay::QuitUnsaved { selected: min(selected + 1, QUIT_CHOICES.len() - 1) });
+                return Ok(Outcome::Done);
+            }
+            KeyCode::Enter => selected,
+            KeyCode::Char('s') => 0,
+            KeyCode::Char('d') => 1,
+            KeyCode::Char('c') | KeyCode::Esc => 2,
+            _ => return Ok(Outcome::Done),
+        };
+        self.overlay = None;
+        match choice {
+            0 if self.try_save() => Ok(Outcome::Quit),
+            1 => Ok(Outcome::Quit),
+            _ => Ok(Outcome::Done),
+        }
+    }
+
+    /// Replace every match in the translations of the listed entries at
+    /// `positions`, as one undo step
+    fn replace_in(&mut self, replacer: &Replacer, positions: Range<usize>) {
+        let (mut matches, mut entries) = (0, 0);
+        self.undo.begin_group();
+        for position in positions {
+            let index = self.filtered_indices[position];
+            let before = self.po_file.entries[index].clone();
+            let count: usize = (0..before.form_count()).map(|form| replacer.count(before.form(form))).sum();
+            if count == 0 {
+                continue;
+            }
+            let entry = &mut self.po_file.entries[index];
+            for form in 0..before.form_count() {
+                entry.set_form(form, replacer.replace_all(before.form(form)));
+            }
+            if self.entry_changed(index, &before) {
+                matches += count;
+                entries += 1;
+            }
+        }
+        self.undo.end_group();
+        self.status_message = Some(match matches {
+            0 => "No matches".to_string(),
+            _ => format!("Replaced {} in {}", plural_count(matches, "match", "matches"), plural_count(entries, "entry", "entries")),
+        });
+        if matches > 0 {
+            self.touch_revision_date();
+        }
+    }
+
+    /// Step through the matches from the selected entry on, wrapping around
+    fn start_replace_steps(&mut self, replacer: Replacer) {
+        let mut step = ReplaceStep {
+            replacer,
+            position: self.current_entry,
+            form: 0,
+            range: 0..0,
+            origin: self.current_entry,
+            wrapped: false,
+            replaced: 0,
+        };
+        if !self.find_replace_match(&mut step, 0) {
+            self.status_message = Some("No matches".to_string());
+            return;
+        }
+        self.undo.begin_group();
+        self.show_replace_match(step);
+    }
+
+    /// Move `step` to the next match, searching its current form from byte
+    /// `start` on. Returns false when all listed entries have been searched.
+    fn find_replace_match(&self, step: &mut ReplaceStep, mut start: usize) -> bool {
+        let len = self.filtered_indices.len();
+        while len > 0 && !(step.wrapped && step.position >= step.origin) {
+            let entry = &self.po_file.entries[self.filtered_indices[step.position]];
+            while step.form < entry.form_count() {
+                let text = entry.form(step.form);
+                if let Some(range) = (start <= text.len()).then(|| step.replacer.find_at(text, start)).flatten() {
+                    step.range = range;
+                    return true;
+                }
+                step.form += 1;
+                start = 0;
+            }
+            step.form = 0;
+            step.position += 1;
+            if step.position == len {
+                step.position = 0;
+                step.wrapped = true;
+            }
+        }
+        false
+    }
+
+    fn show_replace_match(&mut self, step: ReplaceStep) {
+        let index = self.filtered_indices[step.position];
+        self.select_actual_entry(index);
+        self.set_plural_form(step.form);
+        self.overlay = Some(Overlay::ReplaceMatch(step));
+    }
+
+    fn handle_replace_step_input(&mut self, mut step: ReplaceStep, key: KeyEvent) {
+        let replace_rest = match key.code {
+            KeyCode::Char('y') | KeyCode::Enter => false,
+            KeyCode::Char('a') => true,
+            KeyCode::Char('n') | KeyCode::Char(' ') => {
+                let text = self.po_file.entries[self.filtered_indices[step.position]].form(step.form);
+                let next = after_match(text, &step.range);
+                if self.find_replace_match(&mut step, next) {
+                    self.show_replace_match(step);
+                } else {
+                    self.finish_replace_steps(&step);
+                }
+                return;
+            }
+            KeyCode::Char('q') | KeyCode::Esc => {
+                self.finish_replace_steps(&step);
+                return;
+            }
+            _ => {
+                self.overlay = Some(Overlay::ReplaceMatch(step));
+                return;
+            }
+        };
+        loop {
+            let index = self.filtered_indices[step.position];
+            let before = self.po_file.entries[index].clone();
+            let Some((text, end)) = step.replacer.replace_at(before.form(step.form), step.range.start) else {
+                break;
+            };
+            let next = if step.range.is_empty() { after_match(&text, &(end..end)) } else { end };
+            self.po_file.entries[index].set_form(step.form, text);
+            self.entry_changed(index, &before);
+            step.replaced += 1;
+            if !self.find_replace_match(&mut step, next) {
+                self.finish_replace_steps(&step);
+                return;
+            }
+            if !replace_rest {
+                break;
+            }
+        }
+        self.show_replace_match(step);
+    }
+
+    fn finish_replace_steps(&mut self, step: &ReplaceStep) {
+        self.undo.end_group();
+        if step.replaced > 0 {
+            self.touch_revision_date();
+        }
+        self.status_message = Some(format!("Replaced {}", plural_count(step.replaced, "match", "matches")));
+    }
+
+    fn submit_prompt(&mut self, kind: PromptKind, text: String) {
+        match kind {
+            PromptKind::PipeCommand => self.run_pipe_command(&text),
+            PromptKind::TemplateLanguage => self.create_from_template(&text),
+            PromptKind::FlagFilter => {
+                self.filters.flag = Some(text.trim().to_string()).filter(|flag| !flag.is_empty());
+                self.refilter();
+            }
+            PromptKind::SourceFilter => {
+                self.filters.source = Some(text.trim().to_string()).filter(|source| !source.is_empty());
+                self.refilter();
+            }
+            PromptKind::GoToEntry => self.go_to_entry(&text),
+            PromptKind::GoToReference => self.go_to_reference(&text),
+            PromptKind::SaveAs => self.save_as(&text),
+            PromptKind::MergeTemplate => self.merge_template(&text),
+            PromptKind::Export => self.export(&text),
+            PromptKind::Import => self.import(&text),
+        }
+    }
+
+    pub fn start_pipe_prompt(&mut self) {
+        if !self.config.pipe_commands {
+            self.status_message = Some("Shell commands are disabled in the configuration".to_string());
+            return;
+        }
+        if self.search_mode || (!self.editing && self.get_current_entry().is_none()) {
+            return;
+        }
+        if self.template_mode && !self.editing {
+            self.status_message = Some(TEMPLATE_MESSAGE.to_string());
+            return;
+        }
+        let title = if self.editing {
+            "Pipe edit buffer through command"
+        } else {
+            "Pipe msgid through command (output replaces msgstr)"
+        };
+        self.overlay = Some(Overlay::Prompt(Prompt {
+            kind: PromptKind::PipeCommand,
+            title: title.to_string(),
+            input: TextInput::new(&self.last_pipe_command),
+        }));
+    }
+
+    /// Run `command` with the edit buffer (or the msgid when not editing or
+    /// when the buffer is empty) on stdin. While editing its output is
+    /// inserted at the cursor, otherwise it replaces the msgstr.
+    pub fn run_pipe_command(&mut self, command: &str) {
+        let command = command.trim();
+        if command.is_empty() || self.pipe_job.is_some() {
+            return;
+        }
+        self.last_pipe_command = command.to_string();
+
+        let msgid = self.get_current_entry().map(|e| e.msgid.clone());
+        let (input, target) = if self.editing {
+            let input = if self.edit_text.is_empty() {
+                msgid.unwrap_or_default()
+            } else {
+                self.edit_text.clone()
+            };
+            (input, PipeTarget::EditBuffer)
+        } else {
+            match (msgid, self.filtered_indices.get(self.current_entry)) {
+                (Some(msgid), Some(&actual_index)) => (msgid, PipeTarget::Msgstr(actual_index)),
+                _ => return,
+            }
+        };
+
+        let timeout = Duration::from_secs(self.config.pipe_timeout);
+        self.pipe_job = Some((PipeJob::spawn(command, input, timeout), target));
+    }
+
+    /// Periodic housekeeping from the event loop: collects the results of
+    /// background work.
+    pub fn tick(&mut self) {
+        if let Some(ref mut journal) = self.journal {
+            if let Err(e) = journal.sync_if_due(Instant::now()) {
+                self.report_error(format!("{:#}", e));
+            }
+        }
+        self.check_disk_if_due(Instant::now());
+        self.autosave_if_due(Instant::now());
+        self.expire_status(Instant::now());
+        let Some(output) = self.pipe_job.as_ref().and_then(|(job, _)| job.poll()) else {
+            return;
+        };
+        let Some((job, target)) = self.pipe_job.take() else {
+            return;
+        };
+
+        match output {
+            PipeOutput::Success(mut text) => {
+                if text.ends_with('\n') {
+                    text.pop();
+                }
+                match target {
+                    PipeTarget::EditBuffer if self.editing => self.insert_text_at_cursor(&text),
+                    PipeTarget::EditBuffer => {}
+                    PipeTarget::Msgstr(actual_index) => {
+                        if let Some(entry) = self.po_file.entries.get_mut(actual_index) {
+                            if entry.msgstr != text {
+                                let before = entry.clone();
+                                entry.set_msgstr(text);
+                                self.entry_changed(actual_index, &before);
+                            }
+                        }
+                    }
+                }
+                self.status_message = Some(format!("Applied output of `{}`", job.command));
+            }
+            PipeOutput::Failed { code, stderr } => {
+                let title = match code {
+                    Some(code) => format!("`{}` failed with exit code {}", job.command, code),
+                    None => format!("`{}` was terminated", job.command),
+                };
+                let text = if stderr.trim().is_empty() {
+                    "(no error output)".to_string()
+                } else {
+                    stderr
+                };
+                self.overlay = Some(Overlay::Message { title, text });
+            }
+            PipeOutput::TimedOut => {
+                self.status_message = Some(format!(
+                    "`{}` timed out after {}s, nothing changed",
+                    job.command, self.config.pipe_timeout
+                ));
+            }
+            PipeOutput::Cancelled => {
+                self.status_message = Some(format!("`{}` cancelled", job.command));
+            }
+            PipeOutput::Error(message) => {
+                self.overlay = Some(Overlay::Message {
+                    title: format!("Could not run `{}`", job.command),
+                    text: message,
+                });
+            }
+        }
+    }
+
+    pub fn toggle_macro_recording(&mut self) {
+        match self.macro_recording.take() {
+            Some(actions) => {
+                self.status_message = Some(format!("Recorded macro with {} actions", actions.len()));
+                self.macro_register = actions;
+            }
+            None => {
+                self.macro_recording = Some(Vec::new());
+            }
+        }
+    }
+
+    pub fn is_recording_macro(&self) -> bool {
+        self.macro_recording.is_some()
+    }
+
+    /// Replay the recorded macro `times` times, stopping at the first action
+    /// that fails.
+    pub fn play_macro(&mut self, times: usize) -> Result<Outcome> {
+        if self.macro_recording.is_some() {
+            self.status_message = Some("Cannot play a macro while recording".to_string());
+            return Ok(Outcome::Blocked);
+        }
+        if self.macro_register.is_empty() {
+            self.status_message = Some("No macro recorded (press q to record)".to_string());
+            return Ok(Outcome::Blocked);
+        }
+
+        let steps = self.macro_register.clone();
+        for run in 1..=times {
+            for (step, &action) in steps.iter().enumerate() {
+                let failure = match self.apply_action(action) {
+                    Ok(Outcome::Done) => continue,
+                    Ok(_) => "no effect".to_string(),
+                    Err(e) => e.to_string(),
+                };
+                self.status_message = Some(format!(
+                    "Macro stopped at step {} ({:?}) of run {}/{}: {}",
+                    step + 1,
+                    action,
+                    run,
+                    times,
+                    failure
+                ));
+                return Ok(Outcome::Blocked);
+            }
+        }
+
+        self.status_message = Some(if times == 1 {
+            "Macro played".to_string()
+        } else {
+            format!("Macro played {} times", times)
+        });
+        Ok(Outcome::Done)
+    }
+}
+
+/// Copies of `entries` by key
+fn by_key(entries: &[PoEntry]) -> HashMap<EntryKey, PoEntry> {
+    entries.iter().map(|entry| (entry.key(), entry.clone())).collect()
+}
+
+/// Fields of `header` in file order, followed by the standard fields that
+/// are not set
+fn metadata_keys(header: &IndexMap<String, String>) -> Vec<String> {
+    let unset = CANONICAL_HEADER_KEYS
+        .iter()
+        .filter(|key| !header.contains_key(**key))
+        .map(|key| key.to_string());
+    header.keys().cloned().chain(unset).collect()
+}
+
+/// "1 match", "2 matches"
+fn plural_count(count: usize, one: &str, many: &str) -> String {
+    format!("{} {}", format_count(count), if count == 1 { one } else { many })
+}
+
+/// Where to look for the next match after `range` in `text`: its end, or one
+/// character on for an empty match so the search moves forward
+fn after_match(text: &str, range: &Range<usize>) -> usize {
+    if !range.is_empty() {
+        return range.end;
+    }
+    text[range.end..].chars().next().map_or(range.end + 1, |c| range.end + c.len_utf8())
+}
+
+/// Actions that end the current edit without going through Enter or Esc
+fn leaves_edit(action: Action) -> bool {
+    matches!(
+        action,
+        Action::Quit
+            | Action::SaveAndQuit
+            | Action::OpenFile
+            | Action::RecentFiles
+            | Action::Reload
+            | Action::CreateFromTemplate
+            | Action::MergeTemplate
+            | Action::CompileMo
+            | Action::Export
+            | Action::Import
+            | Action::ToggleMetadataMode
+    )
+}
+
+pub fn draw(f: &mut Frame, app: &mut App) {
+    if f.area().width < MIN_WIDTH || f.area().height < MIN_HEIGHT {
+        draw_too_small(f);
+        return;
+    }
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3), // Header
+            Constraint::Min(0),    // Main content
+            Constraint::Length(3), // Footer
+        ])
+        .split(f.area());
+
+    // Draw header
+    draw_header(f, chunks[0], app);
+    app.refresh_suggestions();
+
+    // Draw main content based on mode
+    if app.metadata_mode {
+        draw_metadata_panel(f, chunks[1], app);
+    } else {
+        let main_chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([
+                Constraint::Percentage(ENTRY_LIST_WIDTH_PERCENT), 
+                Constraint::Percentage(ENTRY_DETAILS_WIDTH_PERCENT)
+            ])
+            .split(chunks[1]);
+
+        draw_entry_list(f, main_chunks[0], app);
+        app.refresh_msgid_index();
+        draw_entry_details(f, main_chunks[1], app);
+    }
+
+    // Draw footer
+    draw_footer(f, chunks[2], app);
+
+    // Draw search overlay
+    if app.search_mode {
+        draw_search_overlay(f, app);
+    }
+
+    match app.overlay {
+        Some(Overlay::Comments(ref manager)) => draw_comment_manager(f, app, manager),
+        Some(Overlay::Prompt(ref prompt)) => draw_prompt(f, prompt),
+        Some(Overlay::Picker { kind, ref picker }) => draw_picker(f, kind, picker),
+        Some(Overlay::Qa(ref panel)) => draw_qa_panel(f, app, panel),
+        Some(Overlay::Stats) => draw_stats(f, app),
+        Some(Overlay::Snippets) => draw_snippets(f, app),
+        Some(Overlay::Preview) => draw_preview(f, app),
+        Some(Overlay::FileBrowser(ref browser)) => draw_file_browser(f, browser),
+        Some(Overlay::UnsavedChanges { ref path }) => draw_unsaved_changes(f, app, path),
+        Some(Overlay::Recover(ref swap)) => draw_recover(f, swap),
+        Some(Overlay::RestoreAutosave(ref recovery)) => draw_restore_autosave(f, recovery),
+        Some(Overlay::Recent { selected }) => draw_recent_files(f, app, selected),
+        Some(Overlay::UnlockMsgid) => draw_unlock_msgid(f),
+        Some(Overlay::ConfirmReload) => draw_confirm_reload(f),
+        Some(Overlay::LanguageDefaults { ref changes }) => draw_language_defaults(f, changes),
+        Some(Overlay::DeleteHeaderField { ref key, ref confirm }) => draw_delete_header_field(f, key, confirm.as_ref()),
+        Some(Overlay::UncommittedEdit { then }) => draw_uncommitted_edit(f, then),
+        Some(Overlay::QuitUnsaved { selected }) => draw_quit_unsaved(f, app, selected),
+        Some(Overlay::ChangedOnDisk { selected }) => draw_changed_on_disk(f, app, selected),
+        Some(Overlay::Replace(ref form)) => draw_replace_form(f, app, form),
+        Some(Overlay::ReplaceMatch(ref step)) => draw_replace_match(f, app, step),
+        Some(Overlay::Message { ref title, ref text }) => draw_message(f, title, text),
+        None => {}
+    }
+
+    // Draw help overlay
+    if app.help_visible {
+        draw_help_overlay(f, app);
+    }
+}
+
+/// Drawn instead of the interface until the terminal is resized
+fn draw_too_small(f: &mut Frame) {
+    let text = format!("Terminal too small (need at least {}x{})", MIN_WIDTH, MIN_HEIGHT);
+    f.render_widget(
+        Paragraph::new(text)
+            .style(Style::default().fg(Color::Yellow))
+            .alignment(Alignment::Center)
+            .wrap(Wrap { trim: true }),
+        f.area(),
+    );
+}
+
+fn draw_header(f: &mut Frame, area: Rect, app: &mut App) {
+    let (total, translated, fuzzy) = app.po_file.get_stats();
+    let untranslated = total - translated - fuzzy;
+    let words = app.po_file.word_stats();
+
+    let progress = match app.config.progress {
+        ProgressMetric::Entries => format!("{:.1}%", percent(translated, total)),
+        ProgressMetric::Words => format!("{:.1}% of words", percent(words.translated, words.total)),
+    };
+
+    let mut title = if let Some(ref path) = app.po_file.path {
+        let mut name = path.file_name().unwrap_or_default().to_string_lossy().into_owned();
+        if let Some(ref source) = app.source {
+            name = format!("{} → {}", source.file_name().unwrap_or_default().to_string_lossy(), name);
+        }
+        format!("Poterm - {} {}", name, if app.po_file.is_modified() { "*" } else { "" })
+    } else {
+        "Poterm - New File".to_string()
+    };
+    let mut border = Style::default().fg(Color::Cyan);
+    let parse_issues = app.po_file.parse_issues().len();
+    if parse_issues > 0 {
+        title.push_str(&format!(" [{} - F8] ", plural_count(parse_issues, "parse warning", "parse warnings")));
+        border = Style::default().fg(Color::Yellow);
+    }
+    if app.disk_changed {
+        title.push_str(" [CHANGED ON DISK] ");
+        border = Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD);
+    }
+    if app.read_only {
+        title.push_str(" [READ-ONLY] ");
+        border = Style::default().fg(Color::Red).add_modifier(Modifier::BOLD);
+    } else if app.template_mode {
+        title.push_str(" [TEMPLATE - Ctrl+N: create a .po for a language] ");
+        border = Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD);
+    }
+
+    let mut stats = format!(
+        "Total: {} | Translated: {} ({}) | Fuzzy: {} | Untranslated: {}",
+        total, translated, progress, fuzzy, untranslated
+    );
+    let issues: usize = app.issue_counts().iter().sum();
+    if issues > 0 {
+        stats.push_str(&format!(" | Issues: {}", issues));
+    }
+
+    // Remaining work and catalog identity, most important first; pieces are
+    // dropped from the end when the terminal is too narrow
+    let revision = match app.po_file.revision_date() {
+        Some(date) => format!("updated {}", relative_time(date.with_timezone(&chrono::Utc), chrono::Utc::now())),
+        None => "updated ?".to_string(),
+    };
+    let mut identity = vec![
+        remaining_summary(&words),
+        format!("Language: {}", app.po_file.language().unwrap_or("?")),
+        match app.po_file.plural_count() {
+            Some(n) => format!("{} plural forms", n),
+            None => "? plural forms".to_string(),
+        },
+        revision,
+    ];
+    let available = area.width.saturating_sub(2) as usize;
+    let mut text = stats.clone();
+    while !identity.is_empty() {
+        let candidate = format!("{} | {}", stats, identity.join(" | "));
+        if candidate.width() <= available {
+            text = candidate;
+            break;
+        }
+        identity.pop();
+    }
+
+    let block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_style(border);
+
+    let paragraph = Paragraph::new(text)
+        .block(block)
+        .alignment(Alignment::Center)
+        .style(Style::default().fg(Color::White));
+
+    f.render_widget(paragraph, area);
+}
+
+/// "3 days ago" style description of how long ago `then` was
+pub(crate) fn relative_time(then: chrono::DateTime<chrono::Utc>, now: chrono::DateTime<chrono::Utc>) -> String {
+    let elapsed = now.signed_duration_since(then);
+    let plural = |n: i64, unit: &str| format!("{} {}{} ago", n, unit, if n == 1 { "" } else { "s" });
+
+    if elapsed.num_minutes() < 1 {
+        "just now".to_string()
+    } else if elapsed.num_hours() < 1 {
+        plural(elapsed.num_minutes(), "minute")
+    } else if elapsed.num_days() < 1 {
+        plural(elapsed.num_hours(), "hour")
+    } else if elapsed.num_days() == 1 {
+        "yesterday".to_string()
+    } else if elapsed.num_days() < 31 {
+        plural(elapsed.num_days(), "day")
+    } else if elapsed.num_days() < 365 {
+        plural(elapsed.num_days() / 30, "month")
+    } else {
+        plural(elapsed.num_days() / 365, "year")
+    }
+}
+
+fn draw_entry_list(f: &mut Frame, area: Rect, app: &mut App) {
+    // Borders and the highlight symbol take four cells, entries in the tree
+    // view are indented by two more
+    let indent = if app.tree.is_some() { "  " } else { "" };
+    let width = area.width.saturating_sub(4 + indent.len() as u16);
+    let reference_width = if app.list_columns.reference {
+        app.filtered_indices
+            .iter()
+            .filter_map(|&i| app.po_file.entries[i].first_reference())
+            .map(|reference| reference_label(reference).width() as u16)
+            .max()
+            .unwrap_or(0)
+    } else {
+        0
+    };
+    // The issues filter shows how many each listed entry has
+    let columns = ListConfig {
+        issues: app.list_columns.issues || app.filters.status == FilterMode::Issues,
+        ..app.list_columns.clone()
+    };
+    let layout = ColumnLayout::new(&columns, width, app.po_file.entries.len(), reference_width);
+    let issue_counts = if layout.has(Column::Issues) {
+        app.issue_counts().to_vec()
+    } else {
+        Vec::new()
+    };
+
+    let entry_item = |position: usize| {
+        let actual_index = app.filtered_indices[position];
+        let entry = &app.po_file.entries[actual_index];
+        let cells = layout.columns.iter().flat_map(|&(column, width)| match column {
+            Column::Status => {
+                let (status_char, color) = if entry.is_fuzzy {
+                    ("~", Color::Yellow)
+                } else if app.needs_work(entry) {
+                    ("!", Color::LightBlue)
+                } else if entry.is_translated {
+                    ("✓", Color::Green)
+                } else {
+                    ("○", Color::Red)
+                };
+                vec![Span::styled(format!("{} ", status_char), Style::default().fg(color))]
+            }
+            Column::Index => {
+                let number = match app.list_columns.index {
+                    IndexMode::Filtered => position + 1,
+                    _ => actual_index + 1,
+                };
+                vec![Span::raw(format!("{:>1$}", number, width as usize - 1))]
+            }
+            Column::Mark => {
+                let mark = match app.mark_for(entry) {
+                    Some(digit) => SUPERSCRIPT_DIGITS[digit],
+                    None => ' ',
+                };
+                vec![Span::styled(mark.to_string(), Style::default().fg(Color::Magenta))]
+            }
+            Column::Issues => match issue_counts.get(actual_index) {
+                Some(&count) if count > 0 => {
+                    vec![Span::styled(format!("{:>2} ", count.min(99)), Style::default().fg(Color::Red))]
+                }
+                _ => vec![Span::raw("   ")],
+            },
+            Column::Modified => {
+                let marker = if app.is_entry_changed(actual_index) { "● " } else { "  " };
+                vec![Span::styled(marker, Style::default().fg(Color::Cyan))]
+            }
+            Column::Preview => {
+                let cell = PreviewCell::new(
+                    entry.msgctxt.as_deref(),
+                    &entry.msgid,
+                    &entry.flags,
+                    app.list_columns.badges,
+                    width,
+                );
+                let mut spans = vec![
+                    Span::styled(cell.context.clone(), Style::default().fg(Color::DarkGray)),
+                    Span::raw(cell.msgid.clone()),
+                    Span::styled(cell.badges.clone(), Style::default().fg(Color::Cyan)),
+                ];
+                if layout.has(Column::Reference) {
+                    // Pushes the reference to the right edge
+                    spans.push(Span::raw(" ".repeat((width as usize).saturating_sub(cell.width()))));
+                }
+                spans
+            }
+            Column::Reference => {
+                let label = entry.first_reference().map_or(String::new(), |reference| fit_reference(reference, width - 1));
+                let padding = " ".repeat((width as usize).saturating_sub(label.width()));
+                vec![Span::styled(padding + &label, Style::default().fg(Color::DarkGray))]
+            }
+        });
+
+        let mut spans = vec![Span::raw(indent)];
+        spans.extend(cells);
+        ListItem::new(Line::from(spans))
+    };
+    let items: Vec<ListItem> = if app.tree.is_some() {
+        app.tree_rows()
+            .into_iter()
+            .map(|row| match row {
+                TreeRow::Group { file, translated, total, expanded, .. } => {
+                    let arrow = if expanded { "▾" } else { "▸" };
+                    let counts = format!(" {}/{} ({:.0}%)", translated, total, percent(translated, total));
+                    let name_width = width.saturating_sub(counts.width() as u16 + 2);
+                    ListItem::new(Line::from(vec![
+                        Span::styled(
+                            format!("{} {}", arrow, truncate_to_width(&file, name_width)),
+                            Style::default().add_modifier(Modifier::BOLD),
+                        ),
+                        Span::styled(counts, Style::default().fg(Color::DarkGray)),
+                    ]))
+                }
+                TreeRow::Entry(position) => entry_item(position),
+            })
+            .collect()
+    } else {
+        (0..app.filtered_indices.len()).map(entry_item).collect()
+    };
+
+    // e.g. `Entries [Untranslated · flag:c-format · /export/]`
+    let mut title = vec![Span::raw(if app.tree.is_some() { "Entries by file [" } else { "Entries [" })];
+    let labels = app.filters.labels();
+    let typing = app.quick_filter.as_ref().filter(|filter| filter.typing);
+    if labels.is_empty() && app.filters.text.is_empty() && typing.is_none() {
+        title.push(Span::raw("All"));
+    }
+    title.push(Span::raw(labels.join(" · ")));
+    if !app.filters.text.is_empty() || typing.is_some() {
+        if !labels.is_empty() {
+            title.push(Span::raw(" · "));
+        }
+        title.push(Span::raw("/"));
+        match typing {
+            Some(filter) => title.extend(text_input_line(&filter.input, Style::default().fg(Color::Yellow)).spans),
+            None => title.push(Span::styled(app.filters.text.as_str(), Style::default().fg(Color::Yellow))),
+        }
+        title.push(Span::raw("/"));
+    }
+    title.push(Span::raw("]"));
+    let (shown, total) = (app.filtered_indices.len(), app.po_file.entries.len());
+    if shown != total {
+        title.push(Span::styled(
+            format!(" showing {} of {}", format_count(shown), format_count(total)),
+            Style::default().fg(Color::Yellow),
+        ));
+    }
+    // Keep rows around the selection in view; the list scrolls only as far
+    // as the offset set here
+    let height = area.height.saturating_sub(2) as usize;
+    if let Some(selected) = app.list_state.selected() {
+        let offset = scroll_offset(
+            app.list_state.offset(),
+            selected,
+            items.len(),
+            height,
+            app.list_columns.scroll_margin,
+            app.list_columns.center_selection,
+        );
+        *app.list_state.offset_mut() = offset;
+        if items.len() > height {
+            title.push(Span::styled(
+                format!(" {}%", (selected + 1) * 100 / items.len()),
+                Style::default().fg(Color::DarkGray),
+            ));
+        }
+    }
+    // Numbers are ambiguous without saying which numbering they are
+    if layout.has(Column::Index) {
+        let numbering = match app.list_columns.index {
+            IndexMode::Filtered => " #filter",
+            _ => " #file",
+        };
+        title.push(Span::styled(numbering, Style::default().fg(Color::DarkGray)));
+    }
+    let block = Block::default()
+        .title(Line::from(title))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Blue));
+
+    // An empty list looks broken; say why it is empty
+    if shown == 0 && total > 0 {
+        let notice = match app.filters.status {
+            _ if !app.filters.text.is_empty() || app.filters.labels().len() > 1 => "No entries match the filters",
+            FilterMode::Untranslated => "No untranslated entries left 🎉",
+            FilterMode::Fuzzy => "No fuzzy entries left 🎉",
+            FilterMode::NeedsWork => "No entries need review 🎉",
+            FilterMode::Issues => "No issues left 🎉",
+            FilterMode::All => "No entries match the filters",
+        };
+        let paragraph = Paragraph::new(Line::styled(notice, Style::default().fg(Color::Green)))
+            .block(block)
+            .wrap(Wrap { trim: true });
+        f.render_widget(paragraph, area);
+        return;
+    }
+
+    let list = List::new(items)
+        .block(block)
+        .highlight_style(Style::default().bg(Color::DarkGray).add_modifier(Modifier::BOLD))
+        .highlight_symbol("► ");
+
+    f.render_stateful_widget(list, area, &mut app.list_state);
+}
+
+/// First list row to draw so that `margin` rows stay visible above and
+/// below `selected` (fewer at the ends of the list), or so that it is in
+/// the middle with `center`. The offset moves as little as needed.
+fn scroll_offset(offset: usize, selected: usize, rows: usize, height: usize, margin: usize, center: bool) -> usize {
+    let max_offset = rows.saturating_sub(height);
+    if height == 0 {
+        return offset.min(max_offset);
+    }
+    let offset = if center {
+        selected.saturating_sub(height / 2)
+    } else {
+        let margin = margin.min(height.saturating_sub(1) / 2);
+        if selected < offset + margin {
+            selected.saturating_sub(margin)
+        } else if selected + margin >= offset + height {
+            selected + margin + 1 - height
+        } else {
+            offset
+        }
+    };
+    offset.min(max_offset)
+}
+
+fn draw_entry_details(f: &mut Frame, area: Rect, app: &App) {
+    if let Some(entry) = app.get_current_entry() {
+        let terms = app.glossary.as_ref().map(|glossary| glossary.terms_in(&entry.msgid)).unwrap_or_default();
+        let glossary_height = if terms.is_empty() { 0 } else { min(terms.len(), 4) as u16 + 2 };
+        let suggestions = app.current_suggestions();
+        let memory_height = if suggestions.is_empty() { 0 } else { suggestions.len() as u16 + 2 };
+        let mut issues = app.entry_issues(entry);
+        issues.sort_by_key(|issue| Reverse(issue.severity));
+        let form = app.plural_form();
+        // The source text a fuzzy translation was made for, left by msgmerge
+        let previous = match (&entry.previous_msgid, &entry.previous_msgid_plural) {
+            (_, Some(plural)) if form > 0 => Some(plural),
+            (previous, _) => previous.as_ref(),
+        }
+        .filter(|_| entry.is_fuzzy);
+        let previous_height = if previous.is_some() { 4 } else { 0 };
+        app.edit_width.set(area.width.saturating_sub(2));
+        let query = app.find_query();
+        let comments_query = query.as_ref().filter(|_| app.search_options.context_and_comments);
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(5),  // Msgid
+                Constraint::Length(previous_height),
+                Constraint::Length(5),  // Msgstr
+                Constraint::Min(3),     // Comments
+                Constraint::Length(glossary_height),
+                Constraint::Length(memory_height),
+                Constraint::Length(3 + min(issues.len(), 4) as u16), // References, flags and issues
+            ])
+            .split(area);
+
+        // Draw msgid, or msgid_plural next to the other plural forms
+        let (msgid, msgid_title) = match entry.msgid_plural {
+            Some(ref plural) if form > 0 => (plural, Line::from("Original Text (msgid_plural)")),
+            _ => (&entry.msgid, Line::from("Original Text (msgid)")),
+        };
+        let msgid_title = if app.msgid_unlocked {
+            Line::styled(
+                "Original Text (msgid) - UNLOCKED, edits orphan the translation at the next merge",
+                Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+            )
+        } else {
+            msgid_title
+        };
+        draw_text_field(
+            f,
+            chunks[0],
+            msgid_title,
+            msgid,
+            app.edit_field == EditField::Msgid,
+            app.editing && app.edit_field == EditField::Msgid,
+            &app.edit_text,
+            app.edit_cursor,
+            None,
+            query.as_ref(),
+        );
+
+        if let Some(previous) = previous {
+            let block = Block::default()
+                .title("Previous Original Text (#| msgid)")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::DarkGray));
+            let paragraph = Paragraph::new(previous.as_str())
+                .style(Style::default().fg(Color::Gray))
+                .block(block)
+                .wrap(Wrap { trim: false });
+            f.render_widget(paragraph, chunks[1]);
+        }
+
+        // Draw msgstr
+        draw_text_field(
+            f,
+            chunks[2],
+            Line::from(app.translation_title(entry, form)),
+            entry.form(form),
+            app.edit_field == EditField::Msgstr,
+            app.editing && app.edit_field == EditField::Msgstr,
+            &app.edit_text,
+            app.edit_cursor,
+            app.bidi_base(),
+            query.as_ref(),
+        );
+
+        // Draw comments
+        let comments_text = entry
+            .comments
+            .iter()
+            .cloned()
+            .chain(entry.extracted_comments.iter().map(|c| format!("#. {}", c)))
+            .collect::<Vec<_>>()
+            .join("\n");
+        draw_text_field(
+            f,
+            chunks[3],
+            Line::from("Comments"),
+            &comments_text,
+            app.edit_field == EditField::Comments,
+            app.editing && app.edit_field == EditField::Comments,
+            &app.edit_text,
+            app.edit_cursor,
+            None,
+            comments_query,
+        );
+
+        if !terms.is_empty() {
+            let lines: Vec<Line> = terms
+                .iter()
+                .map(|term| {
+                    Line::from(vec![
+                        Span::styled(format!("{} ", term.source), Style::default().fg(Color::Cyan)),
+                        Span::raw(format!("→ {}", term.translation)),
+                        Span::styled(
+                            if term.forbidden.is_empty() { String::new() } else { format!("  (not {})", term.forbidden.join(", ")) },
+                            Style::default().fg(Color::DarkGray),
+                        ),
+                    ])
+                })
+                .collect();
+            let block = Block::default().title("Glossary").borders(Borders::ALL);
+            f.render_widget(Paragraph::new(lines).block(block), chunks[4]);
+        }
+
+        if !suggestions.is_empty() {
+            let lines: Vec<Line> = suggestions
+                .iter()
+                .enumerate()
+                .map(|(i, suggestion)| {
+                    let color = if suggestion.similarity >= 1.0 { Color::Green } else { Color::Yellow };
+                    let text = suggestion.forms.get(form).or(suggestion.forms.first()).map_or("", String::as_str);
+                    Line::from(vec![
+                        Span::styled(format!("Alt+{} ", i + 1), Style::default().fg(Color::Cyan)),
+                        Span::styled(format!("{:>3.0}% ", suggestion.similarity * 100.0), Style::default().fg(color)),
+                        Span::raw(text.replace('\n', "⏎")),
+                        Span::styled(format!("  ({})", suggestion.origin), Style::default().fg(Color::DarkGray)),
+                    ])
+                })
+                .collect();
+            let block = Block::default().title("Translation Memory").borders(Borders::ALL);
+            f.render_widget(Paragraph::new(lines).block(block), chunks[5]);
+        }
+
+        // Draw issues, references and flags
+        let mut info_lines = Vec::new();
+        for issue in &issues {
+            let color = match issue.severity {
+                Severity::Error => Color::Red,
+                Severity::Warning => Color::Yellow,
+                Severity::Info => Color::Gray,
+            };
+            info_lines.push(Line::styled(
+                format!("{}: {} [{}]", issue.severity, issue.message, issue.check),
+                Style::default().fg(color),
+            ));
+        }
+        if !entry.references.is_empty() {
+            info_lines.push(Line::from(vec![
+                Span::styled("References: ", Style::default().fg(Color::Cyan)),
+                Span::raw(entry.references.join(", ")),
+            ]));
+        }
+        if !entry.flags.is_empty() {
+            info_lines.push(Line::from(vec![
+                Span::styled("Flags: ", Style::default().fg(Color::Yellow)),
+                Span::raw(entry.flags.join(", ")),
+            ]));
+        }
+        let inconsistent = app.inconsistent_translations().len();
+        if inconsistent > 0 {
+            info_lines.push(Line::styled(
+                format!(
+                    "Same msgid translated differently in {} (Alt+N: next)",
+                    consistency::other_entries(inconsistent)
+                ),
+                Style::default().fg(Color::LightRed),
+            ));
+        }
+
+        let block = Block::default()
+            .title("Information")
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Magenta));
+
+        let paragraph = Paragraph::new(info_lines)
+            .block(block)
+            .wrap(Wrap { trim: true });
+
+        f.render_widget(paragraph, chunks[6]);
+
+        // Drawn last so it covers the fields below the msgstr
+        if let Some(ref completion) = app.completion {
+            draw_completion(f, chunks[2], app, completion);
+        }
+    } else {
+        let block = Block::default()
+            .title("Entry Details")
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Gray));
+
+        let paragraph = Paragraph::new("No entry selected")
+            .block(block)
+            .alignment(Alignment::Center)
+            .style(Style::default().fg(Color::DarkGray));
+
+        f.render_widget(paragraph, area);
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn draw_text_field(
+    f: &mut Frame,
+    area: Rect,
+    mut title: Line,
+    text: &str,
+    is_selected: bool,
+    is_editing: bool,
+    edit_text: &str,
+    cursor_pos: usize,
+    bidi: Option<bidi::Direction>,
+    highlight: Option<&TextQuery>,
+) {
+    let border_color = if is_editing {
+        Color::Green
+    } else if is_selected {
+        Color::Yellow
+    } else {
+        Color::White
+    };
+
+    let display_text = if is_editing { edit_text } else { text };
+
+    if is_editing {
+        title.push_span(" (editing)");
+    }
+    let block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(border_color));
+
+    if is_editing {
+        let inner_area = block.inner(area);
+        f.render_widget(block, area);
+        draw_edit_buffer(f, inner_area, edit_text, cursor_pos, bidi);
+        return;
+    }
+
+    let inner_area = block.inner(area);
+    f.render_widget(block, area);
+    let rows = wrap::wrap(display_text, inner_area.width);
+    let base = display_base(display_text, bidi);
+    let mut lines = row_lines(&rows, base);
+    // Rows in display order no longer line up with the byte offsets
+    if let (Some(query), None) = (highlight, base) {
+        let ranges = query.find_ranges(display_text);
+        lines = lines
+            .into_iter()
+            .zip(&rows)
+            .map(|(line, row)| highlight_line(line, App::char_to_byte_index(display_text, row.start), &ranges))
+            .collect();
+    }
+    f.render_widget(Paragraph::new(lines).style(Style::default().fg(Color::White)), inner_area);
+}
+
+/// `line` of the row starting at byte `start` of its text, with the bytes
+/// in `ranges` highlighted
+fn highlight_line<'a>(line: Line<'a>, start: usize, ranges: &[Range<usize>]) -> Line<'a> {
+    let matched = Style::default().fg(Color::Black).bg(Color::Yellow);
+    let mut spans = Vec::new();
+    let mut offset = start;
+    for span in line.spans {
+        let end = offset + span.content.len();
+        let cuts = ranges.iter().flat_map(|range| [range.start, range.end]).filter(|&cut| cut > offset && cut < end);
+        let mut from = offset;
+        for cut in cuts.chain([end]) {
+            let style = if ranges.iter().any(|range| range.contains(&from)) { span.style.patch(matched) } else { span.style };
+            spans.push(Span::styled(span.content[from - offset..cut - offset].to_string(), style));
+            from = cut;
+        }
+        offset = end;
+    }
+    Line::from(spans)
+}
+
+/// Draw an edit buffer into `area`, scrolled so the cursor is visible. The
+/// cursor is the cell (or both cells of a wide character) under it in
+/// inverse video. With a `bidi` paragraph direction, rows containing
+/// right-to-left text are drawn in display order.
+fn draw_edit_buffer(f: &mut Frame, area: Rect, text: &str, cursor: usize, bidi: Option<bidi::Direction>) {
+    let rows = wrap::wrap(text, area.width);
+    let scroll = edit_scroll(&rows, cursor, area);
+    let base = display_base(text, bidi);
+    let visible = &rows[scroll.min(rows.len())..];
+    let lines = row_lines(&visible[..visible.len().min(area.height as usize)], base);
+    f.render_widget(Paragraph::new(lines).style(Style::default().fg(Color::White)), area);
+
+    let cell = match base {
+        Some(base) => bidi_edit_cell(area, &rows, scroll, cursor, base),
+        None => edit_cell(area, &rows, scroll, cursor).map(|(x, y)| (x, y, text.chars().nth(cursor))),
+    };
+    if let Some((x, y, under)) = cell {
+        let under = under.filter(|&c| c != '\n');
+        let width = under.and_then(|c| c.width()).unwrap_or(1).max(1) as u16;
+        f.render_widget(
+            Block::default().style(Style::default().bg(Color::White).fg(Color::Black)),
+            Rect {
+                x,
+                y,
+                width: width.min(area.right() - x),
+                height: 1,
+            },
+        );
+    }
+}
+
+/// First visible row of a wrapped edit buffer, keeping the cursor in view
+fn edit_scroll(rows: &[Row], cursor: usize, area: Rect) -> usize {
+    let (row, _) = wrap::position(rows, cursor, area.width);
+    (row + 1).saturating_sub(area.height as usize)
+}
+
+/// Screen cell of the char index `index` of a wrapped edit buffer, if it is
+/// in view
+fn edit_cell(area: Rect, rows: &[Row], scroll: usize, index: usize) -> Option<(u16, u16)> {
+    let (row, column) = wrap::position(rows, index, area.width);
+    let row = row.checked_sub(scroll).filter(|&row| row < area.height as usize)?;
+    Some((area.x + column, area.y + row as u16))
+}
+
+/// Screen cell of the cursor before char `index` of a wrapped buffer drawn
+/// in display order, and the char drawn there. The cursor covers the char
+/// it is on wherever that is drawn; at the end of a row it sits where the
+/// paragraph direction continues.
+fn bidi_edit_cell(
+    area: Rect,
+    rows: &[Row],
+    scroll: usize,
+    index: usize,
+    base: bidi::Direction,
+) -> Option<(u16, u16, Option<char>)> {
+    let (row, _) = wrap::position(rows, index, area.width);
+    let screen_row = row.checked_sub(scroll).filter(|&row| row < area.height as usize)?;
+    let y = area.y + screen_row as u16;
+    let rtl = base == bidi::Direction::Rtl;
+    let Some(row) = rows.get(row) else {
+        // After a full last row
+        let x = if rtl { area.right().saturating_sub(1) } else { area.x };
+        return Some((x, y, None));
+    };
+
+    let chars: Vec<char> = row.text.chars().collect();
+    let levels = bidi::levels(row.text, base);
+    let order = bidi::visual_order(&levels);
+    let visual: Vec<char> = order.iter().map(|&i| chars[i]).collect();
+    let width = |chars: &[char]| chars.iter().map(|c| c.width().unwrap_or(0)).sum::<usize>() as u16;
+    let offset = if rtl { area.width.saturating_sub(width(&visual)) } else { 0 };
+    let index = index.saturating_sub(row.start);
+    if index < chars.len() {
+        let slot = order.iter().position(|&i| i == index).unwrap_or(0);
+        return Some((area.x + offset + width(&visual[..slot]), y, Some(visual[slot])));
+    }
+    let column = if rtl {
+        offset.saturating_sub(1)
+    } else {
+        width(&visual).min(area.width.saturating_sub(1))
+    };
+    Some((area.x + column, y, None))
+}
+
+/// Paragraph direction for drawing `text` in display order: only when bidi
+/// display is on and the text needs it
+fn display_base(text: &str, bidi: Option<bidi::Direction>) -> Option<bidi::Direction> {
+    bidi.filter(|&base| base == bidi::Direction::Rtl || bidi::has_rtl(text))
+        .map(|fallback| bidi::base_direction(text, fallback))
+}
+
+/// Lines for wrapped rows, in display order with a `base` direction
+fn row_lines<'a>(rows: &[Row<'a>], base: Option<bidi::Direction>) -> Vec<Line<'a>> {
+    rows.iter()
+        .flat_map(|row| match base {
+            Some(base) => vec![display_line(row.text, base)],
+            None => with_visible_nbsp(row.text).lines,
+        })
+        .collect()
+}
+
+/// A row drawn in display order, right-aligned in right-to-left paragraphs
+fn display_line(text: &str, base: bidi::Direction) -> Line<'static> {
+    let visual = bidi::reorder(text, base);
+    let spans: Vec<Span<'static>> = with_visible_nbsp(&visual)
+        .lines
+        .into_iter()
+        .flat_map(|line| line.spans)
+        .map(|span| Span::styled(span.content.into_owned(), span.style))
+        .collect();
+    let line = Line::from(spans);
+    if base == bidi::Direction::Rtl {
+        line.alignment(Alignment::Right)
+    } else {
+        line
+    }
+}
+
+/// Lines of `text` with non-breaking spaces tinted so they stand out from
+/// normal spaces
+fn with_visible_nbsp(text: &str) -> Text<'_> {
+    let nbsp = Style::default().bg(Color::DarkGray);
+    text.split('\n')
+        .map(|line| {
+            let mut spans = Vec::new();
+            for (i, part) in line.split('\u{a0}').enumerate() {
+                if i > 0 {
+                    spans.push(Span::styled("\u{a0}", nbsp));
+                }
+                if !part.is_empty() {
+                    spans.push(Span::raw(part));
+                }
+            }
+            Line::from(spans)
+        })
+        .collect()
+}
+
+/// Completion popup below the cursor of the msgstr being edited
+fn draw_completion(f: &mut Frame, field: Rect, app: &App, completion: &Completion) {
+    let inner = Block::default().borders(Borders::ALL).inner(field);
+    let rows = wrap::wrap(&app.edit_text, inner.width);
+    let scroll = edit_scroll(&rows, app.edit_cursor, inner);
+    let Some((x, y)) = edit_cell(inner, &rows, scroll, completion.start) else {
+        return;
+    };
+    let frame = f.area();
+    let width = completion.words.iter().map(|word| word.width()).max().unwrap_or(0) as u16 + 2;
+    let width = width.min(frame.width);
+    let height = (completion.words.len() as u16 + 2).min(frame.height);
+    let area = Rect {
+        x: x.saturating_sub(1).min(frame.right() - width),
+        y: if y + 1 + height <= frame.bottom() { y + 1 } else { y.saturating_sub(height) },
+        width,
+        height,
+    };
+
+    let items: Vec<ListItem> = completion.words.iter().map(|word| ListItem::new(word.as_str())).collect();
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).border_style(Style::default().fg(Color::DarkGray)))
+        .highlight_style(Style::default().bg(Color::Blue).fg(Color::White));
+    let mut state = ListState::default();
+    state.select(Some(completion.selected));
+    f.render_widget(Clear, area);
+    f.render_stateful_widget(list, area, &mut state);
+}
+
+fn draw_metadata_panel(f: &mut Frame, area: Rect, app: &App) {
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage(40),  // Metadata keys list
+            Constraint::Percentage(60),  // Value editor
+        ])
+        .split(area);
+    
+    // Draw metadata keys list
+    let keys_items: Vec<ListItem> = app.metadata_keys
+        .iter()
+        .enumerate()
+        .map(|(i, key)| {
+            let current_value = app.po_file.get_header()
+                .get(key)
+                .cloned()
+                .unwrap_or_default();
+            
+            let display_value = truncate_to_width(&current_value, 30);
+            
+            let prefix = if i == app.metadata_selected { "► " } else { "  " };
+            if !app.po_file.get_header().contains_key(key) {
+                return ListItem::new(format!("{}{} (not set)", prefix, key)).style(Style::default().fg(Color::DarkGray));
+            }
+            ListItem::new(format!("{}{}: {}", prefix, key, display_value))
+        })
+        .collect();
+    
+    let keys_list = List::new(keys_items)
+        .block(
+            Block::default()
+                .title("Metadata Fields")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Yellow))
+        )
+        .style(Style::default().fg(Color::White))
+        .highlight_style(Style::default().bg(Color::Yellow).fg(Color::Black));
+    
+    f.render_widget(keys_list, chunks[0]);
+    
+    // Draw value editor
+    if app.metadata_selected < app.metadata_keys.len() {
+        let selected_key = &app.metadata_keys[app.metadata_selected];
+        let current_value = app.po_file.get_header()
+            .get(selected_key)
+            .cloned()
+            .unwrap_or_default();
+        
+        let title = if app.editing && app.metadata_key == *selected_key {
+            format!("{} (editing)", selected_key)
+        } else {
+            selected_key.clone()
+        };
+        
+        let display_text = if app.editing && app.metadata_key == *selected_key {
+            &app.edit_text
+        } else {
+            &current_value
+        };
+        
+        let border_color = if app.editing && app.metadata_key == *selected_key {
+            Color::Green
+        } else {
+            Color::Blue
+        };
+        
+        let block = Block::default()
+            .title(title)
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(border_color));
+
+        if app.editing && app.metadata_key == *selected_key {
+            let inner_area = block.inner(chunks[1]);
+            app.edit_width.set(inner_area.width);
+            f.render_widget(block, chunks[1]);
+            draw_edit_buffer(f, inner_area, display_text, app.edit_cursor, None);
+        } else {
+            let inner_area = block.inner(chunks[1]);
+            f.render_widget(block, chunks[1]);
+            let rows = wrap::wrap(display_text, inner_area.width);
+            f.render_widget(
+                Paragraph::new(row_lines(&rows, None)).style(Style::default().fg(Color::White)),
+                inner_area,
+            );
+        }
+    }
+}
+
+/// Piece of the footer line. When the line doesn't fit, key hints shrink
+/// first, then segments are dropped from the lowest priority up.
+struct FooterSegment {
+    text: String,
+    style: Style,
+    priority: u8,
+    /// `A | B | C` hints that may lose items from the end
+    hints: bool,
+}
+
+impl FooterSegment {
+    fn new(text: impl Into<String>, style: Style, priority: u8) -> Self {
+        Self {
+            text: text.into(),
+            style,
+            priority,
+            hints: false,
+        }
+    }
+}
+
+const FOOTER_SEPARATOR: &str = "  ";
+
+fn footer_hints(app: &App) -> &'static str {
+    if app.is_quick_filter_typing() {
+        "Quick filter: Type to narrow the list | Enter: Keep the filter | Esc: Clear"
+    } else if app.search_mode {
+        "Search mode: Type to search | Enter: Finish | Esc: Cancel"
+    } else if app.editing && app.config.esc_commits() {
+        "Edit mode: Type to edit | Enter/Esc: Apply"
+    } else if app.editing {
+        "Edit mode: Type to edit | Enter: Apply | Esc: Discard"
+    } else if app.metadata_mode {
+        "Metadata mode: ↑/↓/j/k: Navigate fields | Enter/i: Edit selected | d/Del: Delete | Alt+↑/↓: Move | S: Sort | Esc: Cancel | F9: Exit | Ctrl+S: Save | F1: Help"
+    } else if app.template_mode {
+        "Template: Ctrl+N: Create .po for a language | Tab: Field | Enter: Edit msgid/comments | F9: Metadata | F1: Help"
+    } else {
+        "Ctrl+Q: Quit | Ctrl+S: Save | Enter: Edit | F2/Ctrl+T: Toggle fuzzy | Ctrl+D: Mark done | F9: Metadata | F1: Help"
+    }
+}
+
+/// Segments describing the current state, in display order
+fn footer_segments(app: &App) -> Vec<FooterSegment> {
+    let mut segments = Vec::new();
+    if app.keymap.preset() == KeymapPreset::Vim {
+        let mode = if app.editing { "-- INSERT --" } else { "-- NORMAL --" };
+        segments.push(FooterSegment::new(mode, Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD), 3));
+    }
+    if app.is_recording_macro() {
+        segments.push(FooterSegment::new(
+            "● REC (q to stop)",
+            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+            4,
+        ));
+    }
+    if let Some((ref job, _)) = app.pipe_job {
+        let frame = SPINNER_FRAMES[(job.elapsed().as_millis() / 100) as usize % SPINNER_FRAMES.len()];
+        segments.push(FooterSegment::new(
+            format!("{} Running `{}` (Esc to cancel)", frame, job.command),
+            Style::default().fg(Color::Cyan),
+            4,
+        ));
+    }
+    if let Some(count) = app.keymap.pending_count() {
+        segments.push(FooterSegment::new(count.to_string(), Style::default().fg(Color::Cyan), 3));
+    }
+    if app.search_mode || app.is_quick_filter_typing() || !app.filters.text.is_empty() {
+        let count = app.filtered_indices.len();
+        let text = format!("{} {}", format_count(count), if count == 1 { "match" } else { "matches" });
+        segments.push(FooterSegment::new(text, Style::default().fg(Color::Green), 2));
+    }
+    let labels = app.filters.labels();
+    if !labels.is_empty() {
+        segments.push(FooterSegment::new(
+            format!("[{}]", labels.join(" · ")),
+            Style::default().fg(Color::Magenta),
+            1,
+        ));
+    }
+    if let Some(ref message) = app.status_message {
+        let color = if app.status_is_error() { Color::Red } else { Color::Yellow };
+        segments.push(FooterSegment::new(message.as_str(), Style::default().fg(color), 5));
+    }
+    segments.push(FooterSegment {
+        hints: true,
+        ..FooterSegment::new(footer_hints(app), Style::default(), 0)
+    });
+    segments
+}
+
+fn footer_width(segments: &[FooterSegment]) -> usize {
+    let text: usize = segments.iter().map(|segment| segment.text.width()).sum();
+    text + FOOTER_SEPARATOR.width() * segments.len().saturating_sub(1)
+}
+
+/// Shrink `segments` until they fit in `width` columns
+fn fit_footer(mut segments: Vec<FooterSegment>, width: usize) -> Vec<FooterSegment> {
+    while footer_width(&segments) > width && segments.len() > 1 {
+        if let Some(hints) = segments.iter_mut().find(|segment| segment.hints) {
+            if let Some((rest, _)) = hints.text.rsplit_once(" | ") {
+                hints.text = rest.to_string();
+                continue;
+            }
+        }
+        let lowest = segments
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, segment)| segment.priority)
+            .map(|(i, _)| i)
+            .unwrap_or(0);
+        segments.remove(lowest);
+    }
+    segments
+}
+
+fn draw_footer(f: &mut Frame, area: Rect, app: &App) {
+    if app.keymap.preset() == KeymapPreset::Vim {
+        if let Some(command) = app.keymap.command_line() {
+            let paragraph = Paragraph::new(format!(":{}█", command))
+                .block(Block::default().borders(Borders::ALL).border_style(Style::default().fg(Color::Gray)))
+                .style(Style::default().fg(Color::White));
+            f.render_widget(paragraph, area);
+            return;
+        }
+    }
+
+    let width = area.width.saturating_sub(2) as usize;
+    let mut spans = Vec::new();
+    for segment in fit_footer(footer_segments(app), width) {
+        if !spans.is_empty() {
+            spans.push(Span::raw(FOOTER_SEPARATOR));
+        }
+        spans.push(Span::styled(segment.text, segment.style));
+    }
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Gray));
+
+    let paragraph = Paragraph::new(Line::from(spans))
+        .block(block)
+        .alignment(Alignment::Center)
+        .style(Style::default().fg(Color::White));
+
+    f.render_widget(paragraph, area);
+}
+
+fn draw_search_overlay(f: &mut Frame, app: &App) {
+    let area = centered_rect(60, if app.search_error.is_some() { 4 } else { 3 }, f.area());
+    
+    f.render_widget(Clear, area);
+    
+    let tag = Style::default().fg(Color::Black).bg(Color::Green);
+    let mut title = vec![
+        Span::raw("Search "),
+        Span::styled(format!("[{}]", app.filters.search_status.label()), tag),
+    ];
+    let options = app.search_options;
+    for (on, label) in [
+        (options.case_sensitive, "[Case]"),
+        (options.regex, "[Regex]"),
+        (options.context_and_comments, "[+Context/comments]"),
+    ] {
+        if on {
+            title.extend([Span::raw(" "), Span::styled(label, tag)]);
+        }
+    }
+    title.push(Span::raw(" (Tab: status, Alt+C/R/X: options)"));
+    let title = Line::from(title);
+    let block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Green));
+
+    // Long queries scroll to keep the cursor inside the border
+    let input = TextInput {
+        text: app.filters.text.clone(),
+        cursor: app.search_cursor,
+    }
+    .window(area.width.saturating_sub(2) as usize);
+    let line = if app.search_selected {
+        Line::styled(input.text.as_str(), Style::default().fg(Color::Black).bg(Color::Cyan))
+    } else {
+        text_input_line(&input, Style::default().fg(Color::White))
+    };
+    let mut lines = vec![line];
+    if let Some(ref error) = app.search_error {
+        lines.push(Line::styled(format!("{} (searching as text)", error), Style::default().fg(Color::Red)));
+    }
+    let paragraph = Paragraph::new(lines).block(block);
+
+    f.render_widget(paragraph, area);
+}
+
+/// Render a text input as a line with the cursor shown in inverse video
+pub(crate) fn text_input_line<'a>(input: &'a TextInput, style: Style) -> Line<'a> {
+    let cursor_style = Style::default().fg(Color::Black).bg(Color::White);
+    let byte_pos = App::char_to_byte_index(&input.text, input.cursor);
+    let (before, rest) = input.text.split_at(byte_pos);
+    let mut chars = rest.chars();
+    let spans = match chars.next() {
+        Some(c) => vec![
+            Span::styled(before, style),
+            Span::styled(c.to_string(), cursor_style),
+            Span::styled(chars.as_str(), style),
+        ],
+        None => vec![Span::styled(before, style), Span::styled(" ", cursor_style)],
+    };
+    Line::from(spans)
+}
+
+fn draw_comment_manager(f: &mut Frame, app: &App, manager: &CommentManager) {
+    let Some(entry) = app.po_file.entries.get(manager.entry_index) else {
+        return;
+    };
+
+    let height = min(22, f.area().height);
+    let area = centered_rect(70, height, f.area());
+    f.render_widget(Clear, area);
+
+    let mut lines = Vec::new();
+    for section in [CommentSection::Translator, CommentSection::Extracted, CommentSection::Flags] {
+        let active = section == manager.section;
+        let title_style = if active {
+            Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(Color::Cyan)
+        };
+        lines.push(Line::from(Span::styled(section.title(), title_style)));
+
+        let rows = match section {
+            CommentSection::Translator => &entry.comments,
+            CommentSection::Extracted => &entry.extracted_comments,
+            CommentSection::Flags => &entry.flags,
+        };
+        let editing = manager.edit.as_ref().filter(|_| active);
+
+        for (i, row) in rows.iter().enumerate() {
+            let selected = active && i == manager.selected;
+            match editing {
+                Some(edit) if edit.row == Some(i) => {
+                    let mut line = text_input_line(&edit.input, Style::default().fg(Color::White));
+                    line.spans.insert(0, Span::raw("► "));
+                    lines.push(line);
+                }
+                _ => {
+                    let style = if selected {
+                        Style::default().bg(Color::DarkGray).add_modifier(Modifier::BOLD)
+                    } else {
+                        Style::default()
+                    };
+                    let prefix = if selected { "► " } else { "  " };
+                    lines.push(Line::from(Span::styled(format!("{}{}", prefix, row), style)));
+                }
+            }
+            if let Some(edit) = editing.filter(|e| e.row.is_none() && i == manager.selected) {
+                let mut line = text_input_line(&edit.input, Style::default().fg(Color::Green));
+                line.spans.insert(0, Span::raw("+ "));
+                lines.push(line);
+            }
+        }
+        if rows.is_empty() {
+            match editing {
+                Some(edit) if edit.row.is_none() => {
+                    let mut line = text_input_line(&edit.input, Style::default().fg(Color::Green));
+                    line.spans.insert(0, Span::raw("+ "));
+                    lines.push(line);
+                }
+                _ => lines.push(Line::from(Span::styled("  (none)", Style::default().fg(Color::DarkGray)))),
+            }
+        }
+        lines.push(Line::from(""));
+    }
+
+    let hint = if manager.edit.is_some() {
+        "Enter: Apply | Esc: Cancel"
+    } else {
+        "a: Add | e/Enter: Edit | d: Delete | J/K: Move | Tab: Section | Esc: Close"
+    };
+    lines.push(Line::from(Span::styled(hint, Style::default().fg(Color::DarkGray))));
+
+    let block = Block::default()
+        .title("Comments")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Magenta));
+
+    let paragraph = Paragraph::new(lines)
+        .block(block)
+        .style(Style::default().fg(Color::White));
+
+    f.render_widget(paragraph, area);
+}
+
+fn draw_qa_panel(f: &mut Frame, app: &App, panel: &QaPanel) {
+    let height = min(20, f.area().height);
+    let area = centered_rect(80, height, f.area());
+    f.render_widget(Clear, area);
+
+    let count = |severity| {
+        panel
+            .issues
+            .iter()
+            .filter(|i| !i.suppressed && i.issue.severity == severity)
+            .count()
+    };
+    let acknowledged = if panel.show_suppressed { "hide" } else { "show" };
+    let title = format!(
+        "QA: {} error(s), {} warning(s) (Enter: Go to entry, f: Fix, a: Acknowledge, s: {} acknowledged, Esc: Close)",
+        count(Severity::Error),
+        count(Severity::Warning),
+        acknowledged
+    );
+    let block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Red));
+
+    if panel.issues.is_empty() {
+        let paragraph = Paragraph::new("No issues found")
+            .block(block)
+            .style(Style::default().fg(Color::Green));
+        f.render_widget(paragraph, area);
+        return;
+    }
+
+    let items: Vec<ListItem> = panel
+        .issues
+        .iter()
+        .map(|found| {
+            let (symbol, color) = match found.issue.severity {
+                Severity::Error => ("✖", Color::Red),
+                Severity::Warning => ("⚠", Color::Yellow),
+                Severity::Info => ("ℹ", Color::Cyan),
+            };
+            let msgid: String = app.po_file.entries[found.entry]
+                .msgid
+                .chars()
+                .take(30)
+                .collect();
+            let fixable = if found.issue.fix.is_some() { " [f]" } else { "" };
+            let line = Line::from(vec![
+                Span::styled(format!("{} ", symbol), Style::default().fg(color)),
+                Span::raw(format!("{:4} ", found.entry + 1)),
+                Span::raw(found.issue.message.as_str()),
+                Span::styled(fixable, Style::default().fg(Color::Cyan)),
+                Span::styled(if found.suppressed { " (acknowledged)" } else { "" }, Style::default().fg(Color::Green)),
+                Span::styled(format!("  {}", msgid), Style::default().fg(Color::DarkGray)),
+            ]);
+            if found.suppressed {
+                ListItem::new(line.patch_style(Style::default().add_modifier(Modifier::DIM)))
+            } else {
+                ListItem::new(line)
+            }
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(block)
+        .highlight_style(Style::default().bg(Color::DarkGray).add_modifier(Modifier::BOLD))
+        .highlight_symbol("► ");
+    let mut state = ListState::default().with_selected(Some(panel.selected));
+    f.render_stateful_widget(list, area, &mut state);
+}
+
+fn draw_prompt(f: &mut Frame, prompt: &Prompt) {
+    let area = centered_rect(60, min(3, f.area().height), f.area());
+    f.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(prompt.title.as_str())
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Green));
+
+    let input = prompt.input.window(area.width.saturating_sub(2) as usize);
+    let paragraph = Paragraph::new(text_input_line(&input, Style::default().fg(Color::White))).block(block);
+    f.render_widget(paragraph, area);
+}
+
+fn draw_message(f: &mut Frame, title: &str, text: &str) {
+    let height = min(text.lines().count() as u16 + 3, f.area().height);
+    let area = centered_rect(70, height, f.area());
+    f.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(format!("{} (Esc to close)", title))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Red));
+
+    let paragraph = Paragraph::new(text)
+        .block(block)
+        .wrap(Wrap { trim: false })
+        .style(Style::default().fg(Color::White));
+    f.render_widget(paragraph, area);
+}
+
+/// The msgstr being edited, or the current entry's, as the program would
+/// show it at the configured width
+fn draw_preview(f: &mut Frame, app: &App) {
+    let Some(entry) = app.get_current_entry() else {
+        return;
+    };
+    let msgstr = if app.editing && app.edit_field == EditField::Msgstr { &app.edit_text } else { entry.form(app.plural_form()) };
+    let text = preview::render(msgstr, preview::SAMPLE_NUMBER);
+    let width = min(app.config.preview_width.max(1), f.area().width.saturating_sub(2));
+    let lines: Vec<Line> = if msgstr.is_empty() {
+        vec![Line::styled("Not translated", Style::default().fg(Color::DarkGray))]
+    } else {
+        wrap::wrap(&text, width).into_iter().map(|row| Line::from(row.text)).collect()
+    };
+
+    let area = f.area();
+    let height = min(lines.len() as u16 + 2, area.height);
+    let area = Rect {
+        x: area.x + area.width.saturating_sub(width + 2) / 2,
+        y: area.y + (area.height - height) / 2,
+        width: min(width + 2, area.width),
+        height,
+    };
+    f.render_widget(Clear, area);
+    let block = Block::default()
+        .title(format!("Preview, {} columns (Esc to close)", width))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan));
+    f.render_widget(Paragraph::new(lines).block(block), area);
+}
+
+fn draw_snippets(f: &mut Frame, app: &App) {
+    let snippets = &app.config.snippets;
+    let text: Vec<Line> = if snippets.is_empty() {
+        vec![
+            Line::from("No snippets are configured."),
+            Line::styled("Add them to the [snippets] section of config.toml.", Style::default().fg(Color::DarkGray)),
+        ]
+    } else {
+        let width = snippets.keys().map(|abbreviation| abbreviation.width()).max().unwrap_or(0);
+        snippets
+            .iter()
+            .map(|(abbreviation, expansion)| {
+                // Show line breaks and non-breaking spaces instead of acting on them
+                let expansion = expansion.replace('\n', "\\n").replace('\u{a0}', "⍽");
+                Line::from(vec![
+                    Span::styled(
+                        format!("{}{}  ", abbreviation, " ".repeat(width - abbreviation.width())),
+                        Style::default().fg(Color::Cyan),
+                    ),
+                    Span::raw(expansion),
+                ])
+            })
+            .collect()
+    };
+
+    let area = centered_rect(70, min(text.len() as u16 + 2, f.area().height), f.area());
+    f.render_widget(Clear, area);
+    let block = Block::default()
+        .title("Snippets (space or Alt+E after an abbreviation expands it)")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan));
+    f.render_widget(Paragraph::new(text).block(block), area);
+}
+
+fn draw_stats(f: &mut Frame, app: &App) {
+    let (total, translated, fuzzy) = app.po_file.get_stats();
+    let words = app.po_file.word_stats();
+    let session = &app.session;
+
+    let label = Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD);
+    let mut text = vec![
+        Line::from(Span::styled("Catalog", label)),
+        Line::from(format!(
+            "  {} entries: {} translated ({:.1}%), {} fuzzy, {} untranslated",
+            format_count(total),
+            format_count(translated),
+            percent(translated, total),
+            format_count(fuzzy),
+            format_count(total - translated - fuzzy)
+        )),
+        Line::from(format!(
+            "  Needs review: {}",
+            format_count(app.po_file.entries.iter().filter(|e| e.is_translated && app.needs_work(e)).count())
+        )),
+        Line::from(format!(
+            "  {} source words, {} translated ({:.1}%)",
+            format_count(words.total),
+            format_count(words.translated),
+            percent(words.translated, words.total)
+        )),
+        Line::from(format!("  {}", remaining_summary(&words))),
+        Line::from(""),
+        Line::from(Span::styled("This session", label)),
+        Line::from(format!(
+            "  Translated   {} entries ({} words)",
+            format_count(session.completed()),
+            format_count(session.words_translated())
+        )),
+        Line::from(format!("  Edited       {} entries", format_count(session.edited()))),
+        Line::from(format!("  Active time  {}", format_duration(session.active_time()))),
+    ];
+    let mut title = "Statistics (Esc to close)";
+    if let Some((total, this_session)) = app.time_in_file() {
+        text.insert(
+            5,
+            Line::from(format!(
+                "  Time in file: {} ({} this session)",
+                format_duration(total),
+                format_duration(this_session)
+            )),
+        );
+        title = "Statistics (r resets the time in file, Esc to close)";
+    }
+
+    let height = min(text.len() as u16 + 2, f.area().height);
+    let area = centered_rect(70, height, f.area());
+    f.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan));
+    f.render_widget(Paragraph::new(text).block(block), area);
+}
+
+fn draw_file_browser(f: &mut Frame, browser: &FileBrowser) {
+    let height = min(20, f.area().height);
+    let area = centered_rect(70, height, f.area());
+    f.render_widget(Clear, area);
+
+    let hidden = if browser.show_hidden { "shown" } else { "hidden" };
+    let block = Block::default()
+        .title(format!("Open: {}", browser.dir.display()))
+        .title_bottom(format!(
+            "Enter: Open  Backspace: Up  Alt+H: Dotfiles ({})  Esc: Close",
+            hidden
+        ))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan));
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(1), Constraint::Min(0)])
+        .split(inner);
+
+    let mut filter = vec![Span::styled("Filter: ", Style::default().fg(Color::DarkGray))];
+    filter.extend(text_input_line(&browser.filter, Style::default().fg(Color::White)).spans);
+    f.render_widget(Paragraph::new(Line::from(filter)), chunks[0]);
+
+    if let Err(ref message) = browser.listing {
+        let error = Paragraph::new(format!("{}\n\nBackspace goes to the parent directory", message))
+            .wrap(Wrap { trim: false })
+            .style(Style::default().fg(Color::Red));
+        f.render_widget(error, chunks[1]);
+        return;
+    }
+
+    let items: Vec<ListItem> = browser
+        .visible()
+        .into_iter()
+        .map(|entry| {
+            if entry.is_dir {
+                ListItem::new(format!("{}/", entry.name)).style(Style::default().fg(Color::Blue))
+            } else {
+                ListItem::new(entry.name.as_str())
+            }
+        })
+        .collect();
+    let list = List::new(items)
+        .highlight_style(Style::default().bg(Color::DarkGray).add_modifier(Modifier::BOLD))
+        .highlight_symbol("► ");
+    let mut state = ListState::default().with_selected(Some(browser.selected));
+    f.render_stateful_widget(list, chunks[1], &mut state);
+}
+
+fn draw_picker(f: &mut Frame, kind: PickerKind, picker: &Picker) {
+    let height = min(20, f.area().height);
+    let area = centered_rect(70, height, f.area());
+    f.render_widget(Clear, area);
+
+    let (title, hints) = match kind {
+        PickerKind::PluralForms => (
+            "Plural-Forms: type a language or an expression",
+            "Enter: Apply  Tab: Edit expression  Esc: Cancel",
+        ),
+        PickerKind::Language => ("Language: type a name or code", "Enter: Apply  Tab: Edit code  Esc: Cancel"),
+        PickerKind::Character => ("Insert character: type a codepoint or name", "Enter: Insert  Esc: Cancel"),
+        PickerKind::Reference => ("Several entries match: type to narrow", "Enter: Go to entry  Esc: Cancel"),
+    };
+    let block = Block::default()
+        .title(title)
+        .title_bottom(hints)
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan));
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(1), Constraint::Min(0), Constraint::Length(2)])
+        .split(inner);
+
+    let mut input = vec![Span::styled("> ", Style::default().fg(Color::DarkGray))];
+    input.extend(text_input_line(&picker.input, Style::default().fg(Color::White)).spans);
+    f.render_widget(Paragraph::new(Line::from(input)), chunks[0]);
+
+    let items: Vec<ListItem> = picker
+        .visible()
+        .into_iter()
+        .map(|item| {
+            ListItem::new(Line::from(vec![
+                Span::raw(item.label.as_str()),
+                Span::styled(format!("  {}", item.detail), Style::default().fg(Color::DarkGray)),
+            ]))
+        })
+        .collect();
+    let list = List::new(items)
+        .highlight_style(Style::default().bg(Color::DarkGray).add_modifier(Modifier::BOLD))
+        .highlight_symbol("► ");
+    let mut state = ListState::default().with_selected(Some(picker.selected));
+    f.render_stateful_widget(list, chunks[1], &mut state);
+
+    // Error of the last attempt, otherwise what Enter would apply
+    let typed = charmap::parse_codepoint(&picker.input.text).filter(|_| kind == PickerKind::Character);
+    let footer = match (&picker.error, typed, picker.selected_item()) {
+        (Some(error), _, _) => Span::styled(error.as_str(), Style::default().fg(Color::Red)),
+        (None, Some(Ok(c)), _) => Span::styled(
+            format!("{}  {}  {}", charmap::codepoint(c), c, charmap::name(c).unwrap_or_default()),
+            Style::default().fg(Color::Yellow),
+        ),
+        (None, Some(Err(error)), _) => Span::styled(error, Style::default().fg(Color::Red)),
+        (None, None, Some(item)) if kind == PickerKind::PluralForms => {
+            Span::styled(item.value.as_str(), Style::default().fg(Color::Yellow))
+        }
+        (None, None, Some(_)) => Span::raw(""),
+        (None, None, None) if kind == PickerKind::Character => Span::styled(
+            "Type a hex codepoint such as 2011, or part of a name",
+            Style::default().fg(Color::DarkGray),
+        ),
+        (None, None, None) => Span::styled("Enter applies the typed text", Style::default().fg(Color::DarkGray)),
+    };
+    f.render_widget(Paragraph::new(footer).wrap(Wrap { trim: false }), chunks[2]);
+}
+
+fn draw_recent_files(f: &mut Frame, app: &App, selected: usize) {
+    let recent = app.state.recent();
+    let height = min(recent.len().max(1) as u16 + 2, min(22, f.area().height));
+    let area = centered_rect(80, height, f.area());
+    f.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title("Recent Files (Enter: Open, Esc: Close)")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan));
+
+    if recent.is_empty() {
+        let paragraph = Paragraph::new("No recent files")
+            .block(block)
+            .style(Style::default().fg(Color::DarkGray));
+        f.render_widget(paragraph, area);
+        return;
+    }
+
+    let now = chrono::Utc::now();
+    let items: Vec<ListItem> = recent
+        .iter()
+        .map(|file| {
+            ListItem::new(Line::from(vec![
+                Span::raw(format!("{:5.1}%  ", file.progress)),
+                Span::raw(file.path.display().to_string()),
+                Span::styled(
+                    format!("  {}", relative_time(file.last_opened, now)),
+                    Style::default().fg(Color::DarkGray),
+                ),
+            ]))
+        })
+        .collect();
+    let list = List::new(items)
         .block(block)
         .highlight_style(Style::default().bg(Color::DarkGray).add_modifier(Modifier::BOLD))
         .highlight_symbol("► ");
+    let mut state = ListState::default().with_selected(Some(selected));
+    f.render_stateful_widget(list, area, &mut state);
+}
+
+fn draw_unlock_msgid(f: &mut Frame) {
+    let area = centered_rect(60, min(7, f.area().height), f.area());
+    f.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title("msgid is read-only")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Red));
+    let text = vec![
+        Line::from("msgmerge matches translations by msgid. Editing it here"),
+        Line::from("orphans the translation at the next merge; change the"),
+        Line::from("source code instead."),
+        Line::from(""),
+        Line::styled("Unlock msgid editing for this file? y: Unlock  n/Esc: Keep locked", Style::default().fg(Color::DarkGray)),
+    ];
+    f.render_widget(Paragraph::new(text).block(block), area);
+}
+
+fn draw_confirm_reload(f: &mut Frame) {
+    let area = centered_rect(60, min(4, f.area().height), f.area());
+    f.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title("Reload from disk")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Yellow));
+    let text = vec![
+        Line::from("Reloading drops the unsaved changes."),
+        Line::styled("y: Reload  n/Esc: Keep editing", Style::default().fg(Color::DarkGray)),
+    ];
+    f.render_widget(Paragraph::new(text).block(block), area);
+}
+
+fn draw_delete_header_field(f: &mut Frame, key: &str, confirm: Option<&TextInput>) {
+    let mut text = vec![Line::from(format!("Delete the {} header field?", key))];
+    match confirm {
+        Some(input) => {
+            text.push(Line::styled(
+                "gettext tools expect this field. Type its name and press Enter to delete it.",
+                Style::default().fg(Color::Yellow),
+            ));
+            let mut line = vec![Span::styled("> ", Style::default().fg(Color::DarkGray))];
+            line.extend(text_input_line(input, Style::default().fg(Color::White)).spans);
+            text.push(Line::from(line));
+        }
+        None => text.push(Line::styled("y: Delete  n/Esc: Keep", Style::default().fg(Color::DarkGray))),
+    }
+
+    let area = centered_rect(70, min(text.len() as u16 + 2, f.area().height), f.area());
+    f.render_widget(Clear, area);
+    let block = Block::default()
+        .title("Delete Field")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Red));
+    f.render_widget(Paragraph::new(text).block(block).wrap(Wrap { trim: false }), area);
+}
+
+fn draw_replace_form(f: &mut Frame, app: &App, form: &ReplaceForm) {
+    let area = centered_rect(70, min(7, f.area().height), f.area());
+    f.render_widget(Clear, area);
+    let block = Block::default()
+        .title("Replace in translations")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Green));
+    let width = area.width.saturating_sub(11) as usize;
+    let label = Style::default().fg(Color::DarkGray);
+    let field = |name: &'static str, input: &TextInput, focused: bool| {
+        let window = input.window(width);
+        let mut line = vec![Span::styled(name, label)];
+        if focused {
+            let spans = text_input_line(&window, Style::default().fg(Color::White)).spans;
+            line.extend(spans.into_iter().map(|span| Span::styled(span.content.into_owned(), span.style)));
+        } else {
+            line.push(Span::raw(window.text));
+        }
+        Line::from(line)
+    };
+    let check = |on: bool| if on { "[x]" } else { "[ ]" };
+    let summary = match (&form.error, form.replacer()) {
+        (Some(error), _) => Line::styled(error.clone(), Style::default().fg(Color::Red)),
+        (None, Ok(replacer)) => {
+            let counts = app
+                .filtered_indices
+                .iter()
+                .map(|&index| &app.po_file.entries[index])
+                .map(|entry| (0..entry.form_count()).map(|form| replacer.count(entry.form(form))).sum::<usize>())
+                .filter(|&count| count > 0);
+            let (matches, entries) = counts.fold((0, 0), |(matches, entries), count| (matches + count, entries + 1));
+            Line::styled(
+                format!("{} in {} listed", plural_count(matches, "match", "matches"), plural_count(entries, "entry", "entries")),
+                label,
+            )
+        }
+        (None, Err(_)) => Line::from(""),
+    };
+    let text = vec![
+        field("Find:      ", &form.pattern, !form.replacement_focused),
+        field("Replace:   ", &form.replacement, form.replacement_focused),
+        Line::from(format!(
+            "{} Case sensitive (Alt+C)   {} Regex, $1 in the replacement (Alt+R)",
+            check(form.case_sensitive),
+            check(form.regex)
+        )),
+        summary,
+        Line::styled("Enter: One by one  Alt+A: All listed  Alt+E: This entry  Tab: Field  Esc: Close", label),
+    ];
+    f.render_widget(Paragraph::new(text).block(block), area);
+}
+
+/// The match in context and what replaces it, above the footer so the entry
+/// stays in view
+fn draw_replace_match(f: &mut Frame, app: &App, step: &ReplaceStep) {
+    let Some(entry) = app.filtered_indices.get(step.position).map(|&index| &app.po_file.entries[index]) else {
+        return;
+    };
+    let screen = f.area();
+    let height = min(4, screen.height);
+    let width = screen.width * 7 / 10;
+    let area = Rect::new(screen.x + (screen.width - width) / 2, screen.bottom().saturating_sub(height + 3), width, height);
+    f.render_widget(Clear, area);
+    let block = Block::default()
+        .title(format!("Replace ({} done)", step.replaced))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Green));
+
+    let text = entry.form(step.form);
+    let found = &text[step.range.clone()];
+    let replacement = step.replacer.replacement_for(text, step.range.clone());
+    let room = (width as usize).saturating_sub(found.width() + replacement.width() + 8) / 2;
+    let before: String = text[..step.range.start].chars().rev().take(room).collect::<Vec<_>>().into_iter().rev().collect();
+    let after: String = text[step.range.end..].chars().take(room).collect();
+    let context = Line::from(vec![
+        Span::raw(before.replace('\n', "⏎")),
+        Span::styled(found.replace('\n', "⏎"), Style::default().fg(Color::Black).bg(Color::Yellow)),
+        Span::styled(format!(" → {}", replacement.replace('\n', "⏎")), Style::default().fg(Color::Green)),
+        Span::raw(" "),
+        Span::raw(after.replace('\n', "⏎")),
+    ]);
+    let keys = Line::styled(
+        "y/Enter: Replace  n/Space: Skip  a: All remaining  Esc: Stop",
+        Style::default().fg(Color::DarkGray),
+    );
+    f.render_widget(Paragraph::new(vec![context, keys]).block(block), area);
+}
+
+fn draw_quit_unsaved(f: &mut Frame, app: &App, selected: usize) {
+    let area = centered_rect(50, min(4, f.area().height), f.area());
+    f.render_widget(Clear, area);
+
+    let name = match app.po_file.path {
+        Some(ref path) => path.display().to_string(),
+        None => "New File".to_string(),
+    };
+    let block = Block::default()
+        .title(format!("Unsaved changes in {}", name))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Yellow));
+    let mut choices = Vec::new();
+    for (index, choice) in QUIT_CHOICES.iter().enumerate() {
+        let style = if index == selected {
+            Style::default().fg(Color::Black).bg(Color::Yellow)
+        } else {
+            Style::default()
+        };
+        choices.push(Span::styled(format!(" {} ", choice), style));
+        choices.push(Span::raw("  "));
+    }
+    let text = vec![
+        Line::from(choices).alignment(Alignment::Center),
+        Line::styled("s: Save  d: Discard  c/Esc: Cancel", Style::default().fg(Color::DarkGray)).alignment(Alignment::Center),
+    ];
+    f.render_widget(Paragraph::new(text).block(block), area);
+}
+
+fn draw_changed_on_disk(f: &mut Frame, app: &App, selected: usize) {
+    let area = centered_rect(60, min(5, f.area().height), f.area());
+    f.render_widget(Clear, area);
+
+    let name = match app.po_file.path {
+        Some(ref path) => path.display().to_string(),
+        None => "New File".to_string(),
+    };
+    let block = Block::default()
+        .title(format!("{} changed on disk", name))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Yellow));
+    let mut choices = Vec::new();
+    for (index, choice) in CONFLICT_CHOICES.iter().enumerate() {
+        let style = if index == selected {
+            Style::default().fg(Color::Black).bg(Color::Yellow)
+        } else {
+            Style::default()
+        };
+        choices.push(Span::styled(format!(" {} ", choice), style));
+        choices.push(Span::raw("  "));
+    }
+    let text = vec![
+        Line::from("Another program wrote the file since it was read").alignment(Alignment::Center),
+        Line::from(choices).alignment(Alignment::Center),
+        Line::styled("o: Overwrite  r: Reload, dropping your changes  a: Save as  c/Esc: Cancel", Style::default().fg(Color::DarkGray))
+            .alignment(Alignment::Center),
+    ];
+    f.render_widget(Paragraph::new(text).block(block), area);
+}
+
+fn draw_uncommitted_edit(f: &mut Frame, then: Action) {
+    let question = match then {
+        Action::Quit | Action::SaveAndQuit => "before quitting?",
+        Action::ToggleMetadataMode => "before switching modes?",
+        Action::Reload => "before reloading the file?",
+        Action::MergeTemplate => "before merging a template?",
+        Action::CompileMo => "before compiling the file?",
+        Action::Export => "before exporting?",
+        Action::Import => "before importing translations?",
+        _ => "before opening another file?",
+    };
+    let text = vec![
+        Line::from(format!("The field being edited has changed. Apply it {}", question)),
+        Line::styled(
+            "a/Enter: Apply  d: Discard  Esc: Keep editing",
+            Style::default().fg(Color::DarkGray),
+        ),
+    ];
+
+    let area = centered_rect(70, min(text.len() as u16 + 2, f.area().height), f.area());
+    f.render_widget(Clear, area);
+    let block = Block::default()
+        .title("Unapplied Edit")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Yellow));
+    f.render_widget(Paragraph::new(text).block(block).wrap(Wrap { trim: false }), area);
+}
+
+fn draw_language_defaults(f: &mut Frame, changes: &[(String, String)]) {
+    let mut text = vec![Line::from("Update these fields to match the language?"), Line::from("")];
+    for (key, value) in changes {
+        text.push(Line::from(vec![
+            Span::styled(format!("{}: ", key), Style::default().fg(Color::Cyan)),
+            Span::raw(value.as_str()),
+        ]));
+    }
+    text.push(Line::from(""));
+    text.push(Line::styled("y/Enter: Update  n/Esc: Keep", Style::default().fg(Color::DarkGray)));
+
+    let area = centered_rect(80, min(text.len() as u16 + 2, f.area().height), f.area());
+    f.render_widget(Clear, area);
+    let block = Block::default()
+        .title("Language")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan));
+    f.render_widget(Paragraph::new(text).block(block).wrap(Wrap { trim: false }), area);
+}
+
+fn draw_unsaved_changes(f: &mut Frame, app: &App, path: &Path) {
+    let area = centered_rect(60, min(4, f.area().height), f.area());
+    f.render_widget(Clear, area);
+
+    let name = match app.po_file.path {
+        Some(ref current) => current.display().to_string(),
+        None => "New File".to_string(),
+    };
+    let block = Block::default()
+        .title(format!("Unsaved changes in {}", name))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Yellow));
+    let text = vec![
+        Line::from(format!("Before opening {}:", path.display())),
+        Line::styled("s: Save  d: Discard  Esc: Cancel", Style::default().fg(Color::DarkGray)),
+    ];
+    f.render_widget(Paragraph::new(text).block(block), area);
+}
+
+fn draw_recover(f: &mut Frame, swap: &Swap) {
+    let started = swap.started.format("%H:%M");
+    let (title, text, color) = if swap.running {
+        (
+            "File already open".to_string(),
+            format!(
+                "Another poterm (pid {}) has had this file open since {}. Saving in both loses the changes of one.",
+                swap.pid, started
+            ),
+            Color::Red,
+        )
+    } else {
+        (
+            format!("Recover {} unsaved changes from {}?", swap.changes.len(), started),
+            "poterm did not exit cleanly last time, so these edits were never saved.".to_string(),
+            Color::Yellow,
+        )
+    };
+    let area = centered_rect(60, min(6, f.area().height), f.area());
+    f.render_widget(Clear, area);
+    let block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(color));
+    let keys = if swap.running {
+        "o/Esc: Open without a swap file  q: Quit"
+    } else {
+        "r: Recover the changes  d: Discard them  Esc: Decide later"
+    };
+    let text = vec![Line::from(text), Line::styled(keys, Style::default().fg(Color::DarkGray))];
+    f.render_widget(Paragraph::new(text).block(block).wrap(Wrap { trim: true }), area);
+}
+
+fn draw_restore_autosave(f: &mut Frame, recovery: &Recovery) {
+    let area = centered_rect(60, min(6, f.area().height), f.area());
+    f.render_widget(Clear, area);
+    let block = Block::default()
+        .title(format!("Restore the changes autosaved at {}?", recovery.written.format("%H:%M")))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Yellow));
+    let text = vec![
+        Line::from("poterm autosaved this file after it was last saved, but the changes were never saved."),
+        Line::styled("r: Restore them  d: Discard them  Esc: Decide later", Style::default().fg(Color::DarkGray)),
+    ];
+    f.render_widget(Paragraph::new(text).block(block).wrap(Wrap { trim: true }), area);
+}
+
+fn draw_help_overlay(f: &mut Frame, app: &App) {
+    let area = centered_rect(80, 25, f.area());
+    
+    f.render_widget(Clear, area);
+    
+    let help_text = vec![
+        Line::from("Navigation:"),
+        Line::from("  j/↓        - Next entry"),
+        Line::from("  k/↑        - Previous entry"),
+        Line::from("  PageUp     - Page up"),
+        Line::from("  PageDown   - Page down"),
+        Line::from("  Home       - First entry"),
+        Line::from("  End        - Last entry"),
+        Line::from(""),
+        Line::from("Editing:"),
+        Line::from("  i/Enter    - Start editing (comments open the comment manager)"),
+        Line::from("  Esc        - Stop editing"),
+        Line::from("  Tab        - Next field"),
+        Line::from("  Shift+Tab  - Previous field"),
+        Line::from("  Alt+Space  - Non-breaking space"),
+        Line::from("  Alt+- / Alt+. - Em dash / ellipsis"),
+        Line::from("  Alt+'      - Quotation marks (again: next style)"),
+        Line::from("  Ctrl+Shift+U/Alt+U - Insert a character by codepoint or name"),
+        Line::from(""),
+        Line::from("Translation Status:"),
+        Line::from("  F2/Ctrl+T  - Toggle fuzzy status"),
+        Line::from("  F4         - Toggle needs-work (review) status"),
+        Line::from("  Ctrl+D     - Mark entry as done (clears fuzzy and needs-work)"),
+        Line::from(""),
+        Line::from("Metadata Editing:"),
+        Line::from("  F9         - Enter/exit metadata mode"),
+        Line::from("  ↑/↓        - Navigate fields (in metadata mode)"),
+        Line::from("  Enter      - Edit selected field"),
+        Line::from("  d/Delete   - Delete selected field"),
+        Line::from("  Alt+↑/↓    - Move selected field"),
+        Line::from("  S          - Sort fields to gettext order"),
+        Line::from(""),
+        Line::from("Search & Filter:"),
+        Line::from("  Ctrl+F     - Search"),
+        Line::from("  Alt+C/R/X  - In search: case / regex / msgctxt and comments"),
+        Line::from("  F3         - Find next"),
+        Line::from("  Shift+F3   - Find previous"),
+        Line::from("  Ctrl+U     - Toggle untranslated filter"),
+        Line::from("  Alt+Z      - Toggle fuzzy filter"),
+        Line::from("  Alt+W      - Toggle needs-work filter"),
+        Line::from("  Ctrl+E     - Toggle entries-with-issues filter"),
+        Line::from("  Alt+F      - Filter by flag"),
+        Line::from("  Alt+S      - Filter by source file"),
+        Line::from("  Alt+D      - Toggle changed-since-save filter"),
+        Line::from("  Alt+M      - Toggle marked filter"),
+        Line::from("  /          - Quick filter (Enter keeps it, Esc clears)"),
+        Line::from("  Ctrl+R     - Replace in translations"),
+        Line::from("  Esc        - Clear all filters"),
+        Line::from(""),
+        Line::from("List:"),
+        Line::from("  #          - Cycle numbering (file / filter position / off)"),
+        Line::from("  Ctrl+G     - Go to entry number (in the shown numbering)"),
+        Line::from("  Ctrl+G×2   - Go to source reference (file:line)"),
+        Line::from("  Alt+N      - Next entry translating the msgid differently"),
+        Line::from("  Ctrl+→     - Next untranslated entry"),
+        Line::from("  Alt+→      - Next fuzzy entry"),
+        Line::from("  R          - Show source references in the list"),
+        Line::from("  t          - Group entries by source file"),
+        Line::from("  ←/→        - Collapse / expand a source file group"),
+        Line::from(""),
+        Line::from("Quality:"),
+        Line::from("  F8         - QA panel (validation issues)"),
+        Line::from("  F7         - Statistics for the file and this session"),
+        Line::from("  Alt+G      - Reload the glossary"),
+        Line::from("  Alt+1..3   - Use a translation memory suggestion (--tm)"),
+        Line::from("  F5         - Preview the translation with sample values"),
+        Line::from(""),
+        Line::from("Shell:"),
+        Line::from("  | / Alt+|  - Pipe msgid (or edit buffer) through a command"),
+        Line::from(""),
+        Line::from("Snippets:"),
+        Line::from("  Space      - Expand the abbreviation before the cursor (when editing)"),
+        Line::from("  Alt+E      - Expand the abbreviation before the cursor"),
+        Line::from("  F6         - List snippets"),
+        Line::from(""),
+        Line::from("Marks:"),
+        Line::from("  m0-m9      - Set mark on current entry"),
+        Line::from("  '0-'9      - Jump to mark"),
+        Line::from(""),
+        Line::from("Macros:"),
+        Line::from("  q          - Start/stop recording"),
+        Line::from("  @ / N@     - Play macro once / N times"),
+        Line::from(""),
+        Line::from("File Operations:"),
+        Line::from("  Ctrl+O     - Open another file"),
+        Line::from("  Alt+O      - Recent files"),
+        Line::from("  Shift+F5   - Reload the file from disk"),
+        Line::from("  Ctrl+N     - Create a .po from the open template"),
+        Line::from("  Alt+T      - Merge an updated template into the file"),
+        Line::from("  Alt+B      - Compile the file to a .mo next to it"),
+        Line::from("  Alt+X      - Export entries to .csv, .xlf or .json"),
+        Line::from("  Alt+I      - Import translations from .csv, .xlf or .json"),
+        Line::from("  Ctrl+S     - Save file"),
+        Line::from("  F12        - Save as"),
+        Line::from("  Ctrl+Shift+P - Save current entry"),
+        Line::from("  Ctrl+Q     - Quit (asks about unsaved changes)"),
+        Line::from("  Alt+Q      - Quit without saving"),
+        Line::from("  Alt+Backspace - Undo"),
+        Line::from("  Ctrl+Y     - Redo"),
+        Line::from("  Ctrl+Z     - Suspend to the shell"),
+        Line::from("  Ctrl+C/X/V - Copy / cut / paste"),
+        Line::from(""),
+        Line::from("Press Esc to close this help"),
+    ];
+
+    let marks: Vec<String> = app
+        .marks
+        .iter()
+        .enumerate()
+        .filter_map(|(digit, mark)| {
+            let key = mark.as_ref()?;
+            let preview: String = key.msgid.chars().take(20).collect();
+            Some(format!("{}: {}", digit, preview))
+        })
+        .collect();
+
+    let mut block = Block::default()
+        .title("Help")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan));
+    if !marks.is_empty() {
+        block = block.title_bottom(format!(" Marks — {} ", marks.join(" · ")));
+    }
+
+    let paragraph = Paragraph::new(help_text)
+        .block(block)
+        .style(Style::default().fg(Color::White));
+
+    f.render_widget(paragraph, area);
+}
+
+fn centered_rect(percent_x: u16, height: u16, r: Rect) -> Rect {
+    let height = height.min(r.height);
+    let popup_layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length((r.height - height) / 2),
+            Constraint::Length(height),
+            Constraint::Length((r.height - height) / 2),
+        ])
+        .split(r);
+
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage((100 - percent_x) / 2),
+            Constraint::Percentage(percent_x),
+            Constraint::Percentage((100 - percent_x) / 2),
+        ])
+        .split(popup_layout[1])[1]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gettext::{PoFile, PoEntry};
+
+    #[test]
+    fn test_char_to_byte_index() {
+        let text = "Hello мир world";  // Contains Cyrillic characters
+        
+        // Test ASCII characters
+        assert_eq!(App::char_to_byte_index(text, 0), 0);  // 'H'
+        assert_eq!(App::char_to_byte_index(text, 5), 5);  // Space after "Hello"
+        
+        // Test Unicode boundaries
+        assert_eq!(App::char_to_byte_index(text, 6), 6);  // 'м' starts at byte 6
+        assert_eq!(App::char_to_byte_index(text, 7), 8);  // 'и' starts at byte 8 (м is 2 bytes)
+        assert_eq!(App::char_to_byte_index(text, 8), 10); // 'р' starts at byte 10 (и is 2 bytes)
+        
+        // Test beyond string length
+        assert_eq!(App::char_to_byte_index(text, 100), text.len());
+    }
+
+    #[test]
+    fn test_remove_char_at() {
+        let mut text = String::from("Hello мир world");
+        
+        // Remove ASCII character
+        App::remove_char_at(&mut text, 0);
+        assert_eq!(text, "ello мир world");
+        
+        // Remove Unicode character
+        let mut text = String::from("Hello мир world");
+        App::remove_char_at(&mut text, 6); // Remove 'м'
+        assert_eq!(text, "Hello ир world");
+        
+        // Remove beyond string length (should do nothing)
+        let mut text = String::from("test");
+        let original_len = text.len();
+        App::remove_char_at(&mut text, 100);
+        assert_eq!(text.len(), original_len);
+    }
+
+    #[test]
+    fn test_toggle_metadata_mode() {
+        let po_file = PoFile::default();
+        let mut app = App::new(po_file, Config::default());
+        
+        // Initially should not be in metadata mode
+        assert!(!app.is_metadata_mode());
+        assert_eq!(app.edit_field, EditField::Msgstr);
+        
+        // Toggle to metadata mode
+        app.toggle_metadata_mode();
+        assert!(app.is_metadata_mode());
+        assert_eq!(app.edit_field, EditField::Metadata);
+        
+        // Toggle back
+        app.toggle_metadata_mode();
+        assert!(!app.is_metadata_mode());
+        assert_eq!(app.edit_field, EditField::Msgstr);
+        
+        // An open edit is closed first
+        app.editing = true;
+        app.toggle_metadata_mode();
+        assert!(app.is_metadata_mode());
+        assert!(!app.is_editing());
+    }
+
+    #[test]
+    fn test_insert_char_at() {
+        let mut text = String::from("Hello world");
+        
+        // Insert ASCII character
+        App::insert_char_at(&mut text, 5, ' ');
+        assert_eq!(text, "Hello  world");
+        
+        // Insert Unicode character
+        let mut text = String::from("Hello world");
+        App::insert_char_at(&mut text, 5, 'ё');
+        assert_eq!(text, "Helloё world");
+        
+        // Insert at beginning
+        let mut text = String::from("test");
+        App::insert_char_at(&mut text, 0, 'X');
+        assert_eq!(text, "Xtest");
+        
+        // Insert at end
+        let mut text = String::from("test");
+        App::insert_char_at(&mut text, 4, '!');
+        assert_eq!(text, "test!");
+    }
+
+    #[test]
+    fn test_page_navigation() {
+        let mut po_file = PoFile::default();
+        // Add test entries
+        for i in 0..25 {
+            let mut entry = PoEntry::new();
+            entry.msgid = format!("test {}", i);
+            po_file.entries.push(entry);
+        }
+        
+        let mut app = App::new(po_file, Config::default());
+        
+        // Test page down
+        app.page_down();
+        assert_eq!(app.current_entry, PAGE_SIZE);
+        
+        // Test page up
+        app.page_up();
+        assert_eq!(app.current_entry, 0);
+        
+        // Test page down at end
+        app.current_entry = 20;
+        app.page_down();
+        assert_eq!(app.current_entry, 24); // Should go to last entry
+    }
+
+    #[test]
+    fn test_edit_field_cycling() {
+        let po_file = PoFile::default();
+        let mut app = App::new(po_file, Config::default());
+        
+        assert_eq!(app.edit_field, EditField::Msgstr);
+        
+        app.next_field();
+        assert_eq!(app.edit_field, EditField::Comments);
+        
+        app.next_field();
+        assert_eq!(app.edit_field, EditField::Msgid);
+        
+        app.next_field();
+        assert_eq!(app.edit_field, EditField::Msgstr);
+        
+        app.previous_field();
+        assert_eq!(app.edit_field, EditField::Msgid);
+    }
+    
+    #[test]
+    fn test_plural_forms_are_edited_one_by_one() {
+        let po_file = PoFile::parse(concat!(
+            "msgid \"\"\nmsgstr \"\"\n",
+            "\"Plural-Forms: nplurals=3; plural=(n%10==1 && n%100!=11 ? 0 : n%10>=2 && n%10<=4 && (n%100<10 || n%100>=20) ? 1 : 2);\\n\"\n\n",
+            "msgid \"%d file\"\nmsgid_plural \"%d files\"\nmsgstr[0] \"%d файл\"\nmsgstr[1] \"\"\n",
+        ))
+        .unwrap();
+        let mut app = App::new(po_file, Config::default());
+        assert_eq!(app.form_count(), 3);
+        assert!(!app.po_file.entries[0].is_translated);
+
+        app.next_field();
+        assert_eq!((app.edit_field, app.plural_form()), (EditField::Msgstr, 1));
+        let screen = screen_text(&render(&mut app, 120, 40));
+        assert!(screen.contains("Translation (msgstr[1] of 3, n = 2, 3, 4…)"));
+        assert!(screen.contains("Original Text (msgid_plural)"));
+
+        app.start_editing();
+        type_keys(&mut app, "%d файла");
+        app.next_field();
+        assert_eq!(app.plural_form(), 2);
+        type_keys(&mut app, "%d файлов");
+        app.stop_editing();
+        let entry = &app.po_file.entries[0];
+        assert_eq!(entry.msgstr_plural, vec!["%d файл", "%d файла", "%d файлов"]);
+        assert!(entry.is_translated);
+
+        app.next_field();
+        assert_eq!((app.edit_field, app.plural_form()), (EditField::Comments, 0));
+        app.previous_field();
+        assert_eq!((app.edit_field, app.plural_form()), (EditField::Msgstr, 2));
+    }
+
+    #[test]
+    fn test_previous_msgid_shown_for_fuzzy_entries() {
+        let po_file = PoFile::parse(concat!(
+            "#, fuzzy\n#| msgid \"Open file\"\nmsgid \"Open a file\"\nmsgstr \"Открыть файл\"\n\n",
+            "#| msgid \"Save\"\nmsgid \"Save all\"\nmsgstr \"Сохранить всё\"\n",
+        ))
+        .unwrap();
+        let mut app = App::new(po_file, Config::default());
+        let screen = screen_text(&render(&mut app, 120, 40));
+        assert!(screen.contains("Previous Original Text (#| msgid)"));
+        assert!(screen.contains("Open file"));
+
+        // Once the translation is reviewed the old text is of no interest
+        app.next_entry();
+        let screen = screen_text(&render(&mut app, 120, 40));
+        assert!(!screen.contains("Previous Original Text"));
+    }
+
+    #[test]
+    fn test_metadata_mode() {
+        let po_file = PoFile::default();
+        let mut app = App::new(po_file, Config::default());
+        
+        assert!(!app.metadata_mode);
+        
+        app.toggle_metadata_mode();
+        assert!(app.metadata_mode);
+        assert_eq!(app.edit_field, EditField::Metadata);
+    }
+
+    #[test]
+    fn test_toggle_fuzzy_functionality() {
+        let mut po_file = PoFile::default();
+        
+        // Add a translated entry
+        let mut entry = PoEntry::new();
+        entry.msgid = "Hello".to_string();
+        entry.set_msgstr("Привет".to_string());
+        po_file.entries.push(entry);
+        
+        // Add a fuzzy entry
+        let mut fuzzy_entry = PoEntry::new();
+        fuzzy_entry.msgid = "World".to_string();
+        fuzzy_entry.msgstr = "Мир".to_string();
+        fuzzy_entry.flags.push("fuzzy".to_string());
+        fuzzy_entry.update_status();
+        po_file.entries.push(fuzzy_entry);
+        
+        let mut app = App::new(po_file, Config::default());
+        
+        // Test toggle fuzzy on translated entry (index 0)
+        assert!(!app.po_file.entries[0].is_fuzzy);
+        app.toggle_current_entry_fuzzy();
+        assert!(app.po_file.entries[0].is_fuzzy);
+        
+        // Toggle back
+        app.toggle_current_entry_fuzzy();
+        assert!(!app.po_file.entries[0].is_fuzzy);
+        
+        // Move to fuzzy entry (index 1)
+        app.next_entry();
+        assert!(app.po_file.entries[1].is_fuzzy);
+        
+        // Toggle fuzzy off (mark as done)
+        app.toggle_current_entry_fuzzy();
+        assert!(!app.po_file.entries[1].is_fuzzy);
+        assert!(app.po_file.entries[1].is_translated);
+    }
+
+    #[test]
+    fn test_mark_entry_done() {
+        let mut po_file = PoFile::default();
+        
+        // Add a fuzzy entry
+        let mut entry = PoEntry::new();
+        entry.msgid = "Test".to_string();
+        entry.msgstr = "Тест".to_string();
+        entry.flags.push("fuzzy".to_string());
+        entry.update_status();
+        po_file.entries.push(entry);
+        
+        let mut app = App::new(po_file, Config::default());
+        
+        assert!(app.po_file.entries[0].is_fuzzy);
+        assert!(!app.po_file.entries[0].is_translated);
+        
+        // Mark as done
+        app.mark_current_entry_done();
+        
+        assert!(!app.po_file.entries[0].is_fuzzy);
+        assert!(app.po_file.entries[0].is_translated);
+        assert!(!app.po_file.entries[0].flags.contains(&"fuzzy".to_string()));
+    }
+
+    #[test]
+    fn test_fuzzy_toggle_edge_cases() {
+        let mut po_file = PoFile::default();
+        
+        // Add an untranslated entry (empty msgstr)
+        let mut entry = PoEntry::new();
+        entry.msgid = "Empty".to_string();
+        entry.msgstr = "".to_string();
+        po_file.entries.push(entry);
+        
+        let mut app = App::new(po_file, Config::default());
+        
+        // Should not toggle fuzzy on empty translation
+        assert!(!app.po_file.entries[0].is_fuzzy);
+        app.toggle_current_entry_fuzzy();
+        assert!(!app.po_file.entries[0].is_fuzzy);
+        
+        // Should not mark as done if no translation
+        app.mark_current_entry_done();
+        assert!(!app.po_file.entries[0].is_translated);
+
+        // A plural entry counts as translated once any form is
+        let mut entry = PoEntry::new();
+        entry.msgid = "%d file".to_string();
+        entry.msgid_plural = Some("%d files".to_string());
+        entry.set_form(1, "%d файла".to_string());
+        app.po_file.entries.push(entry);
+        app.update_filtered_indices();
+        app.current_entry = 1;
+
+        app.toggle_current_entry_fuzzy();
+        assert!(app.po_file.entries[1].is_fuzzy);
+        app.toggle_current_entry_needs_work();
+        assert!(app.po_file.entries[1].flags.contains(&app.config.needs_work_flag));
+        app.mark_current_entry_done();
+        assert!(!app.po_file.entries[1].is_fuzzy);
+        assert!(app.po_file.entries[1].flags.is_empty());
+    }
+
+    fn app_with_entries(count: usize) -> App {
+        let mut po_file = PoFile::default();
+        for i in 0..count {
+            let mut entry = PoEntry::new();
+            entry.msgid = format!("test {}", i);
+            entry.set_msgstr(format!("перевод {}", i));
+            po_file.entries.push(entry);
+        }
+        App::new(po_file, Config::default())
+    }
+
+    #[test]
+    fn test_macro_record_and_play() {
+        let mut app = app_with_entries(5);
+
+        app.perform(Action::ToggleMacroRecording).unwrap();
+        assert!(app.is_recording_macro());
+        app.perform(Action::ToggleFuzzy).unwrap();
+        app.perform(Action::Next).unwrap();
+        app.perform(Action::ToggleMacroRecording).unwrap();
+        assert!(!app.is_recording_macro());
+        assert_eq!(app.macro_register, vec![Action::ToggleFuzzy, Action::Next]);
+
+        assert_eq!(app.perform(Action::PlayMacro(2)).unwrap(), Outcome::Done);
+        assert_eq!(app.current_entry, 3);
+        assert!(app.po_file.entries[..3].iter().all(|e| e.is_fuzzy));
+        assert!(!app.po_file.entries[3].is_fuzzy);
+    }
+
+    #[test]
+    fn test_macro_stops_when_action_fails() {
+        let mut app = app_with_entries(3);
+
+        app.perform(Action::ToggleMacroRecording).unwrap();
+        app.perform(Action::Next).unwrap();
+        app.perform(Action::ToggleMacroRecording).unwrap();
+
+        // Only one more step is possible before hitting the end of the list
+        assert_eq!(app.perform(Action::PlayMacro(5)).unwrap(), Outcome::Blocked);
+        assert_eq!(app.current_entry, 2);
+        assert!(app.status_message.as_deref().unwrap().contains("Macro stopped"));
+    }
+
+    #[test]
+    fn test_macro_records_text_input() {
+        use crossterm::event::KeyModifiers;
+
+        let mut app = app_with_entries(2);
+        let key = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE);
+
+        app.perform(Action::ToggleMacroRecording).unwrap();
+        app.perform(Action::StartEditing).unwrap();
+        app.perform(Action::Input(key('!'))).unwrap();
+        app.perform(Action::Input(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE))).unwrap();
+        app.perform(Action::Next).unwrap();
+        app.perform(Action::ToggleMacroRecording).unwrap();
+
+        app.perform(Action::PlayMacro(1)).unwrap();
+        assert_eq!(app.po_file.entries[0].msgstr, "перевод 0!");
+        assert_eq!(app.po_file.entries[1].msgstr, "перевод 1!");
+    }
+
+    #[test]
+    fn test_edit_translation_selects_msgstr() {
+        let mut app = app_with_entries(2);
+        app.next_field();
+        assert_eq!(app.edit_field, EditField::Comments);
+
+        assert_eq!(app.perform(Action::EditTranslation).unwrap(), Outcome::Done);
+        assert!(app.editing);
+        assert_eq!(app.edit_field, EditField::Msgstr);
+        assert_eq!(app.edit_text, "перевод 0");
+    }
+
+    #[test]
+    fn test_comment_manager_overlay() {
+        use crossterm::event::KeyModifiers;
+
+        let mut app = app_with_entries(1);
+        app.po_file.entries[0].comments = vec!["keep".to_string()];
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+
+        app.next_field();
+        assert_eq!(app.edit_field, EditField::Comments);
+        assert_eq!(app.perform(Action::StartEditing).unwrap(), Outcome::Done);
+        assert!(matches!(app.overlay, Some(Overlay::Comments(_))));
+
+        // Keys go to the overlay instead of the keymap
+        for code in [KeyCode::Char('a'), KeyCode::Char('j'), KeyCode::Char('k'), KeyCode::Enter] {
+            let action = app.resolve_key(key(code)).unwrap();
+            app.perform(action).unwrap();
+        }
+        assert_eq!(app.po_file.entries[0].comments, vec!["keep", "jk"]);
+        assert!(app.is_modified());
+
+        let action = app.resolve_key(key(KeyCode::Esc)).unwrap();
+        app.perform(action).unwrap();
+        assert!(app.overlay.is_none());
+    }
+
+    #[test]
+    fn test_terminal_title() {
+        let mut app = App::new(PoFile::new(std::path::PathBuf::from("po/ru.po")), Config::default());
+        assert_eq!(app.terminal_title(), "poterm — ru.po");
+        app.po_file.mark_modified();
+        assert_eq!(app.terminal_title(), "poterm — ru.po*");
+
+        let app = App::new(PoFile::default(), Config::default());
+        assert_eq!(app.terminal_title(), "poterm — New File");
+    }
+
+    #[cfg(unix)]
+    fn wait_for_pipe(app: &mut App) {
+        while app.pipe_job.is_some() {
+            std::thread::sleep(Duration::from_millis(10));
+            app.tick();
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_pipe_command_replaces_msgstr() {
+        let mut app = app_with_entries(1);
+        app.po_file.entries[0].msgid = "hello".to_string();
+
+        app.perform(Action::PipeCommand).unwrap();
+        assert!(matches!(app.overlay, Some(Overlay::Prompt(_))));
+        app.submit_prompt(PromptKind::PipeCommand, "tr a-z A-Z".to_string());
+        app.overlay = None;
+        wait_for_pipe(&mut app);
+
+        assert_eq!(app.po_file.entries[0].msgstr, "HELLO");
+        assert!(app.is_modified());
+        assert_eq!(app.last_pipe_command, "tr a-z A-Z");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_pipe_command_inserts_at_cursor() {
+        let mut app = app_with_entries(1);
+        app.perform(Action::StartEditing).unwrap();
+        app.edit_cursor = "перевод".chars().count();
+        app.run_pipe_command("printf ' (%s)' ok");
+        wait_for_pipe(&mut app);
+
+        assert_eq!(app.edit_text, "перевод (ok) 0");
+        assert_eq!(app.edit_cursor, "перевод (ok)".chars().count());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_pipe_command_failure_changes_nothing() {
+        let mut app = app_with_entries(1);
+        app.perform(Action::StartEditing).unwrap();
+        app.run_pipe_command("echo broken >&2; exit 1");
+        wait_for_pipe(&mut app);
+
+        assert_eq!(app.edit_text, "перевод 0");
+        match app.overlay {
+            Some(Overlay::Message { ref text, .. }) => assert_eq!(text, "broken\n"),
+            ref other => panic!("unexpected overlay {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_pipe_command_can_be_disabled() {
+        let config = Config {
+            pipe_commands: false,
+            ..Config::default()
+        };
+        let mut po_file = PoFile::default();
+        po_file.entries.push(PoEntry::new());
+        let mut app = App::new(po_file, config);
+
+        app.perform(Action::PipeCommand).unwrap();
+        assert!(app.overlay.is_none());
+        assert!(app.status_message.is_some());
+    }
+
+    #[test]
+    fn test_marks_survive_filtering() {
+        let mut app = app_with_entries(5);
+        app.po_file.entries[1].set_msgstr(String::new());
+        app.po_file.entries[3].set_msgstr(String::new());
 
-    f.render_stateful_widget(list, area, &mut app.list_state);
-}
+        app.go_to_last();
+        app.perform(Action::SetMark(1)).unwrap();
+        app.go_to_first();
+        app.next_entry();
+        app.next_entry();
+        app.next_entry();
+        app.perform(Action::SetMark(2)).unwrap();
+        assert_eq!(app.mark_for(&app.po_file.entries[3]), Some(2));
+
+        // Re-marking an entry moves its digit
+        app.perform(Action::SetMark(5)).unwrap();
+        assert_eq!(app.mark_for(&app.po_file.entries[3]), Some(5));
+        assert!(app.marks[2].is_none());
+
+        app.toggle_untranslated_filter();
+        assert_eq!(app.perform(Action::JumpToMark(5)).unwrap(), Outcome::Done);
+        assert_eq!(app.filtered_indices[app.current_entry], 3);
+        assert_eq!(app.perform(Action::JumpToMark(1)).unwrap(), Outcome::Blocked);
+        assert!(app.status_message.as_ref().unwrap().contains("hidden"));
+
+        app.toggle_untranslated_filter();
+        assert_eq!(app.perform(Action::JumpToMark(1)).unwrap(), Outcome::Done);
+        assert_eq!(app.current_entry, 4);
+        assert_eq!(app.perform(Action::JumpToMark(7)).unwrap(), Outcome::Blocked);
+    }
+
+    /// Press a key as the terminal sends it. None when the keymap waits for
+    /// more keys or has nothing bound to it.
+    fn press(app: &mut App, modifiers: KeyModifiers, code: KeyCode) -> Option<Outcome> {
+        let action = app.resolve_key(KeyEvent::new(code, modifiers))?;
+        Some(app.perform(action).unwrap())
+    }
+
+    /// Select header field `key` in metadata mode
+    fn select_metadata(app: &mut App, key: &str) {
+        app.metadata_selected = app.metadata_keys.iter().position(|k| k == key).unwrap();
+    }
+
+    fn type_keys(app: &mut App, text: &str) {
+        for c in text.chars() {
+            let action = app.resolve_key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE)).unwrap();
+            app.perform(action).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_quick_filter_narrows_and_restores() {
+        let mut app = app_with_entries(12);
+        app.next_entry();
+        app.next_entry();
+
+        app.perform(Action::QuickFilter).unwrap();
+        // 'j' and 'q' are filter text, not navigation or macro keys
+        type_keys(&mut app, "1");
+        assert_eq!(app.filtered_indices, vec![1, 10, 11]);
+        type_keys(&mut app, "j");
+        assert!(app.filtered_indices.is_empty());
+        app.perform(Action::Input(KeyEvent::new(KeyCode::Backspace, KeyModifiers::NONE))).unwrap();
+        assert!(!app.is_recording_macro());
+
+        let esc = app.resolve_key(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE)).unwrap();
+        app.perform(esc).unwrap();
+        assert!(app.quick_filter.is_none());
+        assert_eq!(app.filtered_indices.len(), 12);
+        assert_eq!(app.current_entry, 2);
+    }
+
+    #[test]
+    fn test_quick_filter_composes_with_status_filter() {
+        let mut app = app_with_entries(12);
+        app.po_file.entries[10].set_msgstr(String::new());
+        app.toggle_untranslated_filter();
+
+        app.perform(Action::QuickFilter).unwrap();
+        type_keys(&mut app, "1");
+        assert_eq!(app.filtered_indices, vec![10]);
+
+        let enter = app.resolve_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE)).unwrap();
+        app.perform(enter).unwrap();
+        assert!(!app.is_editing());
+        assert_eq!(app.filtered_indices, vec![10]);
+
+        // Esc while browsing clears every filter and keeps the selection
+        app.perform(Action::Cancel).unwrap();
+        assert_eq!(app.filters.status, FilterMode::All);
+        assert!(app.quick_filter.is_none());
+        assert_eq!(app.filtered_indices[app.current_entry], 10);
+        assert_eq!(app.perform(Action::Cancel).unwrap(), Outcome::Blocked);
+    }
+
+    #[test]
+    fn test_qa_panel_jumps_to_issue() {
+        let mut app = app_with_entries(4);
+        app.po_file.entries[2].msgid = "<b>Bold</b>".to_string();
+        app.po_file.entries[2].set_msgstr("<b>Жирный".to_string());
+        app.toggle_fuzzy_filter();
+
+        app.perform(Action::ToggleQaPanel).unwrap();
+        match app.overlay {
+            Some(Overlay::Qa(ref panel)) => assert_eq!(panel.issues.len(), 1),
+            ref other => panic!("unexpected overlay {:?}", other),
+        }
+
+        // The entry is hidden by the fuzzy filter, so the filters are cleared
+        app.perform(Action::Input(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE))).unwrap();
+        assert!(app.overlay.is_none());
+        assert_eq!(app.filters.status, FilterMode::All);
+        assert_eq!(app.filtered_indices[app.current_entry], 2);
+    }
+
+    #[test]
+    fn test_qa_panel_lists_parse_issues() {
+        let content = "msgid \"Open\"\nmsgstr \"Открыть\"\n\nmsgid \"Save\"\nmsgstr \"Сохранить\"\n\
+                       \"oops\n\nmsgid \"Quit\"\nmsgstr \"Выход\"\n";
+        let mut app = App::new(PoFile::parse(content).unwrap(), Config::default());
+        let screen = screen_text(&render(&mut app, 120, 20));
+        assert!(screen.contains("[1 parse warning - F8]"), "{}", screen);
+
+        app.perform(Action::ToggleQaPanel).unwrap();
+        let screen = screen_text(&render(&mut app, 120, 20));
+        assert!(screen.contains("line 6: Failed to parse msgstr string literal"), "{}", screen);
+        app.perform(Action::Input(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE))).unwrap();
+        assert_eq!(app.filtered_indices[app.current_entry], 1);
+
+        // The file written has no unreadable lines left to warn about
+        let dir = tempfile::tempdir().unwrap();
+        app.save_as(dir.path().join("ru.po").to_str().unwrap());
+        assert!(app.po_file.parse_issues().is_empty());
+        let screen = screen_text(&render(&mut app, 120, 20));
+        assert!(!screen.contains("parse warning"), "{}", screen);
+    }
+
+    #[test]
+    fn test_qa_panel_applies_fix() {
+        let mut app = app_with_entries(2);
+        app.po_file.entries[1].set_msgstr("Открыть...".to_string());
+        app.perform(Action::ToggleQaPanel).unwrap();
+
+        app.perform(Action::Input(KeyEvent::new(KeyCode::Char('f'), KeyModifiers::NONE))).unwrap();
+        assert_eq!(app.po_file.entries[1].msgstr, "Открыть…");
+        assert!(app.is_modified());
+        match app.overlay {
+            Some(Overlay::Qa(ref panel)) => assert!(panel.issues.is_empty()),
+            ref other => panic!("unexpected overlay {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_qa_panel_acknowledges_issue() {
+        let mut app = app_with_entries(2);
+        app.po_file.entries[1].set_msgstr("Открыть...".to_string());
+        app.perform(Action::ToggleQaPanel).unwrap();
+        let key = |c| Action::Input(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE));
+        let issues = |app: &App| match app.overlay {
+            Some(Overlay::Qa(ref panel)) => panel.issues.clone(),
+            ref other => panic!("unexpected overlay {:?}", other),
+        };
+
+        app.perform(key('a')).unwrap();
+        assert_eq!(app.po_file.entries[1].comments, vec!["poterm: ignore=ellipsis"]);
+        assert!(app.is_modified());
+        assert!(issues(&app).is_empty());
+
+        app.perform(key('s')).unwrap();
+        assert!(issues(&app)[0].suppressed);
+        app.perform(key('a')).unwrap();
+        assert!(app.po_file.entries[1].comments.is_empty());
+        assert!(!issues(&app)[0].suppressed);
+    }
+
+    #[test]
+    fn test_glossary_terms_and_check() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ru.po");
+        std::fs::write(&path, "msgid \"Close the tab\"\nmsgstr \"Закрыть таб\"\n").unwrap();
+        std::fs::write(dir.path().join("terms.tsv"), "tab\tвкладка\tтаб\n").unwrap();
+        let config = Config {
+            glossary: Some(PathBuf::from("terms.tsv")),
+            ..Config::default()
+        };
+        let mut app = App::new(PoFile::from_file(&path).unwrap(), config);
+        let buffer = render(&mut app, 120, 30);
+        let screen: String = (0..30)
+            .map(|y| (0..120).map(|x| buffer[(x, y)].symbol()).collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n");
+        assert!(screen.contains("tab → вкладка  (not таб)"));
+
+        let issues = app.qa_issues(false);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].issue.message, "\"tab\" is translated as \"вкладка\", not \"таб\"");
+
+        std::fs::write(dir.path().join("terms.tsv"), "file\tфайл\n").unwrap();
+        app.perform(Action::ReloadGlossary).unwrap();
+        assert!(app.qa_issues(false).is_empty());
+        assert_eq!(app.status_message.as_deref(), Some("Glossary reloaded: 1 terms"));
+    }
+
+    #[test]
+    fn test_changed_entries_and_index_cycle() {
+        let mut app = app_with_entries(3);
+        assert!(!app.is_entry_changed(1));
+
+        app.next_entry();
+        app.start_editing();
+        app.edit_text.push('!');
+        app.stop_editing();
+        assert!(app.is_entry_changed(1));
+        assert!(!app.is_entry_changed(0));
+
+        app.perform(Action::CycleIndexColumn).unwrap();
+        assert_eq!(app.list_columns.index, IndexMode::Filtered);
+    }
+
+    #[test]
+    fn test_issue_counts_follow_revisions() {
+        let mut app = app_with_entries(2);
+        assert_eq!(app.issue_counts(), &[0, 0]);
+
+        app.po_file.entries[0].set_msgstr("Ждите...".to_string());
+        // Unchanged revision keeps the cached counts
+        assert_eq!(app.issue_counts(), &[0, 0]);
+        app.po_file.mark_modified();
+        assert_eq!(app.issue_counts(), &[1, 0]);
+    }
+
+    #[test]
+    fn test_format_issues_on_commit_and_save() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ru.po");
+        std::fs::write(&path, "#, c-format\nmsgid \"%d files\\n\"\nmsgstr \"\"\n").unwrap();
+        let mut app = App::new(PoFile::from_file(&path).unwrap(), Config::default());
+
+        app.start_editing();
+        type_keys(&mut app, "%s файлов");
+        app.perform(Action::Input(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE))).unwrap();
+        assert!(app.status_is_error());
+        assert_eq!(
+            app.status_message.as_deref(),
+            Some("error: Argument 1 is %d in the msgid but %s in the translation [c-format] (+1 more)")
+        );
+        let screen = screen_text(&render(&mut app, 200, 40));
+        assert!(screen.contains("error: The msgid ends with a newline, the translation does not [newline]"), "{}", screen);
+
+        app.perform(Action::Save).unwrap();
+        assert_eq!(app.status_message.as_deref(), Some("Saved ru.po (1 entry), 1 entry with errors (F8 lists them)"));
+    }
+
+    #[test]
+    fn test_relative_time() {
+        let now = chrono::Utc::now();
+        assert_eq!(relative_time(now, now), "just now");
+        assert_eq!(relative_time(now - chrono::Duration::minutes(5), now), "5 minutes ago");
+        assert_eq!(relative_time(now - chrono::Duration::hours(1), now), "1 hour ago");
+        assert_eq!(relative_time(now - chrono::Duration::hours(30), now), "yesterday");
+        assert_eq!(relative_time(now - chrono::Duration::days(3), now), "3 days ago");
+        assert_eq!(relative_time(now - chrono::Duration::days(70), now), "2 months ago");
+        assert_eq!(relative_time(now - chrono::Duration::days(800), now), "2 years ago");
+        // Clock skew between machines
+        assert_eq!(relative_time(now + chrono::Duration::hours(2), now), "just now");
+    }
+
+    #[test]
+    fn test_session_counts_completed_entries() {
+        let mut app = app_with_entries(3);
+        app.po_file.entries[1].toggle_fuzzy();
+
+        app.perform(Action::ToggleFuzzy).unwrap();
+        app.perform(Action::Next).unwrap();
+        app.perform(Action::MarkDone).unwrap();
+        assert_eq!(app.session.completed(), 1);
+        assert_eq!(app.session.words_translated(), 2);
+        assert_eq!(app.session.edited(), 2);
+
+        app.perform(Action::ShowStats).unwrap();
+        assert_eq!(app.overlay, Some(Overlay::Stats));
+        assert!(app.session_summary().unwrap().contains("edited 2 entries"));
+    }
+
+    #[test]
+    fn test_recover_from_swap_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ru.po");
+        std::fs::write(&path, "msgid \"Open\"\nmsgstr \"\"\n\nmsgid \"Quit\"\nmsgstr \"Выход\"\n").unwrap();
+        let open = || App::new(PoFile::from_file(&path).unwrap(), Config::default()).with_journal();
+        let key = |code: KeyCode| Action::Input(KeyEvent::new(code, KeyModifiers::NONE));
+
+        // A session that dies after two edits leaves them in the swap file
+        let mut app = open();
+        assert!(app.overlay.is_none());
+        app.start_editing();
+        type_keys(&mut app, "Открыть");
+        app.perform(key(KeyCode::Enter)).unwrap();
+        app.perform(Action::Next).unwrap();
+        app.perform(Action::ToggleFuzzy).unwrap();
+        drop(app);
+
+        let mut app = open();
+        let Some(Overlay::Recover(ref swap)) = app.overlay else {
+            panic!("no recovery offered: {:?}", app.overlay);
+        };
+        assert_eq!(swap.changes.len(), 2);
+        assert!(!swap.running);
+        let screen = screen_text(&render(&mut app, 100, 30));
+        assert!(screen.contains("Recover 2 unsaved changes from"), "{}", screen);
+
+        app.perform(key(KeyCode::Char('r'))).unwrap();
+        assert_eq!(app.status_message.as_deref(), Some("Recovered 2 changes"));
+        assert_eq!(app.po_file.entries[0].msgstr, "Открыть");
+        assert!(app.po_file.entries[0].is_translated);
+        assert!(app.po_file.entries[1].is_fuzzy);
+        assert!(app.is_modified());
+        // The recovered edits are journaled again until saved
+        assert_eq!(Swap::find(&path).unwrap().changes.len(), 2);
+        app.save().unwrap();
+        assert!(Swap::find(&path).unwrap().changes.is_empty());
+        app.close_journal();
+        assert!(Swap::find(&path).is_none());
+
+        // Discarding keeps the file as saved
+        let mut app = open();
+        app.perform(Action::ToggleFuzzy).unwrap();
+        drop(app);
+        let mut app = open();
+        app.perform(key(KeyCode::Char('d'))).unwrap();
+        assert!(app.overlay.is_none());
+        assert!(!app.po_file.entries[0].is_fuzzy);
+        assert!(Swap::find(&path).unwrap().changes.is_empty());
+
+        // Read-only sessions change nothing, so they keep no swap file
+        app.close_journal();
+        let app = App::new(PoFile::from_file(&path).unwrap(), Config::default())
+            .with_read_only(true)
+            .with_journal();
+        assert!(app.journal.is_none());
+        assert!(Swap::find(&path).is_none());
+    }
+
+    #[test]
+    fn test_save_as_prompt() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("po")).unwrap();
+        std::fs::write(dir.path().join("po/de.po"), "").unwrap();
+        let mut app = app_with_entries(2);
+        app.po_file.path = None;
+
+        // Ctrl+S asks where a new file goes
+        press(&mut app, KeyModifiers::CONTROL, KeyCode::Char('s'));
+        assert!(matches!(app.overlay, Some(Overlay::Prompt(ref p)) if p.kind == PromptKind::SaveAs));
+        type_keys(&mut app, &format!("{}/p", dir.path().display()));
+        press(&mut app, KeyModifiers::NONE, KeyCode::Tab);
+        type_keys(&mut app, "de.po");
+        press(&mut app, KeyModifiers::NONE, KeyCode::Enter);
+        assert_eq!(app.status_message.as_deref(), Some(format!("{}/po/de.po already exists", dir.path().display()).as_str()));
+        assert_eq!(app.po_file.path, None);
+
+        press(&mut app, KeyModifiers::NONE, KeyCode::F(12));
+        type_keys(&mut app, &format!("{}/po/ru.po", dir.path().display()));
+        press(&mut app, KeyModifiers::NONE, KeyCode::Enter);
+        let path = dir.path().join("po/ru.po");
+        assert_eq!(app.po_file.path.as_deref(), Some(path.as_path()));
+        assert_eq!(app.status_message.as_deref(), Some("Saved ru.po (2 entries)"));
+        assert_eq!(PoFile::from_file(&path).unwrap().entries.len(), 2);
+        let screen = screen_text(&render(&mut app, 100, 20));
+        assert!(screen.contains("Poterm - ru.po"), "{}", screen);
+
+        // The prompt starts from the current path
+        press(&mut app, KeyModifiers::NONE, KeyCode::F(12));
+        match app.overlay {
+            Some(Overlay::Prompt(ref prompt)) => assert_eq!(prompt.input.text, path.display().to_string()),
+            ref other => panic!("unexpected overlay {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_autosave() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ru.po");
+        std::fs::write(&path, "msgid \"Open\"\nmsgstr \"\"\n").unwrap();
+        let config = Config {
+            autosave: 60,
+            autosave_in_place: true,
+            ..Config::default()
+        };
+        let mut app = App::new(PoFile::from_file(&path).unwrap(), config);
+        let start = app.last_save;
+        app.autosave_if_due(start + Duration::from_secs(120));
+        assert_eq!(app.status_message, None);
+
+        app.start_editing();
+        type_keys(&mut app, "Открыть");
+        app.perform(Action::Input(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE))).unwrap();
+        app.autosave_if_due(start + Duration::from_secs(30));
+        assert!(app.is_modified());
+        app.autosave_if_due(start + Duration::from_secs(60));
+        assert!(!app.is_modified());
+        assert_eq!(app.status_message.as_deref(), Some("Autosaved ru.po"));
+        assert!(std::fs::read_to_string(&path).unwrap().contains("Открыть"));
+    }
+
+    #[test]
+    fn test_autosave_to_recovery_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ru.po");
+        let original = "msgid \"Open\"\nmsgstr \"\"\n";
+        std::fs::write(&path, original).unwrap();
+        let recovery = journal::recovery_path(&path);
+        let config = Config {
+            autosave: 60,
+            ..Config::default()
+        };
+        let mut app = App::new(PoFile::from_file(&path).unwrap(), config.clone()).with_journal();
+        app.start_editing();
+        type_keys(&mut app, "Открыть");
+        app.perform(Action::Input(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE))).unwrap();
+        let start = app.last_save;
+        app.autosave_if_due(start + Duration::from_secs(60));
+        assert!(app.is_modified());
+        assert_eq!(app.status_message.as_deref(), Some("Autosaved to .ru.po.poterm-recover"));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), original);
+        assert!(std::fs::read_to_string(&recovery).unwrap().contains("Открыть"));
+        // Nothing changed since, so nothing is written
+        app.status_message = None;
+        app.autosave_if_due(start + Duration::from_secs(120));
+        assert_eq!(app.status_message, None);
+
+        // The session dies without saving; the next one offers the changes
+        // and, once they are restored, does not replay its swap file too
+        drop(app);
+        assert_eq!(Swap::find(&path).unwrap().changes.len(), 1);
+        let mut app = App::new(PoFile::from_file(&path).unwrap(), config.clone()).with_journal();
+        assert!(matches!(app.overlay, Some(Overlay::RestoreAutosave(_))));
+        app.perform(Action::Input(KeyEvent::new(KeyCode::Char('r'), KeyModifiers::NONE))).unwrap();
+        assert_eq!(app.overlay, None);
+        assert!(app.journal.is_some());
+        assert!(Swap::find(&path).unwrap().changes.is_empty());
+        assert_eq!(app.po_file.entries[0].msgstr, "Открыть");
+        assert!(app.is_modified());
+        app.perform(Action::Save).unwrap();
+        assert!(!recovery.exists());
+
+        std::fs::write(&recovery, "msgid \"Open\"\nmsgstr \"Отворить\"\n").unwrap();
+        let mut app = App::new(PoFile::from_file(&path).unwrap(), config.clone()).with_journal();
+        app.perform(Action::Input(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE))).unwrap();
+        assert_eq!(app.recovery_file, None);
+        app.close_journal();
+        assert!(recovery.exists());
+
+        let mut app = App::new(PoFile::from_file(&path).unwrap(), config).with_journal();
+        app.perform(Action::Input(KeyEvent::new(KeyCode::Char('d'), KeyModifiers::NONE))).unwrap();
+        assert!(!recovery.exists());
+        assert_eq!(app.po_file.entries[0].msgstr, "Открыть");
+        assert!(!app.is_modified());
+    }
+
+    #[test]
+    fn test_save_over_file_changed_on_disk() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ru.po");
+        std::fs::write(&path, "msgid \"Open\"\nmsgstr \"\"\n").unwrap();
+        let mut app = App::new(PoFile::from_file(&path).unwrap(), Config::default());
+        app.start_editing();
+        type_keys(&mut app, "Открыть");
+        app.perform(Action::Input(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE))).unwrap();
+
+        let merged = "msgid \"Open\"\nmsgstr \"\"\n\nmsgid \"Close\"\nmsgstr \"\"\n";
+        std::fs::write(&path, merged).unwrap();
+        app.check_disk_if_due(app.disk_checked + DISK_CHECK_INTERVAL);
+        assert!(app.disk_changed);
+        assert_eq!(app.status_message.as_deref(), Some("ru.po changed on disk"));
+
+        app.perform(Action::Save).unwrap();
+        assert_eq!(app.overlay, Some(Overlay::ChangedOnDisk { selected: 0 }));
+        app.perform(Action::Input(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE))).unwrap();
+        assert_eq!(app.overlay, None);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), merged);
+
+        app.perform(Action::Save).unwrap();
+        app.perform(Action::Input(KeyEvent::new(KeyCode::Char('o'), KeyModifiers::NONE))).unwrap();
+        assert!(!app.is_modified());
+        assert!(!app.disk_changed);
+        assert!(std::fs::read_to_string(&path).unwrap().contains("Открыть"));
+
+        app.start_editing();
+        type_keys(&mut app, "!");
+        app.perform(Action::Input(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE))).unwrap();
+        std::fs::write(&path, merged).unwrap();
+        app.perform(Action::Save).unwrap();
+        app.perform(Action::Input(KeyEvent::new(KeyCode::Char('r'), KeyModifiers::NONE))).unwrap();
+        assert!(!app.is_modified());
+        assert_eq!(app.po_file.entries.len(), 2);
+        assert_eq!(app.po_file.entries[0].msgstr, "");
+    }
+
+    #[test]
+    fn test_save_reports_in_status_line() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ru.po");
+        std::fs::write(&path, "msgid \"Open\"\nmsgstr \"\"\n\nmsgid Quit\nmsgstr Выход\n").unwrap();
+        let mut app = App::new(PoFile::from_file(&path).unwrap(), Config::default());
+        assert!(app.status_is_error());
+        let message = app.status_message.clone().unwrap();
+        assert!(message.starts_with("2 parse warnings (F8 lists them), saving drops the lines skipped: Line 4:"), "{}", message);
+
+        app.perform(Action::Save).unwrap();
+        assert_eq!(app.status_message.as_deref(), Some("Nothing to save"));
+        app.start_editing();
+        type_keys(&mut app, "Открыть");
+        app.perform(Action::Input(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE))).unwrap();
+        app.perform(Action::Save).unwrap();
+        assert_eq!(app.status_message.as_deref(), Some("Saved ru.po (1 entry)"));
+
+        // Informational messages expire, errors stay until the next key
+        let now = Instant::now();
+        app.expire_status(now);
+        app.expire_status(now + STATUS_TIMEOUT);
+        assert_eq!(app.status_message, None);
+
+        // A failed save keeps the editor running with the changes
+        app.po_file.path = Some(dir.path().join("missing").join("ru.po"));
+        app.perform(Action::ToggleFuzzy).unwrap();
+        assert_eq!(app.perform(Action::SaveAndQuit).unwrap(), Outcome::Done);
+        assert!(app.is_modified());
+        assert!(app.status_message.as_deref().unwrap().starts_with("Save failed: "));
+        app.expire_status(now);
+        app.expire_status(now + STATUS_TIMEOUT);
+        assert!(app.status_is_error());
+        let screen = screen_text(&render(&mut app, 120, 20));
+        assert!(screen.contains("Save failed: "), "{}", screen);
+        app.perform(Action::Next).unwrap();
+        assert_eq!(app.status_message, None);
+    }
+
+    #[test]
+    fn test_save_appends_progress_log() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ru.po");
+        std::fs::write(&path, "msgid \"Open\"\nmsgstr \"\"\n\nmsgid \"Quit\"\nmsgstr \"Выход\"\n").unwrap();
+        let log = dir.path().join("progress.csv");
+        let config = Config {
+            progress_log: Some(log.clone()),
+            ..Config::default()
+        };
+        let mut app = App::new(PoFile::from_file(&path).unwrap(), config);
+
+        app.save().unwrap();
+        app.start_editing();
+        type_keys(&mut app, "Открыть");
+        app.perform(Action::Input(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE))).unwrap();
+        app.save().unwrap();
+        let records = history::read(&log, &path.canonicalize().unwrap());
+        assert_eq!(records.iter().map(|r| r.translated).collect::<Vec<_>>(), vec![1, 2]);
+
+        // A log that cannot be written leaves the save alone
+        app.config.progress_log = Some(dir.path().join("ru.po").join("progress.csv"));
+        app.save().unwrap();
+        assert!(app.status_message.as_deref().unwrap().starts_with("Saved, but the progress log failed"));
+    }
+
+    #[test]
+    fn test_list_shows_context_and_flag_badges() {
+        let mut app = app_with_entries(4);
+        app.po_file.entries[0].msgctxt = Some("column header".to_string());
+        app.po_file.entries[1].flags = vec!["c-format".to_string(), "no-wrap".to_string()];
+        app.po_file.entries[2].msgctxt = Some("menu".to_string());
+        app.po_file.entries[2].flags = vec!["c-format".to_string()];
+        let rows = |app: &mut App| -> Vec<String> {
+            let buffer = render(app, 100, 20);
+            (4..8).map(|y| (0..40).map(|x| buffer[(x, y)].symbol()).collect()).collect()
+        };
+        assert_eq!(
+            rows(&mut app),
+            [
+                "│► ✓ 1 [column head…] test 0           │",
+                "│  ✓ 2 test 1 ⓒ⊘                       │",
+                "│  ✓ 3 [menu] test 2 ⓒ                 │",
+                "│  ✓ 4 test 3                          │",
+            ]
+        );
+        let buffer = render(&mut app, 100, 20);
+        assert_eq!(buffer[(7, 4)].fg, Color::DarkGray);
+        assert_eq!(buffer[(14, 5)].fg, Color::Cyan);
+
+        app.list_columns.badges = false;
+        assert_eq!(rows(&mut app)[1], "│  ✓ 2 test 1                          │");
+    }
+
+    #[test]
+    fn test_header_shows_output_redirection() {
+        let mut po_file = app_with_entries(1).po_file;
+        po_file.path = Some(PathBuf::from("/tmp/ru-experiment.po"));
+        let mut app = App::new(po_file, Config::default()).with_source(Some(PathBuf::from("po/ru.po")));
+        let screen = screen_text(&render(&mut app, 100, 20));
+        assert!(screen.contains("Poterm - ru.po → ru-experiment.po"), "{}", screen);
+    }
+
+    #[test]
+    fn test_emergency_save() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ru.po");
+        let content = "msgid \"Open\"\nmsgstr \"\"\n";
+        std::fs::write(&path, content).unwrap();
+        let mut app = App::new(PoFile::from_file(&path).unwrap(), Config::default());
+        assert_eq!(app.emergency_save().unwrap(), None);
+
+        // An open edit counts as a change
+        app.start_editing();
+        type_keys(&mut app, "Открыть");
+        let recovered = dir.path().join("ru.po.recovered");
+        assert_eq!(app.emergency_save().unwrap(), Some(recovered.clone()));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), content);
+        let saved = PoFile::from_file(&recovered).unwrap();
+        assert_eq!(saved.entries[0].msgstr, "Открыть");
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_swap_file_of_running_instance() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ru.po");
+        std::fs::write(&path, "msgid \"Open\"\nmsgstr \"\"\n").unwrap();
+        // The test runner stands in for another poterm
+        let swap = format!("pid = {}\nstarted = \"2026-10-16T12:41:00+00:00\"\n", std::os::unix::process::parent_id());
+        std::fs::write(journal::swap_path(&path), &swap).unwrap();
+
+        let mut app = App::new(PoFile::from_file(&path).unwrap(), Config::default()).with_journal();
+        assert!(matches!(app.overlay, Some(Overlay::Recover(Swap { running: true, .. }))));
+        let screen = screen_text(&render(&mut app, 100, 30));
+        assert!(screen.contains("Another poterm (pid"), "{}", screen);
+
+        assert!(screen.contains("o/Esc: Open without a swap file  q: Quit"), "{}", screen);
+
+        // Neither recovering nor discarding may touch the other's journal
+        for code in [KeyCode::Char('r'), KeyCode::Char('d')] {
+            app.perform(Action::Input(KeyEvent::new(code, KeyModifiers::NONE))).unwrap();
+            assert!(matches!(app.overlay, Some(Overlay::Recover(_))));
+            assert!(app.journal.is_none());
+        }
+        assert_eq!(std::fs::read_to_string(journal::swap_path(&path)).unwrap(), swap);
+        assert_eq!(
+            app.perform(Action::Input(KeyEvent::new(KeyCode::Char('q'), KeyModifiers::NONE))).unwrap(),
+            Outcome::Quit
+        );
+
+        let mut app = App::new(PoFile::from_file(&path).unwrap(), Config::default()).with_journal();
+        app.perform(Action::Input(KeyEvent::new(KeyCode::Char('o'), KeyModifiers::NONE))).unwrap();
+        assert!(app.overlay.is_none());
+        assert!(app.journal.is_none());
+        assert_eq!(std::fs::read_to_string(journal::swap_path(&path)).unwrap(), swap);
+    }
+
+    #[test]
+    fn test_time_in_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ru.po");
+        std::fs::write(&path, "msgid \"a\"\nmsgstr \"\"\n").unwrap();
+        let screen = |app: &mut App| {
+            let buffer = render(app, 100, 30);
+            (0..30)
+                .map(|y| (0..100).map(|x| buffer[(x, y)].symbol()).collect::<String>())
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
+
+        let mut app = App::new(PoFile::from_file(&path).unwrap(), Config::default());
+        app.perform(Action::ShowStats).unwrap();
+        assert!(!screen(&mut app).contains("Time in file"));
+
+        let config = Config {
+            track_time: true,
+            ..Config::default()
+        };
+        let mut app = App::new(PoFile::from_file(&path).unwrap(), config);
+        app.state.add_time(path.canonicalize().unwrap(), Duration::from_secs(3 * 3600 + 12 * 60));
+        app.resolve_key(KeyEvent::new(KeyCode::F(7), KeyModifiers::NONE));
+        app.perform(Action::ShowStats).unwrap();
+        assert!(screen(&mut app).contains("Time in file: 3h 12m (<1m this session)"));
+
+        app.perform(Action::Input(KeyEvent::new(KeyCode::Char('r'), KeyModifiers::NONE))).unwrap();
+        assert_eq!(app.overlay, Some(Overlay::Stats));
+        assert!(screen(&mut app).contains("Time in file: <1m (<1m this session)"));
+    }
+
+    #[test]
+    fn test_open_file_asks_about_unsaved_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let other = dir.path().join("fr.po");
+        std::fs::write(&other, "msgid \"a\"\nmsgstr \"\"\n").unwrap();
+
+        let mut app = app_with_entries(3);
+        app.perform(Action::ToggleFuzzy).unwrap();
+
+        app.request_open(other.clone());
+        assert_eq!(app.overlay, Some(Overlay::UnsavedChanges { path: other.clone() }));
+        app.perform(Action::Input(KeyEvent::new(KeyCode::Char('x'), KeyModifiers::NONE))).unwrap();
+        assert!(matches!(app.overlay, Some(Overlay::UnsavedChanges { .. })));
+
+        app.perform(Action::Input(KeyEvent::new(KeyCode::Char('d'), KeyModifiers::NONE))).unwrap();
+        assert_eq!(app.overlay, None);
+        assert_eq!(app.po_file.path.as_deref(), Some(other.as_path()));
+        assert_eq!(app.po_file.entries.len(), 1);
+        assert!(!app.is_modified());
+        // Session counters carry over to the next file
+        assert_eq!(app.session.edited(), 1);
+    }
+
+    #[test]
+    fn test_recent_files_restore_view() {
+        let dir = tempfile::tempdir().unwrap();
+        let ru = dir.path().join("ru.po");
+        let fr = dir.path().join("fr.po");
+        std::fs::write(&ru, "msgid \"a\"\nmsgstr \"\"\n\nmsgid \"b\"\nmsgstr \"\"\n\nmsgid \"c\"\nmsgstr \"\"\n").unwrap();
+        std::fs::write(&fr, "msgid \"a\"\nmsgstr \"\"\n").unwrap();
+
+        let config = Config {
+            start_at: StartAt::LastSession,
+            ..Config::default()
+        };
+        let mut app = App::new(PoFile::from_file(&ru).unwrap(), config).with_state(StateStore::default());
+        app.perform(Action::ToggleUntranslatedFilter).unwrap();
+        app.perform(Action::Last).unwrap();
+
+        app.request_open(fr.clone());
+        assert_eq!(app.filters.status, FilterMode::All);
+        assert_eq!(app.state.recent()[0].path, ru.canonicalize().unwrap());
+
+        app.perform(Action::RecentFiles).unwrap();
+        app.perform(Action::Input(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE))).unwrap();
+        assert_eq!(app.po_file.path.as_deref(), Some(ru.canonicalize().unwrap().as_path()));
+        assert_eq!(app.filters.status, FilterMode::Untranslated);
+        assert_eq!(app.get_current_entry().unwrap().msgid, "c");
+        assert_eq!(app.state.recent()[0].path, fr.canonicalize().unwrap());
+    }
+
+    #[test]
+    fn test_reload_keeps_view() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ru.po");
+        std::fs::write(&path, "msgid \"a\"\nmsgstr \"\"\n\nmsgid \"b\"\nmsgstr \"\"\n\nmsgid \"c\"\nmsgstr \"\"\n").unwrap();
+        let mut app = App::new(PoFile::from_file(&path).unwrap(), Config::default());
+        app.perform(Action::ToggleUntranslatedFilter).unwrap();
+        app.perform(Action::Next).unwrap();
+        app.start_editing();
+        type_keys(&mut app, "б");
+        app.perform(Action::Input(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE))).unwrap();
+        assert!(app.is_modified());
+
+        std::fs::write(&path, "msgid \"a\"\nmsgstr \"а\"\n\nmsgid \"b\"\nmsgstr \"\"\n\nmsgid \"c\"\nmsgstr \"\"\n").unwrap();
+        app.perform(Action::Reload).unwrap();
+        assert_eq!(app.overlay, Some(Overlay::ConfirmReload));
+        app.perform(Action::Input(KeyEvent::new(KeyCode::Char('y'), KeyModifiers::NONE))).unwrap();
+        assert!(!app.is_modified());
+        assert_eq!(app.filters.status, FilterMode::Untranslated);
+        assert_eq!(app.filtered_indices, [1, 2]);
+        assert_eq!(app.get_current_entry().unwrap().msgid, "b");
+        assert_eq!(app.get_current_entry().unwrap().msgstr, "");
+        assert_eq!(app.status_message.as_deref(), Some("Reloaded ru.po (3 entries)"));
+    }
+
+    #[test]
+    fn test_reload_keeps_read_only_and_source() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("ru.po");
+        let output = dir.path().join("ru-experiment.po");
+        std::fs::write(&source, "msgid \"a\"\nmsgstr \"\"\n").unwrap();
+        let mut po_file = PoFile::from_file(&source).unwrap();
+        po_file.path = Some(output.clone());
+        let mut app = App::new(po_file, Config::default()).with_source(Some(source.clone())).with_read_only(true);
+
+        std::fs::write(&source, "msgid \"a\"\nmsgstr \"а\"\n").unwrap();
+        app.perform(Action::Reload).unwrap();
+        assert!(app.read_only);
+        assert_eq!(app.source.as_ref(), Some(&source));
+        assert_eq!(app.po_file.path.as_ref(), Some(&output));
+        assert_eq!(app.po_file.entries[0].msgstr, "а");
+        assert_eq!(app.status_message.as_deref(), Some("Reloaded ru-experiment.po (1 entry)"));
+    }
+
+    #[test]
+    fn test_merged_entries_changed_by_key() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ru.po");
+        let pot = dir.path().join("app.pot");
+        std::fs::write(&path, "msgid \"Open\"\nmsgstr \"Открыть\"\n\nmsgid \"Quit\"\nmsgstr \"Выход\"\n").unwrap();
+        std::fs::write(&pot, "msgid \"Quit\"\nmsgstr \"\"\n\nmsgid \"New\"\nmsgstr \"\"\n").unwrap();
+        let mut app = App::new(PoFile::from_file(&path).unwrap(), Config::default());
+        app.merge_template(&pot.display().to_string());
+        assert_eq!(app.po_file.entries[0].msgid, "Quit");
+        assert!(!app.is_entry_changed(0));
+        assert!(app.is_entry_changed(1));
+    }
+
+    #[test]
+    fn test_merge_template_prompt() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ru.po");
+        let pot = dir.path().join("app.pot");
+        std::fs::write(&path, "msgid \"Open\"\nmsgstr \"Открыть\"\n\nmsgid \"Quit\"\nmsgstr \"Выход\"\n").unwrap();
+        std::fs::write(&pot, "msgid \"New\"\nmsgstr \"\"\n\nmsgid \"Quit\"\nmsgstr \"\"\n").unwrap();
+        let mut app = App::new(PoFile::from_file(&path).unwrap(), Config::default());
+        app.perform(Action::Next).unwrap();
+
+        app.perform(Action::MergeTemplate).unwrap();
+        type_keys(&mut app, "app.p");
+        app.perform(Action::Input(KeyEvent::new(KeyCode::Tab, KeyModifiers::NONE))).unwrap();
+        assert!(matches!(app.overlay, Some(Overlay::Prompt(ref p)) if p.input.text == pot.display().to_string()));
+        app.perform(Action::Input(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE))).unwrap();
+        assert_eq!(app.status_message.as_deref(), Some("Merged app.pot: 1 new, 0 fuzzy, 1 obsolete"));
+        assert!(app.is_modified());
+        assert_eq!(app.filtered_indices, [0, 1]);
+        assert_eq!(app.get_current_entry().unwrap().msgid, "Quit");
+        assert_eq!(app.po_file.obsolete_entries.len(), 1);
+    }
+
+    #[test]
+    fn test_compile_mo() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ru.po");
+        std::fs::write(&path, "msgid \"Open\"\nmsgstr \"\"\n\nmsgid \"Quit\"\nmsgstr \"Выход\"\n").unwrap();
+        let mut app = App::new(PoFile::from_file(&path).unwrap(), Config::default());
+        app.start_editing();
+        type_keys(&mut app, "Открыть");
+        app.perform(Action::Input(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE))).unwrap();
+
+        app.perform(Action::CompileMo).unwrap();
+        assert_eq!(app.status_message.as_deref(), Some("Compiled ru.mo: 2 messages"));
+        let (expected, _) = mo::to_mo(&app.po_file).unwrap();
+        assert_eq!(std::fs::read(dir.path().join("ru.mo")).unwrap(), expected);
+        assert!(app.is_modified());
+    }
+
+    #[test]
+    fn test_translation_memory() {
+        let dir = tempfile::tempdir().unwrap();
+        let other = dir.path().join("other.po");
+        std::fs::write(&other, "msgid \"Open file\"\nmsgstr \"Открыть файл\"\n\nmsgid \"Open files\"\nmsgstr \"Открыть файлы\"\n").unwrap();
+        let memory = TranslationMemory::load(&[other]).unwrap();
+        let po_file = PoFile::parse("msgid \"Open file\"\nmsgstr \"\"\n\nmsgid \"Quit\"\nmsgstr \"\"\n").unwrap();
+        let mut app = App::new(po_file, Config::default()).with_memory(Some(memory));
+        assert_eq!(app.status_message.as_deref(), Some("Translation memory: 2 translations from 1 catalog"));
+        let screen = screen_text(&render(&mut app, 100, 40));
+        assert!(screen.contains("Alt+1 100% Открыть файл  (other.po)"));
+        assert!(screen.contains("Alt+2  90% Открыть файлы  (other.po)"));
+
+        app.perform(Action::AcceptSuggestion(2)).unwrap();
+        assert_eq!(app.po_file.entries[0].msgstr, "Открыть файлы");
+        assert!(app.po_file.entries[0].is_fuzzy);
+        assert_eq!(app.status_message.as_deref(), Some("Used the translation from other.po, marked fuzzy"));
+        app.perform(Action::Undo).unwrap();
+        assert_eq!(app.po_file.entries[0].msgstr, "");
+        assert!(!app.po_file.entries[0].is_fuzzy);
+
+        // In the editor the suggestion replaces the buffer
+        app.start_editing();
+        type_keys(&mut app, "Откр");
+        app.perform(Action::AcceptSuggestion(1)).unwrap();
+        assert_eq!(app.edit_text, "Открыть файл");
+        assert_eq!(app.status_message.as_deref(), Some("Used the translation from other.po; Enter applies it, marked fuzzy"));
+        assert_eq!(app.po_file.entries[0].msgstr, "");
+        assert!(!app.po_file.entries[0].is_fuzzy);
+        app.perform(Action::Input(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE))).unwrap();
+        assert_eq!(app.po_file.entries[0].msgstr, "Открыть файл");
+        assert!(app.po_file.entries[0].is_fuzzy);
+
+        app.perform(Action::Next).unwrap();
+        assert_eq!(app.perform(Action::AcceptSuggestion(1)).unwrap(), Outcome::Blocked);
+        assert_eq!(app.status_message.as_deref(), Some("No translation memory suggestion 1"));
+        assert!(!screen_text(&render(&mut app, 100, 40)).contains("Translation Memory"));
+    }
+
+    #[test]
+    fn test_export_and_import() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ru.po");
+        let csv = dir.path().join("ru.csv");
+        std::fs::write(&path, "msgid \"Open\"\nmsgstr \"\"\n\n#, fuzzy\nmsgid \"Quit\"\nmsgstr \"Выйти\"\n").unwrap();
+        let mut app = App::new(PoFile::from_file(&path).unwrap(), Config::default());
+
+        app.perform(Action::Export).unwrap();
+        assert!(matches!(app.overlay, Some(Overlay::Prompt(ref p)) if p.input.text == csv.display().to_string()));
+        app.perform(Action::Input(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE))).unwrap();
+        assert_eq!(app.status_message.as_deref(), Some("Exported 2 entries to ru.csv"));
+        let exported = std::fs::read_to_string(&csv).unwrap();
+        std::fs::write(&csv, exported.replace(",Open,,,,", ",Open,,Открыть,,").replace("Выйти,yes", "Выход,")).unwrap();
+
+        app.perform(Action::Import).unwrap();
+        type_keys(&mut app, "ru.csv");
+        app.perform(Action::Input(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE))).unwrap();
+        assert_eq!(app.status_message.as_deref(), Some("Imported ru.csv: 2 matched, 0 skipped, 0 unknown"));
+        assert!(app.is_modified());
+        assert_eq!(app.po_file.entries[0].msgstr, "Открыть");
+        assert_eq!(app.po_file.entries[1].msgstr, "Выход");
+        assert!(!app.po_file.entries[1].is_fuzzy);
+
+        // One undo step takes back the whole import
+        app.perform(Action::Undo).unwrap();
+        assert_eq!(app.po_file.entries[0].msgstr, "");
+        assert!(app.po_file.entries[1].is_fuzzy);
+        assert!(!app.is_modified());
+    }
+
+    #[test]
+    fn test_template_mode() {
+        let dir = tempfile::tempdir().unwrap();
+        let pot = dir.path().join("app.pot");
+        std::fs::write(&pot, "msgid \"\"\nmsgstr \"PO-Revision-Date: YEAR-MO-DA HO:MI+ZONE\\n\"\n\nmsgid \"Open\"\nmsgstr \"\"\n").unwrap();
+
+        let mut app = App::new(PoFile::from_file(&pot).unwrap(), Config::default());
+        assert!(app.template_mode);
+        app.edit_field = EditField::Msgstr;
+        assert_eq!(app.perform(Action::StartEditing).unwrap(), Outcome::Blocked);
+        assert_eq!(app.status_message.as_deref(), Some(TEMPLATE_MESSAGE));
+        assert!(!app.with_force_po(true).template_mode);
+
+        let mut app = App::new(PoFile::from_file(&pot).unwrap(), Config::default());
+        app.perform(Action::CreateFromTemplate).unwrap();
+        type_keys(&mut app, "pt_BR");
+        app.perform(Action::Input(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE))).unwrap();
+
+        let po_path = dir.path().join("pt_BR.po");
+        assert_eq!(app.po_file.path.as_deref(), Some(po_path.as_path()));
+        assert!(!app.template_mode);
+        assert_eq!(app.po_file.language(), Some("pt_BR"));
+        assert_eq!(PoFile::from_file(&po_path).unwrap().entries.len(), 1);
+        // The template was not stamped
+        assert!(std::fs::read_to_string(&pot).unwrap().contains("YEAR-MO-DA"));
+    }
+
+    #[test]
+    fn test_msgid_is_locked_until_confirmed() {
+        let mut app = app_with_entries(2);
+        app.edit_field = EditField::Msgid;
+
+        app.perform(Action::StartEditing).unwrap();
+        assert!(!app.editing);
+        assert_eq!(app.overlay, Some(Overlay::UnlockMsgid));
+        app.perform(Action::Input(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE))).unwrap();
+        assert_eq!(app.overlay, None);
+        assert!(!app.msgid_unlocked);
+
+        app.perform(Action::StartEditing).unwrap();
+        app.perform(Action::Input(KeyEvent::new(KeyCode::Char('y'), KeyModifiers::NONE))).unwrap();
+        assert!(app.editing);
+        assert_eq!(app.edit_text, "test 0");
+
+        let config = Config {
+            allow_msgid_edit: true,
+            ..Config::default()
+        };
+        let mut app = App::new(PoFile::default(), config);
+        app.po_file.entries.push(PoEntry::new());
+        app.update_filtered_indices();
+        app.edit_field = EditField::Msgid;
+        app.perform(Action::StartEditing).unwrap();
+        assert!(app.editing);
+    }
+
+    #[test]
+    fn test_filters_combine_and_keep_selection() {
+        let mut app = app_with_entries(6);
+        for i in [1, 3, 4] {
+            app.po_file.entries[i].flags.push("c-format".to_string());
+            app.po_file.entries[i].references.push(format!("src/export.c:{}", i));
+        }
+        app.po_file.entries[4].set_msgstr(String::new());
+        app.update_filtered_indices();
+
+        app.perform(Action::FilterByFlag).unwrap();
+        type_keys(&mut app, "c-format");
+        let enter = app.resolve_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE)).unwrap();
+        app.perform(enter).unwrap();
+        assert_eq!(app.filtered_indices, vec![1, 3, 4]);
+
+        app.perform(Action::FilterBySource).unwrap();
+        type_keys(&mut app, "export.c");
+        let enter = app.resolve_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE)).unwrap();
+        app.perform(enter).unwrap();
+        assert_eq!(app.filtered_indices, vec![1, 3, 4]);
+
+        // Selecting entry 3, then hiding it moves to the next listed entry
+        app.next_entry();
+        app.set_mark(1);
+        assert_eq!(app.filtered_indices[app.current_entry], 3);
+        app.toggle_untranslated_filter();
+        assert_eq!(app.filtered_indices, vec![4]);
+        assert_eq!(app.current_entry, 0);
+        app.toggle_untranslated_filter();
+        assert_eq!(app.filtered_indices[app.current_entry], 4);
+
+        app.perform(Action::ToggleMarkedFilter).unwrap();
+        assert_eq!(app.filtered_indices, vec![3]);
+        assert_eq!(app.filters.labels(), vec!["flag:c-format", "src:export.c", "marked"]);
+
+        // An empty prompt clears that predicate only
+        app.perform(Action::FilterByFlag).unwrap();
+        for _ in 0.."c-format".len() {
+            app.perform(Action::Input(KeyEvent::new(KeyCode::Backspace, KeyModifiers::NONE))).unwrap();
+        }
+        app.perform(Action::Input(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE))).unwrap();
+        assert_eq!(app.filters.flag, None);
+        assert!(app.filters.marked);
+
+        app.perform(Action::Cancel).unwrap();
+        assert!(app.filters.is_empty());
+        assert_eq!(app.filtered_indices.len(), 6);
+        assert_eq!(app.filtered_indices[app.current_entry], 3);
+    }
+
+    fn footer_texts(segments: &[FooterSegment]) -> Vec<&str> {
+        segments.iter().map(|segment| segment.text.as_str()).collect()
+    }
+
+    #[test]
+    fn test_footer_reflects_state() {
+        let mut app = app_with_entries(12);
+        let segments = footer_segments(&app);
+        assert_eq!(segments.len(), 1);
+        assert!(segments[0].hints);
+
+        app.perform(Action::QuickFilter).unwrap();
+        type_keys(&mut app, "1");
+        app.toggle_untranslated_filter();
+        app.toggle_untranslated_filter();
+        app.toggle_marked_filter();
+        app.status_message = Some("Saved".to_string());
+        let segments = footer_segments(&app);
+        assert_eq!(footer_texts(&segments)[..3], ["0 matches", "[marked]", "Saved"]);
+    }
+
+    #[test]
+    fn test_footer_shrinks_hints_first() {
+        let mut app = app_with_entries(3);
+        app.toggle_fuzzy_filter();
+        app.status_message = Some("Mark 1 set".to_string());
+
+        let full = footer_width(&footer_segments(&app));
+        let segments = fit_footer(footer_segments(&app), full);
+        assert!(segments[2].text.ends_with("F1: Help"));
+
+        // Hints lose items from the end before anything else goes
+        let segments = fit_footer(footer_segments(&app), 60);
+        assert_eq!(footer_texts(&segments)[..2], ["[Fuzzy]", "Mark 1 set"]);
+        assert!(segments[2].text.starts_with("Ctrl+Q: Quit"));
+        assert!(footer_width(&segments) <= 60);
+
+        // Then the lowest priority segments are dropped; the status stays
+        let segments = fit_footer(footer_segments(&app), 12);
+        assert_eq!(footer_texts(&segments), ["Mark 1 set"]);
+        let segments = fit_footer(footer_segments(&app), 4);
+        assert_eq!(footer_texts(&segments), ["Mark 1 set"]);
+    }
+
+    #[test]
+    fn test_plural_forms_picker() {
+        let mut app = app_with_entries(1);
+        app.po_file.set_header_field("Language".to_string(), "pt-BR".to_string());
+        app.po_file.modified = false;
+        app.toggle_metadata_mode();
+        select_metadata(&mut app, "Plural-Forms");
+        app.start_editing_selected_metadata();
+        assert!(!app.is_editing());
+
+        // The file's language is preselected, so Enter applies its rule
+        press(&mut app, KeyModifiers::NONE, KeyCode::Enter);
+        assert!(app.overlay.is_none());
+        assert_eq!(app.po_file.get_header()["Plural-Forms"], "nplurals=2; plural=(n > 1);");
+        assert!(app.po_file.is_modified());
 
-fn draw_entry_details(f: &mut Frame, area: Rect, app: &App) {
-    if let Some(entry) = app.get_current_entry() {
-        let chunks = Layout::default()
-            .direction(Direction::Vertical)
-            .constraints([
-                Constraint::Length(5),  // Msgid
-                Constraint::Length(5),  // Msgstr
-                Constraint::Min(3),     // Comments
-                Constraint::Length(3),  // References and flags
-            ])
-            .split(area);
+        // Free-form expressions are checked before they are applied
+        app.start_editing_selected_metadata();
+        press(&mut app, KeyModifiers::NONE, KeyCode::Tab);
+        for _ in 0..2 {
+            press(&mut app, KeyModifiers::NONE, KeyCode::Backspace);
+        }
+        press(&mut app, KeyModifiers::NONE, KeyCode::Char('+'));
+        press(&mut app, KeyModifiers::NONE, KeyCode::Char('1'));
+        press(&mut app, KeyModifiers::NONE, KeyCode::Enter);
+        match app.overlay {
+            Some(Overlay::Picker { ref picker, .. }) => {
+                assert_eq!(picker.input.text, "nplurals=2; plural=(n > 1+1");
+                assert_eq!(picker.error.as_deref(), Some("Expected `)` in plural expression"));
+            }
+            ref other => panic!("picker closed: {:?}", other),
+        }
+        press(&mut app, KeyModifiers::NONE, KeyCode::Char(')'));
+        press(&mut app, KeyModifiers::NONE, KeyCode::Enter);
+        assert!(app.overlay.is_none());
+        assert_eq!(app.po_file.get_header()["Plural-Forms"], "nplurals=2; plural=(n > 1+1)");
+    }
 
-        // Draw msgid
-        draw_text_field(
-            f,
-            chunks[0],
-            "Original Text (msgid)",
-            &entry.msgid,
-            app.edit_field == EditField::Msgid,
-            app.editing && app.edit_field == EditField::Msgid,
-            &app.edit_text,
-            app.edit_cursor,
-        );
+    #[test]
+    fn test_language_picker_offers_matching_fields() {
+        let mut app = App::new(PoFile::new(PathBuf::from("ru.po")), Config::default());
+        app.po_file.set_header_field("Language-Team".to_string(), "Portuguese <pt@example.org>".to_string());
+        app.toggle_metadata_mode();
+        select_metadata(&mut app, "Language");
 
-        // Draw msgstr
-        draw_text_field(
-            f,
-            chunks[1],
-            "Translation (msgstr)",
-            &entry.msgstr,
-            app.edit_field == EditField::Msgstr,
-            app.editing && app.edit_field == EditField::Msgstr,
-            &app.edit_text,
-            app.edit_cursor,
+        app.start_editing_selected_metadata();
+        type_keys(&mut app, "braz");
+        press(&mut app, KeyModifiers::NONE, KeyCode::Enter);
+        assert_eq!(app.po_file.get_header()["Language"], "pt_BR");
+        assert_eq!(
+            app.overlay,
+            Some(Overlay::LanguageDefaults {
+                changes: vec![
+                    ("Plural-Forms".to_string(), "nplurals=2; plural=(n > 1);".to_string()),
+                    ("Language-Team".to_string(), "Portuguese (Brazil) <pt@example.org>".to_string()),
+                ]
+            })
         );
+        press(&mut app, KeyModifiers::NONE, KeyCode::Char('y'));
+        assert_eq!(app.po_file.get_header()["Plural-Forms"], "nplurals=2; plural=(n > 1);");
+
+        // Unknown codes are applied with a warning and nothing else changes
+        app.start_editing_selected_metadata();
+        type_keys(&mut app, "x-klingon");
+        press(&mut app, KeyModifiers::NONE, KeyCode::Enter);
+        assert_eq!(app.po_file.get_header()["Language"], "x-klingon");
+        assert!(app.overlay.is_none());
+        assert_eq!(app.status_message.as_deref(), Some("`x-klingon` is not a known language code"));
+        assert_eq!(app.po_file.get_header()["Language-Team"], "Portuguese (Brazil) <pt@example.org>");
+    }
+
+    #[test]
+    fn test_delete_header_field() {
+        let mut po_file = PoFile::new(PathBuf::from("ru.po"));
+        po_file.set_header_field("X-Poedit-Basepath".to_string(), "..".to_string());
+        po_file.modified = false;
+        let mut app = App::new(po_file, Config::default());
+
+        app.toggle_metadata_mode();
+        assert_eq!(app.metadata_keys.last().unwrap(), "X-Poedit-Basepath");
+        app.metadata_selected = app.metadata_keys.len() - 1;
+        press(&mut app, KeyModifiers::NONE, KeyCode::Char('d'));
+        press(&mut app, KeyModifiers::NONE, KeyCode::Char('y'));
+        assert!(!app.po_file.get_header().contains_key("X-Poedit-Basepath"));
+        assert_eq!(app.metadata_keys.len(), CANONICAL_HEADER_KEYS.len());
+        assert_eq!(app.metadata_selected, CANONICAL_HEADER_KEYS.len() - 1);
+        assert!(app.po_file.is_modified());
+
+        // Required fields need their name typed
+        select_metadata(&mut app, "Content-Type");
+        press(&mut app, KeyModifiers::NONE, KeyCode::Delete);
+        press(&mut app, KeyModifiers::NONE, KeyCode::Char('y'));
+        press(&mut app, KeyModifiers::NONE, KeyCode::Enter);
+        assert!(app.po_file.get_header().contains_key("Content-Type"));
+        assert!(app.overlay.is_none());
+
+        press(&mut app, KeyModifiers::NONE, KeyCode::Delete);
+        type_keys(&mut app, "Content-Type");
+        press(&mut app, KeyModifiers::NONE, KeyCode::Enter);
+        assert!(!app.po_file.get_header().contains_key("Content-Type"));
+        // Unset standard fields stay listed so they can be set again
+        assert_eq!(app.metadata_keys.last().unwrap(), "Content-Type");
+        app.metadata_selected = app.metadata_keys.len() - 1;
+        assert!(!app.delete_selected_metadata());
+    }
+
+    #[test]
+    fn test_reorder_header_fields() {
+        let content = "msgid \"\"\nmsgstr \"\"\n\"X-Generator: Poedit\\n\"\n\"Language: ru\\n\"\n\"Project-Id-Version: app\\n\"\n";
+        let mut app = App::new(PoFile::parse(content).unwrap(), Config::default());
+
+        app.toggle_metadata_mode();
+        assert_eq!(app.metadata_keys[..3], ["X-Generator", "Language", "Project-Id-Version"]);
+        // Unset standard fields follow the header
+        assert_eq!(app.metadata_keys[3], "Report-Msgid-Bugs-To");
+
+        press(&mut app, KeyModifiers::ALT, KeyCode::Down);
+        assert_eq!(app.metadata_keys[..2], ["Language", "X-Generator"]);
+        assert_eq!(app.metadata_selected, 1);
+        assert!(app.po_file.is_modified());
+        let header = app.po_file.to_string();
+        assert!(header.find("Language: ru").unwrap() < header.find("X-Generator").unwrap());
+
+        press(&mut app, KeyModifiers::SHIFT, KeyCode::Char('S'));
+        assert_eq!(app.metadata_keys[..3], ["Project-Id-Version", "Language", "X-Generator"]);
+        assert_eq!(app.metadata_keys[app.metadata_selected], "X-Generator");
+
+        // Fields that are not set can't be moved
+        app.metadata_selected = app.metadata_keys.len() - 1;
+        assert!(!app.move_selected_metadata(true));
+    }
+
+    #[test]
+    fn test_unchanged_edits_leave_file_unmodified() {
+        let mut app = app_with_entries(2);
+        app.toggle_metadata_mode();
+        app.start_editing_selected_metadata();
+        app.stop_editing();
+        // Unset fields stay unset
+        select_metadata(&mut app, "Last-Translator");
+        app.start_editing_selected_metadata();
+        app.stop_editing();
+        app.toggle_metadata_mode();
+        assert!(!app.po_file.get_header().contains_key("Last-Translator"));
+
+        app.start_editing();
+        app.stop_editing();
+        app.mark_current_entry_done();
+        assert!(!app.po_file.is_modified());
+        assert_eq!(app.session_summary(), None);
+    }
+
+    #[test]
+    fn test_revision_date_is_stamped_on_save() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ru.po");
+        let mut po_file = PoFile::new(path);
+        let mut entry = PoEntry::new();
+        entry.msgid = "Open".to_string();
+        entry.set_msgstr("Открыть".to_string());
+        po_file.entries.push(entry);
+
+        let mut app = App::new(po_file.clone(), Config::default());
+        app.toggle_metadata_mode();
+        app.start_editing_selected_metadata();
+        app.edit_text = "app 2.0".to_string();
+        app.stop_editing();
+        assert_eq!(app.po_file.get_header()["PO-Revision-Date"], "YEAR-MO-DA HO:MI+ZONE");
+        app.save().unwrap();
+        assert!(app.po_file.revision_date().is_some());
+
+        // A date typed by hand is kept
+        select_metadata(&mut app, "PO-Revision-Date");
+        app.start_editing_selected_metadata();
+        app.edit_text = "2024-01-02 03:04+0000".to_string();
+        app.stop_editing();
+        app.save().unwrap();
+        assert_eq!(app.po_file.get_header()["PO-Revision-Date"], "2024-01-02 03:04+0000");
+
+        // The previous behaviour stamps the date on every header edit
+        let config = Config {
+            revision_date_on_save: false,
+            ..Config::default()
+        };
+        let mut app = App::new(po_file, config);
+        app.toggle_metadata_mode();
+        app.start_editing_selected_metadata();
+        app.edit_text = "app 2.0".to_string();
+        app.stop_editing();
+        assert!(app.po_file.revision_date().is_some());
+    }
+
+    #[test]
+    fn test_letters_and_home_end_are_text_while_editing() {
+        let mut app = app_with_entries(3);
+        app.next_entry();
+
+        app.start_editing();
+        press(&mut app, KeyModifiers::NONE, KeyCode::Home);
+        // j, k, i and q are navigation, edit and quit keys outside the editor
+        type_keys(&mut app, "kiwi ");
+        press(&mut app, KeyModifiers::NONE, KeyCode::End);
+        type_keys(&mut app, " jq");
+        assert!(app.is_editing());
+        assert_eq!(app.current_entry, 1);
+        assert_eq!(app.edit_text, "kiwi перевод 1 jq");
+
+        press(&mut app, KeyModifiers::NONE, KeyCode::Enter);
+        assert!(!app.is_editing());
+        assert_eq!(app.current_entry, 1);
+        assert_eq!(app.po_file.entries[1].msgstr, "kiwi перевод 1 jq");
+    }
+
+    #[test]
+    fn test_up_down_move_through_search_results() {
+        let mut app = app_with_entries(3);
+
+        app.perform(Action::StartSearch).unwrap();
+        type_keys(&mut app, "перевод");
+        press(&mut app, KeyModifiers::NONE, KeyCode::Down);
+        press(&mut app, KeyModifiers::NONE, KeyCode::Down);
+        assert!(app.search_mode);
+        assert_eq!(app.filters.text, "перевод");
+        assert_eq!(app.current_entry, 2);
+        press(&mut app, KeyModifiers::NONE, KeyCode::Up);
+        assert_eq!(app.current_entry, 1);
+    }
+
+    #[test]
+    fn test_esc_discards_and_enter_applies() {
+        let mut app = app_with_entries(2);
+
+        app.start_editing();
+        press(&mut app, KeyModifiers::NONE, KeyCode::Char('!'));
+        assert!(app.has_uncommitted_edit());
+        press(&mut app, KeyModifiers::NONE, KeyCode::Esc);
+        // The first Esc only warns about the changed buffer
+        assert!(app.is_editing());
+        assert!(app.status_message.is_some());
+        press(&mut app, KeyModifiers::NONE, KeyCode::Esc);
+        assert!(!app.is_editing());
+        assert_eq!(app.po_file.entries[0].msgstr, "перевод 0");
+        assert!(!app.po_file.is_modified());
+
+        // An unchanged buffer closes on the first Esc
+        app.start_editing();
+        press(&mut app, KeyModifiers::NONE, KeyCode::Esc);
+        assert!(!app.is_editing());
+
+        app.start_editing();
+        press(&mut app, KeyModifiers::NONE, KeyCode::Char('!'));
+        press(&mut app, KeyModifiers::NONE, KeyCode::Enter);
+        assert!(!app.is_editing());
+        assert_eq!(app.po_file.entries[0].msgstr, "перевод 0!");
+
+        // Metadata edits follow the same rule
+        app.toggle_metadata_mode();
+        app.start_editing_selected_metadata();
+        press(&mut app, KeyModifiers::NONE, KeyCode::Char('x'));
+        press(&mut app, KeyModifiers::NONE, KeyCode::Esc);
+        press(&mut app, KeyModifiers::NONE, KeyCode::Esc);
+        assert!(!app.po_file.get_header().contains_key("Project-Id-Version"));
+    }
+
+    #[test]
+    fn test_esc_commits_option() {
+        let config = Config {
+            esc_commits: Some(true),
+            ..Config::default()
+        };
+        let mut po_file = PoFile::default();
+        let mut entry = PoEntry::new();
+        entry.msgid = "Open".to_string();
+        po_file.entries.push(entry);
+        let mut app = App::new(po_file, config);
+
+        app.start_editing();
+        app.perform(Action::Input(KeyEvent::new(KeyCode::Char('!'), KeyModifiers::NONE))).unwrap();
+        app.perform(Action::Cancel).unwrap();
+        assert!(!app.is_editing());
+        assert_eq!(app.po_file.entries[0].msgstr, "!");
+    }
+
+    /// Edits msgstr of `app`'s first entry with a change in the middle, then
+    /// tabs back to its msgid and returns there
+    fn cycle_fields_with_changed_msgstr(app: &mut App) {
+        app.msgid_unlocked = true;
+        app.start_editing();
+        for _ in 0..2 {
+            press(app, KeyModifiers::NONE, KeyCode::Left);
+        }
+        press(app, KeyModifiers::NONE, KeyCode::Char('ы'));
+        assert_eq!(app.edit_text, "переводы 0");
+        assert_eq!(app.edit_cursor, 8);
+
+        press(app, KeyModifiers::SHIFT, KeyCode::BackTab);
+        assert!(app.is_editing());
+        assert_eq!(app.edit_field, EditField::Msgid);
+        assert_eq!(app.edit_text, "test 0");
+        assert_eq!(app.edit_cursor, 6);
+        press(app, KeyModifiers::NONE, KeyCode::Left);
+        press(app, KeyModifiers::NONE, KeyCode::Tab);
+        assert!(app.is_editing());
+        assert_eq!(app.edit_field, EditField::Msgstr);
+        assert_eq!(app.edit_text, "переводы 0");
+        assert_eq!(app.edit_cursor, 8);
+    }
+
+    #[test]
+    fn test_tab_while_editing_commits() {
+        let mut app = app_with_entries(2);
+        cycle_fields_with_changed_msgstr(&mut app);
+        assert_eq!(app.po_file.entries[0].msgstr, "переводы 0");
+        assert_eq!(app.po_file.entries[0].msgid, "test 0");
+
+        // Editing the msgid again puts the cursor where Tab left it
+        app.perform(Action::Cancel).unwrap();
+        app.perform(Action::PreviousField).unwrap();
+        app.start_editing();
+        assert_eq!(app.edit_cursor, 5);
+
+        // Another entry starts over at the end of the text
+        app.perform(Action::Cancel).unwrap();
+        app.perform(Action::Next).unwrap();
+        app.perform(Action::NextField).unwrap();
+        app.start_editing();
+        assert_eq!(app.edit_field, EditField::Msgstr);
+        assert_eq!(app.edit_cursor, "перевод 1".chars().count());
+    }
+
+    #[test]
+    fn test_tab_while_editing_stashes() {
+        let config = Config {
+            field_switch: FieldSwitch::Stash,
+            ..Config::default()
+        };
+        let mut app = App::new(app_with_entries(2).po_file, config);
+        cycle_fields_with_changed_msgstr(&mut app);
+        // The buffer came back from the stash without touching the entry
+        assert_eq!(app.po_file.entries[0].msgstr, "перевод 0");
+        assert!(app.has_uncommitted_edit());
+        app.perform(Action::Input(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE))).unwrap();
+        assert_eq!(app.po_file.entries[0].msgstr, "переводы 0");
+
+        // A stash outlives selecting another entry
+        app.start_editing();
+        app.perform(Action::Input(KeyEvent::new(KeyCode::Char('!'), KeyModifiers::NONE))).unwrap();
+        app.perform(Action::PreviousField).unwrap();
+        app.perform(Action::Cancel).unwrap();
+        app.perform(Action::Next).unwrap();
+        app.perform(Action::Previous).unwrap();
+        app.perform(Action::NextField).unwrap();
+        app.start_editing();
+        assert_eq!(app.edit_text, "переводы! 0");
+        assert_eq!(app.po_file.entries[0].msgstr, "переводы 0");
+
+        // Opening another file drops it
+        app.perform(Action::PreviousField).unwrap();
+        app.perform(Action::Cancel).unwrap();
+        app.load_file(app_with_entries(2).po_file);
+        app.perform(Action::NextField).unwrap();
+        app.start_editing();
+        assert_eq!(app.edit_text, "перевод 0");
+    }
+
+    #[test]
+    fn test_stash_follows_entry_through_merge() {
+        let dir = tempfile::tempdir().unwrap();
+        let pot = dir.path().join("app.pot");
+        std::fs::write(&pot, "msgid \"new\"\nmsgstr \"\"\n\nmsgid \"test 1\"\nmsgstr \"\"\n").unwrap();
+        let config = Config {
+            field_switch: FieldSwitch::Stash,
+            ..Config::default()
+        };
+        let mut app = App::new(app_with_entries(2).po_file, config);
+        app.perform(Action::Next).unwrap();
+        app.start_editing();
+        app.perform(Action::Input(KeyEvent::new(KeyCode::Char('!'), KeyModifiers::NONE))).unwrap();
+        app.perform(Action::PreviousField).unwrap();
+        app.perform(Action::Cancel).unwrap();
+
+        app.merge_template(pot.to_str().unwrap());
+        let index = app.po_file.entries.iter().position(|entry| entry.msgid == "test 1").unwrap();
+        assert!(app.select_actual_entry(index));
+        app.edit_field = EditField::Msgstr;
+        app.start_editing();
+        assert_eq!(app.edit_text, "перевод 1!");
+    }
+
+    #[test]
+    fn test_quit_with_uncommitted_edit_asks_first() {
+        let mut app = app_with_entries(2);
+        let key = |code: KeyCode| Action::Input(KeyEvent::new(code, KeyModifiers::NONE));
+
+        app.start_editing();
+        app.perform(key(KeyCode::Char('!'))).unwrap();
+        assert_eq!(app.perform(Action::Quit).unwrap(), Outcome::Done);
+        assert!(matches!(app.overlay, Some(Overlay::UncommittedEdit { then: Action::Quit })));
+
+        // Esc keeps editing with the typed text
+        app.perform(key(KeyCode::Esc)).unwrap();
+        assert!(app.overlay.is_none());
+        assert!(app.has_uncommitted_edit());
+
+        // Other keys leave the question open
+        app.perform(Action::Quit).unwrap();
+        app.perform(key(KeyCode::Char('x'))).unwrap();
+        assert!(app.overlay.is_some());
+        assert_eq!(app.perform(key(KeyCode::Char('a'))).unwrap(), Outcome::Done);
+        assert_eq!(app.po_file.entries[0].msgstr, "перевод 0!");
+        assert!(!app.is_editing());
+        // The applied edit is unsaved, so quitting asks about that next
+        assert_eq!(app.overlay, Some(Overlay::QuitUnsaved { selected: 0 }));
+        app.perform(key(KeyCode::Esc)).unwrap();
+
+        // Discarding before a mode switch
+        app.start_editing();
+        app.perform(key(KeyCode::Char('?'))).unwrap();
+        app.perform(Action::ToggleMetadataMode).unwrap();
+        assert!(!app.is_metadata_mode());
+        app.perform(key(KeyCode::Char('d'))).unwrap();
+        assert!(app.is_metadata_mode());
+        assert_eq!(app.po_file.entries[0].msgstr, "перевод 0!");
+
+        // An unchanged buffer does not need a decision
+        app.toggle_metadata_mode();
+        app.start_editing();
+        assert_eq!(app.perform(Action::QuitWithoutSaving).unwrap(), Outcome::Quit);
+    }
+
+    #[test]
+    fn test_next_untranslated_and_fuzzy_wrap_around() {
+        let mut app = app_with_entries(6);
+        for index in [1, 4] {
+            app.po_file.entries[index].set_msgstr(String::new());
+        }
+        app.po_file.entries[2].toggle_fuzzy();
+        app.update_filtered_indices();
+        app.perform(Action::Last).unwrap();
+        app.perform(Action::Previous).unwrap();
+
+        // Fuzzy entries are not translated either
+        assert_eq!(app.perform(Action::NextUntranslated).unwrap(), Outcome::Done);
+        assert_eq!(app.current_entry, 1);
+        app.perform(Action::NextUntranslated).unwrap();
+        assert_eq!(app.current_entry, 2);
+        app.perform(Action::NextUntranslated).unwrap();
+        assert_eq!(app.current_entry, 4);
+        assert_eq!(app.filters, FilterSet::default());
+
+        app.perform(Action::NextFuzzy).unwrap();
+        assert_eq!(app.current_entry, 2);
+        // The only fuzzy entry is selected already
+        assert_eq!(app.perform(Action::NextFuzzy).unwrap(), Outcome::Blocked);
+        assert_eq!(app.current_entry, 2);
+        let screen = screen_text(&render(&mut app, 100, 20));
+        assert!(screen.contains("No more fuzzy entries"), "{}", screen);
+    }
+
+    #[test]
+    fn test_replace_in_translations() {
+        let mut app = app_with_entries(4);
+        app.po_file.entries[2].msgid = "перевод".to_string();
+        let key = |modifiers, code| Action::Input(KeyEvent::new(code, modifiers));
+        let none = KeyModifiers::NONE;
+        let keys = |app: &mut App, text: &str| {
+            for c in text.chars() {
+                app.perform(key(none, KeyCode::Char(c))).unwrap();
+            }
+        };
+        app.perform(Action::Next).unwrap();
+        app.perform(Action::Replace).unwrap();
+        keys(&mut app, r"перевод (\d)");
+        app.perform(key(none, KeyCode::Tab)).unwrap();
+        keys(&mut app, "вариант $1");
+        let screen = screen_text(&render(&mut app, 100, 24));
+        // Without regex the pattern is taken literally
+        assert!(screen.contains("0 matches in 0 entries listed"), "{}", screen);
+        app.perform(key(KeyModifiers::ALT, KeyCode::Char('r'))).unwrap();
+        let screen = screen_text(&render(&mut app, 100, 24));
+        assert!(screen.contains("4 matches in 4 entries listed"), "{}", screen);
+
+        // One by one from the selected entry, wrapping around
+        app.perform(key(none, KeyCode::Enter)).unwrap();
+        assert_eq!(app.current_entry, 1);
+        let screen = screen_text(&render(&mut app, 100, 24));
+        assert!(screen.contains("перевод 1 → вариант 1"), "{}", screen);
+        app.perform(key(none, KeyCode::Char('y'))).unwrap();
+        assert_eq!(app.current_entry, 2);
+        app.perform(key(none, KeyCode::Char('n'))).unwrap();
+        assert_eq!(app.current_entry, 3);
+        app.perform(key(none, KeyCode::Char('a'))).unwrap();
+        assert!(app.overlay.is_none());
+        assert_eq!(app.status_message.as_deref(), Some("Replaced 3 matches"));
+        let msgstrs: Vec<_> = app.po_file.entries.iter().map(|e| e.msgstr.clone()).collect();
+        assert_eq!(msgstrs, ["вариант 0", "вариант 1", "перевод 2", "вариант 3"]);
+        assert!(app.is_modified());
+
+        // The whole replace is one undo step
+        app.perform(Action::Undo).unwrap();
+        assert!(app.po_file.entries.iter().enumerate().all(|(i, e)| e.msgstr == format!("перевод {}", i)));
+        assert!(!app.is_modified());
+
+        // Replace all in the listed entries only; the form is remembered
+        app.filters.text = "перевод 2".to_string();
+        app.update_filtered_indices();
+        app.perform(Action::Replace).unwrap();
+        app.perform(key(KeyModifiers::ALT, KeyCode::Char('a'))).unwrap();
+        assert_eq!(app.status_message.as_deref(), Some("Replaced 1 match in 1 entry"));
+        assert_eq!(app.po_file.entries[2].msgstr, "вариант 2");
+        assert_eq!(app.po_file.entries[1].msgstr, "перевод 1");
+        // msgids are left alone
+        assert_eq!(app.po_file.entries[2].msgid, "перевод");
+
+        app.perform(Action::Replace).unwrap();
+        app.perform(key(KeyModifiers::ALT, KeyCode::Char('r'))).unwrap();
+        app.perform(key(KeyModifiers::ALT, KeyCode::Char('e'))).unwrap();
+        assert_eq!(app.status_message.as_deref(), Some("No matches"));
+    }
+
+    #[test]
+    fn test_undo_and_redo_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = app_with_entries(3);
+        app.po_file.path = Some(dir.path().join("ru.po"));
+        app.po_file.header.insert("Language".to_string(), "ru".to_string());
+
+        app.next_entry();
+        app.start_editing();
+        app.edit_text = "новый перевод".to_string();
+        app.stop_editing();
+        app.perform(Action::Save).unwrap();
+        app.perform(Action::ToggleFuzzy).unwrap();
+        app.perform(Action::Last).unwrap();
+        app.perform(Action::MarkDone).unwrap();
+        assert!(app.po_file.entries[1].is_fuzzy);
+
+        // Undo selects the entry it changes back
+        assert_eq!(app.perform(Action::Undo).unwrap(), Outcome::Done);
+        assert_eq!(app.current_entry, 1);
+        assert!(!app.po_file.entries[1].is_fuzzy);
+        assert!(!app.is_modified());
+        app.perform(Action::Undo).unwrap();
+        assert_eq!(app.po_file.entries[1].msgstr, "перевод 1");
+        assert!(app.is_modified());
+        assert_eq!(app.perform(Action::Undo).unwrap(), Outcome::Blocked);
+        assert_eq!(app.status_message.as_deref(), Some("Nothing to undo"));
+
+        app.perform(Action::Redo).unwrap();
+        app.perform(Action::Redo).unwrap();
+        assert_eq!(app.po_file.entries[1].msgstr, "новый перевод");
+        assert!(app.po_file.entries[1].is_fuzzy);
+        assert_eq!(app.perform(Action::Redo).unwrap(), Outcome::Blocked);
+
+        // Header edits are undone as well, moves included
+        app.toggle_metadata_mode();
+        let keys = app.metadata_keys.clone();
+        app.perform(Action::MoveHeaderFieldDown).unwrap();
+        assert_ne!(app.metadata_keys, keys);
+        app.perform(Action::Undo).unwrap();
+        assert_eq!(app.metadata_keys, keys);
+    }
+
+    #[test]
+    fn test_quit_asks_about_unsaved_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ru.po");
+        let mut app = app_with_entries(2);
+        app.po_file.path = Some(path.clone());
+        let key = |code| Action::Input(KeyEvent::new(code, KeyModifiers::NONE));
+
+        // Nothing to save, nothing to ask
+        assert_eq!(app.perform(Action::Quit).unwrap(), Outcome::Quit);
+
+        app.perform(Action::ToggleFuzzy).unwrap();
+        assert_eq!(app.perform(Action::Quit).unwrap(), Outcome::Done);
+        let screen = screen_text(&render(&mut app, 80, 20));
+        assert!(screen.contains("Save    Discard    Cancel"), "{}", screen);
+
+        // Esc and Cancel keep the editor open
+        assert_eq!(app.perform(key(KeyCode::Esc)).unwrap(), Outcome::Done);
+        assert!(app.overlay.is_none());
+        app.perform(Action::Quit).unwrap();
+        app.perform(key(KeyCode::Right)).unwrap();
+        app.perform(key(KeyCode::Right)).unwrap();
+        app.perform(key(KeyCode::Right)).unwrap();
+        assert_eq!(app.overlay, Some(Overlay::QuitUnsaved { selected: 2 }));
+        assert_eq!(app.perform(key(KeyCode::Enter)).unwrap(), Outcome::Done);
+        assert!(app.overlay.is_none());
+
+        // Discard quits without writing the file
+        app.perform(Action::Quit).unwrap();
+        app.perform(key(KeyCode::Right)).unwrap();
+        assert_eq!(app.perform(key(KeyCode::Enter)).unwrap(), Outcome::Quit);
+        assert!(!path.exists());
+
+        // Save writes it first
+        app.perform(Action::Quit).unwrap();
+        assert_eq!(app.perform(key(KeyCode::Char('s'))).unwrap(), Outcome::Quit);
+        assert!(path.exists());
+        assert!(!app.is_modified());
+
+        // Alt+Q never asks
+        app.perform(Action::ToggleFuzzy).unwrap();
+        let resolved = app.resolve_key(KeyEvent::new(KeyCode::Char('q'), KeyModifiers::ALT));
+        assert_eq!(resolved, Some(Action::QuitWithoutSaving));
+        assert_eq!(app.perform(Action::QuitWithoutSaving).unwrap(), Outcome::Quit);
+    }
+
+    #[test]
+    fn test_autocomplete_from_translations() {
+        let mut app = app_with_entries(3);
+        app.po_file.entries[0].set_msgstr("Профилирование включено".to_string());
+        app.po_file.entries[1].set_msgstr("Профилирование и профилировщик".to_string());
+        app.words = WordIndex::build(&app.po_file.entries);
+
+        app.current_entry = 2;
+        app.start_editing();
+        type_keys(&mut app, " Пр");
+        assert!(app.completion.is_none());
+        type_keys(&mut app, "о");
+        assert_eq!(app.completion.as_ref().unwrap().words, vec!["Профилирование"]);
+
+        // Esc only closes the popup
+        press(&mut app, KeyModifiers::NONE, KeyCode::Esc);
+        assert!(app.completion.is_none());
+        assert!(app.is_editing());
+
+        press(&mut app, KeyModifiers::NONE, KeyCode::Backspace);
+        type_keys(&mut app, "о");
+        press(&mut app, KeyModifiers::NONE, KeyCode::Enter);
+        assert!(app.is_editing());
+        assert_eq!(app.edit_text, "перевод 2 Профилирование");
+
+        // Tab cycles instead of switching fields; committing indexes the text
+        type_keys(&mut app, " проф");
+        assert_eq!(app.completion.as_ref().unwrap().words, vec!["профилировщик"]);
+        press(&mut app, KeyModifiers::NONE, KeyCode::Esc);
+        type_keys(&mut app, "иль");
+        press(&mut app, KeyModifiers::NONE, KeyCode::Enter);
+        assert!(!app.is_editing());
+        assert_eq!(app.words.complete("Проф"), vec!["Профилирование"]);
+        assert_eq!(app.words.complete("проф"), vec!["профилировщик", "профиль"]);
+
+        app.start_editing();
+        app.edit_cursor = 0;
+        type_keys(&mut app, "проф");
+        press(&mut app, KeyModifiers::NONE, KeyCode::Tab);
+        assert_eq!(app.completion.as_ref().unwrap().selected_word(), "профиль");
+        assert_eq!(app.edit_field, EditField::Msgstr);
+        press(&mut app, KeyModifiers::NONE, KeyCode::Right);
+        assert!(app.edit_text.starts_with("профиль"));
+        app.cancel_editing();
+
+        // Search input never completes, and the option turns it off
+        app.perform(Action::StartSearch).unwrap();
+        type_keys(&mut app, "проф");
+        assert!(app.completion.is_none());
+        app.stop_editing();
+        app.config.autocomplete = false;
+        app.start_editing();
+        type_keys(&mut app, " проф");
+        assert!(app.completion.is_none());
+    }
+
+    #[test]
+    fn test_expand_snippets() {
+        let mut app = app_with_entries(1);
+        app.config.snippets = [(";np", "\u{a0}"), (";ps", "-- {msgid}\nXYZ")]
+            .iter()
+            .map(|(abbreviation, expansion)| (abbreviation.to_string(), expansion.to_string()))
+            .collect();
+
+        app.start_editing();
+        type_keys(&mut app, "5;np ");
+        assert_eq!(app.status_message.as_deref(), Some("Expanded ;np"));
+        type_keys(&mut app, "mm ;ps");
+        assert_eq!(app.edit_text, "перевод 05\u{a0}mm ;ps");
+
+        press(&mut app, KeyModifiers::ALT, KeyCode::Char('e'));
+        assert_eq!(app.edit_text, "перевод 05\u{a0}mm -- test 0\nXYZ");
+        assert_eq!(app.edit_cursor, app.edit_text.chars().count());
+
+        press(&mut app, KeyModifiers::ALT, KeyCode::Char('e'));
+        assert!(app.status_message.is_some());
+        type_keys(&mut app, " ");
+        assert!(app.edit_text.ends_with("XYZ "));
+
+        app.stop_editing();
+        press(&mut app, KeyModifiers::NONE, KeyCode::F(6));
+        assert_eq!(app.overlay, Some(Overlay::Snippets));
+        press(&mut app, KeyModifiers::NONE, KeyCode::Esc);
+
+        // Metadata values are not translations
+        app.perform(Action::ToggleMetadataMode).unwrap();
+        app.start_editing();
+        assert_eq!(app.edit_field, EditField::Metadata);
+        let before = app.edit_text.clone();
+        type_keys(&mut app, ";np ");
+        assert_eq!(app.edit_text, format!("{};np ", before));
+        app.stop_editing();
+        app.perform(Action::ToggleMetadataMode).unwrap();
+        press(&mut app, KeyModifiers::NONE, KeyCode::F(6));
+        press(&mut app, KeyModifiers::NONE, KeyCode::Esc);
+        assert!(app.overlay.is_none());
+    }
+
+    #[test]
+    fn test_typographic_characters() {
+        let mut app = app_with_entries(1);
+        app.po_file.header.insert("Language".to_string(), "ru".to_string());
+
+        assert_eq!(press(&mut app, KeyModifiers::ALT, KeyCode::Char(' ')), Some(Outcome::Blocked));
+        app.start_editing();
+        app.edit_text = "5кг".to_string();
+        app.edit_cursor = 1;
+        press(&mut app, KeyModifiers::ALT, KeyCode::Char(' '));
+        app.edit_cursor = 4;
+        press(&mut app, KeyModifiers::ALT, KeyCode::Char('-'));
+        press(&mut app, KeyModifiers::ALT, KeyCode::Char('.'));
+        assert_eq!(app.edit_text, "5\u{a0}кг—…");
+
+        press(&mut app, KeyModifiers::ALT, KeyCode::Char('\''));
+        assert_eq!(app.edit_text, "5\u{a0}кг—…«»");
+        assert_eq!(app.edit_cursor, 7);
+        app.perform(Action::Input(KeyEvent::new(KeyCode::Char('д'), KeyModifiers::NONE))).unwrap();
+        app.edit_cursor = app.edit_text.chars().count();
+        press(&mut app, KeyModifiers::ALT, KeyCode::Char('\''));
+        assert_eq!(app.edit_text, "5\u{a0}кг—…«д»«»");
+        // Pressed again right away, the pair switches style
+        press(&mut app, KeyModifiers::ALT, KeyCode::Char('\''));
+        assert_eq!(app.edit_text, "5\u{a0}кг—…«д»„“");
+        press(&mut app, KeyModifiers::ALT, KeyCode::Char('\''));
+        assert_eq!(app.edit_text, "5\u{a0}кг—…«д»“”");
+        press(&mut app, KeyModifiers::ALT, KeyCode::Char('\''));
+        assert_eq!(app.edit_text, "5\u{a0}кг—…«д»«»");
+    }
+
+    #[test]
+    fn test_insert_character_by_codepoint_or_name() {
+        let mut app = app_with_entries(1);
+
+        app.start_editing();
+        app.edit_cursor = 0;
+        press(&mut app, KeyModifiers::CONTROL | KeyModifiers::SHIFT, KeyCode::Char('U'));
+        type_keys(&mut app, "u+d800");
+        press(&mut app, KeyModifiers::NONE, KeyCode::Enter);
+        let Some(Overlay::Picker { ref picker, .. }) = app.overlay else {
+            panic!("picker closed");
+        };
+        assert_eq!(picker.error.as_deref(), Some("U+D800 is a UTF-16 surrogate, not a character"));
+        press(&mut app, KeyModifiers::NONE, KeyCode::Esc);
+        assert!(app.overlay.is_none());
+
+        press(&mut app, KeyModifiers::CONTROL | KeyModifiers::SHIFT, KeyCode::Char('U'));
+        type_keys(&mut app, "2011");
+        press(&mut app, KeyModifiers::NONE, KeyCode::Enter);
+        assert_eq!(app.edit_text, "\u{2011}перевод 0");
+
+        press(&mut app, KeyModifiers::ALT, KeyCode::Char('u'));
+        type_keys(&mut app, "narrow no-break");
+        press(&mut app, KeyModifiers::NONE, KeyCode::Enter);
+        assert_eq!(app.edit_text, "\u{2011}\u{202f}перевод 0");
+        assert_eq!(app.edit_cursor, 2);
+        assert_eq!(app.recent_chars, vec!['\u{202f}', '\u{2011}']);
+
+        // Recent characters come first
+        press(&mut app, KeyModifiers::CONTROL | KeyModifiers::SHIFT, KeyCode::Char('U'));
+        press(&mut app, KeyModifiers::NONE, KeyCode::Enter);
+        assert_eq!(app.edit_text, "\u{2011}\u{202f}\u{202f}перевод 0");
+
+        // Any named character can be found, not only the common ones
+        press(&mut app, KeyModifiers::CONTROL | KeyModifiers::SHIFT, KeyCode::Char('U'));
+        type_keys(&mut app, "snowman without");
+        press(&mut app, KeyModifiers::NONE, KeyCode::Enter);
+        assert!(app.edit_text.starts_with("\u{2011}\u{202f}\u{202f}\u{26c4}"), "{}", app.edit_text);
+    }
+
+    fn render(app: &mut App, width: u16, height: u16) -> ratatui::buffer::Buffer {
+        let mut terminal = ratatui::Terminal::new(ratatui::backend::TestBackend::new(width, height)).unwrap();
+        terminal.draw(|f| draw(f, app)).unwrap();
+        terminal.backend().buffer().clone()
+    }
+
+    /// Cells drawn as the cursor, in inverse video. A wide character is one
+    /// cell; terminals draw both of its columns with that cell's style.
+    fn cursor_cells(buffer: &ratatui::buffer::Buffer) -> Vec<(u16, u16)> {
+        let area = buffer.area;
+        (area.top()..area.bottom())
+            .flat_map(|y| (area.left()..area.right()).map(move |x| (x, y)))
+            .filter(|&(x, y)| buffer[(x, y)].bg == Color::White && buffer[(x, y)].fg == Color::Black)
+            .collect()
+    }
+
+    #[test]
+    fn test_cursor_on_wide_characters() {
+        let mut app = app_with_entries(1);
+        let text = "日本語のテキスト".repeat(6);
+        app.po_file.entries[0].set_msgstr(text.clone());
+        app.start_editing();
+
+        for cursor in [0, 5, 19, 20, 21, 33] {
+            app.edit_cursor = cursor;
+            let buffer = render(&mut app, 80, 30);
+            let cells = cursor_cells(&buffer);
+            let expected = text.chars().nth(cursor).unwrap().to_string();
+            assert_eq!(cells.len(), 1, "cursor {}", cursor);
+            assert_eq!(buffer[cells[0]].symbol(), expected, "cursor {}", cursor);
+        }
+
+        // A row never ends in half a character: every row of the field
+        // starts right after the border
+        app.edit_cursor = 0;
+        let buffer = render(&mut app, 80, 30);
+        let (x, y) = cursor_cells(&buffer)[0];
+        assert_eq!(buffer[(x - 1, y)].symbol(), "│");
+        assert_eq!(buffer[(x, y + 1)].symbol(), text.chars().nth((80 * 60 / 100 - 2) / 2).unwrap().to_string());
+
+        // After the last character the cursor is a single blank cell
+        app.edit_text = "絵文字😀".to_string();
+        app.edit_cursor = 4;
+        let buffer = render(&mut app, 80, 30);
+        let cells = cursor_cells(&buffer);
+        assert_eq!(cells.len(), 1);
+        assert_eq!(buffer[(cells[0].0 - 2, cells[0].1)].symbol(), "😀");
+    }
+
+    #[test]
+    fn test_wide_text_in_list_metadata_and_search() {
+        let mut app = app_with_entries(1);
+        app.po_file.entries[0].msgid = "日本語のテキストがとても長い場合の表示".repeat(3);
+        app.po_file.header.insert("Language-Team".to_string(), "Русская команда перевода <ru@example.org>".to_string());
+        // Symbols of a row, skipping the second column of wide characters
+        let row = |buffer: &ratatui::buffer::Buffer, y: u16| -> String {
+            let mut line = String::new();
+            let mut x = 0;
+            while x < buffer.area.width {
+                let symbol = buffer[(x, y)].symbol();
+                line.push_str(symbol);
+                x += symbol.width().max(1) as u16;
+            }
+            line
+        };
+
+        let buffer = render(&mut app, 60, 20);
+        let list_row = (0..20).map(|y| row(&buffer, y)).find(|line| line.contains("日本語")).unwrap();
+        assert!(list_row.contains("…"));
+
+        app.toggle_metadata_mode();
+        let buffer = render(&mut app, 160, 20);
+        assert!((0..20).any(|y| row(&buffer, y).contains("Language-Team: Русская команда перевода <ru@…")));
+
+        app.toggle_metadata_mode();
+        app.perform(Action::StartSearch).unwrap();
+        app.filters.text = "日本".to_string();
+        app.search_cursor = 1;
+        let buffer = render(&mut app, 60, 20);
+        let cells = cursor_cells(&buffer);
+        assert_eq!(cells.len(), 1);
+        assert_eq!(buffer[cells[0]].symbol(), "本");
+    }
+
+    #[test]
+    fn test_search_cursor_moves_through_query() {
+        let mut app = app_with_entries(1);
+        app.perform(Action::StartSearch).unwrap();
+        type_keys(&mut app, "перевод");
+        let cursor_symbol = |app: &mut App| {
+            let buffer = render(app, 60, 20);
+            let cells = cursor_cells(&buffer);
+            assert_eq!(cells.len(), 1);
+            buffer[cells[0]].symbol().to_string()
+        };
+        assert_eq!(cursor_symbol(&mut app), " ");
+        let (left, right) = (KeyCode::Left, KeyCode::Right);
+        press(&mut app, KeyModifiers::NONE, left);
+        assert_eq!(cursor_symbol(&mut app), "д");
+        press(&mut app, KeyModifiers::NONE, left);
+        press(&mut app, KeyModifiers::NONE, left);
+        assert_eq!(cursor_symbol(&mut app), "в");
+        press(&mut app, KeyModifiers::NONE, right);
+        assert_eq!(cursor_symbol(&mut app), "о");
+        type_keys(&mut app, "ы");
+        assert_eq!(app.filters.text, "перевыод");
+        assert_eq!(cursor_symbol(&mut app), "о");
+
+        // A query wider than the overlay scrolls to keep the cursor in view
+        app.filters.text = "очень длинный поисковый запрос про перевод".to_string();
+        app.search_cursor = app.filters.text.chars().count();
+        let buffer = render(&mut app, 60, 20);
+        let cells = cursor_cells(&buffer);
+        assert_eq!(cells.len(), 1);
+        let row: String = (0..60).map(|x| buffer[(x, cells[0].1)].symbol()).collect();
+        assert!(row.contains("про перевод │"), "{}", row);
+        app.search_cursor = 0;
+        let buffer = render(&mut app, 60, 20);
+        let cells = cursor_cells(&buffer);
+        assert_eq!(buffer[cells[0]].symbol(), "о");
+        let row: String = (0..60).map(|x| buffer[(x, cells[0].1)].symbol()).collect();
+        assert!(row.contains("│очень длинный"), "{}", row);
+    }
+
+    #[test]
+    fn test_right_to_left_msgstr() {
+        let mut app = app_with_entries(1);
+        app.po_file.set_header_field("Language".to_string(), "he".to_string());
+        app.po_file.entries[0].set_msgstr("שלום %s".to_string());
+        app.edit_field = EditField::Msgstr;
+        let text_row = |buffer: &ratatui::buffer::Buffer| {
+            (0..buffer.area.height)
+                .find_map(|y| {
+                    let line: String = (0..buffer.area.width).map(|x| buffer[(x, y)].symbol()).collect();
+                    line.find("%s םולש").map(|byte| (line[..byte].chars().count() as u16, y))
+                })
+                .unwrap()
+        };
+
+        // Display order, right-aligned against the field's border
+        let buffer = render(&mut app, 80, 30);
+        let (x, y) = text_row(&buffer);
+        assert_eq!(buffer[(x + 7, y)].symbol(), "│");
+
+        // The cursor covers the char it is on wherever it is drawn; at the
+        // end it waits at the left edge for the next word
+        app.start_editing();
+        for (cursor, column) in [(0, 6), (3, 3), (5, 0), (6, 1), (7, -1)] {
+            app.edit_cursor = cursor;
+            let buffer = render(&mut app, 80, 30);
+            assert_eq!(text_row(&buffer), (x, y), "cursor {}", cursor);
+            assert_eq!(cursor_cells(&buffer), vec![((x as i32 + column) as u16, y)], "cursor {}", cursor);
+        }
+
+        // Left and Right follow the text until visual movement is chosen
+        app.edit_cursor = 0;
+        press(&mut app, KeyModifiers::NONE, KeyCode::Left);
+        assert_eq!(app.edit_cursor, 0);
+        app.config.cursor_movement = CursorMovement::Visual;
+        press(&mut app, KeyModifiers::NONE, KeyCode::Left);
+        assert_eq!(app.edit_cursor, 1);
+        app.edit_cursor = 5;
+        press(&mut app, KeyModifiers::NONE, KeyCode::Right);
+        assert_eq!(app.edit_cursor, 6);
+
+        // Terminals that reorder text themselves get it in logical order
+        app.config.bidi = false;
+        let buffer = render(&mut app, 80, 30);
+        assert!((0..30).any(|y| {
+            let line: String = (0..80).map(|x| buffer[(x, y)].symbol()).collect();
+            line.contains("שלום %s")
+        }));
+    }
+
+    #[test]
+    fn test_display_and_edit_wrap_alike() {
+        let mut app = app_with_entries(1);
+        let text = "Translations wrap between words, so the cursors and the text always agree on rows.";
+        app.po_file.entries[0].set_msgstr(text.to_string());
+        app.edit_field = EditField::Msgstr;
+        let line = |buffer: &ratatui::buffer::Buffer, y: u16| -> String {
+            (0..buffer.area.width).map(|x| buffer[(x, y)].symbol()).collect()
+        };
+
+        let shown = render(&mut app, 80, 30);
+        let first = (0..30).find(|&y| line(&shown, y).contains("Translations wrap")).unwrap();
+        // "cursors" would not fit after "the "
+        let rows: Vec<String> = (first..first + 3).map(|y| line(&shown, y)).collect();
+        assert!(rows[0].contains("││Translations wrap between words, so the "));
+        assert!(rows[1].contains("││cursors and the text"));
+
+        app.start_editing();
+        for cursor in [0, 13, 40, 60, 80] {
+            app.edit_cursor = cursor;
+            let edited = render(&mut app, 80, 30);
+            for (i, row) in rows.iter().enumerate() {
+                assert_eq!(&line(&edited, first + i as u16), row, "cursor {}", cursor);
+            }
+            let cells = cursor_cells(&edited);
+            assert_eq!(cells.len(), 1);
+            assert_eq!(edited[cells[0]].symbol(), text.chars().nth(cursor).unwrap().to_string(), "cursor {}", cursor);
+        }
+    }
+
+    #[test]
+    fn test_search_escape_restores_view() {
+        let mut app = app_with_entries(12);
+        app.current_entry = 3;
+        app.update_list_state();
+        let indices = app.filtered_indices.clone();
+        let current = app.current_entry;
+
+        app.perform(Action::StartSearch).unwrap();
+        press(&mut app, KeyModifiers::NONE, KeyCode::Char('1'));
+        assert_eq!(app.filtered_indices, vec![1, 10, 11]);
+        press(&mut app, KeyModifiers::NONE, KeyCode::Esc);
+        assert!(!app.search_mode);
+        assert_eq!(app.filters.text, "");
+        assert_eq!(app.filtered_indices, indices);
+        assert_eq!(app.current_entry, current);
+
+        // Enter keeps the narrowed view
+        app.perform(Action::StartSearch).unwrap();
+        press(&mut app, KeyModifiers::NONE, KeyCode::Char('1'));
+        press(&mut app, KeyModifiers::NONE, KeyCode::Char('1'));
+        press(&mut app, KeyModifiers::NONE, KeyCode::Enter);
+        assert!(!app.search_mode);
+        assert_eq!(app.filtered_indices, vec![11]);
+
+        // Searching again selects the old query: typing replaces it and
+        // Esc goes back to the view it made
+        app.perform(Action::StartSearch).unwrap();
+        assert!(app.search_selected);
+        press(&mut app, KeyModifiers::NONE, KeyCode::Char('5'));
+        assert_eq!(app.filters.text, "5");
+        assert_eq!(app.filtered_indices, vec![5]);
+        press(&mut app, KeyModifiers::NONE, KeyCode::Esc);
+        assert_eq!(app.filters.text, "11");
+        assert_eq!(app.filtered_indices, vec![11]);
+
+        // Moving the cursor keeps the old query to extend it
+        app.perform(Action::StartSearch).unwrap();
+        press(&mut app, KeyModifiers::NONE, KeyCode::Left);
+        press(&mut app, KeyModifiers::NONE, KeyCode::Right);
+        press(&mut app, KeyModifiers::NONE, KeyCode::Char('1'));
+        assert_eq!(app.filters.text, "111");
+    }
+
+    #[test]
+    fn test_copy_cut_and_paste() {
+        let mut app = app_with_entries(12);
+        let ctrl = KeyModifiers::CONTROL;
+        assert_eq!(press(&mut app, ctrl, KeyCode::Char('v')), Some(Outcome::Blocked));
+        assert_eq!(app.status_message.as_deref(), Some("The clipboard is empty"));
+
+        // The msgid when browsing
+        press(&mut app, ctrl, KeyCode::Char('c'));
+        assert_eq!(app.status_message.as_deref(), Some("Copied 6 characters to the clipboard (via poterm)"));
+        assert_eq!(press(&mut app, ctrl, KeyCode::Char('v')), Some(Outcome::Blocked));
+        app.perform(Action::EditTranslation).unwrap();
+        press(&mut app, KeyModifiers::NONE, KeyCode::Home);
+        press(&mut app, ctrl, KeyCode::Char('v'));
+        assert_eq!((app.edit_text.as_str(), app.edit_cursor), ("test 0перевод 0", 6));
+
+        press(&mut app, ctrl, KeyCode::Char('x'));
+        assert_eq!(app.edit_text, "");
+        // A multi-line paste goes in as it is, whatever the terminal sends
+        // for the line breaks
+        assert_eq!(app.paste_text("Первая строка\r\nj k\rq"), Outcome::Done);
+        assert_eq!(app.edit_text, "Первая строка\nj k\nq");
+        assert_eq!(app.current_entry, 0);
+        press(&mut app, KeyModifiers::NONE, KeyCode::Enter);
+        assert_eq!(app.po_file.entries[0].msgstr, "Первая строка\nj k\nq");
+        press(&mut app, ctrl, KeyCode::Char('v'));
+        assert_eq!(app.po_file.entries[0].msgstr, "Первая строка\nj k\nq");
+        // A terminal paste while browsing is not taken for key presses
+        assert_eq!(app.paste_text("jjj"), Outcome::Blocked);
+        assert_eq!(app.current_entry, 0);
+        assert_eq!(app.status_message.as_deref(), Some("Start editing a field to paste into it"));
+
+        // Line breaks become spaces in the search
+        app.perform(Action::StartSearch).unwrap();
+        app.paste_text("test\n1");
+        assert_eq!(app.filters.text, "test 1");
+        assert_eq!(app.filtered_indices, vec![1, 10, 11]);
+    }
 
-        // Draw comments
-        let comments_text = entry.comments.join("\n");
-        draw_text_field(
-            f,
-            chunks[2],
-            "Comments",
-            &comments_text,
-            app.edit_field == EditField::Comments,
-            app.editing && app.edit_field == EditField::Comments,
-            &app.edit_text,
-            app.edit_cursor,
-        );
+    #[test]
+    fn test_word_keys_while_editing_and_searching() {
+        let mut app = app_with_entries(12);
+        let ctrl = KeyModifiers::CONTROL;
+        app.perform(Action::EditTranslation).unwrap();
+        press(&mut app, ctrl, KeyCode::Left);
+        assert_eq!(app.edit_cursor, 8);
+        press(&mut app, ctrl, KeyCode::Left);
+        press(&mut app, ctrl, KeyCode::Delete);
+        assert_eq!(app.edit_text, " 0");
+        press(&mut app, ctrl, KeyCode::Char('k'));
+        assert_eq!(app.edit_text, "");
+        // Ctrl+Right stays a text key rather than the next untranslated entry
+        press(&mut app, ctrl, KeyCode::Right);
+        assert!(app.editing);
+        assert_eq!(app.current_entry, 0);
+        press(&mut app, KeyModifiers::NONE, KeyCode::Esc);
+        press(&mut app, KeyModifiers::NONE, KeyCode::Esc);
 
-        // Draw references and flags
-        let mut info_lines = Vec::new();
-        if !entry.references.is_empty() {
-            info_lines.push(Line::from(vec![
-                Span::styled("References: ", Style::default().fg(Color::Cyan)),
-                Span::raw(entry.references.join(", ")),
-            ]));
-        }
-        if !entry.flags.is_empty() {
-            info_lines.push(Line::from(vec![
-                Span::styled("Flags: ", Style::default().fg(Color::Yellow)),
-                Span::raw(entry.flags.join(", ")),
-            ]));
-        }
+        app.perform(Action::StartSearch).unwrap();
+        type_keys(&mut app, "test 11");
+        assert_eq!(app.filtered_indices, vec![11]);
+        press(&mut app, ctrl, KeyCode::Backspace);
+        assert_eq!(app.filters.text, "test ");
+        assert_eq!(app.filtered_indices.len(), 12);
+    }
 
-        let block = Block::default()
-            .title("Information")
-            .borders(Borders::ALL)
-            .border_style(Style::default().fg(Color::Magenta));
+    #[test]
+    fn test_up_and_down_move_between_rows() {
+        let mut app = app_with_entries(2);
+        app.po_file.entries[0].set_msgstr("日本語のテキスト\nab😀cd\nx".to_string());
+        app.perform(Action::EditTranslation).unwrap();
+        // The details pane is 60 columns wide, so the text is not wrapped
+        render(&mut app, 100, 24);
+        assert_eq!(app.edit_width.get(), 58);
+        app.edit_cursor = 3;
 
-        let paragraph = Paragraph::new(info_lines)
-            .block(block)
-            .wrap(Wrap { trim: true });
+        // 日本語 is 6 columns wide, as is ab😀cd on the next row
+        press(&mut app, KeyModifiers::NONE, KeyCode::Down);
+        assert_eq!(app.edit_cursor, 9 + 5);
+        // The column is kept across the short last row
+        press(&mut app, KeyModifiers::NONE, KeyCode::Down);
+        assert_eq!(app.edit_cursor, 16);
+        press(&mut app, KeyModifiers::NONE, KeyCode::Up);
+        assert_eq!(app.edit_cursor, 14);
+        press(&mut app, KeyModifiers::NONE, KeyCode::Up);
+        assert_eq!(app.edit_cursor, 3);
+        press(&mut app, KeyModifiers::NONE, KeyCode::Up);
+        assert_eq!(app.edit_cursor, 0);
+        assert_eq!(app.current_entry, 0);
 
-        f.render_widget(paragraph, chunks[3]);
-    } else {
-        let block = Block::default()
-            .title("Entry Details")
-            .borders(Borders::ALL)
-            .border_style(Style::default().fg(Color::Gray));
+        // Soft-wrapped rows count as rows too
+        app.edit_width.set(5);
+        app.edit_cursor = 1;
+        press(&mut app, KeyModifiers::NONE, KeyCode::Down);
+        assert_eq!(app.edit_cursor, 3);
+    }
 
-        let paragraph = Paragraph::new("No entry selected")
-            .block(block)
-            .alignment(Alignment::Center)
-            .style(Style::default().fg(Color::DarkGray));
+    #[test]
+    fn test_find_next_jumps_through_full_list() {
+        let mut app = app_with_entries(12);
+        assert_eq!(app.perform(Action::FindNext).unwrap(), Outcome::Blocked);
+        app.current_entry = 3;
+        app.update_list_state();
 
-        f.render_widget(paragraph, area);
+        // Esc restores the full list but F3 still looks for the query
+        app.perform(Action::StartSearch).unwrap();
+        press(&mut app, KeyModifiers::NONE, KeyCode::Char('1'));
+        press(&mut app, KeyModifiers::NONE, KeyCode::Esc);
+        assert_eq!(app.filtered_indices.len(), 12);
+        press(&mut app, KeyModifiers::NONE, KeyCode::F(3));
+        assert_eq!(app.current_entry, 10);
+        assert_eq!(app.status_message, None);
+        press(&mut app, KeyModifiers::NONE, KeyCode::F(3));
+        press(&mut app, KeyModifiers::NONE, KeyCode::F(3));
+        assert_eq!(app.current_entry, 1);
+        assert_eq!(app.status_message.as_deref(), Some("Search wrapped around to the top"));
+        press(&mut app, KeyModifiers::SHIFT, KeyCode::F(3));
+        assert_eq!(app.current_entry, 11);
+        assert_eq!(app.status_message.as_deref(), Some("Search wrapped around to the bottom"));
+
+        // Matches are highlighted in the details pane
+        let buffer = render(&mut app, 100, 24);
+        let highlighted: String = buffer.content.iter().filter(|cell| cell.bg == Color::Yellow).map(|cell| cell.symbol()).collect();
+        assert_eq!(highlighted, "1111", "msgid and msgstr of entry 11");
+        // The query is compiled once, not on every frame
+        assert!(app.find_text_query.borrow().as_ref().is_some_and(|query| query.is_for(&app.find_query, app.search_options)));
+
+        app.find_query = "nothing".to_string();
+        assert_eq!(app.perform(Action::FindNext).unwrap(), Outcome::Blocked);
+        assert_eq!(app.status_message.as_deref(), Some("No matches for \"nothing\""));
     }
-}
 
-fn draw_text_field(
-    f: &mut Frame,
-    area: Rect,
-    title: &str,
-    text: &str,
-    is_selected: bool,
-    is_editing: bool,
-    edit_text: &str,
-    cursor_pos: usize,
-) {
-    let border_color = if is_editing {
-        Color::Green
-    } else if is_selected {
-        Color::Yellow
-    } else {
-        Color::White
-    };
+    #[test]
+    fn test_find_next_after_selected_entry_leaves_list() {
+        let mut app = app_with_entries(4);
+        for (i, msgid) in ["Open", "Save file", "Load file", "Close file"].into_iter().enumerate() {
+            app.po_file.entries[i].msgid = msgid.to_string();
+            app.po_file.entries[i].set_msgstr(String::new());
+        }
+        app.perform(Action::ToggleUntranslatedFilter).unwrap();
+        app.find_query = "file".to_string();
+        press(&mut app, KeyModifiers::NONE, KeyCode::F(3));
+        assert_eq!(app.get_current_entry().unwrap().msgid, "Save file");
 
-    let display_text = if is_editing { edit_text } else { text };
+        // Translated, it drops out of the list before the next search
+        app.start_editing();
+        type_keys(&mut app, "Сохранить файл");
+        press(&mut app, KeyModifiers::NONE, KeyCode::Enter);
+        press(&mut app, KeyModifiers::NONE, KeyCode::F(3));
+        assert_eq!(app.get_current_entry().unwrap().msgid, "Load file");
+        assert_eq!(app.status_message, None);
 
-    let block = Block::default()
-        .title(format!("{}{}", title, if is_editing { " (editing)" } else { "" }))
-        .borders(Borders::ALL)
-        .border_style(Style::default().fg(border_color));
+        app.start_editing();
+        type_keys(&mut app, "Загрузить файл");
+        press(&mut app, KeyModifiers::NONE, KeyCode::Enter);
+        press(&mut app, KeyModifiers::SHIFT, KeyCode::F(3));
+        assert_eq!(app.get_current_entry().unwrap().msgid, "Close file");
+        assert_eq!(app.status_message.as_deref(), Some("Search wrapped around to the bottom"));
+    }
 
-    let inner_area = block.inner(area);
-    
-    let paragraph = Paragraph::new(display_text)
-        .block(block)
-        .wrap(Wrap { trim: false })
-        .style(Style::default().fg(Color::White));
+    #[test]
+    fn test_search_options() {
+        let mut app = app_with_entries(12);
+        app.po_file.entries[3].msgid = "Test %d".to_string();
+        app.po_file.entries[5].msgctxt = Some("menu".to_string());
+        let alt = KeyModifiers::ALT;
 
-    f.render_widget(paragraph, area);
+        app.perform(Action::StartSearch).unwrap();
+        type_keys(&mut app, "Test");
+        assert_eq!(app.filtered_indices.len(), 12);
+        press(&mut app, alt, KeyCode::Char('c'));
+        assert_eq!(app.filtered_indices, vec![3]);
+        let screen = screen_text(&render(&mut app, 120, 20));
+        assert!(screen.contains("[Case]"), "{}", screen);
 
-    // Draw cursor if editing
-    if is_editing {
-        // Convert character index to byte index for slicing
-        let byte_pos = if cursor_pos <= display_text.chars().count() {
-            display_text.char_indices().nth(cursor_pos).map(|(i, _)| i).unwrap_or(display_text.len())
-        } else {
-            display_text.len()
-        };
-        
-        let text_width = display_text[..byte_pos].width();
-        let cursor_x = inner_area.x + (text_width as u16) % inner_area.width;
-        let cursor_y = inner_area.y + (text_width as u16) / inner_area.width;
-        
-        if cursor_x < inner_area.x + inner_area.width && cursor_y < inner_area.y + inner_area.height {
-            f.render_widget(
-                Block::default().style(Style::default().bg(Color::White)),
-                Rect {
-                    x: cursor_x,
-                    y: cursor_y,
-                    width: 1,
-                    height: 1,
-                },
-            );
+        // An invalid regex is reported and searched for as text
+        press(&mut app, alt, KeyCode::Char('c'));
+        press(&mut app, alt, KeyCode::Char('r'));
+        press(&mut app, KeyModifiers::NONE, KeyCode::Char('('));
+        assert!(app.filtered_indices.is_empty());
+        let screen = screen_text(&render(&mut app, 120, 20));
+        assert!(screen.contains("Invalid regex: error: unclosed group"), "{}", screen);
+        press(&mut app, KeyModifiers::NONE, KeyCode::Backspace);
+        for c in r" \d+$".chars() {
+            press(&mut app, KeyModifiers::NONE, KeyCode::Char(c));
         }
+        assert_eq!(app.filtered_indices.len(), 11);
+        assert_eq!(app.search_error, None);
+
+        press(&mut app, alt, KeyCode::Char('r'));
+        app.filters.text = "menu".to_string();
+        app.refilter();
+        assert!(app.filtered_indices.is_empty());
+        press(&mut app, alt, KeyCode::Char('x'));
+        assert_eq!(app.filtered_indices, vec![5]);
     }
-}
 
-fn draw_metadata_panel(f: &mut Frame, area: Rect, app: &App) {
-    let chunks = Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints([
-            Constraint::Percentage(40),  // Metadata keys list
-            Constraint::Percentage(60),  // Value editor
-        ])
-        .split(area);
-    
-    // Draw metadata keys list
-    let keys_items: Vec<ListItem> = app.metadata_keys
-        .iter()
-        .enumerate()
-        .map(|(i, key)| {
-            let current_value = app.po_file.get_header()
-                .get(key)
-                .cloned()
-                .unwrap_or_default();
-            
-            let display_value = if current_value.len() > 30 {
-                format!("{}...", &current_value[..27])
-            } else {
-                current_value
-            };
-            
-            let prefix = if i == app.metadata_selected { "► " } else { "  " };
-            ListItem::new(format!("{}{}: {}", prefix, key, display_value))
-        })
-        .collect();
-    
-    let keys_list = List::new(keys_items)
-        .block(
-            Block::default()
-                .title("Metadata Fields")
-                .borders(Borders::ALL)
-                .border_style(Style::default().fg(Color::Yellow))
-        )
-        .style(Style::default().fg(Color::White))
-        .highlight_style(Style::default().bg(Color::Yellow).fg(Color::Black));
-    
-    f.render_widget(keys_list, chunks[0]);
-    
-    // Draw value editor
-    if app.metadata_selected < app.metadata_keys.len() {
-        let selected_key = &app.metadata_keys[app.metadata_selected];
-        let current_value = app.po_file.get_header()
-            .get(selected_key)
-            .cloned()
-            .unwrap_or_default();
-        
-        let title = if app.editing && app.metadata_key == *selected_key {
-            format!("{} (editing)", selected_key)
-        } else {
-            selected_key.clone()
-        };
-        
-        let display_text = if app.editing && app.metadata_key == *selected_key {
-            &app.edit_text
-        } else {
-            &current_value
-        };
-        
-        let border_color = if app.editing && app.metadata_key == *selected_key {
-            Color::Green
-        } else {
-            Color::Blue
-        };
-        
-        let paragraph = Paragraph::new(display_text.as_str())
-            .block(
-                Block::default()
-                    .title(title)
-                    .borders(Borders::ALL)
-                    .border_style(Style::default().fg(border_color))
-            )
-            .wrap(Wrap { trim: false })
-            .style(Style::default().fg(Color::White));
-        
-        f.render_widget(paragraph, chunks[1]);
-        
-        // Draw cursor if editing
-        if app.editing && app.metadata_key == *selected_key {
-            let inner_area = Block::default().borders(Borders::ALL).inner(chunks[1]);
-            
-            // Convert character index to byte index for slicing
-            let byte_pos = if app.edit_cursor <= display_text.chars().count() {
-                display_text.char_indices().nth(app.edit_cursor).map(|(i, _)| i).unwrap_or(display_text.len())
-            } else {
-                display_text.len()
-            };
-            
-            let text_width = display_text[..byte_pos].width();
-            let cursor_x = inner_area.x + (text_width as u16) % inner_area.width;
-            let cursor_y = inner_area.y + (text_width as u16) / inner_area.width;
-            
-            if cursor_x < inner_area.x + inner_area.width && cursor_y < inner_area.y + inner_area.height {
-                f.render_widget(
-                    Block::default().style(Style::default().bg(Color::White)),
-                    Rect {
-                        x: cursor_x,
-                        y: cursor_y,
-                        width: 1,
-                        height: 1,
-                    },
-                );
-            }
+    #[test]
+    fn test_search_status_scope() {
+        let mut app = app_with_entries(12);
+        for i in [1, 10] {
+            app.po_file.entries[i].set_msgstr(String::new());
         }
+        app.po_file.entries[11].toggle_fuzzy();
+
+        app.perform(Action::StartSearch).unwrap();
+        press(&mut app, KeyModifiers::NONE, KeyCode::Tab);
+        assert_eq!(app.filters.search_status, SearchStatus::Untranslated);
+        press(&mut app, KeyModifiers::NONE, KeyCode::Char('1'));
+        assert_eq!(app.filtered_indices, vec![1, 10]);
+        press(&mut app, KeyModifiers::NONE, KeyCode::Tab);
+        assert_eq!(app.filtered_indices, vec![11]);
+        press(&mut app, KeyModifiers::NONE, KeyCode::Tab);
+        assert_eq!(app.filtered_indices, Vec::<usize>::new());
+        let buffer = render(&mut app, 100, 20);
+        let screen: String = (0..20).flat_map(|y| (0..100).map(move |x| (x, y))).map(|p| buffer[p].symbol()).collect();
+        assert!(screen.contains("Search [Translated] (Tab: status, Alt+C/R/X: options)"));
+
+        // The global status filter still applies: translated entries among
+        // the untranslated ones are none
+        press(&mut app, KeyModifiers::NONE, KeyCode::Backspace);
+        assert_eq!(app.filters.search_status, SearchStatus::All);
+        press(&mut app, KeyModifiers::NONE, KeyCode::Tab);
+        press(&mut app, KeyModifiers::NONE, KeyCode::Char('1'));
+        press(&mut app, KeyModifiers::NONE, KeyCode::Enter);
+        app.toggle_untranslated_filter();
+        assert_eq!(app.filtered_indices, vec![1, 10]);
+        app.perform(Action::StartSearch).unwrap();
+        press(&mut app, KeyModifiers::NONE, KeyCode::Tab);
+        press(&mut app, KeyModifiers::NONE, KeyCode::Tab);
+        assert_eq!(app.filters.search_status, SearchStatus::Translated);
+        assert!(app.filtered_indices.is_empty());
     }
-}
 
-fn draw_footer(f: &mut Frame, area: Rect, app: &App) {
-    let help_text = if app.search_mode {
-        "Search mode: Type to search, Enter to finish, Esc to cancel"
-    } else if app.editing {
-        "Edit mode: Type to edit, Enter to save, Esc to cancel"
-    } else if app.metadata_mode {
-        "Metadata mode: ↑/↓/j/k: Navigate fields | Enter/i: Edit selected | Esc: Cancel | F9: Exit | Ctrl+S: Save | F1: Help"
-    } else {
-        "Ctrl+Q: Quit | Ctrl+S: Save | Enter: Edit | F2/Ctrl+T: Toggle fuzzy | Ctrl+D: Mark done | F9: Metadata | F1: Help"
-    };
+    #[test]
+    fn test_preview() {
+        let mut app = app_with_entries(2);
+        app.config.preview_width = 20;
+        app.po_file.entries[0].set_msgstr("Открыто %d файлов:\t%s и ещё немного текста".to_string());
+        let entry = app.po_file.entries[0].clone();
+        app.perform(Action::ShowPreview).unwrap();
+        assert_eq!(app.overlay, Some(Overlay::Preview));
 
-    let block = Block::default()
-        .borders(Borders::ALL)
-        .border_style(Style::default().fg(Color::Gray));
+        let buffer = render(&mut app, 80, 24);
+        let screen: Vec<String> = (0..24).map(|y| (0..80).map(|x| buffer[(x, y)].symbol()).collect()).collect();
+        let top = screen.iter().position(|line| line.contains("Preview, 20 columns")).unwrap();
+        assert!(screen[top + 1].contains("│Открыто 42 файлов:  │"));
+        assert!(screen[top + 2].contains("│example и ещё       │"));
+        assert!(screen[top + 3].contains("│немного текста      │"));
 
-    let paragraph = Paragraph::new(help_text)
-        .block(block)
-        .alignment(Alignment::Center)
-        .style(Style::default().fg(Color::White));
+        app.perform(Action::Input(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE))).unwrap();
+        assert_eq!(app.overlay, None);
+        assert_eq!(app.po_file.entries[0], entry);
+        assert!(!app.is_modified());
+    }
 
-    f.render_widget(paragraph, area);
-}
+    #[test]
+    fn test_filtered_count_and_empty_notice() {
+        let mut app = app_with_entries(5);
+        for i in [1, 3] {
+            app.po_file.entries[i].set_msgstr(String::new());
+        }
+        let screen = |app: &mut App| {
+            let buffer = render(app, 100, 20);
+            (0..20)
+                .map(|y| (0..100).map(|x| buffer[(x, y)].symbol()).collect::<String>())
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
+        assert!(!screen(&mut app).contains("showing"));
 
-fn draw_search_overlay(f: &mut Frame, app: &App) {
-    let area = centered_rect(60, 3, f.area());
-    
-    f.render_widget(Clear, area);
-    
-    let block = Block::default()
-        .title("Search")
-        .borders(Borders::ALL)
-        .border_style(Style::default().fg(Color::Green));
+        app.toggle_untranslated_filter();
+        assert!(screen(&mut app).contains("Entries [Untranslated] showing 2 of 5"));
 
-    let search_text = format!("{}{}", app.search_query, 
-        if app.search_cursor == app.search_query.len() { "█" } else { "" });
+        for i in [1, 3] {
+            app.po_file.entries[i].set_msgstr("перевод".to_string());
+        }
+        app.refilter();
+        let text = screen(&mut app);
+        assert!(text.contains("showing 0 of 5"));
+        assert!(text.contains("No untranslated entries left"));
 
-    let paragraph = Paragraph::new(search_text)
-        .block(block)
-        .style(Style::default().fg(Color::White));
+        app.toggle_untranslated_filter();
+        app.filters.text = "nothing".to_string();
+        app.refilter();
+        assert!(screen(&mut app).contains("No entries match the filters"));
+    }
 
-    f.render_widget(paragraph, area);
-}
+    #[test]
+    fn test_read_only() {
+        let mut app = app_with_entries(3).with_read_only(true);
+        let entry = app.po_file.entries[0].clone();
+        for action in [Action::StartEditing, Action::ToggleFuzzy, Action::MarkDone, Action::Save] {
+            assert_eq!(app.perform(action).unwrap(), Outcome::Blocked);
+            assert_eq!(app.status_message.as_deref(), Some(READ_ONLY_MESSAGE));
+        }
+        assert_eq!(app.po_file.entries[0], entry);
+        assert!(!app.editing);
 
-fn draw_help_overlay(f: &mut Frame) {
-    let area = centered_rect(80, 25, f.area());
-    
-    f.render_widget(Clear, area);
-    
-    let help_text = vec![
-        Line::from("Navigation:"),
-        Line::from("  j/↓        - Next entry"),
-        Line::from("  k/↑        - Previous entry"),
-        Line::from("  PageUp     - Page up"),
-        Line::from("  PageDown   - Page down"),
-        Line::from("  Home       - First entry"),
-        Line::from("  End        - Last entry"),
-        Line::from(""),
-        Line::from("Editing:"),
-        Line::from("  i/Enter    - Start editing"),
-        Line::from("  Esc        - Stop editing"),
-        Line::from("  Tab        - Next field"),
-        Line::from("  Shift+Tab  - Previous field"),
-        Line::from(""),
-        Line::from("Translation Status:"),
-        Line::from("  F2/Ctrl+T  - Toggle fuzzy status"),
-        Line::from("  Ctrl+D     - Mark entry as done"),
-        Line::from(""),
-        Line::from("Metadata Editing:"),
-        Line::from("  F9         - Enter/exit metadata mode"),
-        Line::from("  ↑/↓        - Navigate fields (in metadata mode)"),
-        Line::from("  Enter      - Edit selected field"),
-        Line::from(""),
-        Line::from("Search & Filter:"),
-        Line::from("  Ctrl+F     - Search"),
-        Line::from("  F3         - Find next"),
-        Line::from("  Shift+F3   - Find previous"),
-        Line::from("  Ctrl+U     - Toggle untranslated filter"),
-        Line::from("  Ctrl+Z     - Toggle fuzzy filter"),
-        Line::from(""),
-        Line::from("File Operations:"),
-        Line::from("  Ctrl+S     - Save file"),
-        Line::from("  Ctrl+Shift+P - Save current entry"),
-        Line::from("  Ctrl+Q     - Quit"),
-        Line::from(""),
-        Line::from("Press Esc to close this help"),
-    ];
+        // Looking around still works
+        assert_eq!(app.perform(Action::Next).unwrap(), Outcome::Done);
+        app.perform(Action::StartSearch).unwrap();
+        assert_eq!(app.perform(Action::StartEditing).unwrap(), Outcome::Done);
+        assert!(!app.search_mode);
 
-    let block = Block::default()
-        .title("Help")
-        .borders(Borders::ALL)
-        .border_style(Style::default().fg(Color::Cyan));
+        let buffer = render(&mut app, 100, 20);
+        let top: String = (0..100).map(|x| buffer[(x, 0)].symbol()).collect();
+        assert!(top.contains("[READ-ONLY]"));
+    }
+
+    #[test]
+    fn test_tree_view() {
+        let mut app = app_with_entries(4);
+        for (i, reference) in ["b.c:1", "a.c:1", "b.c:2", ""].into_iter().enumerate() {
+            if !reference.is_empty() {
+                app.po_file.entries[i].references.push(reference.to_string());
+            }
+        }
+        app.perform(Action::ToggleTreeView).unwrap();
+        // a.c, test 1, b.c, test 0, test 2, (no reference), test 3
+        assert_eq!(app.list_state.selected(), Some(3));
+
+        // Left closes the group and selects its line, which shows the
+        // group's first entry
+        assert_eq!(app.perform(Action::CollapseGroup).unwrap(), Outcome::Done);
+        assert_eq!(app.list_state.selected(), Some(2));
+        assert_eq!(app.get_current_entry().unwrap().msgid, "test 0");
+        assert_eq!(app.perform(Action::CollapseGroup).unwrap(), Outcome::Blocked);
+        app.perform(Action::Next).unwrap();
+        assert_eq!(app.get_current_entry().unwrap().msgid, "test 3");
+        app.perform(Action::First).unwrap();
+        app.perform(Action::Next).unwrap();
+        assert_eq!(app.get_current_entry().unwrap().msgid, "test 1");
+
+        // Enter on a group line opens it instead of editing
+        app.perform(Action::Next).unwrap();
+        app.perform(Action::StartEditing).unwrap();
+        assert!(!app.editing);
+        assert_eq!(app.perform(Action::ExpandGroup).unwrap(), Outcome::Done);
+        assert_eq!(app.get_current_entry().unwrap().msgid, "test 0");
+        assert_eq!(app.list_state.selected(), Some(3));
+        app.perform(Action::StartEditing).unwrap();
+        assert!(app.editing);
+        app.perform(Action::Cancel).unwrap();
+
+        // Filters drop groups without listed entries
+        app.filters.text = "test 2".to_string();
+        app.refilter();
+        let buffer = render(&mut app, 80, 20);
+        let lines: Vec<String> = (0..20)
+            .map(|y| (1..30).map(|x| buffer[(x, y)].symbol()).collect::<String>().trim_end().to_string())
+            .collect();
+        let group = lines.iter().position(|line| line.contains("b.c")).unwrap();
+        assert_eq!(lines[group], "  ▾ b.c 2/2 (100%)");
+        assert!(lines[group + 1].starts_with("►   ✓ 3 test 2"), "{}", lines[group + 1]);
+        assert_eq!(lines[group + 2], "");
+    }
 
-    let paragraph = Paragraph::new(help_text)
-        .block(block)
-        .style(Style::default().fg(Color::White));
+    #[test]
+    fn test_reference_column() {
+        let mut app = app_with_entries(3);
+        app.po_file.entries[0].references.push("src/gtk/window.c:215 src/menu.c:4".to_string());
+        app.po_file.entries[1].references.push("main.c:9".to_string());
+        app.perform(Action::ToggleReferenceColumn).unwrap();
+        assert!(app.list_columns.reference);
 
-    f.render_widget(paragraph, area);
-}
+        // The list is 80 cells wide, the reference column a third at most
+        let buffer = render(&mut app, 200, 20);
+        let lines: Vec<String> = (0..20)
+            .map(|y| (1..79).map(|x| buffer[(x, y)].symbol()).collect::<String>().trim_end().to_string())
+            .collect();
+        let first = lines.iter().position(|line| line.contains("test 0")).unwrap();
+        assert!(lines[first].ends_with(" window.c:215"), "{}", lines[first]);
+        assert!(lines[first + 1].ends_with("     main.c:9"), "{}", lines[first + 1]);
+        assert!(lines[first + 2].ends_with("test 2"));
+    }
 
-fn centered_rect(percent_x: u16, height: u16, r: Rect) -> Rect {
-    let popup_layout = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([
-            Constraint::Length((r.height - height) / 2),
-            Constraint::Length(height),
-            Constraint::Length((r.height - height) / 2),
-        ])
-        .split(r);
+    #[test]
+    fn test_go_to_entry_follows_numbering() {
+        let mut app = app_with_entries(5);
+        for i in [1, 3, 4] {
+            app.po_file.entries[i].set_msgstr(String::new());
+        }
+        app.perform(Action::ToggleUntranslatedFilter).unwrap();
+        assert_eq!(app.filtered_indices, vec![1, 3, 4]);
+        let enter = Action::Input(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
 
-    Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints([
-            Constraint::Percentage((100 - percent_x) / 2),
-            Constraint::Percentage(percent_x),
-            Constraint::Percentage((100 - percent_x) / 2),
-        ])
-        .split(popup_layout[1])[1]
-}
+        // File positions by default
+        app.perform(Action::GoToEntry).unwrap();
+        type_keys(&mut app, "4");
+        app.perform(enter).unwrap();
+        assert_eq!(app.current_entry, 1);
+        assert_eq!(app.status_message.as_deref(), Some("Entry 4 by file position"));
+        app.perform(Action::GoToEntry).unwrap();
+        type_keys(&mut app, "3");
+        app.perform(enter).unwrap();
+        assert_eq!(app.current_entry, 1);
+        assert_eq!(app.status_message.as_deref(), Some("Entry 3 is hidden by the current filter"));
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::gettext::{PoFile, PoEntry};
+        app.perform(Action::CycleIndexColumn).unwrap();
+        app.perform(Action::GoToEntry).unwrap();
+        type_keys(&mut app, "3");
+        app.perform(enter).unwrap();
+        assert_eq!(app.current_entry, 2);
+        assert_eq!(app.status_message.as_deref(), Some("Entry 3 by filter position"));
+
+        let buffer = render(&mut app, 140, 20);
+        let top: String = (0..56).map(|x| buffer[(x, 3)].symbol()).collect();
+        assert!(top.contains("showing 3 of 5 #filter"), "{}", top);
+    }
 
     #[test]
-    fn test_char_to_byte_index() {
-        let text = "Hello мир world";  // Contains Cyrillic characters
-        
-        // Test ASCII characters
-        assert_eq!(App::char_to_byte_index(text, 0), 0);  // 'H'
-        assert_eq!(App::char_to_byte_index(text, 5), 5);  // Space after "Hello"
-        
-        // Test Unicode boundaries
-        assert_eq!(App::char_to_byte_index(text, 6), 6);  // 'м' starts at byte 6
-        assert_eq!(App::char_to_byte_index(text, 7), 8);  // 'и' starts at byte 8 (м is 2 bytes)
-        assert_eq!(App::char_to_byte_index(text, 8), 10); // 'р' starts at byte 10 (и is 2 bytes)
-        
-        // Test beyond string length
-        assert_eq!(App::char_to_byte_index(text, 100), text.len());
+    fn test_go_to_reference() {
+        let mut app = app_with_entries(4);
+        for (i, reference) in ["src/export.c:142", "src/export.c:150", "src/gtk/window.c:10", "src/qt/window.c:10"]
+            .into_iter()
+            .enumerate()
+        {
+            app.po_file.entries[i].references = vec![reference.to_string()];
+        }
+        app.po_file.entries[1].set_msgstr(String::new());
+        app.perform(Action::ToggleUntranslatedFilter).unwrap();
+        assert_eq!(app.filtered_indices, vec![1]);
+
+        // Ctrl+G twice switches to source references; a hidden entry clears the filters
+        press(&mut app, KeyModifiers::CONTROL, KeyCode::Char('g'));
+        press(&mut app, KeyModifiers::CONTROL, KeyCode::Char('g'));
+        assert!(matches!(app.overlay, Some(Overlay::Prompt(ref p)) if p.kind == PromptKind::GoToReference));
+        type_keys(&mut app, "./src/export.c:140");
+        press(&mut app, KeyModifiers::NONE, KeyCode::Enter);
+        assert_eq!(app.filtered_indices.len(), 4);
+        assert_eq!(app.filtered_indices[app.current_entry], 0);
+        assert_eq!(app.status_message.as_deref(), Some("Filters cleared to show the entry"));
+
+        // A location pasted into the number prompt works too
+        press(&mut app, KeyModifiers::CONTROL, KeyCode::Char('g'));
+        type_keys(&mut app, "export.c:149");
+        press(&mut app, KeyModifiers::NONE, KeyCode::Enter);
+        assert_eq!(app.current_entry, 1);
+
+        // Equally good matches are listed to choose from
+        app.perform(Action::GoToEntry).unwrap();
+        type_keys(&mut app, "window.c:10");
+        press(&mut app, KeyModifiers::NONE, KeyCode::Enter);
+        let screen = screen_text(&render(&mut app, 100, 30));
+        assert!(screen.contains("Several entries match"), "{}", screen);
+        assert!(screen.contains("test 2  src/gtk/window.c:10"), "{}", screen);
+        assert!(screen.contains("test 3  src/qt/window.c:10"), "{}", screen);
+        press(&mut app, KeyModifiers::NONE, KeyCode::Down);
+        press(&mut app, KeyModifiers::NONE, KeyCode::Enter);
+        assert!(app.overlay.is_none());
+        assert_eq!(app.current_entry, 3);
+
+        app.perform(Action::GoToEntry).unwrap();
+        type_keys(&mut app, "src/import.c");
+        press(&mut app, KeyModifiers::NONE, KeyCode::Enter);
+        assert_eq!(app.status_message.as_deref(), Some("No entry references src/import.c"));
+    }
+
+    fn screen_text(buffer: &ratatui::buffer::Buffer) -> String {
+        let area = buffer.area;
+        (area.top()..area.bottom())
+            .map(|y| (area.left()..area.right()).map(|x| buffer[(x, y)].symbol()).collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n")
     }
 
     #[test]
-    fn test_remove_char_at() {
-        let mut text = String::from("Hello мир world");
-        
-        // Remove ASCII character
-        App::remove_char_at(&mut text, 0);
-        assert_eq!(text, "ello мир world");
-        
-        // Remove Unicode character
-        let mut text = String::from("Hello мир world");
-        App::remove_char_at(&mut text, 6); // Remove 'м'
-        assert_eq!(text, "Hello ир world");
-        
-        // Remove beyond string length (should do nothing)
-        let mut text = String::from("test");
-        let original_len = text.len();
-        App::remove_char_at(&mut text, 100);
-        assert_eq!(text.len(), original_len);
+    fn test_tiny_terminal() {
+        let mut app = app_with_entries(3);
+        app.help_visible = true;
+        app.search_mode = true;
+        let text = screen_text(&render(&mut app, 20, 5));
+        assert!(text.contains("Terminal too"), "{}", text);
+        assert!(text.contains("40x10"), "{}", text);
+        let text = screen_text(&render(&mut app, 80, 3));
+        assert!(text.contains("Terminal too small (need at least 40x10)"), "{}", text);
+
+        // Resizing back draws the interface, popups included
+        let text = screen_text(&render(&mut app, 40, 10));
+        assert!(!text.contains("Terminal too small"), "{}", text);
+        app.help_visible = false;
+        app.perform(Action::ShowPreview).unwrap();
+        render(&mut app, 40, 10);
     }
 
     #[test]
-    fn test_toggle_metadata_mode() {
-        let po_file = PoFile::default();
-        let mut app = App::new(po_file);
-        
-        // Initially should not be in metadata mode
-        assert!(!app.is_metadata_mode());
-        assert_eq!(app.edit_field, EditField::Msgstr);
-        
-        // Toggle to metadata mode
-        app.toggle_metadata_mode();
-        assert!(app.is_metadata_mode());
-        assert_eq!(app.edit_field, EditField::Metadata);
-        
-        // Toggle back
-        app.toggle_metadata_mode();
-        assert!(!app.is_metadata_mode());
-        assert_eq!(app.edit_field, EditField::Msgstr);
-        
-        // Should not toggle when editing
-        app.editing = true;
-        app.toggle_metadata_mode();
-        assert!(!app.is_metadata_mode());  // Should remain false
+    fn test_centered_rect_fits_area() {
+        let area = Rect::new(0, 0, 80, 12);
+        assert_eq!(centered_rect(80, 25, area).height, 12);
+        assert_eq!(centered_rect(60, 4, area), Rect::new(16, 4, 48, 4));
+        // Smaller than any popup, down to nothing at all
+        assert_eq!(centered_rect(80, 25, Rect::new(3, 2, 5, 1)).height, 1);
+        let empty = centered_rect(80, 25, Rect::new(0, 0, 0, 0));
+        assert_eq!((empty.width, empty.height), (0, 0));
+    }
+
+    /// Entry numbers of the rows shown in the entry list, top to bottom
+    fn visible_entries(buffer: &ratatui::buffer::Buffer) -> Vec<usize> {
+        let area = buffer.area;
+        (area.top()..area.bottom())
+            .filter_map(|y| {
+                let line: String = (0..area.width).map(|x| buffer[(x, y)].symbol()).collect();
+                let rest = line.strip_prefix('│')?;
+                let list = &rest[..rest.find('│')?];
+                let number = list.split("test ").nth(1)?;
+                number.split_whitespace().next()?.parse().ok()
+            })
+            .collect()
     }
 
     #[test]
-    fn test_insert_char_at() {
-        let mut text = String::from("Hello world");
-        
-        // Insert ASCII character
-        App::insert_char_at(&mut text, 5, ' ');
-        assert_eq!(text, "Hello  world");
-        
-        // Insert Unicode character
-        let mut text = String::from("Hello world");
-        App::insert_char_at(&mut text, 5, 'ё');
-        assert_eq!(text, "Helloё world");
-        
-        // Insert at beginning
-        let mut text = String::from("test");
-        App::insert_char_at(&mut text, 0, 'X');
-        assert_eq!(text, "Xtest");
-        
-        // Insert at end
-        let mut text = String::from("test");
-        App::insert_char_at(&mut text, 4, '!');
-        assert_eq!(text, "test!");
+    fn test_scroll_offset() {
+        // Moving down keeps three rows below the selection in view
+        assert_eq!(scroll_offset(0, 6, 100, 10, 3, false), 0);
+        assert_eq!(scroll_offset(0, 7, 100, 10, 3, false), 1);
+        // Moving up keeps three rows above it
+        assert_eq!(scroll_offset(20, 23, 100, 10, 3, false), 20);
+        assert_eq!(scroll_offset(20, 22, 100, 10, 3, false), 19);
+        // Not past either end of the list
+        assert_eq!(scroll_offset(5, 1, 100, 10, 3, false), 0);
+        assert_eq!(scroll_offset(80, 99, 100, 10, 3, false), 90);
+        assert_eq!(scroll_offset(0, 4, 5, 10, 3, false), 0);
+        // A margin that cannot fit shrinks to what the height allows
+        assert_eq!(scroll_offset(0, 3, 100, 4, 3, false), 1);
+        assert_eq!(scroll_offset(0, 3, 100, 1, 3, false), 3);
+        // Centered, except at the ends
+        assert_eq!(scroll_offset(0, 50, 100, 10, 3, true), 45);
+        assert_eq!(scroll_offset(0, 2, 100, 10, 3, true), 0);
+        assert_eq!(scroll_offset(0, 98, 100, 10, 3, true), 90);
     }
 
     #[test]
-    fn test_page_navigation() {
-        let mut po_file = PoFile::default();
-        // Add test entries
-        for i in 0..25 {
-            let mut entry = PoEntry::new();
-            entry.msgid = format!("test {}", i);
-            po_file.entries.push(entry);
+    fn test_list_keeps_context_around_selection() {
+        let mut app = app_with_entries(100);
+        for _ in 0..50 {
+            app.perform(Action::Next).unwrap();
         }
-        
-        let mut app = App::new(po_file);
-        
-        // Test page down
-        app.page_down();
-        assert_eq!(app.current_entry, PAGE_SIZE);
-        
-        // Test page up
-        app.page_up();
-        assert_eq!(app.current_entry, 0);
-        
-        // Test page down at end
-        app.current_entry = 20;
-        app.page_down();
-        assert_eq!(app.current_entry, 24); // Should go to last entry
+        let buffer = render(&mut app, 100, 20);
+        assert_eq!(visible_entries(&buffer), (42..=53).collect::<Vec<_>>());
+        let top: String = (0..40).map(|x| buffer[(x, 3)].symbol()).collect();
+        assert!(top.contains("[All] 51% #file"), "{}", top);
+
+        // Going back up scrolls only once the margin above is reached
+        for _ in 0..5 {
+            app.perform(Action::Previous).unwrap();
+        }
+        assert_eq!(visible_entries(&render(&mut app, 100, 20)), (42..=53).collect::<Vec<_>>());
+        app.perform(Action::Previous).unwrap();
+        assert_eq!(visible_entries(&render(&mut app, 100, 20)), (41..=52).collect::<Vec<_>>());
+
+        // Taller terminals show more rows, still stopping at the last entry
+        for _ in 0..60 {
+            app.perform(Action::Next).unwrap();
+        }
+        let shown = visible_entries(&render(&mut app, 100, 30));
+        assert_eq!(shown.last(), Some(&99));
+        assert_eq!(shown.len(), 22);
     }
 
     #[test]
-    fn test_edit_field_cycling() {
-        let po_file = PoFile::default();
-        let mut app = App::new(po_file);
-        
-        assert_eq!(app.edit_field, EditField::Msgstr);
-        
-        app.next_field();
-        assert_eq!(app.edit_field, EditField::Comments);
-        
-        app.next_field();
-        assert_eq!(app.edit_field, EditField::Msgid);
-        
-        app.next_field();
-        assert_eq!(app.edit_field, EditField::Msgstr);
-        
-        app.previous_field();
-        assert_eq!(app.edit_field, EditField::Msgid);
+    fn test_list_center_selection() {
+        let mut app = app_with_entries(100);
+        app.list_columns.center_selection = true;
+        for _ in 0..50 {
+            app.perform(Action::Next).unwrap();
+        }
+        assert_eq!(visible_entries(&render(&mut app, 100, 20)), (44..=55).collect::<Vec<_>>());
+        app.perform(Action::Next).unwrap();
+        assert_eq!(visible_entries(&render(&mut app, 100, 20)), (45..=56).collect::<Vec<_>>());
+
+        // A list that fits has no position to show
+        let mut app = app_with_entries(5);
+        app.list_columns.center_selection = true;
+        let buffer = render(&mut app, 100, 20);
+        assert_eq!(visible_entries(&buffer), (0..5).collect::<Vec<_>>());
+        let top: String = (0..40).map(|x| buffer[(x, 3)].symbol()).collect();
+        assert!(!top.contains('%'), "{}", top);
     }
-    
+
     #[test]
-    fn test_metadata_mode() {
-        let po_file = PoFile::default();
-        let mut app = App::new(po_file);
-        
-        assert!(!app.metadata_mode);
-        
-        app.toggle_metadata_mode();
-        assert!(app.metadata_mode);
-        assert_eq!(app.edit_field, EditField::Metadata);
+    fn test_start_at() {
+        let open = |start_at: StartAt, filter: Option<FilterMode>| {
+            let mut po_file = app_with_entries(5).po_file;
+            po_file.entries[1].flags.push("fuzzy".to_string());
+            po_file.entries[1].update_status();
+            po_file.entries[3].set_msgstr(String::new());
+            let config = Config {
+                start_at,
+                ..Config::default()
+            };
+            let app = App::new(po_file, config).with_state(StateStore::default()).with_filter(filter);
+            app.get_current_entry().unwrap().msgid.clone()
+        };
+        assert_eq!(open(StartAt::LastSession, None), "test 0");
+        assert_eq!(open(StartAt::FirstUntranslated, None), "test 3");
+        // The untranslated entry is filtered out, so the fuzzy one is next
+        assert_eq!(open(StartAt::FirstUntranslated, Some(FilterMode::Fuzzy)), "test 1");
+        assert_eq!(open(StartAt::First, Some(FilterMode::Untranslated)), "test 1");
     }
 
     #[test]
-    fn test_toggle_fuzzy_functionality() {
-        let mut po_file = PoFile::default();
-        
-        // Add a translated entry
-        let mut entry = PoEntry::new();
-        entry.msgid = "Hello".to_string();
-        entry.set_msgstr("Привет".to_string());
-        po_file.entries.push(entry);
-        
-        // Add a fuzzy entry
-        let mut fuzzy_entry = PoEntry::new();
-        fuzzy_entry.msgid = "World".to_string();
-        fuzzy_entry.msgstr = "Мир".to_string();
-        fuzzy_entry.flags.push("fuzzy".to_string());
-        fuzzy_entry.update_status();
-        po_file.entries.push(fuzzy_entry);
-        
-        let mut app = App::new(po_file);
-        
-        // Test toggle fuzzy on translated entry (index 0)
-        assert!(!app.po_file.entries[0].is_fuzzy);
-        app.toggle_current_entry_fuzzy();
-        assert!(app.po_file.entries[0].is_fuzzy);
-        
-        // Toggle back
-        app.toggle_current_entry_fuzzy();
-        assert!(!app.po_file.entries[0].is_fuzzy);
-        
-        // Move to fuzzy entry (index 1)
-        app.next_entry();
-        assert!(app.po_file.entries[1].is_fuzzy);
-        
-        // Toggle fuzzy off (mark as done)
-        app.toggle_current_entry_fuzzy();
-        assert!(!app.po_file.entries[1].is_fuzzy);
-        assert!(app.po_file.entries[1].is_translated);
+    fn test_needs_work() {
+        let mut app = app_with_entries(3);
+        assert_eq!(app.perform(Action::ToggleNeedsWork).unwrap(), Outcome::Done);
+        let entry = &app.po_file.entries[0];
+        assert!(entry.has_flag("poterm-needs-work"));
+        // Still translated, so msgfmt compiles it
+        assert!(entry.is_translated && !entry.is_fuzzy);
+
+        app.perform(Action::ToggleNeedsWorkFilter).unwrap();
+        assert_eq!(app.filtered_indices, vec![0]);
+        let buffer = render(&mut app, 100, 20);
+        let list: String = (0..20).map(|y| buffer[(3, y)].symbol()).collect();
+        assert!(list.contains('!'), "{}", list);
+
+        app.perform(Action::ToggleFuzzy).unwrap();
+        app.perform(Action::MarkDone).unwrap();
+        assert!(app.po_file.entries[0].flags.is_empty());
     }
 
     #[test]
-    fn test_mark_entry_done() {
-        let mut po_file = PoFile::default();
-        
-        // Add a fuzzy entry
-        let mut entry = PoEntry::new();
-        entry.msgid = "Test".to_string();
-        entry.msgstr = "Тест".to_string();
-        entry.flags.push("fuzzy".to_string());
-        entry.update_status();
-        po_file.entries.push(entry);
-        
-        let mut app = App::new(po_file);
-        
-        assert!(app.po_file.entries[0].is_fuzzy);
-        assert!(!app.po_file.entries[0].is_translated);
-        
-        // Mark as done
-        app.mark_current_entry_done();
-        
-        assert!(!app.po_file.entries[0].is_fuzzy);
-        assert!(app.po_file.entries[0].is_translated);
-        assert!(!app.po_file.entries[0].flags.contains(&"fuzzy".to_string()));
+    fn test_issues_filter() {
+        let mut app = app_with_entries(3);
+        app.po_file.entries[1].set_msgstr("перевод 1 ".to_string());
+        app.po_file.entries[2].set_msgstr("перевод...  2".to_string());
+        app.po_file.mark_modified();
+
+        press(&mut app, KeyModifiers::CONTROL, KeyCode::Char('e'));
+        assert_eq!(app.filters.status, FilterMode::Issues);
+        assert_eq!(app.filtered_indices, vec![1, 2]);
+        let screen = screen_text(&render(&mut app, 120, 20));
+        assert!(screen.contains("Untranslated: 0 | Issues: 3"), "{}", screen);
+        assert!(screen.contains("Entries [Issues]"), "{}", screen);
+        assert!(screen.contains(" 1 test 1"), "{}", screen);
+        assert!(screen.contains(" 2 test 2"), "{}", screen);
+
+        // A fixed entry drops out as soon as the edit is committed
+        app.start_editing();
+        press(&mut app, KeyModifiers::NONE, KeyCode::Backspace);
+        press(&mut app, KeyModifiers::NONE, KeyCode::Enter);
+        assert_eq!(app.filtered_indices, vec![2]);
+        assert_eq!(app.get_current_entry().unwrap().msgid, "test 2");
+
+        press(&mut app, KeyModifiers::CONTROL, KeyCode::Char('e'));
+        assert_eq!(app.filters.status, FilterMode::All);
+        assert_eq!(app.filtered_indices, vec![0, 1, 2]);
     }
 
     #[test]
-    fn test_fuzzy_toggle_edge_cases() {
-        let mut po_file = PoFile::default();
-        
-        // Add an untranslated entry (empty msgstr)
-        let mut entry = PoEntry::new();
-        entry.msgid = "Empty".to_string();
-        entry.msgstr = "".to_string();
-        po_file.entries.push(entry);
-        
-        let mut app = App::new(po_file);
-        
-        // Should not toggle fuzzy on empty translation
-        assert!(!app.po_file.entries[0].is_fuzzy);
-        app.toggle_current_entry_fuzzy();
-        assert!(!app.po_file.entries[0].is_fuzzy);
-        
-        // Should not mark as done if no translation
-        app.mark_current_entry_done();
-        assert!(!app.po_file.entries[0].is_translated);
+    fn test_inconsistent_translations() {
+        let mut app = app_with_entries(4);
+        for i in [0, 2, 3] {
+            app.po_file.entries[i].msgid = "Open".to_string();
+        }
+        app.po_file.entries[2].set_msgstr("Открой".to_string());
+        app.po_file.mark_modified();
+        app.refresh_msgid_index();
+        // Entry 0 and 3 have different translations, as has entry 2
+        assert_eq!(app.inconsistent_translations(), vec![2, 3]);
+
+        let info = |app: &mut App| {
+            let buffer = render(app, 120, 30);
+            (0..30)
+                .map(|y| (0..120).map(|x| buffer[(x, y)].symbol()).collect::<String>())
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
+        assert!(info(&mut app).contains("Same msgid translated differently in 2 other entries"));
+
+        assert_eq!(app.perform(Action::NextInconsistent).unwrap(), Outcome::Done);
+        assert_eq!(app.current_entry, 2);
+        app.perform(Action::NextInconsistent).unwrap();
+        assert_eq!(app.current_entry, 3);
+        app.perform(Action::NextInconsistent).unwrap();
+        assert_eq!(app.current_entry, 0);
+
+        // Edits are seen at the next selection
+        app.edit_field = EditField::Msgstr;
+        app.perform(Action::StartEditing).unwrap();
+        app.edit_text = "Открой".to_string();
+        app.perform(Action::StartEditing).unwrap();
+        assert!(info(&mut app).contains("in 1 other entry"));
     }
-}
\ No newline at end of file
+}
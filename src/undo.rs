@@ -0,0 +1,191 @@
+// Poterm - Modern TUI editor for .po translation files
+// Copyright (c) 2025 AnmiTaliDev <anmitali198@gmail.com>
+// Licensed under the Apache License, Version 2.0
+
+//! Undo and redo of entry and header changes
+
+use crate::gettext::PoEntry;
+use indexmap::IndexMap;
+use std::collections::VecDeque;
+
+/// Changes kept for undo; older ones are forgotten
+const MAX_UNDO: usize = 500;
+
+/// One change, with the state before and after it
+#[derive(Debug, Clone, PartialEq)]
+pub enum Edit {
+    Entry { index: usize, before: Box<PoEntry>, after: Box<PoEntry> },
+    Header { before: IndexMap<String, String>, after: IndexMap<String, String> },
+    /// Changes undone and redone together, in the order they were made
+    Group(Vec<Edit>),
+}
+
+impl Edit {
+    /// The change that takes `after` back to `before`
+    fn reversed(self) -> Self {
+        match self {
+            Edit::Entry { index, before, after } => Edit::Entry {
+                index,
+                before: after,
+                after: before,
+            },
+            Edit::Header { before, after } => Edit::Header {
+                before: after,
+                after: before,
+            },
+            Edit::Group(edits) => Edit::Group(edits.into_iter().rev().map(Edit::reversed).collect()),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct UndoHistory {
+    undo: VecDeque<Edit>,
+    redo: Vec<Edit>,
+    /// Length of `undo` when the file was last saved, `None` once that state
+    /// can't be reached again
+    saved: Option<usize>,
+    /// Changes collected into one step until the group ends
+    group: Option<Vec<Edit>>,
+}
+
+impl Default for UndoHistory {
+    fn default() -> Self {
+        Self {
+            undo: VecDeque::new(),
+            redo: Vec::new(),
+            saved: Some(0),
+            group: None,
+        }
+    }
+}
+
+impl UndoHistory {
+    /// Record a change just made. Changes undone before can't be redone
+    /// after it.
+    pub fn push(&mut self, edit: Edit) {
+        if let Some(ref mut group) = self.group {
+            group.push(edit);
+            return;
+        }
+        if self.saved.is_some_and(|saved| saved > self.undo.len()) {
+            self.saved = None;
+        }
+        self.redo.clear();
+        self.undo.push_back(edit);
+        if self.undo.len() > MAX_UNDO {
+            self.undo.pop_front();
+            self.saved = self.saved.and_then(|saved| saved.checked_sub(1));
+        }
+    }
+
+    /// The change that undoes the last one, to be applied by the caller
+    pub fn undo(&mut self) -> Option<Edit> {
+        let edit = self.undo.pop_back()?;
+        self.redo.push(edit.clone());
+        Some(edit.reversed())
+    }
+
+    /// The last undone change, to be applied again by the caller
+    pub fn redo(&mut self) -> Option<Edit> {
+        let edit = self.redo.pop()?;
+        self.undo.push_back(edit.clone());
+        Some(edit)
+    }
+
+    /// Collect the following changes into one undo step
+    pub fn begin_group(&mut self) {
+        self.group.get_or_insert_with(Vec::new);
+    }
+
+    pub fn end_group(&mut self) {
+        if let Some(edits) = self.group.take().filter(|edits| !edits.is_empty()) {
+            self.push(Edit::Group(edits));
+        }
+    }
+
+    pub fn mark_saved(&mut self) {
+        self.saved = Some(self.undo.len());
+    }
+
+    /// Undo and redo have brought the file back to how it was saved
+    pub fn is_at_saved(&self) -> bool {
+        self.saved == Some(self.undo.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edit(before: &str, after: &str) -> Edit {
+        let entry = |msgstr: &str| {
+            let mut entry = PoEntry::new();
+            entry.msgid = "Open".to_string();
+            entry.set_msgstr(msgstr.to_string());
+            entry
+        };
+        Edit::Entry {
+            index: 3,
+            before: Box::new(entry(before)),
+            after: Box::new(entry(after)),
+        }
+    }
+
+    #[test]
+    fn test_undo_and_redo() {
+        let mut history = UndoHistory::default();
+        history.push(edit("", "Открыть"));
+        history.push(edit("Открыть", "Открыть…"));
+        assert!(!history.is_at_saved());
+
+        assert_eq!(history.undo(), Some(edit("Открыть…", "Открыть")));
+        assert_eq!(history.undo(), Some(edit("Открыть", "")));
+        assert!(history.is_at_saved());
+        assert_eq!(history.undo(), None);
+
+        assert_eq!(history.redo(), Some(edit("", "Открыть")));
+        history.mark_saved();
+        // A new change drops what was undone
+        history.push(edit("Открыть", "Открыть файл"));
+        assert_eq!(history.redo(), None);
+        assert_eq!(history.undo(), Some(edit("Открыть файл", "Открыть")));
+        assert!(history.is_at_saved());
+    }
+
+    #[test]
+    fn test_group_is_one_step() {
+        let mut history = UndoHistory::default();
+        history.begin_group();
+        history.push(edit("", "a"));
+        history.push(edit("a", "b"));
+        history.end_group();
+        assert_eq!(history.undo(), Some(Edit::Group(vec![edit("b", "a"), edit("a", "")])));
+        assert_eq!(history.undo(), None);
+
+        // An empty group leaves no step behind
+        history.begin_group();
+        history.end_group();
+        assert_eq!(history.redo(), Some(Edit::Group(vec![edit("", "a"), edit("a", "b")])));
+    }
+
+    #[test]
+    fn test_saved_state_out_of_reach() {
+        let mut history = UndoHistory::default();
+        history.push(edit("", "a"));
+        history.mark_saved();
+        history.undo();
+        // The saved state was on the branch this change replaces
+        history.push(edit("", "b"));
+        history.undo();
+        assert!(!history.is_at_saved());
+
+        // Or it fell off the bounded history
+        let mut history = UndoHistory::default();
+        for _ in 0..=MAX_UNDO {
+            history.push(edit("", "a"));
+        }
+        while history.undo().is_some() {}
+        assert!(!history.is_at_saved());
+    }
+}
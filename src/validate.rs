@@ -0,0 +1,326 @@
+// Poterm - Modern TUI editor for .po translation files
+// Copyright (c) 2025 AnmiTaliDev <anmitali198@gmail.com>
+// Licensed under the Apache License, Version 2.0
+
+pub mod consistency;
+mod duplicate;
+mod format;
+mod markup;
+mod parse;
+mod same_translation;
+mod style;
+mod terminology;
+mod whitespace;
+
+pub use style::StyleRule;
+
+use crate::config::StyleConfig;
+use crate::gettext::{PoEntry, PoFile};
+use crate::glossary::Glossary;
+use serde::Serialize;
+use std::fmt;
+
+/// Translator comment that silences checks on one entry, e.g.
+/// `# poterm: ignore=double-space,ellipsis`
+const SUPPRESS_MARKER: &str = "poterm: ignore=";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// Worth knowing, not a problem by itself
+    Info,
+    Warning,
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Severity::Info => write!(f, "info"),
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// Problem found in a single entry, or in one entry relative to others
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidationIssue {
+    /// Stable identifier of the check, e.g. `markup`
+    pub check: &'static str,
+    pub severity: Severity,
+    pub message: String,
+    /// Corrected msgstr when the fix is unambiguous
+    pub fix: Option<String>,
+}
+
+impl ValidationIssue {
+    pub fn error(check: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            check,
+            severity: Severity::Error,
+            message: message.into(),
+            fix: None,
+        }
+    }
+
+    pub fn warning(check: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            check,
+            severity: Severity::Warning,
+            message: message.into(),
+            fix: None,
+        }
+    }
+
+    pub fn info(check: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            check,
+            severity: Severity::Info,
+            message: message.into(),
+            fix: None,
+        }
+    }
+
+    pub fn with_fix(mut self, msgstr: String) -> Self {
+        self.fix = Some(msgstr);
+        self
+    }
+}
+
+/// Issue together with the index of the entry it belongs to
+#[derive(Debug, Clone, PartialEq)]
+pub struct EntryIssue {
+    pub entry: usize,
+    pub issue: ValidationIssue,
+    /// Acknowledged by a suppression comment on the entry
+    pub suppressed: bool,
+}
+
+impl EntryIssue {
+    pub fn new(entry: usize, issue: ValidationIssue) -> Self {
+        Self {
+            entry,
+            issue,
+            suppressed: false,
+        }
+    }
+}
+
+/// Per-entry checks configured for one catalog
+pub struct Checker<'a> {
+    style_rules: Vec<StyleRule>,
+    language: Option<String>,
+    glossary: Option<&'a Glossary>,
+}
+
+impl<'a> Checker<'a> {
+    /// Style rules come from the config, falling back to the defaults for
+    /// the catalog's `Language` header
+    pub fn new(config: &StyleConfig, po_file: &PoFile) -> Self {
+        let language = po_file.language().map(str::to_string);
+        let mut style_rules = match config.enabled {
+            Some(ref rules) => rules.clone(),
+            None => StyleRule::defaults_for(language.as_deref()),
+        };
+        style_rules.retain(|rule| !config.disabled.contains(rule));
+
+        Self {
+            style_rules,
+            language,
+            glossary: None,
+        }
+    }
+
+    /// Also check the translation of the glossary's terms
+    pub fn with_glossary(mut self, glossary: Option<&'a Glossary>) -> Self {
+        self.glossary = glossary;
+        self
+    }
+
+    /// Run every check on one entry. Untranslated entries have nothing to
+    /// compare and are skipped.
+    pub fn validate_entry(&self, entry: &PoEntry) -> Vec<ValidationIssue> {
+        let suppressed = suppressed_checks(entry);
+        let mut issues = self.all_entry_issues(entry);
+        issues.retain(|issue| !suppressed.contains(&issue.check));
+        issues
+    }
+
+    /// Like `validate_entry`, including suppressed issues
+    fn all_entry_issues(&self, entry: &PoEntry) -> Vec<ValidationIssue> {
+        let mut issues = Vec::new();
+        if entry.msgstr.is_empty() {
+            return issues;
+        }
+        format::check(entry, &mut issues);
+        whitespace::check(entry, &mut issues);
+        markup::check(entry, &mut issues);
+        style::check(entry, &self.style_rules, self.language.as_deref(), &mut issues);
+        if let Some(glossary) = self.glossary {
+            terminology::check(entry, glossary, &mut issues);
+        }
+        issues
+    }
+}
+
+/// Check ids listed in the entry's suppression comments
+fn suppressed_checks(entry: &PoEntry) -> Vec<&str> {
+    entry
+        .comments
+        .iter()
+        .filter_map(|comment| comment.trim().strip_prefix(SUPPRESS_MARKER))
+        .flat_map(|ids| ids.split(',').map(str::trim))
+        .collect()
+}
+
+/// Acknowledge `check` on the entry by listing it in a suppression comment
+pub fn suppress(entry: &mut PoEntry, check: &str) {
+    if suppressed_checks(entry).contains(&check) {
+        return;
+    }
+    match entry.comments.iter_mut().find(|comment| comment.trim().starts_with(SUPPRESS_MARKER)) {
+        Some(comment) => {
+            *comment = format!("{},{}", comment.trim(), check);
+        }
+        None => entry.comments.push(format!("{}{}", SUPPRESS_MARKER, check)),
+    }
+}
+
+/// Take `check` out of the entry's suppression comments, dropping comments
+/// left without ids
+pub fn unsuppress(entry: &mut PoEntry, check: &str) {
+    entry.comments.retain_mut(|comment| {
+        let Some(ids) = comment.trim().strip_prefix(SUPPRESS_MARKER) else {
+            return true;
+        };
+        let ids: Vec<&str> = ids.split(',').map(str::trim).filter(|&id| id != check && !id.is_empty()).collect();
+        if ids.is_empty() {
+            return false;
+        }
+        *comment = format!("{}{}", SUPPRESS_MARKER, ids.join(","));
+        true
+    });
+}
+
+/// Validate a whole catalog, in entry order. Issues acknowledged by a
+/// suppression comment are kept, marked `suppressed`.
+pub fn run_checks(po_file: &PoFile, config: &StyleConfig, glossary: Option<&Glossary>) -> Vec<EntryIssue> {
+    let checker = Checker::new(config, po_file).with_glossary(glossary);
+    let mut issues: Vec<EntryIssue> = po_file
+        .entries
+        .iter()
+        .enumerate()
+        .flat_map(|(index, entry)| {
+            checker
+                .all_entry_issues(entry)
+                .into_iter()
+                .map(move |issue| EntryIssue::new(index, issue))
+        })
+        .collect();
+    issues.extend(duplicate::check(po_file));
+    issues.extend(consistency::check(po_file));
+    issues.extend(same_translation::check(po_file, config));
+    issues.extend(parse::check(po_file));
+    mark_suppressed(po_file, &mut issues);
+    issues.sort_by_key(|found| found.entry);
+    issues
+}
+
+/// Only the checks across entries: repeated msgctxt and msgid pairs
+pub fn run_duplicate_checks(po_file: &PoFile) -> Vec<EntryIssue> {
+    let mut issues = duplicate::check(po_file);
+    mark_suppressed(po_file, &mut issues);
+    issues
+}
+
+fn mark_suppressed(po_file: &PoFile, issues: &mut [EntryIssue]) {
+    for found in issues {
+        found.suppressed = suppressed_checks(&po_file.entries[found.entry]).contains(&found.issue.check);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_checks_reports_entry_indices() {
+        let mut po_file = PoFile::default();
+        for (msgid, msgstr) in [("<b>Bold</b>", "<b>Жирный</b>"), ("<b>Bold</b>!", "Жирный!"), ("<i>x</i>", "")] {
+            let mut entry = PoEntry::new();
+            entry.msgid = msgid.to_string();
+            entry.set_msgstr(msgstr.to_string());
+            po_file.entries.push(entry);
+        }
+
+        let issues = run_checks(&po_file, &StyleConfig::default(), None);
+        assert!(!issues.is_empty());
+        assert!(issues.iter().all(|i| i.entry == 1 && i.issue.check == "markup"));
+        assert!(issues.iter().all(|i| i.issue.severity == Severity::Error));
+    }
+
+    fn entry(msgid: &str, msgstr: &str) -> PoEntry {
+        let mut entry = PoEntry::new();
+        entry.msgid = msgid.to_string();
+        entry.set_msgstr(msgstr.to_string());
+        entry
+    }
+
+    #[test]
+    fn test_style_rules_from_config_and_language() {
+        let mut po_file = PoFile::default();
+        po_file.set_header_field("Language".to_string(), "fr".to_string());
+        let fr_entry = entry("Done!", "Terminé!");
+
+        let checker = Checker::new(&StyleConfig::default(), &po_file);
+        assert_eq!(checker.validate_entry(&fr_entry)[0].check, "nbsp-before-punctuation");
+
+        let config = StyleConfig {
+            disabled: vec![StyleRule::NbspBeforePunctuation],
+            ..StyleConfig::default()
+        };
+        assert!(Checker::new(&config, &po_file).validate_entry(&fr_entry).is_empty());
+
+        let config = StyleConfig {
+            enabled: Some(vec![StyleRule::Ellipsis]),
+            ..StyleConfig::default()
+        };
+        let checker = Checker::new(&config, &po_file);
+        assert!(checker.validate_entry(&fr_entry).is_empty());
+        assert_eq!(checker.validate_entry(&entry("Wait...", "Attendez..."))[0].check, "ellipsis");
+    }
+
+    #[test]
+    fn test_suppression_comment() {
+        let checker = Checker::new(&StyleConfig::default(), &PoFile::default());
+        let mut entry = entry("Wait... <b>now</b>", "Ждите...  <b>сейчас");
+        assert_eq!(checker.validate_entry(&entry).len(), 3);
+
+        entry.comments.push("poterm: ignore=ellipsis, markup".to_string());
+        let issues = checker.validate_entry(&entry);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].check, "double-space");
+    }
+
+    #[test]
+    fn test_suppress_and_unsuppress() {
+        let mut po_file = PoFile::default();
+        po_file.entries.push(entry("Wait...", "Ждите..."));
+        po_file.entries[0].comments.push("Keep it short".to_string());
+
+        suppress(&mut po_file.entries[0], "ellipsis");
+        suppress(&mut po_file.entries[0], "ellipsis");
+        suppress(&mut po_file.entries[0], "markup");
+        assert_eq!(po_file.entries[0].comments, vec!["Keep it short", "poterm: ignore=ellipsis,markup"]);
+        let issues = run_checks(&po_file, &StyleConfig::default(), None);
+        assert_eq!(issues.len(), 1);
+        assert!(issues[0].suppressed);
+
+        unsuppress(&mut po_file.entries[0], "ellipsis");
+        assert_eq!(po_file.entries[0].comments, vec!["Keep it short", "poterm: ignore=markup"]);
+        assert!(!run_checks(&po_file, &StyleConfig::default(), None)[0].suppressed);
+        unsuppress(&mut po_file.entries[0], "markup");
+        assert_eq!(po_file.entries[0].comments, vec!["Keep it short"]);
+    }
+}
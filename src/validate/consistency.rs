@@ -0,0 +1,87 @@
+// Poterm - Modern TUI editor for .po translation files
+// Copyright (c) 2025 AnmiTaliDev <anmitali198@gmail.com>
+// Licensed under the Apache License, Version 2.0
+
+//! One source string translated in different ways
+
+use super::{EntryIssue, ValidationIssue};
+use crate::gettext::{PoEntry, PoFile};
+use std::collections::HashMap;
+
+const CHECK: &str = "consistency";
+
+/// Indices of the entries with each msgid, in file order
+pub fn msgid_index(entries: &[PoEntry]) -> HashMap<&str, Vec<usize>> {
+    let mut index: HashMap<&str, Vec<usize>> = HashMap::new();
+    for (i, entry) in entries.iter().enumerate() {
+        index.entry(entry.msgid.as_str()).or_default().push(i);
+    }
+    index
+}
+
+/// Translated entries among `same_msgid` whose translation differs from
+/// the one of `entries[index]`
+pub fn differing(entries: &[PoEntry], same_msgid: &[usize], index: usize) -> Vec<usize> {
+    let msgstr = &entries[index].msgstr;
+    if msgstr.is_empty() {
+        return Vec::new();
+    }
+    same_msgid
+        .iter()
+        .copied()
+        .filter(|&other| other != index && !entries[other].msgstr.is_empty() && entries[other].msgstr != *msgstr)
+        .collect()
+}
+
+/// `1 other entry`, `2 other entries`
+pub fn other_entries(count: usize) -> String {
+    if count == 1 {
+        "1 other entry".to_string()
+    } else {
+        format!("{} other entries", count)
+    }
+}
+
+/// Every translated entry whose msgid is translated differently elsewhere
+pub fn check(po_file: &PoFile) -> Vec<EntryIssue> {
+    let index = msgid_index(&po_file.entries);
+    let mut issues = Vec::new();
+    for (i, entry) in po_file.entries.iter().enumerate() {
+        let others = differing(&po_file.entries, &index[entry.msgid.as_str()], i);
+        if others.is_empty() {
+            continue;
+        }
+        let lines: Vec<String> = others.iter().map(|&other| po_file.entries[other].line.to_string()).collect();
+        let message = format!(
+            "Same msgid translated differently in {} (line{} {})",
+            other_entries(others.len()),
+            if lines.len() == 1 { "" } else { "s" },
+            lines.join(", ")
+        );
+        issues.push(EntryIssue::new(i, ValidationIssue::warning(CHECK, message)));
+    }
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_different_translations() {
+        let content = "msgid \"Open\"\nmsgstr \"Открыть\"\n\n\
+                       msgctxt \"menu\"\nmsgid \"Open\"\nmsgstr \"Открыть\"\n\n\
+                       msgctxt \"button\"\nmsgid \"Open\"\nmsgstr \"Открой\"\n\n\
+                       msgctxt \"tab\"\nmsgid \"Open\"\nmsgstr \"\"\n";
+        let po_file = PoFile::parse(content).unwrap();
+        let found: Vec<_> = check(&po_file).into_iter().map(|found| (found.entry, found.issue.message)).collect();
+        assert_eq!(
+            found,
+            vec![
+                (0, "Same msgid translated differently in 1 other entry (line 9)".to_string()),
+                (1, "Same msgid translated differently in 1 other entry (line 9)".to_string()),
+                (2, "Same msgid translated differently in 2 other entries (lines 1, 5)".to_string()),
+            ]
+        );
+    }
+}
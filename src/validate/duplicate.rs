@@ -0,0 +1,80 @@
+// Poterm - Modern TUI editor for .po translation files
+// Copyright (c) 2025 AnmiTaliDev <anmitali198@gmail.com>
+// Licensed under the Apache License, Version 2.0
+
+//! Entries defined more than once, which msgfmt rejects
+
+use super::{EntryIssue, ValidationIssue};
+use crate::gettext::PoFile;
+use std::collections::HashMap;
+
+const CHECK: &str = "duplicate";
+const CONTEXT_CHECK: &str = "duplicate-msgid";
+
+/// Every entry repeating the msgctxt and msgid of an earlier one is an
+/// error. An msgid already used with another msgctxt is only noted, as
+/// contexts exist to allow that.
+pub fn check(po_file: &PoFile) -> Vec<EntryIssue> {
+    let mut issues = Vec::new();
+    let mut first_by_key: HashMap<(Option<&str>, &str), usize> = HashMap::new();
+    let mut first_by_msgid: HashMap<&str, usize> = HashMap::new();
+
+    for (index, entry) in po_file.entries.iter().enumerate() {
+        let key = (entry.msgctxt.as_deref(), entry.msgid.as_str());
+        if let Some(&first) = first_by_key.get(&key) {
+            let message = format!(
+                "Duplicate message definition, first defined at line {}",
+                po_file.entries[first].line
+            );
+            issues.push(EntryIssue::new(index, ValidationIssue::error(CHECK, message)));
+            continue;
+        }
+        first_by_key.insert(key, index);
+
+        match first_by_msgid.get(entry.msgid.as_str()) {
+            Some(&first) => {
+                let context = match po_file.entries[first].msgctxt {
+                    Some(ref msgctxt) => format!("context \"{}\"", msgctxt),
+                    None => "no context".to_string(),
+                };
+                let message = format!(
+                    "Same msgid as line {} with {}",
+                    po_file.entries[first].line, context
+                );
+                issues.push(EntryIssue::new(index, ValidationIssue::info(CONTEXT_CHECK, message)));
+            }
+            None => {
+                first_by_msgid.insert(&entry.msgid, index);
+            }
+        }
+    }
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validate::Severity;
+
+    #[test]
+    fn test_duplicates_and_shared_msgids() {
+        let content = "msgid \"Open\"\nmsgstr \"Открыть\"\n\n\
+                       msgctxt \"menu\"\nmsgid \"Open\"\nmsgstr \"Открыть\"\n\n\
+                       msgid \"Open\"\nmsgstr \"Открыть файл\"\n\n\
+                       msgctxt \"menu\"\nmsgid \"Open\"\nmsgstr \"\"\n\n\
+                       msgid \"Close\"\nmsgstr \"Закрыть\"\n";
+        let po_file = PoFile::parse(content).unwrap();
+        let found: Vec<_> = check(&po_file)
+            .into_iter()
+            .map(|found| (found.entry, found.issue.severity, found.issue.message))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (1, Severity::Info, "Same msgid as line 1 with no context".to_string()),
+                (2, Severity::Error, "Duplicate message definition, first defined at line 1".to_string()),
+                (3, Severity::Error, "Duplicate message definition, first defined at line 5".to_string()),
+            ]
+        );
+    }
+}
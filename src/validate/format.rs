@@ -0,0 +1,460 @@
+// Poterm - Modern TUI editor for .po translation files
+// Copyright (c) 2025 AnmiTaliDev <anmitali198@gmail.com>
+// Licensed under the Apache License, Version 2.0
+
+//! Format directives of entries flagged `c-format`, `python-format` or
+//! `python-brace-format`, compared the way `msgfmt -c` does
+
+use super::ValidationIssue;
+use crate::gettext::PoEntry;
+use std::collections::BTreeMap;
+
+/// Flags that turn the check on, which double as its id
+const FORMATS: [(&str, Parser); 3] = [
+    ("c-format", parse_c),
+    ("python-format", parse_python),
+    ("python-brace-format", parse_brace),
+];
+
+type Parser = fn(&str) -> Result<Vec<Directive>, String>;
+
+/// Argument a directive takes: its position, counted from 1, or its name
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum Argument {
+    Number(usize),
+    Name(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Directive {
+    argument: Argument,
+    /// What the argument must be, e.g. `ld` for `%ld` and `%lx`; directives
+    /// of the same kind accept the same arguments
+    kind: String,
+    /// The directive as written
+    text: String,
+}
+
+pub(super) fn check(entry: &PoEntry, issues: &mut Vec<ValidationIssue>) {
+    for (flag, parse) in FORMATS {
+        if entry.flags.iter().any(|f| f == flag) {
+            check_format(entry, flag, parse, issues);
+        }
+    }
+}
+
+/// Each translation form against the msgid. Plural forms are compared with
+/// the msgid_plural and may leave an argument out, as `One file` does for
+/// the `%d` of `%d files`.
+fn check_format(entry: &PoEntry, flag: &'static str, parse: Parser, issues: &mut Vec<ValidationIssue>) {
+    let source = entry.msgid_plural.as_deref().unwrap_or(&entry.msgid);
+    // An msgid that is not a valid format string has nothing to compare
+    let Ok(expected) = parse(source) else {
+        return;
+    };
+    for form in 0..entry.form_count() {
+        let text = entry.form(form);
+        if text.is_empty() {
+            continue;
+        }
+        let prefix = if entry.is_plural() { format!("msgstr[{}]: ", form) } else { String::new() };
+        let found = match parse(text) {
+            Ok(found) => found,
+            Err(message) => {
+                issues.push(ValidationIssue::error(flag, format!("{}{}", prefix, message)));
+                continue;
+            }
+        };
+        for message in compare(&expected, &found, entry.is_plural()) {
+            issues.push(ValidationIssue::error(flag, format!("{}{}", prefix, message)));
+        }
+    }
+}
+
+/// Differences between the directives of the msgid and the translation
+fn compare(expected: &[Directive], found: &[Directive], allow_missing: bool) -> Vec<String> {
+    let by_argument = |directives: &[Directive]| {
+        let mut map: BTreeMap<Argument, Directive> = BTreeMap::new();
+        for directive in directives {
+            map.entry(directive.argument.clone()).or_insert_with(|| directive.clone());
+        }
+        map
+    };
+    let (expected, found) = (by_argument(expected), by_argument(found));
+
+    let mut messages = Vec::new();
+    for (argument, directive) in &expected {
+        match found.get(argument) {
+            Some(other) if other.kind != directive.kind => messages.push(match argument {
+                Argument::Number(number) => format!(
+                    "Argument {} is {} in the msgid but {} in the translation",
+                    number, directive.text, other.text
+                ),
+                Argument::Name(_) => format!("{} in the msgid is {} in the translation", directive.text, other.text),
+            }),
+            Some(_) => {}
+            None if allow_missing => {}
+            None => messages.push(format!("Missing {}", directive.text)),
+        }
+    }
+    for (argument, directive) in &found {
+        if !expected.contains_key(argument) {
+            messages.push(format!("Extra {}, not in the msgid", directive.text));
+        }
+    }
+    messages
+}
+
+/// Numbers unnumbered arguments in order; a string may not use both kinds
+fn number_arguments(
+    numbered: Option<usize>,
+    next: &mut usize,
+    numbering: &mut Option<bool>,
+) -> Result<Argument, String> {
+    if numbering.is_some_and(|uses_numbers| uses_numbers != numbered.is_some()) {
+        return Err("Mixes numbered and unnumbered arguments".to_string());
+    }
+    *numbering = Some(numbered.is_some());
+    Ok(Argument::Number(numbered.unwrap_or_else(|| {
+        *next += 1;
+        *next
+    })))
+}
+
+fn invalid_directive(text: &str, start: usize) -> String {
+    format!("Invalid directive at character {}", text[..start].chars().count() + 1)
+}
+
+/// Digits at `i` followed by `$`: the number and the index after the `$`
+fn position(bytes: &[u8], i: usize) -> Option<(usize, usize)> {
+    let digits = bytes[i..].iter().take_while(|b| b.is_ascii_digit()).count();
+    if digits == 0 || bytes.get(i + digits) != Some(&b'$') {
+        return None;
+    }
+    let number = std::str::from_utf8(&bytes[i..i + digits]).ok()?.parse().ok()?;
+    (number > 0).then_some((number, i + digits + 1))
+}
+
+/// printf directives: `%s`, `%2$d`, `%-*.3lf`, `%<PRIu64>`
+fn parse_c(text: &str) -> Result<Vec<Directive>, String> {
+    let bytes = text.as_bytes();
+    let mut directives = Vec::new();
+    let mut next = 0;
+    let mut numbering = None;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        if bytes[i] != b'%' {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        i += 1;
+        if bytes.get(i) == Some(&b'%') {
+            i += 1;
+            continue;
+        }
+        let numbered = position(bytes, i).map(|(number, after)| {
+            i = after;
+            number
+        });
+        while bytes.get(i).is_some_and(|b| b"-+ #0'I".contains(b)) {
+            i += 1;
+        }
+        // Width and precision given as `*` take an int argument
+        let mut stars = Vec::new();
+        for precision in [false, true] {
+            if precision {
+                if bytes.get(i) != Some(&b'.') {
+                    break;
+                }
+                i += 1;
+            }
+            if bytes.get(i) == Some(&b'*') {
+                i += 1;
+                let numbered = position(bytes, i).map(|(number, after)| {
+                    i = after;
+                    number
+                });
+                stars.push(numbered);
+            } else {
+                i += bytes[i..].iter().take_while(|b| b.is_ascii_digit()).count();
+            }
+        }
+        for numbered in stars {
+            let argument = number_arguments(numbered, &mut next, &mut numbering)?;
+            directives.push(Directive { argument, kind: "d".to_string(), text: "*".to_string() });
+        }
+
+        let length_end = i + ["hh", "ll", "h", "l", "L", "q", "j", "z", "Z", "t"]
+            .iter()
+            .find(|length| bytes[i..].starts_with(length.as_bytes()))
+            .map_or(0, |length| length.len());
+        let length = &text[i..length_end];
+        i = length_end;
+        let kind = match bytes.get(i) {
+            Some(b'd' | b'i') => format!("{}d", length),
+            Some(b'o' | b'u' | b'x' | b'X') => format!("{}u", length),
+            // `l` makes no difference to a double
+            Some(b'e' | b'E' | b'f' | b'F' | b'g' | b'G' | b'a' | b'A') => format!("{}f", length.replace('l', "")),
+            Some(b'c') => format!("{}c", length),
+            Some(b's') => format!("{}s", length),
+            Some(b'C') => "lc".to_string(),
+            Some(b'S') => "ls".to_string(),
+            Some(b'p') => "p".to_string(),
+            Some(b'n') => format!("{}n", length),
+            // <inttypes.h> macros, kept for msgfmt to expand
+            Some(b'<') if length.is_empty() => match text[i..].find('>') {
+                Some(end) if text[i + 1..i + end].starts_with("PRI") => text[i + 1..i + end].to_string(),
+                _ => return Err(invalid_directive(text, start)),
+            },
+            _ => return Err(invalid_directive(text, start)),
+        };
+        i += if bytes[i] == b'<' { kind.len() + 2 } else { 1 };
+        let argument = number_arguments(numbered, &mut next, &mut numbering)?;
+        directives.push(Directive { argument, kind, text: text[start..i].to_string() });
+    }
+    Ok(directives)
+}
+
+/// Python `%` directives: `%s`, `%5.2f`, `%(name)s`
+fn parse_python(text: &str) -> Result<Vec<Directive>, String> {
+    let bytes = text.as_bytes();
+    let mut directives = Vec::new();
+    let mut next = 0;
+    let mut named = None;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        if bytes[i] != b'%' {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        i += 1;
+        if bytes.get(i) == Some(&b'%') {
+            i += 1;
+            continue;
+        }
+        let name = if bytes.get(i) == Some(&b'(') {
+            let Some(end) = text[i..].find(')') else {
+                return Err(invalid_directive(text, start));
+            };
+            let name = text[i + 1..i + end].to_string();
+            i += end + 1;
+            Some(name)
+        } else {
+            None
+        };
+        if named.is_some_and(|is_named| is_named != name.is_some()) {
+            return Err("Mixes named and unnamed arguments".to_string());
+        }
+        named = Some(name.is_some());
+
+        while bytes.get(i).is_some_and(|b| b"-+ #0".contains(b)) {
+            i += 1;
+        }
+        for precision in [false, true] {
+            if precision {
+                if bytes.get(i) != Some(&b'.') {
+                    break;
+                }
+                i += 1;
+            }
+            if bytes.get(i) == Some(&b'*') {
+                if name.is_some() {
+                    return Err(invalid_directive(text, start));
+                }
+                i += 1;
+                next += 1;
+                directives.push(Directive {
+                    argument: Argument::Number(next),
+                    kind: "d".to_string(),
+                    text: "*".to_string(),
+                });
+            } else {
+                i += bytes[i..].iter().take_while(|b| b.is_ascii_digit()).count();
+            }
+        }
+        if bytes.get(i).is_some_and(|b| b"hlL".contains(b)) {
+            i += 1;
+        }
+        let kind = match bytes.get(i) {
+            Some(b'd' | b'i' | b'o' | b'u' | b'x' | b'X') => "d",
+            Some(b'e' | b'E' | b'f' | b'F' | b'g' | b'G') => "f",
+            Some(b'c') => "c",
+            Some(b's' | b'r' | b'a') => "s",
+            _ => return Err(invalid_directive(text, start)),
+        };
+        i += 1;
+        let argument = match name {
+            Some(name) => Argument::Name(name),
+            None => {
+                next += 1;
+                Argument::Number(next)
+            }
+        };
+        directives.push(Directive { argument, kind: kind.to_string(), text: text[start..i].to_string() });
+    }
+    Ok(directives)
+}
+
+/// `str.format` fields: `{}`, `{0}`, `{name}`, `{user.name!r:>10}`
+fn parse_brace(text: &str) -> Result<Vec<Directive>, String> {
+    let mut directives = Vec::new();
+    let mut next = 0;
+    let mut numbering = None;
+    let mut chars = text.char_indices().peekable();
+
+    while let Some((start, c)) = chars.next() {
+        match c {
+            '{' if chars.peek().is_some_and(|&(_, c)| c == '{') => {
+                chars.next();
+            }
+            '}' if chars.peek().is_some_and(|&(_, c)| c == '}') => {
+                chars.next();
+            }
+            '}' => return Err(format!("Unmatched '}}' at character {}", text[..start].chars().count() + 1)),
+            '{' => {
+                // The format spec may hold fields of its own, e.g. `{:{width}}`
+                let mut depth = 1;
+                let mut end = None;
+                for (i, c) in chars.by_ref() {
+                    match c {
+                        '{' => depth += 1,
+                        '}' => {
+                            depth -= 1;
+                            if depth == 0 {
+                                end = Some(i);
+                                break;
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+                let Some(end) = end else {
+                    return Err(format!("Unclosed '{{' at character {}", text[..start].chars().count() + 1));
+                };
+                let field = &text[start + 1..end];
+                let name_end = field.find(['.', '[', '!', ':']).unwrap_or(field.len());
+                let name = &field[..name_end];
+                let argument = if name.is_empty() || name.bytes().all(|b| b.is_ascii_digit()) {
+                    let numbered = name.parse::<usize>().ok().map(|number| number + 1);
+                    number_arguments(numbered, &mut next, &mut numbering)
+                        .map_err(|_| "Mixes automatic and manual field numbering".to_string())?
+                } else {
+                    Argument::Name(name.to_string())
+                };
+                directives.push(Directive { argument, kind: String::new(), text: format!("{{{}}}", name) });
+            }
+            _ => {}
+        }
+    }
+    Ok(directives)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn issues(flag: &str, msgid: &str, msgstr: &str) -> Vec<String> {
+        let mut entry = PoEntry::new();
+        entry.msgid = msgid.to_string();
+        entry.set_msgstr(msgstr.to_string());
+        entry.flags.push(flag.to_string());
+        let mut issues = Vec::new();
+        check(&entry, &mut issues);
+        issues.into_iter().map(|i| i.message).collect()
+    }
+
+    #[test]
+    fn test_c_format() {
+        assert!(issues("c-format", "%s: %d files (%.1f%%)", "%s: файлов %d (%.1f%%)").is_empty());
+        assert!(issues("c-format", "%lu of %lx", "%lx из %lu").is_empty());
+        assert_eq!(issues("c-format", "%s: %d files", "%s: файлов"), vec!["Missing %d"]);
+        assert_eq!(issues("c-format", "Open", "Открыть %s"), vec!["Extra %s, not in the msgid"]);
+        assert_eq!(
+            issues("c-format", "%d of %ld", "%d из %d"),
+            vec!["Argument 2 is %ld in the msgid but %d in the translation"]
+        );
+        // An msgid that is not a valid format string is not compared
+        assert!(issues("c-format", "50%", "50% off").is_empty());
+        assert_eq!(issues("c-format", "%d%%", "%d %"), vec!["Invalid directive at character 4"]);
+        assert!(issues("c-format", "Using %<PRIu32> threads", "Потоков: %<PRIu32>").is_empty());
+        assert!(issues("c-format", "%*d", "%*d").is_empty());
+        // Without the flag the text is not a format string
+        assert!(issues("no-c-format", "%s", "").is_empty());
+        assert!(issues("fuzzy", "%s", "%d").is_empty());
+    }
+
+    #[test]
+    fn test_c_format_positions() {
+        // Numbered arguments may change places
+        assert!(issues("c-format", "%s copied to %s", "%2$s: скопировано из %1$s").is_empty());
+        assert_eq!(
+            issues("c-format", "%s has %d files", "%2$d файлов в %1$d"),
+            vec!["Argument 1 is %s in the msgid but %1$d in the translation"]
+        );
+        // Unnumbered ones can't
+        assert_eq!(
+            issues("c-format", "%s has %d files", "%d файлов в %s"),
+            vec![
+                "Argument 1 is %s in the msgid but %d in the translation",
+                "Argument 2 is %d in the msgid but %s in the translation",
+            ]
+        );
+        assert_eq!(
+            issues("c-format", "%s: %d", "%2$d: %s"),
+            vec!["Mixes numbered and unnumbered arguments"]
+        );
+    }
+
+    #[test]
+    fn test_plural_forms() {
+        let mut entry = PoEntry::new();
+        entry.msgid = "One file".to_string();
+        entry.msgid_plural = Some("%d files".to_string());
+        entry.flags.push("c-format".to_string());
+        entry.set_form(0, "%d файл".to_string());
+        entry.set_form(1, "Один файл".to_string());
+        entry.set_form(2, "%s файлов".to_string());
+        let mut found = Vec::new();
+        check(&entry, &mut found);
+        let messages: Vec<&str> = found.iter().map(|issue| issue.message.as_str()).collect();
+        assert_eq!(messages, vec!["msgstr[2]: Argument 1 is %d in the msgid but %s in the translation"]);
+        assert_eq!(found[0].check, "c-format");
+    }
+
+    #[test]
+    fn test_python_format() {
+        assert!(issues("python-format", "%(count)d of %(total)d", "%(total)d: %(count)d").is_empty());
+        assert!(issues("python-format", "%s is %5.1f%%", "%s: %5.1f%%").is_empty());
+        assert_eq!(
+            issues("python-format", "%(count)d files", "%(cnt)d файлов"),
+            vec!["Missing %(count)d", "Extra %(cnt)d, not in the msgid"]
+        );
+        assert_eq!(
+            issues("python-format", "%(count)d files", "%(count)s файлов"),
+            vec!["%(count)d in the msgid is %(count)s in the translation"]
+        );
+        assert_eq!(issues("python-format", "%s and %s", "%s и %(x)s"), vec!["Mixes named and unnamed arguments"]);
+    }
+
+    #[test]
+    fn test_python_brace_format() {
+        assert!(issues("python-brace-format", "{name} has {count} files", "{count} файлов у {name}").is_empty());
+        assert!(issues("python-brace-format", "{} of {}", "{} из {}").is_empty());
+        assert!(issues("python-brace-format", "{0} {{literal}}", "{0:>10} {{буквально}}").is_empty());
+        assert!(issues("python-brace-format", "{user.name!r}", "{user.name}").is_empty());
+        assert_eq!(issues("python-brace-format", "Hello {name}", "Привет {nome}"), vec![
+            "Missing {name}",
+            "Extra {nome}, not in the msgid"
+        ]);
+        assert_eq!(issues("python-brace-format", "{0}", "{0"), vec!["Unclosed '{' at character 1"]);
+        assert_eq!(issues("python-brace-format", "{0}", "{0}}"), vec!["Unmatched '}' at character 4"]);
+        assert_eq!(
+            issues("python-brace-format", "{} {}", "{1} {}"),
+            vec!["Mixes automatic and manual field numbering"]
+        );
+    }
+}
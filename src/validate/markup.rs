@@ -0,0 +1,257 @@
+// Poterm - Modern TUI editor for .po translation files
+// Copyright (c) 2025 AnmiTaliDev <anmitali198@gmail.com>
+// Licensed under the Apache License, Version 2.0
+
+//! HTML/XML tag and entity parity between msgid and msgstr
+
+use super::ValidationIssue;
+use crate::gettext::PoEntry;
+
+const CHECK: &str = "markup";
+
+/// Elements that never have a closing tag in HTML
+const VOID_ELEMENTS: [&str; 6] = ["br", "hr", "img", "input", "meta", "link"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TagKind {
+    Open,
+    Close,
+    SelfClosing,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Tag {
+    name: String,
+    kind: TagKind,
+}
+
+impl Tag {
+    fn display(&self) -> String {
+        match self.kind {
+            TagKind::Open => format!("<{}>", self.name),
+            TagKind::Close => format!("</{}>", self.name),
+            TagKind::SelfClosing => format!("<{}/>", self.name),
+        }
+    }
+}
+
+pub(super) fn check(entry: &PoEntry, issues: &mut Vec<ValidationIssue>) {
+    if entry.flags.iter().any(|f| f == "no-markup") {
+        return;
+    }
+    let source_tags = extract_tags(&entry.msgid);
+    if source_tags.is_empty() {
+        return;
+    }
+    let translated_tags = extract_tags(&entry.msgstr);
+
+    let mut unmatched = translated_tags.clone();
+    for tag in &source_tags {
+        match unmatched.iter().position(|t| t == tag) {
+            Some(position) => {
+                unmatched.remove(position);
+            }
+            None => issues.push(ValidationIssue::error(CHECK, format!("Missing tag {}", tag.display()))),
+        }
+    }
+    for tag in unmatched {
+        issues.push(ValidationIssue::error(CHECK, format!("Extra tag {}", tag.display())));
+    }
+
+    // Fragments such as a lone "<b>" in the msgid can't be nested properly
+    if nesting_error(&source_tags).is_none() {
+        if let Some(tag) = nesting_error(&translated_tags) {
+            issues.push(ValidationIssue::error(CHECK, format!("Mis-nested tag {}", tag.display())));
+        }
+    }
+
+    if stray_ampersand(&entry.msgid).is_none() {
+        if let Some(offset) = stray_ampersand(&entry.msgstr) {
+            issues.push(ValidationIssue::warning(
+                CHECK,
+                format!(
+                    "Stray '&' at character {} in markup, use &amp;",
+                    entry.msgstr[..offset].chars().count() + 1
+                ),
+            ));
+        }
+    }
+}
+
+/// Tags in document order. A `<` that does not start a tag name (as in
+/// `a < b`) is plain text, and `>` inside quoted attribute values does not
+/// end the tag.
+fn extract_tags(text: &str) -> Vec<Tag> {
+    let mut tags = Vec::new();
+    let bytes = text.as_bytes();
+    let mut i = 0;
+
+    while i < bytes.len() {
+        if bytes[i] != b'<' {
+            i += 1;
+            continue;
+        }
+
+        let mut j = i + 1;
+        let closing = bytes.get(j) == Some(&b'/');
+        if closing {
+            j += 1;
+        }
+        let name_start = j;
+        if !bytes.get(j).is_some_and(|b| b.is_ascii_alphabetic()) {
+            i += 1;
+            continue;
+        }
+        while bytes
+            .get(j)
+            .is_some_and(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b':' | b'.'))
+        {
+            j += 1;
+        }
+        let name = text[name_start..j].to_ascii_lowercase();
+
+        let mut quote = None;
+        let mut end = None;
+        while j < bytes.len() {
+            match (quote, bytes[j]) {
+                (Some(q), b) if b == q => quote = None,
+                (Some(_), _) => {}
+                (None, b'"' | b'\'') => quote = Some(bytes[j]),
+                (None, b'>') => {
+                    end = Some(j);
+                    break;
+                }
+                (None, b'<') => break,
+                (None, _) => {}
+            }
+            j += 1;
+        }
+        let Some(end) = end else {
+            i += 1;
+            continue;
+        };
+
+        let kind = if closing {
+            TagKind::Close
+        } else if bytes[end - 1] == b'/' || VOID_ELEMENTS.contains(&name.as_str()) {
+            TagKind::SelfClosing
+        } else {
+            TagKind::Open
+        };
+        tags.push(Tag { name, kind });
+        i = end + 1;
+    }
+
+    tags
+}
+
+/// First tag that breaks proper nesting, if any
+fn nesting_error(tags: &[Tag]) -> Option<Tag> {
+    let mut stack: Vec<&str> = Vec::new();
+    for tag in tags {
+        match tag.kind {
+            TagKind::Open => stack.push(&tag.name),
+            TagKind::Close => {
+                if stack.pop() != Some(tag.name.as_str()) {
+                    return Some(tag.clone());
+                }
+            }
+            TagKind::SelfClosing => {}
+        }
+    }
+    None
+}
+
+/// Byte offset of the first `&` that does not start an entity such as
+/// `&amp;`, `&#160;` or `&#xA0;`
+fn stray_ampersand(text: &str) -> Option<usize> {
+    text.match_indices('&').map(|(offset, _)| offset).find(|&offset| {
+        let rest = &text[offset + 1..];
+        let Some(end) = rest.find(';') else {
+            return true;
+        };
+        let name = &rest[..end];
+        let valid = if let Some(hex) = name.strip_prefix("#x").or_else(|| name.strip_prefix("#X")) {
+            !hex.is_empty() && hex.chars().all(|c| c.is_ascii_hexdigit())
+        } else if let Some(decimal) = name.strip_prefix('#') {
+            !decimal.is_empty() && decimal.chars().all(|c| c.is_ascii_digit())
+        } else {
+            name.starts_with(|c: char| c.is_ascii_alphabetic()) && name.chars().all(|c| c.is_ascii_alphanumeric())
+        };
+        !valid
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn issues(msgid: &str, msgstr: &str) -> Vec<String> {
+        let mut entry = PoEntry::new();
+        entry.msgid = msgid.to_string();
+        entry.set_msgstr(msgstr.to_string());
+        let mut issues = Vec::new();
+        check(&entry, &mut issues);
+        issues.into_iter().map(|i| i.message).collect()
+    }
+
+    #[test]
+    fn test_matching_markup_passes() {
+        assert!(issues("<b>%s</b> files", "файлов: <b>%s</b>").is_empty());
+        assert!(issues("Line<br>break<br/>", "Строка<BR>перенос<br />").is_empty());
+        assert!(issues("a < b and c > d", "a < b").is_empty());
+    }
+
+    #[test]
+    fn test_missing_and_extra_tags() {
+        assert_eq!(issues("<b>Bold</b>", "<b>Жирный"), vec!["Missing tag </b>"]);
+        assert_eq!(issues("<i>x</i>", "<i>x</i><br/>"), vec!["Extra tag <br/>"]);
+    }
+
+    #[test]
+    fn test_nested_tags() {
+        assert!(issues("<a href=\"%s\"><b>Go</b></a>", "<a href=\"%s\"><b>Вперёд</b></a>").is_empty());
+        assert_eq!(
+            issues("<a href=\"%s\"><b>Go</b></a>", "<a href=\"%s\"><b>Вперёд</a></b>"),
+            vec!["Mis-nested tag </a>"]
+        );
+        // Unbalanced fragments in the msgid are not checked for nesting
+        assert!(issues("</b> and <b>", "<b> и </b>").is_empty());
+    }
+
+    #[test]
+    fn test_quoted_attribute_with_angle_bracket() {
+        assert_eq!(
+            extract_tags("<a title=\"a > b\" href='x>y'>link</a>"),
+            vec![
+                Tag { name: "a".to_string(), kind: TagKind::Open },
+                Tag { name: "a".to_string(), kind: TagKind::Close },
+            ]
+        );
+        assert!(issues("<a title=\"a > b\">link</a>", "<a title=\"a > b\">ссылка</a>").is_empty());
+    }
+
+    #[test]
+    fn test_entities() {
+        assert!(issues("<b>Tom &amp; Jerry</b>", "<b>Том &amp; Джерри&#160;</b>").is_empty());
+        assert_eq!(
+            issues("<b>Tom &amp; Jerry</b>", "<b>Том & Джерри</b>"),
+            vec!["Stray '&' at character 8 in markup, use &amp;"]
+        );
+        // The msgid itself uses a bare '&', e.g. an accelerator
+        assert!(issues("<b>&Save</b>", "<b>&Сохранить</b>").is_empty());
+    }
+
+    #[test]
+    fn test_skipped_entries() {
+        assert!(issues("Plain text", "<b>Текст</b>").is_empty());
+
+        let mut entry = PoEntry::new();
+        entry.msgid = "<b>Bold</b>".to_string();
+        entry.msgstr = "Жирный".to_string();
+        entry.flags.push("no-markup".to_string());
+        let mut found = Vec::new();
+        check(&entry, &mut found);
+        assert!(found.is_empty());
+    }
+}
@@ -0,0 +1,47 @@
+// Poterm - Modern TUI editor for .po translation files
+// Copyright (c) 2025 AnmiTaliDev <anmitali198@gmail.com>
+// Licensed under the Apache License, Version 2.0
+
+//! Lines of the file that could not be parsed, which saving drops
+
+use super::{EntryIssue, ValidationIssue};
+use crate::gettext::PoFile;
+
+const CHECK: &str = "parse";
+
+/// Every parse issue, reported on the entry at or before its line
+pub fn check(po_file: &PoFile) -> Vec<EntryIssue> {
+    if po_file.entries.is_empty() {
+        return Vec::new();
+    }
+    po_file
+        .parse_issues()
+        .iter()
+        .map(|issue| {
+            let entry = po_file.entries.partition_point(|entry| entry.line <= issue.line).saturating_sub(1);
+            let message = format!("line {}: {}", issue.line, issue.message);
+            EntryIssue::new(entry, ValidationIssue::warning(CHECK, message))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_issues_on_nearest_entry() {
+        let content = "garbage\n\nmsgid \"Open\"\nmsgstr \"Открыть\"\n\nmsgid \"Quit\"\nmsgstr Выход\n";
+        let po_file = PoFile::parse(content).unwrap();
+        let issues = check(&po_file);
+        let found: Vec<_> = issues.iter().map(|found| (found.entry, found.issue.message.as_str())).collect();
+        assert_eq!(
+            found,
+            vec![
+                (0, "line 1: Unrecognized line ignored: garbage"),
+                (1, "line 7: Failed to parse msgstr: expected a quoted string"),
+            ]
+        );
+        assert!(check(&PoFile::parse("msgid \"Open\"\nmsgstr \"\"\n").unwrap()).is_empty());
+    }
+}
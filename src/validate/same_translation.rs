@@ -0,0 +1,88 @@
+// Poterm - Modern TUI editor for .po translation files
+// Copyright (c) 2025 AnmiTaliDev <anmitali198@gmail.com>
+// Licensed under the Apache License, Version 2.0
+
+//! Different source strings given one translation
+
+use super::{EntryIssue, ValidationIssue};
+use crate::config::StyleConfig;
+use crate::gettext::PoFile;
+use std::collections::HashMap;
+
+const CHECK: &str = "same-translation";
+
+/// Every translated entry sharing its msgstr with an entry of another msgid.
+/// Short translations and those in the config's stop-list are skipped.
+pub fn check(po_file: &PoFile, config: &StyleConfig) -> Vec<EntryIssue> {
+    let mut by_msgstr: HashMap<&str, Vec<usize>> = HashMap::new();
+    for (i, entry) in po_file.entries.iter().enumerate() {
+        let msgstr = entry.msgstr.trim();
+        if msgstr.chars().count() < config.same_translation_min_length
+            || config.same_translation_ignore.iter().any(|ignored| ignored == msgstr)
+        {
+            continue;
+        }
+        by_msgstr.entry(msgstr).or_default().push(i);
+    }
+
+    let mut issues = Vec::new();
+    for indices in by_msgstr.values() {
+        for &i in indices {
+            let msgid = &po_file.entries[i].msgid;
+            let others: Vec<String> = indices
+                .iter()
+                .filter(|&&other| po_file.entries[other].msgid != *msgid)
+                .map(|&other| po_file.entries[other].line.to_string())
+                .collect();
+            if others.is_empty() {
+                continue;
+            }
+            let lines = if others.len() == 1 { "line" } else { "lines" };
+            let message = format!("Same translation as {} {} with a different msgid", lines, others.join(", "));
+            issues.push(EntryIssue::new(i, ValidationIssue::warning(CHECK, message)));
+        }
+    }
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONTENT: &str = "msgid \"Close\"\nmsgstr \"Закрыть\"\n\n\
+                           msgid \"Cancel\"\nmsgstr \"Закрыть\"\n\n\
+                           msgctxt \"menu\"\nmsgid \"Close\"\nmsgstr \"Закрыть\"\n\n\
+                           msgid \"OK\"\nmsgstr \"ОК\"\n\n\
+                           msgid \"Okay\"\nmsgstr \"ОК\"\n";
+
+    fn found(config: &StyleConfig) -> Vec<(usize, String)> {
+        let po_file = PoFile::parse(CONTENT).unwrap();
+        let mut found: Vec<_> = check(&po_file, config).into_iter().map(|found| (found.entry, found.issue.message)).collect();
+        found.sort();
+        found
+    }
+
+    #[test]
+    fn test_different_msgids() {
+        assert_eq!(
+            found(&StyleConfig::default()),
+            vec![
+                (0, "Same translation as line 4 with a different msgid".to_string()),
+                (1, "Same translation as lines 1, 8 with a different msgid".to_string()),
+                (2, "Same translation as line 4 with a different msgid".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_min_length_and_stop_list() {
+        let config = StyleConfig {
+            same_translation_min_length: 1,
+            same_translation_ignore: vec!["Закрыть".to_string()],
+            ..StyleConfig::default()
+        };
+        let found = found(&config);
+        assert_eq!(found.len(), 2);
+        assert!(found.iter().all(|(entry, _)| *entry >= 3));
+    }
+}
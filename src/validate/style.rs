@@ -0,0 +1,246 @@
+// Poterm - Modern TUI editor for .po translation files
+// Copyright (c) 2025 AnmiTaliDev <anmitali198@gmail.com>
+// Licensed under the Apache License, Version 2.0
+
+//! Typography and style rules for translations
+
+use super::ValidationIssue;
+use crate::gettext::PoEntry;
+use serde::Deserialize;
+
+const NBSP: char = '\u{a0}';
+
+/// Style rules that can be enabled in the `[style]` config section. The
+/// kebab-case name doubles as the check id.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum StyleRule {
+    /// Two or more consecutive spaces
+    DoubleSpace,
+    /// `"` where the language uses typographic quotes
+    StraightQuotes,
+    /// Regular or missing space before `:` `;` `!` `?` (French typography)
+    NbspBeforePunctuation,
+    /// `...` instead of `…`
+    Ellipsis,
+}
+
+impl StyleRule {
+    pub const ALL: [StyleRule; 4] = [
+        StyleRule::DoubleSpace,
+        StyleRule::StraightQuotes,
+        StyleRule::NbspBeforePunctuation,
+        StyleRule::Ellipsis,
+    ];
+
+    pub fn id(&self) -> &'static str {
+        match self {
+            StyleRule::DoubleSpace => "double-space",
+            StyleRule::StraightQuotes => "straight-quotes",
+            StyleRule::NbspBeforePunctuation => "nbsp-before-punctuation",
+            StyleRule::Ellipsis => "ellipsis",
+        }
+    }
+
+    /// Rules enabled when the config does not list them explicitly
+    pub fn defaults_for(language: Option<&str>) -> Vec<StyleRule> {
+        let language = language.map(primary_language);
+        Self::ALL
+            .into_iter()
+            .filter(|rule| match rule {
+                StyleRule::DoubleSpace | StyleRule::Ellipsis => true,
+                StyleRule::StraightQuotes => language.and_then(quotes_for).is_some(),
+                StyleRule::NbspBeforePunctuation => language == Some("fr"),
+            })
+            .collect()
+    }
+}
+
+/// `fr_CA` -> `fr`
+fn primary_language(language: &str) -> &str {
+    language.split(['_', '-', '@', '.']).next().unwrap_or(language)
+}
+
+/// Opening and closing quotation marks of a language
+fn quotes_for(language: &str) -> Option<(char, char)> {
+    match language {
+        "fr" | "ru" | "uk" | "be" | "es" | "it" | "pt" | "ca" | "el" | "nb" | "nn" | "no" => Some(('«', '»')),
+        "de" | "cs" | "sk" | "lt" | "bg" | "sl" | "et" | "is" => Some(('„', '“')),
+        "pl" | "ro" | "hu" | "nl" | "hr" => Some(('„', '”')),
+        "en" | "zh" | "ko" | "tr" | "ga" => Some(('“', '”')),
+        "ja" => Some(('「', '」')),
+        "sv" | "fi" => Some(('”', '”')),
+        _ => None,
+    }
+}
+
+pub(super) fn check(entry: &PoEntry, rules: &[StyleRule], language: Option<&str>, issues: &mut Vec<ValidationIssue>) {
+    let text = &entry.msgstr;
+    let language = language.map(primary_language);
+
+    for rule in rules {
+        match rule {
+            StyleRule::DoubleSpace => {
+                if text.contains("  ") && !entry.msgid.contains("  ") {
+                    issues.push(
+                        ValidationIssue::warning(rule.id(), "Double space").with_fix(collapse_spaces(text)),
+                    );
+                }
+            }
+            StyleRule::StraightQuotes => {
+                let Some(quotes) = language.and_then(quotes_for) else {
+                    continue;
+                };
+                let count = text.matches('"').count();
+                if count == 0 {
+                    continue;
+                }
+                let message = format!("Straight quotes, use {}…{}", quotes.0, quotes.1);
+                let mut issue = ValidationIssue::warning(rule.id(), message);
+                // Only balanced quotes can be paired up reliably
+                if count.is_multiple_of(2) {
+                    issue = issue.with_fix(curl_quotes(text, quotes));
+                }
+                issues.push(issue);
+            }
+            StyleRule::NbspBeforePunctuation => {
+                if let Some((punctuation, fixed)) = fix_nbsp_before_punctuation(text) {
+                    let message = format!("Use a non-breaking space before '{}'", punctuation);
+                    issues.push(ValidationIssue::warning(rule.id(), message).with_fix(fixed));
+                }
+            }
+            StyleRule::Ellipsis => {
+                if text.contains("...") {
+                    issues.push(
+                        ValidationIssue::warning(rule.id(), "Use … instead of ...").with_fix(text.replace("...", "…")),
+                    );
+                }
+            }
+        }
+    }
+}
+
+fn collapse_spaces(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    for c in text.chars() {
+        if !(c == ' ' && result.ends_with(' ')) {
+            result.push(c);
+        }
+    }
+    result
+}
+
+fn curl_quotes(text: &str, (open, close): (char, char)) -> String {
+    let mut opening = true;
+    text.chars()
+        .map(|c| {
+            if c != '"' {
+                return c;
+            }
+            let quote = if opening { open } else { close };
+            opening = !opening;
+            quote
+        })
+        .collect()
+}
+
+/// First punctuation mark lacking a non-breaking space before it, and the
+/// text with every such place fixed. Only marks that end a word are
+/// considered so URLs like `http://` are left alone.
+fn fix_nbsp_before_punctuation(text: &str) -> Option<(char, String)> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut first = None;
+    let mut result = String::with_capacity(text.len());
+
+    for (i, &c) in chars.iter().enumerate() {
+        let is_mark = matches!(c, ':' | ';' | '!' | '?');
+        let ends_word = chars.get(i + 1).is_none_or(|next| next.is_whitespace());
+        let previous = i.checked_sub(1).map(|p| chars[p]);
+
+        if is_mark && ends_word && i > 0 {
+            match previous {
+                Some(' ') => {
+                    result.pop();
+                    result.push(NBSP);
+                    first.get_or_insert(c);
+                }
+                Some(p) if !p.is_whitespace() && !matches!(p, ':' | ';' | '!' | '?' | '\u{202f}') => {
+                    result.push(NBSP);
+                    first.get_or_insert(c);
+                }
+                _ => {}
+            }
+        }
+        result.push(c);
+    }
+
+    first.map(|c| (c, result))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(msgid: &str, msgstr: &str, rules: &[StyleRule], language: &str) -> Vec<ValidationIssue> {
+        let mut entry = PoEntry::new();
+        entry.msgid = msgid.to_string();
+        entry.set_msgstr(msgstr.to_string());
+        let mut issues = Vec::new();
+        check(&entry, rules, Some(language), &mut issues);
+        issues
+    }
+
+    #[test]
+    fn test_defaults_follow_language() {
+        assert_eq!(
+            StyleRule::defaults_for(Some("fr_CA")),
+            vec![
+                StyleRule::DoubleSpace,
+                StyleRule::StraightQuotes,
+                StyleRule::NbspBeforePunctuation,
+                StyleRule::Ellipsis
+            ]
+        );
+        assert_eq!(StyleRule::defaults_for(Some("ru")).len(), 3);
+        assert_eq!(
+            StyleRule::defaults_for(None),
+            vec![StyleRule::DoubleSpace, StyleRule::Ellipsis]
+        );
+    }
+
+    #[test]
+    fn test_double_space() {
+        let issues = run("Open file", "Открыть   файл", &[StyleRule::DoubleSpace], "ru");
+        assert_eq!(issues[0].fix.as_deref(), Some("Открыть файл"));
+        // Mirrors the source
+        assert!(run("A  B", "А  Б", &[StyleRule::DoubleSpace], "ru").is_empty());
+    }
+
+    #[test]
+    fn test_straight_quotes() {
+        let issues = run("Open \"%s\"", "Открыть \"%s\"", &[StyleRule::StraightQuotes], "ru");
+        assert_eq!(issues[0].fix.as_deref(), Some("Открыть «%s»"));
+
+        let issues = run("Say \"hi", "Sag \"hallo", &[StyleRule::StraightQuotes], "de");
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].fix, None);
+
+        assert!(run("\"x\"", "\"x\"", &[StyleRule::StraightQuotes], "xx").is_empty());
+    }
+
+    #[test]
+    fn test_nbsp_before_punctuation() {
+        let issues = run("Warning: done!", "Attention : terminé!", &[StyleRule::NbspBeforePunctuation], "fr");
+        assert_eq!(issues[0].message, "Use a non-breaking space before ':'");
+        assert_eq!(issues[0].fix.as_deref(), Some("Attention\u{a0}: terminé\u{a0}!"));
+
+        assert!(run("x", "Voir http://exemple.fr et «\u{a0}oui\u{a0}»\u{a0}?!", &[StyleRule::NbspBeforePunctuation], "fr").is_empty());
+    }
+
+    #[test]
+    fn test_ellipsis() {
+        let issues = run("Open...", "Открыть...", &[StyleRule::Ellipsis], "ru");
+        assert_eq!(issues[0].fix.as_deref(), Some("Открыть…"));
+        assert!(run("Open…", "Открыть…", &[StyleRule::Ellipsis], "ru").is_empty());
+    }
+}
@@ -0,0 +1,29 @@
+// Poterm - Modern TUI editor for .po translation files
+// Copyright (c) 2025 AnmiTaliDev <anmitali198@gmail.com>
+// Licensed under the Apache License, Version 2.0
+
+//! Translations of glossary terms
+
+use super::ValidationIssue;
+use crate::gettext::PoEntry;
+use crate::glossary::Glossary;
+
+const CHECK: &str = "glossary";
+
+/// Terms in the msgid translated with a forbidden variant, or without the
+/// required translation
+pub fn check(entry: &PoEntry, glossary: &Glossary, issues: &mut Vec<ValidationIssue>) {
+    for term in glossary.terms_in(&entry.msgid) {
+        if let Some(variant) = term.forbidden_in(&entry.msgstr) {
+            issues.push(ValidationIssue::warning(
+                CHECK,
+                format!("\"{}\" is translated as \"{}\", not \"{}\"", term.source, term.translation, variant),
+            ));
+        } else if !term.translated_in(&entry.msgstr) {
+            issues.push(ValidationIssue::warning(
+                CHECK,
+                format!("\"{}\" should be translated as \"{}\"", term.source, term.translation),
+            ));
+        }
+    }
+}
@@ -0,0 +1,126 @@
+// Poterm - Modern TUI editor for .po translation files
+// Copyright (c) 2025 AnmiTaliDev <anmitali198@gmail.com>
+// Licensed under the Apache License, Version 2.0
+
+//! Line breaks and spaces at the ends of the translation
+
+use super::ValidationIssue;
+use crate::gettext::PoEntry;
+
+const NEWLINE: &str = "newline";
+const TRAILING_WHITESPACE: &str = "trailing-whitespace";
+
+/// Leading and trailing `\n` must match the msgid, as `msgfmt -c` requires,
+/// and the translation should not end in spaces the msgid does not have.
+/// Fixes are only offered for singular entries.
+pub(super) fn check(entry: &PoEntry, issues: &mut Vec<ValidationIssue>) {
+    for form in 0..entry.form_count() {
+        let text = entry.form(form);
+        if text.is_empty() {
+            continue;
+        }
+        let source = match entry.msgid_plural {
+            Some(ref plural) if form > 0 => plural,
+            _ => &entry.msgid,
+        };
+        let prefix = if entry.is_plural() { format!("msgstr[{}]: ", form) } else { String::new() };
+        let fixable = !entry.is_plural();
+
+        for (position, expected, found) in [
+            ("starts", source.starts_with('\n'), text.starts_with('\n')),
+            ("ends", source.ends_with('\n'), text.ends_with('\n')),
+        ] {
+            if expected == found {
+                continue;
+            }
+            let message = if expected {
+                format!("{}The msgid {} with a newline, the translation does not", prefix, position)
+            } else {
+                format!("{}The translation {} with a newline, the msgid does not", prefix, position)
+            };
+            let mut issue = ValidationIssue::error(NEWLINE, message);
+            if fixable {
+                let fixed = match (position, expected) {
+                    ("starts", true) => format!("\n{}", text),
+                    ("starts", false) => text.trim_start_matches('\n').to_string(),
+                    (_, true) => format!("{}\n", text),
+                    (_, false) => text.trim_end_matches('\n').to_string(),
+                };
+                issue = issue.with_fix(fixed);
+            }
+            issues.push(issue);
+        }
+
+        if ends_with_whitespace(text) && !ends_with_whitespace(source) {
+            let mut issue = ValidationIssue::warning(TRAILING_WHITESPACE, format!("{}Trailing whitespace", prefix));
+            if fixable {
+                let body = text.trim_end_matches('\n');
+                let newlines = &text[body.len()..];
+                issue = issue.with_fix(format!("{}{}", body.trim_end_matches([' ', '\t']), newlines));
+            }
+            issues.push(issue);
+        }
+    }
+}
+
+/// Spaces or tabs at the end, before any final newlines
+fn ends_with_whitespace(text: &str) -> bool {
+    text.trim_end_matches('\n').ends_with([' ', '\t'])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn issues(msgid: &str, msgstr: &str) -> Vec<ValidationIssue> {
+        let mut entry = PoEntry::new();
+        entry.msgid = msgid.to_string();
+        entry.set_msgstr(msgstr.to_string());
+        let mut issues = Vec::new();
+        check(&entry, &mut issues);
+        issues
+    }
+
+    #[test]
+    fn test_newline_parity() {
+        assert!(issues("\nDone\n", "\nГотово\n").is_empty());
+
+        let found = issues("Done\n", "Готово");
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].message, "The msgid ends with a newline, the translation does not");
+        assert_eq!(found[0].fix.as_deref(), Some("Готово\n"));
+
+        let found = issues("Done", "\n\nГотово");
+        assert_eq!(found[0].message, "The translation starts with a newline, the msgid does not");
+        assert_eq!(found[0].check, "newline");
+        assert_eq!(found[0].fix.as_deref(), Some("Готово"));
+    }
+
+    #[test]
+    fn test_trailing_whitespace() {
+        assert!(issues("Name: ", "Имя: ").is_empty());
+
+        let found = issues("Name:\n", "Имя: \t\n");
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].message, "Trailing whitespace");
+        assert_eq!(found[0].check, "trailing-whitespace");
+        assert_eq!(found[0].fix.as_deref(), Some("Имя:\n"));
+    }
+
+    #[test]
+    fn test_plural_forms() {
+        let mut entry = PoEntry::new();
+        entry.msgid = "%d file\n".to_string();
+        entry.msgid_plural = Some("%d files\n".to_string());
+        entry.set_form(0, "%d файл\n".to_string());
+        entry.set_form(1, "%d файла ".to_string());
+        let mut found = Vec::new();
+        check(&entry, &mut found);
+        let messages: Vec<&str> = found.iter().map(|issue| issue.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            vec!["msgstr[1]: The msgid ends with a newline, the translation does not", "msgstr[1]: Trailing whitespace"]
+        );
+        assert!(found.iter().all(|issue| issue.fix.is_none()));
+    }
+}
@@ -0,0 +1,177 @@
+// Poterm - Modern TUI editor for .po translation files
+// Copyright (c) 2025 AnmiTaliDev <anmitali198@gmail.com>
+// Licensed under the Apache License, Version 2.0
+
+//! `poterm check --watch`: validate again whenever a watched file changes
+//!
+//! Changes come from a `notify` watcher, debounced so an editor writing a
+//! file in several steps triggers one run.
+
+use crate::check;
+use crate::config::Config;
+use anyhow::{Context, Result};
+use crossterm::style::Stylize;
+use crossterm::terminal::{Clear, ClearType};
+use crossterm::{cursor::MoveTo, ExecutableCommand};
+use notify::{RecommendedWatcher, RecursiveMode};
+use notify_debouncer_mini::{new_debouncer, DebounceEventResult, DebouncedEvent, Debouncer};
+use std::fs;
+use std::io::{self, IsTerminal};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Changes are collected until the files stay unchanged this long
+const DEBOUNCE: Duration = Duration::from_millis(300);
+/// How often Ctrl+C is noticed while waiting for changes
+const INTERRUPT_CHECK: Duration = Duration::from_millis(100);
+
+pub struct WatchOptions {
+    pub duplicates_only: bool,
+    pub show_suppressed: bool,
+    /// Clear the terminal before each run
+    pub clear: bool,
+}
+
+/// Check `paths` now and after every change until Ctrl+C. Returns whether
+/// the last run passed.
+pub fn run(paths: &[PathBuf], config: &Config, options: &WatchOptions) -> Result<bool> {
+    let interrupted = Arc::new(AtomicBool::new(false));
+    #[cfg(unix)]
+    signal_hook::flag::register(signal_hook::consts::SIGINT, Arc::clone(&interrupted))?;
+
+    let (_debouncer, changes) = watch(paths)?;
+    let mut passed = check_once(&po_files(paths), config, options)?;
+    while !interrupted.load(Ordering::Relaxed) {
+        match changes.recv_timeout(INTERRUPT_CHECK) {
+            Ok(Ok(events)) if events.iter().any(is_po_change) => {
+                passed = check_once(&po_files(paths), config, options)?;
+            }
+            Ok(Ok(_)) | Err(RecvTimeoutError::Timeout) => {}
+            Ok(Err(error)) => eprintln!("Warning: watching failed: {}", error),
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+    }
+    Ok(passed)
+}
+
+/// Start watching `paths`, directories recursively. A file is watched
+/// through its directory, so it is still seen after an editor replaces
+/// it or when it is created later.
+fn watch(paths: &[PathBuf]) -> Result<(Debouncer<RecommendedWatcher>, Receiver<DebounceEventResult>)> {
+    let (sender, changes) = mpsc::channel();
+    let mut debouncer = new_debouncer(DEBOUNCE, sender).context("Failed to start watching files")?;
+    for path in paths {
+        let (target, mode) = if path.is_dir() {
+            (path.as_path(), RecursiveMode::Recursive)
+        } else {
+            let parent = path.parent().filter(|parent| !parent.as_os_str().is_empty());
+            (parent.unwrap_or(Path::new(".")), RecursiveMode::NonRecursive)
+        };
+        debouncer
+            .watcher()
+            .watch(target, mode)
+            .with_context(|| format!("Failed to watch {}", target.display()))?;
+    }
+    Ok((debouncer, changes))
+}
+
+fn is_po_change(event: &DebouncedEvent) -> bool {
+    event.path.extension().is_some_and(|extension| extension == "po")
+}
+
+fn check_once(files: &[PathBuf], config: &Config, options: &WatchOptions) -> Result<bool> {
+    if options.clear {
+        io::stdout().execute(Clear(ClearType::All))?.execute(MoveTo(0, 0))?;
+    }
+    let check_options = check::Options {
+        duplicates_only: options.duplicates_only,
+        show_suppressed: options.show_suppressed,
+        ..Default::default()
+    };
+    let passed = check::run(files, config, &check_options)?;
+
+    let time = chrono::Local::now().format("%H:%M:%S");
+    let verdict = match (passed, io::stdout().is_terminal()) {
+        (true, true) => "passed".green().bold().to_string(),
+        (false, true) => "failed".red().bold().to_string(),
+        (true, false) => "passed".to_string(),
+        (false, false) => "failed".to_string(),
+    };
+    println!("[{}] {} file(s) {}, watching for changes (Ctrl+C to stop)", time, files.len(), verdict);
+    Ok(passed)
+}
+
+/// The .po files named by `paths`, directories searched recursively
+pub fn po_files(paths: &[PathBuf]) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    for path in paths {
+        if path.is_dir() {
+            collect_po_files(path, &mut files);
+        } else {
+            // Missing files are kept so the check reports them
+            files.push(path.clone());
+        }
+    }
+    files.sort();
+    files.dedup();
+    files
+}
+
+fn collect_po_files(dir: &Path, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_po_files(&path, files);
+        } else if path.extension().is_some_and(|extension| extension == "po") {
+            files.push(path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_po_files_in_directories() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("sub")).unwrap();
+        for name in ["de.po", "notes.txt", "sub/fr.po", "messages.pot"] {
+            fs::write(dir.path().join(name), "").unwrap();
+        }
+        let missing = dir.path().join("missing.po");
+
+        let files = po_files(&[dir.path().to_path_buf(), missing.clone(), dir.path().join("de.po")]);
+        assert_eq!(files, vec![dir.path().join("de.po"), missing, dir.path().join("sub/fr.po")]);
+    }
+
+    #[test]
+    fn test_watcher_sees_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("de.po");
+        fs::write(&file, "").unwrap();
+        let (_debouncer, changes) = watch(&[file.clone(), dir.path().join("sub.po")]).unwrap();
+
+        // Written in place, and created later
+        fs::write(&file, "msgid \"Open\"\nmsgstr \"\"\n").unwrap();
+        fs::write(dir.path().join("sub.po"), "").unwrap();
+        let mut seen = Vec::new();
+        while seen.len() < 2 {
+            let events = changes.recv_timeout(Duration::from_secs(5)).unwrap().unwrap();
+            for event in events.iter().filter(|event| is_po_change(event)) {
+                let name = event.path.file_name().unwrap().to_owned();
+                if !seen.contains(&name) {
+                    seen.push(name);
+                }
+            }
+        }
+        seen.sort();
+        assert_eq!(seen, ["de.po", "sub.po"]);
+    }
+}
@@ -0,0 +1,248 @@
+// Poterm - Modern TUI editor for .po translation files
+// Copyright (c) 2025 AnmiTaliDev <anmitali198@gmail.com>
+// Licensed under the Apache License, Version 2.0
+
+//! Wrapping of text into display rows, shared by drawing and cursor
+//! placement so both agree on where every character ends up
+//!
+//! Rows break at line break opportunities: after spaces, after hyphens and
+//! dashes, and around CJK characters. Words longer than a row break between
+//! grapheme clusters, never inside one.
+
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// One screen row of a wrapped buffer
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Row<'a> {
+    /// Char index of the row's first character in the buffer
+    pub start: usize,
+    pub text: &'a str,
+}
+
+/// Break `text` at line breaks and at the last break opportunity before
+/// the row would overflow `width` cells. Spaces at the end of a row may
+/// hang past its edge rather than start the next row.
+pub fn wrap(text: &str, width: u16) -> Vec<Row<'_>> {
+    let width = width.max(1) as usize;
+    let mut rows = Vec::new();
+    let mut start = 0;
+    for line in text.split('\n') {
+        wrap_line(line, start, width, &mut rows);
+        start += line.chars().count() + 1;
+    }
+    rows
+}
+
+fn wrap_line<'a>(line: &'a str, first_char: usize, width: usize, rows: &mut Vec<Row<'a>>) {
+    // Row start and the last opportunity in it as (byte, char) offsets,
+    // the opportunity with the cells used before it
+    let (mut row_byte, mut row_char, mut used) = (0, first_char, 0);
+    let mut opportunity: Option<(usize, usize, usize)> = None;
+    let (mut before, mut previous): (Option<&str>, Option<&str>) = (None, None);
+    let mut char_index = first_char;
+
+    for (byte, grapheme) in line.grapheme_indices(true) {
+        let cells = grapheme.width();
+        if previous.is_some_and(|previous| can_break(before, previous, grapheme)) && byte > row_byte {
+            opportunity = Some((byte, char_index, used));
+        }
+        if !is_breaking_space(grapheme) {
+            while used + cells > width && used > 0 {
+                let (end_byte, end_char, before) = opportunity.take().unwrap_or((byte, char_index, used));
+                rows.push(Row {
+                    start: row_char,
+                    text: &line[row_byte..end_byte],
+                });
+                (row_byte, row_char) = (end_byte, end_char);
+                used -= before;
+            }
+        }
+        used += cells;
+        char_index += grapheme.chars().count();
+        (before, previous) = (previous, Some(grapheme));
+    }
+    rows.push(Row {
+        start: row_char,
+        text: &line[row_byte..],
+    });
+}
+
+/// Whitespace that allows a break, unlike no-break spaces
+fn is_breaking_space(grapheme: &str) -> bool {
+    grapheme
+        .chars()
+        .all(|c| c.is_whitespace() && !matches!(c, '\u{a0}' | '\u{2007}' | '\u{202f}'))
+}
+
+/// Whether a row may end between `previous` and `next`, with `before` the
+/// grapheme preceding `previous`
+fn can_break(before: Option<&str>, previous: &str, next: &str) -> bool {
+    let last = previous.chars().next_back().unwrap_or_default();
+    let first = next.chars().next().unwrap_or_default();
+    if is_breaking_space(next) || matches!(last, '\u{2060}' | '\u{feff}') {
+        return false;
+    }
+    if is_breaking_space(previous) || last == '\u{200b}' {
+        return true;
+    }
+    if matches!(last, '-' | '\u{ad}' | '\u{2010}' | '\u{2013}' | '\u{2014}') {
+        let after_word = before.and_then(|before| before.chars().next_back()).is_some_and(char::is_alphanumeric);
+        return after_word && first.is_alphanumeric();
+    }
+    cjk_break(last, first)
+}
+
+/// CJK text breaks between characters, except before closing and after
+/// opening punctuation
+pub fn cjk_break(last: char, first: char) -> bool {
+    let wide = |c: char| c.width().unwrap_or(0) > 1;
+    (wide(last) || wide(first)) && !"、。，．：；？！）」』〉》】〕ー…".contains(first) && !"（「『〈《【〔".contains(last)
+}
+
+/// Row and column of the char index `index`. A cursor after a full row
+/// continues at the start of the next one; one on a space hanging past the
+/// edge stays in the last column.
+pub fn position(rows: &[Row], index: usize, width: u16) -> (usize, u16) {
+    let width = width.max(1);
+    let row = rows.iter().rposition(|row| row.start <= index).unwrap_or(0);
+    let offset = index - rows[row].start;
+    let column: usize = rows[row].text.chars().take(offset).map(|c| c.width().unwrap_or(0)).sum();
+    if column < width as usize {
+        (row, column as u16)
+    } else if offset < rows[row].text.chars().count() {
+        (row, width - 1)
+    } else {
+        (row + 1, 0)
+    }
+}
+
+/// Char index in row `row` closest to `column` without passing it. On a
+/// row that continues on the next one, the index stays before the break so
+/// the cursor does not jump to the next row.
+pub fn index_at(rows: &[Row], row: usize, column: u16) -> usize {
+    let Some(current) = rows.get(row) else {
+        return rows.last().map_or(0, |last| last.start + last.text.chars().count());
+    };
+    let len = current.text.chars().count();
+    let continues = rows.get(row + 1).is_some_and(|next| next.start == current.start + len);
+    let mut width = 0;
+    let mut offset = 0;
+    for c in current.text.chars() {
+        width += c.width().unwrap_or(0);
+        if width > column as usize {
+            break;
+        }
+        offset += 1;
+    }
+    current.start + offset.min(if continues { len.saturating_sub(1) } else { len })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts<'a>(rows: &[Row<'a>]) -> Vec<&'a str> {
+        rows.iter().map(|row| row.text).collect()
+    }
+
+    #[test]
+    fn test_wide_characters_move_to_the_next_row() {
+        let rows = wrap("日本語のテキスト", 5);
+        assert_eq!(texts(&rows), vec!["日本", "語の", "テキ", "スト"]);
+        assert_eq!(rows[1].start, 2);
+        assert_eq!(position(&rows, 3, 5), (1, 2));
+        // After 日本 the row is full at 4 of 5 cells; the cursor stays there
+        assert_eq!(position(&rows, 2, 5), (1, 0));
+        assert_eq!(position(&rows, 8, 5), (3, 4));
+
+        let rows = wrap("ab😀c", 3);
+        assert_eq!(texts(&rows), vec!["ab", "😀c"]);
+    }
+
+    #[test]
+    fn test_index_at_column() {
+        let rows = wrap("日本語のテキスト", 5);
+        // Column 3 is the second half of テ
+        assert_eq!(index_at(&rows, 2, 3), 5);
+        assert_eq!(position(&rows, index_at(&rows, 1, 2), 5), (1, 2));
+        // The end of a wrapped row is the start of the next one
+        assert_eq!(index_at(&rows, 0, 4), 1);
+        assert_eq!(index_at(&rows, 3, 4), 8);
+
+        let rows = wrap("a😀b
+xy", 10);
+        assert_eq!(index_at(&rows, 0, 2), 1);
+        assert_eq!(index_at(&rows, 0, 3), 2);
+        // A line break ends the row, and the cursor may sit before it
+        assert_eq!(index_at(&rows, 0, 9), 3);
+        assert_eq!(index_at(&rows, 1, 9), 6);
+        assert_eq!(index_at(&rows, 5, 0), 6);
+    }
+
+    #[test]
+    fn test_line_breaks_and_full_rows() {
+        let rows = wrap("abcd\nef", 4);
+        assert_eq!(texts(&rows), vec!["abcd", "ef"]);
+        assert_eq!(rows[1].start, 5);
+        // End of a full row wraps to the next row
+        assert_eq!(position(&rows, 4, 4), (1, 0));
+        assert_eq!(position(&rows, 7, 4), (1, 2));
+
+        let rows = wrap("", 10);
+        assert_eq!(texts(&rows), vec![""]);
+        assert_eq!(position(&rows, 0, 10), (0, 0));
+        assert_eq!(texts(&wrap("a\n", 10)), vec!["a", ""]);
+    }
+
+    #[test]
+    fn test_breaks_between_words() {
+        let rows = wrap("Save the file now", 10);
+        assert_eq!(texts(&rows), vec!["Save the ", "file now"]);
+        assert_eq!(rows[1].start, 9);
+        assert_eq!(position(&rows, 9, 10), (1, 0));
+        assert_eq!(position(&rows, 8, 10), (0, 8));
+
+        // Words longer than a row break where they must
+        assert_eq!(texts(&wrap("Internationalization", 8)), vec!["Internat", "ionaliza", "tion"]);
+        assert_eq!(texts(&wrap("a Internationalization", 8)), vec!["a ", "Internat", "ionaliza", "tion"]);
+
+        // After hyphens and dashes, but not a leading minus
+        assert_eq!(texts(&wrap("read-only mode", 7)), vec!["read-", "only ", "mode"]);
+        assert_eq!(texts(&wrap("ab -5", 4)), vec!["ab ", "-5"]);
+        assert_eq!(texts(&wrap("long—dash", 6)), vec!["long—", "dash"]);
+    }
+
+    #[test]
+    fn test_spaces_hang_at_the_edge() {
+        let rows = wrap("abc    def", 5);
+        assert_eq!(texts(&rows), vec!["abc    ", "def"]);
+        // Cursors on hanging spaces stay in the last column
+        assert_eq!(position(&rows, 5, 5), (0, 4));
+        assert_eq!(position(&rows, 6, 5), (0, 4));
+        assert_eq!(position(&rows, 7, 5), (1, 0));
+
+        // No-break spaces keep their neighbours together
+        assert_eq!(texts(&wrap("10\u{a0}km away", 6)), vec!["10\u{a0}km ", "away"]);
+        assert_eq!(texts(&wrap("Fin\u{a0}!", 4)), vec!["Fin\u{a0}", "!"]);
+    }
+
+    #[test]
+    fn test_grapheme_clusters_stay_whole() {
+        // e + combining acute accent is one cell and never split
+        let rows = wrap("e\u{301}e\u{301}e\u{301}", 2);
+        assert_eq!(texts(&rows), vec!["e\u{301}e\u{301}", "e\u{301}"]);
+        assert_eq!(rows[1].start, 4);
+
+        // A flag is two regional indicators drawn as one glyph
+        let rows = wrap("ab\u{1f1fa}\u{1f1e6}", 3);
+        assert_eq!(texts(&rows), vec!["ab", "\u{1f1fa}\u{1f1e6}"]);
+    }
+
+    #[test]
+    fn test_cjk_breaks_between_characters() {
+        assert_eq!(texts(&wrap("保存しました。", 6)), vec!["保存し", "まし", "た。"]);
+        // Brackets stay with the character they enclose
+        assert_eq!(texts(&wrap("「保存」する", 6)), vec!["「保", "存」す", "る"]);
+    }
+}